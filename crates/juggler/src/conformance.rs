@@ -0,0 +1,73 @@
+//! Shared result type for the driver conformance suites.
+//!
+//! Each domain with a driver trait ships a `conformance` module whose
+//! `run_contract_tests` exercises any implementation against the call-order
+//! and error semantics documented on the trait:
+//!
+//! - `wifi::conformance` — [`WifiDriver`](crate::wifi::WifiDriver) and
+//!   [`AsyncWifiDriver`](crate::wifi::AsyncWifiDriver)
+//! - `lora::conformance` — [`LoraRadio`](crate::lora::LoraRadio)
+//! - `espnow::conformance` — [`EspNowDriver`](crate::espnow::EspNowDriver)
+//!
+//! The suites are plain functions rather than `#[test]`s, so they run
+//! equally from a host test against a mock and from a hardware-in-the-loop
+//! firmware image against the real driver.  They stop at the first broken
+//! rule and return it as a [`ContractViolation`]; the driver error behind it,
+//! if any, is logged at `warn` since its type is implementation-specific.
+//!
+//! ```rust,ignore
+//! #[test]
+//! fn my_radio_honours_lora_radio_contract() {
+//!     let mut radio = MyRadio::new_for_test();
+//!     juggler::lora::conformance::run_contract_tests(&mut radio, &LoraContract::default(), || {})
+//!         .unwrap();
+//! }
+//! ```
+
+/// A documented trait rule that an implementation broke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContractViolation {
+    /// Short name of the failed check, e.g. `"prepare_rx_after_timeout"`.
+    pub check: &'static str,
+    /// What the trait documentation requires and the driver did not do.
+    pub detail: &'static str,
+}
+
+impl ContractViolation {
+    /// Creates a violation for `check`.
+    pub const fn new(check: &'static str, detail: &'static str) -> Self {
+        Self { check, detail }
+    }
+}
+
+impl core::fmt::Display for ContractViolation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "contract check `{}` failed: {}", self.check, self.detail)
+    }
+}
+
+/// Unwraps a driver result, turning an error into a [`ContractViolation`]
+/// and logging the driver's own error at `warn`.
+pub(crate) fn require_ok<T, E: core::fmt::Debug>(
+    result: Result<T, E>,
+    check: &'static str,
+    detail: &'static str,
+) -> Result<T, ContractViolation> {
+    result.map_err(|e| {
+        log::warn!("contract check `{}`: driver error {:?}", check, e);
+        ContractViolation::new(check, detail)
+    })
+}
+
+/// Returns a violation unless `condition` holds.
+pub(crate) fn require(
+    condition: bool,
+    check: &'static str,
+    detail: &'static str,
+) -> Result<(), ContractViolation> {
+    if condition {
+        Ok(())
+    } else {
+        Err(ContractViolation::new(check, detail))
+    }
+}
@@ -0,0 +1,41 @@
+//! Bitwise IEEE 802.3 CRC-32 for integrity checks on persisted state.
+
+/// Initial register value; finish with a bitwise NOT.
+pub(crate) const CRC32_INIT: u32 = 0xFFFF_FFFF;
+
+/// Feeds `data` into a running CRC-32 (reflected `0x04C1_1DB7`).
+///
+/// Bitwise rather than table-driven: the inputs are at most a few KiB and
+/// checked rarely, so the 1 KiB table is not worth its flash.
+pub(crate) fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_known_vector() {
+        assert_eq!(!crc32_update(CRC32_INIT, b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn crc32_is_incremental() {
+        let whole = crc32_update(CRC32_INIT, b"123456789");
+        let split = crc32_update(crc32_update(CRC32_INIT, b"1234"), b"56789");
+        assert_eq!(whole, split);
+    }
+}
@@ -0,0 +1,111 @@
+//! Conformance suite for [`EspNowDriver`].
+//!
+//! Available with the `conformance` feature (or `#[cfg(test)]` inside this
+//! crate).  [`run_contract_tests`] registers a peer, sends to it, drains the
+//! receive queue, and re-registers the peer after removing it, returning the
+//! first broken rule; see [`crate::conformance`].
+//!
+//! The peer does not have to exist — ESP-NOW sends are fire-and-forget — so
+//! [`BROADCAST_MAC`](super::BROADCAST_MAC) works on hardware.  The suite
+//! removes the peer again before returning.
+//!
+//! ```rust,ignore
+//! use juggler::espnow::conformance::run_contract_tests;
+//! use juggler::espnow::{PeerConfig, BROADCAST_MAC};
+//!
+//! run_contract_tests(&espnow, &PeerConfig::new(BROADCAST_MAC))?;
+//! ```
+
+use super::{EspNowDriver, PeerConfig, DEFAULT_RX_CHANNEL_CAPACITY, MAX_DATA_LEN};
+use crate::conformance::{require, require_ok, ContractViolation};
+
+/// Runs the [`EspNowDriver`] contract checks against `driver`, using `peer`
+/// as the registered destination.
+pub fn run_contract_tests<D: EspNowDriver>(
+    driver: &D,
+    peer: &PeerConfig,
+) -> Result<(), ContractViolation> {
+    // A non-blocking receive must run dry: anything queued before the suite
+    // started is bounded by the channel capacity.
+    let drained = (0..=DEFAULT_RX_CHANNEL_CAPACITY).any(|_| driver.try_recv().is_none());
+    require(
+        drained,
+        "try_recv_drains",
+        "try_recv must return None once the receive queue is empty",
+    )?;
+
+    require_ok(
+        driver.add_peer(peer),
+        "add_peer",
+        "add_peer must accept a new peer",
+    )?;
+    require_ok(
+        driver.send(&peer.mac, b"juggler"),
+        "send",
+        "send must succeed to a registered peer",
+    )?;
+    require_ok(
+        driver.send(&peer.mac, &[0xA5; MAX_DATA_LEN]),
+        "send_max_len",
+        "send must accept a payload of MAX_DATA_LEN bytes",
+    )?;
+
+    require_ok(
+        driver.remove_peer(&peer.mac),
+        "remove_peer",
+        "remove_peer must succeed for a registered peer",
+    )?;
+    require_ok(
+        driver.add_peer(peer),
+        "add_peer_after_remove",
+        "add_peer must accept a peer again after remove_peer",
+    )?;
+    require_ok(
+        driver.remove_peer(&peer.mac),
+        "remove_peer",
+        "remove_peer must succeed for a registered peer",
+    )
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::espnow::mock::MockEspNowDriver;
+    use crate::espnow::{EspNowEvent, BROADCAST_MAC};
+
+    const PEER_MAC: [u8; 6] = [0x24, 0x0A, 0xC4, 0x00, 0x00, 0x01];
+
+    #[test]
+    fn mock_driver_passes() {
+        let driver = MockEspNowDriver::new();
+        assert_eq!(
+            run_contract_tests(&driver, &PeerConfig::new(PEER_MAC)),
+            Ok(())
+        );
+        assert_eq!(driver.sent_count(), 2);
+        assert_eq!(driver.sent_messages()[1].1.len(), MAX_DATA_LEN);
+        assert!(driver.peer_list().is_empty());
+    }
+
+    #[test]
+    fn mock_driver_passes_with_queued_frames_and_broadcast_peer() {
+        let driver = MockEspNowDriver::new();
+        driver.queue_rx_event(EspNowEvent::new(PEER_MAC, b"stale"));
+        driver.queue_rx_event(EspNowEvent::new(PEER_MAC, b"frames"));
+        assert_eq!(
+            run_contract_tests(&driver, &PeerConfig::new(BROADCAST_MAC)),
+            Ok(())
+        );
+        assert!(driver.try_recv().is_none());
+    }
+
+    #[test]
+    fn failing_send_is_reported() {
+        let driver = MockEspNowDriver::new();
+        driver.set_fail_send(true);
+        let violation = run_contract_tests(&driver, &PeerConfig::new(PEER_MAC)).unwrap_err();
+        assert_eq!(violation.check, "send");
+    }
+}
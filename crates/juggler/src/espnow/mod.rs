@@ -8,14 +8,19 @@
 //! - [`PeerTracker`] — heartbeat-based peer liveness tracking
 //! - [`mock::MockEspNowDriver`] — test double for host-side unit tests
 //!   (requires the `mock` feature or `#[cfg(test)]`)
+//! - `conformance::run_contract_tests` — contract suite for driver
+//!   implementations (requires the `conformance` feature)
 //!
 //! # Feature flags
 //!
 //! | Feature | What it enables                                              |
 //! |:--------|:-------------------------------------------------------------|
 //! | `mock`  | `MockEspNowDriver` for downstream host-side tests            |
+//! | `conformance` | `conformance::run_contract_tests` for driver implementations |
 
 pub mod command;
+#[cfg(any(test, feature = "conformance"))]
+pub mod conformance;
 #[cfg(any(test, feature = "mock"))]
 pub mod mock;
 pub mod tracker;
@@ -36,12 +41,13 @@ pub const DEFAULT_RX_CHANNEL_CAPACITY: usize = 32;
 
 /// Default channels to scan when probing for a peer.
 ///
-/// Covers channels 1-13 (EU/JP region).  US/CA regulatory domains restrict
-/// 2.4 GHz to channels 1-11; if your peer might land on a channel that is not
-/// allowed in your region the radio will silently fail to send on it.  Use
-/// [`ScanConfig::with_channels`] with a region-appropriate slice when this
-/// matters (e.g. `&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]` for US).
-pub const DEFAULT_SCAN_CHANNELS: [u8; 13] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13];
+/// Covers channels 1-11, the worldwide-safe set that every regulatory
+/// domain allows, so a default scan never probes a channel the radio may
+/// not use.  In a domain that also allows 12 and 13 (EU, JP, CN, AU), use
+/// `ScanConfig::with_country` (with the `wifi` feature) or
+/// [`ScanConfig::with_channels`] to include them — a peer parked there is
+/// otherwise never found.
+pub const DEFAULT_SCAN_CHANNELS: [u8; 11] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
 
 /// Default per-channel probe timeout when scanning for a peer.
 ///
@@ -54,7 +60,7 @@ pub const DEFAULT_PROBE_TIMEOUT: core::time::Duration = core::time::Duration::fr
 ///
 /// Caps the wall-clock time the radio spends at boosted TX power during a
 /// scan, regardless of how many channels remain.  At default settings
-/// (13 channels × 100 ms probe) the loop already finishes in ~1.3 s; this
+/// (11 channels × 100 ms probe) the loop already finishes in ~1.1 s; this
 /// only kicks in for long custom probe timeouts or large channel lists.
 /// Override with [`ScanConfig::with_burst_timeout`].
 pub const DEFAULT_BURST_TIMEOUT: core::time::Duration = core::time::Duration::from_secs(3);
@@ -245,7 +251,7 @@ pub struct ScanConfig<'a> {
 }
 
 impl<'a> ScanConfig<'a> {
-    /// Create a scan config with default channels (1-13), the given probe
+    /// Create a scan config with default channels (1-11), the given probe
     /// payload, and the default timeouts and confirmation settings.
     pub fn new(probe_data: &'a [u8]) -> Self {
         Self {
@@ -264,6 +270,15 @@ impl<'a> ScanConfig<'a> {
         self
     }
 
+    /// Scan exactly the channels `country` allows, in ascending order.
+    ///
+    /// Use the same [`Country`](crate::wifi::Country) the Wi-Fi radio was
+    /// started with so the scan never probes a channel the driver rejects.
+    #[cfg(feature = "wifi")]
+    pub fn with_country(self, country: crate::wifi::Country) -> Self {
+        self.with_channels(country.channels())
+    }
+
     /// Override the per-channel probe timeout.
     pub fn with_probe_timeout(mut self, timeout: core::time::Duration) -> Self {
         self.probe_timeout = timeout;
@@ -470,6 +485,23 @@ mod tests {
         assert_eq!(config.confirmation_gap, DEFAULT_CONFIRMATION_GAP);
     }
 
+    #[cfg(feature = "wifi")]
+    #[test]
+    fn default_scan_channels_are_allowed_everywhere() {
+        use crate::wifi::Country;
+
+        for country in Country::ALL {
+            for channel in DEFAULT_SCAN_CHANNELS {
+                assert!(
+                    country.allows_channel(channel),
+                    "{:?} rejects default scan channel {}",
+                    country,
+                    channel
+                );
+            }
+        }
+    }
+
     #[test]
     fn scan_config_custom_channels() {
         let channels = [1, 6, 11];
@@ -477,6 +509,22 @@ mod tests {
         assert_eq!(config.channels, &[1, 6, 11]);
     }
 
+    #[cfg(feature = "wifi")]
+    #[test]
+    fn scan_config_with_country_limits_channels() {
+        use crate::wifi::Country;
+
+        let config = ScanConfig::new(b"probe").with_country(Country::Us);
+        assert_eq!(config.channels, &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
+        let config = ScanConfig::new(b"probe").with_country(Country::Eu);
+        assert_eq!(
+            config.channels,
+            &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13]
+        );
+        let config = ScanConfig::new(b"probe").with_country(Country::World);
+        assert_eq!(config.channels, &DEFAULT_SCAN_CHANNELS);
+    }
+
     #[test]
     fn scan_config_custom_probe_confirmations() {
         let config = ScanConfig::new(b"ping").with_probe_confirmations(3);
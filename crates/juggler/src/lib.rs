@@ -7,7 +7,7 @@
 //! | Feature | Module | External deps |
 //! |:--------|:-------|:--------------|
 //! | (always) | `backoff`, `status_colors` | none |
-//! | `wifi` | `wifi` | none |
+//! | `wifi` | `wifi` | `heapless`, `sha1` |
 //! | `mqtt` | `mqtt` (no_std subset) | none |
 //! | `std` | `mqtt` (full, incl. thread helpers) | `anyhow` |
 //! | `lora` | `lora` | `heapless`, `nb`, `lorawan-device` |
@@ -15,17 +15,22 @@
 //! | `ota` | `ota` | `heapless`, `sha2` |
 //! | `provisioning` | `provisioning` | `heapless` (implies wifi+mqtt+lora) |
 //! | `mock` | `wifi::mock`, `lora::mock`, `espnow::mock` | (implies wifi+lora+espnow) |
+//! | `conformance` | `conformance`, `<domain>::conformance` | none |
 //!
 //! ## Cargo features
 //!
 //! ### Domain features
 //!
 //! - **`wifi`** — Wi-Fi STA configuration, scanning, connection validation, and
-//!   SoftAP lifecycle primitives. Zero external dependencies; `no_std` compatible.
+//!   SoftAP lifecycle primitives, multi-network selection. Requires `heapless`
+//!   (bounded network list) and `sha1` (WPA2 PMK derivation for fast
+//!   reconnects); `no_std` compatible.
 //!
-//! - **`mqtt`** — MQTT topic/client-ID validation and connection state machine.
-//!   No external dependencies in the `no_std` subset. Requires `std` feature to
-//!   unlock the `std`-specific helpers (see `std` feature below).
+//! - **`mqtt`** — MQTT topic/client-ID validation, connection state machine,
+//!   an allocation-free MQTT 3.1.1 packet codec ([`mqtt::codec`]), and the
+//!   offline publish queue ([`mqtt::PublishQueue`]). No external
+//!   dependencies in the `no_std` subset. Requires `std` feature to unlock
+//!   the `std`-specific helpers (see `std` feature below).
 //!
 //! - **`lora`** — LoRa radio primitives, regional config, and LoRaWAN Class A
 //!   protocol state machine. Requires `heapless`, `nb`, and `lorawan-device`.
@@ -48,8 +53,9 @@
 //!
 //! - **`std`** — Enables full MQTT helpers that require the standard library:
 //!   [`mqtt::spawn_subscriber_thread`], [`mqtt::SubscribeClient`] trait,
-//!   [`mqtt::QoS`] enum, and [`mqtt::format_broker_url`]. These functions need
-//!   `std::thread`, `std::sync`, and the `anyhow` crate. **Implies `mqtt` feature.**
+//!   [`mqtt::format_broker_url`], and the offline-queue locking helpers
+//!   [`mqtt::publish_or_queue`] and [`mqtt::retry_replay`]. These functions need `std::thread`,
+//!   `std::sync`, and the `anyhow` crate. **Implies `mqtt` feature.**
 //!   Everything else in `juggler` is `no_std`; this flag does not affect other
 //!   domains. Use this only if you need the thread-based subscriber helpers;
 //!   pure MQTT validation and state machines compile under `mqtt` alone.
@@ -61,6 +67,13 @@
 //!   environment on the host. **Implies `wifi`, `lora`, and `espnow` features.**
 //!   Part of the public, semver-tracked API; safe for downstream production tests.
 //!
+//! - **`conformance`** — Driver contract suites: `run_contract_tests` in
+//!   `wifi::conformance`, `lora::conformance`, and `espnow::conformance`
+//!   exercise any implementation of the domain's driver trait against its
+//!   documented call order and error semantics, reporting a
+//!   [`conformance::ContractViolation`]. Only the suites of enabled domains are
+//!   compiled; combine with `mock` to run them against the bundled test doubles.
+//!
 //! ## Re-export patterns
 //!
 //! Each domain module re-exports its primary types at the crate root for ergonomic imports:
@@ -82,6 +95,20 @@ extern crate alloc;
 pub mod backoff;
 pub mod status_colors;
 
+// CRC-32 shared by the wifi fast-connect cache and the mqtt publish queue.
+#[cfg(any(feature = "wifi", feature = "mqtt", feature = "std"))]
+mod crc32;
+
+// PEM detection shared by MQTT TLS and EAP-TLS certificates.
+#[cfg(any(feature = "wifi", feature = "mqtt", feature = "std"))]
+pub mod pem;
+
+#[cfg(all(
+    any(test, feature = "conformance"),
+    any(feature = "wifi", feature = "lora", feature = "espnow")
+))]
+pub mod conformance;
+
 // ── Domain modules (feature-gated) ──────────────────────────────────────────
 
 #[cfg(feature = "wifi")]
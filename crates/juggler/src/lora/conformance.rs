@@ -0,0 +1,283 @@
+//! Conformance suite for [`LoraRadio`].
+//!
+//! Available with the `conformance` feature (or `#[cfg(test)]` inside this
+//! crate).  [`run_contract_tests`] drives a radio through the call order the
+//! LoRaWAN adapter relies on — tune, transmit, open a receive window, let it
+//! time out, open the next one without a reset, transmit again — and returns
+//! the first broken rule; see [`crate::conformance`].
+//!
+//! ```rust,ignore
+//! use juggler::lora::conformance::{run_contract_tests, LoraContract};
+//!
+//! run_contract_tests(&mut radio, &LoraContract::default().with_max_polls(5_000), || {
+//!     delay.delay_ms(1)
+//! })?;
+//! ```
+
+use super::{Bandwidth, CodingRate, LoraRadio, RxConfig, RxWindow, SpreadingFactor, TxConfig};
+use crate::conformance::{require, require_ok, ContractViolation};
+
+/// Payload sent by the suite: a recognisable, non-LoRaWAN pattern.
+const CONTRACT_PAYLOAD: [u8; 12] = *b"juggler-lora";
+
+/// Radio parameters and poll budget for a LoRa conformance run.
+#[derive(Debug, Clone, Copy)]
+pub struct LoraContract {
+    /// Uplink settings used for both transmissions.
+    pub tx: TxConfig,
+    /// Settings for both receive windows.
+    pub rx: RxConfig,
+    /// How often `transmit` / `receive` are polled (with the `delay`
+    /// callback in between) before the operation counts as stuck or the
+    /// receive window as timed out.
+    pub max_polls: u32,
+}
+
+impl LoraContract {
+    /// Creates a contract for the given TX and RX settings with a poll
+    /// budget of 10, which suits mocks.
+    pub const fn new(tx: TxConfig, rx: RxConfig) -> Self {
+        Self {
+            tx,
+            rx,
+            max_polls: 10,
+        }
+    }
+
+    /// Sets the poll budget for `transmit` and `receive`.
+    pub const fn with_max_polls(mut self, polls: u32) -> Self {
+        self.max_polls = polls;
+        self
+    }
+}
+
+impl Default for LoraContract {
+    /// EU868: uplink on 868.1 MHz at SF12/BW125 and 14 dBm, receive on the
+    /// 869.525 MHz RX2 frequency.
+    fn default() -> Self {
+        Self::new(
+            TxConfig {
+                freq_hz: 868_100_000,
+                sf: SpreadingFactor::SF12,
+                bw: Bandwidth::BW125,
+                cr: CodingRate::Cr45,
+                power_dbm: 14,
+            },
+            RxConfig {
+                freq_hz: 869_525_000,
+                sf: SpreadingFactor::SF12,
+                bw: Bandwidth::BW125,
+                cr: CodingRate::Cr45,
+            },
+        )
+    }
+}
+
+/// Runs the [`LoraRadio`] contract checks against `radio`.
+///
+/// `delay` is called between polls of `transmit` and `receive`.  A receive
+/// window in which nothing arrives is the expected outcome; the suite then
+/// checks that [`LoraRadio::prepare_rx`] can reopen a window without a
+/// reset, as the trait requires.
+pub fn run_contract_tests<R, F>(
+    radio: &mut R,
+    contract: &LoraContract,
+    mut delay: F,
+) -> Result<(), ContractViolation>
+where
+    R: LoraRadio,
+    F: FnMut(),
+{
+    require(
+        radio.rx_window_duration_ms() > 0,
+        "rx_window_duration",
+        "rx_window_duration_ms must be non-zero",
+    )?;
+    require_ok(
+        radio.set_frequency(contract.tx.freq_hz),
+        "set_frequency",
+        "set_frequency must accept the uplink frequency",
+    )?;
+
+    transmit_once(radio, contract, &mut delay, "transmit")?;
+
+    require_ok(
+        radio.prepare_rx(contract.rx, RxWindow::Rx1),
+        "prepare_rx",
+        "prepare_rx must succeed after a completed transmission",
+    )?;
+    receive_window(radio, contract, &mut delay, "receive")?;
+
+    require_ok(
+        radio.prepare_rx(contract.rx, RxWindow::Rx2),
+        "prepare_rx_after_timeout",
+        "prepare_rx must succeed again after a timed-out window, without a reset",
+    )?;
+    receive_window(radio, contract, &mut delay, "receive_after_timeout")?;
+    let _ = radio.rx_quality();
+
+    transmit_once(radio, contract, &mut delay, "transmit_after_rx")
+}
+
+/// `prepare_tx` followed by polling `transmit` until it completes.
+fn transmit_once<R: LoraRadio>(
+    radio: &mut R,
+    contract: &LoraContract,
+    delay: &mut impl FnMut(),
+    check: &'static str,
+) -> Result<(), ContractViolation> {
+    require_ok(
+        radio.prepare_tx(contract.tx, &CONTRACT_PAYLOAD),
+        check,
+        "prepare_tx must accept a short payload",
+    )?;
+    for _ in 0..contract.max_polls.max(1) {
+        match radio.transmit() {
+            Ok(_on_air_ms) => return Ok(()),
+            Err(nb::Error::WouldBlock) => delay(),
+            Err(nb::Error::Other(e)) => {
+                return require_ok(Err(e), check, "transmit must not fail after prepare_tx");
+            }
+        }
+    }
+    Err(ContractViolation::new(
+        check,
+        "transmit did not complete within max_polls",
+    ))
+}
+
+/// Polls `receive` for one window; a packet or a timeout both pass.
+fn receive_window<R: LoraRadio>(
+    radio: &mut R,
+    contract: &LoraContract,
+    delay: &mut impl FnMut(),
+    check: &'static str,
+) -> Result<(), ContractViolation> {
+    let mut buf = [0u8; 256];
+    for _ in 0..contract.max_polls.max(1) {
+        match radio.receive(&mut buf) {
+            Ok((len, _quality)) => {
+                return require(
+                    len <= buf.len(),
+                    check,
+                    "receive must not report more bytes than the buffer holds",
+                );
+            }
+            Err(nb::Error::WouldBlock) => delay(),
+            Err(nb::Error::Other(e)) => {
+                return require_ok(
+                    Err(e),
+                    check,
+                    "receive must report an empty window as WouldBlock",
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lora::mock::{MockLoraRadio, MockRadioError};
+    use crate::lora::RxQuality;
+
+    #[test]
+    fn mock_radio_passes_with_empty_windows() {
+        let mut radio = MockLoraRadio::new();
+        assert_eq!(
+            run_contract_tests(&mut radio, &LoraContract::default(), || {}),
+            Ok(())
+        );
+        assert_eq!(radio.tx_calls.len(), 2);
+        assert_eq!(radio.current_freq_hz(), 868_100_000);
+    }
+
+    #[test]
+    fn mock_radio_passes_with_a_downlink() {
+        let mut radio = MockLoraRadio::new();
+        radio
+            .queue_rx_response(&[0x60, 0x01], RxQuality::default())
+            .unwrap();
+        assert_eq!(
+            run_contract_tests(&mut radio, &LoraContract::default(), || {}),
+            Ok(())
+        );
+        assert!(radio.rx_queue_empty());
+    }
+
+    #[test]
+    fn timed_out_window_polls_full_budget() {
+        let mut radio = MockLoraRadio::new();
+        let mut delays = 0;
+        run_contract_tests(
+            &mut radio,
+            &LoraContract::default().with_max_polls(4),
+            || delays += 1,
+        )
+        .unwrap();
+        // Two empty windows of four polls each; transmit never blocks.
+        assert_eq!(delays, 8);
+    }
+
+    #[test]
+    fn zero_window_duration_is_reported() {
+        let mut radio = MockLoraRadio::new();
+        radio.rx_window_duration = 0;
+        let violation =
+            run_contract_tests(&mut radio, &LoraContract::default(), || {}).unwrap_err();
+        assert_eq!(violation.check, "rx_window_duration");
+    }
+
+    /// Radio that needs a reset before a second receive window.
+    struct OneShotRx {
+        inner: MockLoraRadio,
+        rx_prepared: bool,
+    }
+
+    impl LoraRadio for OneShotRx {
+        type Error = MockRadioError;
+
+        fn prepare_tx(&mut self, config: TxConfig, buf: &[u8]) -> Result<(), MockRadioError> {
+            self.inner.prepare_tx(config, buf)
+        }
+        fn transmit(&mut self) -> nb::Result<u32, MockRadioError> {
+            self.inner.transmit()
+        }
+        fn prepare_rx(&mut self, config: RxConfig, window: RxWindow) -> Result<(), MockRadioError> {
+            if self.rx_prepared {
+                return Err(MockRadioError::CapacityExhausted);
+            }
+            self.rx_prepared = true;
+            self.inner.prepare_rx(config, window)
+        }
+        fn receive(&mut self, buf: &mut [u8]) -> nb::Result<(usize, RxQuality), MockRadioError> {
+            self.inner.receive(buf)
+        }
+        fn set_frequency(&mut self, freq_hz: u32) -> Result<(), MockRadioError> {
+            self.inner.set_frequency(freq_hz)
+        }
+        fn rx_quality(&self) -> RxQuality {
+            self.inner.rx_quality()
+        }
+        fn rx_window_offset_ms(&self) -> i32 {
+            self.inner.rx_window_offset_ms()
+        }
+        fn rx_window_duration_ms(&self) -> u32 {
+            self.inner.rx_window_duration_ms()
+        }
+    }
+
+    #[test]
+    fn radio_needing_reset_after_timeout_is_reported() {
+        let mut radio = OneShotRx {
+            inner: MockLoraRadio::new(),
+            rx_prepared: false,
+        };
+        let violation =
+            run_contract_tests(&mut radio, &LoraContract::default(), || {}).unwrap_err();
+        assert_eq!(violation.check, "prepare_rx_after_timeout");
+    }
+}
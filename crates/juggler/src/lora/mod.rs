@@ -6,17 +6,22 @@
 //! - [`lorawan::LorawanDevice<R>`] — LoRaWAN Class A stack, generic over the radio
 //! - [`mock::MockLoraRadio`] — test double for host-side unit tests
 //!   (requires the `mock` feature or `#[cfg(test)]`)
+//! - `conformance::run_contract_tests` — contract suite for radio
+//!   implementations (requires the `conformance` feature)
 //!
 //! # Feature flags
 //!
 //! | Feature | What it enables                                              |
 //! |:--------|:-------------------------------------------------------------|
 //! | `mock`  | `MockLoraRadio` for downstream host-side tests               |
+//! | `conformance` | `conformance::run_contract_tests` for driver implementations |
 
 pub mod commands;
 pub mod config;
 pub mod lorawan;
 
+#[cfg(any(test, feature = "conformance"))]
+pub mod conformance;
 #[cfg(any(test, feature = "mock"))]
 pub mod mock;
 
@@ -0,0 +1,1535 @@
+//! MQTT 3.1.1 control-packet codec — `no_std`, allocation-free.
+//!
+//! [`Packet`] covers all fourteen MQTT 3.1.1 control packets.
+//! [`Packet::encode`] writes one into a caller buffer, and
+//! [`Packet::decode`] parses one that borrows its strings and payloads from
+//! the input, so neither direction allocates.  Decoding a stream works a
+//! packet at a time: [`packet_len`] reports how many bytes the next packet
+//! needs, and [`CodecError::Incomplete`] means "read more and retry".
+//!
+//! Encoding validates what the client controls — topics through
+//! [`validate_publish_topic`] and [`validate_subscribe_filter`], the client
+//! identifier through [`validate_client_id`] — and decoding rejects every
+//! reserved-bit, flag, and framing violation the specification calls
+//! malformed.
+
+use super::{validate_client_id, validate_publish_topic, validate_subscribe_filter};
+
+/// Protocol level byte of MQTT 3.1.1 in CONNECT.
+pub const PROTOCOL_LEVEL: u8 = 4;
+
+/// Largest Remaining Length a four-byte varint can express (MQTT 3.1.1
+/// §2.2.3).
+pub const REMAINING_LENGTH_MAX: usize = 268_435_455;
+
+/// SUBACK return code for a rejected subscription.
+pub const SUBACK_FAILURE: u8 = 0x80;
+
+/// Protocol name, length-prefixed, as it opens the CONNECT variable header.
+const PROTOCOL_NAME: [u8; 6] = [0x00, 0x04, b'M', b'Q', b'T', b'T'];
+
+// Control packet types (high nibble of the fixed header).
+const CONNECT: u8 = 1;
+const CONNACK: u8 = 2;
+const PUBLISH: u8 = 3;
+const PUBACK: u8 = 4;
+const PUBREC: u8 = 5;
+const PUBREL: u8 = 6;
+const PUBCOMP: u8 = 7;
+const SUBSCRIBE: u8 = 8;
+const SUBACK: u8 = 9;
+const UNSUBSCRIBE: u8 = 10;
+const UNSUBACK: u8 = 11;
+const PINGREQ: u8 = 12;
+const PINGRESP: u8 = 13;
+const DISCONNECT: u8 = 14;
+
+// CONNECT flags (MQTT 3.1.1 §3.1.2.3).
+const FLAG_USERNAME: u8 = 0x80;
+const FLAG_PASSWORD: u8 = 0x40;
+const FLAG_WILL_RETAIN: u8 = 0x20;
+const FLAG_WILL: u8 = 0x04;
+const FLAG_CLEAN_SESSION: u8 = 0x02;
+const WILL_QOS_SHIFT: u8 = 3;
+
+/// MQTT Quality of Service level.
+///
+/// Platform-neutral mirror of `esp_idf_svc::mqtt::client::QoS`, used by the
+/// codec and by [`SubscribeClient`](super::SubscribeClient) and
+/// [`spawn_subscriber_thread`](super::spawn_subscriber_thread) so both can be
+/// compiled and tested on any host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QoS {
+    AtMostOnce,
+    AtLeastOnce,
+    ExactlyOnce,
+}
+
+impl QoS {
+    /// The level as it appears on the wire: 0, 1, or 2.
+    pub const fn level(self) -> u8 {
+        match self {
+            Self::AtMostOnce => 0,
+            Self::AtLeastOnce => 1,
+            Self::ExactlyOnce => 2,
+        }
+    }
+
+    /// The QoS for wire level `level`, or `None` above 2.
+    pub const fn from_level(level: u8) -> Option<Self> {
+        match level {
+            0 => Some(Self::AtMostOnce),
+            1 => Some(Self::AtLeastOnce),
+            2 => Some(Self::ExactlyOnce),
+            _ => None,
+        }
+    }
+}
+
+/// Errors returned by the codec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodecError {
+    /// The output buffer cannot hold the encoded packet.
+    BufferTooSmall,
+    /// The input ends before the packet does; read more bytes and retry.
+    Incomplete,
+    /// The Remaining Length runs past four bytes or above
+    /// [`REMAINING_LENGTH_MAX`].
+    BadRemainingLength,
+    /// A field failed validation; carries the validator's message.
+    Invalid(&'static str),
+    /// The bytes are not a well-formed MQTT 3.1.1 packet.
+    Malformed(&'static str),
+}
+
+/// Last Will and Testament carried in CONNECT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Will<'a> {
+    /// Topic the broker publishes the will to.
+    pub topic: &'a str,
+    /// Will message.
+    pub payload: &'a [u8],
+    /// QoS of the will publish.
+    pub qos: QoS,
+    /// Whether the broker retains the will publish.
+    pub retain: bool,
+}
+
+/// CONNECT: the first packet a client sends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Connect<'a> {
+    /// Client identifier.  May be empty only with `clean_session`, in which
+    /// case the broker assigns one.
+    pub client_id: &'a str,
+    /// Keep-alive interval in seconds; `0` disables it.
+    pub keep_alive_secs: u16,
+    /// Discard any previous session state.
+    pub clean_session: bool,
+    /// Last Will and Testament.
+    pub will: Option<Will<'a>>,
+    /// User name.
+    pub username: Option<&'a str>,
+    /// Password.  MQTT 3.1.1 only allows it together with `username`.
+    pub password: Option<&'a [u8]>,
+}
+
+impl<'a> Connect<'a> {
+    /// A clean-session CONNECT with no will or credentials.
+    pub const fn new(client_id: &'a str, keep_alive_secs: u16) -> Self {
+        Self {
+            client_id,
+            keep_alive_secs,
+            clean_session: true,
+            will: None,
+            username: None,
+            password: None,
+        }
+    }
+}
+
+/// CONNACK return code (MQTT 3.1.1 §3.2.2.3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectReturnCode {
+    /// Connection accepted.
+    Accepted,
+    /// The broker does not support protocol level 4.
+    UnacceptableProtocolVersion,
+    /// The client identifier is not allowed.
+    IdentifierRejected,
+    /// The MQTT service is unavailable.
+    ServerUnavailable,
+    /// The user name or password is malformed.
+    BadUsernameOrPassword,
+    /// The client is not authorised to connect.
+    NotAuthorized,
+}
+
+impl ConnectReturnCode {
+    /// The code as it appears on the wire.
+    pub const fn code(self) -> u8 {
+        match self {
+            Self::Accepted => 0,
+            Self::UnacceptableProtocolVersion => 1,
+            Self::IdentifierRejected => 2,
+            Self::ServerUnavailable => 3,
+            Self::BadUsernameOrPassword => 4,
+            Self::NotAuthorized => 5,
+        }
+    }
+
+    /// The return code for wire value `code`, or `None` if it is reserved.
+    pub const fn from_code(code: u8) -> Option<Self> {
+        Some(match code {
+            0 => Self::Accepted,
+            1 => Self::UnacceptableProtocolVersion,
+            2 => Self::IdentifierRejected,
+            3 => Self::ServerUnavailable,
+            4 => Self::BadUsernameOrPassword,
+            5 => Self::NotAuthorized,
+            _ => return None,
+        })
+    }
+}
+
+/// CONNACK: the broker's answer to CONNECT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnAck {
+    /// The broker resumed a stored session.
+    pub session_present: bool,
+    /// Whether the connection was accepted, and if not, why.
+    pub code: ConnectReturnCode,
+}
+
+/// PUBLISH, in either direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Publish<'a> {
+    /// Topic name; wildcards are not allowed.
+    pub topic: &'a str,
+    /// Application message.
+    pub payload: &'a [u8],
+    /// Delivery QoS.
+    pub qos: QoS,
+    /// Whether the broker retains the message.
+    pub retain: bool,
+    /// This is a redelivery of an earlier attempt (QoS 1 and 2 only).
+    pub dup: bool,
+    /// Packet identifier: present, and non-zero, exactly when `qos` is
+    /// above 0.
+    pub packet_id: Option<u16>,
+}
+
+impl<'a> Publish<'a> {
+    /// A QoS 0 publish.
+    pub const fn new(topic: &'a str, payload: &'a [u8]) -> Self {
+        Self {
+            topic,
+            payload,
+            qos: QoS::AtMostOnce,
+            retain: false,
+            dup: false,
+            packet_id: None,
+        }
+    }
+}
+
+/// Topic filters and requested QoS of a SUBSCRIBE.
+///
+/// Built from a slice with [`new`](Self::new) when encoding; borrowed from
+/// the packet bytes when decoding.  Read either with [`iter`](Self::iter).
+#[derive(Debug, Clone, Copy)]
+pub struct SubscribeFilters<'a>(Filters<'a, (&'a str, QoS)>);
+
+impl<'a> SubscribeFilters<'a> {
+    /// Filters to encode.
+    pub const fn new(filters: &'a [(&'a str, QoS)]) -> Self {
+        Self(Filters::List(filters))
+    }
+
+    /// The `(filter, requested QoS)` pairs, in packet order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, QoS)> + 'a {
+        let (list, mut wire): (&[_], &[u8]) = match self.0 {
+            Filters::List(list) => (list, &[]),
+            Filters::Wire(wire) => (&[], wire),
+        };
+        list.iter().copied().chain(core::iter::from_fn(move || {
+            let mut r = Reader::new(wire);
+            let filter = r.str().ok()?;
+            let qos = QoS::from_level(r.u8().ok()?)?;
+            wire = r.rest();
+            Some((filter, qos))
+        }))
+    }
+
+    /// Number of filters.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Returns `true` if there are no filters (never true for a decoded
+    /// SUBSCRIBE).
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
+impl PartialEq for SubscribeFilters<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for SubscribeFilters<'_> {}
+
+/// Topic filters of an UNSUBSCRIBE.
+///
+/// Built from a slice with [`new`](Self::new) when encoding; borrowed from
+/// the packet bytes when decoding.  Read either with [`iter`](Self::iter).
+#[derive(Debug, Clone, Copy)]
+pub struct UnsubscribeFilters<'a>(Filters<'a, &'a str>);
+
+impl<'a> UnsubscribeFilters<'a> {
+    /// Filters to encode.
+    pub const fn new(filters: &'a [&'a str]) -> Self {
+        Self(Filters::List(filters))
+    }
+
+    /// The filters, in packet order.
+    pub fn iter(&self) -> impl Iterator<Item = &'a str> + 'a {
+        let (list, mut wire): (&[_], &[u8]) = match self.0 {
+            Filters::List(list) => (list, &[]),
+            Filters::Wire(wire) => (&[], wire),
+        };
+        list.iter().copied().chain(core::iter::from_fn(move || {
+            let mut r = Reader::new(wire);
+            let filter = r.str().ok()?;
+            wire = r.rest();
+            Some(filter)
+        }))
+    }
+
+    /// Number of filters.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Returns `true` if there are no filters (never true for a decoded
+    /// UNSUBSCRIBE).
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
+impl PartialEq for UnsubscribeFilters<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for UnsubscribeFilters<'_> {}
+
+#[derive(Debug, Clone, Copy)]
+enum Filters<'a, T> {
+    List(&'a [T]),
+    /// Validated payload bytes of a decoded packet.
+    Wire(&'a [u8]),
+}
+
+/// SUBACK: one return code per requested filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubAck<'a> {
+    /// Identifier of the SUBSCRIBE being answered.
+    pub packet_id: u16,
+    /// The granted QoS level (0–2) or [`SUBACK_FAILURE`] for each filter.
+    pub return_codes: &'a [u8],
+}
+
+impl SubAck<'_> {
+    /// The granted QoS of each filter, `None` where the broker refused it.
+    pub fn granted(&self) -> impl Iterator<Item = Option<QoS>> + '_ {
+        self.return_codes.iter().map(|&code| QoS::from_level(code))
+    }
+}
+
+/// An MQTT 3.1.1 control packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Packet<'a> {
+    /// Client request to connect.
+    Connect(Connect<'a>),
+    /// Connect acknowledgment.
+    ConnAck(ConnAck),
+    /// Application message.
+    Publish(Publish<'a>),
+    /// QoS 1 publish acknowledgment.
+    PubAck(u16),
+    /// QoS 2 publish received (part 1).
+    PubRec(u16),
+    /// QoS 2 publish release (part 2).
+    PubRel(u16),
+    /// QoS 2 publish complete (part 3).
+    PubComp(u16),
+    /// Subscribe request.
+    Subscribe {
+        /// Packet identifier; never 0.
+        packet_id: u16,
+        /// Filters and requested QoS.
+        filters: SubscribeFilters<'a>,
+    },
+    /// Subscribe acknowledgment.
+    SubAck(SubAck<'a>),
+    /// Unsubscribe request.
+    Unsubscribe {
+        /// Packet identifier; never 0.
+        packet_id: u16,
+        /// Filters to remove.
+        filters: UnsubscribeFilters<'a>,
+    },
+    /// Unsubscribe acknowledgment.
+    UnsubAck(u16),
+    /// Keep-alive request.
+    PingReq,
+    /// Keep-alive response.
+    PingResp,
+    /// Clean client disconnect; suppresses the will.
+    Disconnect,
+}
+
+impl<'a> Packet<'a> {
+    /// Encodes the packet into `buf` and returns the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// [`CodecError::Invalid`] for a field the specification forbids (a
+    /// wildcard publish topic, a password without a user name, a missing or
+    /// zero packet identifier, ...), [`CodecError::BadRemainingLength`] if
+    /// the packet exceeds [`REMAINING_LENGTH_MAX`], and
+    /// [`CodecError::BufferTooSmall`] if it does not fit in `buf`.
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, CodecError> {
+        self.validate()?;
+        let remaining = self.remaining_len()?;
+        let mut w = Writer { buf, pos: 0 };
+        w.u8(self.first_byte())?;
+        w.remaining_length(remaining)?;
+        match *self {
+            Self::Connect(ref c) => {
+                w.bytes(&PROTOCOL_NAME)?;
+                w.u8(PROTOCOL_LEVEL)?;
+                w.u8(connect_flags(c))?;
+                w.u16(c.keep_alive_secs)?;
+                w.str(c.client_id)?;
+                if let Some(will) = c.will {
+                    w.str(will.topic)?;
+                    w.binary(will.payload)?;
+                }
+                if let Some(username) = c.username {
+                    w.str(username)?;
+                }
+                if let Some(password) = c.password {
+                    w.binary(password)?;
+                }
+            }
+            Self::ConnAck(ack) => {
+                w.u8(u8::from(ack.session_present))?;
+                w.u8(ack.code.code())?;
+            }
+            Self::Publish(ref p) => {
+                w.str(p.topic)?;
+                if let Some(id) = p.packet_id {
+                    w.u16(id)?;
+                }
+                w.bytes(p.payload)?;
+            }
+            Self::PubAck(id)
+            | Self::PubRec(id)
+            | Self::PubRel(id)
+            | Self::PubComp(id)
+            | Self::UnsubAck(id) => w.u16(id)?,
+            Self::Subscribe {
+                packet_id,
+                ref filters,
+            } => {
+                w.u16(packet_id)?;
+                for (filter, qos) in filters.iter() {
+                    w.str(filter)?;
+                    w.u8(qos.level())?;
+                }
+            }
+            Self::SubAck(ref ack) => {
+                w.u16(ack.packet_id)?;
+                w.bytes(ack.return_codes)?;
+            }
+            Self::Unsubscribe {
+                packet_id,
+                ref filters,
+            } => {
+                w.u16(packet_id)?;
+                for filter in filters.iter() {
+                    w.str(filter)?;
+                }
+            }
+            Self::PingReq | Self::PingResp | Self::Disconnect => {}
+        }
+        Ok(w.pos)
+    }
+
+    /// Decodes the packet at the start of `buf`, returning it and the number
+    /// of bytes it occupied.
+    ///
+    /// # Errors
+    ///
+    /// [`CodecError::Incomplete`] if `buf` holds only part of the packet,
+    /// [`CodecError::BadRemainingLength`] for an overlong length varint,
+    /// and [`CodecError::Malformed`] or [`CodecError::Invalid`] for bytes
+    /// that break the specification.
+    pub fn decode(buf: &'a [u8]) -> Result<(Self, usize), CodecError> {
+        let total = packet_len(buf)?;
+        if buf.len() < total {
+            return Err(CodecError::Incomplete);
+        }
+        let first = buf[0];
+        let (remaining, len_bytes) = decode_remaining_length(&buf[1..])?;
+        let mut r = Reader::new(&buf[1 + len_bytes..1 + len_bytes + remaining]);
+        let (kind, flags) = (first >> 4, first & 0x0F);
+
+        let reserved_flags = match kind {
+            PUBLISH => flags,
+            PUBREL | SUBSCRIBE | UNSUBSCRIBE => 0x02,
+            _ => 0x00,
+        };
+        if flags != reserved_flags {
+            return Err(CodecError::Malformed("reserved fixed-header flags"));
+        }
+
+        let packet = match kind {
+            CONNECT => Self::Connect(decode_connect(&mut r)?),
+            CONNACK => {
+                let ack_flags = r.u8()?;
+                if ack_flags & !0x01 != 0 {
+                    return Err(CodecError::Malformed("reserved CONNACK flags"));
+                }
+                let code = ConnectReturnCode::from_code(r.u8()?)
+                    .ok_or(CodecError::Malformed("reserved CONNACK return code"))?;
+                Self::ConnAck(ConnAck {
+                    session_present: ack_flags & 0x01 != 0,
+                    code,
+                })
+            }
+            PUBLISH => Self::Publish(decode_publish(flags, &mut r)?),
+            PUBACK => Self::PubAck(r.packet_id()?),
+            PUBREC => Self::PubRec(r.packet_id()?),
+            PUBREL => Self::PubRel(r.packet_id()?),
+            PUBCOMP => Self::PubComp(r.packet_id()?),
+            SUBSCRIBE => {
+                let packet_id = r.packet_id()?;
+                let wire = r.rest();
+                let mut check = Reader::new(wire);
+                while !check.is_empty() {
+                    validate_subscribe_filter(check.str()?).map_err(CodecError::Invalid)?;
+                    if QoS::from_level(check.u8()?).is_none() {
+                        return Err(CodecError::Malformed("requested QoS above 2"));
+                    }
+                }
+                if wire.is_empty() {
+                    return Err(CodecError::Malformed("SUBSCRIBE without filters"));
+                }
+                Self::Subscribe {
+                    packet_id,
+                    filters: SubscribeFilters(Filters::Wire(wire)),
+                }
+            }
+            SUBACK => {
+                let packet_id = r.packet_id()?;
+                let return_codes = r.rest();
+                if return_codes.is_empty() {
+                    return Err(CodecError::Malformed("SUBACK without return codes"));
+                }
+                if return_codes
+                    .iter()
+                    .any(|&c| QoS::from_level(c).is_none() && c != SUBACK_FAILURE)
+                {
+                    return Err(CodecError::Malformed("reserved SUBACK return code"));
+                }
+                Self::SubAck(SubAck {
+                    packet_id,
+                    return_codes,
+                })
+            }
+            UNSUBSCRIBE => {
+                let packet_id = r.packet_id()?;
+                let wire = r.rest();
+                let mut check = Reader::new(wire);
+                while !check.is_empty() {
+                    validate_subscribe_filter(check.str()?).map_err(CodecError::Invalid)?;
+                }
+                if wire.is_empty() {
+                    return Err(CodecError::Malformed("UNSUBSCRIBE without filters"));
+                }
+                Self::Unsubscribe {
+                    packet_id,
+                    filters: UnsubscribeFilters(Filters::Wire(wire)),
+                }
+            }
+            UNSUBACK => Self::UnsubAck(r.packet_id()?),
+            PINGREQ => Self::PingReq,
+            PINGRESP => Self::PingResp,
+            DISCONNECT => Self::Disconnect,
+            _ => return Err(CodecError::Malformed("reserved packet type")),
+        };
+        if !r.is_empty() {
+            return Err(CodecError::Malformed("bytes past the end of the packet"));
+        }
+        Ok((packet, total))
+    }
+
+    /// Checks the fields the specification constrains before encoding.
+    fn validate(&self) -> Result<(), CodecError> {
+        match *self {
+            Self::Connect(ref c) => {
+                if !(c.client_id.is_empty() && c.clean_session) {
+                    validate_client_id(c.client_id).map_err(CodecError::Invalid)?;
+                }
+                if let Some(will) = c.will {
+                    validate_publish_topic(will.topic).map_err(CodecError::Invalid)?;
+                }
+                if c.password.is_some() && c.username.is_none() {
+                    return Err(CodecError::Invalid(
+                        "MQTT 3.1.1 does not allow a password without a user name",
+                    ));
+                }
+            }
+            Self::Publish(ref p) => {
+                validate_publish_topic(p.topic).map_err(CodecError::Invalid)?;
+                match (p.qos, p.packet_id) {
+                    (QoS::AtMostOnce, None) if !p.dup => {}
+                    (QoS::AtMostOnce, _) => {
+                        return Err(CodecError::Invalid(
+                            "QoS 0 publish must have no packet identifier and no DUP flag",
+                        ))
+                    }
+                    (_, Some(id)) if id != 0 => {}
+                    _ => {
+                        return Err(CodecError::Invalid(
+                            "QoS 1 and 2 publishes need a non-zero packet identifier",
+                        ))
+                    }
+                }
+            }
+            Self::Subscribe {
+                packet_id,
+                ref filters,
+            } => {
+                check_packet_id(packet_id)?;
+                if filters.is_empty() {
+                    return Err(CodecError::Invalid("SUBSCRIBE needs at least one filter"));
+                }
+                for (filter, _) in filters.iter() {
+                    validate_subscribe_filter(filter).map_err(CodecError::Invalid)?;
+                }
+            }
+            Self::SubAck(ref ack) => {
+                check_packet_id(ack.packet_id)?;
+                if ack.return_codes.is_empty() {
+                    return Err(CodecError::Invalid("SUBACK needs at least one return code"));
+                }
+                if ack
+                    .return_codes
+                    .iter()
+                    .any(|&c| QoS::from_level(c).is_none() && c != SUBACK_FAILURE)
+                {
+                    return Err(CodecError::Invalid(
+                        "SUBACK return code must be 0-2 or 0x80",
+                    ));
+                }
+            }
+            Self::Unsubscribe {
+                packet_id,
+                ref filters,
+            } => {
+                check_packet_id(packet_id)?;
+                if filters.is_empty() {
+                    return Err(CodecError::Invalid("UNSUBSCRIBE needs at least one filter"));
+                }
+                for filter in filters.iter() {
+                    validate_subscribe_filter(filter).map_err(CodecError::Invalid)?;
+                }
+            }
+            Self::PubAck(id)
+            | Self::PubRec(id)
+            | Self::PubRel(id)
+            | Self::PubComp(id)
+            | Self::UnsubAck(id) => check_packet_id(id)?,
+            Self::ConnAck(_) | Self::PingReq | Self::PingResp | Self::Disconnect => {}
+        }
+        Ok(())
+    }
+
+    /// The fixed header's first byte: packet type and flags.
+    fn first_byte(&self) -> u8 {
+        let (kind, flags) = match *self {
+            Self::Connect(_) => (CONNECT, 0),
+            Self::ConnAck(_) => (CONNACK, 0),
+            Self::Publish(ref p) => (
+                PUBLISH,
+                u8::from(p.dup) << 3 | p.qos.level() << 1 | u8::from(p.retain),
+            ),
+            Self::PubAck(_) => (PUBACK, 0),
+            Self::PubRec(_) => (PUBREC, 0),
+            Self::PubRel(_) => (PUBREL, 0x02),
+            Self::PubComp(_) => (PUBCOMP, 0),
+            Self::Subscribe { .. } => (SUBSCRIBE, 0x02),
+            Self::SubAck(_) => (SUBACK, 0),
+            Self::Unsubscribe { .. } => (UNSUBSCRIBE, 0x02),
+            Self::UnsubAck(_) => (UNSUBACK, 0),
+            Self::PingReq => (PINGREQ, 0),
+            Self::PingResp => (PINGRESP, 0),
+            Self::Disconnect => (DISCONNECT, 0),
+        };
+        kind << 4 | flags
+    }
+
+    /// Length of everything after the fixed header.
+    fn remaining_len(&self) -> Result<usize, CodecError> {
+        let len = match *self {
+            Self::Connect(ref c) => {
+                let mut len = PROTOCOL_NAME.len() + 1 + 1 + 2 + string_len(c.client_id.len())?;
+                if let Some(will) = c.will {
+                    len += string_len(will.topic.len())? + string_len(will.payload.len())?;
+                }
+                if let Some(username) = c.username {
+                    len += string_len(username.len())?;
+                }
+                if let Some(password) = c.password {
+                    len += string_len(password.len())?;
+                }
+                len
+            }
+            Self::ConnAck(_) => 2,
+            Self::Publish(ref p) => {
+                string_len(p.topic.len())?
+                    + if p.packet_id.is_some() { 2 } else { 0 }
+                    + p.payload.len()
+            }
+            Self::PubAck(_)
+            | Self::PubRec(_)
+            | Self::PubRel(_)
+            | Self::PubComp(_)
+            | Self::UnsubAck(_) => 2,
+            Self::Subscribe { ref filters, .. } => {
+                let mut len = 2;
+                for (filter, _) in filters.iter() {
+                    len += string_len(filter.len())? + 1;
+                }
+                len
+            }
+            Self::SubAck(ref ack) => 2 + ack.return_codes.len(),
+            Self::Unsubscribe { ref filters, .. } => {
+                let mut len = 2;
+                for filter in filters.iter() {
+                    len += string_len(filter.len())?;
+                }
+                len
+            }
+            Self::PingReq | Self::PingResp | Self::Disconnect => 0,
+        };
+        if len > REMAINING_LENGTH_MAX {
+            return Err(CodecError::BadRemainingLength);
+        }
+        Ok(len)
+    }
+}
+
+/// Total length of the packet at the start of `buf` — fixed header
+/// included — once its header has arrived.
+///
+/// Returns [`CodecError::Incomplete`] while the fixed header itself is cut
+/// short, so a reader can call this on a growing buffer until it succeeds
+/// and then wait for that many bytes.
+pub fn packet_len(buf: &[u8]) -> Result<usize, CodecError> {
+    let Some(rest) = buf.get(1..) else {
+        return Err(CodecError::Incomplete);
+    };
+    let (remaining, len_bytes) = decode_remaining_length(rest)?;
+    Ok(1 + len_bytes + remaining)
+}
+
+/// Encodes `len` as a Remaining Length varint into `buf`, returning the
+/// number of bytes written (1–4).
+pub fn encode_remaining_length(len: usize, buf: &mut [u8]) -> Result<usize, CodecError> {
+    let mut w = Writer { buf, pos: 0 };
+    w.remaining_length(len)?;
+    Ok(w.pos)
+}
+
+/// Decodes a Remaining Length varint from the start of `buf`, returning the
+/// value and the number of bytes it took.
+///
+/// Returns [`CodecError::Incomplete`] if `buf` ends mid-varint and
+/// [`CodecError::BadRemainingLength`] if it runs past four bytes.
+pub fn decode_remaining_length(buf: &[u8]) -> Result<(usize, usize), CodecError> {
+    let mut value = 0usize;
+    for i in 0..4 {
+        let byte = *buf.get(i).ok_or(CodecError::Incomplete)?;
+        value |= usize::from(byte & 0x7F) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    Err(CodecError::BadRemainingLength)
+}
+
+fn connect_flags(c: &Connect<'_>) -> u8 {
+    let mut flags = 0;
+    if c.clean_session {
+        flags |= FLAG_CLEAN_SESSION;
+    }
+    if let Some(will) = c.will {
+        flags |= FLAG_WILL | will.qos.level() << WILL_QOS_SHIFT;
+        if will.retain {
+            flags |= FLAG_WILL_RETAIN;
+        }
+    }
+    if c.username.is_some() {
+        flags |= FLAG_USERNAME;
+    }
+    if c.password.is_some() {
+        flags |= FLAG_PASSWORD;
+    }
+    flags
+}
+
+fn decode_connect<'a>(r: &mut Reader<'a>) -> Result<Connect<'a>, CodecError> {
+    if r.take(PROTOCOL_NAME.len())? != PROTOCOL_NAME {
+        return Err(CodecError::Malformed("protocol name is not MQTT"));
+    }
+    if r.u8()? != PROTOCOL_LEVEL {
+        return Err(CodecError::Malformed(
+            "protocol level is not 4 (MQTT 3.1.1)",
+        ));
+    }
+    let flags = r.u8()?;
+    if flags & 0x01 != 0 {
+        return Err(CodecError::Malformed("reserved CONNECT flag set"));
+    }
+    let has_will = flags & FLAG_WILL != 0;
+    let will_qos = QoS::from_level((flags >> WILL_QOS_SHIFT) & 0x03)
+        .ok_or(CodecError::Malformed("will QoS above 2"))?;
+    let will_retain = flags & FLAG_WILL_RETAIN != 0;
+    if !has_will && (will_qos != QoS::AtMostOnce || will_retain) {
+        return Err(CodecError::Malformed(
+            "will QoS or retain set without a will",
+        ));
+    }
+    if flags & FLAG_PASSWORD != 0 && flags & FLAG_USERNAME == 0 {
+        return Err(CodecError::Malformed("password flag set without user name"));
+    }
+    let keep_alive_secs = r.u16()?;
+    let client_id = r.str()?;
+    let clean_session = flags & FLAG_CLEAN_SESSION != 0;
+    if client_id.is_empty() && !clean_session {
+        return Err(CodecError::Invalid(
+            "an empty client identifier requires a clean session",
+        ));
+    }
+    let will = if has_will {
+        let topic = r.str()?;
+        validate_publish_topic(topic).map_err(CodecError::Invalid)?;
+        Some(Will {
+            topic,
+            payload: r.binary()?,
+            qos: will_qos,
+            retain: will_retain,
+        })
+    } else {
+        None
+    };
+    let username = if flags & FLAG_USERNAME != 0 {
+        Some(r.str()?)
+    } else {
+        None
+    };
+    let password = if flags & FLAG_PASSWORD != 0 {
+        Some(r.binary()?)
+    } else {
+        None
+    };
+    Ok(Connect {
+        client_id,
+        keep_alive_secs,
+        clean_session,
+        will,
+        username,
+        password,
+    })
+}
+
+fn decode_publish<'a>(flags: u8, r: &mut Reader<'a>) -> Result<Publish<'a>, CodecError> {
+    let qos = QoS::from_level((flags >> 1) & 0x03).ok_or(CodecError::Malformed("publish QoS 3"))?;
+    let dup = flags & 0x08 != 0;
+    if dup && qos == QoS::AtMostOnce {
+        return Err(CodecError::Malformed("DUP set on a QoS 0 publish"));
+    }
+    let topic = r.str()?;
+    validate_publish_topic(topic).map_err(CodecError::Invalid)?;
+    let packet_id = if qos == QoS::AtMostOnce {
+        None
+    } else {
+        Some(r.packet_id()?)
+    };
+    Ok(Publish {
+        topic,
+        payload: r.rest(),
+        qos,
+        retain: flags & 0x01 != 0,
+        dup,
+        packet_id,
+    })
+}
+
+fn check_packet_id(id: u16) -> Result<(), CodecError> {
+    if id == 0 {
+        return Err(CodecError::Invalid("packet identifier must be non-zero"));
+    }
+    Ok(())
+}
+
+/// Encoded size of a length-prefixed string or binary field of `len` bytes.
+fn string_len(len: usize) -> Result<usize, CodecError> {
+    if len > usize::from(u16::MAX) {
+        return Err(CodecError::Invalid(
+            "string or binary field exceeds 65535 bytes",
+        ));
+    }
+    Ok(2 + len)
+}
+
+struct Writer<'b> {
+    buf: &'b mut [u8],
+    pos: usize,
+}
+
+impl Writer<'_> {
+    fn bytes(&mut self, data: &[u8]) -> Result<(), CodecError> {
+        let end = self.pos + data.len();
+        self.buf
+            .get_mut(self.pos..end)
+            .ok_or(CodecError::BufferTooSmall)?
+            .copy_from_slice(data);
+        self.pos = end;
+        Ok(())
+    }
+
+    fn u8(&mut self, value: u8) -> Result<(), CodecError> {
+        self.bytes(&[value])
+    }
+
+    fn u16(&mut self, value: u16) -> Result<(), CodecError> {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    /// Length-prefixed binary data; the length was checked by `string_len`.
+    fn binary(&mut self, data: &[u8]) -> Result<(), CodecError> {
+        self.u16(data.len() as u16)?;
+        self.bytes(data)
+    }
+
+    fn str(&mut self, s: &str) -> Result<(), CodecError> {
+        self.binary(s.as_bytes())
+    }
+
+    fn remaining_length(&mut self, mut len: usize) -> Result<(), CodecError> {
+        if len > REMAINING_LENGTH_MAX {
+            return Err(CodecError::BadRemainingLength);
+        }
+        loop {
+            let mut byte = (len & 0x7F) as u8;
+            len >>= 7;
+            if len > 0 {
+                byte |= 0x80;
+            }
+            self.u8(byte)?;
+            if len == 0 {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Reads the body of a packet whose Remaining Length has already been
+/// satisfied, so running out of bytes means the packet is malformed.
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], CodecError> {
+        if self.buf.len() < n {
+            return Err(CodecError::Malformed(
+                "field runs past the end of the packet",
+            ));
+        }
+        let (head, tail) = self.buf.split_at(n);
+        self.buf = tail;
+        Ok(head)
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        core::mem::take(&mut self.buf)
+    }
+
+    fn u8(&mut self) -> Result<u8, CodecError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, CodecError> {
+        let b = self.take(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn packet_id(&mut self) -> Result<u16, CodecError> {
+        let id = self.u16()?;
+        if id == 0 {
+            return Err(CodecError::Malformed("packet identifier is zero"));
+        }
+        Ok(id)
+    }
+
+    fn binary(&mut self) -> Result<&'a [u8], CodecError> {
+        let len = self.u16()?;
+        self.take(usize::from(len))
+    }
+
+    fn str(&mut self) -> Result<&'a str, CodecError> {
+        let s = core::str::from_utf8(self.binary()?)
+            .map_err(|_| CodecError::Malformed("string is not valid UTF-8"))?;
+        if s.contains('\0') {
+            return Err(CodecError::Malformed("string contains U+0000"));
+        }
+        Ok(s)
+    }
+}
+
+// ── Tests ────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode<'b>(packet: &Packet<'_>, buf: &'b mut [u8]) -> &'b [u8] {
+        let n = packet.encode(buf).unwrap();
+        &buf[..n]
+    }
+
+    fn round_trip(packet: Packet<'_>) {
+        let mut buf = [0u8; 512];
+        let bytes = encode(&packet, &mut buf);
+        let (decoded, used) = Packet::decode(bytes).unwrap();
+        assert_eq!(decoded, packet);
+        assert_eq!(used, bytes.len());
+    }
+
+    fn decode_err(bytes: &[u8]) -> CodecError {
+        Packet::decode(bytes).unwrap_err()
+    }
+
+    #[test]
+    fn remaining_length_boundaries() {
+        let cases: [(usize, &[u8]); 8] = [
+            (0, &[0x00]),
+            (127, &[0x7F]),
+            (128, &[0x80, 0x01]),
+            (16_383, &[0xFF, 0x7F]),
+            (16_384, &[0x80, 0x80, 0x01]),
+            (2_097_151, &[0xFF, 0xFF, 0x7F]),
+            (2_097_152, &[0x80, 0x80, 0x80, 0x01]),
+            (REMAINING_LENGTH_MAX, &[0xFF, 0xFF, 0xFF, 0x7F]),
+        ];
+        for (value, bytes) in cases {
+            let mut buf = [0u8; 4];
+            let n = encode_remaining_length(value, &mut buf).unwrap();
+            assert_eq!(&buf[..n], bytes, "{value}");
+            assert_eq!(decode_remaining_length(bytes), Ok((value, bytes.len())));
+        }
+        assert_eq!(
+            encode_remaining_length(REMAINING_LENGTH_MAX + 1, &mut [0u8; 4]),
+            Err(CodecError::BadRemainingLength)
+        );
+        assert_eq!(
+            decode_remaining_length(&[0xFF, 0xFF, 0xFF, 0xFF, 0x01]),
+            Err(CodecError::BadRemainingLength)
+        );
+        assert_eq!(
+            decode_remaining_length(&[0x80, 0x80]),
+            Err(CodecError::Incomplete)
+        );
+    }
+
+    #[test]
+    fn minimal_connect_bytes() {
+        let mut buf = [0u8; 32];
+        let bytes = encode(&Packet::Connect(Connect::new("abc", 60)), &mut buf);
+        assert_eq!(
+            bytes,
+            [
+                0x10, 0x0F, 0x00, 0x04, b'M', b'Q', b'T', b'T', 0x04, 0x02, 0x00, 0x3C, 0x00, 0x03,
+                b'a', b'b', b'c'
+            ]
+        );
+    }
+
+    #[test]
+    fn connect_with_will_and_credentials() {
+        let connect = Connect {
+            will: Some(Will {
+                topic: "dev/1/status",
+                payload: b"offline",
+                qos: QoS::AtLeastOnce,
+                retain: true,
+            }),
+            username: Some("user"),
+            password: Some(b"secret"),
+            ..Connect::new("dev-1", 30)
+        };
+        let mut buf = [0u8; 64];
+        let bytes = encode(&Packet::Connect(connect), &mut buf);
+        // user | password | will retain | will QoS 1 | will | clean session
+        assert_eq!(bytes[9], 0xEE);
+        round_trip(Packet::Connect(connect));
+        round_trip(Packet::Connect(Connect {
+            clean_session: false,
+            username: Some("user"),
+            ..Connect::new("dev-1", 0)
+        }));
+    }
+
+    #[test]
+    fn connect_validation() {
+        let encode = |c: Connect<'_>| Packet::Connect(c).encode(&mut [0u8; 128]);
+        assert!(encode(Connect::new("", 60)).is_ok());
+        assert!(matches!(
+            encode(Connect {
+                clean_session: false,
+                ..Connect::new("", 60)
+            }),
+            Err(CodecError::Invalid(_))
+        ));
+        assert!(matches!(
+            encode(Connect::new("a-client-id-over-23-bytes", 60)),
+            Err(CodecError::Invalid(_))
+        ));
+        assert!(matches!(
+            encode(Connect {
+                password: Some(b"pw"),
+                ..Connect::new("dev", 60)
+            }),
+            Err(CodecError::Invalid(_))
+        ));
+        assert!(matches!(
+            encode(Connect {
+                will: Some(Will {
+                    topic: "dev/#",
+                    payload: b"",
+                    qos: QoS::AtMostOnce,
+                    retain: false,
+                }),
+                ..Connect::new("dev", 60)
+            }),
+            Err(CodecError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn connect_decode_rejects_bad_headers() {
+        let mut buf = [0u8; 32];
+        let n = Packet::Connect(Connect::new("abc", 60))
+            .encode(&mut buf)
+            .unwrap();
+        let mutate = |i: usize, value: u8| {
+            let mut bytes = buf;
+            bytes[i] = value;
+            decode_err(&bytes[..n])
+        };
+        assert!(matches!(mutate(4, b'X'), CodecError::Malformed(_))); // protocol name
+        assert!(matches!(mutate(8, 3), CodecError::Malformed(_))); // MQTT 3.1
+        assert!(matches!(mutate(9, 0x03), CodecError::Malformed(_))); // reserved flag
+        assert!(matches!(mutate(9, 0x0A), CodecError::Malformed(_))); // will QoS, no will
+        assert!(matches!(mutate(9, 0x42), CodecError::Malformed(_))); // password, no user
+    }
+
+    #[test]
+    fn connack_bytes() {
+        let ack = Packet::ConnAck(ConnAck {
+            session_present: true,
+            code: ConnectReturnCode::Accepted,
+        });
+        let mut buf = [0u8; 4];
+        assert_eq!(encode(&ack, &mut buf), [0x20, 0x02, 0x01, 0x00]);
+        round_trip(ack);
+        assert_eq!(
+            Packet::decode(&[0x20, 0x02, 0x00, 0x05]).unwrap().0,
+            Packet::ConnAck(ConnAck {
+                session_present: false,
+                code: ConnectReturnCode::NotAuthorized,
+            })
+        );
+        assert!(matches!(
+            decode_err(&[0x20, 0x02, 0x00, 0x06]),
+            CodecError::Malformed(_)
+        ));
+        assert!(matches!(
+            decode_err(&[0x20, 0x02, 0x02, 0x00]),
+            CodecError::Malformed(_)
+        ));
+    }
+
+    #[test]
+    fn return_codes_round_trip() {
+        for code in 0..=5 {
+            assert_eq!(ConnectReturnCode::from_code(code).unwrap().code(), code);
+        }
+        assert_eq!(ConnectReturnCode::from_code(6), None);
+        for level in 0..=2 {
+            assert_eq!(QoS::from_level(level).unwrap().level(), level);
+        }
+        assert_eq!(QoS::from_level(3), None);
+    }
+
+    #[test]
+    fn publish_qos0_bytes() {
+        let mut buf = [0u8; 16];
+        let bytes = encode(&Packet::Publish(Publish::new("a/b", b"hello")), &mut buf);
+        assert_eq!(
+            bytes,
+            [0x30, 0x0A, 0x00, 0x03, b'a', b'/', b'b', b'h', b'e', b'l', b'l', b'o']
+        );
+    }
+
+    #[test]
+    fn publish_flags_and_packet_id() {
+        let publish = Publish {
+            qos: QoS::AtLeastOnce,
+            retain: true,
+            dup: true,
+            packet_id: Some(0x1234),
+            ..Publish::new("a", b"x")
+        };
+        let mut buf = [0u8; 16];
+        let bytes = encode(&Packet::Publish(publish), &mut buf);
+        assert_eq!(bytes, [0x3B, 0x06, 0x00, 0x01, b'a', 0x12, 0x34, b'x']);
+        round_trip(Packet::Publish(publish));
+        round_trip(Packet::Publish(Publish {
+            qos: QoS::ExactlyOnce,
+            packet_id: Some(7),
+            ..Publish::new("sensors/temp", b"")
+        }));
+    }
+
+    #[test]
+    fn publish_with_two_byte_remaining_length() {
+        let payload = [0xA5u8; 300];
+        let publish = Packet::Publish(Publish::new("t", &payload));
+        let mut buf = [0u8; 512];
+        let bytes = encode(&publish, &mut buf);
+        assert_eq!(&bytes[..3], [0x30, 0xAF, 0x02]); // 303 = 0x2F + 2 × 128
+        assert_eq!(packet_len(bytes), Ok(306));
+        round_trip(publish);
+    }
+
+    #[test]
+    fn publish_validation() {
+        let encode = |p: Publish<'_>| Packet::Publish(p).encode(&mut [0u8; 64]);
+        assert!(matches!(
+            encode(Publish::new("a/+", b"")),
+            Err(CodecError::Invalid(_))
+        ));
+        assert!(matches!(
+            encode(Publish {
+                qos: QoS::AtLeastOnce,
+                ..Publish::new("a", b"")
+            }),
+            Err(CodecError::Invalid(_))
+        ));
+        assert!(matches!(
+            encode(Publish {
+                qos: QoS::AtLeastOnce,
+                packet_id: Some(0),
+                ..Publish::new("a", b"")
+            }),
+            Err(CodecError::Invalid(_))
+        ));
+        assert!(matches!(
+            encode(Publish {
+                packet_id: Some(1),
+                ..Publish::new("a", b"")
+            }),
+            Err(CodecError::Invalid(_))
+        ));
+        assert!(matches!(
+            encode(Publish {
+                dup: true,
+                ..Publish::new("a", b"")
+            }),
+            Err(CodecError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn publish_decode_rejections() {
+        // QoS 3.
+        assert!(matches!(
+            decode_err(&[0x36, 0x05, 0x00, 0x01, b'a', 0x00, 0x01]),
+            CodecError::Malformed(_)
+        ));
+        // DUP on QoS 0.
+        assert!(matches!(
+            decode_err(&[0x38, 0x03, 0x00, 0x01, b'a']),
+            CodecError::Malformed(_)
+        ));
+        // Wildcard topic.
+        assert!(matches!(
+            decode_err(&[0x30, 0x03, 0x00, 0x01, b'#']),
+            CodecError::Invalid(_)
+        ));
+        // Zero packet identifier.
+        assert!(matches!(
+            decode_err(&[0x32, 0x05, 0x00, 0x01, b'a', 0x00, 0x00]),
+            CodecError::Malformed(_)
+        ));
+        // Invalid UTF-8.
+        assert!(matches!(
+            decode_err(&[0x30, 0x03, 0x00, 0x01, 0xFF]),
+            CodecError::Malformed(_)
+        ));
+        // Topic length past the packet.
+        assert!(matches!(
+            decode_err(&[0x30, 0x03, 0x00, 0x05, b'a']),
+            CodecError::Malformed(_)
+        ));
+    }
+
+    #[test]
+    fn acknowledgements() {
+        let mut buf = [0u8; 4];
+        assert_eq!(
+            encode(&Packet::PubAck(7), &mut buf),
+            [0x40, 0x02, 0x00, 0x07]
+        );
+        assert_eq!(
+            encode(&Packet::PubRec(7), &mut buf),
+            [0x50, 0x02, 0x00, 0x07]
+        );
+        assert_eq!(
+            encode(&Packet::PubRel(7), &mut buf),
+            [0x62, 0x02, 0x00, 0x07]
+        );
+        assert_eq!(
+            encode(&Packet::PubComp(7), &mut buf),
+            [0x70, 0x02, 0x00, 0x07]
+        );
+        assert_eq!(
+            encode(&Packet::UnsubAck(7), &mut buf),
+            [0xB0, 0x02, 0x00, 0x07]
+        );
+        for packet in [
+            Packet::PubAck(1),
+            Packet::PubRec(2),
+            Packet::PubRel(3),
+            Packet::PubComp(4),
+            Packet::UnsubAck(u16::MAX),
+        ] {
+            round_trip(packet);
+        }
+        assert!(matches!(
+            Packet::PubAck(0).encode(&mut buf),
+            Err(CodecError::Invalid(_))
+        ));
+        // PUBREL must carry flags 0b0010.
+        assert!(matches!(
+            decode_err(&[0x60, 0x02, 0x00, 0x07]),
+            CodecError::Malformed(_)
+        ));
+        // Trailing byte inside the Remaining Length.
+        assert!(matches!(
+            decode_err(&[0x40, 0x03, 0x00, 0x07, 0x00]),
+            CodecError::Malformed(_)
+        ));
+    }
+
+    #[test]
+    fn subscribe_bytes_and_filters() {
+        let list = [("a/#", QoS::AtLeastOnce), ("b/+/c", QoS::AtMostOnce)];
+        let subscribe = Packet::Subscribe {
+            packet_id: 10,
+            filters: SubscribeFilters::new(&list),
+        };
+        let mut buf = [0u8; 32];
+        let bytes = encode(&subscribe, &mut buf);
+        assert_eq!(
+            bytes,
+            [
+                0x82, 0x10, 0x00, 0x0A, 0x00, 0x03, b'a', b'/', b'#', 0x01, 0x00, 0x05, b'b', b'/',
+                b'+', b'/', b'c', 0x00
+            ]
+        );
+        let (decoded, _) = Packet::decode(bytes).unwrap();
+        assert_eq!(decoded, subscribe);
+        let Packet::Subscribe { filters, .. } = decoded else {
+            panic!("expected SUBSCRIBE");
+        };
+        assert_eq!(filters.len(), 2);
+        let mut iter = filters.iter();
+        assert_eq!(iter.next(), Some(("a/#", QoS::AtLeastOnce)));
+        assert_eq!(iter.next(), Some(("b/+/c", QoS::AtMostOnce)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn subscribe_validation() {
+        let encode = |filters: &[(&str, QoS)]| {
+            Packet::Subscribe {
+                packet_id: 1,
+                filters: SubscribeFilters::new(filters),
+            }
+            .encode(&mut [0u8; 64])
+        };
+        assert!(matches!(encode(&[]), Err(CodecError::Invalid(_))));
+        assert!(matches!(
+            encode(&[("a/b#", QoS::AtMostOnce)]),
+            Err(CodecError::Invalid(_))
+        ));
+        assert!(matches!(
+            Packet::Subscribe {
+                packet_id: 0,
+                filters: SubscribeFilters::new(&[("a", QoS::AtMostOnce)]),
+            }
+            .encode(&mut [0u8; 64]),
+            Err(CodecError::Invalid(_))
+        ));
+        // Requested QoS 3.
+        assert!(matches!(
+            decode_err(&[0x82, 0x06, 0x00, 0x01, 0x00, 0x01, b'a', 0x03]),
+            CodecError::Malformed(_)
+        ));
+        // No filters.
+        assert!(matches!(
+            decode_err(&[0x82, 0x02, 0x00, 0x01]),
+            CodecError::Malformed(_)
+        ));
+        // Flags must be 0b0010.
+        assert!(matches!(
+            decode_err(&[0x80, 0x06, 0x00, 0x01, 0x00, 0x01, b'a', 0x00]),
+            CodecError::Malformed(_)
+        ));
+    }
+
+    #[test]
+    fn suback_codes() {
+        let codes = [0x00, 0x02, SUBACK_FAILURE];
+        let ack = SubAck {
+            packet_id: 10,
+            return_codes: &codes,
+        };
+        let mut buf = [0u8; 8];
+        assert_eq!(
+            encode(&Packet::SubAck(ack), &mut buf),
+            [0x90, 0x05, 0x00, 0x0A, 0x00, 0x02, 0x80]
+        );
+        round_trip(Packet::SubAck(ack));
+        let mut granted = ack.granted();
+        assert_eq!(granted.next(), Some(Some(QoS::AtMostOnce)));
+        assert_eq!(granted.next(), Some(Some(QoS::ExactlyOnce)));
+        assert_eq!(granted.next(), Some(None));
+        assert!(matches!(
+            decode_err(&[0x90, 0x03, 0x00, 0x0A, 0x03]),
+            CodecError::Malformed(_)
+        ));
+    }
+
+    #[test]
+    fn unsubscribe_round_trip() {
+        let list = ["a/#", "b"];
+        let unsubscribe = Packet::Unsubscribe {
+            packet_id: 3,
+            filters: UnsubscribeFilters::new(&list),
+        };
+        let mut buf = [0u8; 32];
+        let bytes = encode(&unsubscribe, &mut buf);
+        assert_eq!(
+            bytes,
+            [0xA2, 0x0A, 0x00, 0x03, 0x00, 0x03, b'a', b'/', b'#', 0x00, 0x01, b'b']
+        );
+        round_trip(unsubscribe);
+        let Packet::Unsubscribe { filters, .. } = Packet::decode(bytes).unwrap().0 else {
+            panic!("expected UNSUBSCRIBE");
+        };
+        assert!(filters.iter().eq(list));
+        assert!(matches!(
+            Packet::Unsubscribe {
+                packet_id: 3,
+                filters: UnsubscribeFilters::new(&[]),
+            }
+            .encode(&mut buf),
+            Err(CodecError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn header_only_packets() {
+        let mut buf = [0u8; 2];
+        assert_eq!(encode(&Packet::PingReq, &mut buf), [0xC0, 0x00]);
+        assert_eq!(encode(&Packet::PingResp, &mut buf), [0xD0, 0x00]);
+        assert_eq!(encode(&Packet::Disconnect, &mut buf), [0xE0, 0x00]);
+        for packet in [Packet::PingReq, Packet::PingResp, Packet::Disconnect] {
+            round_trip(packet);
+        }
+        assert!(matches!(
+            decode_err(&[0x00, 0x00]),
+            CodecError::Malformed(_)
+        ));
+        assert!(matches!(
+            decode_err(&[0xF0, 0x00]),
+            CodecError::Malformed(_)
+        ));
+        assert!(matches!(
+            decode_err(&[0xC1, 0x00]),
+            CodecError::Malformed(_)
+        ));
+    }
+
+    #[test]
+    fn stream_decoding() {
+        let mut buf = [0u8; 32];
+        let a = Packet::PubAck(5).encode(&mut buf).unwrap();
+        let b = Packet::Publish(Publish::new("t", b"hi"))
+            .encode(&mut buf[a..])
+            .unwrap();
+        let stream = &buf[..a + b];
+
+        assert_eq!(packet_len(&[]), Err(CodecError::Incomplete));
+        assert_eq!(packet_len(&stream[..1]), Err(CodecError::Incomplete));
+        assert_eq!(packet_len(&stream[..2]), Ok(4));
+        assert_eq!(Packet::decode(&stream[..3]), Err(CodecError::Incomplete));
+
+        let (first, used) = Packet::decode(stream).unwrap();
+        assert_eq!((first, used), (Packet::PubAck(5), a));
+        let (second, used) = Packet::decode(&stream[a..]).unwrap();
+        assert_eq!(second, Packet::Publish(Publish::new("t", b"hi")));
+        assert_eq!(used, b);
+    }
+
+    #[test]
+    fn buffer_too_small() {
+        let publish = Packet::Publish(Publish::new("a/b", b"hello"));
+        assert_eq!(
+            publish.encode(&mut [0u8; 11]),
+            Err(CodecError::BufferTooSmall)
+        );
+        assert_eq!(publish.encode(&mut [0u8; 12]), Ok(12));
+    }
+}
@@ -0,0 +1,789 @@
+//! Store-and-forward queue for publishes made while the broker is unreachable.
+//!
+//! [`PublishQueue`] is a bounded FIFO of QoS 1 and QoS 2 publishes packed
+//! into a caller-supplied byte buffer: a `[u8; N]` static, a `Vec<u8>`, or a
+//! borrowed slice.  The platform clients push into it while disconnected and
+//! drain it, oldest first, after the next `Connected` event.  What happens
+//! when a publish does not fit is the queue's [`OverflowPolicy`].
+
+use crate::crc32::{crc32_update, CRC32_INIT};
+
+/// Bytes of per-record framing ahead of the topic and payload.
+pub const QUEUE_RECORD_HEADER_LEN: usize = 6;
+
+/// Length of the header written by [`PublishQueue::snapshot_header`].
+pub const SNAPSHOT_HEADER_LEN: usize = 28;
+
+/// Length of the header written by [`slot_header`].
+pub const SLOT_HEADER_LEN: usize = 8;
+
+/// Header byte marking the skipped tail of the buffer.
+const PAD: u8 = 0xFF;
+
+/// Retain bit of the record flags byte.
+const FLAG_RETAIN: u8 = 0x01;
+
+/// Identifies a publish-queue snapshot (and its format version).
+const SNAPSHOT_MAGIC: [u8; 4] = *b"MQQ1";
+
+/// What [`PublishQueue::push`] does when the new publish does not fit.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Evict the oldest queued publishes until the new one fits.  Suits
+    /// telemetry, where the latest readings matter most.
+    #[default]
+    DropOldest,
+    /// Discard the new publish and keep what is queued.  Suits event logs,
+    /// where the start of an outage matters most.
+    DropNewest,
+    /// Refuse the new publish with [`QueueError::Full`] so the caller can
+    /// decide.
+    Reject,
+}
+
+/// Successful outcome of [`PublishQueue::push`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Enqueued {
+    /// The publish was queued.
+    Stored,
+    /// The publish was queued after evicting this many older ones
+    /// ([`OverflowPolicy::DropOldest`]).
+    Evicted(usize),
+    /// The queue was full and the publish was discarded
+    /// ([`OverflowPolicy::DropNewest`]).
+    Dropped,
+}
+
+/// Errors returned by [`PublishQueue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueError {
+    /// QoS 0 is fire-and-forget and is never queued; QoS above 2 does not
+    /// exist.
+    InvalidQos,
+    /// The topic or payload exceeds 65 535 bytes, or the record is larger
+    /// than the whole buffer.
+    TooLarge,
+    /// The queue is full and the policy is [`OverflowPolicy::Reject`].
+    Full,
+    /// A snapshot failed its magic, size, CRC, or framing checks.
+    Corrupt,
+}
+
+/// A queued publish, borrowed from the queue's buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueuedPublish<'a> {
+    /// Topic to publish to.
+    pub topic: &'a str,
+    /// Message payload.
+    pub payload: &'a [u8],
+    /// QoS level, 1 or 2.
+    pub qos: u8,
+    /// Whether the broker should retain the message.
+    pub retain: bool,
+}
+
+/// Bounded FIFO of publishes packed into `B`'s bytes.
+///
+/// # Layout
+///
+/// Each record is a 6-byte header followed by the topic and payload bytes:
+///
+/// | Offset | Size | Field                                |
+/// |--------|------|--------------------------------------|
+/// | 0      | 1    | QoS (1 or 2)                         |
+/// | 1      | 1    | flags (bit 0: retain)                |
+/// | 2      | 2    | topic length, little-endian          |
+/// | 4      | 2    | payload length, little-endian        |
+///
+/// Records never straddle the end of the buffer.  When the next record does
+/// not fit before the end, the remainder is skipped as padding (marked by a
+/// `0xFF` header byte when there is room for one) and the record starts
+/// again at offset 0, so [`PublishQueue::front`] can always hand out
+/// borrowed `&str`/`&[u8]` views.
+///
+/// # Persistence
+///
+/// The buffer plus a [`SNAPSHOT_HEADER_LEN`]-byte header from
+/// [`PublishQueue::snapshot_header`] is everything needed to rebuild the
+/// queue after a reset.  [`PublishQueue::restore`] checks the header's
+/// CRC-32 (which covers the buffer too) and walks every record before it
+/// trusts the snapshot, so a torn write is detected rather than replayed.
+#[derive(Debug)]
+pub struct PublishQueue<B> {
+    buf: B,
+    policy: OverflowPolicy,
+    /// Offset of the oldest record (or of the padding in front of it).
+    head: usize,
+    /// Offset the next record is written at, before any wrap.
+    tail: usize,
+    /// Bytes occupied by records and padding.
+    used: usize,
+    /// Number of queued records.
+    count: usize,
+    /// Publishes evicted or discarded since construction.
+    dropped: u32,
+}
+
+impl<B: AsRef<[u8]> + AsMut<[u8]>> PublishQueue<B> {
+    /// Creates an empty queue over `buf`.  Its length is the capacity.
+    pub fn new(buf: B, policy: OverflowPolicy) -> Self {
+        Self {
+            buf,
+            policy,
+            head: 0,
+            tail: 0,
+            used: 0,
+            count: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Capacity in bytes, framing included.
+    pub fn capacity(&self) -> usize {
+        self.buf.as_ref().len()
+    }
+
+    /// Number of queued publishes.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns `true` if nothing is queued.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Bytes in use, framing and padding included.
+    pub fn bytes_used(&self) -> usize {
+        self.used
+    }
+
+    /// The overflow policy.
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Publishes evicted or discarded by the overflow policy since the queue
+    /// was created or restored.
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Queues a publish at the back, applying the overflow policy if it does
+    /// not fit.
+    ///
+    /// The topic is stored as given; validate it with
+    /// [`validate_publish_topic`](super::validate_publish_topic) first.
+    pub fn push(
+        &mut self,
+        topic: &str,
+        payload: &[u8],
+        qos: u8,
+        retain: bool,
+    ) -> Result<Enqueued, QueueError> {
+        if !(1..=2).contains(&qos) {
+            return Err(QueueError::InvalidQos);
+        }
+        let (Ok(topic_len), Ok(payload_len)) =
+            (u16::try_from(topic.len()), u16::try_from(payload.len()))
+        else {
+            return Err(QueueError::TooLarge);
+        };
+        let len = record_len(topic.len(), payload.len());
+        if len > self.capacity() {
+            return Err(QueueError::TooLarge);
+        }
+
+        let mut evicted = 0;
+        let at = loop {
+            if let Some(at) = self.slot_for(len) {
+                break at;
+            }
+            match self.policy {
+                OverflowPolicy::DropOldest => {
+                    // Cannot fail: the record fits an empty buffer, so the
+                    // loop ends before the queue runs dry.
+                    self.pop_front();
+                    self.dropped = self.dropped.saturating_add(1);
+                    evicted += 1;
+                }
+                OverflowPolicy::DropNewest => {
+                    self.dropped = self.dropped.saturating_add(1);
+                    return Ok(Enqueued::Dropped);
+                }
+                OverflowPolicy::Reject => return Err(QueueError::Full),
+            }
+        };
+
+        let cap = self.capacity();
+        if self.used == 0 {
+            self.head = 0;
+        } else if at < self.tail {
+            // Wrapping: the rest of the buffer becomes padding.
+            if cap - self.tail >= QUEUE_RECORD_HEADER_LEN {
+                self.buf.as_mut()[self.tail] = PAD;
+            }
+            self.used += cap - self.tail;
+        }
+        let buf = &mut self.buf.as_mut()[at..at + len];
+        buf[0] = qos;
+        buf[1] = if retain { FLAG_RETAIN } else { 0 };
+        buf[2..4].copy_from_slice(&topic_len.to_le_bytes());
+        buf[4..6].copy_from_slice(&payload_len.to_le_bytes());
+        let (topic_buf, payload_buf) = buf[QUEUE_RECORD_HEADER_LEN..].split_at_mut(topic.len());
+        topic_buf.copy_from_slice(topic.as_bytes());
+        payload_buf.copy_from_slice(payload);
+
+        self.tail = at + len;
+        if self.tail == cap {
+            self.tail = 0;
+        }
+        self.used += len;
+        self.count += 1;
+
+        Ok(if evicted == 0 {
+            Enqueued::Stored
+        } else {
+            Enqueued::Evicted(evicted)
+        })
+    }
+
+    /// The oldest queued publish, or `None` if the queue is empty.
+    ///
+    /// Drain by publishing the front and only then calling
+    /// [`pop_front`](Self::pop_front), so a failed publish stays queued.
+    pub fn front(&self) -> Option<QueuedPublish<'_>> {
+        let at = self.front_offset()?;
+        let buf = self.buf.as_ref();
+        let (topic_len, payload_len) = lengths(&buf[at..]);
+        let topic_start = at + QUEUE_RECORD_HEADER_LEN;
+        let payload_start = topic_start + topic_len;
+        Some(QueuedPublish {
+            // Checked on push and on restore; never fails.
+            topic: core::str::from_utf8(&buf[topic_start..payload_start]).unwrap_or_default(),
+            payload: &buf[payload_start..payload_start + payload_len],
+            qos: buf[at],
+            retain: buf[at + 1] & FLAG_RETAIN != 0,
+        })
+    }
+
+    /// Removes the oldest queued publish.  Returns `false` if the queue was
+    /// empty.
+    pub fn pop_front(&mut self) -> bool {
+        let Some(at) = self.front_offset() else {
+            return false;
+        };
+        let cap = self.capacity();
+        if at != self.head {
+            // Skip the padding in front of a wrapped record.
+            self.used -= cap - self.head;
+        }
+        let (topic_len, payload_len) = lengths(&self.buf.as_ref()[at..]);
+        let len = record_len(topic_len, payload_len);
+        self.head = at + len;
+        if self.head == cap {
+            self.head = 0;
+        }
+        self.used -= len;
+        self.count -= 1;
+        if self.count == 0 {
+            self.clear();
+        }
+        true
+    }
+
+    /// Removes every queued publish.
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.tail = 0;
+        self.used = 0;
+        self.count = 0;
+    }
+
+    /// The backing buffer, for persisting alongside
+    /// [`snapshot_header`](Self::snapshot_header).
+    pub fn as_bytes(&self) -> &[u8] {
+        self.buf.as_ref()
+    }
+
+    /// Header that, with [`as_bytes`](Self::as_bytes), lets
+    /// [`restore`](Self::restore) rebuild this queue.
+    ///
+    /// All fields are little-endian `u32`s: magic `MQQ1`, capacity, head,
+    /// tail, bytes used, record count, then a CRC-32 over the preceding 24
+    /// header bytes and the whole buffer.
+    pub fn snapshot_header(&self) -> [u8; SNAPSHOT_HEADER_LEN] {
+        let mut header = [0u8; SNAPSHOT_HEADER_LEN];
+        header[0..4].copy_from_slice(&SNAPSHOT_MAGIC);
+        for (i, field) in [self.capacity(), self.head, self.tail, self.used, self.count]
+            .into_iter()
+            .enumerate()
+        {
+            // Buffers beyond 4 GiB do not exist on the targets this runs on.
+            let field = field as u32;
+            header[4 + i * 4..8 + i * 4].copy_from_slice(&field.to_le_bytes());
+        }
+        let crc = snapshot_crc(&header[..24], self.buf.as_ref());
+        header[24..].copy_from_slice(&crc.to_le_bytes());
+        header
+    }
+
+    /// Rebuilds a queue from a persisted buffer and the header saved with it.
+    ///
+    /// Returns [`QueueError::Corrupt`] unless the header matches `buf`'s
+    /// length and CRC and every record in it is well-formed.  The
+    /// [`dropped`](Self::dropped) counter starts again at zero.
+    pub fn restore(buf: B, header: &[u8], policy: OverflowPolicy) -> Result<Self, QueueError> {
+        let header: &[u8; SNAPSHOT_HEADER_LEN] =
+            header.try_into().map_err(|_| QueueError::Corrupt)?;
+        if header[0..4] != SNAPSHOT_MAGIC {
+            return Err(QueueError::Corrupt);
+        }
+        let field = |i: usize| {
+            u32::from_le_bytes([
+                header[4 + i * 4],
+                header[5 + i * 4],
+                header[6 + i * 4],
+                header[7 + i * 4],
+            ]) as usize
+        };
+        let crc = u32::from_le_bytes([header[24], header[25], header[26], header[27]]);
+        if field(0) != buf.as_ref().len() || crc != snapshot_crc(&header[..24], buf.as_ref()) {
+            return Err(QueueError::Corrupt);
+        }
+        let queue = Self {
+            buf,
+            policy,
+            head: field(1),
+            tail: field(2),
+            used: field(3),
+            count: field(4),
+            dropped: 0,
+        };
+        queue.check_framing()?;
+        Ok(queue)
+    }
+
+    /// Offset of the oldest record, skipping padding.
+    fn front_offset(&self) -> Option<usize> {
+        if self.count == 0 {
+            return None;
+        }
+        let buf = self.buf.as_ref();
+        let at = self.head;
+        Some(if is_padding(buf, at) { 0 } else { at })
+    }
+
+    /// Offset a `len`-byte record would be written at, or `None` if it does
+    /// not fit without evicting anything.
+    fn slot_for(&self, len: usize) -> Option<usize> {
+        let cap = self.capacity();
+        if self.used == 0 {
+            return (len <= cap).then_some(0);
+        }
+        if self.tail > self.head {
+            // Free: tail..cap, then 0..head.
+            if cap - self.tail >= len {
+                Some(self.tail)
+            } else {
+                (self.head >= len).then_some(0)
+            }
+        } else if self.tail < self.head {
+            (self.head - self.tail >= len).then_some(self.tail)
+        } else {
+            // Non-empty with tail == head: full.
+            None
+        }
+    }
+
+    /// Walks every record from `head`, checking it against the restored
+    /// counters.
+    fn check_framing(&self) -> Result<(), QueueError> {
+        let buf = self.buf.as_ref();
+        let cap = buf.len();
+        if self.used > cap || (self.count > 0 && (self.head >= cap || self.tail >= cap)) {
+            return Err(QueueError::Corrupt);
+        }
+        if self.count == 0 {
+            return if self.used == 0 && self.head == 0 && self.tail == 0 {
+                Ok(())
+            } else {
+                Err(QueueError::Corrupt)
+            };
+        }
+
+        let mut at = self.head;
+        let mut consumed = 0;
+        for _ in 0..self.count {
+            if is_padding(buf, at) {
+                if at == 0 {
+                    return Err(QueueError::Corrupt);
+                }
+                consumed += cap - at;
+                at = 0;
+            }
+            let (topic_len, payload_len) = lengths(&buf[at..]);
+            let len = record_len(topic_len, payload_len);
+            let topic_start = at + QUEUE_RECORD_HEADER_LEN;
+            if !(1..=2).contains(&buf[at])
+                || at + len > cap
+                || core::str::from_utf8(&buf[topic_start..topic_start + topic_len]).is_err()
+            {
+                return Err(QueueError::Corrupt);
+            }
+            consumed += len;
+            if consumed > self.used {
+                return Err(QueueError::Corrupt);
+            }
+            at += len;
+            if at == cap {
+                at = 0;
+            }
+        }
+        if consumed == self.used && at == self.tail {
+            Ok(())
+        } else {
+            Err(QueueError::Corrupt)
+        }
+    }
+}
+
+fn record_len(topic_len: usize, payload_len: usize) -> usize {
+    QUEUE_RECORD_HEADER_LEN + topic_len + payload_len
+}
+
+/// Returns `true` if the header at `at` is padding — marked, or too close to
+/// the end of `buf` to hold a header at all.
+fn is_padding(buf: &[u8], at: usize) -> bool {
+    buf.len() - at < QUEUE_RECORD_HEADER_LEN || buf[at] == PAD
+}
+
+/// Topic and payload lengths from the record header at the start of `record`.
+fn lengths(record: &[u8]) -> (usize, usize) {
+    (
+        usize::from(u16::from_le_bytes([record[2], record[3]])),
+        usize::from(u16::from_le_bytes([record[4], record[5]])),
+    )
+}
+
+fn snapshot_crc(header: &[u8], buf: &[u8]) -> u32 {
+    !crc32_update(crc32_update(CRC32_INIT, header), buf)
+}
+
+// ─── A/B snapshot slots ─────────────────────────────────────────────────────
+
+/// Header that tags a snapshot with `seq` for A/B storage.
+///
+/// A store that alternates between two slots writes this, then the
+/// snapshot header, then the buffer, so a power cut mid-write only ever
+/// damages the slot being written and the other one still restores.  The
+/// layout is `seq` as a little-endian `u32`, then a CRC-32 over it and
+/// `snapshot_header` (whose own CRC covers the buffer).
+pub fn slot_header(seq: u32, snapshot_header: &[u8; SNAPSHOT_HEADER_LEN]) -> [u8; SLOT_HEADER_LEN] {
+    let seq = seq.to_le_bytes();
+    let crc = snapshot_crc(&seq, snapshot_header);
+    let mut header = [0u8; SLOT_HEADER_LEN];
+    header[..4].copy_from_slice(&seq);
+    header[4..].copy_from_slice(&crc.to_le_bytes());
+    header
+}
+
+/// Sequence number of a slot written with [`slot_header`], or `None` if
+/// `slot_header` does not match `snapshot_header` — an erased, torn, or
+/// corrupt slot.
+///
+/// Check the snapshot itself with [`PublishQueue::restore`] as well; this
+/// only vouches for the two headers.
+pub fn slot_seq(slot_header: &[u8], snapshot_header: &[u8]) -> Option<u32> {
+    let slot_header: &[u8; SLOT_HEADER_LEN] = slot_header.try_into().ok()?;
+    let seq = [
+        slot_header[0],
+        slot_header[1],
+        slot_header[2],
+        slot_header[3],
+    ];
+    let crc = u32::from_le_bytes([
+        slot_header[4],
+        slot_header[5],
+        slot_header[6],
+        slot_header[7],
+    ]);
+    (crc == snapshot_crc(&seq, snapshot_header)).then(|| u32::from_le_bytes(seq))
+}
+
+/// Index of the slot holding the newest snapshot, given each slot's
+/// sequence number (`None` for an invalid slot), or `None` if neither is
+/// valid.  The next write goes to the other slot.
+///
+/// A tie goes to slot 0.  Sequence numbers saturate at `u32::MAX`, which
+/// takes 136 years at one write a second.
+pub fn newest_slot(seqs: [Option<u32>; 2]) -> Option<usize> {
+    match seqs {
+        [None, None] => None,
+        [Some(_), None] => Some(0),
+        [None, Some(_)] => Some(1),
+        [Some(a), Some(b)] => Some(if a >= b { 0 } else { 1 }),
+    }
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue<const N: usize>(policy: OverflowPolicy) -> PublishQueue<[u8; N]> {
+        PublishQueue::new([0u8; N], policy)
+    }
+
+    fn pop<B: AsRef<[u8]> + AsMut<[u8]>>(q: &mut PublishQueue<B>) -> Option<(u8, bool)> {
+        let front = q.front()?;
+        let out = (front.payload[0], front.retain);
+        assert!(q.pop_front());
+        Some(out)
+    }
+
+    #[test]
+    fn fifo_order_and_fields() {
+        let mut q = queue::<64>(OverflowPolicy::Reject);
+        assert_eq!(q.push("a/b", b"1", 1, false), Ok(Enqueued::Stored));
+        assert_eq!(q.push("c", b"22", 2, true), Ok(Enqueued::Stored));
+        assert_eq!(q.len(), 2);
+        assert_eq!(q.bytes_used(), 6 + 3 + 1 + 6 + 1 + 2);
+        assert_eq!(
+            q.front(),
+            Some(QueuedPublish {
+                topic: "a/b",
+                payload: b"1",
+                qos: 1,
+                retain: false
+            })
+        );
+        assert!(q.pop_front());
+        assert_eq!(
+            q.front(),
+            Some(QueuedPublish {
+                topic: "c",
+                payload: b"22",
+                qos: 2,
+                retain: true
+            })
+        );
+        assert!(q.pop_front());
+        assert!(q.is_empty());
+        assert_eq!(q.bytes_used(), 0);
+        assert!(!q.pop_front());
+        assert_eq!(q.front(), None);
+    }
+
+    #[test]
+    fn rejects_qos0_and_oversize() {
+        let mut q = queue::<16>(OverflowPolicy::DropOldest);
+        assert_eq!(q.push("t", b"x", 0, false), Err(QueueError::InvalidQos));
+        assert_eq!(q.push("t", b"x", 3, false), Err(QueueError::InvalidQos));
+        assert_eq!(q.push("t", &[0; 10], 1, false), Err(QueueError::TooLarge));
+        // Exactly the capacity fits.
+        assert_eq!(q.push("t", &[0; 9], 1, false), Ok(Enqueued::Stored));
+        assert_eq!(q.bytes_used(), 16);
+    }
+
+    #[test]
+    fn drop_oldest_evicts_until_it_fits() {
+        let mut q = queue::<32>(OverflowPolicy::DropOldest);
+        for i in 0..4 {
+            assert_eq!(q.push("t", &[i], 1, false), Ok(Enqueued::Stored));
+        }
+        // 4 × 8 bytes: full.  A 16-byte record evicts two.
+        assert_eq!(q.push("t", &[4; 9], 1, false), Ok(Enqueued::Evicted(2)));
+        assert_eq!(q.dropped(), 2);
+        assert_eq!(pop(&mut q), Some((2, false)));
+        assert_eq!(pop(&mut q), Some((3, false)));
+        assert_eq!(pop(&mut q), Some((4, false)));
+        assert_eq!(pop(&mut q), None);
+    }
+
+    #[test]
+    fn drop_newest_keeps_the_queue() {
+        let mut q = queue::<16>(OverflowPolicy::DropNewest);
+        assert_eq!(q.push("t", &[1], 1, false), Ok(Enqueued::Stored));
+        assert_eq!(q.push("t", &[2], 1, false), Ok(Enqueued::Stored));
+        assert_eq!(q.push("t", &[3], 1, false), Ok(Enqueued::Dropped));
+        assert_eq!(q.dropped(), 1);
+        assert_eq!(pop(&mut q), Some((1, false)));
+        assert_eq!(pop(&mut q), Some((2, false)));
+    }
+
+    #[test]
+    fn reject_returns_full() {
+        let mut q = queue::<16>(OverflowPolicy::Reject);
+        assert_eq!(q.push("t", &[1], 1, false), Ok(Enqueued::Stored));
+        assert_eq!(q.push("t", &[2], 1, false), Ok(Enqueued::Stored));
+        assert_eq!(q.push("t", &[3], 1, false), Err(QueueError::Full));
+        assert_eq!(q.dropped(), 0);
+        assert_eq!(q.len(), 2);
+    }
+
+    #[test]
+    fn wraps_with_marked_padding() {
+        let mut q = queue::<30>(OverflowPolicy::Reject);
+        for i in 0..3 {
+            q.push("t", &[i], 1, false).unwrap(); // 8 bytes each
+        }
+        assert_eq!(pop(&mut q), Some((0, false)));
+        assert_eq!(pop(&mut q), Some((1, false)));
+        // 6 bytes left at the end: too few for an 8-byte record, enough for
+        // a padding marker.  The record goes to offset 0.
+        assert_eq!(q.push("t", &[3], 1, true), Ok(Enqueued::Stored));
+        assert_eq!(q.bytes_used(), 8 + 6 + 8);
+        assert_eq!(pop(&mut q), Some((2, false)));
+        assert_eq!(pop(&mut q), Some((3, true)));
+        assert_eq!(q.bytes_used(), 0);
+    }
+
+    #[test]
+    fn wraps_with_implicit_padding() {
+        let mut q = queue::<28>(OverflowPolicy::DropOldest);
+        for i in 0..3 {
+            q.push("t", &[i], 1, false).unwrap();
+        }
+        // 4 bytes left: shorter than a header, so no marker is written.
+        assert_eq!(q.push("t", &[3], 1, false), Ok(Enqueued::Evicted(1)));
+        assert_eq!(pop(&mut q), Some((1, false)));
+        assert_eq!(pop(&mut q), Some((2, false)));
+        assert_eq!(pop(&mut q), Some((3, false)));
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn sustained_churn_keeps_order() {
+        let mut q = queue::<50>(OverflowPolicy::DropOldest);
+        let mut next_out = 0u8;
+        for i in 0..=255u8 {
+            let payload = [i; 3];
+            let payload = &payload[..usize::from(i % 3) + 1];
+            if let Ok(Enqueued::Evicted(n)) = q.push("x/y", payload, 1 + i % 2, false) {
+                next_out += n as u8;
+            }
+            if i % 4 == 0 {
+                assert_eq!(pop(&mut q).map(|(p, _)| p), Some(next_out));
+                next_out += 1;
+            }
+            assert!(q.bytes_used() <= q.capacity());
+        }
+        while let Some((p, _)) = pop(&mut q) {
+            assert_eq!(p, next_out);
+            next_out = next_out.wrapping_add(1);
+        }
+        assert_eq!(next_out, 0);
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let mut q = queue::<30>(OverflowPolicy::Reject);
+        for i in 0..3 {
+            q.push("t", &[i], 1, false).unwrap();
+        }
+        q.pop_front();
+        q.pop_front();
+        q.push("t", &[3], 2, true).unwrap();
+
+        let header = q.snapshot_header();
+        let bytes: [u8; 30] = q.as_bytes().try_into().unwrap();
+        let mut restored = PublishQueue::restore(bytes, &header, q.policy()).unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored.bytes_used(), q.bytes_used());
+        assert_eq!(pop(&mut restored), Some((2, false)));
+        assert_eq!(restored.front().map(|p| p.qos), Some(2));
+    }
+
+    #[test]
+    fn restore_empty_snapshot() {
+        let q = queue::<16>(OverflowPolicy::DropOldest);
+        let restored =
+            PublishQueue::restore([0u8; 16], &q.snapshot_header(), OverflowPolicy::DropOldest)
+                .unwrap();
+        assert!(restored.is_empty());
+    }
+
+    #[test]
+    fn slot_header_round_trip() {
+        let mut q = queue::<64>(OverflowPolicy::DropOldest);
+        q.push("a/b", b"1", 1, false).unwrap();
+        let snapshot = q.snapshot_header();
+        let slot = slot_header(7, &snapshot);
+        assert_eq!(slot_seq(&slot, &snapshot), Some(7));
+
+        // Any change to either header invalidates the slot.
+        let mut bumped = slot;
+        bumped[0] = 8;
+        assert_eq!(slot_seq(&bumped, &snapshot), None);
+        q.push("a/b", b"2", 1, false).unwrap();
+        assert_eq!(slot_seq(&slot, &q.snapshot_header()), None);
+
+        // Erased flash is never a valid slot.
+        assert_eq!(
+            slot_seq(&[0xFF; SLOT_HEADER_LEN], &[0xFF; SNAPSHOT_HEADER_LEN]),
+            None
+        );
+        assert_eq!(slot_seq(&slot[..4], &snapshot), None);
+    }
+
+    #[test]
+    fn newest_slot_prefers_the_higher_valid_seq() {
+        assert_eq!(newest_slot([None, None]), None);
+        assert_eq!(newest_slot([Some(3), None]), Some(0));
+        assert_eq!(newest_slot([None, Some(3)]), Some(1));
+        assert_eq!(newest_slot([Some(3), Some(4)]), Some(1));
+        assert_eq!(newest_slot([Some(5), Some(4)]), Some(0));
+        assert_eq!(newest_slot([Some(4), Some(4)]), Some(0));
+    }
+
+    #[test]
+    fn restore_rejects_damage() {
+        let mut q = queue::<32>(OverflowPolicy::Reject);
+        q.push("a", b"hello", 1, false).unwrap();
+        let header = q.snapshot_header();
+        let bytes: [u8; 32] = q.as_bytes().try_into().unwrap();
+        let restore = |buf: [u8; 32], header: &[u8]| {
+            PublishQueue::restore(buf, header, OverflowPolicy::Reject).map(|_| ())
+        };
+        assert_eq!(restore(bytes, &header), Ok(()));
+
+        let mut flipped = bytes;
+        flipped[7] ^= 1;
+        assert_eq!(restore(flipped, &header), Err(QueueError::Corrupt));
+
+        let mut bad_magic = header;
+        bad_magic[0] = b'X';
+        assert_eq!(restore(bytes, &bad_magic), Err(QueueError::Corrupt));
+
+        assert_eq!(restore(bytes, &header[..20]), Err(QueueError::Corrupt));
+
+        let short = PublishQueue::restore([0u8; 16], &header, OverflowPolicy::Reject);
+        assert_eq!(short.map(|_| ()), Err(QueueError::Corrupt));
+    }
+
+    #[test]
+    fn restore_rejects_bad_framing_with_valid_crc() {
+        // A CRC only proves the bytes are what was written; the walk catches
+        // a header whose counters disagree with the records.
+        let mut q = queue::<32>(OverflowPolicy::Reject);
+        q.push("a", b"hello", 1, false).unwrap();
+        let mut header = q.snapshot_header();
+        header[20] = 2; // count: 2, but only one record
+        let bytes: [u8; 32] = q.as_bytes().try_into().unwrap();
+        let crc = snapshot_crc(&header[..24], &bytes);
+        header[24..].copy_from_slice(&crc.to_le_bytes());
+        let restored = PublishQueue::restore(bytes, &header, q.policy());
+        assert_eq!(restored.map(|_| ()), Err(QueueError::Corrupt));
+    }
+
+    #[test]
+    fn works_over_a_borrowed_slice() {
+        let mut storage = [0u8; 24];
+        let mut q = PublishQueue::new(&mut storage[..], OverflowPolicy::DropOldest);
+        q.push("t", b"x", 1, false).unwrap();
+        assert_eq!(q.front().map(|p| p.topic), Some("t"));
+        assert_eq!(q.capacity(), 24);
+    }
+}
@@ -568,7 +568,7 @@ fn is_valid_ota_url(url: &str) -> bool {
 /// Validates the `mqtt_uri` form input, splitting it into host and port.
 ///
 /// The accepted shape is `mqtt://{host}:{port}`: the scheme is locked to
-/// `mqtt://` (mirroring `format_broker_url`'s single hard-coded scheme), the
+/// `mqtt://` (the profile has nowhere to keep TLS certificates), the
 /// host is non-empty, and the port is present, parses as a `u16`, and is
 /// non-zero (mirroring `validate_broker_port`'s `!= 0` rule). Every shape,
 /// scheme, host, and port failure maps to a single [`ValidationError::InvalidUrl`]
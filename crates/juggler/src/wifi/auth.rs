@@ -0,0 +1,276 @@
+//! Station authentication methods: WPA2/WPA3 personal and 802.1X enterprise.
+//!
+//! [`WifiAuthMethod`] tells the driver how to authenticate to a network.  The
+//! personal variants take their pre-shared key from the network's `password`
+//! field; the enterprise (EAP) variants carry their own identity material and
+//! ignore `password`.  [`validate_auth_method`] checks the combination before
+//! anything reaches the radio.
+//!
+//! Enterprise support is currently wired through the ESP-IDF tier only; the
+//! bare-metal tier rejects EAP variants at init.
+
+use super::PASSWORD_MAX_LEN;
+
+/// Maximum length of an EAP identity, username, or password (bytes).
+///
+/// ESP-IDF's `esp_eap_client_set_*` functions reject anything longer.
+pub const EAP_FIELD_MAX_LEN: usize = 128;
+
+/// Minimum WPA2 passphrase length (bytes); shorter PSKs cannot be derived.
+pub const WPA2_PASSPHRASE_MIN_LEN: usize = 8;
+
+/// How the station authenticates to a network.
+///
+/// The default, [`WifiAuthMethod::Wpa2Personal`], preserves the behaviour of
+/// configs built before this type existed: the `password` is used as a PSK,
+/// and an empty `password` joins an open network.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum WifiAuthMethod<'a> {
+    /// WPA2-PSK (also accepts WPA/WPA2 mixed-mode APs).
+    #[default]
+    Wpa2Personal,
+    /// WPA3-SAE only.  The AP must support SAE; PSK-only APs are skipped.
+    Wpa3Personal,
+    /// WPA2-PSK/WPA3-SAE transition: SAE where the AP offers it, PSK
+    /// otherwise.  The password must therefore satisfy the WPA2 rules.
+    Wpa2Wpa3Personal,
+    /// WPA2-Enterprise with PEAP (MSCHAPv2 inner method).
+    EapPeap {
+        /// Outer (anonymous) identity sent in the clear, e.g. `"anonymous"`.
+        identity: &'a str,
+        /// Inner username.
+        username: &'a str,
+        /// Inner password.
+        password: &'a str,
+    },
+    /// WPA2-Enterprise with EAP-TLS (certificate authentication).
+    ///
+    /// Certificates and the key are PEM or DER bytes.  The ESP-IDF tier
+    /// copies them, so the slices only need to outlive initialisation.
+    EapTls {
+        /// EAP identity, usually the certificate subject.
+        identity: &'a str,
+        /// Client certificate.
+        client_cert: &'a [u8],
+        /// Client private key (unencrypted).
+        private_key: &'a [u8],
+        /// CA certificate used to verify the RADIUS server, or `None` to skip
+        /// server verification (not recommended outside a lab).
+        ca_cert: Option<&'a [u8]>,
+    },
+}
+
+impl WifiAuthMethod<'_> {
+    /// Returns `true` for the 802.1X (EAP) variants.
+    pub fn is_enterprise(&self) -> bool {
+        matches!(self, Self::EapPeap { .. } | Self::EapTls { .. })
+    }
+
+    /// Short human-readable name, suitable for logs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Wpa2Personal => "WPA2-Personal",
+            Self::Wpa3Personal => "WPA3-Personal",
+            Self::Wpa2Wpa3Personal => "WPA2/WPA3-Personal",
+            Self::EapPeap { .. } => "WPA2-Enterprise (PEAP)",
+            Self::EapTls { .. } => "WPA2-Enterprise (EAP-TLS)",
+        }
+    }
+}
+
+impl core::fmt::Debug for WifiAuthMethod<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Wpa2Personal => f.write_str("Wpa2Personal"),
+            Self::Wpa3Personal => f.write_str("Wpa3Personal"),
+            Self::Wpa2Wpa3Personal => f.write_str("Wpa2Wpa3Personal"),
+            Self::EapPeap {
+                identity, username, ..
+            } => f
+                .debug_struct("EapPeap")
+                .field("identity", identity)
+                .field("username", username)
+                .field("password", &"<redacted>")
+                .finish(),
+            Self::EapTls {
+                identity,
+                client_cert,
+                ca_cert,
+                ..
+            } => f
+                .debug_struct("EapTls")
+                .field("identity", identity)
+                .field("client_cert_len", &client_cert.len())
+                .field("private_key", &"<redacted>")
+                .field("ca_cert_len", &ca_cert.map(<[u8]>::len))
+                .finish(),
+        }
+    }
+}
+
+/// Validates `auth` together with the network's `password`.
+///
+/// - [`WifiAuthMethod::Wpa2Personal`]: `password` must pass
+///   [`validate_password`][super::validate_password] (empty means open).
+/// - [`WifiAuthMethod::Wpa3Personal`]: `password` must be non-empty and at
+///   most [`PASSWORD_MAX_LEN`] bytes; SAE has no minimum length.
+/// - [`WifiAuthMethod::Wpa2Wpa3Personal`]: `password` must be a valid WPA2
+///   passphrase — [`WPA2_PASSPHRASE_MIN_LEN`] to [`PASSWORD_MAX_LEN`] bytes.
+/// - EAP variants: `password` is ignored.  Identity, username, and inner
+///   password must each be 1 to [`EAP_FIELD_MAX_LEN`] bytes; the EAP-TLS
+///   certificate, key, and (if set) CA certificate must be non-empty.
+pub fn validate_auth_method(auth: &WifiAuthMethod<'_>, password: &str) -> Result<(), &'static str> {
+    match *auth {
+        WifiAuthMethod::Wpa2Personal => super::validate_password(password),
+        WifiAuthMethod::Wpa3Personal => {
+            if password.is_empty() {
+                Err("WPA3 password must not be empty")
+            } else {
+                super::validate_password(password)
+            }
+        }
+        WifiAuthMethod::Wpa2Wpa3Personal => {
+            if password.len() < WPA2_PASSPHRASE_MIN_LEN {
+                Err("WPA2/WPA3 password must be at least 8 bytes")
+            } else if password.len() > PASSWORD_MAX_LEN {
+                Err("Password exceeds maximum length of 64 bytes")
+            } else {
+                Ok(())
+            }
+        }
+        WifiAuthMethod::EapPeap {
+            identity,
+            username,
+            password,
+        } => {
+            validate_eap_field(identity, "EAP identity must be 1 to 128 bytes")?;
+            validate_eap_field(username, "EAP username must be 1 to 128 bytes")?;
+            validate_eap_field(password, "EAP password must be 1 to 128 bytes")
+        }
+        WifiAuthMethod::EapTls {
+            identity,
+            client_cert,
+            private_key,
+            ca_cert,
+        } => {
+            validate_eap_field(identity, "EAP identity must be 1 to 128 bytes")?;
+            if client_cert.is_empty() {
+                return Err("EAP-TLS client certificate must not be empty");
+            }
+            if private_key.is_empty() {
+                return Err("EAP-TLS private key must not be empty");
+            }
+            if ca_cert.is_some_and(<[u8]>::is_empty) {
+                return Err("EAP-TLS CA certificate must not be empty when set");
+            }
+            Ok(())
+        }
+    }
+}
+
+fn validate_eap_field(value: &str, error: &'static str) -> Result<(), &'static str> {
+    if value.is_empty() || value.len() > EAP_FIELD_MAX_LEN {
+        Err(error)
+    } else {
+        Ok(())
+    }
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_PSK: &str = "open-sesame";
+    const CERT: &[u8] = b"-----BEGIN CERTIFICATE-----\n...\n-----END CERTIFICATE-----\n";
+
+    fn peap<'a>(identity: &'a str, username: &'a str, password: &'a str) -> WifiAuthMethod<'a> {
+        WifiAuthMethod::EapPeap {
+            identity,
+            username,
+            password,
+        }
+    }
+
+    fn tls(ca_cert: Option<&[u8]>) -> WifiAuthMethod<'_> {
+        WifiAuthMethod::EapTls {
+            identity: "device-01",
+            client_cert: CERT,
+            private_key: b"key",
+            ca_cert,
+        }
+    }
+
+    #[test]
+    fn default_is_wpa2_personal() {
+        assert_eq!(WifiAuthMethod::default(), WifiAuthMethod::Wpa2Personal);
+        assert!(!WifiAuthMethod::default().is_enterprise());
+    }
+
+    #[test]
+    fn wpa2_personal_keeps_legacy_rules() {
+        assert!(validate_auth_method(&WifiAuthMethod::Wpa2Personal, "").is_ok());
+        assert!(validate_auth_method(&WifiAuthMethod::Wpa2Personal, TEST_PSK).is_ok());
+        let long = "p".repeat(PASSWORD_MAX_LEN + 1);
+        assert!(validate_auth_method(&WifiAuthMethod::Wpa2Personal, &long).is_err());
+    }
+
+    #[test]
+    fn wpa3_requires_non_empty_password_of_any_length() {
+        assert!(validate_auth_method(&WifiAuthMethod::Wpa3Personal, "").is_err());
+        assert!(validate_auth_method(&WifiAuthMethod::Wpa3Personal, "abc").is_ok());
+    }
+
+    #[test]
+    fn transition_mode_requires_wpa2_length() {
+        assert_eq!(
+            validate_auth_method(&WifiAuthMethod::Wpa2Wpa3Personal, "short"),
+            Err("WPA2/WPA3 password must be at least 8 bytes")
+        );
+        assert!(validate_auth_method(&WifiAuthMethod::Wpa2Wpa3Personal, TEST_PSK).is_ok());
+    }
+
+    #[test]
+    fn peap_requires_all_fields() {
+        assert!(validate_auth_method(&peap("anonymous", "alice", TEST_PSK), "").is_ok());
+        assert!(validate_auth_method(&peap("", "alice", TEST_PSK), "").is_err());
+        assert!(validate_auth_method(&peap("anonymous", "", TEST_PSK), "").is_err());
+        assert!(validate_auth_method(&peap("anonymous", "alice", ""), "").is_err());
+        let long = "u".repeat(EAP_FIELD_MAX_LEN + 1);
+        assert!(validate_auth_method(&peap("anonymous", &long, TEST_PSK), "").is_err());
+    }
+
+    #[test]
+    fn tls_requires_cert_and_key() {
+        assert!(validate_auth_method(&tls(None), "").is_ok());
+        assert!(validate_auth_method(&tls(Some(CERT)), "").is_ok());
+        assert!(validate_auth_method(&tls(Some(b"")), "").is_err());
+        let no_key = WifiAuthMethod::EapTls {
+            identity: "device-01",
+            client_cert: CERT,
+            private_key: b"",
+            ca_cert: None,
+        };
+        assert_eq!(
+            validate_auth_method(&no_key, ""),
+            Err("EAP-TLS private key must not be empty")
+        );
+    }
+
+    #[test]
+    fn enterprise_ignores_network_password() {
+        let long = "p".repeat(PASSWORD_MAX_LEN + 1);
+        assert!(validate_auth_method(&tls(None), &long).is_ok());
+    }
+
+    #[test]
+    fn debug_redacts_secrets() {
+        let peap_dbg = alloc::format!("{:?}", peap("anonymous", "alice", TEST_PSK));
+        assert!(peap_dbg.contains("alice"));
+        assert!(!peap_dbg.contains(TEST_PSK));
+        let tls_dbg = alloc::format!("{:?}", tls(None));
+        assert!(tls_dbg.contains("<redacted>"));
+        assert!(!tls_dbg.contains("key\""));
+    }
+}
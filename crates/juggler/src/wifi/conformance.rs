@@ -0,0 +1,353 @@
+//! Conformance suites for [`WifiDriver`] and [`AsyncWifiDriver`].
+//!
+//! Available with the `conformance` feature (or `#[cfg(test)]` inside this
+//! crate).  Both suites walk one driver through the documented life cycle —
+//! configure, start, scan, connect, disconnect, reconnect, reconfigure — and
+//! return the first broken rule; see [`crate::conformance`].
+//!
+//! The driver must be able to associate with the network in the
+//! [`WifiContract`]; mocks accept any credentials.  The suites leave the
+//! driver started but disconnected.
+//!
+//! ```rust,ignore
+//! use juggler::wifi::conformance::{run_contract_tests, WifiContract};
+//!
+//! let contract = WifiContract::new("lab-net", "lab-psk").with_settle_polls(100);
+//! run_contract_tests(&mut wifi, &contract, || FreeRtos::delay_ms(100))?;
+//! ```
+
+use super::{AsyncWifiDriver, ScanResults, WifiDriver, SCAN_MAX_RESULTS};
+use crate::conformance::{require, require_ok, ContractViolation};
+
+/// Network and timing parameters for a Wi-Fi conformance run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WifiContract<'a> {
+    /// SSID the driver under test can associate with.
+    pub ssid: &'a str,
+    /// Password for [`ssid`](Self::ssid).
+    pub password: &'a str,
+    /// How often [`WifiDriver::is_connected`] is polled after `connect` /
+    /// `disconnect` before the check fails; the `delay` callback runs
+    /// between polls.  Unused by the async suite, whose `connect` and
+    /// `disconnect` only resolve once the link has changed.
+    pub settle_polls: u32,
+}
+
+impl<'a> WifiContract<'a> {
+    /// Creates a contract for the given network that expects the link state
+    /// to settle on the first poll, which suits mocks.
+    pub const fn new(ssid: &'a str, password: &'a str) -> Self {
+        Self {
+            ssid,
+            password,
+            settle_polls: 1,
+        }
+    }
+
+    /// Sets how often the link state is polled before a check fails.
+    pub const fn with_settle_polls(mut self, polls: u32) -> Self {
+        self.settle_polls = polls;
+        self
+    }
+}
+
+/// Runs the [`WifiDriver`] contract checks against `driver`.
+///
+/// `driver` must be freshly constructed and not yet connected.  `delay` is
+/// called between link-state polls; on hardware it should sleep for the
+/// poll interval (e.g. [`POLL_INTERVAL_MS`](super::POLL_INTERVAL_MS)).
+pub fn run_contract_tests<D, F>(
+    driver: &mut D,
+    contract: &WifiContract<'_>,
+    mut delay: F,
+) -> Result<(), ContractViolation>
+where
+    D: WifiDriver,
+    F: FnMut(),
+{
+    require_ok(
+        driver.configure(contract.ssid, contract.password),
+        "configure",
+        "configure must accept valid credentials before start",
+    )?;
+    require_ok(
+        driver.start(),
+        "start",
+        "start must succeed after configure",
+    )?;
+
+    let results = require_ok(
+        driver.scan(),
+        "scan",
+        "scan must succeed once the driver is started",
+    )?;
+    check_scan_results(&results)?;
+
+    require_ok(
+        driver.connect(),
+        "connect",
+        "connect must succeed for reachable credentials",
+    )?;
+    settle(
+        driver,
+        true,
+        contract,
+        &mut delay,
+        "connect_reaches_connected",
+    )?;
+
+    require_ok(
+        driver.disconnect(),
+        "disconnect",
+        "disconnect must succeed while connected",
+    )?;
+    settle(
+        driver,
+        false,
+        contract,
+        &mut delay,
+        "disconnect_clears_connected",
+    )?;
+
+    require_ok(
+        driver.connect(),
+        "reconnect",
+        "connect must succeed again after disconnect without a restart",
+    )?;
+    settle(
+        driver,
+        true,
+        contract,
+        &mut delay,
+        "reconnect_reaches_connected",
+    )?;
+    require_ok(
+        driver.disconnect(),
+        "disconnect",
+        "disconnect must succeed while connected",
+    )?;
+    settle(
+        driver,
+        false,
+        contract,
+        &mut delay,
+        "disconnect_clears_connected",
+    )?;
+
+    require_ok(
+        driver.configure(contract.ssid, contract.password),
+        "reconfigure",
+        "configure must succeed on a started, disconnected driver",
+    )?;
+    Ok(())
+}
+
+/// Runs the [`AsyncWifiDriver`] contract checks against `driver`.
+///
+/// Same sequence as [`run_contract_tests`], except that `is_connected` must
+/// reflect the new link state as soon as `connect` / `disconnect` resolve.
+pub async fn run_async_contract_tests<D: AsyncWifiDriver>(
+    driver: &mut D,
+    contract: &WifiContract<'_>,
+) -> Result<(), ContractViolation> {
+    require_ok(
+        driver.configure(contract.ssid, contract.password).await,
+        "configure",
+        "configure must accept valid credentials before start",
+    )?;
+    require_ok(
+        driver.start().await,
+        "start",
+        "start must succeed after configure",
+    )?;
+
+    let results = require_ok(
+        driver.scan().await,
+        "scan",
+        "scan must succeed once the driver is started",
+    )?;
+    check_scan_results(&results)?;
+
+    for check in ["connect", "reconnect"] {
+        require_ok(
+            driver.connect().await,
+            check,
+            "connect must succeed for reachable credentials, again after disconnect",
+        )?;
+        let connected = require_ok(
+            driver.is_connected(),
+            "is_connected",
+            "is_connected must not fail on a started driver",
+        )?;
+        require(
+            connected,
+            "connect_reaches_connected",
+            "is_connected must be true once connect has resolved",
+        )?;
+
+        require_ok(
+            driver.disconnect().await,
+            "disconnect",
+            "disconnect must succeed while connected",
+        )?;
+        let connected = require_ok(
+            driver.is_connected(),
+            "is_connected",
+            "is_connected must not fail on a started driver",
+        )?;
+        require(
+            !connected,
+            "disconnect_clears_connected",
+            "is_connected must be false once disconnect has resolved",
+        )?;
+    }
+
+    require_ok(
+        driver.configure(contract.ssid, contract.password).await,
+        "reconfigure",
+        "configure must succeed on a started, disconnected driver",
+    )?;
+    Ok(())
+}
+
+/// Scan results must be bounded and sorted strongest-first.
+fn check_scan_results(results: &ScanResults) -> Result<(), ContractViolation> {
+    require(
+        results.len() <= SCAN_MAX_RESULTS,
+        "scan_bounded",
+        "scan must return at most SCAN_MAX_RESULTS entries",
+    )?;
+    require(
+        results.windows(2).all(|pair| pair[0].rssi >= pair[1].rssi),
+        "scan_sorted",
+        "scan results must be sorted strongest-first",
+    )
+}
+
+/// Polls `is_connected` until it equals `want`, up to
+/// [`WifiContract::settle_polls`] times.
+fn settle<D: WifiDriver>(
+    driver: &D,
+    want: bool,
+    contract: &WifiContract<'_>,
+    delay: &mut impl FnMut(),
+    check: &'static str,
+) -> Result<(), ContractViolation> {
+    for _ in 0..contract.settle_polls.max(1) {
+        let connected = require_ok(
+            driver.is_connected(),
+            "is_connected",
+            "is_connected must not fail on a started driver",
+        )?;
+        if connected == want {
+            return Ok(());
+        }
+        delay();
+    }
+    Err(ContractViolation::new(
+        check,
+        "link state did not settle within settle_polls",
+    ))
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wifi::mock::{block_on, AsyncMockWifiDriver, MockWifiDriver, MockWifiError};
+    use crate::wifi::{insert_scan_result, ApInfo, AuthMode};
+
+    const CONTRACT: WifiContract<'static> = WifiContract::new("test-net", "open-sesame");
+
+    #[test]
+    fn mock_driver_passes() {
+        let mut driver = MockWifiDriver::new();
+        assert_eq!(run_contract_tests(&mut driver, &CONTRACT, || {}), Ok(()));
+        assert_eq!(driver.connect_count, 2);
+        assert!(!driver.connected);
+    }
+
+    #[test]
+    fn async_mock_driver_passes() {
+        let mut driver = AsyncMockWifiDriver::new();
+        driver.yield_polls = 1;
+        assert_eq!(
+            block_on(run_async_contract_tests(&mut driver, &CONTRACT)),
+            Ok(())
+        );
+        assert_eq!(driver.driver.connect_count, 2);
+    }
+
+    #[test]
+    fn failing_connect_is_reported() {
+        let mut driver = MockWifiDriver::new();
+        driver.fail_connect = true;
+        let violation = run_contract_tests(&mut driver, &CONTRACT, || {}).unwrap_err();
+        assert_eq!(violation.check, "connect");
+    }
+
+    #[test]
+    fn failing_scan_is_reported() {
+        let mut driver = AsyncMockWifiDriver::new();
+        driver.driver.fail_scan = true;
+        let violation = block_on(run_async_contract_tests(&mut driver, &CONTRACT)).unwrap_err();
+        assert_eq!(violation.check, "scan");
+    }
+
+    #[test]
+    fn unsorted_scan_results_are_reported() {
+        let mut results = ScanResults::new();
+        insert_scan_result(
+            &mut results,
+            ApInfo::new("a", [0; 6], 1, -40, AuthMode::Open),
+        );
+        insert_scan_result(
+            &mut results,
+            ApInfo::new("b", [1; 6], 1, -70, AuthMode::Open),
+        );
+        assert_eq!(check_scan_results(&results), Ok(()));
+        results.swap(0, 1);
+        assert_eq!(
+            check_scan_results(&results).unwrap_err().check,
+            "scan_sorted"
+        );
+    }
+
+    /// Driver whose link never comes up, to exercise the settle timeout.
+    struct NeverConnects(MockWifiDriver);
+
+    impl WifiDriver for NeverConnects {
+        type Error = MockWifiError;
+
+        fn configure(&mut self, ssid: &str, password: &str) -> Result<(), MockWifiError> {
+            self.0.configure(ssid, password)
+        }
+        fn start(&mut self) -> Result<(), MockWifiError> {
+            self.0.start()
+        }
+        fn connect(&mut self) -> Result<(), MockWifiError> {
+            Ok(())
+        }
+        fn disconnect(&mut self) -> Result<(), MockWifiError> {
+            self.0.disconnect()
+        }
+        fn is_connected(&self) -> Result<bool, MockWifiError> {
+            Ok(false)
+        }
+        fn scan(&mut self) -> Result<ScanResults, MockWifiError> {
+            self.0.scan()
+        }
+    }
+
+    #[test]
+    fn link_that_never_settles_is_reported_after_polls() {
+        let mut driver = NeverConnects(MockWifiDriver::new());
+        let mut delays = 0;
+        let violation =
+            run_contract_tests(&mut driver, &CONTRACT.with_settle_polls(3), || delays += 1)
+                .unwrap_err();
+        assert_eq!(violation.check, "connect_reaches_connected");
+        assert_eq!(delays, 3);
+    }
+}
@@ -0,0 +1,232 @@
+//! Regulatory domains: allowed 2.4 GHz channels and transmit power.
+//!
+//! Which channels a radio may use, and how loud it may be, depends on where
+//! it is deployed.  A [`Country`] captures both for the domains our devices
+//! ship to.  [`WiFiConfig::with_country`][super::WiFiConfig::with_country]
+//! and [`ApConfig::with_country`][super::ApConfig::with_country] carry it to
+//! the platform managers, which apply it to the radio at start;
+//! [`validate_ap_config`][super::validate_ap_config] rejects SoftAP channels
+//! the domain does not allow, and the ESP-NOW `ScanConfig::with_country`
+//! limits a peer scan to legal channels.
+//!
+//! The table is deliberately conservative: it lists the channels every
+//! device class may use actively, and the EIRP limit for ordinary
+//! (non-point-to-point) operation.
+
+use super::TxPowerLevel;
+
+/// All 2.4 GHz channel numbers; [`Country::channels`] returns a sub-slice.
+const CHANNELS_2G4: [u8; 14] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14];
+
+/// Peak antenna gain assumed by [`Country::clamp_tx_power`] (dBi).
+///
+/// A conservative figure for the PCB and ceramic chip antennas on common
+/// ESP32 modules.  Boards with an external antenna should compute their
+/// limit with [`Country::max_tx_power`] and the real gain.
+pub const DEFAULT_ANTENNA_GAIN_DBI: i8 = 2;
+
+/// Regulatory domain the radio operates in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Country {
+    /// Worldwide-safe subset (ESP-IDF country `"01"`): channels 1–11,
+    /// 20 dBm EIRP.  Legal everywhere, at the cost of channels 12 and 13.
+    World,
+    /// European Union / ETSI (`"EU"`): channels 1–13, 20 dBm EIRP.
+    Eu,
+    /// United States / FCC (`"US"`): channels 1–11, 30 dBm EIRP.
+    Us,
+    /// Canada / ISED (`"CA"`): channels 1–11, 30 dBm EIRP.
+    Ca,
+    /// Japan / ARIB (`"JP"`): channels 1–14, 20 dBm EIRP.  Channel 14 is
+    /// 802.11b only.
+    Jp,
+    /// China / SRRC (`"CN"`): channels 1–13, 20 dBm EIRP.  This is the
+    /// `esp-radio` default.
+    Cn,
+    /// Australia / ACMA (`"AU"`): channels 1–13, 36 dBm EIRP.
+    Au,
+}
+
+impl Country {
+    /// Every supported domain, for lookups and UI lists.
+    pub const ALL: [Country; 7] = [
+        Self::World,
+        Self::Eu,
+        Self::Us,
+        Self::Ca,
+        Self::Jp,
+        Self::Cn,
+        Self::Au,
+    ];
+
+    /// Two-character country code as passed to the Wi-Fi driver.
+    pub const fn code(self) -> [u8; 2] {
+        match self {
+            Self::World => *b"01",
+            Self::Eu => *b"EU",
+            Self::Us => *b"US",
+            Self::Ca => *b"CA",
+            Self::Jp => *b"JP",
+            Self::Cn => *b"CN",
+            Self::Au => *b"AU",
+        }
+    }
+
+    /// Looks up a domain by its two-character code, case-insensitively.
+    ///
+    /// Returns `None` for codes not in the table.
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.as_bytes();
+        Self::ALL
+            .into_iter()
+            .find(|country| code.eq_ignore_ascii_case(&country.code()))
+    }
+
+    /// Lowest allowed channel.
+    pub const fn first_channel(self) -> u8 {
+        1
+    }
+
+    /// Number of consecutive allowed channels from
+    /// [`first_channel`](Self::first_channel).
+    pub const fn channel_count(self) -> u8 {
+        match self {
+            Self::World | Self::Us | Self::Ca => 11,
+            Self::Eu | Self::Cn | Self::Au => 13,
+            Self::Jp => 14,
+        }
+    }
+
+    /// Highest allowed channel.
+    pub const fn last_channel(self) -> u8 {
+        self.first_channel() + self.channel_count() - 1
+    }
+
+    /// Returns `true` if `channel` may be used in this domain.
+    pub const fn allows_channel(self, channel: u8) -> bool {
+        channel >= self.first_channel() && channel <= self.last_channel()
+    }
+
+    /// The allowed channels in ascending order, e.g. for an ESP-NOW scan.
+    pub fn channels(self) -> &'static [u8] {
+        let first = usize::from(self.first_channel()) - 1;
+        &CHANNELS_2G4[first..first + usize::from(self.channel_count())]
+    }
+
+    /// Maximum equivalent isotropically radiated power (dBm).
+    pub const fn max_eirp_dbm(self) -> i8 {
+        match self {
+            Self::World | Self::Eu | Self::Jp | Self::Cn => 20,
+            Self::Us | Self::Ca => 30,
+            Self::Au => 36,
+        }
+    }
+
+    /// Highest [`TxPowerLevel`] that stays within
+    /// [`max_eirp_dbm`](Self::max_eirp_dbm) with an antenna of
+    /// `antenna_gain_dbi`.
+    ///
+    /// Falls back to [`TxPowerLevel::Lowest`] if even that exceeds the
+    /// limit.
+    pub fn max_tx_power(self, antenna_gain_dbi: i8) -> TxPowerLevel {
+        // Compare in quarter-dBm, the unit `to_quarter_dbm` already uses.
+        let limit = (i16::from(self.max_eirp_dbm()) - i16::from(antenna_gain_dbi)) * 4;
+        [
+            TxPowerLevel::Max,
+            TxPowerLevel::High,
+            TxPowerLevel::Medium,
+            TxPowerLevel::Low,
+        ]
+        .into_iter()
+        .find(|level| i16::from(level.to_quarter_dbm()) <= limit)
+        .unwrap_or(TxPowerLevel::Lowest)
+    }
+
+    /// Lowers `level` to [`max_tx_power`](Self::max_tx_power) for
+    /// [`DEFAULT_ANTENNA_GAIN_DBI`] if it would exceed the domain limit.
+    pub fn clamp_tx_power(self, level: TxPowerLevel) -> TxPowerLevel {
+        let max = self.max_tx_power(DEFAULT_ANTENNA_GAIN_DBI);
+        if level.to_quarter_dbm() > max.to_quarter_dbm() {
+            max
+        } else {
+            level
+        }
+    }
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_ranges_match_domains() {
+        assert_eq!(Country::Us.channels(), &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
+        assert_eq!(Country::Eu.last_channel(), 13);
+        assert_eq!(Country::Jp.channels().last(), Some(&14));
+        assert_eq!(Country::World.channel_count(), 11);
+    }
+
+    #[test]
+    fn channels_agree_with_allows_channel() {
+        for country in Country::ALL {
+            for channel in 0..=15 {
+                assert_eq!(
+                    country.allows_channel(channel),
+                    country.channels().contains(&channel),
+                    "{:?} channel {}",
+                    country,
+                    channel
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn us_rejects_channels_12_and_13() {
+        assert!(Country::Us.allows_channel(11));
+        assert!(!Country::Us.allows_channel(12));
+        assert!(!Country::Us.allows_channel(13));
+        assert!(Country::Eu.allows_channel(13));
+    }
+
+    #[test]
+    fn from_code_round_trips_and_ignores_case() {
+        for country in Country::ALL {
+            let code = country.code();
+            let code = core::str::from_utf8(&code).unwrap();
+            assert_eq!(Country::from_code(code), Some(country));
+        }
+        assert_eq!(Country::from_code("us"), Some(Country::Us));
+        assert_eq!(Country::from_code("DE"), None);
+        assert_eq!(Country::from_code("USA"), None);
+        assert_eq!(Country::from_code(""), None);
+    }
+
+    #[test]
+    fn max_tx_power_accounts_for_antenna_gain() {
+        // 19.5 dBm conducted fits a 20 dBm EIRP limit only at 0 dBi.
+        assert_eq!(Country::Eu.max_tx_power(0), TxPowerLevel::Max);
+        assert_eq!(Country::Eu.max_tx_power(2), TxPowerLevel::High);
+        assert_eq!(Country::Eu.max_tx_power(6), TxPowerLevel::Medium);
+        assert_eq!(Country::Us.max_tx_power(6), TxPowerLevel::Max);
+        assert_eq!(Country::Eu.max_tx_power(30), TxPowerLevel::Lowest);
+    }
+
+    #[test]
+    fn clamp_tx_power_only_lowers() {
+        assert_eq!(
+            Country::Eu.clamp_tx_power(TxPowerLevel::Max),
+            TxPowerLevel::High
+        );
+        assert_eq!(
+            Country::Eu.clamp_tx_power(TxPowerLevel::Low),
+            TxPowerLevel::Low
+        );
+        assert_eq!(
+            Country::Us.clamp_tx_power(TxPowerLevel::Max),
+            TxPowerLevel::Max
+        );
+    }
+}
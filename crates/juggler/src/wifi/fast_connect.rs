@@ -0,0 +1,630 @@
+//! Scan-free reconnects across deep sleep.
+//!
+//! A battery sensor that wakes, publishes, and sleeps again spends most of
+//! its radio-on time looking for an access point it already knew about a
+//! minute ago.  A [`WifiFastConnectCache`] remembers where that AP was —
+//! BSSID and channel — plus, optionally, the WPA2 pairwise master key
+//! derived from the passphrase and the last DHCP lease.  It is small,
+//! `#[repr(C)]`, `Copy`, and CRC-protected, so it can live in RTC memory
+//! (e.g. an `#[link_section = ".rtc.data"]` / `#[ram(rtc_fast, persistent)]`
+//! `static`) and be trusted only when the checksum still matches.
+//!
+//! The platform managers take the cache, connect to the cached BSSID on the
+//! cached channel without scanning, fall back to a full connect when that AP
+//! is gone, and hand back a refreshed cache to store before the next sleep.
+//!
+//! Reusing the IP lease is left to the caller: pass
+//! [`WifiFastConnectCache::ip_lease`] to
+//! [`WiFiConfig::with_ip_config`](super::WiFiConfig::with_ip_config) to skip
+//! DHCP as well, accepting that the lease may have expired on the server.
+
+use core::net::Ipv4Addr;
+
+use sha1::{Digest, Sha1};
+
+use super::{IpConfig, WifiAuthMethod, WifiNetworkList, PASSWORD_MAX_LEN, WPA2_PASSPHRASE_MIN_LEN};
+use crate::crc32::{crc32_update, CRC32_INIT};
+
+/// How long a manager waits for the cached AP before falling back to a full
+/// connect (seconds).
+///
+/// Association with a known BSSID on a known channel normally completes in
+/// well under a second; anything slower means the AP has moved or is gone.
+pub const FAST_CONNECT_TIMEOUT_SECS: u64 = 5;
+
+/// Length of a derived pairwise master key in bytes.
+pub const PMK_LEN: usize = 32;
+
+/// PBKDF2 iteration count fixed by IEEE 802.11i for the WPA2 PSK.
+const PBKDF2_ITERATIONS: u32 = 4096;
+
+const FLAG_PMK: u8 = 1 << 0;
+const FLAG_LEASE: u8 = 1 << 1;
+const FLAG_LEASE_DNS: u8 = 1 << 2;
+
+/// Cached association details for a scan-free reconnect.
+///
+/// Build one with [`WifiFastConnectCache::new`] or take the one a manager
+/// returns after connecting; on cold boot start from
+/// [`WifiFastConnectCache::empty`].  Every builder re-seals the CRC, and
+/// every getter that could hand out garbage from corrupt memory checks it.
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct WifiFastConnectCache {
+    /// CRC-32 of the SSID and passphrase the entry was recorded for, so a
+    /// cache for another network (or an old password) is ignored.
+    network: u32,
+    bssid: [u8; 6],
+    channel: u8,
+    /// `1` for a sealed entry.  `u8` rather than `bool` so that reading
+    /// uninitialised RTC memory is never undefined behaviour.
+    valid: u8,
+    flags: u8,
+    _pad: [u8; 3],
+    pmk: [u8; PMK_LEN],
+    ip: [u8; 4],
+    netmask: [u8; 4],
+    gateway: [u8; 4],
+    dns: [u8; 4],
+    crc32: u32,
+}
+
+// Keep the RTC footprint fixed: 68 bytes, no implicit padding.
+const _: () = assert!(core::mem::size_of::<WifiFastConnectCache>() == 68);
+
+impl WifiFastConnectCache {
+    /// An invalid, zeroed entry for cold boot.
+    ///
+    /// Use this to initialise the RTC `static` rather than relying on the
+    /// memory's reset state, which is undefined.
+    pub const fn empty() -> Self {
+        Self {
+            network: 0,
+            bssid: [0; 6],
+            channel: 0,
+            valid: 0,
+            flags: 0,
+            _pad: [0; 3],
+            pmk: [0; PMK_LEN],
+            ip: [0; 4],
+            netmask: [0; 4],
+            gateway: [0; 4],
+            dns: [0; 4],
+            crc32: 0,
+        }
+    }
+
+    /// Records that `ssid` (secured with `password`, empty for open and
+    /// 802.1X networks) was reachable at `bssid` on `channel`.
+    pub fn new(ssid: &str, password: &str, bssid: [u8; 6], channel: u8) -> Self {
+        Self {
+            network: network_key(ssid, password),
+            bssid,
+            channel,
+            valid: 1,
+            ..Self::empty()
+        }
+        .sealed()
+    }
+
+    /// Stores a pairwise master key, e.g. from [`derive_pmk`].
+    ///
+    /// The managers hand it to the driver instead of the passphrase, which
+    /// saves the 4096-round PBKDF2 on every wake.  Only meaningful for
+    /// WPA2-Personal; see [`pmk_applicable`].
+    pub fn with_pmk(mut self, pmk: [u8; PMK_LEN]) -> Self {
+        self.pmk = pmk;
+        self.flags |= FLAG_PMK;
+        self.sealed()
+    }
+
+    /// Stores the address the station was given; [`IpConfig::Dhcp`] clears
+    /// a stored lease.
+    pub fn with_ip_lease(mut self, lease: IpConfig) -> Self {
+        self.flags &= !(FLAG_LEASE | FLAG_LEASE_DNS);
+        self.ip = [0; 4];
+        self.netmask = [0; 4];
+        self.gateway = [0; 4];
+        self.dns = [0; 4];
+        if let IpConfig::Static {
+            ip,
+            netmask,
+            gateway,
+            dns,
+        } = lease
+        {
+            self.flags |= FLAG_LEASE;
+            self.ip = ip.octets();
+            self.netmask = netmask.octets();
+            self.gateway = gateway.octets();
+            if let Some(dns) = dns {
+                self.flags |= FLAG_LEASE_DNS;
+                self.dns = dns.octets();
+            }
+        }
+        self.sealed()
+    }
+
+    /// Builds the entry to store after connecting to `ssid` at `bssid` on
+    /// `channel`.
+    ///
+    /// With `use_pmk` (normally [`pmk_applicable`] for the network) the
+    /// PMK is carried over from `self` when `self` was recorded for the same
+    /// credentials, and derived from the passphrase otherwise.  The IP lease
+    /// is not carried over; add the new one with
+    /// [`with_ip_lease`](Self::with_ip_lease).
+    pub fn refreshed(
+        &self,
+        ssid: &str,
+        password: &str,
+        use_pmk: bool,
+        bssid: [u8; 6],
+        channel: u8,
+    ) -> Self {
+        let cache = self.refreshed_reusing_pmk(ssid, password, use_pmk, bssid, channel);
+        if use_pmk {
+            cache.with_derived_pmk(ssid, password)
+        } else {
+            cache
+        }
+    }
+
+    /// [`refreshed`](Self::refreshed) without the PBKDF2: the PMK is carried
+    /// over when `self` was recorded for the same credentials and left out
+    /// otherwise.
+    ///
+    /// For async executors, where deriving would stall every other task;
+    /// add a missing PMK later, off the connect path, with
+    /// [`with_derived_pmk`](Self::with_derived_pmk).
+    pub fn refreshed_reusing_pmk(
+        &self,
+        ssid: &str,
+        password: &str,
+        use_pmk: bool,
+        bssid: [u8; 6],
+        channel: u8,
+    ) -> Self {
+        let cache = Self::new(ssid, password, bssid, channel);
+        match self.pmk() {
+            Some(pmk) if use_pmk && self.matches(ssid, password) => cache.with_pmk(pmk),
+            _ => cache,
+        }
+    }
+
+    /// Derives and stores the PMK for `ssid` and `password` unless the entry
+    /// already has one.
+    ///
+    /// This runs [`derive_pmk`] (PBKDF2, tens of milliseconds on target).
+    /// An entry that is invalid or was recorded for other credentials is
+    /// returned unchanged.  Only meaningful for WPA2-Personal; see
+    /// [`pmk_applicable`].
+    pub fn with_derived_pmk(self, ssid: &str, password: &str) -> Self {
+        if self.pmk().is_some() || !self.matches(ssid, password) {
+            return self;
+        }
+        self.with_pmk(derive_pmk(ssid, password))
+    }
+
+    /// Marks the entry invalid, e.g. after the credentials were reprovisioned.
+    pub fn invalidate(&mut self) {
+        *self = Self::empty();
+    }
+
+    /// Returns `true` if the entry is sealed, its CRC matches, and the
+    /// channel is a 2.4 GHz channel.
+    pub fn is_valid(&self) -> bool {
+        self.valid == 1 && (1..=14).contains(&self.channel) && self.crc32 == self.checksum()
+    }
+
+    /// Returns `true` if the entry is valid and was recorded for `ssid` and
+    /// `password`.
+    pub fn matches(&self, ssid: &str, password: &str) -> bool {
+        self.is_valid() && self.network == network_key(ssid, password)
+    }
+
+    /// Index of the network in `networks` this entry was recorded for, or
+    /// `None` if the entry is invalid or belongs to none of them.
+    ///
+    /// The password of 802.1X entries is ignored, matching what the managers
+    /// record for them.
+    pub fn network_index(&self, networks: &WifiNetworkList<'_>) -> Option<usize> {
+        networks.iter().position(|network| {
+            let password = if network.auth.is_enterprise() {
+                ""
+            } else {
+                network.password
+            };
+            self.matches(network.ssid, password)
+        })
+    }
+
+    /// BSSID of the cached AP.  Check [`is_valid`](Self::is_valid) first.
+    pub fn bssid(&self) -> [u8; 6] {
+        self.bssid
+    }
+
+    /// Channel of the cached AP.  Check [`is_valid`](Self::is_valid) first.
+    pub fn channel(&self) -> u8 {
+        self.channel
+    }
+
+    /// The stored PMK, if the entry is valid and has one.
+    pub fn pmk(&self) -> Option<[u8; PMK_LEN]> {
+        (self.is_valid() && self.flags & FLAG_PMK != 0).then_some(self.pmk)
+    }
+
+    /// The stored PMK as the 64-hex-digit PSK string both Wi-Fi drivers
+    /// accept in place of a passphrase.
+    pub fn psk_hex(&self) -> Option<heapless::String<PASSWORD_MAX_LEN>> {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+        let pmk = self.pmk()?;
+        let mut psk = heapless::String::new();
+        for byte in pmk {
+            // 32 bytes → 64 digits, exactly the capacity.
+            let _ = psk.push(char::from(HEX[usize::from(byte >> 4)]));
+            let _ = psk.push(char::from(HEX[usize::from(byte & 0x0f)]));
+        }
+        Some(psk)
+    }
+
+    /// The stored lease as an [`IpConfig::Static`], if the entry is valid
+    /// and has one.
+    pub fn ip_lease(&self) -> Option<IpConfig> {
+        if !self.is_valid() || self.flags & FLAG_LEASE == 0 {
+            return None;
+        }
+        Some(IpConfig::Static {
+            ip: Ipv4Addr::from(self.ip),
+            netmask: Ipv4Addr::from(self.netmask),
+            gateway: Ipv4Addr::from(self.gateway),
+            dns: (self.flags & FLAG_LEASE_DNS != 0).then(|| Ipv4Addr::from(self.dns)),
+        })
+    }
+
+    fn sealed(mut self) -> Self {
+        self.crc32 = self.checksum();
+        self
+    }
+
+    /// CRC-32 over every field but `crc32`, in declaration order.
+    fn checksum(&self) -> u32 {
+        let mut crc = CRC32_INIT;
+        for part in [
+            &self.network.to_le_bytes()[..],
+            &self.bssid,
+            &[self.channel, self.valid, self.flags],
+            &self._pad,
+            &self.pmk,
+            &self.ip,
+            &self.netmask,
+            &self.gateway,
+            &self.dns,
+        ] {
+            crc = crc32_update(crc, part);
+        }
+        !crc
+    }
+}
+
+impl Default for WifiFastConnectCache {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl core::fmt::Debug for WifiFastConnectCache {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("WifiFastConnectCache")
+            .field("valid", &self.is_valid())
+            .field("bssid", &self.bssid)
+            .field("channel", &self.channel)
+            .field("pmk", &(self.flags & FLAG_PMK != 0).then_some("<redacted>"))
+            .field("ip_lease", &self.ip_lease())
+            .finish()
+    }
+}
+
+/// Returns `true` if a PMK can stand in for `password` on a network using
+/// `auth`.
+///
+/// Only WPA2-Personal derives its key from the passphrase alone; SAE
+/// (WPA3 and transition mode) and 802.1X do not.  A 64-character password
+/// already is a hex PSK, and an empty one means an open network.
+pub fn pmk_applicable(auth: &WifiAuthMethod<'_>, password: &str) -> bool {
+    matches!(auth, WifiAuthMethod::Wpa2Personal)
+        && (WPA2_PASSPHRASE_MIN_LEN..PASSWORD_MAX_LEN).contains(&password.len())
+}
+
+/// Derives the WPA2 pairwise master key for `passphrase` on `ssid`
+/// (PBKDF2-HMAC-SHA1, 4096 iterations, 256-bit output, per IEEE 802.11i).
+///
+/// This is the work the supplicant does on every connect; it takes tens of
+/// milliseconds on an ESP32-C3, so do it once and keep the result in a
+/// [`WifiFastConnectCache`].
+pub fn derive_pmk(ssid: &str, passphrase: &str) -> [u8; PMK_LEN] {
+    // HMAC keys longer than the block size are hashed first; passphrases
+    // are at most 63 bytes, but stay correct for any input.
+    let mut key = [0u8; 64];
+    if passphrase.len() > key.len() {
+        key[..20].copy_from_slice(&Sha1::digest(passphrase.as_bytes()));
+    } else {
+        key[..passphrase.len()].copy_from_slice(passphrase.as_bytes());
+    }
+    // The keyed inner and outer states are the same for all 8192 HMAC
+    // rounds; hash the padded key once and clone.
+    let mut inner = Sha1::new();
+    inner.update(key.map(|b| b ^ 0x36));
+    let mut outer = Sha1::new();
+    outer.update(key.map(|b| b ^ 0x5c));
+
+    let mut pmk = [0u8; PMK_LEN];
+    for (index, chunk) in pmk.chunks_mut(20).enumerate() {
+        let block = pbkdf2_block(&inner, &outer, ssid.as_bytes(), index as u32 + 1);
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+    pmk
+}
+
+/// One PBKDF2 output block: `U1 ^ U2 ^ … ^ U4096`.
+fn pbkdf2_block(inner: &Sha1, outer: &Sha1, salt: &[u8], index: u32) -> [u8; 20] {
+    let mut u = hmac(inner, outer, &[salt, &index.to_be_bytes()]);
+    let mut block = u;
+    for _ in 1..PBKDF2_ITERATIONS {
+        u = hmac(inner, outer, &[&u]);
+        for (acc, byte) in block.iter_mut().zip(u) {
+            *acc ^= byte;
+        }
+    }
+    block
+}
+
+fn hmac(inner: &Sha1, outer: &Sha1, message: &[&[u8]]) -> [u8; 20] {
+    let mut hasher = inner.clone();
+    for part in message {
+        hasher.update(part);
+    }
+    let inner_hash = hasher.finalize();
+    let mut hasher = outer.clone();
+    hasher.update(inner_hash);
+    hasher.finalize().into()
+}
+
+/// Identifies a set of credentials without storing them.
+fn network_key(ssid: &str, password: &str) -> u32 {
+    let crc = crc32_update(CRC32_INIT, ssid.as_bytes());
+    let crc = crc32_update(crc, &[0]);
+    !crc32_update(crc, password.as_bytes())
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wifi::WifiNetwork;
+
+    const TEST_SSID: &str = "test-net";
+    const TEST_PSK: &str = "open-sesame";
+    const BSSID: [u8; 6] = [0x24, 0x0a, 0xc4, 0x01, 0x02, 0x03];
+
+    fn hex(bytes: &[u8]) -> heapless::String<128> {
+        let mut out = heapless::String::new();
+        for b in bytes {
+            core::fmt::Write::write_fmt(&mut out, format_args!("{:02x}", b)).unwrap();
+        }
+        out
+    }
+
+    #[test]
+    fn derive_pmk_matches_ieee_802_11i_vectors() {
+        assert_eq!(
+            hex(&derive_pmk("IEEE", "password")),
+            "f42c6fc52df0ebef9ebb4b90b38a5f902e83fe1b135a70e23aed762e9710a12e"
+        );
+        assert_eq!(
+            hex(&derive_pmk("ThisIsASSID", "ThisIsAPassword")),
+            "0dc0d6eb90555ed6419756b9a15ec3e3209b63df707dd508d14581f8982721af"
+        );
+    }
+
+    #[test]
+    fn empty_is_invalid() {
+        let cache = WifiFastConnectCache::empty();
+        assert!(!cache.is_valid());
+        assert_eq!(cache.pmk(), None);
+        assert_eq!(cache.ip_lease(), None);
+        assert_eq!(WifiFastConnectCache::default(), cache);
+    }
+
+    #[test]
+    fn new_entry_is_valid_and_keyed_to_credentials() {
+        let cache = WifiFastConnectCache::new(TEST_SSID, TEST_PSK, BSSID, 6);
+        assert!(cache.is_valid());
+        assert_eq!(cache.bssid(), BSSID);
+        assert_eq!(cache.channel(), 6);
+        assert!(cache.matches(TEST_SSID, TEST_PSK));
+        assert!(!cache.matches(TEST_SSID, "new-password"));
+        assert!(!cache.matches("other-net", TEST_PSK));
+    }
+
+    #[test]
+    fn any_corrupt_byte_invalidates() {
+        let cache = WifiFastConnectCache::new(TEST_SSID, TEST_PSK, BSSID, 6)
+            .with_pmk([7; PMK_LEN])
+            .with_ip_lease(IpConfig::Dhcp);
+
+        let mut corrupt = cache;
+        corrupt.bssid[5] ^= 1;
+        assert!(!corrupt.is_valid());
+
+        let mut corrupt = cache;
+        corrupt.pmk[31] ^= 0x80;
+        assert!(!corrupt.is_valid());
+        assert_eq!(corrupt.pmk(), None);
+
+        let mut corrupt = cache;
+        corrupt.crc32 ^= 1;
+        assert!(!corrupt.is_valid());
+
+        // A sealed entry with an impossible channel is still rejected.
+        assert!(!WifiFastConnectCache::new(TEST_SSID, TEST_PSK, BSSID, 0).is_valid());
+        assert!(!WifiFastConnectCache::new(TEST_SSID, TEST_PSK, BSSID, 15).is_valid());
+    }
+
+    #[test]
+    fn ip_lease_round_trips() {
+        let lease = IpConfig::Static {
+            ip: Ipv4Addr::new(192, 168, 1, 42),
+            netmask: Ipv4Addr::new(255, 255, 255, 0),
+            gateway: Ipv4Addr::new(192, 168, 1, 1),
+            dns: None,
+        };
+        let cache = WifiFastConnectCache::new(TEST_SSID, TEST_PSK, BSSID, 11).with_ip_lease(lease);
+        assert!(cache.is_valid());
+        assert_eq!(cache.ip_lease(), Some(lease));
+
+        let with_dns = IpConfig::Static {
+            ip: Ipv4Addr::new(192, 168, 1, 42),
+            netmask: Ipv4Addr::new(255, 255, 255, 0),
+            gateway: Ipv4Addr::new(192, 168, 1, 1),
+            dns: Some(Ipv4Addr::new(1, 1, 1, 1)),
+        };
+        assert_eq!(cache.with_ip_lease(with_dns).ip_lease(), Some(with_dns));
+        assert_eq!(cache.with_ip_lease(IpConfig::Dhcp).ip_lease(), None);
+    }
+
+    #[test]
+    fn psk_hex_is_64_lowercase_digits() {
+        let cache = WifiFastConnectCache::new(TEST_SSID, TEST_PSK, BSSID, 1);
+        assert_eq!(cache.psk_hex(), None);
+        let mut pmk = [0u8; PMK_LEN];
+        pmk[0] = 0xAB;
+        pmk[31] = 0x0F;
+        let psk = cache.with_pmk(pmk).psk_hex().unwrap();
+        assert_eq!(psk.len(), 64);
+        assert!(psk.starts_with("ab00"));
+        assert!(psk.ends_with("000f"));
+    }
+
+    #[test]
+    fn refreshed_reuses_pmk_only_for_the_same_credentials() {
+        let pmk = derive_pmk(TEST_SSID, TEST_PSK);
+        let previous = WifiFastConnectCache::new(TEST_SSID, TEST_PSK, BSSID, 6).with_pmk(pmk);
+
+        let moved = previous.refreshed(TEST_SSID, TEST_PSK, true, [1; 6], 11);
+        assert_eq!(moved.pmk(), Some(pmk));
+        assert_eq!(moved.bssid(), [1; 6]);
+        assert_eq!(moved.channel(), 11);
+
+        // A stale PMK from a planted value proves it is carried, not re-derived.
+        let planted = WifiFastConnectCache::new(TEST_SSID, TEST_PSK, BSSID, 6).with_pmk([9; 32]);
+        assert_eq!(
+            planted.refreshed(TEST_SSID, TEST_PSK, true, BSSID, 6).pmk(),
+            Some([9; 32])
+        );
+
+        let changed = previous.refreshed(TEST_SSID, "new-password", true, BSSID, 6);
+        assert_eq!(changed.pmk(), Some(derive_pmk(TEST_SSID, "new-password")));
+
+        let cold = WifiFastConnectCache::empty().refreshed(TEST_SSID, TEST_PSK, true, BSSID, 6);
+        assert_eq!(cold.pmk(), Some(pmk));
+    }
+
+    #[test]
+    fn refreshed_reusing_pmk_never_derives() {
+        let pmk = derive_pmk(TEST_SSID, TEST_PSK);
+        let previous = WifiFastConnectCache::new(TEST_SSID, TEST_PSK, BSSID, 6).with_pmk(pmk);
+        let moved = previous.refreshed_reusing_pmk(TEST_SSID, TEST_PSK, true, [1; 6], 11);
+        assert_eq!(moved.pmk(), Some(pmk));
+        assert_eq!(moved.channel(), 11);
+
+        let changed = previous.refreshed_reusing_pmk(TEST_SSID, "new-password", true, BSSID, 6);
+        assert!(changed.matches(TEST_SSID, "new-password"));
+        assert_eq!(changed.pmk(), None);
+
+        let open = previous.refreshed_reusing_pmk(TEST_SSID, TEST_PSK, false, BSSID, 6);
+        assert_eq!(open.pmk(), None);
+    }
+
+    #[test]
+    fn with_derived_pmk_fills_only_a_missing_key() {
+        let bare = WifiFastConnectCache::new(TEST_SSID, TEST_PSK, BSSID, 6);
+        assert_eq!(
+            bare.with_derived_pmk(TEST_SSID, TEST_PSK).pmk(),
+            Some(derive_pmk(TEST_SSID, TEST_PSK))
+        );
+
+        let planted = bare.with_pmk([9; 32]);
+        assert_eq!(
+            planted.with_derived_pmk(TEST_SSID, TEST_PSK).pmk(),
+            Some([9; 32])
+        );
+
+        assert_eq!(bare.with_derived_pmk(TEST_SSID, "other").pmk(), None);
+        let empty = WifiFastConnectCache::empty();
+        assert_eq!(empty.with_derived_pmk(TEST_SSID, TEST_PSK), empty);
+    }
+
+    #[test]
+    fn refreshed_without_pmk_drops_lease_and_key() {
+        let previous = WifiFastConnectCache::new(TEST_SSID, TEST_PSK, BSSID, 6)
+            .with_pmk([9; 32])
+            .with_ip_lease(IpConfig::Static {
+                ip: Ipv4Addr::new(10, 0, 0, 2),
+                netmask: Ipv4Addr::new(255, 0, 0, 0),
+                gateway: Ipv4Addr::new(10, 0, 0, 1),
+                dns: None,
+            });
+        let refreshed = previous.refreshed(TEST_SSID, TEST_PSK, false, BSSID, 6);
+        assert!(refreshed.matches(TEST_SSID, TEST_PSK));
+        assert_eq!(refreshed.pmk(), None);
+        assert_eq!(refreshed.ip_lease(), None);
+    }
+
+    #[test]
+    fn pmk_applies_to_wpa2_passphrases_only() {
+        assert!(pmk_applicable(&WifiAuthMethod::Wpa2Personal, TEST_PSK));
+        assert!(!pmk_applicable(&WifiAuthMethod::Wpa2Personal, ""));
+        assert!(!pmk_applicable(&WifiAuthMethod::Wpa2Personal, "short"));
+        assert!(!pmk_applicable(
+            &WifiAuthMethod::Wpa2Personal,
+            core::str::from_utf8(&[b'a'; 64]).unwrap()
+        ));
+        assert!(!pmk_applicable(&WifiAuthMethod::Wpa3Personal, TEST_PSK));
+        assert!(!pmk_applicable(&WifiAuthMethod::Wpa2Wpa3Personal, TEST_PSK));
+    }
+
+    #[test]
+    fn network_index_finds_the_recorded_network() {
+        let networks = WifiNetworkList::new()
+            .with(WifiNetwork::new("home", "home-password"))
+            .unwrap()
+            .with(WifiNetwork::new(TEST_SSID, TEST_PSK))
+            .unwrap()
+            .with(
+                WifiNetwork::new("campus", "ignored").with_auth(WifiAuthMethod::EapPeap {
+                    identity: "anonymous",
+                    username: "alice",
+                    password: "secret",
+                }),
+            )
+            .unwrap();
+
+        let cache = WifiFastConnectCache::new(TEST_SSID, TEST_PSK, BSSID, 6);
+        assert_eq!(cache.network_index(&networks), Some(1));
+        let campus = WifiFastConnectCache::new("campus", "", BSSID, 6);
+        assert_eq!(campus.network_index(&networks), Some(2));
+        let stale = WifiFastConnectCache::new("home", "old-password", BSSID, 6);
+        assert_eq!(stale.network_index(&networks), None);
+        assert_eq!(WifiFastConnectCache::empty().network_index(&networks), None);
+    }
+
+    #[test]
+    fn debug_redacts_pmk() {
+        let cache = WifiFastConnectCache::new(TEST_SSID, TEST_PSK, BSSID, 6).with_pmk([0xAA; 32]);
+        let mut out = heapless::String::<512>::new();
+        core::fmt::Write::write_fmt(&mut out, format_args!("{:?}", cache)).unwrap();
+        assert!(out.contains("<redacted>"));
+        assert!(!out.contains("170"));
+    }
+}
@@ -0,0 +1,524 @@
+//! IPv4 addressing: station DHCP or a fixed address, and the SoftAP subnet.
+//!
+//! Some installations sit on networks without a DHCP server.  [`IpConfig`]
+//! lets a [`WiFiConfig`][super::WiFiConfig] carry a static address, netmask,
+//! gateway, and optional DNS server; [`validate_ip_config`] rejects settings
+//! that could never route (address outside its own subnet, gateway equal to
+//! the subnet broadcast, non-contiguous netmask, …) before the radio starts.
+//!
+//! [`ApSubnet`] is the other side: the address a SoftAP gives itself and the
+//! DHCP pool it hands to stations.  It defaults to `192.168.4.1/24` with a
+//! pool of `.10`–`.20`; moving it avoids clashing with a nearby router (or a
+//! second device in provisioning mode) that uses the same subnet.
+//! [`validate_ap_subnet`] checks that the pieces agree with each other.
+//!
+//! Addresses are [`core::net::Ipv4Addr`], which is the same type as
+//! `std::net::Ipv4Addr` on ESP-IDF and `embassy_net::Ipv4Address` on
+//! bare-metal, so both platform crates pass them through without conversion.
+
+use core::net::Ipv4Addr;
+
+/// IPv4 addressing mode for the station interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IpConfig {
+    /// Obtain address, gateway, and DNS from a DHCP server (the default).
+    #[default]
+    Dhcp,
+    /// Use a fixed address.
+    Static {
+        /// Station address.
+        ip: Ipv4Addr,
+        /// Subnet mask, e.g. `255.255.255.0`; must be contiguous.
+        netmask: Ipv4Addr,
+        /// Default gateway; must lie inside the subnet.
+        gateway: Ipv4Addr,
+        /// DNS server, or `None` if the application resolves nothing.
+        dns: Option<Ipv4Addr>,
+    },
+}
+
+impl IpConfig {
+    /// Returns the netmask as a prefix length (`255.255.255.0` → `24`), or
+    /// `None` for DHCP or a non-contiguous mask.
+    pub fn prefix_len(&self) -> Option<u8> {
+        match self {
+            Self::Dhcp => None,
+            Self::Static { netmask, .. } => netmask_prefix_len(*netmask),
+        }
+    }
+}
+
+/// Converts a subnet mask to a prefix length.
+///
+/// Returns `None` if the mask is not contiguous (ones followed by zeros).
+pub fn netmask_prefix_len(netmask: Ipv4Addr) -> Option<u8> {
+    let bits = u32::from(netmask);
+    let ones = bits.leading_ones();
+    if bits.checked_shl(ones).unwrap_or(0) != 0 {
+        return None;
+    }
+    Some(ones as u8)
+}
+
+/// Validates an [`IpConfig`].
+///
+/// [`IpConfig::Dhcp`] is always valid.  For [`IpConfig::Static`]:
+///
+/// - the netmask must be contiguous with a prefix of `/1` to `/30` (a `/31`
+///   or `/32` leaves no room for both a host and a gateway);
+/// - the address must be a unicast host address inside its subnet — not the
+///   network address, not the subnet broadcast, not `0.0.0.0`, loopback,
+///   multicast, or `255.255.255.255`;
+/// - the gateway must be in the same subnet, must not be its network or
+///   broadcast address, and must differ from the station address;
+/// - the DNS server, if set, must be a unicast address.
+pub fn validate_ip_config(config: &IpConfig) -> Result<(), &'static str> {
+    let IpConfig::Static {
+        ip,
+        netmask,
+        gateway,
+        dns,
+    } = *config
+    else {
+        return Ok(());
+    };
+
+    let prefix = netmask_prefix_len(netmask).ok_or("netmask must be contiguous")?;
+    if !(1..=30).contains(&prefix) {
+        return Err("netmask prefix must be between /1 and /30");
+    }
+
+    if !is_unicast(ip) {
+        return Err("static IP must be a unicast address");
+    }
+    let mask = u32::from(netmask);
+    let network = u32::from(ip) & mask;
+    let broadcast = network | !mask;
+    let host = u32::from(ip);
+    if host == network || host == broadcast {
+        return Err("static IP must not be the network or broadcast address");
+    }
+
+    let gw = u32::from(gateway);
+    if gw & mask != network {
+        return Err("gateway must be inside the static IP's subnet");
+    }
+    if gw == broadcast || gw == network {
+        return Err("gateway must not be the network or broadcast address");
+    }
+    if gw == host {
+        return Err("gateway must differ from the static IP");
+    }
+
+    if let Some(dns) = dns {
+        if !is_unicast(dns) {
+            return Err("DNS server must be a unicast address");
+        }
+    }
+    Ok(())
+}
+
+// ─── SoftAP subnet ──────────────────────────────────────────────────────────
+
+/// Largest DHCP pool an [`ApSubnet`] may hold.
+///
+/// The bare-metal tier's DHCP server keeps a fixed lease table of this many
+/// entries and refuses to start with a larger pool, so the limit applies to
+/// both tiers.
+pub const AP_DHCP_POOL_MAX: u32 = 11;
+
+/// Address, netmask, and DHCP pool of a SoftAP interface.
+///
+/// The AP is its clients' gateway and DNS server, so one address covers all
+/// three roles.  The pool is inclusive at both ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApSubnet {
+    /// Address of the AP itself.
+    pub ip: Ipv4Addr,
+    /// Subnet mask, e.g. `255.255.255.0`; must be contiguous.
+    pub netmask: Ipv4Addr,
+    /// First address handed out by the DHCP server.
+    pub dhcp_start: Ipv4Addr,
+    /// Last address handed out by the DHCP server (inclusive).
+    pub dhcp_end: Ipv4Addr,
+}
+
+impl ApSubnet {
+    /// `192.168.4.1/24` with a DHCP pool of `192.168.4.10`–`192.168.4.20`,
+    /// the subnet ESP-IDF uses out of the box.
+    pub const DEFAULT: Self = Self {
+        ip: Ipv4Addr::new(192, 168, 4, 1),
+        netmask: Ipv4Addr::new(255, 255, 255, 0),
+        dhcp_start: Ipv4Addr::new(192, 168, 4, 10),
+        dhcp_end: Ipv4Addr::new(192, 168, 4, 20),
+    };
+
+    /// Returns the netmask as a prefix length, or `None` if it is not
+    /// contiguous.
+    pub fn prefix_len(&self) -> Option<u8> {
+        netmask_prefix_len(self.netmask)
+    }
+
+    /// Number of addresses in the DHCP pool, or `0` if the range is
+    /// reversed.
+    pub fn pool_size(&self) -> u32 {
+        let start = u32::from(self.dhcp_start);
+        let end = u32::from(self.dhcp_end);
+        if end < start {
+            0
+        } else {
+            end - start + 1
+        }
+    }
+}
+
+impl Default for ApSubnet {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Validates an [`ApSubnet`].
+///
+/// - the netmask must be contiguous with a prefix of `/1` to `/30`;
+/// - the AP address must be a unicast host address inside its subnet;
+/// - `dhcp_start` must not be greater than `dhcp_end`, and both must be
+///   host addresses in the AP's subnet;
+/// - the pool must not contain the AP address;
+/// - the pool must hold at most [`AP_DHCP_POOL_MAX`] addresses and lie in
+///   the same /24 as the AP address, which both tiers' DHCP servers require
+///   even when the subnet itself is wider.
+pub fn validate_ap_subnet(subnet: &ApSubnet) -> Result<(), &'static str> {
+    let prefix = subnet.prefix_len().ok_or("AP netmask must be contiguous")?;
+    if !(1..=30).contains(&prefix) {
+        return Err("AP netmask prefix must be between /1 and /30");
+    }
+
+    if !is_unicast(subnet.ip) {
+        return Err("AP IP must be a unicast address");
+    }
+    let mask = u32::from(subnet.netmask);
+    let network = u32::from(subnet.ip) & mask;
+    let broadcast = network | !mask;
+    let host = u32::from(subnet.ip);
+    if host == network || host == broadcast {
+        return Err("AP IP must not be the network or broadcast address");
+    }
+
+    let start = u32::from(subnet.dhcp_start);
+    let end = u32::from(subnet.dhcp_end);
+    if start > end {
+        return Err("DHCP pool start must not be after its end");
+    }
+    if start & mask != network || end & mask != network {
+        return Err("DHCP pool must be inside the AP's subnet");
+    }
+    if start == network || end == broadcast {
+        return Err("DHCP pool must not include the network or broadcast address");
+    }
+    if (start..=end).contains(&host) {
+        return Err("DHCP pool must not include the AP IP");
+    }
+    if subnet.pool_size() > AP_DHCP_POOL_MAX {
+        return Err("DHCP pool exceeds 11 addresses");
+    }
+    if start >> 8 != host >> 8 || end >> 8 != host >> 8 {
+        return Err("DHCP pool must be in the same /24 as the AP IP");
+    }
+    Ok(())
+}
+
+fn is_unicast(addr: Ipv4Addr) -> bool {
+    !(addr.is_unspecified() || addr.is_broadcast() || addr.is_multicast() || addr.is_loopback())
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MASK_24: Ipv4Addr = Ipv4Addr::new(255, 255, 255, 0);
+
+    fn fixed(ip: [u8; 4], netmask: Ipv4Addr, gateway: [u8; 4]) -> IpConfig {
+        IpConfig::Static {
+            ip: ip.into(),
+            netmask,
+            gateway: gateway.into(),
+            dns: None,
+        }
+    }
+
+    #[test]
+    fn dhcp_is_default_and_valid() {
+        assert_eq!(IpConfig::default(), IpConfig::Dhcp);
+        assert!(validate_ip_config(&IpConfig::Dhcp).is_ok());
+        assert_eq!(IpConfig::Dhcp.prefix_len(), None);
+    }
+
+    #[test]
+    fn typical_static_config_is_valid() {
+        let config = IpConfig::Static {
+            ip: Ipv4Addr::new(192, 168, 10, 50),
+            netmask: MASK_24,
+            gateway: Ipv4Addr::new(192, 168, 10, 1),
+            dns: Some(Ipv4Addr::new(192, 168, 10, 1)),
+        };
+        assert!(validate_ip_config(&config).is_ok());
+        assert_eq!(config.prefix_len(), Some(24));
+    }
+
+    #[test]
+    fn netmask_prefix_len_accepts_contiguous_masks() {
+        assert_eq!(netmask_prefix_len(Ipv4Addr::new(0, 0, 0, 0)), Some(0));
+        assert_eq!(netmask_prefix_len(Ipv4Addr::new(255, 0, 0, 0)), Some(8));
+        assert_eq!(
+            netmask_prefix_len(Ipv4Addr::new(255, 255, 252, 0)),
+            Some(22)
+        );
+        assert_eq!(netmask_prefix_len(Ipv4Addr::BROADCAST), Some(32));
+    }
+
+    #[test]
+    fn netmask_prefix_len_rejects_holes() {
+        assert_eq!(netmask_prefix_len(Ipv4Addr::new(255, 0, 255, 0)), None);
+        assert_eq!(netmask_prefix_len(Ipv4Addr::new(0, 255, 255, 255)), None);
+    }
+
+    #[test]
+    fn non_contiguous_netmask_is_rejected() {
+        let config = fixed([10, 0, 0, 5], Ipv4Addr::new(255, 0, 255, 0), [10, 0, 0, 1]);
+        assert_eq!(
+            validate_ip_config(&config),
+            Err("netmask must be contiguous")
+        );
+    }
+
+    #[test]
+    fn prefix_outside_1_to_30_is_rejected() {
+        let slash31 = fixed(
+            [10, 0, 0, 0],
+            Ipv4Addr::new(255, 255, 255, 254),
+            [10, 0, 0, 1],
+        );
+        assert!(validate_ip_config(&slash31).is_err());
+        let slash0 = fixed([10, 0, 0, 5], Ipv4Addr::UNSPECIFIED, [10, 0, 0, 1]);
+        assert!(validate_ip_config(&slash0).is_err());
+    }
+
+    #[test]
+    fn network_and_broadcast_host_addresses_are_rejected() {
+        assert!(validate_ip_config(&fixed([192, 168, 1, 0], MASK_24, [192, 168, 1, 1])).is_err());
+        assert!(validate_ip_config(&fixed([192, 168, 1, 255], MASK_24, [192, 168, 1, 1])).is_err());
+    }
+
+    #[test]
+    fn non_unicast_host_addresses_are_rejected() {
+        for ip in [[0, 0, 0, 0], [127, 0, 0, 1], [224, 0, 0, 1], [255; 4]] {
+            assert!(
+                validate_ip_config(&fixed(ip, MASK_24, [192, 168, 1, 1])).is_err(),
+                "{:?} accepted",
+                ip
+            );
+        }
+    }
+
+    #[test]
+    fn gateway_outside_subnet_is_rejected() {
+        assert_eq!(
+            validate_ip_config(&fixed([192, 168, 1, 50], MASK_24, [192, 168, 2, 1])),
+            Err("gateway must be inside the static IP's subnet")
+        );
+    }
+
+    #[test]
+    fn broadcast_gateway_is_rejected() {
+        assert_eq!(
+            validate_ip_config(&fixed([192, 168, 1, 50], MASK_24, [192, 168, 1, 255])),
+            Err("gateway must not be the network or broadcast address")
+        );
+    }
+
+    #[test]
+    fn gateway_equal_to_ip_is_rejected() {
+        assert_eq!(
+            validate_ip_config(&fixed([192, 168, 1, 50], MASK_24, [192, 168, 1, 50])),
+            Err("gateway must differ from the static IP")
+        );
+    }
+
+    #[test]
+    fn gateway_in_wider_subnet_is_valid() {
+        let mask_16 = Ipv4Addr::new(255, 255, 0, 0);
+        assert!(validate_ip_config(&fixed([10, 1, 7, 9], mask_16, [10, 1, 0, 1])).is_ok());
+    }
+
+    fn ap_subnet(ip: [u8; 4], netmask: Ipv4Addr, start: [u8; 4], end: [u8; 4]) -> ApSubnet {
+        ApSubnet {
+            ip: ip.into(),
+            netmask,
+            dhcp_start: start.into(),
+            dhcp_end: end.into(),
+        }
+    }
+
+    #[test]
+    fn default_ap_subnet_is_valid() {
+        let subnet = ApSubnet::default();
+        assert_eq!(subnet.ip, Ipv4Addr::new(192, 168, 4, 1));
+        assert_eq!(subnet.prefix_len(), Some(24));
+        assert_eq!(subnet.pool_size(), 11);
+        assert!(validate_ap_subnet(&subnet).is_ok());
+    }
+
+    #[test]
+    fn moved_ap_subnet_is_valid() {
+        let subnet = ap_subnet(
+            [10, 42, 0, 1],
+            Ipv4Addr::new(255, 255, 252, 0),
+            [10, 42, 0, 100],
+            [10, 42, 0, 110],
+        );
+        assert!(validate_ap_subnet(&subnet).is_ok());
+        assert_eq!(subnet.pool_size(), AP_DHCP_POOL_MAX);
+    }
+
+    #[test]
+    fn dhcp_pool_larger_than_max_is_rejected() {
+        let subnet = ap_subnet(
+            [192, 168, 7, 1],
+            MASK_24,
+            [192, 168, 7, 10],
+            [192, 168, 7, 21],
+        );
+        assert_eq!(subnet.pool_size(), AP_DHCP_POOL_MAX + 1);
+        assert_eq!(
+            validate_ap_subnet(&subnet),
+            Err("DHCP pool exceeds 11 addresses")
+        );
+    }
+
+    #[test]
+    fn dhcp_pool_outside_ap_slash_24_is_rejected() {
+        // Inside the /22, but not in the AP's /24.
+        let mask_22 = Ipv4Addr::new(255, 255, 252, 0);
+        let elsewhere = ap_subnet([10, 42, 0, 1], mask_22, [10, 42, 1, 0], [10, 42, 1, 9]);
+        assert_eq!(
+            validate_ap_subnet(&elsewhere),
+            Err("DHCP pool must be in the same /24 as the AP IP")
+        );
+        let straddling = ap_subnet([10, 42, 0, 1], mask_22, [10, 42, 0, 250], [10, 42, 1, 4]);
+        assert_eq!(
+            validate_ap_subnet(&straddling),
+            Err("DHCP pool must be in the same /24 as the AP IP")
+        );
+    }
+
+    #[test]
+    fn ap_subnet_with_bad_netmask_is_rejected() {
+        let holes = ap_subnet(
+            [10, 0, 0, 1],
+            Ipv4Addr::new(255, 0, 255, 0),
+            [10, 0, 0, 10],
+            [10, 0, 0, 20],
+        );
+        assert_eq!(
+            validate_ap_subnet(&holes),
+            Err("AP netmask must be contiguous")
+        );
+        let slash31 = ap_subnet(
+            [10, 0, 0, 1],
+            Ipv4Addr::new(255, 255, 255, 254),
+            [10, 0, 0, 0],
+            [10, 0, 0, 0],
+        );
+        assert!(validate_ap_subnet(&slash31).is_err());
+    }
+
+    #[test]
+    fn ap_ip_on_network_or_broadcast_is_rejected() {
+        for ip in [[192, 168, 7, 0], [192, 168, 7, 255]] {
+            assert_eq!(
+                validate_ap_subnet(&ap_subnet(
+                    ip,
+                    MASK_24,
+                    [192, 168, 7, 10],
+                    [192, 168, 7, 20]
+                )),
+                Err("AP IP must not be the network or broadcast address")
+            );
+        }
+    }
+
+    #[test]
+    fn reversed_dhcp_pool_is_rejected() {
+        let subnet = ap_subnet(
+            [192, 168, 7, 1],
+            MASK_24,
+            [192, 168, 7, 20],
+            [192, 168, 7, 10],
+        );
+        assert_eq!(subnet.pool_size(), 0);
+        assert_eq!(
+            validate_ap_subnet(&subnet),
+            Err("DHCP pool start must not be after its end")
+        );
+    }
+
+    #[test]
+    fn dhcp_pool_outside_subnet_is_rejected() {
+        // The AP moved but the pool did not.
+        let subnet = ap_subnet(
+            [192, 168, 7, 1],
+            MASK_24,
+            [192, 168, 4, 10],
+            [192, 168, 4, 20],
+        );
+        assert_eq!(
+            validate_ap_subnet(&subnet),
+            Err("DHCP pool must be inside the AP's subnet")
+        );
+    }
+
+    #[test]
+    fn dhcp_pool_reaching_broadcast_is_rejected() {
+        let subnet = ap_subnet(
+            [192, 168, 7, 1],
+            MASK_24,
+            [192, 168, 7, 200],
+            [192, 168, 7, 255],
+        );
+        assert_eq!(
+            validate_ap_subnet(&subnet),
+            Err("DHCP pool must not include the network or broadcast address")
+        );
+    }
+
+    #[test]
+    fn dhcp_pool_containing_ap_ip_is_rejected() {
+        let subnet = ap_subnet(
+            [192, 168, 7, 15],
+            MASK_24,
+            [192, 168, 7, 10],
+            [192, 168, 7, 20],
+        );
+        assert_eq!(
+            validate_ap_subnet(&subnet),
+            Err("DHCP pool must not include the AP IP")
+        );
+    }
+
+    #[test]
+    fn non_unicast_dns_is_rejected() {
+        let config = IpConfig::Static {
+            ip: Ipv4Addr::new(192, 168, 1, 50),
+            netmask: MASK_24,
+            gateway: Ipv4Addr::new(192, 168, 1, 1),
+            dns: Some(Ipv4Addr::BROADCAST),
+        };
+        assert_eq!(
+            validate_ip_config(&config),
+            Err("DNS server must be a unicast address")
+        );
+    }
+}
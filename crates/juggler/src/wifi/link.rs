@@ -0,0 +1,353 @@
+//! Station link supervision: a pure state machine plus backoff-driven retry.
+//!
+//! Every firmware used to hand-roll its own "associate, wait for a
+//! disconnect, sleep, try again" loop.  This module captures that loop once,
+//! independent of any driver:
+//!
+//! - [`WifiLinkState`] / [`WifiLinkEvent`] / [`next_link_state`] — the
+//!   transition table, in the same style as `juggler::mqtt::next_state`
+//!   (invalid transitions return `None`).
+//! - [`WifiLinkSupervisor`] — wraps the table with an
+//!   [`ExponentialBackoff`] and tells the driver loop what to do next via
+//!   [`WifiLinkAction`].
+//!
+//! The platform crates only translate driver results into events and
+//! actions into driver calls; all timing decisions live here and are
+//! host-testable.
+
+use super::{DisconnectClass, DisconnectReason};
+use crate::backoff::ExponentialBackoff;
+
+/// Delay before the first reconnect attempt after a disconnect (milliseconds).
+pub const LINK_RETRY_BASE_MS: u64 = 1_000;
+
+/// Upper bound on the delay between reconnect attempts (milliseconds).
+///
+/// One minute keeps a device responsive once the AP comes back without
+/// hammering a router that is rebooting or rejecting us.
+pub const LINK_RETRY_MAX_MS: u64 = 60_000;
+
+// ─── State machine ──────────────────────────────────────────────────────────
+
+/// Observable states of the station link.
+///
+/// Invalid transitions return `None` from [`next_link_state`], meaning the
+/// event is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WifiLinkState {
+    /// The supervisor has not been started.
+    #[default]
+    Idle,
+    /// An association attempt is in progress.
+    Connecting,
+    /// Associated with an access point.
+    Connected,
+    /// The last attempt failed or the link dropped; waiting out the backoff.
+    WaitingToRetry,
+    /// Supervision was stopped; no further attempts will be made.
+    Stopped,
+}
+
+/// Events that drive the link state machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WifiLinkEvent {
+    /// The application asked the supervisor to bring the link up.
+    Start,
+    /// Association (and, for the platform crates, the connect call) succeeded.
+    Connected,
+    /// An attempt failed or an established link dropped.
+    ///
+    /// `reason` is the raw 802.11 / driver disconnect reason code (for
+    /// example `201` for "no AP found", see [`DisconnectReason`]); `0` if the
+    /// driver did not report one.
+    Disconnected {
+        /// Raw disconnect reason code.
+        reason: u16,
+    },
+    /// The backoff delay requested by [`WifiLinkAction::Wait`] has elapsed.
+    RetryTimerElapsed,
+    /// The application requested supervision to stop.
+    Stop,
+}
+
+/// Returns the next [`WifiLinkState`] given the current state and an
+/// incoming event, or `None` if the transition is invalid.
+///
+/// `None` encodes the following invariants:
+/// - `Connected` is only reachable from `Connecting`: a stray "connected"
+///   notification while waiting out the backoff does not skip the retry.
+/// - `RetryTimerElapsed` is only meaningful in `WaitingToRetry`; a late
+///   timer firing after the link came back is ignored.
+/// - Any event from `Stopped` returns `None`: a stopped supervisor never
+///   reconnects.
+pub fn next_link_state(current: WifiLinkState, event: WifiLinkEvent) -> Option<WifiLinkState> {
+    use WifiLinkEvent as E;
+    use WifiLinkState as S;
+    match (current, event) {
+        (S::Stopped, _) => None,
+        (_, E::Stop) => Some(S::Stopped),
+        (S::Idle, E::Start) => Some(S::Connecting),
+        (S::Connecting, E::Connected) => Some(S::Connected),
+        (S::Connecting | S::Connected, E::Disconnected { .. }) => Some(S::WaitingToRetry),
+        (S::WaitingToRetry, E::RetryTimerElapsed) => Some(S::Connecting),
+        _ => None,
+    }
+}
+
+// ─── Supervisor ─────────────────────────────────────────────────────────────
+
+/// What the driver loop should do after a state change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WifiLinkAction {
+    /// Start an association attempt, then report
+    /// [`WifiLinkEvent::Connected`] or [`WifiLinkEvent::Disconnected`].
+    Connect,
+    /// Sleep for `delay_ms`, then report [`WifiLinkEvent::RetryTimerElapsed`].
+    Wait {
+        /// Backoff delay (milliseconds).
+        delay_ms: u64,
+    },
+    /// The link is up; block until the driver reports a disconnect.
+    WaitForDisconnect,
+    /// Tear the link down and exit the loop.
+    Stop,
+}
+
+/// Drives [`next_link_state`] and owns the reconnect backoff.
+///
+/// Feed it every [`WifiLinkEvent`] via [`handle`](Self::handle) and perform
+/// the returned [`WifiLinkAction`].  The backoff resets on each successful
+/// association, so a link that drops after running for hours retries quickly
+/// again.
+pub struct WifiLinkSupervisor {
+    state: WifiLinkState,
+    backoff: ExponentialBackoff,
+    // Upper bound of `backoff`, which does not expose its own.
+    retry_max_ms: u64,
+    retry_count: u32,
+    last_disconnect_reason: Option<u16>,
+}
+
+impl WifiLinkSupervisor {
+    /// Creates a supervisor using [`LINK_RETRY_BASE_MS`] and
+    /// [`LINK_RETRY_MAX_MS`].
+    pub fn new() -> Self {
+        Self::with_backoff(LINK_RETRY_BASE_MS, LINK_RETRY_MAX_MS)
+    }
+
+    /// Creates a supervisor with a custom backoff range (milliseconds).
+    pub fn with_backoff(base_ms: u64, max_ms: u64) -> Self {
+        Self {
+            state: WifiLinkState::Idle,
+            backoff: ExponentialBackoff::new(base_ms, max_ms),
+            retry_max_ms: max_ms,
+            retry_count: 0,
+            last_disconnect_reason: None,
+        }
+    }
+
+    /// Current link state.
+    pub fn state(&self) -> WifiLinkState {
+        self.state
+    }
+
+    /// Number of consecutive failed attempts or drops since the last
+    /// successful association.
+    pub fn retry_count(&self) -> u32 {
+        self.retry_count
+    }
+
+    /// Reason code of the most recent [`WifiLinkEvent::Disconnected`], if any.
+    pub fn last_disconnect_reason(&self) -> Option<u16> {
+        self.last_disconnect_reason
+    }
+
+    /// Classification of [`last_disconnect_reason`](Self::last_disconnect_reason).
+    ///
+    /// Lets the caller leave the retry loop when retrying cannot help — for
+    /// example, re-entering provisioning on
+    /// [`DisconnectClass::CredentialsRejected`].
+    pub fn last_disconnect_class(&self) -> Option<DisconnectClass> {
+        self.last_disconnect_reason
+            .map(|code| DisconnectReason::from_code(code).class())
+    }
+
+    /// Applies `event` and returns the action the driver loop should take,
+    /// or `None` if the event was invalid in the current state and ignored.
+    pub fn handle(&mut self, event: WifiLinkEvent) -> Option<WifiLinkAction> {
+        let next = next_link_state(self.state, event)?;
+        self.state = next;
+        let action = match (next, event) {
+            (WifiLinkState::Connecting, _) => WifiLinkAction::Connect,
+            (WifiLinkState::Connected, _) => {
+                self.backoff.reset();
+                self.retry_count = 0;
+                WifiLinkAction::WaitForDisconnect
+            }
+            (WifiLinkState::WaitingToRetry, WifiLinkEvent::Disconnected { reason }) => {
+                self.last_disconnect_reason = Some(reason);
+                self.retry_count = self.retry_count.saturating_add(1);
+                // ExponentialBackoff is infinite; `unwrap_or` is never taken.
+                let delay_ms = self.backoff.next().unwrap_or(self.retry_max_ms);
+                WifiLinkAction::Wait { delay_ms }
+            }
+            (WifiLinkState::Stopped, _) => WifiLinkAction::Stop,
+            // `next_link_state` never produces Idle or enters WaitingToRetry
+            // without a Disconnected event.
+            _ => return None,
+        };
+        Some(action)
+    }
+}
+
+impl Default for WifiLinkSupervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use WifiLinkEvent as E;
+    use WifiLinkState as S;
+
+    const DROP: WifiLinkEvent = E::Disconnected { reason: 8 };
+
+    fn assert_transition(current: S, event: E, expected: Option<S>) {
+        assert_eq!(
+            next_link_state(current, event),
+            expected,
+            "{:?} + {:?}",
+            current,
+            event
+        );
+    }
+
+    // ── next_link_state ─────────────────────────────────────────────────────
+
+    #[test]
+    fn idle_start_connects() {
+        assert_transition(S::Idle, E::Start, Some(S::Connecting));
+    }
+
+    #[test]
+    fn idle_ignores_everything_but_start_and_stop() {
+        assert_transition(S::Idle, E::Connected, None);
+        assert_transition(S::Idle, DROP, None);
+        assert_transition(S::Idle, E::RetryTimerElapsed, None);
+        assert_transition(S::Idle, E::Stop, Some(S::Stopped));
+    }
+
+    #[test]
+    fn connecting_transitions() {
+        assert_transition(S::Connecting, E::Connected, Some(S::Connected));
+        assert_transition(S::Connecting, DROP, Some(S::WaitingToRetry));
+        assert_transition(S::Connecting, E::Start, None);
+        assert_transition(S::Connecting, E::RetryTimerElapsed, None);
+    }
+
+    #[test]
+    fn connected_drop_waits_to_retry() {
+        assert_transition(S::Connected, DROP, Some(S::WaitingToRetry));
+        assert_transition(S::Connected, E::Connected, None);
+        assert_transition(S::Connected, E::RetryTimerElapsed, None);
+    }
+
+    #[test]
+    fn waiting_only_leaves_on_timer_or_stop() {
+        assert_transition(S::WaitingToRetry, E::RetryTimerElapsed, Some(S::Connecting));
+        assert_transition(S::WaitingToRetry, E::Connected, None);
+        assert_transition(S::WaitingToRetry, DROP, None);
+        assert_transition(S::WaitingToRetry, E::Stop, Some(S::Stopped));
+    }
+
+    #[test]
+    fn stopped_is_terminal() {
+        for event in [E::Start, E::Connected, DROP, E::RetryTimerElapsed, E::Stop] {
+            assert_transition(S::Stopped, event, None);
+        }
+    }
+
+    // ── WifiLinkSupervisor ──────────────────────────────────────────────────
+
+    #[test]
+    fn supervisor_starts_idle() {
+        let sup = WifiLinkSupervisor::new();
+        assert_eq!(sup.state(), S::Idle);
+        assert_eq!(sup.retry_count(), 0);
+        assert_eq!(sup.last_disconnect_reason(), None);
+        assert_eq!(sup.last_disconnect_class(), None);
+    }
+
+    #[test]
+    fn supervisor_happy_path() {
+        let mut sup = WifiLinkSupervisor::new();
+        assert_eq!(sup.handle(E::Start), Some(WifiLinkAction::Connect));
+        assert_eq!(
+            sup.handle(E::Connected),
+            Some(WifiLinkAction::WaitForDisconnect)
+        );
+        assert_eq!(sup.state(), S::Connected);
+    }
+
+    #[test]
+    fn supervisor_backs_off_exponentially() {
+        let mut sup = WifiLinkSupervisor::with_backoff(100, 350);
+        sup.handle(E::Start);
+        let mut delays = [0u64; 4];
+        for delay in delays.iter_mut() {
+            match sup.handle(E::Disconnected { reason: 201 }) {
+                Some(WifiLinkAction::Wait { delay_ms }) => *delay = delay_ms,
+                other => panic!("expected Wait, got {:?}", other),
+            }
+            assert_eq!(
+                sup.handle(E::RetryTimerElapsed),
+                Some(WifiLinkAction::Connect)
+            );
+        }
+        assert_eq!(delays, [100, 200, 350, 350]);
+        assert_eq!(sup.retry_count(), 4);
+        assert_eq!(sup.last_disconnect_reason(), Some(201));
+        assert_eq!(
+            sup.last_disconnect_class(),
+            Some(DisconnectClass::ApNotFound)
+        );
+    }
+
+    #[test]
+    fn supervisor_resets_backoff_after_connect() {
+        let mut sup = WifiLinkSupervisor::with_backoff(100, 10_000);
+        sup.handle(E::Start);
+        sup.handle(DROP);
+        sup.handle(E::RetryTimerElapsed);
+        sup.handle(DROP);
+        sup.handle(E::RetryTimerElapsed);
+        sup.handle(E::Connected);
+        assert_eq!(sup.retry_count(), 0);
+
+        assert_eq!(
+            sup.handle(DROP),
+            Some(WifiLinkAction::Wait { delay_ms: 100 })
+        );
+    }
+
+    #[test]
+    fn supervisor_ignores_invalid_events() {
+        let mut sup = WifiLinkSupervisor::new();
+        assert_eq!(sup.handle(E::Connected), None);
+        assert_eq!(sup.state(), S::Idle);
+    }
+
+    #[test]
+    fn supervisor_stop_is_final() {
+        let mut sup = WifiLinkSupervisor::new();
+        sup.handle(E::Start);
+        assert_eq!(sup.handle(E::Stop), Some(WifiLinkAction::Stop));
+        assert_eq!(sup.handle(E::Start), None);
+        assert_eq!(sup.state(), S::Stopped);
+    }
+}
@@ -0,0 +1,498 @@
+//! Device naming: DHCP hostname and mDNS / DNS-SD advertisement.
+//!
+//! A station set up with [`WiFiConfig::with_hostname`][super::WiFiConfig::with_hostname]
+//! sends that name to the DHCP server (option 12), so it shows up in the
+//! router's client table.  An [`MdnsConfig`] goes further and makes the
+//! device answer multicast DNS queries for `<hostname>.local`, optionally
+//! advertising DNS-SD services such as `_http._tcp` so browsers and
+//! `avahi-browse` / `dns-sd -B` can find it without knowing the IP.
+//!
+//! Both platform crates consume the same types: the ESP-IDF tier hands them
+//! to the `espressif/mdns` component, the bare-metal tier to its own
+//! responder.  [`validate_hostname`] and [`validate_mdns_config`] reject
+//! names that either would refuse before the radio starts.
+
+/// Maximum hostname length (bytes).
+///
+/// Both ESP-IDF (`esp_netif_set_hostname`) and `embassy-net` (DHCP option
+/// 12) cap the hostname at 32 bytes.
+pub const HOSTNAME_MAX_LEN: usize = 32;
+
+/// Maximum number of DNS-SD services one [`MdnsConfig`] may advertise.
+///
+/// Long names and TXT records can still push an announcement past
+/// [`MDNS_MESSAGE_MAX_LEN`], which [`validate_mdns_config`] checks
+/// separately.
+pub const MDNS_MAX_SERVICES: usize = 4;
+
+/// Maximum number of TXT key/value pairs per service.
+pub const MDNS_MAX_TXT_ENTRIES: usize = 4;
+
+/// Maximum encoded size of the announcement of an [`MdnsConfig`] (bytes).
+///
+/// The bare-metal responder encodes every message into a buffer of this
+/// size, which stays below the 1472-byte UDP payload of a 1500-byte MTU;
+/// see [`mdns_announcement_len`].
+pub const MDNS_MESSAGE_MAX_LEN: usize = 1024;
+
+/// Default TTL for mDNS records (seconds), the RFC 6762 §10 value for
+/// records that carry a host name.
+pub const MDNS_DEFAULT_TTL_SECS: u32 = 120;
+
+/// Maximum length of a DNS-SD service name including the leading underscore,
+/// e.g. `"_http"` (RFC 6335 §5.1 allows 15 characters after it).
+const SERVICE_NAME_MAX_LEN: usize = 16;
+
+/// Maximum length of one DNS label (RFC 1035 §2.3.4).
+const LABEL_MAX_LEN: usize = 63;
+
+/// Maximum length of one TXT `key=value` string (RFC 6763 §6.1).
+const TXT_ENTRY_MAX_LEN: usize = 255;
+
+/// DNS message header length.
+const DNS_HEADER_LEN: usize = 12;
+
+/// Type, class, TTL, and RDLENGTH fields of a resource record.
+const RR_FIXED_LEN: usize = 10;
+
+/// Labels of the DNS-SD service enumeration name (RFC 6763 §9).
+const ENUMERATION_NAME: [&str; 4] = ["_services", "_dns-sd", "_udp", "local"];
+
+// ─── ServiceProtocol ────────────────────────────────────────────────────────
+
+/// Transport protocol label of a DNS-SD service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ServiceProtocol {
+    /// `_tcp` — HTTP, SSH, MQTT, and most other services.
+    #[default]
+    Tcp,
+    /// `_udp` — everything else (RFC 6763 §7).
+    Udp,
+}
+
+impl ServiceProtocol {
+    /// Returns the DNS label, `"_tcp"` or `"_udp"`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Tcp => "_tcp",
+            Self::Udp => "_udp",
+        }
+    }
+}
+
+// ─── MdnsService ────────────────────────────────────────────────────────────
+
+/// One DNS-SD service instance, e.g. the device's web UI on port 80.
+///
+/// ```ignore
+/// const HTTP: MdnsService = MdnsService::new("_http", ServiceProtocol::Tcp, 80)
+///     .with_txt(&[("path", "/")]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MdnsService<'a> {
+    /// Service name with its leading underscore, e.g. `"_http"`.
+    pub service: &'a str,
+    /// Transport protocol label.
+    pub protocol: ServiceProtocol,
+    /// Port the service listens on.
+    pub port: u16,
+    /// Instance name shown in service browsers, or `None` to use the
+    /// hostname.
+    pub instance: Option<&'a str>,
+    /// TXT record key/value pairs; empty for an empty TXT record.
+    pub txt: &'a [(&'a str, &'a str)],
+}
+
+impl<'a> MdnsService<'a> {
+    /// Creates a service with no TXT data, named after the host.
+    pub const fn new(service: &'a str, protocol: ServiceProtocol, port: u16) -> Self {
+        Self {
+            service,
+            protocol,
+            port,
+            instance: None,
+            txt: &[],
+        }
+    }
+
+    /// Sets the instance name shown in service browsers.
+    pub const fn with_instance(mut self, instance: &'a str) -> Self {
+        self.instance = Some(instance);
+        self
+    }
+
+    /// Sets the TXT record key/value pairs.
+    pub const fn with_txt(mut self, txt: &'a [(&'a str, &'a str)]) -> Self {
+        self.txt = txt;
+        self
+    }
+}
+
+// ─── MdnsConfig ─────────────────────────────────────────────────────────────
+
+/// mDNS responder configuration: the `.local` hostname plus the services to
+/// advertise.
+///
+/// ```ignore
+/// static SERVICES: [MdnsService; 1] = [MdnsService::new("_http", ServiceProtocol::Tcp, 80)];
+/// let mdns = MdnsConfig::new("greenhouse").with_services(&SERVICES);
+/// // answers `greenhouse.local` and advertises `greenhouse._http._tcp.local`
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MdnsConfig<'a> {
+    /// Hostname without the `.local` suffix.
+    pub hostname: &'a str,
+    /// DNS-SD services to advertise; at most [`MDNS_MAX_SERVICES`].
+    pub services: &'a [MdnsService<'a>],
+    /// TTL of every record (seconds); [`MDNS_DEFAULT_TTL_SECS`] by default.
+    pub ttl_secs: u32,
+}
+
+impl<'a> MdnsConfig<'a> {
+    /// Creates a configuration that answers `<hostname>.local` only.
+    pub const fn new(hostname: &'a str) -> Self {
+        Self {
+            hostname,
+            services: &[],
+            ttl_secs: MDNS_DEFAULT_TTL_SECS,
+        }
+    }
+
+    /// Sets the DNS-SD services to advertise.
+    pub const fn with_services(mut self, services: &'a [MdnsService<'a>]) -> Self {
+        self.services = services;
+        self
+    }
+
+    /// Overrides the record TTL.
+    pub const fn with_ttl(mut self, secs: u32) -> Self {
+        self.ttl_secs = secs;
+        self
+    }
+}
+
+// ─── Validation ─────────────────────────────────────────────────────────────
+
+/// Validates a device hostname.
+///
+/// The name must be a single RFC 1123 label of 1 to [`HOSTNAME_MAX_LEN`]
+/// bytes: ASCII letters, digits, and hyphens, not starting or ending with a
+/// hyphen.  It is given without a domain — the mDNS responder appends
+/// `.local` itself.
+pub fn validate_hostname(hostname: &str) -> Result<(), &'static str> {
+    if hostname.is_empty() {
+        return Err("hostname must not be empty");
+    }
+    if hostname.len() > HOSTNAME_MAX_LEN {
+        return Err("hostname exceeds maximum length of 32 bytes");
+    }
+    if !hostname
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    {
+        return Err("hostname may only contain ASCII letters, digits, and hyphens");
+    }
+    if hostname.starts_with('-') || hostname.ends_with('-') {
+        return Err("hostname must not start or end with a hyphen");
+    }
+    Ok(())
+}
+
+/// Validates an [`MdnsConfig`].
+///
+/// - the hostname must pass [`validate_hostname`];
+/// - there may be at most [`MDNS_MAX_SERVICES`] services, no two with the
+///   same service name and protocol;
+/// - each service name must start with `_` followed by 1 to 15 letters,
+///   digits, or hyphens (RFC 6335 §5.1), and the port must be non-zero;
+/// - an instance name, if set, must be 1 to 63 bytes;
+/// - each service may carry at most [`MDNS_MAX_TXT_ENTRIES`] TXT pairs with
+///   a non-empty key free of `=`, and `key=value` at most 255 bytes;
+/// - the announcement of every record, as measured by
+///   [`mdns_announcement_len`], must fit in [`MDNS_MESSAGE_MAX_LEN`] bytes.
+pub fn validate_mdns_config(config: &MdnsConfig<'_>) -> Result<(), &'static str> {
+    validate_hostname(config.hostname)?;
+    if config.services.len() > MDNS_MAX_SERVICES {
+        return Err("too many mDNS services (maximum 4)");
+    }
+    for (i, service) in config.services.iter().enumerate() {
+        validate_service(service)?;
+        let duplicate = config.services[..i].iter().any(|s| {
+            s.protocol == service.protocol && s.service.eq_ignore_ascii_case(service.service)
+        });
+        if duplicate {
+            return Err("mDNS service advertised twice");
+        }
+    }
+    if mdns_announcement_len(config) > MDNS_MESSAGE_MAX_LEN {
+        return Err("mDNS announcement exceeds 1024 bytes");
+    }
+    Ok(())
+}
+
+/// Encoded size of an announcement of every record of `config` (bytes).
+///
+/// Counts the header, the host's `A` record, and per service its `PTR`,
+/// `SRV`, `TXT`, and service-enumeration `PTR` records, with every name
+/// written uncompressed — the layout of the bare-metal responder's
+/// announcement.  Answers to queries carry a subset of these records.
+pub fn mdns_announcement_len(config: &MdnsConfig<'_>) -> usize {
+    fn name_len(labels: &[&str]) -> usize {
+        labels.iter().map(|label| 1 + label.len()).sum::<usize>() + 1
+    }
+
+    let host = name_len(&[config.hostname, "local"]);
+    let services: usize = config
+        .services
+        .iter()
+        .map(|service| {
+            let service_type = name_len(&[service.service, service.protocol.label(), "local"]);
+            let instance = name_len(&[
+                service.instance.unwrap_or(config.hostname),
+                service.service,
+                service.protocol.label(),
+                "local",
+            ]);
+            // An empty TXT record is a single zero-length string.
+            let txt = if service.txt.is_empty() {
+                1
+            } else {
+                service
+                    .txt
+                    .iter()
+                    .map(|(key, value)| 1 + key.len() + 1 + value.len())
+                    .sum()
+            };
+            let ptr = service_type + RR_FIXED_LEN + instance;
+            let srv = instance + RR_FIXED_LEN + 6 + host;
+            let txt = instance + RR_FIXED_LEN + txt;
+            let enumeration = name_len(&ENUMERATION_NAME) + RR_FIXED_LEN + service_type;
+            ptr + srv + txt + enumeration
+        })
+        .sum();
+    DNS_HEADER_LEN + host + RR_FIXED_LEN + 4 + services
+}
+
+fn validate_service(service: &MdnsService<'_>) -> Result<(), &'static str> {
+    let name = service
+        .service
+        .strip_prefix('_')
+        .ok_or("mDNS service name must start with an underscore")?;
+    if name.is_empty() || service.service.len() > SERVICE_NAME_MAX_LEN {
+        return Err("mDNS service name must be 1 to 15 characters after the underscore");
+    }
+    if !name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+        return Err("mDNS service name may only contain ASCII letters, digits, and hyphens");
+    }
+    if service.port == 0 {
+        return Err("mDNS service port must not be 0");
+    }
+    if let Some(instance) = service.instance {
+        if instance.is_empty() || instance.len() > LABEL_MAX_LEN {
+            return Err("mDNS instance name must be 1 to 63 bytes");
+        }
+    }
+    if service.txt.len() > MDNS_MAX_TXT_ENTRIES {
+        return Err("too many TXT entries (maximum 4)");
+    }
+    for (key, value) in service.txt {
+        if key.is_empty() || key.contains('=') {
+            return Err("TXT key must be non-empty and must not contain '='");
+        }
+        if key.len() + 1 + value.len() > TXT_ENTRY_MAX_LEN {
+            return Err("TXT entry exceeds 255 bytes");
+        }
+    }
+    Ok(())
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HTTP: MdnsService<'static> = MdnsService::new("_http", ServiceProtocol::Tcp, 80);
+
+    #[test]
+    fn typical_hostnames_are_valid() {
+        for name in ["greenhouse", "node-7", "ESP32C6", "a"] {
+            assert!(validate_hostname(name).is_ok(), "{} rejected", name);
+        }
+        assert!(validate_hostname(&"h".repeat(HOSTNAME_MAX_LEN)).is_ok());
+    }
+
+    #[test]
+    fn empty_or_long_hostname_is_rejected() {
+        assert_eq!(validate_hostname(""), Err("hostname must not be empty"));
+        assert!(validate_hostname(&"h".repeat(HOSTNAME_MAX_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn hostname_with_dots_or_underscores_is_rejected() {
+        assert!(validate_hostname("node.local").is_err());
+        assert!(validate_hostname("node_7").is_err());
+        assert!(validate_hostname("nöde").is_err());
+    }
+
+    #[test]
+    fn hostname_with_edge_hyphen_is_rejected() {
+        assert_eq!(
+            validate_hostname("-node"),
+            Err("hostname must not start or end with a hyphen")
+        );
+        assert!(validate_hostname("node-").is_err());
+    }
+
+    #[test]
+    fn config_defaults() {
+        let config = MdnsConfig::new("greenhouse");
+        assert!(config.services.is_empty());
+        assert_eq!(config.ttl_secs, MDNS_DEFAULT_TTL_SECS);
+        assert!(validate_mdns_config(&config).is_ok());
+    }
+
+    #[test]
+    fn service_builders_set_fields() {
+        let service = HTTP
+            .with_instance("Greenhouse UI")
+            .with_txt(&[("path", "/")]);
+        assert_eq!(service.instance, Some("Greenhouse UI"));
+        assert_eq!(service.txt, &[("path", "/")]);
+        assert_eq!(service.protocol.label(), "_tcp");
+        assert_eq!(ServiceProtocol::Udp.label(), "_udp");
+    }
+
+    #[test]
+    fn valid_services_pass() {
+        let services = [
+            HTTP.with_txt(&[("path", "/"), ("v", "1")]),
+            MdnsService::new("_mqtt", ServiceProtocol::Tcp, 1883),
+        ];
+        let config = MdnsConfig::new("greenhouse").with_services(&services);
+        assert!(validate_mdns_config(&config).is_ok());
+    }
+
+    #[test]
+    fn malformed_service_names_are_rejected() {
+        for name in ["http", "_", "_web_ui", "_abcdefghijklmnop"] {
+            let services = [MdnsService::new(name, ServiceProtocol::Tcp, 80)];
+            let config = MdnsConfig::new("node").with_services(&services);
+            assert!(validate_mdns_config(&config).is_err(), "{} accepted", name);
+        }
+    }
+
+    #[test]
+    fn zero_port_is_rejected() {
+        let services = [MdnsService::new("_http", ServiceProtocol::Tcp, 0)];
+        assert_eq!(
+            validate_mdns_config(&MdnsConfig::new("node").with_services(&services)),
+            Err("mDNS service port must not be 0")
+        );
+    }
+
+    #[test]
+    fn duplicate_service_is_rejected() {
+        let services = [HTTP, MdnsService::new("_HTTP", ServiceProtocol::Tcp, 8080)];
+        assert_eq!(
+            validate_mdns_config(&MdnsConfig::new("node").with_services(&services)),
+            Err("mDNS service advertised twice")
+        );
+        let both_protocols = [HTTP, MdnsService::new("_http", ServiceProtocol::Udp, 80)];
+        assert!(
+            validate_mdns_config(&MdnsConfig::new("node").with_services(&both_protocols)).is_ok()
+        );
+    }
+
+    #[test]
+    fn too_many_services_is_rejected() {
+        let services = [HTTP; MDNS_MAX_SERVICES + 1];
+        assert_eq!(
+            validate_mdns_config(&MdnsConfig::new("node").with_services(&services)),
+            Err("too many mDNS services (maximum 4)")
+        );
+    }
+
+    #[test]
+    fn bad_txt_entries_are_rejected() {
+        let eq_key = [HTTP.with_txt(&[("a=b", "c")])];
+        assert!(validate_mdns_config(&MdnsConfig::new("node").with_services(&eq_key)).is_err());
+        let empty_key = [HTTP.with_txt(&[("", "c")])];
+        assert!(validate_mdns_config(&MdnsConfig::new("node").with_services(&empty_key)).is_err());
+        let long = "v".repeat(TXT_ENTRY_MAX_LEN);
+        let long_txt = [("k", long.as_str())];
+        let too_long = [HTTP.with_txt(&long_txt)];
+        assert_eq!(
+            validate_mdns_config(&MdnsConfig::new("node").with_services(&too_long)),
+            Err("TXT entry exceeds 255 bytes")
+        );
+    }
+
+    #[test]
+    fn host_only_announcement_len() {
+        // Header, then `greenhouse.local` A: 18-byte name, 10 fixed, 4 address.
+        assert_eq!(
+            mdns_announcement_len(&MdnsConfig::new("greenhouse")),
+            12 + 18 + 10 + 4
+        );
+    }
+
+    /// Four TXT entries sharing `fill` bytes of value between them.
+    fn filled_txt(fill: &str) -> [(&str, &str); 4] {
+        let chunk = TXT_ENTRY_MAX_LEN - 2;
+        let part = |i: usize| &fill[(i * chunk).min(fill.len())..((i + 1) * chunk).min(fill.len())];
+        [
+            ("a", part(0)),
+            ("b", part(1)),
+            ("c", part(2)),
+            ("d", part(3)),
+        ]
+    }
+
+    #[test]
+    fn announcement_at_size_limit_is_valid() {
+        let empty = filled_txt("");
+        let base = [HTTP.with_txt(&empty)];
+        let base_len = mdns_announcement_len(&MdnsConfig::new("greenhouse").with_services(&base));
+        let fill = "v".repeat(MDNS_MESSAGE_MAX_LEN - base_len);
+        let txt = filled_txt(&fill);
+        let services = [HTTP.with_txt(&txt)];
+        let config = MdnsConfig::new("greenhouse").with_services(&services);
+        assert_eq!(mdns_announcement_len(&config), MDNS_MESSAGE_MAX_LEN);
+        assert!(validate_mdns_config(&config).is_ok());
+
+        let fill = "v".repeat(MDNS_MESSAGE_MAX_LEN - base_len + 1);
+        let txt = filled_txt(&fill);
+        let services = [HTTP.with_txt(&txt)];
+        assert_eq!(
+            validate_mdns_config(&MdnsConfig::new("greenhouse").with_services(&services)),
+            Err("mDNS announcement exceeds 1024 bytes")
+        );
+    }
+
+    #[test]
+    fn many_long_txt_records_are_rejected() {
+        // Every entry is individually valid; together they cannot be announced.
+        let long = "v".repeat(TXT_ENTRY_MAX_LEN - 2);
+        let txt = [
+            ("a", long.as_str()),
+            ("b", long.as_str()),
+            ("c", long.as_str()),
+            ("d", long.as_str()),
+        ];
+        let services = [
+            HTTP.with_txt(&txt),
+            MdnsService::new("_mqtt", ServiceProtocol::Tcp, 1883).with_txt(&txt),
+        ];
+        assert_eq!(
+            validate_mdns_config(&MdnsConfig::new("node").with_services(&services)),
+            Err("mDNS announcement exceeds 1024 bytes")
+        );
+    }
+
+    #[test]
+    fn empty_instance_name_is_rejected() {
+        let services = [HTTP.with_instance("")];
+        assert!(validate_mdns_config(&MdnsConfig::new("node").with_services(&services)).is_err());
+    }
+}
@@ -21,26 +21,116 @@
 //! assert!(driver.is_connected().unwrap());
 //! assert_eq!(driver.connect_count, 1);
 //! ```
+//!
+//! Reconnect and timeout logic is exercised with a script: queue one
+//! [`MockConnect`] outcome per `connect` call and move the virtual clock with
+//! [`MockWifiDriver::advance_ms`].  Scheduled link drops and the simulated
+//! DHCP delay fire as the clock passes them; nothing depends on wall time.
+//!
+//! ```rust,ignore
+//! use juggler::wifi::mock::{MockConnect, MockWifiDriver};
+//! use juggler::wifi::{DisconnectReason, WifiDriver};
+//!
+//! let mut driver = MockWifiDriver::new();
+//! for _ in 0..3 {
+//!     driver.queue_connect(MockConnect::Fail(DisconnectReason::NoApFound)).unwrap();
+//! }
+//! driver
+//!     .queue_connect(MockConnect::SucceedThenDrop {
+//!         after_ms: 30_000,
+//!         reason: DisconnectReason::BeaconTimeout,
+//!     })
+//!     .unwrap();
+//! driver.dhcp_delay_ms = 1_500;
+//!
+//! assert!(driver.connect().is_err());
+//! assert_eq!(driver.take_disconnect_event(), Some(DisconnectReason::NoApFound));
+//! // ... two more failures, then:
+//! driver.connect().unwrap();
+//! driver.advance_ms(1_500);
+//! assert!(driver.netif_up);
+//! driver.advance_ms(28_500);
+//! assert_eq!(driver.take_disconnect_event(), Some(DisconnectReason::BeaconTimeout));
+//! ```
+//!
+//! Async code written against [`AsyncWifiDriver`] uses [`AsyncMockWifiDriver`]
+//! instead, driven by [`block_on`] (or any other executor):
+//!
+//! ```rust,ignore
+//! use juggler::wifi::mock::{block_on, AsyncMockWifiDriver};
+//! use juggler::wifi::AsyncWifiDriver;
+//!
+//! let mut driver = AsyncMockWifiDriver::new();
+//! block_on(driver.connect()).unwrap();
+//! assert_eq!(driver.driver.connect_count, 1);
+//! ```
+
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+use heapless::Deque;
 
-use super::{WifiDriver, WifiPowerSave};
+use super::{AsyncWifiDriver, DisconnectReason, ScanResults, WifiDriver, WifiPowerSave};
+
+/// Maximum number of queued [`MockConnect`] outcomes.
+pub const MOCK_CONNECT_SCRIPT_LEN: usize = 16;
 
 /// Error type for [`MockWifiDriver`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MockWifiError {
     /// Returned when `fail_connect` is set to `true`.
     ConnectFailed,
+    /// Returned when `fail_scan` is set to `true`.
+    ScanFailed,
+    /// Returned by [`MockWifiDriver::queue_connect`] when the script already
+    /// holds [`MOCK_CONNECT_SCRIPT_LEN`] outcomes.
+    ScriptFull,
 }
 
 impl core::fmt::Display for MockWifiError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "mock wifi connect failed")
+        match self {
+            Self::ConnectFailed => write!(f, "mock wifi connect failed"),
+            Self::ScanFailed => write!(f, "mock wifi scan failed"),
+            Self::ScriptFull => write!(f, "mock wifi connect script full"),
+        }
     }
 }
 
+/// Scripted outcome of one [`WifiDriver::connect`] call on [`MockWifiDriver`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockConnect {
+    /// Association succeeds and the link stays up.
+    Succeed,
+    /// Association succeeds; the link drops with `reason` once the virtual
+    /// clock has advanced `after_ms` past the connect.
+    SucceedThenDrop {
+        /// Virtual milliseconds between connect and drop.
+        after_ms: u64,
+        /// Reason reported for the drop.
+        reason: DisconnectReason,
+    },
+    /// `connect` returns [`MockWifiError::ConnectFailed`] and reports
+    /// the reason as a disconnect event, as real drivers do.
+    Fail(DisconnectReason),
+}
+
 /// Mock implementation of [`WifiDriver`] for host-side unit tests.
 ///
-/// All state is public for direct assertion in tests.
+/// Build one with [`new`](Self::new) or `Default`; the script state is
+/// private, so struct literals do not compile.  Everything else is public
+/// for direct assertion in tests.
 /// Set `fail_connect` to `true` before calling `connect()` to simulate failure.
+/// Fill `scan_results` (e.g. with [`insert_scan_result`][super::insert_scan_result])
+/// to control what `scan()` returns; set `fail_scan` to simulate a scan error.
+///
+/// For multi-step scenarios, queue per-call outcomes with
+/// [`queue_connect`](Self::queue_connect) (they take precedence over
+/// `fail_connect`), set `dhcp_delay_ms`, and drive time with
+/// [`advance_ms`](Self::advance_ms).  Disconnects the driver reports on its
+/// own — failed scripted attempts and scheduled drops — are collected with
+/// [`take_disconnect_event`](Self::take_disconnect_event).
 pub struct MockWifiDriver {
     pub configured: bool,
     pub started: bool,
@@ -49,6 +139,23 @@ pub struct MockWifiDriver {
     pub connect_count: u32,
     pub fail_connect: bool,
     pub power_save: WifiPowerSave,
+    pub scan_results: ScanResults,
+    pub scan_count: u32,
+    pub fail_scan: bool,
+    /// Every `connect` call, successful or not.
+    pub connect_attempts: u32,
+    /// Virtual clock in milliseconds; only [`advance_ms`](Self::advance_ms)
+    /// moves it.
+    pub now_ms: u64,
+    /// Virtual time between association and `netif_up`; `0` brings the
+    /// interface up inside `connect`.
+    pub dhcp_delay_ms: u64,
+    /// Reason of the most recent driver-reported disconnect.
+    pub last_disconnect_reason: Option<DisconnectReason>,
+    connect_script: Deque<MockConnect, MOCK_CONNECT_SCRIPT_LEN>,
+    dhcp_ready_at: Option<u64>,
+    drop_at: Option<(u64, DisconnectReason)>,
+    pending_disconnect: Option<DisconnectReason>,
 }
 
 impl MockWifiDriver {
@@ -62,6 +169,87 @@ impl MockWifiDriver {
             connect_count: 0,
             fail_connect: false,
             power_save: WifiPowerSave::default(),
+            scan_results: ScanResults::new(),
+            scan_count: 0,
+            fail_scan: false,
+            connect_attempts: 0,
+            now_ms: 0,
+            dhcp_delay_ms: 0,
+            last_disconnect_reason: None,
+            connect_script: Deque::new(),
+            dhcp_ready_at: None,
+            drop_at: None,
+            pending_disconnect: None,
+        }
+    }
+
+    /// Queues the outcome of a future `connect` call, in call order.
+    ///
+    /// Returns `Err(MockWifiError::ScriptFull)` once
+    /// [`MOCK_CONNECT_SCRIPT_LEN`] outcomes are pending.
+    pub fn queue_connect(&mut self, outcome: MockConnect) -> Result<(), MockWifiError> {
+        self.connect_script
+            .push_back(outcome)
+            .map_err(|_| MockWifiError::ScriptFull)
+    }
+
+    /// Number of queued `connect` outcomes not yet consumed.
+    pub fn pending_connects(&self) -> usize {
+        self.connect_script.len()
+    }
+
+    /// Drops the current link with `reason` once the virtual clock has
+    /// advanced `after_ms`, replacing any earlier scheduled drop.
+    ///
+    /// Has no effect if the link is already down at that time.
+    pub fn schedule_disconnect(&mut self, after_ms: u64, reason: DisconnectReason) {
+        self.drop_at = Some((self.now_ms.saturating_add(after_ms), reason));
+    }
+
+    /// Advances the virtual clock and fires any DHCP completion or
+    /// scheduled drop that falls due.
+    pub fn advance_ms(&mut self, ms: u64) {
+        self.now_ms = self.now_ms.saturating_add(ms);
+        if let Some(ready_at) = self.dhcp_ready_at {
+            if ready_at <= self.now_ms {
+                self.dhcp_ready_at = None;
+                self.netif_up = self.connected;
+            }
+        }
+        if let Some((at, reason)) = self.drop_at {
+            if at <= self.now_ms {
+                self.drop_at = None;
+                if self.connected {
+                    self.connected = false;
+                    self.netif_up = false;
+                    self.dhcp_ready_at = None;
+                    self.report_disconnect(reason);
+                }
+            }
+        }
+    }
+
+    /// Takes the disconnect the driver reported since the last call, if any.
+    ///
+    /// Only the most recent one is kept.  Application-initiated
+    /// [`disconnect`](WifiDriver::disconnect) calls are not reported.
+    pub fn take_disconnect_event(&mut self) -> Option<DisconnectReason> {
+        self.pending_disconnect.take()
+    }
+
+    fn report_disconnect(&mut self, reason: DisconnectReason) {
+        self.last_disconnect_reason = Some(reason);
+        self.pending_disconnect = Some(reason);
+    }
+
+    fn associate(&mut self) {
+        self.connected = true;
+        self.connect_count += 1;
+        if self.dhcp_delay_ms == 0 {
+            self.netif_up = true;
+        } else {
+            self.netif_up = false;
+            self.dhcp_ready_at = Some(self.now_ms.saturating_add(self.dhcp_delay_ms));
         }
     }
 }
@@ -86,22 +274,148 @@ impl WifiDriver for MockWifiDriver {
     }
 
     fn connect(&mut self) -> Result<(), Self::Error> {
-        if self.fail_connect {
-            return Err(MockWifiError::ConnectFailed);
+        self.connect_attempts += 1;
+        match self.connect_script.pop_front() {
+            Some(MockConnect::Fail(reason)) => {
+                self.report_disconnect(reason);
+                Err(MockWifiError::ConnectFailed)
+            }
+            Some(MockConnect::SucceedThenDrop { after_ms, reason }) => {
+                self.associate();
+                self.schedule_disconnect(after_ms, reason);
+                Ok(())
+            }
+            Some(MockConnect::Succeed) => {
+                self.associate();
+                Ok(())
+            }
+            None if self.fail_connect => Err(MockWifiError::ConnectFailed),
+            None => {
+                self.associate();
+                Ok(())
+            }
         }
-        self.connected = true;
-        self.netif_up = true;
-        self.connect_count += 1;
-        Ok(())
     }
 
     fn disconnect(&mut self) -> Result<(), Self::Error> {
         self.connected = false;
         self.netif_up = false;
+        self.dhcp_ready_at = None;
+        self.drop_at = None;
         Ok(())
     }
 
     fn is_connected(&self) -> Result<bool, Self::Error> {
         Ok(self.connected)
     }
+
+    fn scan(&mut self) -> Result<ScanResults, Self::Error> {
+        if self.fail_scan {
+            return Err(MockWifiError::ScanFailed);
+        }
+        self.scan_count += 1;
+        Ok(self.scan_results.clone())
+    }
+}
+
+// ─── AsyncMockWifiDriver ────────────────────────────────────────────────────
+
+/// Mock implementation of [`AsyncWifiDriver`] for host-side unit tests.
+///
+/// Wraps a [`MockWifiDriver`], so failure flags, scan results, and counters
+/// behave exactly as in the blocking mock; set and assert them on
+/// [`driver`](Self::driver).  Set `yield_polls` to make every async call
+/// return `Pending` that many times before completing, which checks that the
+/// code under test keeps polling (and wakes) correctly.
+pub struct AsyncMockWifiDriver {
+    pub driver: MockWifiDriver,
+    pub yield_polls: u32,
+}
+
+impl AsyncMockWifiDriver {
+    /// Create a new mock driver in the disconnected state.
+    pub fn new() -> Self {
+        Self {
+            driver: MockWifiDriver::new(),
+            yield_polls: 0,
+        }
+    }
+
+    async fn yield_now(&self) {
+        for _ in 0..self.yield_polls {
+            YieldNow { yielded: false }.await;
+        }
+    }
+}
+
+impl Default for AsyncMockWifiDriver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AsyncWifiDriver for AsyncMockWifiDriver {
+    type Error = MockWifiError;
+
+    async fn configure(&mut self, ssid: &str, password: &str) -> Result<(), Self::Error> {
+        self.yield_now().await;
+        self.driver.configure(ssid, password)
+    }
+
+    async fn start(&mut self) -> Result<(), Self::Error> {
+        self.yield_now().await;
+        self.driver.start()
+    }
+
+    async fn connect(&mut self) -> Result<(), Self::Error> {
+        self.yield_now().await;
+        self.driver.connect()
+    }
+
+    async fn disconnect(&mut self) -> Result<(), Self::Error> {
+        self.yield_now().await;
+        self.driver.disconnect()
+    }
+
+    fn is_connected(&self) -> Result<bool, Self::Error> {
+        self.driver.is_connected()
+    }
+
+    async fn scan(&mut self) -> Result<ScanResults, Self::Error> {
+        self.yield_now().await;
+        self.driver.scan()
+    }
+}
+
+/// Returns `Pending` once, waking itself first.
+struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: core::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+/// Minimal executor for host tests: polls `future` until it completes.
+///
+/// Uses a no-op waker and re-polls in a loop, so it suits mocks whose
+/// futures always make progress; a future waiting on a real event source
+/// would spin forever.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
 }
@@ -5,17 +5,96 @@
 //! - [`WifiDriver`] — hardware-agnostic Wi-Fi driver interface
 //! - [`WiFiConfig`] — connection configuration (SSID, password, connect mode)
 //! - [`ConnectMode`] — blocking vs. non-blocking association strategy
-//! - [`mock::MockWifiDriver`] — test double for host-side unit tests
-//!   (requires the `mock` feature or `#[cfg(test)]`)
+//! - [`WifiNetworkList`] — bounded multi-network credential list with
+//!   priority; [`rank_networks`] orders it against a scan result
+//! - [`ApInfo`] / [`ScanResults`] — typed, bounded scan results returned by
+//!   [`WifiDriver::scan`]
+//! - [`WifiAuthMethod`] — WPA2/WPA3 personal or 802.1X (PEAP, EAP-TLS)
+//!   station authentication
+//! - [`IpConfig`] — DHCP or static IPv4 addressing for the station
+//! - [`Country`] — regulatory domain: allowed channels and TX power limit,
+//!   applied by the platform managers at start
+//! - [`WifiPhy`] — 802.11b/g/n protocol set, HT20/HT40 bandwidth, and
+//!   Espressif long range, checked by [`validate_phy`]
+//! - [`WifiFastConnectCache`] — CRC-protected BSSID, channel, PMK, and IP
+//!   lease kept in RTC memory for scan-free reconnects after deep sleep
+//! - [`ApStationTable`] / [`ApStationEvent`] — SoftAP client list with
+//!   leased addresses, plus max-clients and MAC allowlist admission
+//! - [`MdnsConfig`] — `.local` hostname and DNS-SD services advertised over
+//!   multicast DNS; [`WiFiConfig::with_hostname`] sets the DHCP hostname
+//! - [`DisconnectReason`] / [`DisconnectClass`] — typed 802.11 + ESP
+//!   disconnect reasons and what they imply for reconnecting
+//! - [`WifiLinkSupervisor`] — link state machine with backoff-driven
+//!   reconnect, shared by the platform supervisors
+//! - [`LinkQualityMonitor`] — smoothed RSSI with weak / recovered /
+//!   roam-candidate events
+//! - [`AsyncWifiDriver`] — async variant of the driver interface, for the
+//!   async-only bare-metal tier
+//! - [`mock::MockWifiDriver`] / [`mock::AsyncMockWifiDriver`] — test doubles
+//!   for host-side unit tests (requires the `mock` feature or `#[cfg(test)]`)
+//! - `conformance::run_contract_tests` / `run_async_contract_tests` — contract
+//!   suites for driver implementations (requires the `conformance` feature)
 //!
 //! # Feature flags
 //!
 //! | Feature | What it enables                                              |
 //! |:--------|:-------------------------------------------------------------|
-//! | `mock`  | `MockWifiDriver` for downstream host-side tests              |
-
+//! | `mock`  | `MockWifiDriver` / `AsyncMockWifiDriver` for host-side tests |
+//! | `conformance` | `conformance::run_contract_tests` for driver implementations |
+
+mod auth;
+#[cfg(any(test, feature = "conformance"))]
+pub mod conformance;
+mod country;
+mod fast_connect;
+mod ip;
+mod link;
+mod mdns;
 #[cfg(any(test, feature = "mock"))]
 pub mod mock;
+mod networks;
+mod phy;
+mod quality;
+mod reason;
+mod scan;
+mod stations;
+
+pub use auth::{validate_auth_method, WifiAuthMethod, EAP_FIELD_MAX_LEN, WPA2_PASSPHRASE_MIN_LEN};
+pub use country::{Country, DEFAULT_ANTENNA_GAIN_DBI};
+pub use fast_connect::{
+    derive_pmk, pmk_applicable, WifiFastConnectCache, FAST_CONNECT_TIMEOUT_SECS, PMK_LEN,
+};
+pub use ip::{
+    netmask_prefix_len, validate_ap_subnet, validate_ip_config, ApSubnet, IpConfig,
+    AP_DHCP_POOL_MAX,
+};
+pub use link::{
+    next_link_state, WifiLinkAction, WifiLinkEvent, WifiLinkState, WifiLinkSupervisor,
+    LINK_RETRY_BASE_MS, LINK_RETRY_MAX_MS,
+};
+
+pub use mdns::{
+    mdns_announcement_len, validate_hostname, validate_mdns_config, MdnsConfig, MdnsService,
+    ServiceProtocol, HOSTNAME_MAX_LEN, MDNS_DEFAULT_TTL_SECS, MDNS_MAX_SERVICES,
+    MDNS_MAX_TXT_ENTRIES, MDNS_MESSAGE_MAX_LEN,
+};
+pub use networks::{
+    priority_order, rank_networks, select_network, validate_network_list, NetworkOrder,
+    WifiNetwork, WifiNetworkList, MAX_NETWORKS,
+};
+pub use phy::{validate_phy, WifiBandwidth, WifiPhy, WifiProtocol};
+pub use quality::{
+    validate_link_quality_config, LinkQuality, LinkQualityConfig, LinkQualityEvent,
+    LinkQualityMonitor, QUALITY_DEFAULT_SMOOTHING_PERCENT, QUALITY_RECOVERED_DBM, QUALITY_ROAM_DBM,
+    QUALITY_WEAK_DBM,
+};
+pub use reason::{classify_disconnect, DisconnectClass, DisconnectReason};
+pub use scan::{
+    insert_scan_result, scan_candidates, ApInfo, AuthMode, ScanResults, SCAN_MAX_RESULTS,
+};
+pub use stations::{
+    ApRejectReason, ApStation, ApStationEvent, ApStationTable, ApStations, AP_ALLOWLIST_MAX,
+};
 
 // ─── Constants ──────────────────────────────────────────────────────────────
 
@@ -39,15 +118,24 @@ pub const AP_PASSWORD_MIN_LEN: usize = 8;
 /// Lowest 2.4 GHz channel a SoftAP may be configured on.
 pub const AP_CHANNEL_MIN: u8 = 1;
 
-/// Highest 2.4 GHz channel a SoftAP may be configured on.
+/// Highest 2.4 GHz channel a SoftAP may be configured on when no
+/// [`Country`] is set.
 ///
 /// Channels above 13 are not permitted in most regulatory domains, so the
-/// validator caps the range at 13 to stay portable across regions.
+/// validator caps the range at 13.  Channels 12 and 13 are still illegal in
+/// the US and Canada; set [`ApConfig::with_country`] to validate against the
+/// actual domain.
 pub const AP_CHANNEL_MAX: u8 = 13;
 
 /// Default maximum number of stations that may associate with a SoftAP.
 pub const AP_MAX_CONNECTIONS_DEFAULT: u8 = 4;
 
+/// Most stations a SoftAP may be configured to accept.
+///
+/// The ESP-IDF default for `ESP_WIFI_MAX_CONN_NUM`, which `esp-radio` also
+/// applies; it sizes [`ApStations`].
+pub const AP_MAX_CONNECTIONS_MAX: u8 = 10;
+
 // ─── Validation ─────────────────────────────────────────────────────────────
 
 /// Returns `Ok(())` if `ssid` fits within the ESP-IDF limit, or an error
@@ -73,11 +161,18 @@ pub fn validate_password(password: &str) -> Result<(), &'static str> {
 
 /// Returns `Ok(())` if `config` is a valid SoftAP configuration.
 ///
-/// Validates the SSID via [`validate_ssid`], the channel against
-/// [`AP_CHANNEL_MIN`]`..=`[`AP_CHANNEL_MAX`], and `max_connections >= 1`.
-/// When a password is present, it must be at least [`AP_PASSWORD_MIN_LEN`]
-/// bytes (the WPA2-Personal minimum) and at most [`PASSWORD_MAX_LEN`] bytes —
-/// the upper bound is delegated to [`validate_password`].
+/// Checks, in order:
+///
+/// - the SSID, via [`validate_ssid`];
+/// - the password, when present: at least [`AP_PASSWORD_MIN_LEN`] bytes (the
+///   WPA2-Personal minimum) and at most [`PASSWORD_MAX_LEN`] bytes, the upper
+///   bound delegated to [`validate_password`];
+/// - the channel, against the configured [`Country`] (or
+///   [`AP_CHANNEL_MIN`]`..=`[`AP_CHANNEL_MAX`] when none is set);
+/// - `max_connections`, in `1..=`[`AP_MAX_CONNECTIONS_MAX`];
+/// - the allowlist, when set: non-empty, at most [`AP_ALLOWLIST_MAX`] entries;
+/// - the PHY settings, via [`validate_phy`];
+/// - the subnet, via [`validate_ap_subnet`].
 pub fn validate_ap_config(config: &ApConfig<'_>) -> Result<(), &'static str> {
     validate_ssid(config.ssid)?;
     if let Some(password) = config.password {
@@ -86,13 +181,31 @@ pub fn validate_ap_config(config: &ApConfig<'_>) -> Result<(), &'static str> {
         }
         validate_password(password)?;
     }
-    if config.channel < AP_CHANNEL_MIN || config.channel > AP_CHANNEL_MAX {
-        return Err("AP channel must be in the range 1..=13");
+    match config.country {
+        Some(country) if !country.allows_channel(config.channel) => {
+            return Err("AP channel is not allowed in the configured country");
+        }
+        Some(_) => {}
+        None if config.channel < AP_CHANNEL_MIN || config.channel > AP_CHANNEL_MAX => {
+            return Err("AP channel must be in the range 1..=13");
+        }
+        None => {}
     }
     if config.max_connections < 1 {
         return Err("AP max_connections must be at least 1");
     }
-    Ok(())
+    if config.max_connections > AP_MAX_CONNECTIONS_MAX {
+        return Err("AP max_connections must be at most 10");
+    }
+    match config.allowlist {
+        Some([]) => return Err("AP allowlist must not be empty"),
+        Some(macs) if macs.len() > AP_ALLOWLIST_MAX => {
+            return Err("AP allowlist exceeds 16 entries");
+        }
+        _ => {}
+    }
+    validate_phy(&config.phy)?;
+    validate_ap_subnet(&config.subnet)
 }
 
 // ─── ConnectMode ────────────────────────────────────────────────────────────
@@ -213,13 +326,42 @@ impl TxPowerLevel {
 ///     .with_power_save(WifiPowerSave::MinModem) // optional: modem sleep for battery savings
 ///     .with_tx_power(TxPowerLevel::Low);       // optional: reduce transmit power
 /// ```
+///
+/// Devices that move between sites can carry several networks instead —
+/// see [`WiFiConfig::from_networks`].
+///
+/// The struct is `#[non_exhaustive]` so settings can be added without
+/// breaking callers: build it with `new` or `from_networks` and the `with_*`
+/// methods rather than a struct literal.  The fields stay public for reading
+/// and for assignment.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct WiFiConfig<'a> {
     pub ssid: &'a str,
     pub password: &'a str,
     pub connect_mode: ConnectMode,
     pub power_save: WifiPowerSave,
     pub tx_power: TxPowerLevel,
+    /// Additional networks set via [`WiFiConfig::from_networks`]; empty for a
+    /// single-network config.  Read through [`WiFiConfig::networks`].
+    pub network_list: WifiNetworkList<'a>,
+    /// Station IPv4 addressing; [`IpConfig::Dhcp`] by default.
+    pub ip: IpConfig,
+    /// Authentication method for the `ssid`/`password` network;
+    /// [`WifiAuthMethod::Wpa2Personal`] by default.  Entries of a
+    /// [`WiFiConfig::from_networks`] list carry their own.
+    pub auth: WifiAuthMethod<'a>,
+    /// Hostname sent to the DHCP server (option 12); `None` keeps the
+    /// platform default (`espressif` on ESP-IDF, none on bare-metal).
+    pub hostname: Option<&'a str>,
+    /// Regulatory domain applied to the radio at start; `None` keeps the
+    /// driver default.
+    pub country: Option<Country>,
+    /// Association details from the previous wake, tried before scanning.
+    pub fast_connect: Option<WifiFastConnectCache>,
+    /// Station protocol set and bandwidth, applied by the ESP-IDF managers;
+    /// [`WifiPhy::DEFAULT`] leaves the driver's 802.11b/g/n at 20 MHz.
+    pub phy: WifiPhy,
 }
 
 impl<'a> WiFiConfig<'a> {
@@ -233,9 +375,78 @@ impl<'a> WiFiConfig<'a> {
             connect_mode: ConnectMode::default(),
             power_save: WifiPowerSave::default(),
             tx_power: TxPowerLevel::default(),
+            network_list: WifiNetworkList::new(),
+            ip: IpConfig::default(),
+            auth: WifiAuthMethod::default(),
+            hostname: None,
+            country: None,
+            fast_connect: None,
+            phy: WifiPhy::DEFAULT,
         }
     }
 
+    /// Creates a configuration that tries several networks with fallback.
+    ///
+    /// `ssid` and `password` are set to the highest-priority entry so that
+    /// logging and single-network code paths keep working; the managers use
+    /// [`WiFiConfig::networks`] to walk the full list.  An empty list yields
+    /// an empty `ssid`, which fails validation at init time.
+    ///
+    /// In blocking mode each candidate gets the full connect timeout before
+    /// the manager falls back to the next one.
+    pub fn from_networks(networks: WifiNetworkList<'a>) -> Self {
+        let (ssid, password, auth) = networks
+            .preferred()
+            .map_or(("", "", WifiAuthMethod::default()), |n| {
+                (n.ssid, n.password, n.auth)
+            });
+        Self {
+            network_list: networks,
+            auth,
+            ..Self::new(ssid, password)
+        }
+    }
+
+    /// Returns the networks to try, as a list.
+    ///
+    /// For a config built with [`WiFiConfig::new`] this is a single entry made
+    /// from `ssid`/`password`/`auth`; for [`WiFiConfig::from_networks`] it is
+    /// the list passed in.
+    pub fn networks(&self) -> WifiNetworkList<'a> {
+        if self.network_list.is_empty() {
+            WifiNetworkList::single_network(
+                WifiNetwork::new(self.ssid, self.password).with_auth(self.auth),
+            )
+        } else {
+            self.network_list.clone()
+        }
+    }
+
+    /// Sets the authentication method for the `ssid`/`password` network.
+    ///
+    /// ```ignore
+    /// let config = WiFiConfig::new("campus", "").with_auth(WifiAuthMethod::EapPeap {
+    ///     identity: "anonymous",
+    ///     username: "alice",
+    ///     password: "secret",
+    /// });
+    /// ```
+    pub fn with_auth(mut self, auth: WifiAuthMethod<'a>) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Sets the station hostname announced to the DHCP server.
+    ///
+    /// Checked with [`validate_hostname`] when the manager initialises.  The
+    /// name is advertised over DHCP only; to answer `<hostname>.local`
+    /// queries as well, start the platform's mDNS responder with an
+    /// [`MdnsConfig`] carrying the same name.
+    pub fn with_hostname(mut self, hostname: &'a str) -> Self {
+        self.hostname = Some(hostname);
+        self
+    }
+
     /// Sets a blocking connection with the given timeout in seconds.
     pub fn with_timeout(mut self, secs: u64) -> Self {
         self.connect_mode = ConnectMode::Blocking { timeout_secs: secs };
@@ -272,6 +483,35 @@ impl<'a> WiFiConfig<'a> {
         self
     }
 
+    /// Sets the station IPv4 addressing mode.
+    ///
+    /// Defaults to [`IpConfig::Dhcp`].  A static configuration is checked
+    /// with [`validate_ip_config`] when the manager initialises.  With
+    /// several networks (see [`WiFiConfig::from_networks`]) the same
+    /// addressing applies to every one of them.
+    pub fn with_ip_config(mut self, ip: IpConfig) -> Self {
+        self.ip = ip;
+        self
+    }
+
+    /// Shorthand for [`with_ip_config`][Self::with_ip_config] with
+    /// [`IpConfig::Static`].
+    pub fn with_static_ip(
+        mut self,
+        ip: core::net::Ipv4Addr,
+        netmask: core::net::Ipv4Addr,
+        gateway: core::net::Ipv4Addr,
+        dns: Option<core::net::Ipv4Addr>,
+    ) -> Self {
+        self.ip = IpConfig::Static {
+            ip,
+            netmask,
+            gateway,
+            dns,
+        };
+        self
+    }
+
     /// Sets the Wi-Fi transmit power level applied after `wifi.start()`.
     ///
     /// Defaults to [`TxPowerLevel::Medium`] (~13 dBm).
@@ -282,6 +522,57 @@ impl<'a> WiFiConfig<'a> {
         self.tx_power = level;
         self
     }
+
+    /// Sets the regulatory domain the managers apply to the radio at start.
+    ///
+    /// Restricts the radio to the domain's channels and caps the transmit
+    /// power — see [`WiFiConfig::effective_tx_power`].
+    pub fn with_country(mut self, country: Country) -> Self {
+        self.country = Some(country);
+        self
+    }
+
+    /// Reconnects using the AP recorded in `cache` before falling back to a
+    /// full connect.
+    ///
+    /// When `cache` is valid and belongs to one of the configured networks
+    /// (see [`WifiFastConnectCache::network_index`]), the ESP-IDF manager
+    /// skips the scan, pins the cached BSSID and channel, and uses the cached
+    /// PMK in place of the passphrase; if the AP does not answer within
+    /// [`FAST_CONNECT_TIMEOUT_SECS`] it connects as usual.  An invalid or
+    /// foreign cache is ignored.  On bare metal, pass the cache to the
+    /// manager's `connect_fast` instead.
+    pub fn with_fast_connect(mut self, cache: WifiFastConnectCache) -> Self {
+        self.fast_connect = Some(cache);
+        self
+    }
+
+    /// Sets the 802.11 protocols the station may use.
+    ///
+    /// Defaults to [`WifiProtocol::Bgn`].  Checked together with the
+    /// bandwidth by [`validate_phy`] when the manager initialises.  A
+    /// station limited to [`WifiProtocol::Lr`] can only join an LR-only
+    /// Espressif AP; use [`WifiProtocol::BgnLr`] to keep ordinary APs
+    /// reachable.
+    pub fn with_protocol(mut self, protocol: WifiProtocol) -> Self {
+        self.phy.protocol = protocol;
+        self
+    }
+
+    /// Sets the station channel bandwidth; [`WifiBandwidth::Ht40`] requires
+    /// an 802.11n protocol without long range.
+    pub fn with_bandwidth(mut self, bandwidth: WifiBandwidth) -> Self {
+        self.phy.bandwidth = bandwidth;
+        self
+    }
+
+    /// The transmit power to apply: [`tx_power`](Self::tx_power), lowered
+    /// by [`Country::clamp_tx_power`] when a country is set.
+    pub fn effective_tx_power(&self) -> TxPowerLevel {
+        self.country.map_or(self.tx_power, |country| {
+            country.clamp_tx_power(self.tx_power)
+        })
+    }
 }
 
 // ─── ApConfig ───────────────────────────────────────────────────────────────
@@ -303,12 +594,25 @@ pub struct ApConfig<'a> {
     pub ssid: &'a str,
     /// WPA2 pre-shared key, or `None` for an open network.
     pub password: Option<&'a str>,
-    /// 2.4 GHz channel (1..=13).
+    /// 2.4 GHz channel (1..=13, or the range of [`country`](Self::country)).
     pub channel: u8,
     /// Maximum number of stations that may associate.
     pub max_connections: u8,
     /// Transmit power level applied after the AP starts.
     pub tx_power: TxPowerLevel,
+    /// Regulatory domain applied to the radio at start; `None` keeps the
+    /// driver default.
+    pub country: Option<Country>,
+    /// AP address, netmask, and DHCP pool.
+    pub subnet: ApSubnet,
+    /// MAC addresses allowed to associate; `None` admits any station.
+    ///
+    /// Enforced by the managers through an [`ApStationTable`], which
+    /// deauthenticates stations that are not listed.
+    pub allowlist: Option<&'a [[u8; 6]]>,
+    /// AP protocol set and bandwidth, applied by the ESP-IDF managers;
+    /// [`WifiPhy::DEFAULT`] leaves the driver's 802.11b/g/n at 20 MHz.
+    pub phy: WifiPhy,
 }
 
 impl<'a> Clone for ApConfig<'a> {
@@ -319,6 +623,10 @@ impl<'a> Clone for ApConfig<'a> {
             channel: self.channel,
             max_connections: self.max_connections,
             tx_power: self.tx_power,
+            country: self.country,
+            subnet: self.subnet,
+            allowlist: self.allowlist,
+            phy: self.phy,
         }
     }
 }
@@ -331,6 +639,10 @@ impl core::fmt::Debug for ApConfig<'_> {
             .field("channel", &self.channel)
             .field("max_connections", &self.max_connections)
             .field("tx_power", &self.tx_power)
+            .field("country", &self.country)
+            .field("subnet", &self.subnet)
+            .field("allowlist", &self.allowlist)
+            .field("phy", &self.phy)
             .finish()
     }
 }
@@ -354,6 +666,10 @@ impl<'a> ApConfig<'a> {
             channel: AP_CHANNEL_MIN,
             max_connections: AP_MAX_CONNECTIONS_DEFAULT,
             tx_power: TxPowerLevel::default(),
+            country: None,
+            subnet: ApSubnet::DEFAULT,
+            allowlist: None,
+            phy: WifiPhy::DEFAULT,
         }
     }
 
@@ -369,16 +685,22 @@ impl<'a> ApConfig<'a> {
             channel: AP_CHANNEL_MIN,
             max_connections: AP_MAX_CONNECTIONS_DEFAULT,
             tx_power: TxPowerLevel::default(),
+            country: None,
+            subnet: ApSubnet::DEFAULT,
+            allowlist: None,
+            phy: WifiPhy::DEFAULT,
         }
     }
 
-    /// Sets the 2.4 GHz channel (validated to 1..=13 by [`validate_ap_config`]).
+    /// Sets the 2.4 GHz channel (validated to 1..=13, or the range of the
+    /// configured [`Country`], by [`validate_ap_config`]).
     pub fn with_channel(mut self, channel: u8) -> Self {
         self.channel = channel;
         self
     }
 
-    /// Sets the maximum number of associated stations.
+    /// Sets the maximum number of associated stations (at most
+    /// [`AP_MAX_CONNECTIONS_MAX`]).
     pub fn with_max_connections(mut self, max: u8) -> Self {
         self.max_connections = max;
         self
@@ -389,26 +711,74 @@ impl<'a> ApConfig<'a> {
         self.tx_power = level;
         self
     }
+
+    /// Sets the regulatory domain: [`validate_ap_config`] then checks the
+    /// channel against it, and the managers apply it to the radio at start.
+    pub fn with_country(mut self, country: Country) -> Self {
+        self.country = Some(country);
+        self
+    }
+
+    /// Sets the AP address and netmask (default `192.168.4.1/24`).
+    ///
+    /// The DHCP pool is not moved along with it; set one inside the new
+    /// subnet with [`with_dhcp_range`](Self::with_dhcp_range).
+    pub fn with_ip(mut self, ip: core::net::Ipv4Addr, netmask: core::net::Ipv4Addr) -> Self {
+        self.subnet.ip = ip;
+        self.subnet.netmask = netmask;
+        self
+    }
+
+    /// Sets the inclusive DHCP pool (default `192.168.4.10`–`192.168.4.20`).
+    pub fn with_dhcp_range(mut self, start: core::net::Ipv4Addr, end: core::net::Ipv4Addr) -> Self {
+        self.subnet.dhcp_start = start;
+        self.subnet.dhcp_end = end;
+        self
+    }
+
+    /// Admits only stations whose MAC address is in `macs` (at most
+    /// [`AP_ALLOWLIST_MAX`] entries).
+    pub fn with_allowlist(mut self, macs: &'a [[u8; 6]]) -> Self {
+        self.allowlist = Some(macs);
+        self
+    }
+
+    /// Sets the 802.11 protocols the AP offers (default
+    /// [`WifiProtocol::Bgn`]).
+    ///
+    /// An AP limited to [`WifiProtocol::Lr`] is invisible to anything but
+    /// LR-capable Espressif stations.
+    pub fn with_protocol(mut self, protocol: WifiProtocol) -> Self {
+        self.phy.protocol = protocol;
+        self
+    }
+
+    /// Sets the AP channel bandwidth, checked with the protocol by
+    /// [`validate_ap_config`].
+    pub fn with_bandwidth(mut self, bandwidth: WifiBandwidth) -> Self {
+        self.phy.bandwidth = bandwidth;
+        self
+    }
+
+    /// The transmit power to apply: [`tx_power`](Self::tx_power), lowered
+    /// by [`Country::clamp_tx_power`] when a country is set.
+    pub fn effective_tx_power(&self) -> TxPowerLevel {
+        self.country.map_or(self.tx_power, |country| {
+            country.clamp_tx_power(self.tx_power)
+        })
+    }
 }
 
 // ─── Disconnect reason mapping ──────────────────────────────────────────────
 
-/// Maps common ESP-IDF Wi-Fi disconnect reason codes to human-readable names.
+/// Maps ESP-IDF Wi-Fi disconnect reason codes to human-readable names.
 ///
-/// Codes follow `wifi_err_reason_t` in ESP-IDF.
+/// Codes follow `wifi_err_reason_t` in ESP-IDF; see [`DisconnectReason`] for
+/// the full typed table and [`DisconnectReason::class`] for classification.
 /// Returns `None` for unmapped codes so callers can log the raw number instead
 /// of a misleading `"unknown"` string.
 pub fn wifi_disconnect_reason_name(reason: u16) -> Option<&'static str> {
-    match reason {
-        2 => Some("AUTH_EXPIRE"),
-        15 => Some("4WAY_HANDSHAKE_TIMEOUT"),
-        200 => Some("BEACON_TIMEOUT"),
-        201 => Some("NO_AP_FOUND"),
-        202 => Some("AUTH_FAIL"),
-        203 => Some("ASSOC_FAIL"),
-        204 => Some("HANDSHAKE_TIMEOUT"),
-        _ => None,
-    }
+    DisconnectReason::from_code(reason).name()
 }
 
 // ─── WifiDriver trait ───────────────────────────────────────────────────────
@@ -422,8 +792,10 @@ pub fn wifi_disconnect_reason_name(reason: u16) -> Option<&'static str> {
 /// # Implementors
 ///
 /// - `rustyfarian_esp_idf_network::wifi::WiFiManager` — ESP-IDF driver
-/// - `rustyfarian_esp_hal_network::wifi::EspHalWifiDriver` — bare-metal driver (future)
 /// - [`mock::MockWifiDriver`] — test double (behind `mock` feature / `#[cfg(test)]`)
+///
+/// The bare-metal tier is async-only and implements [`AsyncWifiDriver`]
+/// instead.
 pub trait WifiDriver {
     /// Driver-specific error type.
     type Error: core::fmt::Debug;
@@ -442,6 +814,69 @@ pub trait WifiDriver {
 
     /// Returns `true` if the station is associated and authenticated.
     fn is_connected(&self) -> Result<bool, Self::Error>;
+
+    /// Scans for nearby access points.
+    ///
+    /// Blocks until the scan completes (typically 1–3 s across all channels).
+    /// The driver must be started.  Results are sorted strongest-first and
+    /// capped at [`SCAN_MAX_RESULTS`]; see [`insert_scan_result`].
+    ///
+    /// The default returns no access points, so drivers written before this
+    /// method existed keep compiling; [`rank_networks`] then falls back to
+    /// plain priority order.
+    fn scan(&mut self) -> Result<ScanResults, Self::Error> {
+        Ok(ScanResults::new())
+    }
+}
+
+// ─── AsyncWifiDriver trait ──────────────────────────────────────────────────
+
+/// Async counterpart of [`WifiDriver`] for executor-driven stacks.
+///
+/// Same surface and call order (`configure` → `start` → `connect`), with one
+/// semantic difference: [`connect`](Self::connect) resolves once the station
+/// is associated (or the attempt failed) rather than merely initiating it,
+/// and [`disconnect`](Self::disconnect) resolves once the link is down.
+///
+/// The returned futures carry no `Send` bound, matching the single-threaded
+/// embassy executors the bare-metal tier runs on.  On the host, drive them
+/// with any executor — [`mock::block_on`] is enough for unit tests.
+///
+/// # Implementors
+///
+/// - `rustyfarian_esp_hal_network::wifi::EspHalWifiDriver` — bare-metal driver
+///   over `esp_radio::wifi::WifiController`
+/// - [`mock::AsyncMockWifiDriver`] — test double (behind `mock` feature /
+///   `#[cfg(test)]`)
+#[allow(async_fn_in_trait)]
+pub trait AsyncWifiDriver {
+    /// Driver-specific error type.
+    type Error: core::fmt::Debug;
+
+    /// Configure the driver with the given SSID and password.
+    async fn configure(&mut self, ssid: &str, password: &str) -> Result<(), Self::Error>;
+
+    /// Start the Wi-Fi hardware.
+    async fn start(&mut self) -> Result<(), Self::Error>;
+
+    /// Associate with the configured AP; resolves once associated.
+    async fn connect(&mut self) -> Result<(), Self::Error>;
+
+    /// Disconnect from the current AP; resolves once the link is down.
+    async fn disconnect(&mut self) -> Result<(), Self::Error>;
+
+    /// Returns `true` if the station is associated and authenticated.
+    fn is_connected(&self) -> Result<bool, Self::Error>;
+
+    /// Scans for nearby access points.
+    ///
+    /// The driver must be started.  Results are sorted strongest-first and
+    /// capped at [`SCAN_MAX_RESULTS`]; see [`insert_scan_result`].
+    ///
+    /// The default returns no access points, like [`WifiDriver::scan`].
+    async fn scan(&mut self) -> Result<ScanResults, Self::Error> {
+        Ok(ScanResults::new())
+    }
 }
 
 // ─── Tests ──────────────────────────────────────────────────────────────────
@@ -518,6 +953,15 @@ mod tests {
         assert_eq!(wifi_disconnect_reason_name(204), Some("HANDSHAKE_TIMEOUT"));
     }
 
+    #[test]
+    fn full_reason_table_is_mapped() {
+        assert_eq!(wifi_disconnect_reason_name(8), Some("ASSOC_LEAVE"));
+        assert_eq!(
+            wifi_disconnect_reason_name(210),
+            Some("NO_AP_FOUND_W_COMPATIBLE_SECURITY")
+        );
+    }
+
     #[test]
     fn unknown_disconnect_reason_returns_none() {
         assert_eq!(wifi_disconnect_reason_name(0), None);
@@ -548,6 +992,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn wifi_config_new_networks_is_single_entry() {
+        let networks = test_config().networks();
+        assert_eq!(networks.len(), 1);
+        assert_eq!(networks.get(0).unwrap().ssid, TEST_SSID);
+    }
+
+    #[test]
+    fn wifi_config_from_networks_uses_preferred_entry() {
+        let list = WifiNetworkList::new()
+            .with(WifiNetwork::new("lab", TEST_PSK).with_priority(1))
+            .and_then(|l| l.with(WifiNetwork::new(TEST_SSID, TEST_PSK).with_priority(4)))
+            .unwrap();
+        let config = WiFiConfig::from_networks(list).with_timeout(10);
+        assert_eq!(config.ssid, TEST_SSID);
+        assert_eq!(config.networks().len(), 2);
+    }
+
+    #[test]
+    fn wifi_config_auth_flows_into_networks() {
+        let config = test_config().with_auth(WifiAuthMethod::Wpa3Personal);
+        assert_eq!(
+            config.networks().get(0).unwrap().auth,
+            WifiAuthMethod::Wpa3Personal
+        );
+
+        let list = WifiNetworkList::new()
+            .with(WifiNetwork::new(TEST_SSID, TEST_PSK).with_auth(WifiAuthMethod::Wpa2Wpa3Personal))
+            .unwrap();
+        assert_eq!(
+            WiFiConfig::from_networks(list).auth,
+            WifiAuthMethod::Wpa2Wpa3Personal
+        );
+    }
+
+    #[test]
+    fn wifi_config_from_empty_networks_fails_ssid_validation() {
+        let config = WiFiConfig::from_networks(WifiNetworkList::new());
+        assert!(validate_ssid(config.ssid).is_err());
+    }
+
+    #[test]
+    fn wifi_config_defaults_to_dhcp() {
+        assert_eq!(test_config().ip, IpConfig::Dhcp);
+    }
+
+    #[test]
+    fn wifi_config_with_static_ip() {
+        use core::net::Ipv4Addr;
+        let config = test_config().with_static_ip(
+            Ipv4Addr::new(10, 0, 0, 20),
+            Ipv4Addr::new(255, 255, 255, 0),
+            Ipv4Addr::new(10, 0, 0, 1),
+            None,
+        );
+        assert_eq!(config.ip.prefix_len(), Some(24));
+        assert!(validate_ip_config(&config.ip).is_ok());
+    }
+
+    #[test]
+    fn wifi_config_hostname_defaults_to_none() {
+        assert_eq!(test_config().hostname, None);
+        let config = test_config().with_hostname("greenhouse");
+        assert_eq!(config.hostname, Some("greenhouse"));
+        assert!(validate_hostname("greenhouse").is_ok());
+    }
+
     #[test]
     fn wifi_config_with_timeout() {
         let config = test_config().with_timeout(60);
@@ -681,6 +1192,311 @@ mod tests {
         assert!(!driver.is_connected().unwrap());
     }
 
+    #[test]
+    fn mock_driver_scan_returns_configured_results() {
+        let mut driver = mock::MockWifiDriver::new();
+        insert_scan_result(
+            &mut driver.scan_results,
+            ApInfo::new(
+                TEST_SSID,
+                [2, 0, 0, 0, 0, 1],
+                6,
+                -48,
+                AuthMode::Wpa2Personal,
+            ),
+        );
+        driver.start().unwrap();
+
+        let results = driver.scan().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].ssid.as_str(), TEST_SSID);
+        assert_eq!(driver.scan_count, 1);
+    }
+
+    #[test]
+    fn mock_driver_fail_scan() {
+        let mut driver = mock::MockWifiDriver::new();
+        driver.fail_scan = true;
+        assert_eq!(driver.scan(), Err(mock::MockWifiError::ScanFailed));
+        assert_eq!(driver.scan_count, 0);
+    }
+
+    #[test]
+    fn mock_driver_scripted_connects_run_in_order() {
+        use mock::MockConnect;
+
+        let mut driver = mock::MockWifiDriver::new();
+        driver.fail_connect = true;
+        driver
+            .queue_connect(MockConnect::Fail(DisconnectReason::AuthFail))
+            .unwrap();
+        driver.queue_connect(MockConnect::Succeed).unwrap();
+        assert_eq!(driver.pending_connects(), 2);
+
+        assert_eq!(driver.connect(), Err(mock::MockWifiError::ConnectFailed));
+        assert_eq!(
+            driver.take_disconnect_event(),
+            Some(DisconnectReason::AuthFail)
+        );
+        assert_eq!(driver.take_disconnect_event(), None);
+
+        // The script takes precedence over `fail_connect` ...
+        driver.connect().unwrap();
+        assert!(driver.is_connected().unwrap());
+        driver.disconnect().unwrap();
+        // ... which applies again once the script is exhausted.
+        assert_eq!(driver.connect(), Err(mock::MockWifiError::ConnectFailed));
+        assert_eq!(driver.take_disconnect_event(), None);
+        assert_eq!(driver.connect_attempts, 3);
+        assert_eq!(driver.connect_count, 1);
+    }
+
+    #[test]
+    fn mock_driver_script_is_bounded() {
+        let mut driver = mock::MockWifiDriver::new();
+        for _ in 0..mock::MOCK_CONNECT_SCRIPT_LEN {
+            driver.queue_connect(mock::MockConnect::Succeed).unwrap();
+        }
+        assert_eq!(
+            driver.queue_connect(mock::MockConnect::Succeed),
+            Err(mock::MockWifiError::ScriptFull)
+        );
+    }
+
+    #[test]
+    fn mock_driver_drop_fires_on_virtual_clock() {
+        let mut driver = mock::MockWifiDriver::new();
+        driver
+            .queue_connect(mock::MockConnect::SucceedThenDrop {
+                after_ms: 30_000,
+                reason: DisconnectReason::BeaconTimeout,
+            })
+            .unwrap();
+        driver.advance_ms(5_000);
+        driver.connect().unwrap();
+
+        driver.advance_ms(29_999);
+        assert!(driver.is_connected().unwrap());
+        assert_eq!(driver.take_disconnect_event(), None);
+
+        driver.advance_ms(1);
+        assert!(!driver.is_connected().unwrap());
+        assert!(!driver.netif_up);
+        assert_eq!(
+            driver.take_disconnect_event(),
+            Some(DisconnectReason::BeaconTimeout)
+        );
+        assert_eq!(
+            driver.last_disconnect_reason,
+            Some(DisconnectReason::BeaconTimeout)
+        );
+        assert_eq!(driver.now_ms, 35_000);
+    }
+
+    #[test]
+    fn mock_driver_app_disconnect_cancels_scheduled_drop() {
+        let mut driver = mock::MockWifiDriver::new();
+        driver.connect().unwrap();
+        driver.schedule_disconnect(1_000, DisconnectReason::BeaconTimeout);
+        driver.disconnect().unwrap();
+        driver.connect().unwrap();
+
+        driver.advance_ms(2_000);
+        assert!(driver.is_connected().unwrap());
+        assert_eq!(driver.take_disconnect_event(), None);
+    }
+
+    #[test]
+    fn mock_driver_dhcp_delay_holds_netif_down() {
+        let mut driver = mock::MockWifiDriver::new();
+        driver.dhcp_delay_ms = 1_500;
+        driver.connect().unwrap();
+        assert!(driver.is_connected().unwrap());
+        assert!(!driver.netif_up);
+
+        driver.advance_ms(1_499);
+        assert!(!driver.netif_up);
+        driver.advance_ms(1);
+        assert!(driver.netif_up);
+    }
+
+    #[test]
+    fn mock_driver_drop_before_dhcp_leaves_netif_down() {
+        let mut driver = mock::MockWifiDriver::new();
+        driver.dhcp_delay_ms = 5_000;
+        driver
+            .queue_connect(mock::MockConnect::SucceedThenDrop {
+                after_ms: 2_000,
+                reason: DisconnectReason::AssocExpire,
+            })
+            .unwrap();
+        driver.connect().unwrap();
+        driver.advance_ms(10_000);
+        assert!(!driver.netif_up);
+        assert!(!driver.is_connected().unwrap());
+    }
+
+    #[test]
+    fn mock_driver_clock_saturates_instead_of_overflowing() {
+        let mut driver = mock::MockWifiDriver::new();
+        driver.dhcp_delay_ms = u64::MAX;
+        driver
+            .queue_connect(mock::MockConnect::SucceedThenDrop {
+                after_ms: u64::MAX,
+                reason: DisconnectReason::BeaconTimeout,
+            })
+            .unwrap();
+        driver.advance_ms(1);
+        driver.connect().unwrap();
+        assert!(!driver.netif_up);
+
+        // Both deadlines saturate to the end of time and fire together.
+        driver.advance_ms(u64::MAX);
+        assert_eq!(driver.now_ms, u64::MAX);
+        assert!(!driver.is_connected().unwrap());
+        assert_eq!(
+            driver.take_disconnect_event(),
+            Some(DisconnectReason::BeaconTimeout)
+        );
+    }
+
+    /// Runs `supervisor` against `driver` until the link has dropped once
+    /// after coming up, returning the virtual time of the drop.
+    fn supervise_until_drop(
+        supervisor: &mut WifiLinkSupervisor,
+        driver: &mut mock::MockWifiDriver,
+    ) -> u64 {
+        let mut action = supervisor.handle(WifiLinkEvent::Start);
+        loop {
+            action = match action.expect("supervisor ignored an event") {
+                WifiLinkAction::Connect => {
+                    let event = match driver.connect() {
+                        Ok(()) => WifiLinkEvent::Connected,
+                        Err(_) => WifiLinkEvent::Disconnected {
+                            reason: driver.take_disconnect_event().map_or(0, |r| r.code()),
+                        },
+                    };
+                    supervisor.handle(event)
+                }
+                WifiLinkAction::Wait { delay_ms } => {
+                    driver.advance_ms(delay_ms);
+                    supervisor.handle(WifiLinkEvent::RetryTimerElapsed)
+                }
+                WifiLinkAction::WaitForDisconnect => loop {
+                    driver.advance_ms(100);
+                    if let Some(reason) = driver.take_disconnect_event() {
+                        supervisor.handle(WifiLinkEvent::Disconnected {
+                            reason: reason.code(),
+                        });
+                        return driver.now_ms;
+                    }
+                },
+                WifiLinkAction::Stop => unreachable!(),
+            };
+        }
+    }
+
+    #[test]
+    fn mock_scenario_drives_link_supervisor_deterministically() {
+        let mut driver = mock::MockWifiDriver::new();
+        for _ in 0..3 {
+            driver
+                .queue_connect(mock::MockConnect::Fail(DisconnectReason::NoApFound))
+                .unwrap();
+        }
+        driver
+            .queue_connect(mock::MockConnect::SucceedThenDrop {
+                after_ms: 30_000,
+                reason: DisconnectReason::BeaconTimeout,
+            })
+            .unwrap();
+
+        let mut supervisor = WifiLinkSupervisor::new();
+        let dropped_at = supervise_until_drop(&mut supervisor, &mut driver);
+
+        // Backoff of 1 s, 2 s, 4 s before the fourth attempt succeeds.
+        assert_eq!(dropped_at, 7_000 + 30_000);
+        assert_eq!(driver.connect_attempts, 4);
+        assert_eq!(supervisor.state(), WifiLinkState::WaitingToRetry);
+        assert_eq!(
+            supervisor.last_disconnect_class(),
+            Some(DisconnectClass::RadioIssue)
+        );
+        // The successful association reset the retry counter.
+        assert_eq!(supervisor.retry_count(), 1);
+    }
+
+    // ── AsyncMockWifiDriver tests ───────────────────────────────────────
+
+    /// Shared logic written once against the async trait.
+    async fn bring_up<D: AsyncWifiDriver>(driver: &mut D) -> Result<bool, D::Error> {
+        driver.configure(TEST_SSID, TEST_PSK).await?;
+        driver.start().await?;
+        driver.connect().await?;
+        driver.is_connected()
+    }
+
+    #[test]
+    fn async_mock_driver_connect_disconnect_cycle() {
+        let mut driver = mock::AsyncMockWifiDriver::new();
+        assert_eq!(mock::block_on(bring_up(&mut driver)), Ok(true));
+        assert!(driver.driver.configured);
+        assert!(driver.driver.started);
+        assert_eq!(driver.driver.connect_count, 1);
+
+        mock::block_on(AsyncWifiDriver::disconnect(&mut driver)).unwrap();
+        assert_eq!(AsyncWifiDriver::is_connected(&driver), Ok(false));
+    }
+
+    #[test]
+    fn async_mock_driver_fail_connect() {
+        let mut driver = mock::AsyncMockWifiDriver::new();
+        driver.driver.fail_connect = true;
+        assert_eq!(
+            mock::block_on(bring_up(&mut driver)),
+            Err(mock::MockWifiError::ConnectFailed)
+        );
+        assert!(!driver.driver.connected);
+    }
+
+    #[test]
+    fn async_mock_driver_yields_before_completing() {
+        use core::future::Future;
+        use core::task::{Context, Poll, Waker};
+
+        let mut driver = mock::AsyncMockWifiDriver::new();
+        driver.yield_polls = 2;
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut connect = core::pin::pin!(AsyncWifiDriver::connect(&mut driver));
+        assert!(connect.as_mut().poll(&mut cx).is_pending());
+        assert!(connect.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(connect.as_mut().poll(&mut cx), Poll::Ready(Ok(())));
+    }
+
+    #[test]
+    fn async_mock_driver_scan_uses_shared_state() {
+        let mut driver = mock::AsyncMockWifiDriver::new();
+        insert_scan_result(
+            &mut driver.driver.scan_results,
+            ApInfo::new(
+                TEST_SSID,
+                [2, 0, 0, 0, 0, 1],
+                6,
+                -48,
+                AuthMode::Wpa2Personal,
+            ),
+        );
+        let results = mock::block_on(AsyncWifiDriver::scan(&mut driver)).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(driver.driver.scan_count, 1);
+
+        driver.driver.fail_scan = true;
+        assert_eq!(
+            mock::block_on(AsyncWifiDriver::scan(&mut driver)),
+            Err(mock::MockWifiError::ScanFailed)
+        );
+    }
+
     // ── ApConfig tests ────────────────────────────────────────────────────
 
     #[test]
@@ -748,6 +1564,121 @@ mod tests {
         assert!(validate_ap_config(&ApConfig::open(TEST_SSID).with_channel(14)).is_err());
     }
 
+    #[test]
+    fn ap_config_subnet_builders() {
+        let config = ApConfig::open(TEST_SSID)
+            .with_ip(
+                core::net::Ipv4Addr::new(10, 42, 0, 1),
+                core::net::Ipv4Addr::new(255, 255, 255, 0),
+            )
+            .with_dhcp_range(
+                core::net::Ipv4Addr::new(10, 42, 0, 100),
+                core::net::Ipv4Addr::new(10, 42, 0, 110),
+            );
+        assert_eq!(config.subnet.ip, core::net::Ipv4Addr::new(10, 42, 0, 1));
+        assert_eq!(config.subnet.pool_size(), AP_DHCP_POOL_MAX);
+        assert!(validate_ap_config(&config).is_ok());
+        assert_eq!(ApConfig::open(TEST_SSID).subnet, ApSubnet::DEFAULT);
+    }
+
+    #[test]
+    fn validate_ap_config_rejects_pool_outside_moved_subnet() {
+        let config = ApConfig::open(TEST_SSID).with_ip(
+            core::net::Ipv4Addr::new(10, 42, 0, 1),
+            core::net::Ipv4Addr::new(255, 255, 255, 0),
+        );
+        assert_eq!(
+            validate_ap_config(&config),
+            Err("DHCP pool must be inside the AP's subnet")
+        );
+    }
+
+    #[test]
+    fn validate_ap_config_caps_max_connections() {
+        let config = ApConfig::open(TEST_SSID).with_max_connections(AP_MAX_CONNECTIONS_MAX);
+        assert!(validate_ap_config(&config).is_ok());
+        assert_eq!(
+            validate_ap_config(&config.with_max_connections(AP_MAX_CONNECTIONS_MAX + 1)),
+            Err("AP max_connections must be at most 10")
+        );
+    }
+
+    #[test]
+    fn validate_ap_config_checks_allowlist_length() {
+        let macs = [[0x02, 0, 0, 0, 0, 0]; AP_ALLOWLIST_MAX + 1];
+        let config = ApConfig::open(TEST_SSID);
+        assert!(validate_ap_config(&config.clone().with_allowlist(&macs[..1])).is_ok());
+        assert!(
+            validate_ap_config(&config.clone().with_allowlist(&macs[..AP_ALLOWLIST_MAX])).is_ok()
+        );
+        assert_eq!(
+            validate_ap_config(&config.clone().with_allowlist(&macs)),
+            Err("AP allowlist exceeds 16 entries")
+        );
+        assert_eq!(
+            validate_ap_config(&config.with_allowlist(&[])),
+            Err("AP allowlist must not be empty")
+        );
+    }
+
+    #[test]
+    fn validate_ap_config_channel_follows_country() {
+        let us = ApConfig::open(TEST_SSID).with_country(Country::Us);
+        assert!(validate_ap_config(&us.clone().with_channel(11)).is_ok());
+        assert_eq!(
+            validate_ap_config(&us.with_channel(13)),
+            Err("AP channel is not allowed in the configured country")
+        );
+        let jp = ApConfig::open(TEST_SSID).with_country(Country::Jp);
+        assert!(validate_ap_config(&jp.clone().with_channel(14)).is_ok());
+        assert!(validate_ap_config(&jp.with_channel(0)).is_err());
+    }
+
+    #[test]
+    fn effective_tx_power_is_clamped_by_country() {
+        let ap = ApConfig::open(TEST_SSID).with_tx_power(TxPowerLevel::Max);
+        assert_eq!(ap.effective_tx_power(), TxPowerLevel::Max);
+        assert_eq!(
+            ap.with_country(Country::Eu).effective_tx_power(),
+            TxPowerLevel::High
+        );
+
+        let sta = test_config().with_tx_power(TxPowerLevel::Max);
+        assert_eq!(sta.country, None);
+        assert_eq!(
+            sta.with_country(Country::Eu).effective_tx_power(),
+            TxPowerLevel::High
+        );
+    }
+
+    #[test]
+    fn phy_builders_set_protocol_and_bandwidth() {
+        let sta = test_config();
+        assert!(sta.phy.is_default());
+        let sta = sta
+            .with_protocol(WifiProtocol::BgnLr)
+            .with_bandwidth(WifiBandwidth::Ht20);
+        assert_eq!(sta.phy.protocol, WifiProtocol::BgnLr);
+
+        let ap = ApConfig::open(TEST_SSID).with_bandwidth(WifiBandwidth::Ht40);
+        assert_eq!(ap.phy.protocol, WifiProtocol::Bgn);
+        assert_eq!(ap.clone().phy.bandwidth, WifiBandwidth::Ht40);
+    }
+
+    #[test]
+    fn validate_ap_config_rejects_lr_with_ht40() {
+        let ap = ApConfig::open(TEST_SSID).with_bandwidth(WifiBandwidth::Ht40);
+        assert!(validate_ap_config(&ap).is_ok());
+        assert_eq!(
+            validate_ap_config(&ap.clone().with_protocol(WifiProtocol::Lr)),
+            Err("long-range protocol cannot be combined with HT40")
+        );
+        assert!(validate_ap_config(&ap.with_protocol(WifiProtocol::Bg)).is_err());
+        assert!(
+            validate_ap_config(&ApConfig::open(TEST_SSID).with_protocol(WifiProtocol::Lr)).is_ok()
+        );
+    }
+
     #[test]
     fn ap_config_max_connections_zero_rejected() {
         assert!(validate_ap_config(&ApConfig::open(TEST_SSID).with_max_connections(0)).is_err());
@@ -769,4 +1700,28 @@ mod tests {
         assert!(rendered.contains("None"));
         assert!(!rendered.contains("<redacted>"));
     }
+
+    #[test]
+    fn driver_without_scan_reports_no_access_points() {
+        struct NoScan;
+        impl WifiDriver for NoScan {
+            type Error = ();
+            fn configure(&mut self, _: &str, _: &str) -> Result<(), ()> {
+                Ok(())
+            }
+            fn start(&mut self) -> Result<(), ()> {
+                Ok(())
+            }
+            fn connect(&mut self) -> Result<(), ()> {
+                Ok(())
+            }
+            fn disconnect(&mut self) -> Result<(), ()> {
+                Ok(())
+            }
+            fn is_connected(&self) -> Result<bool, ()> {
+                Ok(false)
+            }
+        }
+        assert_eq!(NoScan.scan(), Ok(ScanResults::new()));
+    }
 }
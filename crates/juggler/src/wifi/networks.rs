@@ -0,0 +1,505 @@
+//! Multi-network credential list and scan-based AP selection.
+//!
+//! A device that moves between sites (workshop, lab, field) needs more than
+//! one SSID/password pair.  [`WifiNetworkList`] holds a bounded set of
+//! [`WifiNetwork`] entries, each with a priority, and [`rank_networks`] turns
+//! a Wi-Fi scan into an ordered list of candidates the platform managers walk
+//! with fallback:
+//!
+//! 1. Networks seen in the scan, ordered by priority (higher first), then by
+//!    RSSI (stronger first).
+//! 2. Networks **not** seen in the scan, ordered by priority — hidden SSIDs
+//!    do not appear in a passive scan result but may still accept a directed
+//!    association, so they are tried last rather than dropped.
+//!
+//! Everything here is pure and `no_std`: the scan is passed in as an iterator
+//! of `(ssid, rssi)` pairs so that each platform crate can adapt its own scan
+//! result type without a conversion allocation.
+//!
+//! ```ignore
+//! let mut networks = WifiNetworkList::new();
+//! networks.push(WifiNetwork::new("workshop", "psk-1").with_priority(10))?;
+//! networks.push(WifiNetwork::new("lab", "psk-2").with_priority(5))?;
+//!
+//! let scan = [("lab", -48), ("workshop", -71)];
+//! let order = rank_networks(&networks, scan.iter().copied());
+//! // workshop first (higher priority), then lab
+//! ```
+
+use super::{validate_auth_method, validate_ssid, WifiAuthMethod};
+
+/// Maximum number of entries a [`WifiNetworkList`] can hold.
+///
+/// Eight covers every deployment seen so far (home, workshop, lab, a handful
+/// of field sites) while keeping the list small enough to live on the stack
+/// of a bare-metal init function.
+pub const MAX_NETWORKS: usize = 8;
+
+/// Ordered candidate indices produced by [`rank_networks`].
+///
+/// Each element is an index into the [`WifiNetworkList`] the ranking was
+/// computed from.
+pub type NetworkOrder = heapless::Vec<usize, MAX_NETWORKS>;
+
+// ─── WifiNetwork ────────────────────────────────────────────────────────────
+
+/// A single known network: SSID, password, authentication method, and
+/// selection priority.
+///
+/// Higher `priority` values are preferred.  Entries with equal priority are
+/// ordered by signal strength when they are visible in a scan.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct WifiNetwork<'a> {
+    /// Network SSID (1..=32 bytes).
+    pub ssid: &'a str,
+    /// Pre-shared key, or an empty string for an open network.  Ignored for
+    /// enterprise [`auth`](Self::auth) methods.
+    pub password: &'a str,
+    /// Selection priority — higher wins.  Defaults to `0`.
+    pub priority: u8,
+    /// How to authenticate; [`WifiAuthMethod::Wpa2Personal`] by default.
+    pub auth: WifiAuthMethod<'a>,
+}
+
+impl core::fmt::Debug for WifiNetwork<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("WifiNetwork")
+            .field("ssid", &self.ssid)
+            .field("password", &"<redacted>")
+            .field("priority", &self.priority)
+            .field("auth", &self.auth)
+            .finish()
+    }
+}
+
+impl<'a> WifiNetwork<'a> {
+    /// Creates a network entry with priority `0`.
+    pub fn new(ssid: &'a str, password: &'a str) -> Self {
+        Self {
+            ssid,
+            password,
+            priority: 0,
+            auth: WifiAuthMethod::Wpa2Personal,
+        }
+    }
+
+    /// Sets the selection priority (higher wins).
+    pub fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets the authentication method (WPA3, transition mode, or EAP).
+    pub fn with_auth(mut self, auth: WifiAuthMethod<'a>) -> Self {
+        self.auth = auth;
+        self
+    }
+}
+
+// ─── WifiNetworkList ────────────────────────────────────────────────────────
+
+/// Bounded list of known networks (at most [`MAX_NETWORKS`] entries).
+///
+/// Entries are validated on insertion with [`validate_ssid`] and
+/// [`validate_auth_method`], and duplicate SSIDs are rejected — the same SSID
+/// with two different passwords would make fallback order ambiguous.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WifiNetworkList<'a> {
+    networks: heapless::Vec<WifiNetwork<'a>, MAX_NETWORKS>,
+}
+
+impl<'a> WifiNetworkList<'a> {
+    /// Creates an empty list.
+    pub fn new() -> Self {
+        Self {
+            networks: heapless::Vec::new(),
+        }
+    }
+
+    /// Creates a single-entry list from one SSID/password pair.
+    ///
+    /// This is what [`WiFiConfig::networks`][super::WiFiConfig::networks]
+    /// returns for a config built with [`WiFiConfig::new`][super::WiFiConfig::new].
+    /// The entry is **not** validated here; the managers validate every
+    /// candidate before touching the radio.
+    pub fn single(ssid: &'a str, password: &'a str) -> Self {
+        Self::single_network(WifiNetwork::new(ssid, password))
+    }
+
+    /// Like [`single`](Self::single), for a fully built entry (unvalidated).
+    pub(crate) fn single_network(network: WifiNetwork<'a>) -> Self {
+        let mut networks = heapless::Vec::new();
+        // Capacity is MAX_NETWORKS >= 1, so the first push cannot fail.
+        let _ = networks.push(network);
+        Self { networks }
+    }
+
+    /// Appends a network to the list.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the SSID or authentication settings are invalid
+    /// (see [`validate_auth_method`]), if the SSID is already present, or if
+    /// the list already holds [`MAX_NETWORKS`] entries.
+    pub fn push(&mut self, network: WifiNetwork<'a>) -> Result<(), &'static str> {
+        validate_ssid(network.ssid)?;
+        validate_auth_method(&network.auth, network.password)?;
+        if self.networks.iter().any(|n| n.ssid == network.ssid) {
+            return Err("network list already contains this SSID");
+        }
+        self.networks
+            .push(network)
+            .map_err(|_| "network list is full")
+    }
+
+    /// Builder-style variant of [`push`][Self::push].
+    pub fn with(mut self, network: WifiNetwork<'a>) -> Result<Self, &'static str> {
+        self.push(network)?;
+        Ok(self)
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.networks.len()
+    }
+
+    /// Returns `true` if the list holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.networks.is_empty()
+    }
+
+    /// Returns the entry at `index`, if any.
+    pub fn get(&self, index: usize) -> Option<&WifiNetwork<'a>> {
+        self.networks.get(index)
+    }
+
+    /// Iterates over the entries in insertion order.
+    pub fn iter(&self) -> core::slice::Iter<'_, WifiNetwork<'a>> {
+        self.networks.iter()
+    }
+
+    /// Returns the entry with the highest priority (first inserted on ties).
+    pub fn preferred(&self) -> Option<&WifiNetwork<'a>> {
+        priority_order(self).first().map(|&i| &self.networks[i])
+    }
+}
+
+impl<'l, 'a> IntoIterator for &'l WifiNetworkList<'a> {
+    type Item = &'l WifiNetwork<'a>;
+    type IntoIter = core::slice::Iter<'l, WifiNetwork<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Validates a network list before it is handed to a platform manager.
+///
+/// Lists built with [`WifiNetworkList::push`] are already validated entry by
+/// entry; this catches the one case `push` cannot: an empty list.
+pub fn validate_network_list(networks: &WifiNetworkList<'_>) -> Result<(), &'static str> {
+    if networks.is_empty() {
+        return Err("network list must contain at least one network");
+    }
+    for network in networks {
+        validate_ssid(network.ssid)?;
+        validate_auth_method(&network.auth, network.password)?;
+    }
+    Ok(())
+}
+
+// ─── Selection ──────────────────────────────────────────────────────────────
+
+/// Returns list indices ordered by priority (higher first), ties in insertion
+/// order.
+///
+/// Used when no scan data is available, e.g. for a non-blocking connect or
+/// when the scan itself failed.
+pub fn priority_order(networks: &WifiNetworkList<'_>) -> NetworkOrder {
+    let mut order: NetworkOrder = (0..networks.len()).collect();
+    // Stable insertion sort: the list holds at most MAX_NETWORKS entries and
+    // `core` only offers unstable slice sorts (`sort_by` needs `alloc`).
+    insertion_sort_by(&mut order, |&a, &b| {
+        networks.networks[b].priority > networks.networks[a].priority
+    });
+    order
+}
+
+/// Ranks the networks in `networks` against a scan result.
+///
+/// `visible` yields one `(ssid, rssi_dbm)` pair per access point seen.  The
+/// same SSID may appear several times (multiple BSSIDs); the strongest RSSI
+/// is used.  SSIDs not in `networks` are ignored.
+///
+/// The returned order contains every index in `networks` exactly once:
+/// visible networks first (priority, then RSSI), followed by networks that
+/// were not seen (priority only).  Platform managers try each candidate in
+/// turn and fall back to the next on failure.
+pub fn rank_networks<'s, I>(networks: &WifiNetworkList<'_>, visible: I) -> NetworkOrder
+where
+    I: IntoIterator<Item = (&'s str, i8)>,
+{
+    let mut best_rssi: heapless::Vec<Option<i8>, MAX_NETWORKS> =
+        networks.iter().map(|_| None).collect();
+
+    for (ssid, rssi) in visible {
+        if let Some(i) = networks.iter().position(|n| n.ssid == ssid) {
+            let slot = &mut best_rssi[i];
+            *slot = Some(slot.map_or(rssi, |prev| prev.max(rssi)));
+        }
+    }
+
+    let mut order = priority_order(networks);
+    insertion_sort_by(&mut order, |&a, &b| {
+        match (best_rssi[a], best_rssi[b]) {
+            // Visible beats not visible.
+            (None, Some(_)) => true,
+            (Some(ra), Some(rb)) => {
+                let (pa, pb) = (networks.networks[a].priority, networks.networks[b].priority);
+                pb > pa || (pb == pa && rb > ra)
+            }
+            // Both unseen: keep the priority order established above.
+            _ => false,
+        }
+    });
+    order
+}
+
+/// Returns the index of the best **visible** network, or `None` if no
+/// network in the list appears in the scan.
+///
+/// Equivalent to the first entry of [`rank_networks`] when at least one
+/// network was seen.
+pub fn select_network<'s, I>(networks: &WifiNetworkList<'_>, visible: I) -> Option<usize>
+where
+    I: IntoIterator<Item = (&'s str, i8)>,
+{
+    let mut best: Option<(usize, u8, i8)> = None;
+    for (ssid, rssi) in visible {
+        let Some(i) = networks.iter().position(|n| n.ssid == ssid) else {
+            continue;
+        };
+        let priority = networks.networks[i].priority;
+        let better = match best {
+            None => true,
+            Some((_, bp, br)) => priority > bp || (priority == bp && rssi > br),
+        };
+        if better {
+            best = Some((i, priority, rssi));
+        }
+    }
+    best.map(|(i, _, _)| i)
+}
+
+/// Stable insertion sort where `should_swap(a, b)` returns `true` when `b`
+/// must come before `a`.
+fn insertion_sort_by<F>(items: &mut [usize], mut should_swap: F)
+where
+    F: FnMut(&usize, &usize) -> bool,
+{
+    for i in 1..items.len() {
+        let mut j = i;
+        while j > 0 && should_swap(&items[j - 1], &items[j]) {
+            items.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    const PSK: &str = "open-sesame";
+
+    fn list(entries: &[(&'static str, u8)]) -> WifiNetworkList<'static> {
+        let mut l = WifiNetworkList::new();
+        for &(ssid, priority) in entries {
+            l.push(WifiNetwork::new(ssid, PSK).with_priority(priority))
+                .unwrap();
+        }
+        l
+    }
+
+    fn ssids<'a>(l: &WifiNetworkList<'a>, order: &NetworkOrder) -> heapless::Vec<&'a str, 8> {
+        order.iter().map(|&i| l.get(i).unwrap().ssid).collect()
+    }
+
+    // ── List construction ───────────────────────────────────────────────
+
+    #[test]
+    fn push_rejects_invalid_ssid() {
+        let mut l = WifiNetworkList::new();
+        assert!(l.push(WifiNetwork::new("", PSK)).is_err());
+        assert!(l.is_empty());
+    }
+
+    #[test]
+    fn push_rejects_duplicate_ssid() {
+        let mut l = list(&[("lab", 1)]);
+        assert_eq!(
+            l.push(WifiNetwork::new("lab", PSK)),
+            Err("network list already contains this SSID")
+        );
+        assert_eq!(l.len(), 1);
+    }
+
+    #[test]
+    fn push_rejects_when_full() {
+        let mut l = WifiNetworkList::new();
+        let names = ["n0", "n1", "n2", "n3", "n4", "n5", "n6", "n7"];
+        for name in names {
+            l.push(WifiNetwork::new(name, PSK)).unwrap();
+        }
+        assert_eq!(l.len(), MAX_NETWORKS);
+        assert_eq!(
+            l.push(WifiNetwork::new("n8", PSK)),
+            Err("network list is full")
+        );
+    }
+
+    #[test]
+    fn with_builder_chains() {
+        let l = WifiNetworkList::new()
+            .with(WifiNetwork::new("a", PSK))
+            .and_then(|l| l.with(WifiNetwork::new("b", PSK)))
+            .unwrap();
+        assert_eq!(l.len(), 2);
+    }
+
+    #[test]
+    fn single_holds_one_entry_with_default_priority() {
+        let l = WifiNetworkList::single("home", PSK);
+        assert_eq!(l.len(), 1);
+        assert_eq!(l.get(0).unwrap().priority, 0);
+    }
+
+    #[test]
+    fn validate_network_list_rejects_empty() {
+        assert!(validate_network_list(&WifiNetworkList::new()).is_err());
+        assert!(validate_network_list(&list(&[("lab", 0)])).is_ok());
+    }
+
+    #[test]
+    fn validate_network_list_rejects_invalid_single_entry() {
+        // `single` skips validation, so the list-level check must catch it.
+        assert!(validate_network_list(&WifiNetworkList::single("", PSK)).is_err());
+    }
+
+    #[test]
+    fn push_validates_auth_method() {
+        let mut l = WifiNetworkList::new();
+        let wpa3_open = WifiNetwork::new("lab", "").with_auth(WifiAuthMethod::Wpa3Personal);
+        assert_eq!(l.push(wpa3_open), Err("WPA3 password must not be empty"));
+        let peap = WifiNetwork::new("campus", "").with_auth(WifiAuthMethod::EapPeap {
+            identity: "anonymous",
+            username: "alice",
+            password: PSK,
+        });
+        assert!(l.push(peap).is_ok());
+        assert!(l.get(0).unwrap().auth.is_enterprise());
+    }
+
+    #[test]
+    fn debug_redacts_psk() {
+        let out = alloc::format!("{:?}", WifiNetwork::new("lab", PSK));
+        assert!(!out.contains(PSK));
+        assert!(out.contains("<redacted>"));
+    }
+
+    #[test]
+    fn preferred_is_highest_priority_first_inserted_on_tie() {
+        let l = list(&[("a", 1), ("b", 5), ("c", 5)]);
+        assert_eq!(l.preferred().unwrap().ssid, "b");
+        assert!(WifiNetworkList::new().preferred().is_none());
+    }
+
+    // ── priority_order ──────────────────────────────────────────────────
+
+    #[test]
+    fn priority_order_is_descending_and_stable() {
+        let l = list(&[("a", 1), ("b", 3), ("c", 1), ("d", 3)]);
+        assert_eq!(ssids(&l, &priority_order(&l)), ["b", "d", "a", "c"]);
+    }
+
+    // ── rank_networks ───────────────────────────────────────────────────
+
+    #[test]
+    fn rank_prefers_priority_over_rssi() {
+        let l = list(&[("lab", 1), ("workshop", 9)]);
+        let scan = [("lab", -40), ("workshop", -80)];
+        assert_eq!(
+            ssids(&l, &rank_networks(&l, scan.iter().copied())),
+            ["workshop", "lab"]
+        );
+    }
+
+    #[test]
+    fn rank_breaks_priority_tie_by_rssi() {
+        let l = list(&[("lab", 5), ("workshop", 5)]);
+        let scan = [("workshop", -70), ("lab", -50)];
+        assert_eq!(
+            ssids(&l, &rank_networks(&l, scan.iter().copied())),
+            ["lab", "workshop"]
+        );
+    }
+
+    #[test]
+    fn rank_places_unseen_networks_last_in_priority_order() {
+        let l = list(&[("hidden-hi", 9), ("seen-lo", 1), ("hidden-lo", 2)]);
+        let scan = [("seen-lo", -60)];
+        assert_eq!(
+            ssids(&l, &rank_networks(&l, scan.iter().copied())),
+            ["seen-lo", "hidden-hi", "hidden-lo"]
+        );
+    }
+
+    #[test]
+    fn rank_uses_strongest_bssid_for_repeated_ssid() {
+        let l = list(&[("mesh", 0), ("lab", 0)]);
+        let scan = [("mesh", -85), ("lab", -60), ("mesh", -45)];
+        assert_eq!(
+            ssids(&l, &rank_networks(&l, scan.iter().copied())),
+            ["mesh", "lab"]
+        );
+    }
+
+    #[test]
+    fn rank_ignores_unknown_ssids() {
+        let l = list(&[("lab", 0)]);
+        let scan = [("neighbour", -30)];
+        assert_eq!(ssids(&l, &rank_networks(&l, scan.iter().copied())), ["lab"]);
+    }
+
+    #[test]
+    fn rank_accepts_owned_scan_entries() {
+        let l = list(&[("lab", 0), ("workshop", 0)]);
+        let owned = [
+            (String::from("workshop"), -40i8),
+            (String::from("lab"), -70),
+        ];
+        let order = rank_networks(&l, owned.iter().map(|(s, r)| (s.as_str(), *r)));
+        assert_eq!(ssids(&l, &order), ["workshop", "lab"]);
+    }
+
+    // ── select_network ──────────────────────────────────────────────────
+
+    #[test]
+    fn select_returns_none_when_nothing_visible() {
+        let l = list(&[("lab", 0)]);
+        assert_eq!(select_network(&l, [("other", -30)]), None);
+        assert_eq!(select_network(&l, []), None);
+    }
+
+    #[test]
+    fn select_matches_first_ranked_visible_entry() {
+        let l = list(&[("a", 2), ("b", 7), ("c", 7)]);
+        let scan = [("a", -30), ("b", -75), ("c", -55)];
+        let best = select_network(&l, scan.iter().copied()).unwrap();
+        assert_eq!(l.get(best).unwrap().ssid, "c");
+        assert_eq!(rank_networks(&l, scan.iter().copied())[0], best);
+    }
+}
@@ -0,0 +1,209 @@
+//! PHY mode: 802.11 protocols, channel bandwidth, and Espressif long range.
+//!
+//! The radio defaults to 802.11b/g/n on a 20 MHz channel (the ESP32-C6 adds
+//! 802.11ax), which suits ordinary access points.  [`WifiPhy`] overrides
+//! that per interface:
+//! [`WiFiConfig::with_protocol`][super::WiFiConfig::with_protocol] and
+//! [`ApConfig::with_protocol`][super::ApConfig::with_protocol] (plus the
+//! matching `with_bandwidth`) carry it to the ESP-IDF managers, and the
+//! ESP-IDF ESP-NOW driver takes one in its `init_with_radio*_phy`
+//! constructors.
+//!
+//! [`WifiProtocol::Lr`] is Espressif's proprietary long-range mode: a
+//! 1/2 Mbps and 1/4 Mbps PHY that trades throughput for reach.  Only
+//! Espressif radios speak it, which makes it a good fit for ESP-NOW links
+//! between our own nodes and a poor one for a station joining a third-party
+//! AP.  Both ends of a link must enable it.  [`validate_phy`] rejects
+//! combinations the driver cannot run.
+
+/// `WIFI_PROTOCOL_11B` bit of the ESP-IDF protocol bitmap.
+const PROTOCOL_11B: u8 = 0x01;
+/// `WIFI_PROTOCOL_11G` bit of the ESP-IDF protocol bitmap.
+const PROTOCOL_11G: u8 = 0x02;
+/// `WIFI_PROTOCOL_11N` bit of the ESP-IDF protocol bitmap.
+const PROTOCOL_11N: u8 = 0x04;
+/// `WIFI_PROTOCOL_LR` bit of the ESP-IDF protocol bitmap.
+const PROTOCOL_LR: u8 = 0x08;
+
+/// 802.11 protocol set an interface may use.
+///
+/// Only the combinations the Espressif driver accepts are representable.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WifiProtocol {
+    /// 802.11b only (1–11 Mbps DSSS).  Longest range of the standard modes,
+    /// and the only one legal on channel 14.
+    B,
+    /// 802.11b/g.
+    Bg,
+    /// 802.11b/g/n — the driver default on every chip except the ESP32-C6,
+    /// whose default also enables 802.11ax.
+    #[default]
+    Bgn,
+    /// 802.11b/g/n plus Espressif long range.  Keeps ordinary peers and
+    /// APs reachable while LR-capable Espressif peers fall back to LR at
+    /// the edge of range.
+    BgnLr,
+    /// Espressif long range only.  A station in this mode associates only
+    /// with an LR-only Espressif AP; intended for ESP-NOW.
+    Lr,
+}
+
+impl WifiProtocol {
+    /// The `WIFI_PROTOCOL_*` bitmap passed to `esp_wifi_set_protocol()`.
+    pub const fn bitmap(self) -> u8 {
+        match self {
+            Self::B => PROTOCOL_11B,
+            Self::Bg => PROTOCOL_11B | PROTOCOL_11G,
+            Self::Bgn => PROTOCOL_11B | PROTOCOL_11G | PROTOCOL_11N,
+            Self::BgnLr => PROTOCOL_11B | PROTOCOL_11G | PROTOCOL_11N | PROTOCOL_LR,
+            Self::Lr => PROTOCOL_LR,
+        }
+    }
+
+    /// Returns `true` if 802.11n (and therefore HT40) is enabled.
+    pub const fn has_11n(self) -> bool {
+        self.bitmap() & PROTOCOL_11N != 0
+    }
+
+    /// Returns `true` if Espressif long range is enabled.
+    pub const fn has_lr(self) -> bool {
+        self.bitmap() & PROTOCOL_LR != 0
+    }
+}
+
+/// Channel bandwidth of an 802.11n interface.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WifiBandwidth {
+    /// 20 MHz — the driver default, and the only width b/g and LR use.
+    #[default]
+    Ht20,
+    /// 40 MHz (HT40).  Roughly doubles 802.11n throughput but occupies two
+    /// of the three non-overlapping 2.4 GHz channels.
+    Ht40,
+}
+
+/// PHY settings for one Wi-Fi interface.
+///
+/// The default, [`WifiPhy::DEFAULT`], means "leave the radio alone": the
+/// managers skip `esp_wifi_set_protocol()` and `esp_wifi_set_bandwidth()`
+/// when given it, so the interface keeps the driver's own setting — b/g/n
+/// at 20 MHz on most chips, b/g/n/ax on the ESP32-C6.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WifiPhy {
+    /// Protocols the interface may use.
+    pub protocol: WifiProtocol,
+    /// Channel bandwidth.
+    pub bandwidth: WifiBandwidth,
+}
+
+impl WifiPhy {
+    /// 802.11b/g/n at 20 MHz.  Treated as "not set" by the managers, so on
+    /// the ESP32-C6 the interface keeps its 802.11ax-capable driver default
+    /// instead of dropping to b/g/n.
+    pub const DEFAULT: Self = Self {
+        protocol: WifiProtocol::Bgn,
+        bandwidth: WifiBandwidth::Ht20,
+    };
+
+    /// Espressif long range only, at 20 MHz — the setting for long ESP-NOW
+    /// links.
+    pub const LONG_RANGE: Self = Self {
+        protocol: WifiProtocol::Lr,
+        bandwidth: WifiBandwidth::Ht20,
+    };
+
+    /// Creates PHY settings for `protocol` at 20 MHz.
+    pub const fn new(protocol: WifiProtocol) -> Self {
+        Self {
+            protocol,
+            bandwidth: WifiBandwidth::Ht20,
+        }
+    }
+
+    /// Sets the channel bandwidth.
+    pub const fn with_bandwidth(mut self, bandwidth: WifiBandwidth) -> Self {
+        self.bandwidth = bandwidth;
+        self
+    }
+
+    /// Returns `true` if this is [`WifiPhy::DEFAULT`], so nothing is
+    /// applied and the interface keeps the driver's own setting.
+    pub fn is_default(&self) -> bool {
+        *self == Self::DEFAULT
+    }
+}
+
+/// Returns `Ok(())` if `phy` is a combination the driver can run.
+///
+/// HT40 needs 802.11n, so it is rejected with [`WifiProtocol::B`] and
+/// [`WifiProtocol::Bg`].  Long range only runs on a 20 MHz channel, so HT40
+/// is also rejected whenever LR is enabled, [`WifiProtocol::BgnLr`]
+/// included.
+pub fn validate_phy(phy: &WifiPhy) -> Result<(), &'static str> {
+    if phy.bandwidth == WifiBandwidth::Ht40 {
+        if phy.protocol.has_lr() {
+            return Err("long-range protocol cannot be combined with HT40");
+        }
+        if !phy.protocol.has_11n() {
+            return Err("HT40 requires 802.11n");
+        }
+    }
+    Ok(())
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [WifiProtocol; 5] = [
+        WifiProtocol::B,
+        WifiProtocol::Bg,
+        WifiProtocol::Bgn,
+        WifiProtocol::BgnLr,
+        WifiProtocol::Lr,
+    ];
+
+    #[test]
+    fn bitmaps_match_esp_idf() {
+        assert_eq!(WifiProtocol::B.bitmap(), 0x01);
+        assert_eq!(WifiProtocol::Bg.bitmap(), 0x03);
+        assert_eq!(WifiProtocol::Bgn.bitmap(), 0x07);
+        assert_eq!(WifiProtocol::BgnLr.bitmap(), 0x0F);
+        assert_eq!(WifiProtocol::Lr.bitmap(), 0x08);
+    }
+
+    #[test]
+    fn default_is_driver_default() {
+        assert_eq!(WifiPhy::default(), WifiPhy::DEFAULT);
+        assert!(WifiPhy::default().is_default());
+        assert!(!WifiPhy::LONG_RANGE.is_default());
+        assert!(!WifiPhy::DEFAULT
+            .with_bandwidth(WifiBandwidth::Ht40)
+            .is_default());
+    }
+
+    #[test]
+    fn every_protocol_is_valid_at_ht20() {
+        for protocol in ALL {
+            assert!(
+                validate_phy(&WifiPhy::new(protocol)).is_ok(),
+                "{protocol:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn ht40_needs_11n_without_lr() {
+        let ht40 = |protocol| WifiPhy::new(protocol).with_bandwidth(WifiBandwidth::Ht40);
+        assert!(validate_phy(&ht40(WifiProtocol::Bgn)).is_ok());
+        assert!(validate_phy(&ht40(WifiProtocol::B)).is_err());
+        assert!(validate_phy(&ht40(WifiProtocol::Bg)).is_err());
+        assert_eq!(
+            validate_phy(&ht40(WifiProtocol::Lr)),
+            Err("long-range protocol cannot be combined with HT40")
+        );
+        assert!(validate_phy(&ht40(WifiProtocol::BgnLr)).is_err());
+    }
+}
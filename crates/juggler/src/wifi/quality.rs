@@ -0,0 +1,485 @@
+//! Link-quality monitoring: smoothed RSSI with hysteresis-based events.
+//!
+//! A link usually degrades for a while before it drops.  The platform
+//! crates sample the station RSSI periodically and feed it to a
+//! [`LinkQualityMonitor`], which keeps an exponentially-weighted moving
+//! average and reports when the link crosses into (or back out of) weak
+//! territory:
+//!
+//! - [`LinkQualityEvent::Weak`] — the average fell below
+//!   [`LinkQualityConfig::weak_dbm`]; a good moment to raise the transmit
+//!   power or log a warning.
+//! - [`LinkQualityEvent::RoamCandidate`] — the average fell below
+//!   [`LinkQualityConfig::roam_dbm`]; a background rescan may find a better
+//!   access point.
+//! - [`LinkQualityEvent::Recovered`] — the average climbed back to
+//!   [`LinkQualityConfig::recovered_dbm`].
+//!
+//! The gap between `weak_dbm` and `recovered_dbm` is the hysteresis band: a
+//! link hovering around one threshold does not flap between events.  All
+//! arithmetic is integer-only.
+
+/// Default RSSI below which the link is reported weak (dBm).
+pub const QUALITY_WEAK_DBM: i8 = -75;
+
+/// Default RSSI a weak link must climb back to before it counts as
+/// recovered (dBm).
+pub const QUALITY_RECOVERED_DBM: i8 = -68;
+
+/// Default RSSI below which a roam candidate is reported (dBm).
+pub const QUALITY_ROAM_DBM: i8 = -82;
+
+/// Default weight of each new sample in the moving average (percent).
+///
+/// At 25 % and one sample per second a step change is 90 % absorbed after
+/// about eight seconds — slow enough to ride out a person walking past the
+/// antenna, fast enough to notice a link that is really fading.
+pub const QUALITY_DEFAULT_SMOOTHING_PERCENT: u8 = 25;
+
+// ─── LinkQualityConfig ──────────────────────────────────────────────────────
+
+/// Thresholds and smoothing factor for a [`LinkQualityMonitor`].
+///
+/// Checked by [`validate_link_quality_config`]: the thresholds must satisfy
+/// `roam_dbm < weak_dbm < recovered_dbm <= 0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkQualityConfig {
+    /// Average RSSI below which [`LinkQualityEvent::Weak`] fires (dBm).
+    pub weak_dbm: i8,
+    /// Average RSSI at which a weak link fires
+    /// [`LinkQualityEvent::Recovered`] (dBm).
+    pub recovered_dbm: i8,
+    /// Average RSSI below which [`LinkQualityEvent::RoamCandidate`] fires
+    /// (dBm).
+    pub roam_dbm: i8,
+    /// Weight of each new sample in the moving average, 1–100 %; `100`
+    /// disables smoothing.
+    pub smoothing_percent: u8,
+}
+
+impl LinkQualityConfig {
+    /// Creates a configuration with the `QUALITY_*` defaults.
+    pub const fn new() -> Self {
+        Self {
+            weak_dbm: QUALITY_WEAK_DBM,
+            recovered_dbm: QUALITY_RECOVERED_DBM,
+            roam_dbm: QUALITY_ROAM_DBM,
+            smoothing_percent: QUALITY_DEFAULT_SMOOTHING_PERCENT,
+        }
+    }
+
+    /// Sets the weak and recovered thresholds (dBm).
+    pub const fn with_weak_threshold(mut self, weak_dbm: i8, recovered_dbm: i8) -> Self {
+        self.weak_dbm = weak_dbm;
+        self.recovered_dbm = recovered_dbm;
+        self
+    }
+
+    /// Sets the roam-candidate threshold (dBm).
+    pub const fn with_roam_threshold(mut self, roam_dbm: i8) -> Self {
+        self.roam_dbm = roam_dbm;
+        self
+    }
+
+    /// Sets the weight of each new sample in the moving average (percent).
+    pub const fn with_smoothing(mut self, smoothing_percent: u8) -> Self {
+        self.smoothing_percent = smoothing_percent;
+        self
+    }
+}
+
+impl Default for LinkQualityConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Validates a [`LinkQualityConfig`].
+///
+/// Requires `roam_dbm < weak_dbm < recovered_dbm <= 0` and a smoothing
+/// weight of 1–100 %.
+pub fn validate_link_quality_config(config: &LinkQualityConfig) -> Result<(), &'static str> {
+    if config.recovered_dbm > 0 {
+        return Err("recovered threshold must be 0 dBm or below");
+    }
+    if config.weak_dbm >= config.recovered_dbm {
+        return Err("weak threshold must be below the recovered threshold");
+    }
+    if config.roam_dbm >= config.weak_dbm {
+        return Err("roam threshold must be below the weak threshold");
+    }
+    if config.smoothing_percent == 0 || config.smoothing_percent > 100 {
+        return Err("smoothing must be between 1 and 100 percent");
+    }
+    Ok(())
+}
+
+// ─── LinkQuality ────────────────────────────────────────────────────────────
+
+/// Current link-quality band of a [`LinkQualityMonitor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LinkQuality {
+    /// No sample since creation or the last [`reset`](LinkQualityMonitor::reset).
+    #[default]
+    Unknown,
+    /// At or above the weak threshold, or recovered since.
+    Good,
+    /// Below the weak threshold and not yet recovered.
+    Weak,
+    /// Below the roam threshold; a roam candidate has been reported.
+    Poor,
+}
+
+/// Threshold crossing reported by [`LinkQualityMonitor::sample`].
+///
+/// `rssi_dbm` is the smoothed average that triggered the event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkQualityEvent {
+    /// The link dropped below [`LinkQualityConfig::weak_dbm`].
+    Weak {
+        /// Smoothed RSSI (dBm).
+        rssi_dbm: i8,
+    },
+    /// A weak link climbed back to [`LinkQualityConfig::recovered_dbm`].
+    Recovered {
+        /// Smoothed RSSI (dBm).
+        rssi_dbm: i8,
+    },
+    /// The link dropped below [`LinkQualityConfig::roam_dbm`]; scanning for
+    /// a stronger access point may help.
+    RoamCandidate {
+        /// Smoothed RSSI (dBm).
+        rssi_dbm: i8,
+    },
+}
+
+// ─── LinkQualityMonitor ─────────────────────────────────────────────────────
+
+/// Smooths periodic RSSI samples and reports threshold crossings.
+///
+/// Feed it one sample per polling interval (typically 1–10 s) while the
+/// station is associated and [`reset`](Self::reset) it on disconnect, so the
+/// next link starts from a fresh average:
+///
+/// ```ignore
+/// let mut monitor = LinkQualityMonitor::new(LinkQualityConfig::new())?;
+/// loop {
+///     match monitor.sample(read_rssi()?) {
+///         Some(LinkQualityEvent::Weak { .. }) => raise_tx_power(),
+///         Some(LinkQualityEvent::RoamCandidate { .. }) => request_rescan(),
+///         Some(LinkQualityEvent::Recovered { .. }) => restore_tx_power(),
+///         None => {}
+///     }
+///     sleep(POLL_INTERVAL);
+/// }
+/// ```
+///
+/// The first sample seeds the average directly, so a link that comes up
+/// already weak is reported on that first sample.  A link that falls from
+/// good straight past the roam threshold reports only
+/// [`LinkQualityEvent::RoamCandidate`], which implies weak.  Once
+/// [`LinkQuality::Poor`], the monitor re-arms the roam event when the
+/// average climbs back above the weak threshold.
+#[derive(Debug, Clone)]
+pub struct LinkQualityMonitor {
+    config: LinkQualityConfig,
+    /// Moving average in hundredths of a dBm; `None` before the first sample.
+    average_centi_dbm: Option<i32>,
+    quality: LinkQuality,
+    samples: u32,
+}
+
+impl LinkQualityMonitor {
+    /// Creates a monitor after checking `config` with
+    /// [`validate_link_quality_config`].
+    pub fn new(config: LinkQualityConfig) -> Result<Self, &'static str> {
+        validate_link_quality_config(&config)?;
+        Ok(Self {
+            config,
+            average_centi_dbm: None,
+            quality: LinkQuality::Unknown,
+            samples: 0,
+        })
+    }
+
+    /// The configuration in use.
+    pub fn config(&self) -> &LinkQualityConfig {
+        &self.config
+    }
+
+    /// Current quality band.
+    pub fn quality(&self) -> LinkQuality {
+        self.quality
+    }
+
+    /// Smoothed RSSI rounded to whole dBm, or `None` before the first
+    /// sample.
+    pub fn average_dbm(&self) -> Option<i8> {
+        self.average_centi_dbm.map(round_centi_dbm)
+    }
+
+    /// Number of samples since creation or the last [`reset`](Self::reset).
+    pub fn sample_count(&self) -> u32 {
+        self.samples
+    }
+
+    /// Folds `rssi_dbm` into the average and returns the event, if any,
+    /// that the new average triggers.
+    pub fn sample(&mut self, rssi_dbm: i8) -> Option<LinkQualityEvent> {
+        let sample = i32::from(rssi_dbm) * 100;
+        let average = match self.average_centi_dbm {
+            None => sample,
+            Some(previous) => {
+                previous + (sample - previous) * i32::from(self.config.smoothing_percent) / 100
+            }
+        };
+        self.average_centi_dbm = Some(average);
+        self.samples = self.samples.saturating_add(1);
+
+        let weak = i32::from(self.config.weak_dbm) * 100;
+        let recovered = i32::from(self.config.recovered_dbm) * 100;
+        let roam = i32::from(self.config.roam_dbm) * 100;
+        let rssi_dbm = round_centi_dbm(average);
+
+        let (next, event) = match self.quality {
+            LinkQuality::Unknown | LinkQuality::Good if average < roam => (
+                LinkQuality::Poor,
+                Some(LinkQualityEvent::RoamCandidate { rssi_dbm }),
+            ),
+            LinkQuality::Unknown | LinkQuality::Good if average < weak => {
+                (LinkQuality::Weak, Some(LinkQualityEvent::Weak { rssi_dbm }))
+            }
+            LinkQuality::Unknown | LinkQuality::Good => (LinkQuality::Good, None),
+            LinkQuality::Weak | LinkQuality::Poor if average >= recovered => (
+                LinkQuality::Good,
+                Some(LinkQualityEvent::Recovered { rssi_dbm }),
+            ),
+            LinkQuality::Weak if average < roam => (
+                LinkQuality::Poor,
+                Some(LinkQualityEvent::RoamCandidate { rssi_dbm }),
+            ),
+            LinkQuality::Poor if average >= weak => (LinkQuality::Weak, None),
+            current => (current, None),
+        };
+        self.quality = next;
+        event
+    }
+
+    /// Forgets the average and returns to [`LinkQuality::Unknown`] without
+    /// emitting an event.  Call when the link drops.
+    pub fn reset(&mut self) {
+        self.average_centi_dbm = None;
+        self.quality = LinkQuality::Unknown;
+        self.samples = 0;
+    }
+}
+
+impl Default for LinkQualityMonitor {
+    fn default() -> Self {
+        Self {
+            config: LinkQualityConfig::new(),
+            average_centi_dbm: None,
+            quality: LinkQuality::Unknown,
+            samples: 0,
+        }
+    }
+}
+
+/// Rounds hundredths of a dBm to whole dBm, half away from zero.
+fn round_centi_dbm(centi_dbm: i32) -> i8 {
+    let rounded = if centi_dbm < 0 {
+        (centi_dbm - 50) / 100
+    } else {
+        (centi_dbm + 50) / 100
+    };
+    rounded.clamp(i32::from(i8::MIN), i32::from(i8::MAX)) as i8
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Monitor without smoothing, so each sample is the new average.
+    fn raw_monitor() -> LinkQualityMonitor {
+        LinkQualityMonitor::new(LinkQualityConfig::new().with_smoothing(100)).unwrap()
+    }
+
+    // ── validate_link_quality_config ────────────────────────────────────────
+
+    #[test]
+    fn default_config_is_valid() {
+        assert_eq!(
+            validate_link_quality_config(&LinkQualityConfig::new()),
+            Ok(())
+        );
+        assert_eq!(LinkQualityConfig::default(), LinkQualityConfig::new());
+    }
+
+    #[test]
+    fn rejects_positive_recovered_threshold() {
+        let config = LinkQualityConfig::new().with_weak_threshold(-70, 1);
+        assert!(validate_link_quality_config(&config).is_err());
+    }
+
+    #[test]
+    fn rejects_missing_hysteresis() {
+        let config = LinkQualityConfig::new().with_weak_threshold(-70, -70);
+        assert!(validate_link_quality_config(&config).is_err());
+    }
+
+    #[test]
+    fn rejects_roam_at_or_above_weak() {
+        let config = LinkQualityConfig::new().with_roam_threshold(QUALITY_WEAK_DBM);
+        assert!(validate_link_quality_config(&config).is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_range_smoothing() {
+        for pct in [0, 101] {
+            let config = LinkQualityConfig::new().with_smoothing(pct);
+            assert!(validate_link_quality_config(&config).is_err(), "{}", pct);
+        }
+        let config = LinkQualityConfig::new().with_smoothing(100);
+        assert_eq!(validate_link_quality_config(&config), Ok(()));
+    }
+
+    #[test]
+    fn new_rejects_invalid_config() {
+        let config = LinkQualityConfig::new().with_smoothing(0);
+        assert!(LinkQualityMonitor::new(config).is_err());
+    }
+
+    // ── smoothing ───────────────────────────────────────────────────────────
+
+    #[test]
+    fn starts_unknown_without_average() {
+        let monitor = LinkQualityMonitor::default();
+        assert_eq!(monitor.quality(), LinkQuality::Unknown);
+        assert_eq!(monitor.average_dbm(), None);
+        assert_eq!(monitor.sample_count(), 0);
+    }
+
+    #[test]
+    fn first_sample_seeds_average() {
+        let mut monitor = LinkQualityMonitor::default();
+        assert_eq!(monitor.sample(-50), None);
+        assert_eq!(monitor.average_dbm(), Some(-50));
+        assert_eq!(monitor.quality(), LinkQuality::Good);
+        assert_eq!(monitor.sample_count(), 1);
+    }
+
+    #[test]
+    fn average_moves_by_smoothing_weight() {
+        let mut monitor = LinkQualityMonitor::default();
+        monitor.sample(-50);
+        monitor.sample(-70);
+        // 25 % of the 20 dB step.
+        assert_eq!(monitor.average_dbm(), Some(-55));
+    }
+
+    #[test]
+    fn single_outlier_does_not_trigger_weak() {
+        let mut monitor = LinkQualityMonitor::default();
+        monitor.sample(-60);
+        assert_eq!(monitor.sample(-90), None);
+        assert_eq!(monitor.quality(), LinkQuality::Good);
+    }
+
+    #[test]
+    fn rounds_half_away_from_zero() {
+        assert_eq!(round_centi_dbm(-7_550), -76);
+        assert_eq!(round_centi_dbm(-7_549), -75);
+        assert_eq!(round_centi_dbm(0), 0);
+        assert_eq!(round_centi_dbm(-20_000), i8::MIN);
+    }
+
+    // ── events ──────────────────────────────────────────────────────────────
+
+    #[test]
+    fn weak_then_recovered_with_hysteresis() {
+        let mut monitor = raw_monitor();
+        assert_eq!(monitor.sample(-60), None);
+        assert_eq!(
+            monitor.sample(-76),
+            Some(LinkQualityEvent::Weak { rssi_dbm: -76 })
+        );
+        // Inside the hysteresis band: no event either way.
+        assert_eq!(monitor.sample(-72), None);
+        assert_eq!(monitor.sample(-77), None);
+        assert_eq!(monitor.quality(), LinkQuality::Weak);
+        assert_eq!(
+            monitor.sample(-68),
+            Some(LinkQualityEvent::Recovered { rssi_dbm: -68 })
+        );
+        assert_eq!(monitor.quality(), LinkQuality::Good);
+    }
+
+    #[test]
+    fn weak_link_falling_further_is_roam_candidate() {
+        let mut monitor = raw_monitor();
+        monitor.sample(-76);
+        assert_eq!(
+            monitor.sample(-83),
+            Some(LinkQualityEvent::RoamCandidate { rssi_dbm: -83 })
+        );
+        assert_eq!(monitor.quality(), LinkQuality::Poor);
+        assert_eq!(monitor.sample(-90), None);
+    }
+
+    #[test]
+    fn good_link_collapsing_reports_roam_only() {
+        let mut monitor = raw_monitor();
+        monitor.sample(-55);
+        assert_eq!(
+            monitor.sample(-85),
+            Some(LinkQualityEvent::RoamCandidate { rssi_dbm: -85 })
+        );
+    }
+
+    #[test]
+    fn first_sample_can_be_weak() {
+        let mut monitor = raw_monitor();
+        assert_eq!(
+            monitor.sample(-78),
+            Some(LinkQualityEvent::Weak { rssi_dbm: -78 })
+        );
+    }
+
+    #[test]
+    fn poor_link_rearms_roam_above_weak() {
+        let mut monitor = raw_monitor();
+        monitor.sample(-85);
+        assert_eq!(monitor.sample(-80), None);
+        assert_eq!(monitor.quality(), LinkQuality::Poor);
+        assert_eq!(monitor.sample(-72), None);
+        assert_eq!(monitor.quality(), LinkQuality::Weak);
+        assert_eq!(
+            monitor.sample(-84),
+            Some(LinkQualityEvent::RoamCandidate { rssi_dbm: -84 })
+        );
+    }
+
+    #[test]
+    fn poor_link_recovers_directly() {
+        let mut monitor = raw_monitor();
+        monitor.sample(-85);
+        assert_eq!(
+            monitor.sample(-60),
+            Some(LinkQualityEvent::Recovered { rssi_dbm: -60 })
+        );
+    }
+
+    #[test]
+    fn reset_forgets_average_and_band() {
+        let mut monitor = raw_monitor();
+        monitor.sample(-80);
+        monitor.reset();
+        assert_eq!(monitor.quality(), LinkQuality::Unknown);
+        assert_eq!(monitor.average_dbm(), None);
+        assert_eq!(monitor.sample_count(), 0);
+        // The next link starts fresh: a good first sample is silent.
+        assert_eq!(monitor.sample(-50), None);
+    }
+}
@@ -0,0 +1,531 @@
+//! Typed 802.11 disconnect reasons and failure classification.
+//!
+//! Drivers report a disconnect as a raw `u16`: the IEEE 802.11 reason code
+//! (1–68) or one of the ESP-specific codes from 200 upward
+//! (`wifi_err_reason_t` in ESP-IDF, mirrored by `esp-radio`).
+//! [`DisconnectReason::from_code`] turns that number into an enum, and
+//! [`DisconnectReason::class`] sorts it into a [`DisconnectClass`] that
+//! reconnect and provisioning logic can act on — for example, re-entering the
+//! captive portal only on [`DisconnectClass::CredentialsRejected`] instead of
+//! on every dropped beacon.
+
+// ─── DisconnectClass ────────────────────────────────────────────────────────
+
+/// Coarse category of a disconnect, for deciding what to do next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisconnectClass {
+    /// The AP rejected the credentials (wrong WPA3-SAE password, failed
+    /// 802.1X).
+    ///
+    /// Retrying with the same credentials will not help.
+    CredentialsRejected,
+    /// The key handshake timed out, which is how ESP-IDF reports a wrong
+    /// WPA2 passphrase — but also what lost frames on a marginal link look
+    /// like.  Callers that reset stored credentials should wait for the
+    /// class to repeat.
+    CredentialsSuspected,
+    /// No AP with the configured SSID (and a compatible security mode) was
+    /// found.  The AP may be off or out of range; a different network from
+    /// the list may be visible.
+    ApNotFound,
+    /// Radio-level trouble: beacons lost, frames unacknowledged, or the
+    /// channel became unusable.  Usually signal strength or interference.
+    RadioIssue,
+    /// Anything else — AP-initiated disassociation, roaming, load balancing,
+    /// unknown codes.  Retrying after a backoff is appropriate.
+    Transient,
+}
+
+impl DisconnectClass {
+    /// Short human-readable name, suitable for logs.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::CredentialsRejected => "credentials rejected",
+            Self::CredentialsSuspected => "credentials suspected",
+            Self::ApNotFound => "AP not found",
+            Self::RadioIssue => "radio issue",
+            Self::Transient => "transient",
+        }
+    }
+}
+
+// ─── DisconnectReason ───────────────────────────────────────────────────────
+
+/// Wi-Fi disconnect reason.
+///
+/// Covers every code in ESP-IDF's `wifi_err_reason_t`: the IEEE 802.11
+/// reason codes plus the ESP-specific 200+ range.  Codes not in that table
+/// are preserved in [`DisconnectReason::Other`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisconnectReason {
+    /// Unspecified reason.
+    Unspecified,
+    /// Previous authentication no longer valid.
+    AuthExpire,
+    /// Deauthenticated because the sender is leaving.
+    AuthLeave,
+    /// Disassociated due to inactivity.
+    AssocExpire,
+    /// AP cannot handle all currently associated stations.
+    AssocTooMany,
+    /// Class 2 frame received from a non-authenticated station.
+    NotAuthed,
+    /// Class 3 frame received from a non-associated station.
+    NotAssoced,
+    /// Disassociated because the sender is leaving.
+    AssocLeave,
+    /// Association requested before authentication completed.
+    AssocNotAuthed,
+    /// Power capability element unacceptable.
+    DisassocPwrcapBad,
+    /// Supported channels element unacceptable.
+    DisassocSupchanBad,
+    /// Disassociated for BSS transition management.
+    BssTransitionDisassoc,
+    /// Invalid information element.
+    IeInvalid,
+    /// Message integrity code failure.
+    MicFailure,
+    /// 4-way handshake timed out (typically a wrong WPA2 passphrase).
+    FourWayHandshakeTimeout,
+    /// Group key handshake timed out.
+    GroupKeyUpdateTimeout,
+    /// RSN element in the 4-way handshake differs from the association request.
+    IeIn4wayDiffers,
+    /// Invalid group cipher.
+    GroupCipherInvalid,
+    /// Invalid pairwise cipher.
+    PairwiseCipherInvalid,
+    /// Invalid AKMP.
+    AkmpInvalid,
+    /// Unsupported RSN element version.
+    UnsuppRsnIeVersion,
+    /// Invalid RSN element capabilities.
+    InvalidRsnIeCap,
+    /// IEEE 802.1X authentication failed.
+    Ieee8021xAuthFailed,
+    /// Cipher suite rejected by security policy.
+    CipherSuiteRejected,
+    /// TDLS peer unreachable.
+    TdlsPeerUnreachable,
+    /// TDLS teardown, unspecified reason.
+    TdlsUnspecified,
+    /// Disassociated at the subscription service provider's request.
+    SspRequestedDisassoc,
+    /// No roaming agreement with the subscription service provider.
+    NoSspRoamingAgreement,
+    /// Cipher or AKM not supported by the subscription service provider.
+    BadCipherOrAkm,
+    /// Not authorized in this location.
+    NotAuthorizedThisLocation,
+    /// Service change precludes traffic stream.
+    ServiceChangePrecludesTs,
+    /// Unspecified QoS-related reason.
+    UnspecifiedQos,
+    /// AP lacks bandwidth for this QoS station.
+    NotEnoughBandwidth,
+    /// Too many frames unacknowledged (poor channel conditions).
+    MissingAcks,
+    /// Station transmitting outside its TXOP limits.
+    ExceededTxop,
+    /// Peer station is leaving the BSS.
+    StaLeaving,
+    /// Block Ack agreement ended.
+    EndBa,
+    /// Block Ack agreement unknown.
+    UnknownBa,
+    /// Peer-to-peer timeout.
+    Timeout,
+    /// Disassociated at the peer's request.
+    PeerInitiated,
+    /// AP requested disassociation.
+    ApInitiated,
+    /// Invalid fast-transition action frame count.
+    InvalidFtActionFrameCount,
+    /// Invalid PMKID.
+    InvalidPmkid,
+    /// Invalid mobility domain element.
+    InvalidMde,
+    /// Invalid fast-transition element.
+    InvalidFte,
+    /// Link establishment failed in the alternative channel.
+    TransmissionLinkEstablishFailed,
+    /// Alternative channel is occupied.
+    AlternativeChannelOccupied,
+    /// No beacons received from the AP (ESP-specific).
+    BeaconTimeout,
+    /// No AP with the configured SSID found (ESP-specific).
+    NoApFound,
+    /// Authentication failed (ESP-specific; wrong WPA3 password).
+    AuthFail,
+    /// Association failed (ESP-specific).
+    AssocFail,
+    /// Key handshake timed out (ESP-specific; wrong passphrase).
+    HandshakeTimeout,
+    /// Connection to the AP failed (ESP-specific).
+    ConnectionFail,
+    /// AP reset its TSF timer (ESP-specific).
+    ApTsfReset,
+    /// Disconnected while roaming (ESP-specific).
+    Roaming,
+    /// AP association comeback time too long (ESP-specific).
+    AssocComebackTimeTooLong,
+    /// SA query timed out (ESP-specific).
+    SaQueryTimeout,
+    /// SSID found, but not with a compatible security mode (ESP-specific).
+    NoApFoundWithCompatibleSecurity,
+    /// SSID found, but below the configured auth-mode threshold (ESP-specific).
+    NoApFoundInAuthmodeThreshold,
+    /// SSID found, but below the configured RSSI threshold (ESP-specific).
+    NoApFoundInRssiThreshold,
+    /// Code not covered by this table (including `0`, "no reason given").
+    Other(u16),
+}
+
+impl DisconnectReason {
+    /// Converts a raw reason code as reported by the driver.
+    pub fn from_code(code: u16) -> Self {
+        match code {
+            1 => Self::Unspecified,
+            2 => Self::AuthExpire,
+            3 => Self::AuthLeave,
+            4 => Self::AssocExpire,
+            5 => Self::AssocTooMany,
+            6 => Self::NotAuthed,
+            7 => Self::NotAssoced,
+            8 => Self::AssocLeave,
+            9 => Self::AssocNotAuthed,
+            10 => Self::DisassocPwrcapBad,
+            11 => Self::DisassocSupchanBad,
+            12 => Self::BssTransitionDisassoc,
+            13 => Self::IeInvalid,
+            14 => Self::MicFailure,
+            15 => Self::FourWayHandshakeTimeout,
+            16 => Self::GroupKeyUpdateTimeout,
+            17 => Self::IeIn4wayDiffers,
+            18 => Self::GroupCipherInvalid,
+            19 => Self::PairwiseCipherInvalid,
+            20 => Self::AkmpInvalid,
+            21 => Self::UnsuppRsnIeVersion,
+            22 => Self::InvalidRsnIeCap,
+            23 => Self::Ieee8021xAuthFailed,
+            24 => Self::CipherSuiteRejected,
+            25 => Self::TdlsPeerUnreachable,
+            26 => Self::TdlsUnspecified,
+            27 => Self::SspRequestedDisassoc,
+            28 => Self::NoSspRoamingAgreement,
+            29 => Self::BadCipherOrAkm,
+            30 => Self::NotAuthorizedThisLocation,
+            31 => Self::ServiceChangePrecludesTs,
+            32 => Self::UnspecifiedQos,
+            33 => Self::NotEnoughBandwidth,
+            34 => Self::MissingAcks,
+            35 => Self::ExceededTxop,
+            36 => Self::StaLeaving,
+            37 => Self::EndBa,
+            38 => Self::UnknownBa,
+            39 => Self::Timeout,
+            46 => Self::PeerInitiated,
+            47 => Self::ApInitiated,
+            48 => Self::InvalidFtActionFrameCount,
+            49 => Self::InvalidPmkid,
+            50 => Self::InvalidMde,
+            51 => Self::InvalidFte,
+            67 => Self::TransmissionLinkEstablishFailed,
+            68 => Self::AlternativeChannelOccupied,
+            200 => Self::BeaconTimeout,
+            201 => Self::NoApFound,
+            202 => Self::AuthFail,
+            203 => Self::AssocFail,
+            204 => Self::HandshakeTimeout,
+            205 => Self::ConnectionFail,
+            206 => Self::ApTsfReset,
+            207 => Self::Roaming,
+            208 => Self::AssocComebackTimeTooLong,
+            209 => Self::SaQueryTimeout,
+            210 => Self::NoApFoundWithCompatibleSecurity,
+            211 => Self::NoApFoundInAuthmodeThreshold,
+            212 => Self::NoApFoundInRssiThreshold,
+            other => Self::Other(other),
+        }
+    }
+
+    /// Returns the raw reason code; the inverse of [`from_code`](Self::from_code).
+    pub fn code(self) -> u16 {
+        match self {
+            Self::Unspecified => 1,
+            Self::AuthExpire => 2,
+            Self::AuthLeave => 3,
+            Self::AssocExpire => 4,
+            Self::AssocTooMany => 5,
+            Self::NotAuthed => 6,
+            Self::NotAssoced => 7,
+            Self::AssocLeave => 8,
+            Self::AssocNotAuthed => 9,
+            Self::DisassocPwrcapBad => 10,
+            Self::DisassocSupchanBad => 11,
+            Self::BssTransitionDisassoc => 12,
+            Self::IeInvalid => 13,
+            Self::MicFailure => 14,
+            Self::FourWayHandshakeTimeout => 15,
+            Self::GroupKeyUpdateTimeout => 16,
+            Self::IeIn4wayDiffers => 17,
+            Self::GroupCipherInvalid => 18,
+            Self::PairwiseCipherInvalid => 19,
+            Self::AkmpInvalid => 20,
+            Self::UnsuppRsnIeVersion => 21,
+            Self::InvalidRsnIeCap => 22,
+            Self::Ieee8021xAuthFailed => 23,
+            Self::CipherSuiteRejected => 24,
+            Self::TdlsPeerUnreachable => 25,
+            Self::TdlsUnspecified => 26,
+            Self::SspRequestedDisassoc => 27,
+            Self::NoSspRoamingAgreement => 28,
+            Self::BadCipherOrAkm => 29,
+            Self::NotAuthorizedThisLocation => 30,
+            Self::ServiceChangePrecludesTs => 31,
+            Self::UnspecifiedQos => 32,
+            Self::NotEnoughBandwidth => 33,
+            Self::MissingAcks => 34,
+            Self::ExceededTxop => 35,
+            Self::StaLeaving => 36,
+            Self::EndBa => 37,
+            Self::UnknownBa => 38,
+            Self::Timeout => 39,
+            Self::PeerInitiated => 46,
+            Self::ApInitiated => 47,
+            Self::InvalidFtActionFrameCount => 48,
+            Self::InvalidPmkid => 49,
+            Self::InvalidMde => 50,
+            Self::InvalidFte => 51,
+            Self::TransmissionLinkEstablishFailed => 67,
+            Self::AlternativeChannelOccupied => 68,
+            Self::BeaconTimeout => 200,
+            Self::NoApFound => 201,
+            Self::AuthFail => 202,
+            Self::AssocFail => 203,
+            Self::HandshakeTimeout => 204,
+            Self::ConnectionFail => 205,
+            Self::ApTsfReset => 206,
+            Self::Roaming => 207,
+            Self::AssocComebackTimeTooLong => 208,
+            Self::SaQueryTimeout => 209,
+            Self::NoApFoundWithCompatibleSecurity => 210,
+            Self::NoApFoundInAuthmodeThreshold => 211,
+            Self::NoApFoundInRssiThreshold => 212,
+            Self::Other(code) => code,
+        }
+    }
+
+    /// Returns the ESP-IDF name without its `WIFI_REASON_` prefix
+    /// (e.g. `"NO_AP_FOUND"`), or `None` for [`DisconnectReason::Other`].
+    pub fn name(self) -> Option<&'static str> {
+        let name = match self {
+            Self::Unspecified => "UNSPECIFIED",
+            Self::AuthExpire => "AUTH_EXPIRE",
+            Self::AuthLeave => "AUTH_LEAVE",
+            Self::AssocExpire => "ASSOC_EXPIRE",
+            Self::AssocTooMany => "ASSOC_TOOMANY",
+            Self::NotAuthed => "NOT_AUTHED",
+            Self::NotAssoced => "NOT_ASSOCED",
+            Self::AssocLeave => "ASSOC_LEAVE",
+            Self::AssocNotAuthed => "ASSOC_NOT_AUTHED",
+            Self::DisassocPwrcapBad => "DISASSOC_PWRCAP_BAD",
+            Self::DisassocSupchanBad => "DISASSOC_SUPCHAN_BAD",
+            Self::BssTransitionDisassoc => "BSS_TRANSITION_DISASSOC",
+            Self::IeInvalid => "IE_INVALID",
+            Self::MicFailure => "MIC_FAILURE",
+            Self::FourWayHandshakeTimeout => "4WAY_HANDSHAKE_TIMEOUT",
+            Self::GroupKeyUpdateTimeout => "GROUP_KEY_UPDATE_TIMEOUT",
+            Self::IeIn4wayDiffers => "IE_IN_4WAY_DIFFERS",
+            Self::GroupCipherInvalid => "GROUP_CIPHER_INVALID",
+            Self::PairwiseCipherInvalid => "PAIRWISE_CIPHER_INVALID",
+            Self::AkmpInvalid => "AKMP_INVALID",
+            Self::UnsuppRsnIeVersion => "UNSUPP_RSN_IE_VERSION",
+            Self::InvalidRsnIeCap => "INVALID_RSN_IE_CAP",
+            Self::Ieee8021xAuthFailed => "802_1X_AUTH_FAILED",
+            Self::CipherSuiteRejected => "CIPHER_SUITE_REJECTED",
+            Self::TdlsPeerUnreachable => "TDLS_PEER_UNREACHABLE",
+            Self::TdlsUnspecified => "TDLS_UNSPECIFIED",
+            Self::SspRequestedDisassoc => "SSP_REQUESTED_DISASSOC",
+            Self::NoSspRoamingAgreement => "NO_SSP_ROAMING_AGREEMENT",
+            Self::BadCipherOrAkm => "BAD_CIPHER_OR_AKM",
+            Self::NotAuthorizedThisLocation => "NOT_AUTHORIZED_THIS_LOCATION",
+            Self::ServiceChangePrecludesTs => "SERVICE_CHANGE_PERCLUDES_TS",
+            Self::UnspecifiedQos => "UNSPECIFIED_QOS",
+            Self::NotEnoughBandwidth => "NOT_ENOUGH_BANDWIDTH",
+            Self::MissingAcks => "MISSING_ACKS",
+            Self::ExceededTxop => "EXCEEDED_TXOP",
+            Self::StaLeaving => "STA_LEAVING",
+            Self::EndBa => "END_BA",
+            Self::UnknownBa => "UNKNOWN_BA",
+            Self::Timeout => "TIMEOUT",
+            Self::PeerInitiated => "PEER_INITIATED",
+            Self::ApInitiated => "AP_INITIATED",
+            Self::InvalidFtActionFrameCount => "INVALID_FT_ACTION_FRAME_COUNT",
+            Self::InvalidPmkid => "INVALID_PMKID",
+            Self::InvalidMde => "INVALID_MDE",
+            Self::InvalidFte => "INVALID_FTE",
+            Self::TransmissionLinkEstablishFailed => "TRANSMISSION_LINK_ESTABLISH_FAILED",
+            Self::AlternativeChannelOccupied => "ALTERATIVE_CHANNEL_OCCUPIED",
+            Self::BeaconTimeout => "BEACON_TIMEOUT",
+            Self::NoApFound => "NO_AP_FOUND",
+            Self::AuthFail => "AUTH_FAIL",
+            Self::AssocFail => "ASSOC_FAIL",
+            Self::HandshakeTimeout => "HANDSHAKE_TIMEOUT",
+            Self::ConnectionFail => "CONNECTION_FAIL",
+            Self::ApTsfReset => "AP_TSF_RESET",
+            Self::Roaming => "ROAMING",
+            Self::AssocComebackTimeTooLong => "ASSOC_COMEBACK_TIME_TOO_LONG",
+            Self::SaQueryTimeout => "SA_QUERY_TIMEOUT",
+            Self::NoApFoundWithCompatibleSecurity => "NO_AP_FOUND_W_COMPATIBLE_SECURITY",
+            Self::NoApFoundInAuthmodeThreshold => "NO_AP_FOUND_IN_AUTHMODE_THRESHOLD",
+            Self::NoApFoundInRssiThreshold => "NO_AP_FOUND_IN_RSSI_THRESHOLD",
+            Self::Other(_) => return None,
+        };
+        Some(name)
+    }
+
+    /// Classifies this reason; see [`DisconnectClass`].
+    ///
+    /// A wrong WPA3-SAE password (`AUTH_FAIL`, 202) and a failed 802.1X
+    /// exchange (23) are [`DisconnectClass::CredentialsRejected`].  ESP-IDF
+    /// reports a wrong WPA2 passphrase as a handshake timeout (15 / 204),
+    /// which frame loss can also cause, so those are only
+    /// [`DisconnectClass::CredentialsSuspected`].
+    pub fn class(self) -> DisconnectClass {
+        match self {
+            Self::AuthFail | Self::Ieee8021xAuthFailed => DisconnectClass::CredentialsRejected,
+            Self::FourWayHandshakeTimeout | Self::HandshakeTimeout => {
+                DisconnectClass::CredentialsSuspected
+            }
+            Self::NoApFound
+            | Self::NoApFoundWithCompatibleSecurity
+            | Self::NoApFoundInAuthmodeThreshold
+            | Self::NoApFoundInRssiThreshold => DisconnectClass::ApNotFound,
+            Self::BeaconTimeout
+            | Self::MissingAcks
+            | Self::Timeout
+            | Self::DisassocPwrcapBad
+            | Self::DisassocSupchanBad
+            | Self::TransmissionLinkEstablishFailed
+            | Self::AlternativeChannelOccupied => DisconnectClass::RadioIssue,
+            _ => DisconnectClass::Transient,
+        }
+    }
+}
+
+impl From<u16> for DisconnectReason {
+    fn from(code: u16) -> Self {
+        Self::from_code(code)
+    }
+}
+
+/// Classifies a raw disconnect reason code.
+///
+/// Shorthand for `DisconnectReason::from_code(code).class()`.
+pub fn classify_disconnect(code: u16) -> DisconnectClass {
+    DisconnectReason::from_code(code).class()
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_known_code_round_trips() {
+        for code in 0..=u16::from(u8::MAX) {
+            let reason = DisconnectReason::from_code(code);
+            assert_eq!(reason.code(), code, "{:?}", reason);
+            assert_eq!(
+                reason.name().is_some(),
+                !matches!(reason, DisconnectReason::Other(_)),
+                "code {}",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn table_covers_both_ranges() {
+        assert_eq!(
+            DisconnectReason::from_code(1),
+            DisconnectReason::Unspecified
+        );
+        assert_eq!(
+            DisconnectReason::from_code(68),
+            DisconnectReason::AlternativeChannelOccupied
+        );
+        assert_eq!(
+            DisconnectReason::from_code(212),
+            DisconnectReason::NoApFoundInRssiThreshold
+        );
+        assert_eq!(DisconnectReason::from_code(40), DisconnectReason::Other(40));
+        assert_eq!(DisconnectReason::from_code(0), DisconnectReason::Other(0));
+    }
+
+    #[test]
+    fn names_match_esp_idf() {
+        assert_eq!(
+            DisconnectReason::FourWayHandshakeTimeout.name(),
+            Some("4WAY_HANDSHAKE_TIMEOUT")
+        );
+        assert_eq!(
+            DisconnectReason::Ieee8021xAuthFailed.name(),
+            Some("802_1X_AUTH_FAILED")
+        );
+        assert_eq!(DisconnectReason::Other(999).name(), None);
+    }
+
+    #[test]
+    fn wrong_password_codes_are_credentials_rejected() {
+        for code in [202, 23] {
+            assert_eq!(
+                classify_disconnect(code),
+                DisconnectClass::CredentialsRejected,
+                "code {}",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn handshake_timeouts_are_credentials_suspected() {
+        for code in [15, 204] {
+            assert_eq!(
+                classify_disconnect(code),
+                DisconnectClass::CredentialsSuspected,
+                "code {}",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn missing_ap_codes_are_ap_not_found() {
+        for code in [201, 210, 211, 212] {
+            assert_eq!(classify_disconnect(code), DisconnectClass::ApNotFound);
+        }
+    }
+
+    #[test]
+    fn beacon_loss_is_radio_issue() {
+        assert_eq!(classify_disconnect(200), DisconnectClass::RadioIssue);
+        assert_eq!(classify_disconnect(34), DisconnectClass::RadioIssue);
+    }
+
+    #[test]
+    fn ap_initiated_and_unknown_are_transient() {
+        assert_eq!(classify_disconnect(8), DisconnectClass::Transient);
+        assert_eq!(classify_disconnect(207), DisconnectClass::Transient);
+        assert_eq!(classify_disconnect(0), DisconnectClass::Transient);
+        assert_eq!(classify_disconnect(999), DisconnectClass::Transient);
+    }
+
+    #[test]
+    fn from_u16_matches_from_code() {
+        assert_eq!(DisconnectReason::from(201), DisconnectReason::NoApFound);
+    }
+}
@@ -0,0 +1,271 @@
+//! Typed Wi-Fi scan results shared by every driver tier.
+//!
+//! Each platform crate converts its native scan record (`esp-idf-svc`
+//! `AccessPointInfo`, `esp-radio` `AccessPointInfo`) into [`ApInfo`] and
+//! collects at most [`SCAN_MAX_RESULTS`] of them into a [`ScanResults`] list
+//! with [`insert_scan_result`], which keeps the list sorted strongest-first and
+//! drops the weakest entry on overflow.  Consumers (firmware, the provisioning
+//! portal's network picker, [`rank_networks`][super::rank_networks]) therefore
+//! see the same type and ordering regardless of the driver underneath.
+
+use super::SSID_MAX_LEN;
+
+/// Maximum number of access points kept from one scan.
+///
+/// Sixteen fills a provisioning-portal dropdown comfortably and keeps a
+/// [`ScanResults`] under 1 KiB; weaker APs beyond this are dropped.
+pub const SCAN_MAX_RESULTS: usize = 16;
+
+/// Bounded scan result list, sorted by RSSI (strongest first) when built with
+/// [`insert_scan_result`].
+pub type ScanResults = heapless::Vec<ApInfo, SCAN_MAX_RESULTS>;
+
+// ─── AuthMode ───────────────────────────────────────────────────────────────
+
+/// Authentication mode advertised by an access point.
+///
+/// Mirrors the modes both ESP32 driver stacks report; anything a driver
+/// cannot map (e.g. a mode added in a newer SDK) becomes [`AuthMode::Unknown`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AuthMode {
+    /// No authentication.
+    Open,
+    /// WEP (legacy, insecure).
+    Wep,
+    /// WPA-PSK.
+    WpaPersonal,
+    /// WPA2-PSK.
+    Wpa2Personal,
+    /// WPA/WPA2-PSK mixed mode.
+    WpaWpa2Personal,
+    /// WPA2-Enterprise (802.1X).
+    Wpa2Enterprise,
+    /// WPA3-SAE.
+    Wpa3Personal,
+    /// WPA2-PSK/WPA3-SAE transition mode.
+    Wpa2Wpa3Personal,
+    /// WPA3-Enterprise.
+    Wpa3Enterprise,
+    /// WAPI-PSK.
+    WapiPersonal,
+    /// Opportunistic Wireless Encryption (encrypted, no password).
+    Owe,
+    /// Mode not reported or not recognised by this crate.
+    #[default]
+    Unknown,
+}
+
+impl AuthMode {
+    /// Returns `true` if joining this network needs a pre-shared key.
+    ///
+    /// Enterprise modes return `false` — they need EAP identity material,
+    /// not a PSK — as do [`AuthMode::Open`], [`AuthMode::Owe`], and
+    /// [`AuthMode::Unknown`].
+    pub fn requires_password(self) -> bool {
+        matches!(
+            self,
+            Self::Wep
+                | Self::WpaPersonal
+                | Self::Wpa2Personal
+                | Self::WpaWpa2Personal
+                | Self::Wpa3Personal
+                | Self::Wpa2Wpa3Personal
+                | Self::WapiPersonal
+        )
+    }
+
+    /// Short human-readable name, suitable for a network picker.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Open => "Open",
+            Self::Wep => "WEP",
+            Self::WpaPersonal => "WPA",
+            Self::Wpa2Personal => "WPA2",
+            Self::WpaWpa2Personal => "WPA/WPA2",
+            Self::Wpa2Enterprise => "WPA2-Enterprise",
+            Self::Wpa3Personal => "WPA3",
+            Self::Wpa2Wpa3Personal => "WPA2/WPA3",
+            Self::Wpa3Enterprise => "WPA3-Enterprise",
+            Self::WapiPersonal => "WAPI",
+            Self::Owe => "OWE",
+            Self::Unknown => "Unknown",
+        }
+    }
+}
+
+// ─── ApInfo ─────────────────────────────────────────────────────────────────
+
+/// One access point seen during a scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApInfo {
+    /// Network SSID; empty for hidden networks.
+    pub ssid: heapless::String<SSID_MAX_LEN>,
+    /// Access-point MAC address.
+    pub bssid: [u8; 6],
+    /// Primary 2.4 GHz channel.
+    pub channel: u8,
+    /// Received signal strength (dBm).
+    pub rssi: i8,
+    /// Advertised authentication mode.
+    pub auth_mode: AuthMode,
+}
+
+impl ApInfo {
+    /// Creates a scan record.
+    ///
+    /// SSIDs longer than [`SSID_MAX_LEN`] bytes are truncated on a UTF-8
+    /// character boundary; drivers only ever report up to 32 bytes, so this
+    /// only matters for hand-built records.
+    pub fn new(ssid: &str, bssid: [u8; 6], channel: u8, rssi: i8, auth_mode: AuthMode) -> Self {
+        let mut end = ssid.len().min(SSID_MAX_LEN);
+        while !ssid.is_char_boundary(end) {
+            end -= 1;
+        }
+        let mut owned = heapless::String::new();
+        // Cannot fail: `end <= SSID_MAX_LEN` is the string's capacity.
+        let _ = owned.push_str(&ssid[..end]);
+        Self {
+            ssid: owned,
+            bssid,
+            channel,
+            rssi,
+            auth_mode,
+        }
+    }
+
+    /// Returns `true` if the AP hides its SSID (empty SSID in the beacon).
+    pub fn is_hidden(&self) -> bool {
+        self.ssid.is_empty()
+    }
+}
+
+/// Inserts `ap` into `results`, keeping the list sorted by RSSI (strongest
+/// first, insertion order on ties).
+///
+/// When the list is full, `ap` replaces the weakest entry if it is stronger
+/// and is dropped otherwise.  Returns `true` if `ap` was kept.
+pub fn insert_scan_result(results: &mut ScanResults, ap: ApInfo) -> bool {
+    let pos = results
+        .iter()
+        .position(|existing| ap.rssi > existing.rssi)
+        .unwrap_or(results.len());
+    if results.is_full() {
+        if pos == results.len() {
+            return false;
+        }
+        results.pop();
+    }
+    // Cannot fail: either there was room or the weakest entry was just popped.
+    results.insert(pos, ap).is_ok()
+}
+
+/// Adapts scan results to the `(ssid, rssi)` pairs expected by
+/// [`rank_networks`][super::rank_networks] and
+/// [`select_network`][super::select_network].
+pub fn scan_candidates(results: &[ApInfo]) -> impl Iterator<Item = (&str, i8)> {
+    results.iter().map(|ap| (ap.ssid.as_str(), ap.rssi))
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ap(ssid: &str, rssi: i8) -> ApInfo {
+        ApInfo::new(ssid, [0x02, 0, 0, 0, 0, 1], 6, rssi, AuthMode::Wpa2Personal)
+    }
+
+    fn rssis(results: &ScanResults) -> heapless::Vec<i8, SCAN_MAX_RESULTS> {
+        results.iter().map(|a| a.rssi).collect()
+    }
+
+    #[test]
+    fn ap_info_new_copies_fields() {
+        let info = ApInfo::new("lab", [1, 2, 3, 4, 5, 6], 11, -52, AuthMode::Wpa3Personal);
+        assert_eq!(info.ssid.as_str(), "lab");
+        assert_eq!(info.bssid, [1, 2, 3, 4, 5, 6]);
+        assert_eq!(info.channel, 11);
+        assert_eq!(info.rssi, -52);
+        assert_eq!(info.auth_mode, AuthMode::Wpa3Personal);
+        assert!(!info.is_hidden());
+    }
+
+    #[test]
+    fn ap_info_truncates_long_ssid_on_char_boundary() {
+        // 31 ASCII bytes + a 2-byte character straddling the 32-byte limit.
+        let ssid = alloc::format!("{}é", "a".repeat(31));
+        let info = ApInfo::new(&ssid, [0; 6], 1, -60, AuthMode::Open);
+        assert_eq!(info.ssid.len(), 31);
+    }
+
+    #[test]
+    fn empty_ssid_is_hidden() {
+        assert!(ap("", -70).is_hidden());
+    }
+
+    #[test]
+    fn insert_keeps_results_sorted_strongest_first() {
+        let mut results = ScanResults::new();
+        for rssi in [-70, -40, -90, -55] {
+            assert!(insert_scan_result(&mut results, ap("n", rssi)));
+        }
+        assert_eq!(rssis(&results), [-40, -55, -70, -90]);
+    }
+
+    #[test]
+    fn insert_is_stable_on_equal_rssi() {
+        let mut results = ScanResults::new();
+        insert_scan_result(&mut results, ap("first", -60));
+        insert_scan_result(&mut results, ap("second", -60));
+        assert_eq!(results[0].ssid.as_str(), "first");
+        assert_eq!(results[1].ssid.as_str(), "second");
+    }
+
+    #[test]
+    fn insert_into_full_list_evicts_weakest() {
+        let mut results = ScanResults::new();
+        for i in 0..SCAN_MAX_RESULTS {
+            insert_scan_result(&mut results, ap("n", -50 - i as i8));
+        }
+        let weakest = results.last().unwrap().rssi;
+
+        assert!(!insert_scan_result(&mut results, ap("weak", weakest - 1)));
+        assert!(insert_scan_result(&mut results, ap("strong", -10)));
+        assert_eq!(results.len(), SCAN_MAX_RESULTS);
+        assert_eq!(results[0].ssid.as_str(), "strong");
+        assert!(results.iter().all(|a| a.rssi > weakest));
+    }
+
+    #[test]
+    fn requires_password_covers_psk_modes_only() {
+        assert!(AuthMode::Wpa2Personal.requires_password());
+        assert!(AuthMode::Wpa2Wpa3Personal.requires_password());
+        assert!(AuthMode::Wep.requires_password());
+        assert!(!AuthMode::Open.requires_password());
+        assert!(!AuthMode::Owe.requires_password());
+        assert!(!AuthMode::Wpa2Enterprise.requires_password());
+        assert!(!AuthMode::Unknown.requires_password());
+    }
+
+    #[test]
+    fn auth_mode_names_are_short() {
+        assert_eq!(AuthMode::Wpa2Wpa3Personal.name(), "WPA2/WPA3");
+        assert_eq!(AuthMode::default().name(), "Unknown");
+    }
+
+    #[test]
+    fn scan_candidates_feed_rank_networks() {
+        use crate::wifi::{rank_networks, WifiNetwork, WifiNetworkList};
+
+        let mut results = ScanResults::new();
+        insert_scan_result(&mut results, ap("lab", -80));
+        insert_scan_result(&mut results, ap("workshop", -45));
+        let networks = WifiNetworkList::new()
+            .with(WifiNetwork::new("lab", "open-sesame"))
+            .and_then(|l| l.with(WifiNetwork::new("workshop", "open-sesame")))
+            .unwrap();
+        let order = rank_networks(&networks, scan_candidates(&results));
+        assert_eq!(order[0], 1);
+    }
+}
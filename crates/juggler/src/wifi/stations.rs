@@ -0,0 +1,384 @@
+//! SoftAP station tracking: association events, client list, and admission.
+//!
+//! The platform managers feed what the driver reports about the SoftAP's
+//! clients into an [`ApStationTable`] — a station associated, the DHCP
+//! server leased it an address, it left — and get back the
+//! [`ApStationEvent`] to pass on to the application.  The table is also the
+//! answer to "who is connected right now", and it enforces the admission
+//! policy of an [`ApConfig`]:
+//!
+//! - a station whose MAC is not on [`ApConfig::allowlist`] is rejected with
+//!   [`ApRejectReason::NotAllowed`];
+//! - a station arriving while [`ApConfig::max_connections`] are already
+//!   associated is rejected with [`ApRejectReason::Full`].
+//!
+//! The driver has already let a rejected station associate by the time the
+//! table sees it, so the managers deauthenticate it on
+//! [`ApStationEvent::Rejected`].  Rejected stations are never added to the
+//! table; their later disassociation and any DHCP lease they manage to
+//! obtain in between produce no event.
+
+use core::net::Ipv4Addr;
+
+use super::{ApConfig, AP_MAX_CONNECTIONS_MAX};
+
+/// Maximum number of MAC addresses in an [`ApConfig::allowlist`].
+pub const AP_ALLOWLIST_MAX: usize = 16;
+
+/// Stations currently associated with the SoftAP, in association order.
+pub type ApStations = heapless::Vec<ApStation, { AP_MAX_CONNECTIONS_MAX as usize }>;
+
+// ─── Events ─────────────────────────────────────────────────────────────────
+
+/// Why an associating station was turned away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApRejectReason {
+    /// The MAC address is not on the configured allowlist.
+    NotAllowed,
+    /// The configured maximum number of stations is already associated.
+    Full,
+}
+
+/// Something that happened to a SoftAP client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApStationEvent {
+    /// A station associated and was admitted.
+    Connected {
+        /// Station hardware (MAC) address.
+        mac: [u8; 6],
+    },
+    /// The DHCP server leased an address to an associated station.
+    IpAssigned {
+        /// Station hardware (MAC) address.
+        mac: [u8; 6],
+        /// Leased IPv4 address.
+        ip: Ipv4Addr,
+    },
+    /// An admitted station disassociated.
+    Disconnected {
+        /// Station hardware (MAC) address.
+        mac: [u8; 6],
+        /// Address it held, if it had obtained one.
+        ip: Option<Ipv4Addr>,
+    },
+    /// A station associated but was refused; the manager deauthenticates it.
+    Rejected {
+        /// Station hardware (MAC) address.
+        mac: [u8; 6],
+        /// Which admission rule it failed.
+        reason: ApRejectReason,
+    },
+}
+
+impl ApStationEvent {
+    /// MAC address of the station the event is about.
+    pub fn mac(&self) -> [u8; 6] {
+        match *self {
+            Self::Connected { mac }
+            | Self::IpAssigned { mac, .. }
+            | Self::Disconnected { mac, .. }
+            | Self::Rejected { mac, .. } => mac,
+        }
+    }
+}
+
+/// A station associated with the SoftAP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApStation {
+    /// Station hardware (MAC) address.
+    pub mac: [u8; 6],
+    /// Address leased by the DHCP server, or `None` until it has one.
+    pub ip: Option<Ipv4Addr>,
+}
+
+// ─── ApStationTable ─────────────────────────────────────────────────────────
+
+/// Associated SoftAP stations plus the admission policy they passed.
+#[derive(Debug, Clone)]
+pub struct ApStationTable {
+    stations: ApStations,
+    allowlist: Option<heapless::Vec<[u8; 6], AP_ALLOWLIST_MAX>>,
+    max_clients: u8,
+}
+
+impl ApStationTable {
+    /// Creates an empty table that admits any station, up to `max_clients`
+    /// (capped at [`AP_MAX_CONNECTIONS_MAX`]).
+    pub const fn new(max_clients: u8) -> Self {
+        Self {
+            stations: heapless::Vec::new(),
+            allowlist: None,
+            max_clients: if max_clients > AP_MAX_CONNECTIONS_MAX {
+                AP_MAX_CONNECTIONS_MAX
+            } else {
+                max_clients
+            },
+        }
+    }
+
+    /// Creates an empty table enforcing the `max_connections` and
+    /// `allowlist` of `config`.
+    ///
+    /// Only the first [`AP_ALLOWLIST_MAX`] allowlist entries are kept;
+    /// [`validate_ap_config`](super::validate_ap_config) rejects longer lists.
+    pub fn from_config(config: &ApConfig<'_>) -> Self {
+        let mut table = Self::new(config.max_connections);
+        table.allowlist = config.allowlist.map(|macs| {
+            macs.iter()
+                .take(AP_ALLOWLIST_MAX)
+                .copied()
+                .collect::<heapless::Vec<_, AP_ALLOWLIST_MAX>>()
+        });
+        table
+    }
+
+    /// Returns `true` if `mac` passes the allowlist (always, without one).
+    pub fn is_allowed(&self, mac: &[u8; 6]) -> bool {
+        self.allowlist
+            .as_ref()
+            .is_none_or(|allowed| allowed.contains(mac))
+    }
+
+    /// Records that `mac` associated, returning [`ApStationEvent::Connected`]
+    /// or [`ApStationEvent::Rejected`].
+    ///
+    /// A station that re-associates without having disassociated keeps its
+    /// entry and address.
+    pub fn associate(&mut self, mac: [u8; 6]) -> ApStationEvent {
+        if !self.is_allowed(&mac) {
+            return ApStationEvent::Rejected {
+                mac,
+                reason: ApRejectReason::NotAllowed,
+            };
+        }
+        if self.get(&mac).is_none() {
+            let full = self.stations.len() >= usize::from(self.max_clients)
+                || self.stations.push(ApStation { mac, ip: None }).is_err();
+            if full {
+                return ApStationEvent::Rejected {
+                    mac,
+                    reason: ApRejectReason::Full,
+                };
+            }
+        }
+        ApStationEvent::Connected { mac }
+    }
+
+    /// Records a DHCP lease of `ip` to `mac`.
+    ///
+    /// Returns `None` — and records nothing — if `mac` is not an admitted
+    /// station, or if it already held `ip` (a lease renewal).
+    pub fn assign_ip(&mut self, mac: [u8; 6], ip: Ipv4Addr) -> Option<ApStationEvent> {
+        let station = self.stations.iter_mut().find(|s| s.mac == mac)?;
+        if station.ip == Some(ip) {
+            return None;
+        }
+        station.ip = Some(ip);
+        Some(ApStationEvent::IpAssigned { mac, ip })
+    }
+
+    /// Records that `mac` disassociated.
+    ///
+    /// Returns `None` if `mac` was not an admitted station.
+    pub fn disassociate(&mut self, mac: [u8; 6]) -> Option<ApStationEvent> {
+        let index = self.stations.iter().position(|s| s.mac == mac)?;
+        let station = self.stations.remove(index);
+        Some(ApStationEvent::Disconnected {
+            mac,
+            ip: station.ip,
+        })
+    }
+
+    /// Admitted stations, in association order.
+    pub fn stations(&self) -> &[ApStation] {
+        &self.stations
+    }
+
+    /// The entry for `mac`, if it is an admitted station.
+    pub fn get(&self, mac: &[u8; 6]) -> Option<&ApStation> {
+        self.stations.iter().find(|s| &s.mac == mac)
+    }
+
+    /// Forgets every station, keeping the admission policy; call it when
+    /// the AP stops.
+    pub fn clear(&mut self) {
+        self.stations.clear();
+    }
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHONE: [u8; 6] = [0x02, 0x00, 0x00, 0x00, 0x00, 0x01];
+    const LAPTOP: [u8; 6] = [0x02, 0x00, 0x00, 0x00, 0x00, 0x02];
+    const STRANGER: [u8; 6] = [0x02, 0x00, 0x00, 0x00, 0x00, 0x03];
+    const LEASE: Ipv4Addr = Ipv4Addr::new(192, 168, 4, 10);
+
+    #[test]
+    fn connect_lease_disconnect_round_trip() {
+        let mut table = ApStationTable::new(4);
+        assert_eq!(
+            table.associate(PHONE),
+            ApStationEvent::Connected { mac: PHONE }
+        );
+        assert_eq!(
+            table.assign_ip(PHONE, LEASE),
+            Some(ApStationEvent::IpAssigned {
+                mac: PHONE,
+                ip: LEASE
+            })
+        );
+        assert_eq!(
+            table.stations(),
+            &[ApStation {
+                mac: PHONE,
+                ip: Some(LEASE)
+            }]
+        );
+        assert_eq!(
+            table.disassociate(PHONE),
+            Some(ApStationEvent::Disconnected {
+                mac: PHONE,
+                ip: Some(LEASE)
+            })
+        );
+        assert!(table.stations().is_empty());
+    }
+
+    #[test]
+    fn lease_renewal_is_not_reported_again() {
+        let mut table = ApStationTable::new(4);
+        table.associate(PHONE);
+        assert!(table.assign_ip(PHONE, LEASE).is_some());
+        assert_eq!(table.assign_ip(PHONE, LEASE), None);
+        let moved = Ipv4Addr::new(192, 168, 4, 11);
+        assert!(table.assign_ip(PHONE, moved).is_some());
+        assert_eq!(table.get(&PHONE).unwrap().ip, Some(moved));
+    }
+
+    #[test]
+    fn unknown_stations_produce_no_events() {
+        let mut table = ApStationTable::new(4);
+        assert_eq!(table.assign_ip(STRANGER, LEASE), None);
+        assert_eq!(table.disassociate(STRANGER), None);
+        assert!(table.stations().is_empty());
+    }
+
+    #[test]
+    fn reassociation_keeps_entry_and_address() {
+        let mut table = ApStationTable::new(4);
+        table.associate(PHONE);
+        table.assign_ip(PHONE, LEASE);
+        assert_eq!(
+            table.associate(PHONE),
+            ApStationEvent::Connected { mac: PHONE }
+        );
+        assert_eq!(table.stations().len(), 1);
+        assert_eq!(table.get(&PHONE).unwrap().ip, Some(LEASE));
+    }
+
+    #[test]
+    fn full_table_rejects_new_stations() {
+        let mut table = ApStationTable::new(1);
+        table.associate(PHONE);
+        assert_eq!(
+            table.associate(LAPTOP),
+            ApStationEvent::Rejected {
+                mac: LAPTOP,
+                reason: ApRejectReason::Full
+            }
+        );
+        assert_eq!(table.stations().len(), 1);
+        table.disassociate(PHONE);
+        assert_eq!(
+            table.associate(LAPTOP),
+            ApStationEvent::Connected { mac: LAPTOP }
+        );
+    }
+
+    #[test]
+    fn max_clients_is_capped_at_driver_limit() {
+        let mut table = ApStationTable::new(u8::MAX);
+        for i in 0..AP_MAX_CONNECTIONS_MAX {
+            let event = table.associate([0x02, 0, 0, 0, 1, i]);
+            assert!(matches!(event, ApStationEvent::Connected { .. }));
+        }
+        assert!(matches!(
+            table.associate(STRANGER),
+            ApStationEvent::Rejected {
+                reason: ApRejectReason::Full,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn allowlist_rejects_unlisted_stations() {
+        let allowed = [PHONE, LAPTOP];
+        let config = ApConfig::wpa2("test-net", "open-sesame").with_allowlist(&allowed);
+        let mut table = ApStationTable::from_config(&config);
+        assert!(table.is_allowed(&LAPTOP));
+        assert_eq!(
+            table.associate(STRANGER),
+            ApStationEvent::Rejected {
+                mac: STRANGER,
+                reason: ApRejectReason::NotAllowed
+            }
+        );
+        assert_eq!(table.assign_ip(STRANGER, LEASE), None);
+        assert_eq!(
+            table.associate(PHONE),
+            ApStationEvent::Connected { mac: PHONE }
+        );
+    }
+
+    #[test]
+    fn from_config_copies_max_connections() {
+        let config = ApConfig::open("test-net").with_max_connections(2);
+        let mut table = ApStationTable::from_config(&config);
+        assert!(table.is_allowed(&STRANGER));
+        table.associate(PHONE);
+        table.associate(LAPTOP);
+        assert!(matches!(
+            table.associate(STRANGER),
+            ApStationEvent::Rejected {
+                reason: ApRejectReason::Full,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn clear_keeps_policy() {
+        let allowed = [PHONE];
+        let config = ApConfig::open("test-net").with_allowlist(&allowed);
+        let mut table = ApStationTable::from_config(&config);
+        table.associate(PHONE);
+        table.clear();
+        assert!(table.stations().is_empty());
+        assert!(!table.is_allowed(&LAPTOP));
+    }
+
+    #[test]
+    fn event_mac_accessor() {
+        let events = [
+            ApStationEvent::Connected { mac: PHONE },
+            ApStationEvent::IpAssigned {
+                mac: PHONE,
+                ip: LEASE,
+            },
+            ApStationEvent::Disconnected {
+                mac: PHONE,
+                ip: None,
+            },
+            ApStationEvent::Rejected {
+                mac: PHONE,
+                reason: ApRejectReason::Full,
+            },
+        ];
+        assert!(events.iter().all(|event| event.mac() == PHONE));
+    }
+}
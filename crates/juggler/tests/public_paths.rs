@@ -17,6 +17,7 @@
 //   just test-lora         → -p juggler --features lora,mock
 //   just test-espnow       → -p juggler --features mock           (mock implies espnow)
 //   just test-mqtt         → -p juggler --features std mqtt        (std implies mqtt)
+//   just test-conformance  → -p juggler --features conformance,mock
 //   just test-ota          → -p juggler --features ota
 //   just test-provisioning → -p juggler --features provisioning
 
@@ -26,10 +27,24 @@
 #[test]
 fn wifi_public_paths() {
     use juggler::wifi::{
-        validate_ap_config, validate_password, validate_ssid, wifi_disconnect_reason_name,
-        ApConfig, ConnectMode, TxPowerLevel, WiFiConfig, WifiDriver, WifiPowerSave, AP_CHANNEL_MAX,
-        AP_CHANNEL_MIN, AP_MAX_CONNECTIONS_DEFAULT, AP_PASSWORD_MIN_LEN, DEFAULT_TIMEOUT_SECS,
-        PASSWORD_MAX_LEN, POLL_INTERVAL_MS, SSID_MAX_LEN,
+        classify_disconnect, derive_pmk, insert_scan_result, netmask_prefix_len, next_link_state,
+        pmk_applicable, priority_order, rank_networks, scan_candidates, select_network,
+        validate_ap_config, validate_ap_subnet, validate_auth_method, validate_hostname,
+        validate_ip_config, validate_link_quality_config, validate_mdns_config,
+        validate_network_list, validate_password, validate_phy, validate_ssid,
+        wifi_disconnect_reason_name, ApConfig, ApInfo, ApRejectReason, ApStation, ApStationEvent,
+        ApStationTable, ApStations, ApSubnet, AsyncWifiDriver, AuthMode, ConnectMode, Country,
+        DisconnectClass, DisconnectReason, IpConfig, LinkQuality, LinkQualityConfig,
+        LinkQualityEvent, LinkQualityMonitor, MdnsConfig, MdnsService, NetworkOrder, ScanResults,
+        ServiceProtocol, TxPowerLevel, WiFiConfig, WifiAuthMethod, WifiBandwidth, WifiDriver,
+        WifiFastConnectCache, WifiLinkAction, WifiLinkEvent, WifiLinkState, WifiLinkSupervisor,
+        WifiNetwork, WifiNetworkList, WifiPhy, WifiPowerSave, WifiProtocol, AP_ALLOWLIST_MAX,
+        AP_CHANNEL_MAX, AP_CHANNEL_MIN, AP_MAX_CONNECTIONS_DEFAULT, AP_MAX_CONNECTIONS_MAX,
+        AP_PASSWORD_MIN_LEN, DEFAULT_ANTENNA_GAIN_DBI, DEFAULT_TIMEOUT_SECS, EAP_FIELD_MAX_LEN,
+        FAST_CONNECT_TIMEOUT_SECS, HOSTNAME_MAX_LEN, LINK_RETRY_BASE_MS, LINK_RETRY_MAX_MS,
+        MAX_NETWORKS, PASSWORD_MAX_LEN, PMK_LEN, POLL_INTERVAL_MS,
+        QUALITY_DEFAULT_SMOOTHING_PERCENT, QUALITY_RECOVERED_DBM, QUALITY_ROAM_DBM,
+        QUALITY_WEAK_DBM, SCAN_MAX_RESULTS, SSID_MAX_LEN, WPA2_PASSPHRASE_MIN_LEN,
     };
 
     // Constants are reachable.
@@ -41,12 +56,88 @@ fn wifi_public_paths() {
     let _: u8 = AP_MAX_CONNECTIONS_DEFAULT;
     let _: u64 = DEFAULT_TIMEOUT_SECS;
     let _: u64 = POLL_INTERVAL_MS;
+    let _: i8 = DEFAULT_ANTENNA_GAIN_DBI;
+
+    // Regulatory domain.
+    let us = Country::from_code("US").unwrap();
+    assert_eq!(us.channels().len(), 11);
+    assert!(!us.allows_channel(13));
+    assert_eq!(Country::Eu.max_eirp_dbm(), 20);
+    let _: TxPowerLevel = Country::Eu.clamp_tx_power(TxPowerLevel::Max);
+    assert!(validate_ap_config(&ApConfig::open("ap").with_country(us).with_channel(13)).is_err());
+    let _: TxPowerLevel = WiFiConfig::new("TestNet", "hunter2")
+        .with_country(Country::Jp)
+        .effective_tx_power();
+
+    // PHY mode.
+    let lr = ApConfig::open("ap").with_protocol(WifiProtocol::Lr);
+    assert!(validate_ap_config(&lr).is_ok());
+    assert!(validate_ap_config(&lr.with_bandwidth(WifiBandwidth::Ht40)).is_err());
+    assert!(validate_phy(&WifiPhy::LONG_RANGE).is_ok());
+    let _: WifiPhy = WiFiConfig::new("TestNet", "hunter2")
+        .with_protocol(WifiProtocol::BgnLr)
+        .phy;
+
+    // SoftAP subnet.
+    let subnet = ApSubnet::default();
+    assert!(validate_ap_subnet(&subnet).is_ok());
+    assert_eq!(subnet.prefix_len(), Some(24));
+    let moved = ApConfig::open("ap")
+        .with_ip(
+            core::net::Ipv4Addr::new(10, 42, 0, 1),
+            core::net::Ipv4Addr::new(255, 255, 255, 0),
+        )
+        .with_dhcp_range(
+            core::net::Ipv4Addr::new(10, 42, 0, 10),
+            core::net::Ipv4Addr::new(10, 42, 0, 20),
+        );
+    assert!(validate_ap_config(&moved).is_ok());
+    assert_eq!(moved.subnet.pool_size(), 11);
+
+    // SoftAP stations.
+    let _: usize = AP_ALLOWLIST_MAX;
+    let allowed = [[0x02, 0, 0, 0, 0, 1]];
+    let ap = ApConfig::open("ap")
+        .with_max_connections(AP_MAX_CONNECTIONS_MAX)
+        .with_allowlist(&allowed);
+    assert!(validate_ap_config(&ap).is_ok());
+    let mut table = ApStationTable::from_config(&ap);
+    assert_eq!(
+        table.associate([0x02, 0, 0, 0, 0, 2]),
+        ApStationEvent::Rejected {
+            mac: [0x02, 0, 0, 0, 0, 2],
+            reason: ApRejectReason::NotAllowed,
+        }
+    );
+    let event = table.associate(allowed[0]);
+    assert_eq!(event.mac(), allowed[0]);
+    let stations: &[ApStation] = table.stations();
+    assert_eq!(stations.len(), 1);
+    let _: ApStations = ApStations::new();
+
+    // Fast reconnect cache.
+    let _: u64 = FAST_CONNECT_TIMEOUT_SECS;
+    let pmk: [u8; PMK_LEN] = derive_pmk("TestNet", "hunter22");
+    assert!(pmk_applicable(&WifiAuthMethod::Wpa2Personal, "hunter22"));
+    let cache = WifiFastConnectCache::new("TestNet", "hunter22", [0x02, 0, 0, 0, 0, 1], 6)
+        .with_pmk(pmk)
+        .with_ip_lease(IpConfig::Dhcp);
+    assert!(cache.matches("TestNet", "hunter22"));
+    assert_eq!(cache.psk_hex().map(|psk| psk.len()), Some(64));
+    let config = WiFiConfig::new("TestNet", "hunter22").with_fast_connect(cache);
+    assert_eq!(cache.network_index(&config.networks()), Some(0));
+    assert!(!WifiFastConnectCache::empty().is_valid());
 
     // Validator functions are callable.
     assert!(validate_ssid("TestNet").is_ok());
     assert!(validate_password("hunter2").is_ok());
     assert_eq!(wifi_disconnect_reason_name(201), Some("NO_AP_FOUND"));
     assert_eq!(wifi_disconnect_reason_name(0), None);
+    assert_eq!(DisconnectReason::from_code(15).code(), 15);
+    assert_eq!(
+        classify_disconnect(202),
+        DisconnectClass::CredentialsRejected
+    );
 
     // WiFiConfig builder chain.
     let cfg = WiFiConfig::new("TestNet", "hunter2")
@@ -75,9 +166,102 @@ fn wifi_public_paths() {
     let ap_open = ApConfig::open("OpenNet");
     assert!(validate_ap_config(&ap_open).is_ok());
 
+    // Multi-network list and selection.
+    let _: usize = MAX_NETWORKS;
+    let networks = WifiNetworkList::new()
+        .with(WifiNetwork::new("Lab", "hunter2").with_priority(2))
+        .and_then(|l| l.with(WifiNetwork::new("TestNet", "hunter2")))
+        .unwrap();
+    assert!(validate_network_list(&networks).is_ok());
+    let order: NetworkOrder = rank_networks(&networks, [("TestNet", -40)]);
+    assert_eq!(order[0], 1);
+    assert_eq!(priority_order(&networks)[0], 0);
+    assert_eq!(select_network(&networks, [("Lab", -70)]), Some(0));
+    assert_eq!(WiFiConfig::from_networks(networks).ssid, "Lab");
+
+    // Typed scan results.
+    let _: usize = SCAN_MAX_RESULTS;
+    let mut results = ScanResults::new();
+    assert!(insert_scan_result(
+        &mut results,
+        ApInfo::new("TestNet", [0; 6], 6, -50, AuthMode::Wpa2Personal)
+    ));
+    assert_eq!(scan_candidates(&results).next(), Some(("TestNet", -50)));
+    assert!(AuthMode::Wpa2Personal.requires_password());
+
+    // Static IPv4 addressing.
+    use std::net::Ipv4Addr;
+    let mask = Ipv4Addr::new(255, 255, 255, 0);
+    assert_eq!(netmask_prefix_len(mask), Some(24));
+    let static_cfg = WiFiConfig::new("TestNet", "hunter2").with_static_ip(
+        Ipv4Addr::new(192, 168, 1, 20),
+        mask,
+        Ipv4Addr::new(192, 168, 1, 1),
+        None,
+    );
+    assert!(validate_ip_config(&static_cfg.ip).is_ok());
+    assert_eq!(
+        WiFiConfig::new("TestNet", "hunter2")
+            .with_ip_config(IpConfig::Dhcp)
+            .ip,
+        IpConfig::Dhcp
+    );
+
+    // Station authentication methods.
+    let _: usize = EAP_FIELD_MAX_LEN;
+    let _: usize = WPA2_PASSPHRASE_MIN_LEN;
+    let peap = WifiAuthMethod::EapPeap {
+        identity: "anonymous",
+        username: "alice",
+        password: "hunter2",
+    };
+    assert!(validate_auth_method(&peap, "").is_ok());
+    assert!(validate_auth_method(&WifiAuthMethod::Wpa3Personal, "").is_err());
+    let wpa3 = WiFiConfig::new("TestNet", "hunter2").with_auth(WifiAuthMethod::Wpa3Personal);
+    assert_eq!(
+        wpa3.networks().get(0).unwrap().auth,
+        WifiAuthMethod::Wpa3Personal
+    );
+    let _ = WifiNetwork::new("Campus", "").with_auth(peap);
+
+    // Link supervision.
+    let _: u64 = LINK_RETRY_BASE_MS;
+    let _: u64 = LINK_RETRY_MAX_MS;
+    assert_eq!(
+        next_link_state(WifiLinkState::Idle, WifiLinkEvent::Start),
+        Some(WifiLinkState::Connecting)
+    );
+    let mut supervisor = WifiLinkSupervisor::new();
+    assert_eq!(
+        supervisor.handle(WifiLinkEvent::Start),
+        Some(WifiLinkAction::Connect)
+    );
+
+    // Hostname and mDNS.
+    let _: usize = HOSTNAME_MAX_LEN;
+    let named = WiFiConfig::new("TestNet", "hunter2").with_hostname("greenhouse");
+    assert!(validate_hostname(named.hostname.unwrap()).is_ok());
+    let services = [MdnsService::new("_http", ServiceProtocol::Tcp, 80).with_txt(&[("path", "/")])];
+    let mdns = MdnsConfig::new("greenhouse").with_services(&services);
+    assert!(validate_mdns_config(&mdns).is_ok());
+
+    // Link-quality monitoring.
+    let _: u8 = QUALITY_DEFAULT_SMOOTHING_PERCENT;
+    let _: i8 = QUALITY_RECOVERED_DBM;
+    let _: i8 = QUALITY_ROAM_DBM;
+    let quality = LinkQualityConfig::new().with_weak_threshold(QUALITY_WEAK_DBM, -65);
+    assert!(validate_link_quality_config(&quality).is_ok());
+    let mut monitor = LinkQualityMonitor::new(quality.with_smoothing(100)).unwrap();
+    assert_eq!(
+        monitor.sample(-80),
+        Some(LinkQualityEvent::Weak { rssi_dbm: -80 })
+    );
+    assert_eq!(monitor.quality(), LinkQuality::Weak);
+
     // WifiDriver trait is in scope (turbofish would require a concrete type;
     // just confirm it names a trait by using it as a bound in a local fn).
     fn _accepts_driver<D: WifiDriver>(_: &D) {}
+    fn _accepts_async_driver<D: AsyncWifiDriver>(_: &D) {}
 }
 
 // ── wifi::mock ────────────────────────────────────────────────────────────────
@@ -85,8 +269,11 @@ fn wifi_public_paths() {
 #[cfg(all(feature = "wifi", feature = "mock"))]
 #[test]
 fn wifi_mock_public_paths() {
-    use juggler::wifi::mock::{MockWifiDriver, MockWifiError};
-    use juggler::wifi::WifiDriver;
+    use juggler::wifi::mock::{
+        block_on, AsyncMockWifiDriver, MockConnect, MockWifiDriver, MockWifiError,
+        MOCK_CONNECT_SCRIPT_LEN,
+    };
+    use juggler::wifi::{AsyncWifiDriver, DisconnectReason, WifiDriver};
 
     let mut drv = MockWifiDriver::new();
     drv.configure("ssid", "psk").unwrap();
@@ -95,9 +282,55 @@ fn wifi_mock_public_paths() {
     assert!(drv.is_connected().unwrap());
     drv.disconnect().unwrap();
     assert!(!drv.is_connected().unwrap());
+    assert!(drv.scan().unwrap().is_empty());
 
     // MockWifiError is reachable.
     let _: MockWifiError = MockWifiError::ConnectFailed;
+    let _: MockWifiError = MockWifiError::ScanFailed;
+    let _: MockWifiError = MockWifiError::ScriptFull;
+
+    // Scripted scenario on the virtual clock.
+    let _: usize = MOCK_CONNECT_SCRIPT_LEN;
+    drv.queue_connect(MockConnect::Fail(DisconnectReason::NoApFound))
+        .unwrap();
+    drv.queue_connect(MockConnect::SucceedThenDrop {
+        after_ms: 30_000,
+        reason: DisconnectReason::BeaconTimeout,
+    })
+    .unwrap();
+    drv.dhcp_delay_ms = 500;
+    assert!(drv.connect().is_err());
+    assert_eq!(
+        drv.take_disconnect_event(),
+        Some(DisconnectReason::NoApFound)
+    );
+    drv.connect().unwrap();
+    drv.advance_ms(500);
+    assert!(drv.netif_up);
+    drv.schedule_disconnect(1_000, DisconnectReason::AssocExpire);
+    drv.advance_ms(1_000);
+    assert_eq!(
+        drv.take_disconnect_event(),
+        Some(DisconnectReason::AssocExpire)
+    );
+    assert_eq!(drv.pending_connects(), 0);
+    let _: MockConnect = MockConnect::Succeed;
+
+    // Async variant, driven by the bundled executor.
+    let mut async_drv = AsyncMockWifiDriver::new();
+    block_on(AsyncWifiDriver::connect(&mut async_drv)).unwrap();
+    assert!(AsyncWifiDriver::is_connected(&async_drv).unwrap());
+}
+
+// ── pem ───────────────────────────────────────────────────────────────────────
+
+#[cfg(any(feature = "wifi", feature = "mqtt", feature = "std"))]
+#[test]
+fn pem_public_paths() {
+    use juggler::pem::{is_pem, pem_needs_nul, PEM_BEGIN};
+
+    assert!(is_pem(PEM_BEGIN));
+    assert!(pem_needs_nul(b"-----BEGIN CERTIFICATE-----\n"));
 }
 
 // ── mqtt ──────────────────────────────────────────────────────────────────────
@@ -106,15 +339,53 @@ fn wifi_mock_public_paths() {
 #[test]
 fn mqtt_public_paths() {
     use juggler::mqtt::{
-        connection_wait_iterations, next_state, resolve_client_id, topic_matches_filter,
-        validate_broker_host, validate_broker_port, validate_client_id, validate_publish_topic,
-        validate_subscribe_filter, validate_topic, MqttConnectionState, MqttEvent,
-        CLIENT_ID_MAX_LEN, TOPIC_MAX_LEN,
+        connection_wait_iterations, newest_slot, next_state, resolve_client_id, slot_header,
+        slot_seq, topic_matches_filter, validate_broker_host, validate_broker_port,
+        validate_client_id, validate_publish_topic, validate_subscribe_filter, validate_tls,
+        validate_tls_cert, validate_topic, validate_transport, validate_ws_path, Enqueued,
+        MqttConnectionState, MqttEvent, MqttTls, MqttTransport, OverflowPolicy, PublishQueue,
+        QueueError, QueuedPublish, TlsCa, TlsCert, CLIENT_ID_MAX_LEN, MQTT_PORT, MQTT_TLS_PORT,
+        MQTT_WSS_PORT, MQTT_WS_PORT, QUEUE_RECORD_HEADER_LEN, SLOT_HEADER_LEN, SNAPSHOT_HEADER_LEN,
+        TLS_SERVER_NAME_MAX_LEN, TOPIC_MAX_LEN, WS_DEFAULT_PATH, WS_PATH_MAX_LEN,
     };
 
     // Constants.
     let _: usize = CLIENT_ID_MAX_LEN;
     let _: usize = TOPIC_MAX_LEN;
+    let _: u16 = MQTT_PORT;
+    let _: u16 = MQTT_TLS_PORT;
+    let _: u16 = MQTT_WS_PORT;
+    let _: u16 = MQTT_WSS_PORT;
+    let _: usize = WS_PATH_MAX_LEN;
+    let _: usize = TLS_SERVER_NAME_MAX_LEN;
+
+    // Transport and TLS settings.
+    assert_eq!(MqttTransport::Tls.scheme(), "mqtts");
+    let wss = MqttTransport::SecureWebSocket {
+        path: WS_DEFAULT_PATH,
+    };
+    assert!(validate_transport(&wss).is_ok());
+    assert!(validate_ws_path("/mqtt").is_ok());
+    let tls: MqttTls<'_> = MqttTls::new(TlsCa::Bundle);
+    assert!(validate_tls(&tls).is_ok());
+    assert!(validate_tls_cert(&TlsCert::Der(&[0x30])).is_ok());
+    assert!(validate_tls_cert(&TlsCert::Pem(b"-----BEGIN CERTIFICATE-----\n")).is_ok());
+
+    // Offline publish queue.
+    let _: usize = QUEUE_RECORD_HEADER_LEN;
+    let mut queue = PublishQueue::new([0u8; 64], OverflowPolicy::Reject);
+    assert_eq!(
+        queue.push("sensors/temp", b"22.5", 1, false),
+        Ok(Enqueued::Stored)
+    );
+    assert_eq!(queue.push("t", b"x", 0, false), Err(QueueError::InvalidQos));
+    let front: Option<QueuedPublish<'_>> = queue.front();
+    assert_eq!(front.map(|p| p.topic), Some("sensors/temp"));
+    let header: [u8; SNAPSHOT_HEADER_LEN] = queue.snapshot_header();
+    assert!(PublishQueue::restore([0u8; 64], &header, OverflowPolicy::Reject).is_err());
+    let slot: [u8; SLOT_HEADER_LEN] = slot_header(1, &header);
+    assert_eq!(slot_seq(&slot, &header), Some(1));
+    assert_eq!(newest_slot([Some(1), None]), Some(0));
 
     // Validation functions.
     assert!(validate_topic("sensors/temperature").is_ok());
@@ -163,12 +434,70 @@ fn mqtt_public_paths() {
     );
 }
 
+// ── mqtt codec ───────────────────────────────────────────────────────────────
+
+#[cfg(any(feature = "mqtt", feature = "std"))]
+#[test]
+fn mqtt_codec_public_paths() {
+    use juggler::mqtt::codec::{
+        decode_remaining_length, encode_remaining_length, packet_len, CodecError, ConnAck, Connect,
+        ConnectReturnCode, Packet, Publish, QoS, SubAck, SubscribeFilters, UnsubscribeFilters,
+        Will, PROTOCOL_LEVEL, REMAINING_LENGTH_MAX, SUBACK_FAILURE,
+    };
+
+    let _: u8 = PROTOCOL_LEVEL;
+    let _: u8 = SUBACK_FAILURE;
+    let _: usize = REMAINING_LENGTH_MAX;
+    // The root re-export and the codec's QoS are the same type.
+    let _: juggler::mqtt::QoS = QoS::AtLeastOnce;
+
+    let mut buf = [0u8; 64];
+    let connect = Connect {
+        will: Some(Will {
+            topic: "dev/status",
+            payload: b"offline",
+            qos: QoS::AtLeastOnce,
+            retain: true,
+        }),
+        ..Connect::new("dev", 60)
+    };
+    let n = Packet::Connect(connect).encode(&mut buf).unwrap();
+    assert_eq!(packet_len(&buf[..n]), Ok(n));
+    assert_eq!(Packet::decode(&buf[..n]), Ok((Packet::Connect(connect), n)));
+
+    let ack = ConnAck {
+        session_present: false,
+        code: ConnectReturnCode::Accepted,
+    };
+    let _ = Packet::ConnAck(ack);
+    let _ = Packet::Publish(Publish::new("a/b", b"x"));
+    let _ = Packet::Subscribe {
+        packet_id: 1,
+        filters: SubscribeFilters::new(&[("a/#", QoS::AtMostOnce)]),
+    };
+    let _ = Packet::Unsubscribe {
+        packet_id: 2,
+        filters: UnsubscribeFilters::new(&["a/#"]),
+    };
+    let _ = Packet::SubAck(SubAck {
+        packet_id: 1,
+        return_codes: &[0],
+    });
+
+    let mut varint = [0u8; 4];
+    assert_eq!(encode_remaining_length(128, &mut varint), Ok(2));
+    assert_eq!(decode_remaining_length(&varint), Ok((128, 2)));
+    assert_eq!(Packet::decode(&[0x40]), Err(CodecError::Incomplete));
+}
+
 // ── mqtt std helpers ──────────────────────────────────────────────────────────
 
 #[cfg(feature = "std")]
 #[test]
 fn mqtt_std_public_paths() {
-    use juggler::mqtt::{format_broker_url, spawn_subscriber_thread, QoS, SubscribeClient};
+    use juggler::mqtt::{
+        format_broker_url, spawn_subscriber_thread, MqttTransport, QoS, SubscribeClient,
+    };
     use std::sync::{Arc, Mutex};
 
     // QoS enum is reachable.
@@ -177,7 +506,7 @@ fn mqtt_std_public_paths() {
     let _: QoS = QoS::ExactlyOnce;
 
     // format_broker_url produces a usable URL.
-    let url = format_broker_url("broker.local", 1883);
+    let url = format_broker_url("broker.local", 1883, MqttTransport::Tcp);
     assert_eq!(url, "mqtt://broker.local:1883");
 
     // SubscribeClient trait in scope: verify with a minimal impl.
@@ -325,7 +654,7 @@ fn espnow_public_paths() {
     // Constants.
     let _: usize = MAX_DATA_LEN;
     let _: usize = DEFAULT_RX_CHANNEL_CAPACITY;
-    let _: [u8; 13] = DEFAULT_SCAN_CHANNELS;
+    let _: [u8; 11] = DEFAULT_SCAN_CHANNELS;
     let _: core::time::Duration = DEFAULT_PROBE_TIMEOUT;
     let _: core::time::Duration = DEFAULT_BURST_TIMEOUT;
     let _: u8 = DEFAULT_PROBE_CONFIRMATIONS;
@@ -405,6 +734,41 @@ fn espnow_mock_public_paths() {
     let _: MockEspNowError = MockEspNowError::SendFailed;
 }
 
+// ── conformance ───────────────────────────────────────────────────────────────
+
+#[cfg(all(feature = "conformance", feature = "mock"))]
+#[test]
+fn conformance_public_paths() {
+    use juggler::conformance::ContractViolation;
+    use juggler::espnow::conformance as espnow_conformance;
+    use juggler::espnow::mock::MockEspNowDriver;
+    use juggler::espnow::{PeerConfig, BROADCAST_MAC};
+    use juggler::lora::conformance::{self as lora_conformance, LoraContract};
+    use juggler::lora::mock::MockLoraRadio;
+    use juggler::wifi::conformance::{self as wifi_conformance, WifiContract};
+    use juggler::wifi::mock::{block_on, AsyncMockWifiDriver, MockWifiDriver};
+
+    let contract = WifiContract::new("test-net", "open-sesame").with_settle_polls(2);
+    wifi_conformance::run_contract_tests(&mut MockWifiDriver::new(), &contract, || {}).unwrap();
+    block_on(wifi_conformance::run_async_contract_tests(
+        &mut AsyncMockWifiDriver::new(),
+        &contract,
+    ))
+    .unwrap();
+
+    let lora = LoraContract::default().with_max_polls(3);
+    lora_conformance::run_contract_tests(&mut MockLoraRadio::new(), &lora, || {}).unwrap();
+
+    espnow_conformance::run_contract_tests(
+        &MockEspNowDriver::new(),
+        &PeerConfig::new(BROADCAST_MAC),
+    )
+    .unwrap();
+
+    let violation = ContractViolation::new("send", "send must succeed");
+    assert_eq!(violation.check, "send");
+}
+
 // ── ota ───────────────────────────────────────────────────────────────────────
 
 #[cfg(feature = "ota")]
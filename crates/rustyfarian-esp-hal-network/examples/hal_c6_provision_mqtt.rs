@@ -55,12 +55,17 @@ extern crate alloc;
 use embassy_executor::Spawner;
 use embassy_time::{Duration, Timer};
 use esp_backtrace as _;
+use esp_hal::peripherals::{SW_INTERRUPT, TIMG0, WIFI};
 use esp_println::println;
+use esp_radio::wifi::{Interface, WifiController};
+use rustyfarian_esp_hal_network::mqtt::{LwtConfig, MqttBuilder, MqttConfig, QoS};
 use rustyfarian_esp_hal_network::provisioning::{
-    PortalConfig, ProvisioningBuilder, ProvisioningEvent, ProvisioningOutcome, ProvisioningStore,
-    SchemaProfile,
+    PortalConfig, ProvisioningBuilder, ProvisioningConfig, ProvisioningEvent, ProvisioningOutcome,
+    ProvisioningStore, SchemaProfile,
+};
+use rustyfarian_esp_hal_network::wifi::{
+    ApConfig, ApConfigExt, AsyncWifiHandle, WiFiConfig, WiFiConfigExt, WiFiManager,
 };
-use rustyfarian_esp_hal_network::wifi::{ApConfig, ApConfigExt, WiFiManager};
 
 esp_bootloader_esp_idf::esp_app_desc!();
 
@@ -93,6 +98,27 @@ const FLASH_PARTITION_OFFSET: u32 = 0x0030_0000;
 /// Must be >= 8192 (two 4 KiB sectors).
 const FLASH_PARTITION_SIZE: u32 = 8192;
 
+/// Retained `online` / `offline` status topic; `offline` is the last will.
+const STATUS_TOPIC: &str = match option_env!("MQTT_STATUS_TOPIC") {
+    Some(s) => s,
+    None => "rustyfarian/status",
+};
+
+/// Topic the uptime counter is published to.
+const UPTIME_TOPIC: &str = match option_env!("MQTT_UPTIME_TOPIC") {
+    Some(s) => s,
+    None => "rustyfarian/uptime",
+};
+
+/// Seconds between uptime publishes.
+const PUBLISH_INTERVAL_SECS: u64 = 30;
+
+/// Delay before re-associating after the link drops.
+const RECONNECT_DELAY_MS: u64 = 500;
+
+/// Delay before retrying a failed association.
+const CONNECT_BACKOFF_MS: u64 = 5000;
+
 /// Lifecycle-event callback.
 /// Runs synchronously inside the HTTP task and must not block.
 fn on_event(event: ProvisioningEvent) {
@@ -107,7 +133,7 @@ fn on_event(event: ProvisioningEvent) {
                 m[4],
                 m[5]
             ),
-            None => log::info!("Portal: phone joined AP (mac not yet exposed by esp-radio 0.18)"),
+            None => log::info!("Portal: phone joined AP"),
         },
         ProvisioningEvent::ClientDisconnected { mac } => match mac {
             Some(m) => log::info!(
@@ -119,7 +145,7 @@ fn on_event(event: ProvisioningEvent) {
                 m[4],
                 m[5]
             ),
-            None => log::info!("Portal: phone left AP (mac not yet exposed by esp-radio 0.18)"),
+            None => log::info!("Portal: phone left AP"),
         },
         ProvisioningEvent::SubmissionAccepted => {
             log::info!("Portal: submission accepted");
@@ -169,24 +195,30 @@ async fn main(spawner: Spawner) {
         .expect("provisioning store open");
 
     // Check whether the device is already provisioned.
-    // If so, log the stored config and idle — a real application would reboot
-    // into its normal STA + MQTT boot path here.
+    // If so, boot normally: STA + MQTT from the stored config.
     if store.is_provisioned().unwrap_or(false) {
         match store.load() {
-            Ok(Some(cfg)) => {
+            Ok(Some(cfg)) if cfg.mqtt().is_some() => {
                 log::info!(
                     "Already provisioned — ssid len={}, device_name={}, profile={:?}",
                     cfg.wifi_ssid().len(),
                     cfg.device_name(),
                     cfg.profile(),
                 );
-                if let Some(mqtt) = cfg.mqtt() {
-                    log::info!(
-                        "MQTT target: host len={}, port={}",
-                        mqtt.host().len(),
-                        mqtt.port(),
-                    );
-                }
+                run_provisioned(
+                    spawner,
+                    cfg,
+                    peripherals.TIMG0,
+                    peripherals.SW_INTERRUPT,
+                    peripherals.WIFI,
+                )
+                .await;
+            }
+            Ok(Some(cfg)) => {
+                log::warn!(
+                    "Stored profile {:?} has no MQTT broker; re-provisioning",
+                    cfg.profile()
+                );
             }
             Ok(None) => {
                 log::warn!("Store reports provisioned but load returned None; re-provisioning");
@@ -195,20 +227,6 @@ async fn main(spawner: Spawner) {
                 log::warn!("Store load error: {:?}; re-provisioning", e);
             }
         }
-        // A real application reboots into its normal STA + MQTT boot here.
-        // That normal path brings up Wi-Fi via `WiFiManager::init_async`, which
-        // calls `esp_rtos::start` and installs the scheduler's time driver.
-        // This provisioned branch performs no Wi-Fi bring-up, so the scheduler
-        // is never started — and the esp-rtos async executor panics the moment
-        // any task `.await`s and it tries to park on the absent time driver
-        // (`Timer::after` and even `core::future::pending().await` both trip it).
-        // We therefore halt with a non-async spin loop rather than awaiting.
-        println!(
-            "Already provisioned — halting (real app would reboot into normal STA + MQTT mode)"
-        );
-        loop {
-            core::hint::spin_loop();
-        }
     }
 
     // Not yet provisioned — bring up the SoftAP captive portal.
@@ -284,3 +302,84 @@ async fn main(spawner: Spawner) {
         }
     }
 }
+
+/// Normal boot of a provisioned device: join the stored network, connect to
+/// the stored broker, and publish the uptime forever.
+async fn run_provisioned(
+    spawner: Spawner,
+    cfg: ProvisioningConfig,
+    timg0: TIMG0<'static>,
+    sw_interrupt: SW_INTERRUPT<'static>,
+    wifi: WIFI<'static>,
+) -> ! {
+    // `init_async` starts the esp-rtos scheduler, so awaiting is safe from
+    // here on.
+    let wifi_config = WiFiConfig::new(cfg.wifi_ssid(), cfg.wifi_password()).with_peripherals(
+        timg0,
+        sw_interrupt,
+        wifi,
+    );
+    let AsyncWifiHandle {
+        controller,
+        stack,
+        runner,
+    } = WiFiManager::init_async(wifi_config).expect("Wi-Fi init");
+    spawner.spawn(wifi_task(controller).unwrap());
+    spawner.spawn(net_task(runner).unwrap());
+
+    let mqtt_config = MqttConfig::from_provisioned(&cfg)
+        .expect("stored MQTT settings")
+        .with_lwt(LwtConfig::new(
+            STATUS_TOPIC,
+            b"offline",
+            QoS::AtLeastOnce,
+            true,
+        ));
+    let mqtt = MqttBuilder::new(mqtt_config)
+        .on_connect(|mqtt| {
+            log::info!("MQTT connected");
+            if let Err(e) = mqtt.publish_retained(STATUS_TOPIC, "online") {
+                log::warn!("status publish failed: {}", e);
+            }
+        })
+        .on_disconnect(|| log::warn!("MQTT disconnected"))
+        .build(spawner, stack)
+        .expect("MQTT start");
+
+    let mut uptime_secs: u64 = 0;
+    loop {
+        Timer::after(Duration::from_secs(PUBLISH_INTERVAL_SECS)).await;
+        uptime_secs += PUBLISH_INTERVAL_SECS;
+        let payload = alloc::format!("{}", uptime_secs);
+        // QoS 1 so readings taken while the broker is unreachable are
+        // delivered after the reconnect, as long as the outbox has room.
+        match mqtt.publish_with(UPTIME_TOPIC, payload.as_bytes(), QoS::AtLeastOnce, false) {
+            Ok(()) => log::info!("uptime {} s queued", uptime_secs),
+            Err(e) => log::warn!("uptime publish failed: {}", e),
+        }
+    }
+}
+
+// Keeps the station associated with the stored network.
+#[embassy_executor::task]
+async fn wifi_task(mut controller: WifiController<'static>) {
+    loop {
+        match controller.connect_async().await {
+            Ok(_) => {
+                log::info!("Wi-Fi link up");
+                let _ = controller.wait_for_disconnect_async().await;
+                log::warn!("Wi-Fi disconnected — reconnecting");
+                Timer::after(Duration::from_millis(RECONNECT_DELAY_MS)).await;
+            }
+            Err(e) => {
+                log::warn!("Wi-Fi connect failed: {:?}", e);
+                Timer::after(Duration::from_millis(CONNECT_BACKOFF_MS)).await;
+            }
+        }
+    }
+}
+
+#[embassy_executor::task]
+async fn net_task(mut runner: embassy_net::Runner<'static, Interface<'static>>) -> ! {
+    runner.run().await
+}
@@ -4,13 +4,15 @@
 //! with per-domain + chip feature gates:
 //!
 //! - [`wifi`] — async STA + SoftAP Wi-Fi via `esp-radio 0.18`
-//!   (requires `embassy`; supported on `esp32c3`, `esp32c6`)
+//!   (requires `embassy`; supported on `esp32c3`, `esp32c6`, `esp32s3`, `esp32`)
 //! - [`lora`] — synchronous LoRa stub via embedded-hal SPI + GPIO
 //!   (all chips; `esp32s3` uses hardware integration)
 //! - [`ota`] — async OTA download + verify + swap
 //!   (requires `embassy`; supported on `esp32c3`, `esp32c6`, `esp32`)
 //! - [`provisioning`] — SoftAP captive-portal credential provisioning
-//!   (requires `wifi` + `embassy`; supported on `esp32c3`, `esp32c6`)
+//!   (requires `wifi` + `embassy`; supported on `esp32c3`, `esp32c6`, `esp32s3`, `esp32`)
+//! - [`mqtt`] — async MQTT 3.1.1 client over an `embassy-net` TCP socket
+//!   (requires `embassy`; chip-independent)
 //!
 //! # Feature flags
 //!
@@ -20,11 +22,13 @@
 //! | `lora` | LoRa radio stub |
 //! | `ota` | OTA manager (implies `embassy`) |
 //! | `provisioning` | SoftAP captive portal (implies `wifi` + `embassy`) |
+//! | `ipv6` | Link-local + SLAAC on the station stack (`wifi::ipv6`) |
+//! | `mqtt` | Async MQTT client (implies `embassy`) |
 //! | `embassy` | Async executor + embassy-net stack |
 //! | `esp32c3` | Target chip ESP32-C3 |
 //! | `esp32c6` | Target chip ESP32-C6 |
-//! | `esp32` | Target chip ESP32 (lora + ota only) |
-//! | `esp32s3` | Target chip ESP32-S3 (lora only) |
+//! | `esp32` | Target chip ESP32 |
+//! | `esp32s3` | Target chip ESP32-S3 (no ota) |
 //! | `unstable` | Forward `esp-hal/unstable` |
 //! | `rt` | Forward `esp-hal/rt` |
 //! | `ws2812` | Enable `rustyfarian-esp-hal-ws2812` LED dep |
@@ -60,3 +64,6 @@ pub mod ota;
 
 #[cfg(any(feature = "provisioning", test))]
 pub mod provisioning;
+
+#[cfg(any(feature = "mqtt", test))]
+pub mod mqtt;
@@ -0,0 +1,307 @@
+//! The client task: owns the `TcpSocket` and drives [`Session`] over it.
+//!
+//! Compiled only with the `mqtt` feature, which brings in `embassy-net`.
+
+use embassy_futures::select::{select3, Either3};
+use embassy_net::dns::DnsQueryType;
+use embassy_net::tcp::TcpSocket;
+use embassy_net::{IpAddress, IpEndpoint, Ipv4Address, Stack};
+use embassy_time::{with_timeout, Duration, Instant, Timer};
+use juggler::backoff::ExponentialBackoff;
+use juggler::mqtt::codec::{ConnectReturnCode, Packet};
+
+use super::session::{Framer, Inbound, KeepAliveAction, Session};
+use super::{MqttHandle, SharedState, PACKET_MAX_LEN};
+
+/// First reconnect delay; doubles up to `reconnect_timeout_ms`.
+const RECONNECT_BASE_MS: u64 = 1_000;
+
+/// Size of each of the socket's receive and transmit buffers.
+const SOCKET_BUFFER_LEN: usize = 1536;
+
+/// Poll interval while the stack is up without a routable address.
+const CONFIG_POLL_MS: u64 = 250;
+
+/// Connects, serves, and reconnects forever.
+#[embassy_executor::task]
+pub(super) async fn mqtt_task(stack: Stack<'static>, shared: &'static SharedState) -> ! {
+    let config = &shared.config;
+    let mut rx_buffer = [0u8; SOCKET_BUFFER_LEN];
+    let mut tx_buffer = [0u8; SOCKET_BUFFER_LEN];
+    let mut framer = Framer::new();
+    let mut packet = [0u8; PACKET_MAX_LEN];
+    let mut backoff = ExponentialBackoff::new(RECONNECT_BASE_MS, config.reconnect_timeout_ms);
+
+    loop {
+        wait_routable(stack).await;
+        let mut socket = TcpSocket::new(stack, &mut rx_buffer, &mut tx_buffer);
+        let reason = run_connection(stack, &mut socket, shared, &mut framer, &mut packet).await;
+        socket.abort();
+
+        let was_connected = shared.outbox.lock(|outbox| {
+            let mut outbox = outbox.borrow_mut();
+            let was_connected = outbox.is_connected();
+            outbox.set_connected(false);
+            was_connected
+        });
+        if was_connected {
+            log::warn!("MQTT: disconnected: {}", reason);
+            backoff.reset();
+            if let Some(cb) = config.on_disconnect {
+                cb();
+            }
+        } else {
+            log::warn!(
+                "MQTT: connecting to {}:{} failed: {}",
+                config.host,
+                config.port,
+                reason
+            );
+        }
+        let delay = backoff.next().unwrap_or(config.reconnect_timeout_ms);
+        Timer::after_millis(delay).await;
+    }
+}
+
+/// Waits for an address the broker can be reached from: IPv4 or, with the
+/// `ipv6` feature, a global IPv6 address.
+///
+/// With `ipv6`, `Stack::wait_config_up` returns on the link-local address
+/// alone, before DHCPv4 has supplied an address or a DNS server.
+async fn wait_routable(stack: Stack<'static>) {
+    loop {
+        stack.wait_config_up().await;
+        if stack.config_v4().is_some() {
+            return;
+        }
+        #[cfg(feature = "ipv6")]
+        if stack
+            .config_v6()
+            .is_some_and(|config| !config.address.address().is_unicast_link_local())
+        {
+            return;
+        }
+        Timer::after_millis(CONFIG_POLL_MS).await;
+    }
+}
+
+/// One connection, from DNS lookup until it fails; returns why it ended.
+async fn run_connection(
+    stack: Stack<'static>,
+    socket: &mut TcpSocket<'_>,
+    shared: &'static SharedState,
+    framer: &mut Framer,
+    packet: &mut [u8; PACKET_MAX_LEN],
+) -> &'static str {
+    let config = &shared.config;
+    let timeout = Duration::from_millis(config.connection_timeout_ms);
+
+    let address = match with_timeout(timeout, resolve(stack, &config.host)).await {
+        Ok(Ok(address)) => address,
+        Ok(Err(e)) => return e,
+        Err(_) => return "DNS lookup timed out",
+    };
+    match with_timeout(
+        timeout,
+        socket.connect(IpEndpoint::new(address, config.port)),
+    )
+    .await
+    {
+        Ok(Ok(())) => {}
+        Ok(Err(_)) => return "TCP connect failed",
+        Err(_) => return "TCP connect timed out",
+    }
+
+    // CONNECT was encoded once by `MqttBuilder::build`, so it fits.
+    let n = match Packet::Connect(config.connect()).encode(packet) {
+        Ok(n) => n,
+        Err(_) => return "CONNECT does not encode",
+    };
+    if let Err(e) = write_all(socket, &packet[..n], timeout).await {
+        return e;
+    }
+    let mut session = Session::new(config.keep_alive_secs, now_ms());
+    framer.reset();
+    let connack = match with_timeout(timeout, read_frame(socket, framer)).await {
+        Ok(Ok(len)) => len,
+        Ok(Err(e)) => return e,
+        Err(_) => return "no CONNACK from broker",
+    };
+    match Packet::decode(framer.packet(connack)) {
+        Ok((Packet::ConnAck(ack), _)) if ack.code == ConnectReturnCode::Accepted => {}
+        Ok((Packet::ConnAck(ack), _)) => return refused(ack.code),
+        _ => return "expected CONNACK",
+    }
+    framer.consume(connack);
+
+    log::info!("MQTT: connected to {}:{}", config.host, config.port);
+    shared
+        .outbox
+        .lock(|outbox| outbox.borrow_mut().set_connected(true));
+    if let Some(cb) = config.on_connect {
+        cb(MqttHandle { shared });
+    }
+
+    loop {
+        // Send everything the outbox has ready.
+        loop {
+            let n = shared
+                .outbox
+                .lock(|outbox| session.poll_outgoing(&mut outbox.borrow_mut(), packet));
+            if n == 0 {
+                break;
+            }
+            if let Err(e) = write_all(socket, &packet[..n], timeout).await {
+                return e;
+            }
+            session.sent(now_ms());
+        }
+
+        let deadline = session
+            .deadline()
+            .map_or(Instant::MAX, Instant::from_millis);
+        match select3(
+            socket.read(framer.space()),
+            shared.wake.wait(),
+            Timer::at(deadline),
+        )
+        .await
+        {
+            Either3::First(Ok(0)) => return "connection closed by broker",
+            Either3::First(Err(_)) => return "connection reset",
+            Either3::First(Ok(n)) => {
+                framer.commit(n);
+                while let Some(len) = match framer.frame() {
+                    Ok(len) => len,
+                    Err(_) => return "malformed packet from broker",
+                } {
+                    let incoming = match Packet::decode(framer.packet(len)) {
+                        Ok((incoming, _)) => incoming,
+                        Err(_) => return "malformed packet from broker",
+                    };
+                    let inbound = match shared
+                        .outbox
+                        .lock(|outbox| session.handle(incoming, &mut outbox.borrow_mut()))
+                    {
+                        Ok(inbound) => inbound,
+                        Err(e) => return e,
+                    };
+                    let (reply, message) = match inbound {
+                        Inbound::Nothing => (None, None),
+                        Inbound::Reply(reply) => (Some(reply), None),
+                        Inbound::Message {
+                            topic,
+                            payload,
+                            ack,
+                        } => (ack, Some((topic, payload))),
+                    };
+                    if let Some(reply) = reply {
+                        let n = reply.encode(packet).unwrap_or(0);
+                        if let Err(e) = write_all(socket, &packet[..n], timeout).await {
+                            return e;
+                        }
+                        session.sent(now_ms());
+                    }
+                    if let (Some((topic, payload)), Some(cb)) = (message, config.on_message) {
+                        cb(topic, payload);
+                    }
+                    framer.consume(len);
+                }
+            }
+            Either3::Second(()) => {}
+            Either3::Third(()) => match session.poll_keep_alive(now_ms()) {
+                KeepAliveAction::Idle => {}
+                KeepAliveAction::Ping => {
+                    let n = Packet::PingReq.encode(packet).unwrap_or(0);
+                    if let Err(e) = write_all(socket, &packet[..n], timeout).await {
+                        return e;
+                    }
+                    session.sent(now_ms());
+                }
+                KeepAliveAction::Expired => return "no PINGRESP within the keep-alive interval",
+            },
+        }
+    }
+}
+
+/// Resolves the broker host: an address literal, or a DNS lookup — `A`
+/// while the stack has IPv4, otherwise (IPv6-only, `ipv6` feature) `AAAA`.
+async fn resolve(stack: Stack<'static>, host: &str) -> Result<IpAddress, &'static str> {
+    if let Ok(ip) = host.parse::<Ipv4Address>() {
+        return Ok(IpAddress::Ipv4(ip));
+    }
+    #[cfg(feature = "ipv6")]
+    if let Ok(ip) = host.parse::<embassy_net::Ipv6Address>() {
+        return Ok(IpAddress::Ipv6(ip));
+    }
+    #[cfg(feature = "ipv6")]
+    let query = if stack.config_v4().is_some() {
+        DnsQueryType::A
+    } else {
+        DnsQueryType::Aaaa
+    };
+    #[cfg(not(feature = "ipv6"))]
+    let query = DnsQueryType::A;
+    let addresses = stack
+        .dns_query(host, query)
+        .await
+        .map_err(|_| "DNS lookup failed")?;
+    addresses
+        .first()
+        .copied()
+        .ok_or("DNS lookup returned no address")
+}
+
+/// Reads until `framer` holds a complete packet and returns its length.
+async fn read_frame(
+    socket: &mut TcpSocket<'_>,
+    framer: &mut Framer,
+) -> Result<usize, &'static str> {
+    loop {
+        match framer.frame() {
+            Ok(Some(len)) => return Ok(len),
+            Ok(None) => {}
+            Err(_) => return Err("malformed packet from broker"),
+        }
+        match socket.read(framer.space()).await {
+            Ok(0) => return Err("connection closed by broker"),
+            Ok(n) => framer.commit(n),
+            Err(_) => return Err("connection reset"),
+        }
+    }
+}
+
+/// Writes all of `bytes`, handling partial writes (`TcpSocket::write` does
+/// not implement `embedded_io_async::Write`).
+async fn write_all(
+    socket: &mut TcpSocket<'_>,
+    mut bytes: &[u8],
+    timeout: Duration,
+) -> Result<(), &'static str> {
+    while !bytes.is_empty() {
+        match with_timeout(timeout, socket.write(bytes)).await {
+            Ok(Ok(0)) | Ok(Err(_)) => return Err("connection reset"),
+            Ok(Ok(n)) => bytes = &bytes[n..],
+            Err(_) => return Err("write timed out"),
+        }
+    }
+    Ok(())
+}
+
+/// Log reason for a refused CONNECT.
+fn refused(code: ConnectReturnCode) -> &'static str {
+    match code {
+        ConnectReturnCode::UnacceptableProtocolVersion => "broker refused MQTT 3.1.1",
+        ConnectReturnCode::IdentifierRejected => "broker rejected the client ID",
+        ConnectReturnCode::ServerUnavailable => "broker unavailable",
+        ConnectReturnCode::BadUsernameOrPassword => "broker rejected the credentials",
+        ConnectReturnCode::NotAuthorized | ConnectReturnCode::Accepted => {
+            "broker refused the connection"
+        }
+    }
+}
+
+/// Milliseconds since boot.
+fn now_ms() -> u64 {
+    Instant::now().as_millis()
+}
@@ -0,0 +1,750 @@
+//! Async MQTT 3.1.1 client for bare-metal targets.
+//!
+//! The bare-metal counterpart of the ESP-IDF tier's `MqttBuilder` /
+//! `MqttHandle`: [`MqttBuilder::build`] spawns an embassy task that owns an
+//! `embassy_net::tcp::TcpSocket`, connects to the broker, and keeps the
+//! connection up — keep-alive pings, reconnects with exponential backoff,
+//! and every [subscription](MqttBuilder::subscribe) restored on each
+//! reconnect.  Packets are encoded and decoded by
+//! [`juggler::mqtt::codec`].
+//!
+//! # Quick start
+//!
+//! ```ignore
+//! use rustyfarian_esp_hal_network::mqtt::{LwtConfig, MqttBuilder, MqttConfig, QoS};
+//!
+//! let config = MqttConfig::new("192.168.1.100", 1883, "my-device")
+//!     .with_lwt(LwtConfig::new("devices/my-device/status", b"offline", QoS::AtLeastOnce, true));
+//!
+//! let mqtt = MqttBuilder::new(config)
+//!     .subscribe("commands/#", QoS::AtLeastOnce)
+//!     .on_connect(|mqtt| {
+//!         let _ = mqtt.publish_retained("devices/my-device/status", "online");
+//!     })
+//!     .on_message(|topic, payload| log::info!("{}: {} bytes", topic, payload.len()))
+//!     .build(spawner, wifi.stack)?;
+//!
+//! mqtt.publish("sensors/temp", "22.5")?;
+//! ```
+//!
+//! A provisioned device builds its configuration from the stored record
+//! with [`MqttConfig::from_provisioned`].
+//!
+//! # Delivery
+//!
+//! QoS 0 and QoS 1 are supported.  A QoS 1 publish waits in a fixed
+//! [`OUTBOX_LEN`]-byte outbox until the broker acknowledges it, so publishes
+//! made while the broker is unreachable go out after the next connect; one
+//! is in flight at a time, which keeps them in order.  A QoS 0 publish needs
+//! a connection ([`MqttError::NotConnected`] otherwise) and is lost with it.
+//! Publishing never blocks: a full outbox returns [`MqttError::OutboxFull`].
+//!
+//! # Socket budget
+//!
+//! The client holds [`SOCKETS`] TCP socket on the stack it is given, and the
+//! `mqtt` feature makes `embassy-net` reserve one more for its DNS resolver;
+//! the station stack's default `STA_SOCKET_COUNT` includes both.
+//!
+//! All public APIs are experimental.
+
+// Without the `mqtt` feature the client task and `MqttBuilder::build` are
+// compiled away; allow dead-code on what remains for host test builds.
+#![cfg_attr(not(feature = "mqtt"), allow(dead_code))]
+
+mod session;
+
+#[cfg(feature = "mqtt")]
+mod client;
+
+use core::cell::RefCell;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::CriticalSectionMutex;
+use embassy_sync::signal::Signal;
+use heapless::{String, Vec};
+use juggler::mqtt::codec::{Connect, Packet, Will};
+use juggler::mqtt::{
+    validate_broker_host, validate_broker_port, validate_client_id, validate_publish_topic,
+    CLIENT_ID_MAX_LEN,
+};
+
+use session::{check_qos, Outbox, Subscription};
+
+pub use juggler::mqtt::{QoS, MQTT_PORT};
+
+/// `embassy-net` sockets the client task keeps open: one TCP socket.
+pub const SOCKETS: usize = 1;
+
+/// Longest broker host name or address the client stores.
+pub const HOST_MAX_LEN: usize = 128;
+
+/// Longest user name or password the client stores.
+pub const CREDENTIAL_MAX_LEN: usize = 64;
+
+/// Longest subscription filter or will topic the client stores.
+pub const TOPIC_MAX_LEN: usize = 128;
+
+/// Longest will message the client stores.
+pub const WILL_PAYLOAD_MAX_LEN: usize = 128;
+
+/// Most subscriptions, from the builder and the handle together.
+pub const MAX_SUBSCRIPTIONS: usize = 8;
+
+/// Largest packet sent or received, in bytes.  Bounds a publish's topic plus
+/// payload (less a few bytes of framing); larger incoming packets are skipped.
+pub const PACKET_MAX_LEN: usize = 1024;
+
+/// Bytes of QoS 1 publishes the outbox holds until they are acknowledged.
+/// Each takes 6 bytes of framing plus its topic and payload.
+pub const OUTBOX_LEN: usize = 2048;
+
+/// Bytes of encoded QoS 0 publishes staged for the client task.  At most
+/// [`PACKET_MAX_LEN`], since the client task sends them in one write.
+pub const QOS0_BUFFER_LEN: usize = 1024;
+
+/// Keep-alive interval used unless [`MqttConfig::with_keep_alive`] changes it.
+pub const DEFAULT_KEEP_ALIVE_SECS: u16 = 30;
+
+/// Connect timeout used unless [`MqttConfig::with_timeout`] changes it.
+pub const DEFAULT_TIMEOUT_MS: u64 = 5_000;
+
+/// Reconnect backoff ceiling used unless
+/// [`MqttConfig::with_reconnect_timeout`] changes it.
+pub const DEFAULT_RECONNECT_TIMEOUT_MS: u64 = 10_000;
+
+/// Client-ID fallback of [`MqttConfig::from_provisioned`], as on the
+/// ESP-IDF tier.
+#[cfg(any(feature = "provisioning", test))]
+const FALLBACK_CLIENT_ID: &str = "rustyfarian";
+
+/// Errors returned by the MQTT client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MqttError {
+    /// A configuration value, topic, or QoS failed validation; carries the
+    /// validator's message.
+    Invalid(&'static str),
+    /// A string or publish does not fit its fixed-size buffer (see the
+    /// `*_MAX_LEN` constants).
+    TooLarge,
+    /// A QoS 0 publish was made while the broker is not connected.
+    NotConnected,
+    /// The outbox has no room for the publish; retry once it drains.
+    OutboxFull,
+    /// More than [`MAX_SUBSCRIPTIONS`] subscriptions were registered.
+    TooManySubscriptions,
+    /// [`MqttBuilder::build`] was already called this boot.
+    AlreadyStarted,
+}
+
+impl core::fmt::Display for MqttError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MqttError::Invalid(e) => write!(f, "invalid MQTT setting: {}", e),
+            MqttError::TooLarge => write!(f, "MQTT value exceeds its buffer"),
+            MqttError::NotConnected => write!(f, "MQTT broker not connected"),
+            MqttError::OutboxFull => write!(f, "MQTT outbox full"),
+            MqttError::TooManySubscriptions => {
+                write!(f, "more than {} MQTT subscriptions", MAX_SUBSCRIPTIONS)
+            }
+            MqttError::AlreadyStarted => {
+                write!(
+                    f,
+                    "MQTT client already started (call build at most once per boot)"
+                )
+            }
+        }
+    }
+}
+
+/// Last Will and Testament configuration.
+///
+/// The broker publishes this message on behalf of the client when the
+/// connection drops without a clean `DISCONNECT`.
+#[derive(Debug, Clone)]
+pub struct LwtConfig<'a> {
+    topic: &'a str,
+    payload: &'a [u8],
+    qos: QoS,
+    retain: bool,
+}
+
+impl<'a> LwtConfig<'a> {
+    /// Creates a new LWT configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - Topic the broker publishes to on unexpected disconnect
+    /// * `payload` - Message payload
+    /// * `qos` - Quality of Service level
+    /// * `retain` - Whether the broker retains the LWT message
+    pub fn new(topic: &'a str, payload: &'a [u8], qos: QoS, retain: bool) -> Self {
+        Self {
+            topic,
+            payload,
+            qos,
+            retain,
+        }
+    }
+}
+
+/// MQTT broker connection configuration.
+///
+/// Credentials are redacted in the `Debug` output.  [`MqttBuilder::build`]
+/// validates every field and copies it, so the borrowed strings only need
+/// to outlive the call.
+#[derive(Clone)]
+pub struct MqttConfig<'a> {
+    /// Broker IPv4 address or DNS name (resolved through the stack's DHCP- or
+    /// statically-configured server; `.local` names are not resolved).  IPv6
+    /// literals are accepted with the `ipv6` feature, which also looks up
+    /// `AAAA` records once the stack has a global IPv6 address but no IPv4.
+    pub host: &'a str,
+    /// Broker port (typically [`MQTT_PORT`]).
+    pub port: u16,
+    /// Unique client identifier.
+    pub client_id: &'a str,
+    /// Keep-alive interval in seconds; `0` disables keep-alive pings.
+    pub keep_alive_secs: u16,
+    /// Timeout of the DNS lookup, the TCP connect, and the CONNACK wait, in
+    /// milliseconds.
+    pub connection_timeout_ms: u64,
+    /// Ceiling of the reconnect backoff in milliseconds: attempts start 1 s
+    /// apart and double up to this interval.
+    pub reconnect_timeout_ms: u64,
+    lwt: Option<LwtConfig<'a>>,
+    username: Option<&'a str>,
+    password: Option<&'a str>,
+}
+
+impl core::fmt::Debug for MqttConfig<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MqttConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("client_id", &self.client_id)
+            .field("keep_alive_secs", &self.keep_alive_secs)
+            .field("connection_timeout_ms", &self.connection_timeout_ms)
+            .field("reconnect_timeout_ms", &self.reconnect_timeout_ms)
+            .field("lwt", &self.lwt)
+            .field("username", &self.username.map(|_| "<redacted>"))
+            .field("password", &self.password.map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+impl<'a> MqttConfig<'a> {
+    /// Creates a new configuration with the required fields.
+    pub fn new(host: &'a str, port: u16, client_id: &'a str) -> Self {
+        Self {
+            host,
+            port,
+            client_id,
+            keep_alive_secs: DEFAULT_KEEP_ALIVE_SECS,
+            connection_timeout_ms: DEFAULT_TIMEOUT_MS,
+            reconnect_timeout_ms: DEFAULT_RECONNECT_TIMEOUT_MS,
+            lwt: None,
+            username: None,
+            password: None,
+        }
+    }
+
+    /// Creates a configuration from a provisioned `WifiMqttDevice` record:
+    /// its broker host, port, and credentials.
+    ///
+    /// The client ID is resolved by [`juggler::mqtt::resolve_client_id`]
+    /// from the operator-supplied ID, the device name, and the fallback
+    /// `"rustyfarian"` — the policy of the ESP-IDF tier's `WifiMqttBoot`.
+    ///
+    /// # Errors
+    ///
+    /// [`MqttError::Invalid`] if the record has no MQTT group or no usable
+    /// client ID.
+    #[cfg(any(feature = "provisioning", test))]
+    pub fn from_provisioned(
+        provisioned: &'a juggler::provisioning::ProvisioningConfig,
+    ) -> Result<Self, MqttError> {
+        let mqtt = provisioned
+            .mqtt()
+            .ok_or(MqttError::Invalid("provisioned config has no MQTT broker"))?;
+        let client_id = juggler::mqtt::resolve_client_id(
+            mqtt.client_id(),
+            provisioned.device_name(),
+            FALLBACK_CLIENT_ID,
+        )
+        .map_err(MqttError::Invalid)?;
+        let config = Self::new(mqtt.host(), mqtt.port(), client_id);
+        Ok(match (mqtt.username(), mqtt.password()) {
+            (Some(username), Some(password)) => config.with_auth(username, password),
+            (Some(username), None) => config.with_username_only(username),
+            (None, _) => config,
+        })
+    }
+
+    /// Sets the keep-alive interval.
+    pub fn with_keep_alive(mut self, secs: u16) -> Self {
+        self.keep_alive_secs = secs;
+        self
+    }
+
+    /// Sets the connection timeout.
+    pub fn with_timeout(mut self, ms: u64) -> Self {
+        self.connection_timeout_ms = ms;
+        self
+    }
+
+    /// Sets the ceiling of the reconnect backoff.
+    ///
+    /// Battery-powered devices may want 30 000–60 000 ms to reduce radio
+    /// time during prolonged broker outages.
+    pub fn with_reconnect_timeout(mut self, ms: u64) -> Self {
+        self.reconnect_timeout_ms = ms;
+        self
+    }
+
+    /// Configures a Last Will and Testament message.
+    pub fn with_lwt(mut self, lwt: LwtConfig<'a>) -> Self {
+        self.lwt = Some(lwt);
+        self
+    }
+
+    /// Sets MQTT broker authentication credentials.
+    pub fn with_auth(mut self, username: &'a str, password: &'a str) -> Self {
+        self.username = Some(username);
+        self.password = Some(password);
+        self
+    }
+
+    /// Sets a username with no password, for brokers that authorise by
+    /// username alone.  The CONNECT packet omits the password field.
+    pub fn with_username_only(mut self, username: &'a str) -> Self {
+        self.username = Some(username);
+        self.password = None;
+        self
+    }
+}
+
+/// Owned copy of a [`LwtConfig`].
+#[derive(Debug)]
+struct OwnedWill {
+    topic: String<TOPIC_MAX_LEN>,
+    payload: Vec<u8, WILL_PAYLOAD_MAX_LEN>,
+    qos: QoS,
+    retain: bool,
+}
+
+/// Validated, owned configuration of the client task.
+pub(crate) struct ClientConfig {
+    pub(crate) host: String<HOST_MAX_LEN>,
+    pub(crate) port: u16,
+    client_id: String<CLIENT_ID_MAX_LEN>,
+    pub(crate) keep_alive_secs: u16,
+    pub(crate) connection_timeout_ms: u64,
+    pub(crate) reconnect_timeout_ms: u64,
+    will: Option<OwnedWill>,
+    username: Option<String<CREDENTIAL_MAX_LEN>>,
+    password: Option<String<CREDENTIAL_MAX_LEN>>,
+    pub(crate) on_connect: Option<fn(MqttHandle)>,
+    pub(crate) on_disconnect: Option<fn()>,
+    pub(crate) on_message: Option<fn(&str, &[u8])>,
+}
+
+impl ClientConfig {
+    /// Validates and copies `config`.
+    fn new(config: &MqttConfig<'_>) -> Result<Self, MqttError> {
+        validate_broker_host(config.host).map_err(MqttError::Invalid)?;
+        validate_broker_port(config.port).map_err(MqttError::Invalid)?;
+        validate_client_id(config.client_id).map_err(MqttError::Invalid)?;
+        let will = match &config.lwt {
+            Some(lwt) => {
+                validate_publish_topic(lwt.topic).map_err(MqttError::Invalid)?;
+                check_qos(lwt.qos)?;
+                Some(OwnedWill {
+                    topic: copy_str(lwt.topic)?,
+                    payload: Vec::from_slice(lwt.payload).map_err(|_| MqttError::TooLarge)?,
+                    qos: lwt.qos,
+                    retain: lwt.retain,
+                })
+            }
+            None => None,
+        };
+        let client = Self {
+            host: copy_str(config.host)?,
+            port: config.port,
+            client_id: copy_str(config.client_id)?,
+            keep_alive_secs: config.keep_alive_secs,
+            connection_timeout_ms: config.connection_timeout_ms,
+            reconnect_timeout_ms: config.reconnect_timeout_ms,
+            will,
+            username: config.username.map(copy_str).transpose()?,
+            password: config.password.map(copy_str).transpose()?,
+            on_connect: None,
+            on_disconnect: None,
+            on_message: None,
+        };
+        // Runs the codec's own checks, such as a password without a user name.
+        Packet::Connect(client.connect())
+            .encode(&mut [0; PACKET_MAX_LEN])
+            .map_err(|e| match e {
+                juggler::mqtt::codec::CodecError::Invalid(e) => MqttError::Invalid(e),
+                _ => MqttError::TooLarge,
+            })?;
+        Ok(client)
+    }
+
+    /// The CONNECT packet sent on every connect.
+    pub(crate) fn connect(&self) -> Connect<'_> {
+        Connect {
+            will: self.will.as_ref().map(|will| Will {
+                topic: &will.topic,
+                payload: &will.payload,
+                qos: will.qos,
+                retain: will.retain,
+            }),
+            username: self.username.as_deref(),
+            password: self.password.as_deref().map(str::as_bytes),
+            ..Connect::new(&self.client_id, self.keep_alive_secs)
+        }
+    }
+}
+
+/// Copies `s` into a fixed-capacity string.
+fn copy_str<const N: usize>(s: &str) -> Result<String<N>, MqttError> {
+    String::try_from(s).map_err(|_| MqttError::TooLarge)
+}
+
+/// State shared by the [`MqttHandle`]s and the client task, allocated once
+/// per boot in a `StaticCell` by [`MqttBuilder::build`].
+pub(crate) struct SharedState {
+    pub(crate) config: ClientConfig,
+    pub(crate) outbox: CriticalSectionMutex<RefCell<Outbox>>,
+    /// Wakes the client task when the outbox has something to send.
+    pub(crate) wake: Signal<CriticalSectionRawMutex, ()>,
+}
+
+impl SharedState {
+    fn new(config: ClientConfig, subscriptions: Vec<Subscription, MAX_SUBSCRIPTIONS>) -> Self {
+        Self {
+            config,
+            outbox: CriticalSectionMutex::new(RefCell::new(Outbox::new(subscriptions))),
+            wake: Signal::new(),
+        }
+    }
+}
+
+/// Builder for the bare-metal MQTT client.
+///
+/// Callbacks are bare `fn` pointers (not closures) because `no_std` forbids
+/// heap-allocated closures.  They run inside the client task and must
+/// return quickly without blocking.
+pub struct MqttBuilder<'a> {
+    config: MqttConfig<'a>,
+    subscriptions: Vec<Subscription, MAX_SUBSCRIPTIONS>,
+    /// First error from a [`subscribe`](Self::subscribe) call, reported by
+    /// [`build`](Self::build).
+    error: Option<MqttError>,
+    on_connect: Option<fn(MqttHandle)>,
+    on_disconnect: Option<fn()>,
+    on_message: Option<fn(&str, &[u8])>,
+}
+
+impl<'a> MqttBuilder<'a> {
+    /// Creates a new builder from the given configuration.
+    pub fn new(config: MqttConfig<'a>) -> Self {
+        Self {
+            config,
+            subscriptions: Vec::new(),
+            error: None,
+            on_connect: None,
+            on_disconnect: None,
+            on_message: None,
+        }
+    }
+
+    /// Registers a topic filter to subscribe to on every (re)connect.
+    ///
+    /// QoS 2 is not supported.  An invalid filter, or more than
+    /// [`MAX_SUBSCRIPTIONS`], fails [`build`](Self::build).  A filter the
+    /// broker refuses is logged and requested again on the next reconnect.
+    pub fn subscribe(mut self, topic: &str, qos: QoS) -> Self {
+        let added = Subscription::new(topic, qos).and_then(|sub| {
+            self.subscriptions
+                .push(sub)
+                .map_err(|_| MqttError::TooManySubscriptions)
+        });
+        if let Err(e) = added {
+            self.error.get_or_insert(e);
+        }
+        self
+    }
+
+    /// Registers a callback invoked on every (re)connect, once the broker
+    /// has accepted the connection and before the subscriptions are sent.
+    ///
+    /// The handle it receives can publish, e.g. a retained "online" status.
+    pub fn on_connect(mut self, callback: fn(MqttHandle)) -> Self {
+        self.on_connect = Some(callback);
+        self
+    }
+
+    /// Registers a callback invoked when an established connection drops.
+    pub fn on_disconnect(mut self, callback: fn()) -> Self {
+        self.on_disconnect = Some(callback);
+        self
+    }
+
+    /// Registers a callback invoked with `(topic, payload)` for each
+    /// incoming message.
+    pub fn on_message(mut self, callback: fn(&str, &[u8])) -> Self {
+        self.on_message = Some(callback);
+        self
+    }
+
+    /// Validates the configuration and copies it into `'static` storage.
+    fn into_shared(self) -> Result<SharedState, MqttError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        let mut config = ClientConfig::new(&self.config)?;
+        config.on_connect = self.on_connect;
+        config.on_disconnect = self.on_disconnect;
+        config.on_message = self.on_message;
+        Ok(SharedState::new(config, self.subscriptions))
+    }
+
+    /// Spawns the client task on `stack` and returns an [`MqttHandle`].
+    ///
+    /// Returns immediately — the task waits for the stack's IP configuration
+    /// and connects in the background.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration or a subscription is invalid,
+    /// or if `build` was already called this boot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the executor's task pool is exhausted, like the
+    /// provisioning substrate's spawns.
+    #[cfg(feature = "mqtt")]
+    pub fn build(
+        self,
+        spawner: embassy_executor::Spawner,
+        stack: embassy_net::Stack<'static>,
+    ) -> Result<MqttHandle, MqttError> {
+        use static_cell::StaticCell;
+
+        let state = self.into_shared()?;
+        static SHARED: StaticCell<SharedState> = StaticCell::new();
+        let shared: &'static SharedState =
+            SHARED.try_init(state).ok_or(MqttError::AlreadyStarted)?;
+        spawner.spawn(client::mqtt_task(stack, shared).unwrap());
+        Ok(MqttHandle { shared })
+    }
+}
+
+/// Handle to the running client; cheap to copy into any task.
+///
+/// Publishing never blocks or awaits: the publish is staged in the outbox
+/// and the client task is woken to send it.
+#[derive(Clone, Copy)]
+pub struct MqttHandle {
+    shared: &'static SharedState,
+}
+
+impl MqttHandle {
+    /// Publishes a message with QoS 1 and no retain flag.
+    pub fn publish(&self, topic: &str, payload: &str) -> Result<(), MqttError> {
+        self.publish_with(topic, payload.as_bytes(), QoS::AtLeastOnce, false)
+    }
+
+    /// Publishes a retained message with QoS 1.
+    pub fn publish_retained(&self, topic: &str, payload: &str) -> Result<(), MqttError> {
+        self.publish_with(topic, payload.as_bytes(), QoS::AtLeastOnce, true)
+    }
+
+    /// Publishes a message with explicit QoS and retain control.
+    ///
+    /// # Errors
+    ///
+    /// [`MqttError::NotConnected`] for QoS 0 while disconnected,
+    /// [`MqttError::OutboxFull`] when there is no room, and
+    /// [`MqttError::Invalid`] / [`MqttError::TooLarge`] for a topic,
+    /// payload, or QoS the client cannot send.
+    pub fn publish_with(
+        &self,
+        topic: &str,
+        payload: &[u8],
+        qos: QoS,
+        retain: bool,
+    ) -> Result<(), MqttError> {
+        self.shared
+            .outbox
+            .lock(|outbox| outbox.borrow_mut().publish(topic, payload, qos, retain))?;
+        self.shared.wake.signal(());
+        Ok(())
+    }
+
+    /// Subscribes to a topic filter now, if connected, and on every
+    /// reconnect.
+    pub fn subscribe(&self, topic: &str, qos: QoS) -> Result<(), MqttError> {
+        self.shared
+            .outbox
+            .lock(|outbox| outbox.borrow_mut().subscribe(topic, qos))?;
+        self.shared.wake.signal(());
+        Ok(())
+    }
+
+    /// Returns `true` once the broker has accepted the connection.
+    pub fn is_connected(&self) -> bool {
+        self.shared
+            .outbox
+            .lock(|outbox| outbox.borrow().is_connected())
+    }
+
+    /// Number of QoS 1 publishes the broker has not acknowledged yet.
+    pub fn queued_publishes(&self) -> usize {
+        self.shared.outbox.lock(|outbox| outbox.borrow().queued())
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builder() -> MqttBuilder<'static> {
+        MqttBuilder::new(MqttConfig::new("10.0.0.2", MQTT_PORT, "dev-1"))
+    }
+
+    #[test]
+    fn connect_packet_carries_will_and_credentials() {
+        let config = MqttConfig::new("broker.lan", 1883, "dev-1")
+            .with_keep_alive(15)
+            .with_auth("user", "secret")
+            .with_lwt(LwtConfig::new(
+                "dev/status",
+                b"offline",
+                QoS::AtLeastOnce,
+                true,
+            ));
+        let client = ClientConfig::new(&config).unwrap();
+        let connect = client.connect();
+        assert_eq!(connect.client_id, "dev-1");
+        assert_eq!(connect.keep_alive_secs, 15);
+        assert!(connect.clean_session);
+        assert_eq!(connect.username, Some("user"));
+        assert_eq!(connect.password, Some(&b"secret"[..]));
+        assert_eq!(
+            connect.will,
+            Some(Will {
+                topic: "dev/status",
+                payload: b"offline",
+                qos: QoS::AtLeastOnce,
+                retain: true,
+            })
+        );
+
+        let username_only = MqttConfig::new("broker.lan", 1883, "dev-1").with_username_only("u");
+        let connect = ClientConfig::new(&username_only).unwrap();
+        assert_eq!(connect.connect().password, None);
+    }
+
+    #[test]
+    fn invalid_configuration_is_rejected() {
+        let bad = [
+            MqttConfig::new("", 1883, "dev"),
+            MqttConfig::new("broker", 0, "dev"),
+            MqttConfig::new("broker", 1883, "a-client-id-that-is-far-too-long"),
+            MqttConfig::new("broker", 1883, "dev").with_lwt(LwtConfig::new(
+                "dev/+",
+                b"",
+                QoS::AtMostOnce,
+                false,
+            )),
+            MqttConfig::new("broker", 1883, "dev").with_lwt(LwtConfig::new(
+                "dev",
+                b"",
+                QoS::ExactlyOnce,
+                false,
+            )),
+        ];
+        for config in &bad {
+            assert!(
+                matches!(ClientConfig::new(config), Err(MqttError::Invalid(_))),
+                "{:?}",
+                config
+            );
+        }
+        let long_password = [b'p'; CREDENTIAL_MAX_LEN + 1];
+        let config = MqttConfig::new("broker", 1883, "dev")
+            .with_auth("u", core::str::from_utf8(&long_password).unwrap());
+        assert!(matches!(
+            ClientConfig::new(&config),
+            Err(MqttError::TooLarge)
+        ));
+    }
+
+    #[test]
+    fn builder_reports_the_first_subscription_error() {
+        assert!(builder()
+            .subscribe("a/#", QoS::AtLeastOnce)
+            .into_shared()
+            .is_ok());
+        assert_eq!(
+            builder()
+                .subscribe("a/#", QoS::ExactlyOnce)
+                .subscribe("a/#/b", QoS::AtMostOnce)
+                .into_shared()
+                .err(),
+            Some(MqttError::Invalid("QoS 2 is not supported"))
+        );
+        let mut full = builder();
+        for _ in 0..=MAX_SUBSCRIPTIONS {
+            full = full.subscribe("a/#", QoS::AtMostOnce);
+        }
+        assert_eq!(
+            full.into_shared().err(),
+            Some(MqttError::TooManySubscriptions)
+        );
+    }
+
+    #[test]
+    fn debug_redacts_credentials() {
+        extern crate alloc;
+        let config = MqttConfig::new("broker", 1883, "dev").with_auth("alice", "hunter2");
+        let debug = alloc::format!("{:?}", config);
+        assert!(!debug.contains("alice"));
+        assert!(!debug.contains("hunter2"));
+        assert!(debug.contains("<redacted>"));
+    }
+
+    #[test]
+    fn from_provisioned_copies_broker_and_credentials() {
+        use juggler::provisioning::profile::MqttFields;
+        use juggler::provisioning::{ProvisioningConfig, SchemaProfile};
+
+        let mqtt = MqttFields::from_storage_parts(
+            "mqtt.lan".try_into().unwrap(),
+            8883,
+            Some("user".try_into().unwrap()),
+            Some("pass".try_into().unwrap()),
+            None,
+        );
+        let provisioned = ProvisioningConfig::from_storage_parts(
+            "ssid".try_into().unwrap(),
+            "password".try_into().unwrap(),
+            heapless::String::new(),
+            "kitchen-sensor".try_into().unwrap(),
+            None,
+            Some(mqtt),
+        );
+        assert_eq!(provisioned.profile(), SchemaProfile::WifiMqttDevice);
+        let config = MqttConfig::from_provisioned(&provisioned).unwrap();
+        assert_eq!((config.host, config.port), ("mqtt.lan", 8883));
+        assert_eq!(config.client_id, "kitchen-sensor");
+        let connect = ClientConfig::new(&config).unwrap();
+        assert_eq!(connect.connect().username, Some("user"));
+        assert_eq!(connect.connect().password, Some(&b"pass"[..]));
+    }
+}
@@ -0,0 +1,746 @@
+//! Pure session state of the bare-metal MQTT client.
+//!
+//! Everything here is synchronous and free of `embassy-net`, so the protocol
+//! decisions — what to send next, how to answer a broker packet, when to
+//! ping — are host-tested; `client.rs` only moves bytes between these types
+//! and the `TcpSocket`.
+
+use heapless::{String, Vec};
+use juggler::mqtt::codec::{packet_len, CodecError, Packet, Publish, SubscribeFilters};
+use juggler::mqtt::{validate_publish_topic, validate_subscribe_filter, OverflowPolicy};
+use juggler::mqtt::{PublishQueue, QoS, QueueError};
+
+use super::{
+    MqttError, MAX_SUBSCRIPTIONS, OUTBOX_LEN, PACKET_MAX_LEN, QOS0_BUFFER_LEN, TOPIC_MAX_LEN,
+};
+
+/// Worst-case PUBLISH framing around topic and payload: a 5-byte fixed
+/// header, the 2-byte topic length, and the 2-byte packet identifier.
+pub(crate) const PUBLISH_OVERHEAD: usize = 9;
+
+// `poll_outgoing` hands over the whole QoS 0 buffer in one packet-sized
+// write and then clears it, so it must never hold more than fits.
+const _: () = assert!(QOS0_BUFFER_LEN <= PACKET_MAX_LEN);
+
+/// A topic filter restored on every connect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Subscription {
+    filter: String<TOPIC_MAX_LEN>,
+    qos: QoS,
+}
+
+impl Subscription {
+    /// Validates and copies `filter`.
+    pub(crate) fn new(filter: &str, qos: QoS) -> Result<Self, MqttError> {
+        validate_subscribe_filter(filter).map_err(MqttError::Invalid)?;
+        check_qos(qos)?;
+        let filter = String::try_from(filter).map_err(|_| MqttError::TooLarge)?;
+        Ok(Self { filter, qos })
+    }
+}
+
+/// Rejects QoS 2, which the client does not implement.
+pub(crate) fn check_qos(qos: QoS) -> Result<(), MqttError> {
+    match qos {
+        QoS::ExactlyOnce => Err(MqttError::Invalid("QoS 2 is not supported")),
+        _ => Ok(()),
+    }
+}
+
+/// Everything the handles hand to the client task, shared behind a
+/// critical-section mutex.
+///
+/// QoS 1 publishes wait in a [`PublishQueue`] until the broker acknowledges
+/// them, across reconnects; QoS 0 publishes are encoded straight into a
+/// staging buffer and are dropped with the connection.
+pub(crate) struct Outbox {
+    queue: PublishQueue<[u8; OUTBOX_LEN]>,
+    qos0: Vec<u8, QOS0_BUFFER_LEN>,
+    subscriptions: Vec<Subscription, MAX_SUBSCRIPTIONS>,
+    /// How many of `subscriptions` the broker acknowledged on this connection.
+    subscribed: usize,
+    connected: bool,
+    next_id: u16,
+    /// Packet identifier the front publish was last sent with; reused, with
+    /// DUP set, when it is sent again after a reconnect.
+    front_id: Option<u16>,
+}
+
+impl Outbox {
+    /// An empty, disconnected outbox restoring `subscriptions`.
+    pub(crate) fn new(subscriptions: Vec<Subscription, MAX_SUBSCRIPTIONS>) -> Self {
+        Self {
+            queue: PublishQueue::new([0; OUTBOX_LEN], OverflowPolicy::Reject),
+            qos0: Vec::new(),
+            subscriptions,
+            subscribed: 0,
+            connected: false,
+            next_id: 0,
+            front_id: None,
+        }
+    }
+
+    /// Stages a publish for the client task.
+    pub(crate) fn publish(
+        &mut self,
+        topic: &str,
+        payload: &[u8],
+        qos: QoS,
+        retain: bool,
+    ) -> Result<(), MqttError> {
+        validate_publish_topic(topic).map_err(MqttError::Invalid)?;
+        check_qos(qos)?;
+        if topic.len() + payload.len() + PUBLISH_OVERHEAD > PACKET_MAX_LEN {
+            return Err(MqttError::TooLarge);
+        }
+        if qos == QoS::AtLeastOnce {
+            return match self.queue.push(topic, payload, qos.level(), retain) {
+                Ok(_) => Ok(()),
+                Err(QueueError::Full) => Err(MqttError::OutboxFull),
+                Err(_) => Err(MqttError::TooLarge),
+            };
+        }
+        if !self.connected {
+            return Err(MqttError::NotConnected);
+        }
+        let packet = Packet::Publish(Publish {
+            retain,
+            ..Publish::new(topic, payload)
+        });
+        let start = self.qos0.len();
+        // Encode into the unused tail; the buffer only grows by what fits.
+        self.qos0
+            .resize(QOS0_BUFFER_LEN, 0)
+            .expect("resize to capacity");
+        let encoded = packet.encode(&mut self.qos0[start..]);
+        self.qos0.truncate(start + encoded.unwrap_or(0));
+        match encoded {
+            Ok(_) => Ok(()),
+            Err(CodecError::BufferTooSmall) => Err(MqttError::OutboxFull),
+            Err(CodecError::Invalid(e)) => Err(MqttError::Invalid(e)),
+            Err(_) => Err(MqttError::TooLarge),
+        }
+    }
+
+    /// Adds a topic filter, sent now if connected and on every reconnect.
+    pub(crate) fn subscribe(&mut self, filter: &str, qos: QoS) -> Result<(), MqttError> {
+        let subscription = Subscription::new(filter, qos)?;
+        self.subscriptions
+            .push(subscription)
+            .map_err(|_| MqttError::TooManySubscriptions)
+    }
+
+    /// Records a connection change.  A new connection restores every
+    /// subscription; a lost one discards the staged QoS 0 publishes.
+    pub(crate) fn set_connected(&mut self, connected: bool) {
+        self.connected = connected;
+        self.subscribed = 0;
+        if !connected {
+            self.qos0.clear();
+        }
+    }
+
+    /// Whether the broker accepted the current connection.
+    pub(crate) fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Number of QoS 1 publishes not yet acknowledged by the broker.
+    pub(crate) fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Next packet identifier: 1 to 65 535, wrapping.
+    fn packet_id(&mut self) -> u16 {
+        self.next_id = self.next_id.checked_add(1).unwrap_or(1);
+        self.next_id
+    }
+
+    /// Drops the front publish once acknowledged, or once it proved
+    /// unsendable.
+    fn pop_front(&mut self) {
+        self.queue.pop_front();
+        self.front_id = None;
+    }
+}
+
+/// What [`Session::handle`] asks the client task to do.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Inbound<'p> {
+    /// Nothing.
+    Nothing,
+    /// Deliver an application message, sending `ack` back first.
+    Message {
+        topic: &'p str,
+        payload: &'p [u8],
+        ack: Option<Packet<'static>>,
+    },
+    /// Send this packet back.
+    Reply(Packet<'static>),
+}
+
+/// What [`Session::poll_keep_alive`] asks the client task to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum KeepAliveAction {
+    /// Nothing is due.
+    Idle,
+    /// Send PINGREQ.
+    Ping,
+    /// The broker did not answer a PINGREQ within the keep-alive interval.
+    Expired,
+}
+
+/// State of one broker connection, from CONNACK until it drops.
+#[derive(Debug)]
+pub(crate) struct Session {
+    /// Identifier of the front publish while it awaits PUBACK.
+    inflight: Option<u16>,
+    /// Identifier of the SUBSCRIBE awaiting SUBACK.
+    suback: Option<u16>,
+    keep_alive_ms: u64,
+    last_tx_ms: u64,
+    ping_sent_ms: Option<u64>,
+}
+
+impl Session {
+    /// A session whose CONNECT went out at `now_ms`.
+    pub(crate) fn new(keep_alive_secs: u16, now_ms: u64) -> Self {
+        Self {
+            inflight: None,
+            suback: None,
+            keep_alive_ms: u64::from(keep_alive_secs) * 1000,
+            last_tx_ms: now_ms,
+            ping_sent_ms: None,
+        }
+    }
+
+    /// Encodes the next packet to send into `buf` and returns its length,
+    /// or `0` when nothing is due.
+    ///
+    /// Pending subscriptions go first, one SUBSCRIBE at a time, then the
+    /// staged QoS 0 publishes, then the oldest QoS 1 publish — only one is
+    /// in flight at a time, so the broker sees them in order.
+    pub(crate) fn poll_outgoing(&mut self, outbox: &mut Outbox, buf: &mut [u8]) -> usize {
+        while self.suback.is_none() && outbox.subscribed < outbox.subscriptions.len() {
+            let packet_id = outbox.packet_id();
+            let sub = &outbox.subscriptions[outbox.subscribed];
+            let filters = [(sub.filter.as_str(), sub.qos)];
+            let packet = Packet::Subscribe {
+                packet_id,
+                filters: SubscribeFilters::new(&filters),
+            };
+            match packet.encode(buf) {
+                Ok(n) => {
+                    self.suback = Some(packet_id);
+                    return n;
+                }
+                Err(e) => {
+                    log::warn!("MQTT: cannot subscribe to '{}': {:?}", sub.filter, e);
+                    outbox.subscribed += 1;
+                }
+            }
+        }
+
+        if !outbox.qos0.is_empty() {
+            let n = outbox.qos0.len().min(buf.len());
+            buf[..n].copy_from_slice(&outbox.qos0[..n]);
+            outbox.qos0.clear();
+            return n;
+        }
+
+        while self.inflight.is_none() && !outbox.queue.is_empty() {
+            let (packet_id, dup) = match outbox.front_id {
+                Some(id) => (id, true),
+                None => (outbox.packet_id(), false),
+            };
+            let front = outbox.queue.front().expect("queue is not empty");
+            let packet = Packet::Publish(Publish {
+                topic: front.topic,
+                payload: front.payload,
+                qos: QoS::AtLeastOnce,
+                retain: front.retain,
+                dup,
+                packet_id: Some(packet_id),
+            });
+            match packet.encode(buf) {
+                Ok(n) => {
+                    outbox.front_id = Some(packet_id);
+                    self.inflight = Some(packet_id);
+                    return n;
+                }
+                Err(e) => {
+                    log::warn!(
+                        "MQTT: dropping queued publish to '{}': {:?}",
+                        front.topic,
+                        e
+                    );
+                    outbox.pop_front();
+                }
+            }
+        }
+        0
+    }
+
+    /// Applies a packet received from the broker.
+    ///
+    /// # Errors
+    ///
+    /// Returns a reason when the packet is one a broker never sends; the
+    /// connection should be dropped.
+    pub(crate) fn handle<'p>(
+        &mut self,
+        packet: Packet<'p>,
+        outbox: &mut Outbox,
+    ) -> Result<Inbound<'p>, &'static str> {
+        match packet {
+            Packet::Publish(publish) => {
+                let ack = match (publish.qos, publish.packet_id) {
+                    (QoS::AtLeastOnce, Some(id)) => Some(Packet::PubAck(id)),
+                    (QoS::ExactlyOnce, Some(id)) => Some(Packet::PubRec(id)),
+                    _ => None,
+                };
+                Ok(Inbound::Message {
+                    topic: publish.topic,
+                    payload: publish.payload,
+                    ack,
+                })
+            }
+            Packet::PubAck(id) => {
+                if self.inflight == Some(id) {
+                    self.inflight = None;
+                    outbox.pop_front();
+                } else {
+                    log::debug!("MQTT: ignoring PUBACK for unknown id {}", id);
+                }
+                Ok(Inbound::Nothing)
+            }
+            Packet::PubRel(id) => Ok(Inbound::Reply(Packet::PubComp(id))),
+            Packet::SubAck(suback) => {
+                if self.suback != Some(suback.packet_id) {
+                    log::debug!("MQTT: ignoring SUBACK for unknown id {}", suback.packet_id);
+                    return Ok(Inbound::Nothing);
+                }
+                self.suback = None;
+                if let Some(sub) = outbox.subscriptions.get(outbox.subscribed) {
+                    if suback.granted().any(|granted| granted.is_none()) {
+                        log::warn!("MQTT: broker refused subscription to '{}'", sub.filter);
+                    }
+                }
+                outbox.subscribed += 1;
+                Ok(Inbound::Nothing)
+            }
+            Packet::PingResp => {
+                self.ping_sent_ms = None;
+                Ok(Inbound::Nothing)
+            }
+            Packet::PubRec(_) | Packet::PubComp(_) | Packet::UnsubAck(_) => Ok(Inbound::Nothing),
+            Packet::Connect(_)
+            | Packet::ConnAck(_)
+            | Packet::Subscribe { .. }
+            | Packet::Unsubscribe { .. }
+            | Packet::PingReq
+            | Packet::Disconnect => Err("unexpected packet from broker"),
+        }
+    }
+
+    /// Records that a packet went out at `now_ms`.
+    pub(crate) fn sent(&mut self, now_ms: u64) {
+        self.last_tx_ms = now_ms;
+    }
+
+    /// When [`poll_keep_alive`](Self::poll_keep_alive) next has work, or
+    /// `None` with keep-alive disabled.
+    pub(crate) fn deadline(&self) -> Option<u64> {
+        if self.keep_alive_ms == 0 {
+            return None;
+        }
+        Some(match self.ping_sent_ms {
+            Some(sent) => sent + self.keep_alive_ms,
+            None => self.last_tx_ms + self.keep_alive_ms,
+        })
+    }
+
+    /// Decides whether a PINGREQ is due, or an unanswered one has expired.
+    pub(crate) fn poll_keep_alive(&mut self, now_ms: u64) -> KeepAliveAction {
+        match self.deadline() {
+            Some(deadline) if now_ms >= deadline => {
+                if self.ping_sent_ms.is_some() {
+                    KeepAliveAction::Expired
+                } else {
+                    self.ping_sent_ms = Some(now_ms);
+                    KeepAliveAction::Ping
+                }
+            }
+            _ => KeepAliveAction::Idle,
+        }
+    }
+}
+
+/// Splits the broker's byte stream into packets.
+///
+/// Packets longer than [`PACKET_MAX_LEN`] are skipped — read and thrown
+/// away — rather than failing the connection.
+pub(crate) struct Framer {
+    buf: [u8; PACKET_MAX_LEN],
+    filled: usize,
+    /// Bytes of an oversized packet still to throw away.
+    skip: usize,
+}
+
+impl Framer {
+    /// An empty framer.
+    pub(crate) const fn new() -> Self {
+        Self {
+            buf: [0; PACKET_MAX_LEN],
+            filled: 0,
+            skip: 0,
+        }
+    }
+
+    /// Forgets buffered bytes; call on every new connection.
+    pub(crate) fn reset(&mut self) {
+        self.filled = 0;
+        self.skip = 0;
+    }
+
+    /// Free space to read into; never empty.
+    pub(crate) fn space(&mut self) -> &mut [u8] {
+        &mut self.buf[self.filled..]
+    }
+
+    /// Accounts for `n` bytes read into [`space`](Self::space).
+    pub(crate) fn commit(&mut self, n: usize) {
+        let dropped = n.min(self.skip);
+        self.skip -= dropped;
+        let start = self.filled;
+        self.buf.copy_within(start + dropped..start + n, start);
+        self.filled += n - dropped;
+    }
+
+    /// Length of the complete packet at the front, if one is buffered.
+    ///
+    /// # Errors
+    ///
+    /// [`CodecError::BadRemainingLength`] when the stream is not MQTT.
+    pub(crate) fn frame(&mut self) -> Result<Option<usize>, CodecError> {
+        let total = match packet_len(&self.buf[..self.filled]) {
+            Ok(total) => total,
+            Err(CodecError::Incomplete) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        if total > PACKET_MAX_LEN {
+            log::warn!(
+                "MQTT: skipping {}-byte packet (limit {})",
+                total,
+                PACKET_MAX_LEN
+            );
+            self.skip = total - self.filled;
+            self.filled = 0;
+            return Ok(None);
+        }
+        Ok((total <= self.filled).then_some(total))
+    }
+
+    /// The buffered bytes of a packet [`frame`](Self::frame) reported.
+    pub(crate) fn packet(&self, len: usize) -> &[u8] {
+        &self.buf[..len]
+    }
+
+    /// Drops a handled packet of `len` bytes.
+    pub(crate) fn consume(&mut self, len: usize) {
+        self.buf.copy_within(len..self.filled, 0);
+        self.filled -= len;
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use juggler::mqtt::codec::SubAck;
+
+    fn outbox(filters: &[&str]) -> Outbox {
+        let mut subs = Vec::new();
+        for filter in filters {
+            subs.push(Subscription::new(filter, QoS::AtLeastOnce).unwrap())
+                .unwrap();
+        }
+        Outbox::new(subs)
+    }
+
+    fn decode(buf: &[u8]) -> Packet<'_> {
+        let (packet, used) = Packet::decode(buf).unwrap();
+        assert_eq!(used, buf.len());
+        packet
+    }
+
+    #[test]
+    fn subscriptions_are_sent_one_at_a_time_and_restored_on_reconnect() {
+        let mut outbox = outbox(&["a/#", "b/+"]);
+        outbox.set_connected(true);
+        let mut session = Session::new(30, 0);
+        let mut buf = [0u8; PACKET_MAX_LEN];
+
+        let n = session.poll_outgoing(&mut outbox, &mut buf);
+        let Packet::Subscribe { packet_id, filters } = decode(&buf[..n]) else {
+            panic!("expected SUBSCRIBE");
+        };
+        assert_eq!(filters.iter().next(), Some(("a/#", QoS::AtLeastOnce)));
+        // Nothing else until the SUBACK arrives.
+        assert_eq!(session.poll_outgoing(&mut outbox, &mut buf), 0);
+
+        let suback = Packet::SubAck(SubAck {
+            packet_id,
+            return_codes: &[1],
+        });
+        assert_eq!(session.handle(suback, &mut outbox), Ok(Inbound::Nothing));
+        let n = session.poll_outgoing(&mut outbox, &mut buf);
+        let Packet::Subscribe { filters, .. } = decode(&buf[..n]) else {
+            panic!("expected SUBSCRIBE");
+        };
+        assert_eq!(filters.iter().next(), Some(("b/+", QoS::AtLeastOnce)));
+
+        // A new connection starts over from the first filter.
+        outbox.set_connected(false);
+        outbox.set_connected(true);
+        let mut session = Session::new(30, 0);
+        let n = session.poll_outgoing(&mut outbox, &mut buf);
+        let Packet::Subscribe { filters, .. } = decode(&buf[..n]) else {
+            panic!("expected SUBSCRIBE");
+        };
+        assert_eq!(filters.iter().next(), Some(("a/#", QoS::AtLeastOnce)));
+    }
+
+    #[test]
+    fn qos1_publish_stays_queued_until_puback() {
+        let mut outbox = outbox(&[]);
+        outbox
+            .publish("t/1", b"one", QoS::AtLeastOnce, false)
+            .unwrap();
+        outbox
+            .publish("t/2", b"two", QoS::AtLeastOnce, true)
+            .unwrap();
+        outbox.set_connected(true);
+        let mut session = Session::new(30, 0);
+        let mut buf = [0u8; PACKET_MAX_LEN];
+
+        let n = session.poll_outgoing(&mut outbox, &mut buf);
+        let Packet::Publish(first) = decode(&buf[..n]) else {
+            panic!("expected PUBLISH");
+        };
+        assert_eq!(
+            (first.topic, first.payload, first.dup),
+            ("t/1", &b"one"[..], false)
+        );
+        let first_id = first.packet_id;
+        // One in flight at a time.
+        assert_eq!(session.poll_outgoing(&mut outbox, &mut buf), 0);
+        assert_eq!(outbox.queued(), 2);
+
+        let ack = Packet::PubAck(first_id.unwrap());
+        assert_eq!(session.handle(ack, &mut outbox), Ok(Inbound::Nothing));
+        assert_eq!(outbox.queued(), 1);
+        let n = session.poll_outgoing(&mut outbox, &mut buf);
+        let Packet::Publish(second) = decode(&buf[..n]) else {
+            panic!("expected PUBLISH");
+        };
+        assert_eq!((second.topic, second.retain), ("t/2", true));
+        assert_ne!(second.packet_id, first_id);
+    }
+
+    #[test]
+    fn unacknowledged_publish_is_resent_with_dup_after_reconnect() {
+        let mut outbox = outbox(&[]);
+        outbox.set_connected(true);
+        outbox.publish("t", b"x", QoS::AtLeastOnce, false).unwrap();
+        let mut buf = [0u8; PACKET_MAX_LEN];
+        let mut session = Session::new(30, 0);
+        let n = session.poll_outgoing(&mut outbox, &mut buf);
+        let Packet::Publish(sent) = decode(&buf[..n]) else {
+            panic!("expected PUBLISH");
+        };
+        let sent_id = sent.packet_id;
+
+        outbox.set_connected(false);
+        outbox.set_connected(true);
+        let mut session = Session::new(30, 0);
+        let n = session.poll_outgoing(&mut outbox, &mut buf);
+        let Packet::Publish(resent) = decode(&buf[..n]) else {
+            panic!("expected PUBLISH");
+        };
+        assert!(resent.dup);
+        assert_eq!(resent.packet_id, sent_id);
+    }
+
+    #[test]
+    fn qos0_publish_needs_a_connection_and_is_dropped_with_it() {
+        let mut outbox = outbox(&[]);
+        assert_eq!(
+            outbox.publish("t", b"x", QoS::AtMostOnce, false),
+            Err(MqttError::NotConnected)
+        );
+        outbox.set_connected(true);
+        outbox.publish("t", b"a", QoS::AtMostOnce, false).unwrap();
+        outbox.publish("t", b"b", QoS::AtMostOnce, false).unwrap();
+        outbox.set_connected(false);
+        outbox.set_connected(true);
+        outbox.publish("t", b"c", QoS::AtMostOnce, false).unwrap();
+
+        let mut session = Session::new(30, 0);
+        let mut buf = [0u8; PACKET_MAX_LEN];
+        let n = session.poll_outgoing(&mut outbox, &mut buf);
+        let (packet, used) = Packet::decode(&buf[..n]).unwrap();
+        assert_eq!(used, n, "only the publish staged after the reconnect");
+        assert_eq!(packet, Packet::Publish(Publish::new("t", b"c")));
+        assert_eq!(session.poll_outgoing(&mut outbox, &mut buf), 0);
+    }
+
+    #[test]
+    fn publish_rejects_what_the_client_cannot_send() {
+        let mut outbox = outbox(&[]);
+        outbox.set_connected(true);
+        assert!(matches!(
+            outbox.publish("a/+", b"", QoS::AtMostOnce, false),
+            Err(MqttError::Invalid(_))
+        ));
+        assert!(matches!(
+            outbox.publish("t", b"", QoS::ExactlyOnce, false),
+            Err(MqttError::Invalid(_))
+        ));
+        let big = [0u8; PACKET_MAX_LEN];
+        assert_eq!(
+            outbox.publish("t", &big, QoS::AtLeastOnce, false),
+            Err(MqttError::TooLarge)
+        );
+
+        let payload = [0u8; 500];
+        while outbox
+            .publish("t", &payload, QoS::AtLeastOnce, false)
+            .is_ok()
+        {}
+        assert_eq!(
+            outbox.publish("t", &payload, QoS::AtLeastOnce, false),
+            Err(MqttError::OutboxFull)
+        );
+        while outbox
+            .publish("t", &payload, QoS::AtMostOnce, false)
+            .is_ok()
+        {}
+        assert_eq!(
+            outbox.publish("t", &payload, QoS::AtMostOnce, false),
+            Err(MqttError::OutboxFull)
+        );
+    }
+
+    #[test]
+    fn subscribe_validates_and_caps_the_filter_list() {
+        let mut outbox = outbox(&[]);
+        assert!(matches!(
+            outbox.subscribe("a/#/b", QoS::AtMostOnce),
+            Err(MqttError::Invalid(_))
+        ));
+        for _ in 0..MAX_SUBSCRIPTIONS {
+            outbox.subscribe("a/#", QoS::AtMostOnce).unwrap();
+        }
+        assert_eq!(
+            outbox.subscribe("a/#", QoS::AtMostOnce),
+            Err(MqttError::TooManySubscriptions)
+        );
+    }
+
+    #[test]
+    fn incoming_publishes_are_acknowledged_by_qos() {
+        let mut outbox = outbox(&[]);
+        let mut session = Session::new(30, 0);
+        let qos0 = Packet::Publish(Publish::new("t", b"0"));
+        assert_eq!(
+            session.handle(qos0, &mut outbox),
+            Ok(Inbound::Message {
+                topic: "t",
+                payload: b"0",
+                ack: None
+            })
+        );
+        let qos1 = Packet::Publish(Publish {
+            qos: QoS::AtLeastOnce,
+            packet_id: Some(7),
+            ..Publish::new("t", b"1")
+        });
+        assert_eq!(
+            session.handle(qos1, &mut outbox),
+            Ok(Inbound::Message {
+                topic: "t",
+                payload: b"1",
+                ack: Some(Packet::PubAck(7))
+            })
+        );
+        assert_eq!(
+            session.handle(Packet::PubRel(9), &mut outbox),
+            Ok(Inbound::Reply(Packet::PubComp(9)))
+        );
+        assert!(session.handle(Packet::PingReq, &mut outbox).is_err());
+    }
+
+    #[test]
+    fn keep_alive_pings_when_idle_and_expires_without_pingresp() {
+        let mut session = Session::new(10, 1_000);
+        assert_eq!(session.deadline(), Some(11_000));
+        assert_eq!(session.poll_keep_alive(10_999), KeepAliveAction::Idle);
+
+        // Traffic pushes the ping back.
+        session.sent(5_000);
+        assert_eq!(session.poll_keep_alive(11_000), KeepAliveAction::Idle);
+        assert_eq!(session.poll_keep_alive(15_000), KeepAliveAction::Ping);
+        session.sent(15_000);
+        assert_eq!(session.deadline(), Some(25_000));
+
+        let mut outbox = outbox(&[]);
+        session.handle(Packet::PingResp, &mut outbox).unwrap();
+        assert_eq!(session.poll_keep_alive(25_000), KeepAliveAction::Ping);
+        assert_eq!(session.poll_keep_alive(35_000), KeepAliveAction::Expired);
+
+        let disabled = Session::new(0, 0);
+        assert_eq!(disabled.deadline(), None);
+    }
+
+    #[test]
+    fn framer_splits_the_stream_into_packets() {
+        let mut framer = Framer::new();
+        // PINGRESP, then the first half of a PUBACK.
+        let chunk = [0xD0, 0x00, 0x40, 0x02];
+        framer.space()[..chunk.len()].copy_from_slice(&chunk);
+        framer.commit(chunk.len());
+        assert_eq!(framer.frame(), Ok(Some(2)));
+        assert_eq!(decode(framer.packet(2)), Packet::PingResp);
+        framer.consume(2);
+        assert_eq!(framer.frame(), Ok(None));
+
+        framer.space()[..2].copy_from_slice(&[0x00, 0x05]);
+        framer.commit(2);
+        assert_eq!(framer.frame(), Ok(Some(4)));
+        assert_eq!(decode(framer.packet(4)), Packet::PubAck(5));
+    }
+
+    #[test]
+    fn framer_skips_oversized_packets() {
+        let mut framer = Framer::new();
+        // A PUBLISH with a 2000-byte remaining length: 0xD0 0x0F.
+        let header = [0x30, 0xD0, 0x0F, 0xAA];
+        framer.space()[..header.len()].copy_from_slice(&header);
+        framer.commit(header.len());
+        assert_eq!(framer.frame(), Ok(None));
+
+        // The rest of it (1999 bytes) arrives, followed by a PINGRESP.
+        let mut left = 2003 - header.len();
+        while left > 0 {
+            let n = left.min(framer.space().len());
+            framer.commit(n);
+            left -= n;
+        }
+        framer.space()[..2].copy_from_slice(&[0xD0, 0x00]);
+        framer.commit(2);
+        assert_eq!(framer.frame(), Ok(Some(2)));
+        assert_eq!(decode(framer.packet(2)), Packet::PingResp);
+    }
+}
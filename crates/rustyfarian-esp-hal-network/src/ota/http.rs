@@ -103,6 +103,8 @@ pub(crate) struct HttpResponse {
 /// expected.  If the caller passes a hostname, it is forwarded verbatim in the
 /// `Host` header — connect/resolve is the application's concern.
 pub(crate) struct ParsedUrl<'a> {
+    /// Host name or IP literal.  IPv6 literals are stored without their
+    /// brackets; [`format_get_request`] adds them back.
     pub host: &'a str,
     /// TCP port — used both to connect the socket and to populate the `Host`
     /// request header when not the HTTP/1.1 default of 80 (per RFC 7230 §5.4,
@@ -115,6 +117,9 @@ pub(crate) struct ParsedUrl<'a> {
 ///
 /// `https://` is rejected — plain HTTP only (ADR 011 §2).
 /// The path defaults to `/` when absent.
+///
+/// IPv6 literals must be bracketed, as in `http://[2001:db8::1]:8080/fw.bin`
+/// (RFC 3986 §3.2.2); zone identifiers are not supported.
 pub(crate) fn parse_url(url: &str) -> Result<ParsedUrl<'_>, HttpError> {
     let rest = url.strip_prefix("http://").ok_or(HttpError::BadUrl)?;
 
@@ -124,15 +129,32 @@ pub(crate) fn parse_url(url: &str) -> Result<ParsedUrl<'_>, HttpError> {
         None => (rest, "/"),
     };
 
-    let (host, port) = if let Some(colon) = authority.rfind(':') {
-        let port_str = &authority[colon + 1..];
-        let port: u16 = port_str.parse().map_err(|_| HttpError::BadUrl)?;
-        (&authority[..colon], port)
+    // Split host from the optional `:port` suffix.  A bracketed IPv6 literal
+    // contains colons of its own, so the port is whatever follows `]`.
+    let (host, port_str) = if let Some(bracketed) = authority.strip_prefix('[') {
+        let close = bracketed.find(']').ok_or(HttpError::BadUrl)?;
+        let host = &bracketed[..close];
+        if host.parse::<core::net::Ipv6Addr>().is_err() {
+            return Err(HttpError::BadUrl);
+        }
+        match &bracketed[close + 1..] {
+            "" => (host, None),
+            tail => (host, Some(tail.strip_prefix(':').ok_or(HttpError::BadUrl)?)),
+        }
     } else {
-        (authority, 80u16)
+        match authority.split_once(':') {
+            Some((host, port_str)) => (host, Some(port_str)),
+            None => (authority, None),
+        }
     };
 
-    if host.is_empty() {
+    let port = match port_str {
+        Some(port_str) => port_str.parse().map_err(|_| HttpError::BadUrl)?,
+        None => 80u16,
+    };
+
+    // `[` and `]` may only delimit an IPv6 literal.
+    if host.is_empty() || host.contains(['[', ']']) {
         return Err(HttpError::BadUrl);
     }
 
@@ -471,6 +493,7 @@ pub(crate) mod async_client {
 /// component.  The port is included whenever it is not the HTTP/1.1
 /// default of 80; servers behind reverse proxies / virtual-host routers
 /// will otherwise route the request incorrectly or reject it outright.
+/// An IPv6 literal `host` (one containing `:`) is written in brackets.
 pub(crate) fn format_get_request(
     buf: &mut [u8],
     host: &str,
@@ -481,7 +504,15 @@ pub(crate) fn format_get_request(
     write_bytes(buf, &mut pos, b"GET ")?;
     write_bytes(buf, &mut pos, path.as_bytes())?;
     write_bytes(buf, &mut pos, b" HTTP/1.1\r\nHost: ")?;
+    // Only an IPv6 literal contains `:`; it goes back in brackets.
+    let bracketed = host.contains(':');
+    if bracketed {
+        write_bytes(buf, &mut pos, b"[")?;
+    }
     write_bytes(buf, &mut pos, host.as_bytes())?;
+    if bracketed {
+        write_bytes(buf, &mut pos, b"]")?;
+    }
     if port != 80 {
         write_bytes(buf, &mut pos, b":")?;
         write_u16_decimal(buf, &mut pos, port)?;
@@ -797,6 +828,40 @@ mod tests {
         assert_eq!(p.path, "/");
     }
 
+    #[test]
+    fn url_ipv6_literal() {
+        let p = parse_url("http://[2001:db8::1]/firmware.bin").unwrap();
+        assert_eq!(p.host, "2001:db8::1");
+        assert_eq!(p.port, 80);
+        assert_eq!(p.path, "/firmware.bin");
+    }
+
+    #[test]
+    fn url_ipv6_literal_with_port() {
+        // NAT64 well-known prefix mapping of 192.0.2.10.
+        let p = parse_url("http://[64:ff9b::c000:20a]:8080").unwrap();
+        assert_eq!(p.host, "64:ff9b::c000:20a");
+        assert_eq!(p.port, 8080);
+        assert_eq!(p.path, "/");
+    }
+
+    #[test]
+    fn url_ipv6_malformed_rejected() {
+        for url in [
+            "http://2001:db8::1/fw.bin",       // unbracketed
+            "http://[2001:db8::1/fw.bin",      // unclosed
+            "http://[2001:db8::1]8080/fw.bin", // missing `:` before port
+            "http://[2001:db8::1]:/fw.bin",    // empty port
+            "http://[]/fw.bin",                // empty literal
+            "http://[not-an-address]/fw.bin",  // not IPv6
+            "http://[fe80::1%25wlan0]/fw.bin", // zone identifier
+            "http://[192.168.1.1]/fw.bin",     // IPv4 in brackets
+            "http://host]:80/fw.bin",          // stray bracket
+        ] {
+            assert!(parse_url(url).is_err(), "{url}");
+        }
+    }
+
     // ── GET request formatting (Host header port handling) ───────────────────
 
     #[test]
@@ -843,4 +908,16 @@ mod tests {
         let req = core::str::from_utf8(&buf[..n]).unwrap();
         assert!(req.contains("Host: h:65535\r\n"));
     }
+
+    #[test]
+    fn host_header_brackets_ipv6_literal() {
+        let mut buf = [0u8; 256];
+        let n = format_get_request(&mut buf, "2001:db8::1", 80, "/fw.bin").unwrap();
+        let req = core::str::from_utf8(&buf[..n]).unwrap();
+        assert!(req.contains("Host: [2001:db8::1]\r\n"));
+
+        let n = format_get_request(&mut buf, "2001:db8::1", 8080, "/fw.bin").unwrap();
+        let req = core::str::from_utf8(&buf[..n]).unwrap();
+        assert!(req.contains("Host: [2001:db8::1]:8080\r\n"));
+    }
 }
@@ -48,7 +48,11 @@ pub struct OtaManagerConfig {
 ///
 /// Only `http://` URLs with IP-literal hosts are supported for the MVP.
 /// DNS resolution is the caller's responsibility; pass an IP address in
-/// the URL (e.g. `http://192.168.1.100/firmware.bin`).
+/// the URL (e.g. `http://192.168.1.100/firmware.bin`).  IPv6 literals go
+/// in brackets (e.g. `http://[2001:db8::10]:8080/firmware.bin`); on a NAT64
+/// segment, an IPv4 origin is reachable through its mapped address such as
+/// `http://[64:ff9b::c0a8:164]/firmware.bin`, provided the socket was
+/// connected to that IPv6 address (crate feature `ipv6`).
 /// `https://` is rejected per ADR 011 §2.
 ///
 /// # Flash peripheral
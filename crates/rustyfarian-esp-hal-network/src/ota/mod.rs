@@ -3,6 +3,9 @@
 //! Wraps `esp_bootloader_esp_idf::OtaUpdater` and provides streaming
 //! firmware download with strict HTTP/1.1 GET over `embassy-net`.
 //!
+//! On ESP32-S3, and without `embassy`, the types compile to placeholders
+//! without the OTA operations.
+//!
 //! All public APIs are experimental.
 
 // Internal HTTP/1.1 GET client — implementation detail per ADR 011 §2.
@@ -1,6 +1,7 @@
 //! Host-build stub.
 //!
-//! Compiled when no chip + embassy features are enabled.
+//! Compiled without the `embassy` feature, and on chips the real manager does
+//! not support yet (`esp32s3`).
 //! Provides type placeholders so `cargo check -p rustyfarian-esp-hal-network`
 //! (no features) succeeds on the host.
 //!
@@ -25,7 +26,8 @@ pub struct OtaManagerConfig {
 ///
 /// Bare-metal OTA manager placeholder.
 ///
-/// Compiled when chip + `embassy` features are not active. The real type
+/// Compiled when chip + `embassy` features are not active, and on
+/// `esp32s3`. The real type
 /// (with `new`, `fetch_and_apply`, `mark_valid`, `rollback`) lives in
 /// `manager.rs` and is gated on those features.
 #[derive(Debug, Default)]
@@ -38,7 +38,7 @@
 // and its UdpSocket usage are compiled away.  Allow dead-code on the types
 // that remain so clippy -D warnings does not fail on stub/host builds.
 #![cfg_attr(
-    not(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6"))),
+    not(all(feature = "embassy", feature = "_wifi-chip")),
     allow(dead_code)
 )]
 
@@ -49,6 +49,9 @@ const DHCP_SERVER_PORT: u16 = 67;
 /// Standard DHCP client port (reply destination).
 const DHCP_CLIENT_PORT: u16 = 68;
 
+/// `embassy-net` sockets the server task keeps open: one UDP socket.
+pub(crate) const SOCKETS: usize = 1;
+
 /// RFC 2131 minimum BOOTP packet size (BOOTP fixed header = 236 bytes).
 const BOOTP_FIXED_LEN: usize = 236;
 /// DHCP magic cookie appended immediately after the BOOTP header.
@@ -127,6 +130,12 @@ impl Ipv4Addr {
     }
 }
 
+impl From<core::net::Ipv4Addr> for Ipv4Addr {
+    fn from(addr: core::net::Ipv4Addr) -> Self {
+        Self(addr.octets())
+    }
+}
+
 impl core::fmt::Display for Ipv4Addr {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}.{}.{}.{}", self.0[0], self.0[1], self.0[2], self.0[3])
@@ -139,7 +148,13 @@ impl core::fmt::Display for Ipv4Addr {
 ///
 /// All fields use network-order `Ipv4Addr` values.  The default matches the
 /// standard captive-portal `192.168.4.x/24` subnet with the AP at
-/// `192.168.4.1` and a pool of `192.168.4.10` – `192.168.4.20`.
+/// `192.168.4.1` and a pool of `192.168.4.10` – `192.168.4.20`; use
+/// [`DhcpServerConfig::for_subnet`] to serve the subnet of an `ApConfig`.
+///
+/// The pool must lie within one /24 and hold at most
+/// [`AP_DHCP_POOL_MAX`](juggler::wifi::AP_DHCP_POOL_MAX) addresses; [`run`]
+/// refuses to serve anything else.  `validate_ap_subnet` enforces the same
+/// limits, so a validated `ApSubnet` is always served.
 pub struct DhcpServerConfig {
     /// IP address of the DHCP server itself (= the SoftAP IP, `192.168.4.1`).
     pub server_ip: Ipv4Addr,
@@ -151,6 +166,9 @@ pub struct DhcpServerConfig {
     pub pool_end: Ipv4Addr,
     /// Lease duration in seconds (`300` = 5 minutes).
     pub lease_secs: u32,
+    /// Station monitor told about every ACKed lease, so its client list
+    /// carries each station's address (`None` by default).
+    pub stations: Option<&'static crate::wifi::ApStationMonitor>,
 }
 
 impl Default for DhcpServerConfig {
@@ -161,10 +179,34 @@ impl Default for DhcpServerConfig {
             pool_start: Ipv4Addr::new(192, 168, 4, 10),
             pool_end: Ipv4Addr::new(192, 168, 4, 20),
             lease_secs: 300,
+            stations: None,
         }
     }
 }
 
+impl DhcpServerConfig {
+    /// Serves `subnet` — the AP address, netmask, and pool of an `ApConfig` —
+    /// with the default lease time.
+    pub fn for_subnet(subnet: &juggler::wifi::ApSubnet) -> Self {
+        Self {
+            server_ip: subnet.ip.into(),
+            subnet_mask: subnet.netmask.into(),
+            pool_start: subnet.dhcp_start.into(),
+            pool_end: subnet.dhcp_end.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Reports every ACKed lease to `stations`.
+    pub fn with_station_monitor(
+        mut self,
+        stations: &'static crate::wifi::ApStationMonitor,
+    ) -> Self {
+        self.stations = Some(stations);
+        self
+    }
+}
+
 // ── DhcpError ─────────────────────────────────────────────────────────────────
 
 /// Errors produced by the DHCP codec.
@@ -542,8 +584,12 @@ pub fn encode_nak(
 
 // ── Lease table ───────────────────────────────────────────────────────────────
 
-/// Maximum number of simultaneous leases in the default pool (`.10` to `.20`).
-const POOL_SIZE: usize = 11;
+/// Maximum number of simultaneous leases — the size of the default pool
+/// (`.10` to `.20`).  Smaller pools use the first slots of the table.
+///
+/// Tied to [`juggler::wifi::AP_DHCP_POOL_MAX`], so an `ApSubnet` that passes
+/// `validate_ap_subnet` always fits.
+const POOL_SIZE: usize = juggler::wifi::AP_DHCP_POOL_MAX as usize;
 
 /// A single DHCP lease entry.
 ///
@@ -558,9 +604,11 @@ pub struct Lease {
     pub offered_at_secs: u64,
 }
 
-/// Fixed-size DHCP lease table for the default 11-address pool.
+/// Fixed-size DHCP lease table for pools of up to 11 addresses.
 pub struct LeaseTable {
     entries: [Option<Lease>; POOL_SIZE],
+    /// Number of addresses in the pool; slots at and beyond it stay unused.
+    len: usize,
     /// First address in the pool — bound at construction; per-call sites cannot
     /// override it.  See `docs/project-lore.md` "esp-hal April 2026 Stack" for
     /// the rationale (the original API passed this per-call and caused the
@@ -576,17 +624,25 @@ impl LeaseTable {
     ///
     /// `pool_start` is the first IP address handed out by this table; the
     /// table itself is `[Option<Lease>; POOL_SIZE]`, so the inclusive last
-    /// address is `pool_start + POOL_SIZE - 1`.  `lease_secs` is the lifetime
-    /// applied to each issued lease (used by [`Self::allocate`] for the
-    /// expired-slot reuse policy).
+    /// address is `pool_start + POOL_SIZE - 1` unless
+    /// [`with_len`](Self::with_len) shortens the pool.  `lease_secs` is the
+    /// lifetime applied to each issued lease (used by [`Self::allocate`] for
+    /// the expired-slot reuse policy).
     pub const fn new(pool_start: Ipv4Addr, lease_secs: u32) -> Self {
         Self {
             entries: [None; POOL_SIZE],
+            len: POOL_SIZE,
             pool_start,
             lease_secs,
         }
     }
 
+    /// Limits the pool to its first `len` addresses (capped at `POOL_SIZE`).
+    pub const fn with_len(mut self, len: usize) -> Self {
+        self.len = if len < POOL_SIZE { len } else { POOL_SIZE };
+        self
+    }
+
     /// Returns the pool index for an IP address, or `None` if out of range.
     ///
     /// The pool is within one /24 subnet — only the last octet varies.
@@ -598,7 +654,7 @@ impl LeaseTable {
             return None;
         }
         let offset = addr.0[3].checked_sub(self.pool_start.0[3])? as usize;
-        if offset < POOL_SIZE {
+        if offset < self.len {
             Some(offset)
         } else {
             None
@@ -672,7 +728,7 @@ impl LeaseTable {
         }
 
         // 3. First free or expired slot.
-        for idx in 0..POOL_SIZE {
+        for idx in 0..self.len {
             let available = self.entries[idx].is_none_or(|l| self.is_expired(&l, now_secs));
             if available {
                 let addr = self.addr_of(idx);
@@ -838,22 +894,23 @@ pub(crate) fn decide_request(
 /// `LeaseTable` is a `[Option<Lease>; POOL_SIZE]` where `addr_of` derives
 /// each pool slot by `wrapping_add`-ing the index onto `pool_start`'s last
 /// octet and leaving the first three octets unchanged. The pool must
-/// therefore lie wholly within a single /24 and span exactly `pool_size`
-/// slots. A `pool_start[3] + pool_size - 1 > 255` overflow is structurally
-/// unreachable under this contract — if it would happen, then by definition
-/// `pool_end` either lands in the next /24 (caught by `CrossesSubnet`) or
-/// the implied `end - start + 1` no longer equals `pool_size` (caught by
-/// `SizeMismatch`), so no separate `LastOctetOverflow` variant is needed.
+/// therefore lie wholly within a single /24 and span between one and
+/// `pool_size` slots. A last-octet overflow is structurally unreachable
+/// under this contract — if it would happen, then by definition `pool_end`
+/// lands in the next /24 (caught by `CrossesSubnet`), so no separate
+/// `LastOctetOverflow` variant is needed.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum PoolGeometryError {
-    SizeMismatch { configured: usize, required: usize },
+    Reversed,
+    TooLarge { configured: usize, max: usize },
     CrossesSubnet,
 }
 
 impl PoolGeometryError {
     pub(crate) fn as_str(self) -> &'static str {
         match self {
-            PoolGeometryError::SizeMismatch { .. } => "configured pool size != POOL_SIZE",
+            PoolGeometryError::Reversed => "pool_end is before pool_start",
+            PoolGeometryError::TooLarge { .. } => "configured pool size > POOL_SIZE",
             PoolGeometryError::CrossesSubnet => "pool crosses a /24 boundary",
         }
     }
@@ -862,30 +919,33 @@ impl PoolGeometryError {
 /// Validate that the pool range fits `LeaseTable`'s within-/24 layout.
 ///
 /// Pure helper extracted so the boundary cases can be locked by host tests
-/// rather than only discovered at server startup. Returns `Ok(())` for the
-/// well-formed default config and `Err` for every shape `LeaseTable` cannot
-/// faithfully address.
+/// rather than only discovered at server startup. Returns the number of
+/// addresses in the pool for a well-formed config and `Err` for every shape
+/// `LeaseTable` cannot faithfully address.
 pub(crate) fn validate_pool_geometry(
     pool_start: Ipv4Addr,
     pool_end: Ipv4Addr,
     pool_size: usize,
-) -> Result<(), PoolGeometryError> {
+) -> Result<usize, PoolGeometryError> {
     let start_u32 = u32::from_be_bytes(pool_start.0);
     let end_u32 = u32::from_be_bytes(pool_end.0);
-    let configured = end_u32.saturating_sub(start_u32).saturating_add(1) as usize;
-    if configured != pool_size {
-        return Err(PoolGeometryError::SizeMismatch {
+    let Some(span) = end_u32.checked_sub(start_u32) else {
+        return Err(PoolGeometryError::Reversed);
+    };
+    let configured = span.saturating_add(1) as usize;
+    if configured > pool_size {
+        return Err(PoolGeometryError::TooLarge {
             configured,
-            required: pool_size,
+            max: pool_size,
         });
     }
     if pool_start.0[0..3] != pool_end.0[0..3] {
         return Err(PoolGeometryError::CrossesSubnet);
     }
-    Ok(())
+    Ok(configured)
 }
 
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 pub async fn run(stack: embassy_net::Stack<'static>, config: DhcpServerConfig) -> ! {
     use embassy_net::udp::{PacketMetadata, UdpSocket};
     use static_cell::StaticCell;
@@ -894,18 +954,21 @@ pub async fn run(stack: embassy_net::Stack<'static>, config: DhcpServerConfig) -
     // table is a `[Option<Lease>; POOL_SIZE]` and `addr_of` only varies the
     // last octet via `wrapping_add`, so a misconfigured pool would silently
     // allocate the wrong IPs.  Refuse to start instead.
-    if let Err(reason) = validate_pool_geometry(config.pool_start, config.pool_end, POOL_SIZE) {
-        log::error!(
-            "DHCP config invalid: pool_start={} pool_end={} — {} (LeaseTable is fixed at POOL_SIZE={}, lives within a single /24, and must not cross the last-octet 255 wraparound; Phase 2 will lift these constraints via const generics)",
-            config.pool_start,
-            config.pool_end,
-            reason.as_str(),
-            POOL_SIZE,
-        );
-        loop {
-            embassy_time::Timer::after(embassy_time::Duration::from_secs(60)).await;
+    let pool_len = match validate_pool_geometry(config.pool_start, config.pool_end, POOL_SIZE) {
+        Ok(len) => len,
+        Err(reason) => {
+            log::error!(
+                "DHCP config invalid: pool_start={} pool_end={} — {} (LeaseTable holds at most POOL_SIZE={}, lives within a single /24, and must not cross the last-octet 255 wraparound; Phase 2 will lift these constraints via const generics)",
+                config.pool_start,
+                config.pool_end,
+                reason.as_str(),
+                POOL_SIZE,
+            );
+            loop {
+                embassy_time::Timer::after(embassy_time::Duration::from_secs(60)).await;
+            }
         }
-    }
+    };
 
     // Static socket buffers — required because `UdpSocket::new` in
     // `embassy-net 0.8` internally transmutes the buffer slices to `'static`
@@ -949,7 +1012,7 @@ pub async fn run(stack: embassy_net::Stack<'static>, config: DhcpServerConfig) -
     // `encode_nak`, all well under 548 B.
     let mut rx_pkt = [0u8; SOCKET_BUF_LEN];
     let mut tx_pkt = [0u8; PACKET_BUF];
-    let mut leases = LeaseTable::new(config.pool_start, config.lease_secs);
+    let mut leases = LeaseTable::new(config.pool_start, config.lease_secs).with_len(pool_len);
 
     let server_ip = config.server_ip;
 
@@ -1098,6 +1161,12 @@ pub async fn run(stack: embassy_net::Stack<'static>, config: DhcpServerConfig) -
                                 log::warn!("DHCP ACK send failed: {:?}", e);
                             } else {
                                 log::info!("DHCP ACK: {} → chaddr={:02x?}", offered_ip, msg.chaddr);
+                                if let Some(stations) = config.stations {
+                                    stations.lease_assigned(
+                                        msg.chaddr,
+                                        core::net::Ipv4Addr::from(offered_ip.octets()),
+                                    );
+                                }
                             }
                         }
                         Err(e) => log::warn!("DHCP ACK encode failed: {:?}", e),
@@ -1135,7 +1204,7 @@ pub async fn run(stack: embassy_net::Stack<'static>, config: DhcpServerConfig) -
 /// netif is the only adjacent broadcast domain.  Phase 2 proper, which
 /// migrates this module into `rustyfarian-esp-hal-network`, should
 /// add a unicast path for renew/rebind transitions.
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 fn dhcp_broadcast_endpoint() -> embassy_net::IpEndpoint {
     use embassy_net::{IpAddress, IpEndpoint, Ipv4Address};
     IpEndpoint {
@@ -1145,7 +1214,7 @@ fn dhcp_broadcast_endpoint() -> embassy_net::IpEndpoint {
 }
 
 /// Encodes and sends a DHCP NAK to `255.255.255.255:68`.
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 async fn do_send_nak(
     sock: &embassy_net::udp::UdpSocket<'_>,
     msg: &DhcpMessage,
@@ -1512,22 +1581,40 @@ mod tests {
         // 192.168.4.10..=192.168.4.20 (POOL_SIZE = 11) — the shipping default.
         let start = Ipv4Addr([192, 168, 4, 10]);
         let end = Ipv4Addr([192, 168, 4, 20]);
-        assert_eq!(validate_pool_geometry(start, end, POOL_SIZE), Ok(()));
+        assert_eq!(validate_pool_geometry(start, end, POOL_SIZE), Ok(POOL_SIZE));
+    }
+
+    #[test]
+    fn pool_geometry_accepts_smaller_pool() {
+        let start = Ipv4Addr([10, 42, 0, 100]);
+        let end = Ipv4Addr([10, 42, 0, 104]);
+        assert_eq!(validate_pool_geometry(start, end, POOL_SIZE), Ok(5));
+        assert_eq!(validate_pool_geometry(start, start, POOL_SIZE), Ok(1));
     }
 
     #[test]
-    fn pool_geometry_rejects_size_mismatch() {
+    fn pool_geometry_rejects_oversized_pool() {
         let start = Ipv4Addr([192, 168, 4, 10]);
-        let end = Ipv4Addr([192, 168, 4, 24]); // 15 slots, not 11
+        let end = Ipv4Addr([192, 168, 4, 24]); // 15 slots, more than 11
         assert_eq!(
             validate_pool_geometry(start, end, POOL_SIZE),
-            Err(PoolGeometryError::SizeMismatch {
+            Err(PoolGeometryError::TooLarge {
                 configured: 15,
-                required: POOL_SIZE,
+                max: POOL_SIZE,
             })
         );
     }
 
+    #[test]
+    fn pool_geometry_rejects_reversed_pool() {
+        let start = Ipv4Addr([192, 168, 4, 20]);
+        let end = Ipv4Addr([192, 168, 4, 10]);
+        assert_eq!(
+            validate_pool_geometry(start, end, POOL_SIZE),
+            Err(PoolGeometryError::Reversed)
+        );
+    }
+
     #[test]
     fn pool_geometry_rejects_crosses_24_boundary() {
         // 192.168.4.250..=192.168.5.4 — 11 slots numerically, but `addr_of(6)`
@@ -1541,6 +1628,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn short_pool_allocates_only_its_own_slots() {
+        let mut table = LeaseTable::new(POOL_START, LEASE_SECS).with_len(2);
+        let a = table.allocate(&[1; 6], None, 0).unwrap();
+        let b = table.allocate(&[2; 6], None, 0).unwrap();
+        // Third client evicts rather than spilling past `.11`.
+        let c = table.allocate(&[3; 6], None, 1).unwrap();
+        assert_eq!(a, Ipv4Addr::new(192, 168, 4, 10));
+        assert_eq!(b, Ipv4Addr::new(192, 168, 4, 11));
+        assert_eq!(c, a);
+        // A requested address past the short pool is not honoured.
+        let d = table.allocate(&[4; 6], Some(Ipv4Addr::new(192, 168, 4, 15)), 2);
+        assert_ne!(d, Some(Ipv4Addr::new(192, 168, 4, 15)));
+    }
+
+    #[test]
+    fn server_config_for_subnet_copies_addresses() {
+        let subnet = juggler::wifi::ApSubnet {
+            ip: core::net::Ipv4Addr::new(10, 42, 0, 1),
+            netmask: core::net::Ipv4Addr::new(255, 255, 255, 0),
+            dhcp_start: core::net::Ipv4Addr::new(10, 42, 0, 100),
+            dhcp_end: core::net::Ipv4Addr::new(10, 42, 0, 110),
+        };
+        let config = DhcpServerConfig::for_subnet(&subnet);
+        assert_eq!(config.server_ip, Ipv4Addr::new(10, 42, 0, 1));
+        assert_eq!(config.subnet_mask, Ipv4Addr::new(255, 255, 255, 0));
+        assert_eq!(config.pool_start, Ipv4Addr::new(10, 42, 0, 100));
+        assert_eq!(config.pool_end, Ipv4Addr::new(10, 42, 0, 110));
+        assert_eq!(config.lease_secs, DhcpServerConfig::default().lease_secs);
+        assert!(config.stations.is_none());
+        assert_eq!(
+            validate_pool_geometry(config.pool_start, config.pool_end, POOL_SIZE),
+            Ok(11)
+        );
+    }
+
     // ── decide_request — RFC 2131 §4.3.2 decision-tree tests ─────────────────
 
     /// Build a minimal REQUEST `DhcpMessage` for the decision tests.
@@ -1724,17 +1847,18 @@ mod tests {
     fn pool_geometry_accepts_boundary_at_255() {
         // The boundary case the dropped `LastOctetOverflow` variant claimed to
         // catch — pool_start.0[3] = 250, pool_size = 6 → last slot lands on
-        // .255 exactly. The remaining two checks (size match, same subnet)
-        // accept it as valid, which is correct: nothing wraps.
+        // .255 exactly. The remaining checks (size fits, same subnet) accept
+        // it as valid, which is correct: nothing wraps.
         let start = Ipv4Addr([10, 0, 0, 250]);
         let end = Ipv4Addr([10, 0, 0, 255]);
-        assert_eq!(validate_pool_geometry(start, end, 6), Ok(()));
-        // Claiming one more slot than the addressable range trips the size
-        // check — which is the correct error surface; there is no separate
-        // overflow variant to reach.
-        assert!(matches!(
-            validate_pool_geometry(start, end, 7),
-            Err(PoolGeometryError::SizeMismatch { .. })
-        ));
+        assert_eq!(validate_pool_geometry(start, end, 6), Ok(6));
+        // A larger table simply leaves its last slot unused.
+        assert_eq!(validate_pool_geometry(start, end, 7), Ok(6));
+        // Running one address further lands in the next /24, which the
+        // subnet check rejects — there is no separate overflow variant.
+        assert_eq!(
+            validate_pool_geometry(start, Ipv4Addr([10, 0, 1, 0]), 7),
+            Err(PoolGeometryError::CrossesSubnet)
+        );
     }
 }
@@ -35,7 +35,7 @@
 // and its UdpSocket usage are compiled away.  Allow dead-code on the types
 // that remain so clippy -D warnings does not fail on stub/host builds.
 #![cfg_attr(
-    not(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6"))),
+    not(all(feature = "embassy", feature = "_wifi-chip")),
     allow(dead_code)
 )]
 
@@ -44,6 +44,9 @@
 /// Standard DNS server port.
 const DNS_PORT: u16 = 53;
 
+/// `embassy-net` sockets the server task keeps open: one UDP socket.
+pub(crate) const SOCKETS: usize = 1;
+
 /// Maximum DNS message size (RFC 1035 §2.3.4 — UDP payload cap before EDNS).
 const DNS_MSG_MAX: usize = 512;
 
@@ -89,6 +92,12 @@ impl Ipv4Addr {
     }
 }
 
+impl From<core::net::Ipv4Addr> for Ipv4Addr {
+    fn from(addr: core::net::Ipv4Addr) -> Self {
+        Self(addr.octets())
+    }
+}
+
 impl core::fmt::Display for Ipv4Addr {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}.{}.{}.{}", self.0[0], self.0[1], self.0[2], self.0[3])
@@ -102,6 +111,8 @@ impl core::fmt::Display for Ipv4Addr {
 /// The defaults cover the standard captive-portal scenario: bind on port 53,
 /// answer every query with the SoftAP IP (`192.168.4.1`), and use a short TTL
 /// so phones do not cache the address long after the portal session ends.
+/// [`DnsCatchallConfig::for_subnet`] answers with the address of a moved AP
+/// instead.
 pub struct DnsCatchallConfig {
     /// UDP port the server binds on (`53` by default).
     pub bind_port: u16,
@@ -122,6 +133,16 @@ impl Default for DnsCatchallConfig {
     }
 }
 
+impl DnsCatchallConfig {
+    /// Answers every query with the AP address of `subnet`.
+    pub fn for_subnet(subnet: &juggler::wifi::ApSubnet) -> Self {
+        Self {
+            answer_ip: subnet.ip.into(),
+            ..Self::default()
+        }
+    }
+}
+
 // ── DnsError ──────────────────────────────────────────────────────────────────
 
 /// Errors produced by the DNS codec.
@@ -429,7 +450,7 @@ pub fn encode_response(
 ///
 /// Bind failure, send errors, and malformed packets are all logged at `warn`
 /// and do not abort the server loop.
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 pub async fn run(stack: embassy_net::Stack<'static>, config: DnsCatchallConfig) -> ! {
     use embassy_net::udp::{PacketMetadata, UdpSocket};
     use static_cell::StaticCell;
@@ -898,4 +919,15 @@ mod tests {
         assert_eq!(q.id, 0x1111);
         assert_eq!(q.qtype, 1);
     }
+
+    #[test]
+    fn config_for_subnet_answers_with_ap_ip() {
+        let subnet = juggler::wifi::ApSubnet {
+            ip: core::net::Ipv4Addr::new(10, 42, 0, 1),
+            ..juggler::wifi::ApSubnet::DEFAULT
+        };
+        let config = DnsCatchallConfig::for_subnet(&subnet);
+        assert_eq!(config.answer_ip, Ipv4Addr::new(10, 42, 0, 1));
+        assert_eq!(config.bind_port, DNS_PORT);
+    }
 }
@@ -15,7 +15,8 @@
 //!
 //! This crate is `#![no_std]` and host-testable end-to-end using a
 //! `MockFlash` test double in the test suite. The embassy tasks require
-//! `features = ["esp32c3", "embassy", "rt"]` (or `esp32c6`).
+//! `features = ["esp32c3", "embassy", "rt"]` (or `esp32c6`, `esp32s3`,
+//! `esp32`).
 //!
 //! # Real-hardware use
 //!
@@ -37,6 +38,19 @@ pub mod session;
 
 pub use store::{ProvisioningStore, StoreError};
 
+// ── Socket budget ──────────────────────────────────────────────────────────────
+
+/// `embassy-net` sockets the substrate tasks keep open on the SoftAP stack:
+/// the DHCP server, the DNS catch-all, and the HTTP portal.
+///
+/// Computed from each task's own declaration, so a new socket-owning task
+/// only has to declare its need.  [`ProvisioningBuilder::start`] rejects a
+/// [`SoftApHandle`](crate::wifi::SoftApHandle) built with fewer sockets.
+pub const SUBSTRATE_SOCKETS: usize = dhcp::SOCKETS + dns_catchall::SOCKETS + portal::SOCKETS;
+
+// The default SoftAP stack must keep room for the substrate.
+const _: () = assert!(SUBSTRATE_SOCKETS <= crate::wifi::AP_SOCKET_COUNT);
+
 // ── Public session API ─────────────────────────────────────────────────────────
 
 pub use session::{
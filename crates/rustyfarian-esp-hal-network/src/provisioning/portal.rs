@@ -60,39 +60,27 @@
 // function and its TcpSocket usage are compiled away.  Allow dead-code on the
 // types that remain so clippy -D warnings does not fail on stub/host builds.
 #![cfg_attr(
-    not(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6"))),
+    not(all(feature = "embassy", feature = "_wifi-chip")),
     allow(dead_code)
 )]
 
 // The bare-metal embassy builds have a global allocator (required by esp-radio);
 // bring `alloc` into scope for the validation-error string formatting path.
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 extern crate alloc;
 
-#[cfg(any(
-    test,
-    all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6"))
-))]
+#[cfg(any(test, all(feature = "embassy", feature = "_wifi-chip")))]
 use juggler::provisioning::html_json_escape::html_escape_to;
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 use juggler::provisioning::parse_form;
-#[cfg(any(
-    test,
-    all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6"))
-))]
+#[cfg(any(test, all(feature = "embassy", feature = "_wifi-chip")))]
 use juggler::provisioning::templates::WIFI_MQTT_PORTAL_HTML;
-#[cfg(any(
-    test,
-    all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6"))
-))]
+#[cfg(any(test, all(feature = "embassy", feature = "_wifi-chip")))]
 use juggler::provisioning::SchemaProfile;
 
-#[cfg(any(
-    test,
-    all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6"))
-))]
+#[cfg(any(test, all(feature = "embassy", feature = "_wifi-chip")))]
 use super::session::portal::PortalRenderConfig;
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 use super::session::{PortalStore, ProvisioningEvent, ProvisioningOutcome, SharedState};
 
 // ── HTTP wire constants ───────────────────────────────────────────────────────
@@ -111,6 +99,10 @@ const _: () = assert!(DEFAULT_REQUEST_SIZE_CAP == 2048);
 /// Default TCP port the portal listens on.
 const DEFAULT_PORT: u16 = 80;
 
+/// `embassy-net` sockets the portal task keeps open: one TCP socket,
+/// reused for each connection.
+pub(crate) const SOCKETS: usize = 1;
+
 /// Default socket receive buffer size.
 const DEFAULT_RX_BUF: usize = 1024;
 
@@ -708,10 +700,7 @@ fn extract_form_field<'a>(body: &'a [u8], key: &[u8]) -> Option<&'a [u8]> {
 /// other credential field.  The fields present correspond exactly to the
 /// `{{PLACEHOLDER}}` tokens in the portal HTML template that the current
 /// render pass substitutes.
-#[cfg(any(
-    test,
-    all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6"))
-))]
+#[cfg(any(test, all(feature = "embassy", feature = "_wifi-chip")))]
 struct Prefill {
     wifi_ssid: heapless::String<32>,
     mqtt_uri: heapless::String<74>, // mqtt://host:port where host ≤ 64, port ≤ 5
@@ -721,10 +710,7 @@ struct Prefill {
     dev_name: heapless::String<24>,
 }
 
-#[cfg(any(
-    test,
-    all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6"))
-))]
+#[cfg(any(test, all(feature = "embassy", feature = "_wifi-chip")))]
 impl Prefill {
     fn empty() -> Self {
         Self {
@@ -740,7 +726,7 @@ impl Prefill {
 
 /// Loads non-secret pre-fill values from the store, falling back to empty on
 /// any error, when unprovisioned, or when the stored profile doesn't match.
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 fn load_prefill(store: &dyn PortalStore, profile: SchemaProfile) -> Prefill {
     match store.load() {
         Ok(Some(cfg)) if cfg.profile() == profile => {
@@ -817,10 +803,7 @@ fn load_prefill(store: &dyn PortalStore, profile: SchemaProfile) -> Prefill {
 /// selection — `unreachable!()` on the LoRaWAN arm documents the runtime
 /// invariant `validate_profile` upholds, while the absence of a wildcard arm
 /// is the compile-time guard.
-#[cfg(any(
-    test,
-    all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6"))
-))]
+#[cfg(any(test, all(feature = "embassy", feature = "_wifi-chip")))]
 fn template_for(profile: SchemaProfile) -> &'static str {
     match profile {
         SchemaProfile::WifiMqttDevice => WIFI_MQTT_PORTAL_HTML,
@@ -846,10 +829,7 @@ fn template_for(profile: SchemaProfile) -> &'static str {
 /// including when an HTML-escaped substituted value would overflow the buffer
 /// (the `write_html_escaped` helper propagates the overflow via a captured
 /// `overflowed` flag and returns `Err(())`).
-#[cfg(any(
-    test,
-    all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6"))
-))]
+#[cfg(any(test, all(feature = "embassy", feature = "_wifi-chip")))]
 #[allow(clippy::result_unit_err)]
 fn render_portal_template(
     config: &PortalRenderConfig,
@@ -994,7 +974,7 @@ fn render_portal_template(
 ///
 /// Baked from `PortalConfig` at `start()` time; the portal task holds this
 /// by value.
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 #[allow(dead_code)]
 struct PortalState<'r> {
     ap_ip_str: heapless::String<16>, // "192.168.4.1" max
@@ -1017,7 +997,7 @@ struct PortalState<'r> {
 /// | POST   | /save                 | 200/400/403/500 | Credential commit |
 /// | POST   | /factory-reset        | 200/403 | Factory-reset signal          |
 /// | POST   | anything else         | 404    | Not found (POST-only — unknown GETs hit the catch-all row above) |
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 #[allow(clippy::result_unit_err, clippy::too_many_arguments)]
 pub(crate) fn dispatch_request(
     req: &ParsedRequest,
@@ -1413,7 +1393,7 @@ pub(crate) fn dispatch_request(
 /// Called from the non-generic `http_task` embassy task.  The store is
 /// accessed via the [`PortalStore`] trait object so no generic type parameter
 /// is needed.
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 pub(crate) async fn run_portal_dyn(
     stack: embassy_net::Stack<'static>,
     shared: &'static SharedState,
@@ -1605,7 +1585,7 @@ pub(crate) async fn run_portal_dyn(
 
 /// Read from `socket` until the request headers are complete (`\r\n\r\n`)
 /// or the buffer fills.
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 async fn read_request(
     socket: &mut embassy_net::tcp::TcpSocket<'_>,
     buf: &mut [u8],
@@ -1635,7 +1615,7 @@ async fn read_request(
 }
 
 /// Read and discard up to `remaining` bytes from `socket`, honouring `deadline`.
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 async fn drain_body_with_deadline(
     socket: &mut embassy_net::tcp::TcpSocket<'_>,
     mut remaining: usize,
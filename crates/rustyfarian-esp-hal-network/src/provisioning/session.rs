@@ -8,8 +8,8 @@
 //! # no_std / embassy
 //!
 //! The public types compile unconditionally.  The `start` body and the embassy
-//! tasks are gated on `#[cfg(all(feature = "embassy", any(feature = "esp32c3",
-//! feature = "esp32c6")))]`.
+//! tasks are gated on `#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]`,
+//! where `_wifi-chip` is the internal feature every chip feature enables.
 //!
 //! # The library never reboots or erases
 //!
@@ -22,7 +22,7 @@ use heapless::String as HS;
 
 use juggler::provisioning::{ProvisioningConfig, SchemaProfile};
 
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 use super::store::ProvisioningStore;
 
 // ── Public types ──────────────────────────────────────────────────────────────
@@ -62,26 +62,19 @@ pub struct PortalConfig<'a> {
 pub enum ProvisioningEvent {
     /// A station (phone) associated with the provisioning AP.
     ///
-    /// `mac` is `Option<[u8; 6]>` rather than `[u8; 6]` because v1 does not
-    /// yet extract the MAC from the `esp-radio 0.18`
-    /// `AccessPointStationEventInfo` payload (the field name has not been
-    /// confirmed and the placeholder zero-bytes would otherwise look like a
-    /// real address).  The event itself fires reliably on every association;
-    /// `mac` will become `Some(_)` in a follow-up once the upstream field is
-    /// verified.
+    /// `mac` is always `Some(_)` now that the `wifi_task` reads the
+    /// `esp-radio` association payload; it stays an `Option` so callers
+    /// written against v1, which could not extract it, keep compiling.
     ClientConnected {
-        /// Client hardware (MAC) address, or `None` if not yet extractable in
-        /// the current `esp-radio` surface (v1 behaviour).
+        /// Client hardware (MAC) address.
         mac: Option<[u8; 6]>,
     },
     /// A station disassociated from the provisioning AP.
     ///
-    /// **Reserved for a follow-up release.**  v1 does not subscribe to the
-    /// disassociation event from `esp-radio 0.18`, so this variant is part
-    /// of the public surface for forward compatibility but is never
-    /// constructed by the current `wifi_task`.
+    /// [`ProvisioningSession::stations`] lists the stations still connected.
     ClientDisconnected {
-        /// Client hardware (MAC) address, or `None` if not yet extractable.
+        /// Client hardware (MAC) address; always `Some(_)`, see
+        /// [`ClientConnected`](Self::ClientConnected).
         mac: Option<[u8; 6]>,
     },
     /// A `POST /save` passed nonce and form validation.
@@ -153,6 +146,16 @@ pub enum ProvisioningError {
         /// The profile that was requested but is not implemented in v1.
         profile: SchemaProfile,
     },
+    /// The SoftAP stack has fewer sockets than the substrate tasks open.
+    ///
+    /// Build it with at least [`SUBSTRATE_SOCKETS`](super::SUBSTRATE_SOCKETS)
+    /// via `HalApConfig::with_sockets`, or keep the default.
+    SocketBudget {
+        /// Sockets the substrate tasks need.
+        needed: usize,
+        /// Sockets the stack was built with.
+        available: usize,
+    },
 }
 
 impl core::fmt::Display for ProvisioningError {
@@ -176,6 +179,13 @@ impl core::fmt::Display for ProvisioningError {
                     profile
                 )
             }
+            ProvisioningError::SocketBudget { needed, available } => {
+                write!(
+                    f,
+                    "SoftAP stack has {} sockets, provisioning needs {}",
+                    available, needed
+                )
+            }
         }
     }
 }
@@ -193,10 +203,7 @@ impl core::fmt::Display for ProvisioningError {
 /// is consumed, before the `StaticCell` is initialised, and before the nonce
 /// is generated — so the rejection is cheap and leaves the system state
 /// unchanged.
-#[cfg(any(
-    test,
-    all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6"))
-))]
+#[cfg(any(test, all(feature = "embassy", feature = "_wifi-chip")))]
 pub(crate) fn validate_profile(profile: SchemaProfile) -> Result<(), ProvisioningError> {
     match profile {
         SchemaProfile::WifiMqttDevice => Ok(()),
@@ -204,6 +211,23 @@ pub(crate) fn validate_profile(profile: SchemaProfile) -> Result<(), Provisionin
     }
 }
 
+/// Validates that a SoftAP stack with `available` sockets can host the
+/// substrate tasks.
+///
+/// Returns [`ProvisioningError::SocketBudget`] when `available` is below
+/// [`SUBSTRATE_SOCKETS`](super::SUBSTRATE_SOCKETS), plus the DNS resolver
+/// socket `embassy-net` reserves when the `mqtt` feature is on.  Checked in
+/// [`ProvisioningBuilder::start`] right after the profile, so a short
+/// budget fails at start-up rather than as a socket error in a task.
+#[cfg(any(test, all(feature = "embassy", feature = "_wifi-chip")))]
+pub(crate) fn validate_socket_budget(available: usize) -> Result<(), ProvisioningError> {
+    let needed = super::SUBSTRATE_SOCKETS + crate::wifi::RESOLVER_SOCKETS;
+    if available < needed {
+        return Err(ProvisioningError::SocketBudget { needed, available });
+    }
+    Ok(())
+}
+
 // ── Internal shared state ─────────────────────────────────────────────────────
 
 /// State shared between the HTTP portal task and the `ProvisioningSession`
@@ -214,7 +238,7 @@ pub(crate) fn validate_profile(profile: SchemaProfile) -> Result<(), Provisionin
 // Fields are accessed only in embassy+chip builds; the cfg_attr suppresses
 // dead-code warnings in host (no-default-features) builds.
 #[cfg_attr(
-    not(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6"))),
+    not(all(feature = "embassy", feature = "_wifi-chip")),
     allow(dead_code)
 )]
 pub(crate) struct SharedState {
@@ -239,6 +263,8 @@ pub(crate) struct SharedState {
     /// Optional lifecycle-event callback.  `fn` pointer (not `Arc<dyn Fn>`)
     /// because `no_std` forbids heap-allocated closures.
     pub on_event: Option<fn(ProvisioningEvent)>,
+    /// Stations on the AP, fed by the Wi-Fi and DHCP tasks.
+    pub stations: crate::wifi::ApStationMonitor,
 }
 
 // ── ProvisioningSession ───────────────────────────────────────────────────────
@@ -270,6 +296,12 @@ impl ProvisioningSession {
         self.ap_ip
     }
 
+    /// The stations currently associated with the provisioning AP, with the
+    /// addresses the DHCP server leased them.
+    pub fn stations(&self) -> juggler::wifi::ApStations {
+        self.shared.stations.stations()
+    }
+
     /// Waits for the provisioning session to reach a terminal outcome.
     ///
     /// Returns once a [`ProvisioningOutcome`] is signalled by the HTTP portal
@@ -334,7 +366,7 @@ impl ProvisioningSession {
 /// ```
 // `config` is only read inside the embassy+chip-gated `start` method.
 #[cfg_attr(
-    not(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6"))),
+    not(all(feature = "embassy", feature = "_wifi-chip")),
     allow(dead_code)
 )]
 pub struct ProvisioningBuilder<'a> {
@@ -377,15 +409,16 @@ impl<'a> ProvisioningBuilder<'a> {
     ///
     /// # Errors
     ///
-    /// Returns `Err` if the AP password is too short, if a task spawn fails,
-    /// or if `start` was already called this boot.
+    /// Returns `Err` if the AP password is too short, if the SoftAP stack has
+    /// fewer than [`SUBSTRATE_SOCKETS`](super::SUBSTRATE_SOCKETS) sockets, if
+    /// a task spawn fails, or if `start` was already called this boot.
     ///
     /// # Panics
     ///
     /// Panics if `start` is called a second time in the same boot and the
     /// `StaticCell::try_init` path is unavailable.  Prefer checking
     /// [`ProvisioningError::AlreadyStarted`] at runtime.
-    #[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+    #[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
     pub fn start<F>(
         self,
         spawner: embassy_executor::Spawner,
@@ -404,6 +437,7 @@ impl<'a> ProvisioningBuilder<'a> {
         // that a caller selecting an unsupported profile gets a clean error
         // without any side effects.
         validate_profile(self.config.profile)?;
+        validate_socket_budget(ap.sockets)?;
 
         // ── Step 1: validate AP password ───────────────────────────────────
         if let Some(pw) = self.config.ap_password {
@@ -420,6 +454,14 @@ impl<'a> ProvisioningBuilder<'a> {
         let nonce = generate_nonce(rng);
 
         // ── Step 3: initialise SharedState in a StaticCell ─────────────────
+        let crate::wifi::SoftApHandle {
+            controller,
+            stack,
+            runner,
+            subnet,
+            stations,
+            sockets: _,
+        } = ap;
         static SHARED: StaticCell<SharedState> = StaticCell::new();
         let shared: &'static SharedState = SHARED
             .try_init(SharedState {
@@ -430,8 +472,9 @@ impl<'a> ProvisioningBuilder<'a> {
                 ),
                 outcome: embassy_sync::signal::Signal::new(),
                 nonce,
-                ap_ip: AP_IP,
+                ap_ip: subnet.ip.octets(),
                 on_event: self.on_event,
+                stations: crate::wifi::ApStationMonitor::new(stations),
             })
             .ok_or(ProvisioningError::AlreadyStarted)?;
 
@@ -465,12 +508,6 @@ impl<'a> ProvisioningBuilder<'a> {
         };
 
         // ── Step 6: spawn tasks ─────────────────────────────────────────────
-        let crate::wifi::SoftApHandle {
-            controller,
-            stack,
-            runner,
-        } = ap;
-
         // `Spawner::spawn()` in embassy-executor 0.10 takes a `SpawnToken` and
         // returns `()`.  It panics internally if the task pool is exhausted
         // (a build-time misconfiguration rather than a runtime condition).
@@ -490,14 +527,17 @@ impl<'a> ProvisioningBuilder<'a> {
         // condition `start()` can recover from.
         spawner.spawn(tasks::net_task(runner).unwrap());
         spawner.spawn(tasks::wifi_task(controller, shared).unwrap());
-        spawner.spawn(tasks::dhcp_task(stack).unwrap());
-        spawner.spawn(tasks::dns_task(stack).unwrap());
+        let dhcp_config = super::dhcp::DhcpServerConfig::for_subnet(&subnet)
+            .with_station_monitor(&shared.stations);
+        let dns_config = super::dns_catchall::DnsCatchallConfig::for_subnet(&subnet);
+        spawner.spawn(tasks::dhcp_task(stack, dhcp_config).unwrap());
+        spawner.spawn(tasks::dns_task(stack, dns_config).unwrap());
         spawner.spawn(tasks::http_task(stack, shared, store_ref, portal_config).unwrap());
 
         // ── Step 7: return session ──────────────────────────────────────────
         Ok(ProvisioningSession {
             shared,
-            ap_ip: AP_IP,
+            ap_ip: subnet.ip.octets(),
         })
     }
 }
@@ -513,10 +553,7 @@ impl<'a> ProvisioningBuilder<'a> {
 /// An open AP lets any device in radio range reach the portal.  The warning
 /// is emitted unconditionally every time `start` is called with no password,
 /// so the integrator cannot accidentally silence it by mistake.
-#[cfg(any(
-    test,
-    all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6"))
-))]
+#[cfg(any(test, all(feature = "embassy", feature = "_wifi-chip")))]
 pub(crate) fn warn_if_open_ap(ap_password: Option<&str>) {
     if ap_password.is_none() {
         log::warn!(
@@ -526,16 +563,10 @@ pub(crate) fn warn_if_open_ap(ap_password: Option<&str>) {
     }
 }
 
-/// The static AP IP address — `192.168.4.1` in network byte order.
-///
-/// Matches the address configured by `WiFiManager::init_softap_async`.
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
-const AP_IP: [u8; 4] = [192, 168, 4, 1];
-
 /// Generates an 8-hex-character session nonce from the hardware RNG.
 ///
 /// Reads 4 bytes from `rng` and formats them as 8 lowercase hex characters.
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 fn generate_nonce(rng: esp_hal::rng::Rng) -> HS<16> {
     let value = rng.random();
     let mut nonce = HS::<16>::new();
@@ -570,7 +601,7 @@ fn generate_nonce(rng: esp_hal::rng::Rng) -> HS<16> {
 ///
 /// The leaked allocation is never freed — the provisioning session runs for
 /// the lifetime of the device.
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 fn store_cell_init<F>(store: ProvisioningStore<F>) -> &'static dyn PortalStore
 where
     F: embedded_storage::nor_flash::NorFlash + Send + Sync + 'static,
@@ -602,10 +633,7 @@ where
 /// renderer prefers `Prefill.dev_name` when non-empty, falling back to
 /// `device_name` here so a fresh device still surfaces the integrator's
 /// intended name in the portal header.
-#[cfg(any(
-    test,
-    all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6"))
-))]
+#[cfg(any(test, all(feature = "embassy", feature = "_wifi-chip")))]
 pub(crate) mod portal {
     use heapless::String as HS;
     use juggler::provisioning::SchemaProfile;
@@ -630,7 +658,7 @@ pub(crate) mod portal {
 /// Used to pass the store into the HTTP task without a generic type parameter.
 /// Embassy tasks cannot be generic, so the concrete `ProvisioningStore<F>` is
 /// hidden behind this trait and leaked as a `Box<dyn PortalStore>`.
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 pub(crate) trait PortalStore: Send + Sync {
     /// Save `config` to flash.
     fn save(
@@ -644,7 +672,7 @@ pub(crate) trait PortalStore: Send + Sync {
 }
 
 /// Blanket impl of [`PortalStore`] for a `Mutex<RefCell<ProvisioningStore<F>>>`.
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 impl<F> PortalStore
     for embassy_sync::blocking_mutex::CriticalSectionMutex<
         core::cell::RefCell<ProvisioningStore<F>>,
@@ -666,10 +694,13 @@ where
     }
 }
 
-#[cfg(all(feature = "embassy", any(feature = "esp32c3", feature = "esp32c6")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 pub(crate) mod tasks {
     use embassy_net::{Runner, Stack};
     use esp_radio::wifi::{Interface, WifiController};
+    use juggler::wifi::ApStationEvent;
+
+    use crate::wifi::WiFiManager;
 
     use super::super::dhcp::{run as dhcp_run, DhcpServerConfig};
     use super::super::dns_catchall::{run as dns_run, DnsCatchallConfig};
@@ -688,39 +719,38 @@ pub(crate) mod tasks {
     /// session callback.
     ///
     /// In SoftAP mode the radio is already started when `init_softap_async`
-    /// returns.  This task loops on
-    /// `wait_for_access_point_connected_event_async` and fires the
-    /// `on_event(ClientConnected)` callback.
-    ///
-    /// Note: `WifiController` in `esp-radio 0.18` does not expose a
-    /// `wait_for_access_point_disconnected_event_async` method; only the
-    /// connect event is observable via this API.  `ClientDisconnected` events
-    /// are therefore not fired in v1.
+    /// returns.  This task loops on [`WiFiManager::next_ap_station_event`],
+    /// which keeps `shared.stations` current, and fires the
+    /// `on_event(ClientConnected / ClientDisconnected)` callback.
     #[embassy_executor::task]
     pub(crate) async fn wifi_task(
         controller: WifiController<'static>,
         shared: &'static SharedState,
     ) {
         loop {
-            // Wait for a station to connect.
-            match controller
-                .wait_for_access_point_connected_event_async()
-                .await
-            {
-                Ok(_info) => {
-                    // `AccessPointStationEventInfo` — the MAC field name has
-                    // not been verified for esp-radio 0.18, so the event
-                    // carries `None` rather than a synthetic zero-bytes
-                    // placeholder that callers might mistake for a real
-                    // address.  When the field name is confirmed in a
-                    // follow-up, pass `Some(_info.<field>)` here.
-                    log::info!("AP: station connected");
+            match WiFiManager::next_ap_station_event(&controller, &shared.stations).await {
+                Ok(event) => {
+                    let event = match event {
+                        ApStationEvent::Connected { mac } => {
+                            log::info!("AP: station {:02x?} connected", mac);
+                            ProvisioningEvent::ClientConnected { mac: Some(mac) }
+                        }
+                        ApStationEvent::Disconnected { mac, .. } => {
+                            log::info!("AP: station {:02x?} disconnected", mac);
+                            ProvisioningEvent::ClientDisconnected { mac: Some(mac) }
+                        }
+                        // Leases are reported by the DHCP task; rejections
+                        // are logged by `next_ap_station_event`.
+                        ApStationEvent::IpAssigned { .. } | ApStationEvent::Rejected { .. } => {
+                            continue;
+                        }
+                    };
                     if let Some(cb) = shared.on_event {
-                        (cb)(ProvisioningEvent::ClientConnected { mac: None });
+                        (cb)(event);
                     }
                 }
                 Err(e) => {
-                    log::warn!("AP: wait_for_access_point_connected error: {:?}", e);
+                    log::warn!("AP: station event error: {}", e);
                     // Yield before retrying so we do not spin on a persistent error.
                     embassy_time::Timer::after(embassy_time::Duration::from_millis(100)).await;
                 }
@@ -730,14 +760,14 @@ pub(crate) mod tasks {
 
     /// Runs the DHCP server on the AP stack.
     #[embassy_executor::task]
-    pub(crate) async fn dhcp_task(stack: Stack<'static>) -> ! {
-        dhcp_run(stack, DhcpServerConfig::default()).await
+    pub(crate) async fn dhcp_task(stack: Stack<'static>, config: DhcpServerConfig) -> ! {
+        dhcp_run(stack, config).await
     }
 
     /// Runs the DNS catch-all server on the AP stack.
     #[embassy_executor::task]
-    pub(crate) async fn dns_task(stack: Stack<'static>) -> ! {
-        dns_run(stack, DnsCatchallConfig::default()).await
+    pub(crate) async fn dns_task(stack: Stack<'static>, config: DnsCatchallConfig) -> ! {
+        dns_run(stack, config).await
     }
 
     /// Runs the captive-portal HTTP server.
@@ -882,6 +912,39 @@ mod tests {
         );
     }
 
+    // ── validate_socket_budget tests ──────────────────────────────────────────
+
+    /// The default SoftAP budget, and anything down to exactly the substrate's
+    /// need plus the DNS resolver's socket, is accepted.
+    #[test]
+    fn socket_budget_accepts_default_and_exact() {
+        assert_eq!(validate_socket_budget(crate::wifi::AP_SOCKET_COUNT), Ok(()));
+        assert_eq!(
+            validate_socket_budget(
+                crate::provisioning::SUBSTRATE_SOCKETS + crate::wifi::RESOLVER_SOCKETS
+            ),
+            Ok(())
+        );
+    }
+
+    /// A stack one socket short is rejected with both counts in the error.
+    #[test]
+    fn socket_budget_rejects_short_stack() {
+        assert_eq!(
+            crate::provisioning::SUBSTRATE_SOCKETS,
+            3,
+            "DHCP + DNS + HTTP each own one socket"
+        );
+        let needed = crate::provisioning::SUBSTRATE_SOCKETS + crate::wifi::RESOLVER_SOCKETS;
+        assert_eq!(
+            validate_socket_budget(needed - 1),
+            Err(ProvisioningError::SocketBudget {
+                needed,
+                available: needed - 1
+            })
+        );
+    }
+
     /// `ProfileNotSupported` carries only a `SchemaProfile` discriminant — no
     /// credential bytes.
     ///
@@ -0,0 +1,965 @@
+//! IPv6 link-local addressing and stateless autoconfiguration (RFC 4862) for
+//! the station interface.
+//!
+//! ## What it covers
+//!
+//! `embassy-net` 0.8 only knows a static IPv6 configuration, so this module
+//! does the autoconfiguration itself.  [`run`] gives the stack a link-local
+//! address derived from the MAC (modified EUI-64, RFC 4291 appendix A),
+//! sends Router Solicitations, and turns the first usable Router
+//! Advertisement into a global address, default router, and DNS servers.
+//! The codec — [`parse_router_advert`], [`encode_router_solicit`], and
+//! [`slaac_config`] — is hand-rolled in the style of [`mdns`](super::mdns)
+//! and host-tested; only [`run`] touches `embassy-net`.
+//!
+//! ## Protocol coverage
+//!
+//! - Router Advertisements are validated per RFC 4861 §6.1.2: hop limit 255,
+//!   link-local source, code 0, correct ICMPv6 checksum, and no zero-length
+//!   options.
+//! - The first Prefix Information option with the autonomous flag and a
+//!   /64 prefix forms the address; the RA's source becomes the default
+//!   router while its router lifetime is non-zero.
+//! - Recursive DNS Server options (RFC 8106) supply up to
+//!   [`RA_MAX_DNS_SERVERS`] resolvers — on NAT64 segments this is the DNS64
+//!   server.
+//! - Prefix valid lifetime and router lifetime are honoured; an expired
+//!   prefix drops the stack back to its link-local address.
+//! - [`run`] starts over after a link drop, so roaming to another network
+//!   renumbers the interface.
+//!
+//! ## Not covered
+//!
+//! Duplicate address detection, temporary (privacy) addresses (RFC 8981),
+//! DHCPv6, and more than one prefix.  `embassy-net` holds a single IPv6
+//! address, so the global address replaces the link-local one once an RA
+//! arrives.  The interface identifier embeds the MAC address.
+//!
+//! ## Readiness
+//!
+//! With the `ipv6` feature, `Stack::wait_config_up` returns as soon as
+//! *either* protocol is configured, which for IPv6 is the instant [`run`]
+//! sets the link-local address.  Wait on
+//! `AsyncWifiHandle::wait_for_ip` for IPv4 and
+//! `AsyncWifiHandle::wait_for_ipv6` for a global IPv6 address instead.
+
+// When building without the embassy + chip + ipv6 features the async `run`
+// function and its RawSocket usage are compiled away.  Allow dead-code on the
+// items that remain so clippy -D warnings does not fail on stub/host builds.
+#![cfg_attr(
+    not(all(feature = "embassy", feature = "ipv6", feature = "_wifi-chip")),
+    allow(dead_code)
+)]
+
+use core::net::Ipv6Addr;
+
+// ── Constants ─────────────────────────────────────────────────────────────────
+
+/// `embassy-net` sockets [`run`] keeps open: one raw ICMPv6 socket.
+pub const SOCKETS: usize = 1;
+
+/// Link-local all-routers multicast address, the destination of Router
+/// Solicitations.
+pub const ALL_ROUTERS: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 2);
+
+/// Maximum DNS servers taken from a Router Advertisement — the capacity of
+/// `embassy_net::StaticConfigV6::dns_servers`.
+pub const RA_MAX_DNS_SERVERS: usize = 3;
+
+/// Prefix length SLAAC needs on Ethernet-like links (RFC 4862 §5.5.3).
+pub const SLAAC_PREFIX_LEN: u8 = 64;
+
+/// Floor an advertisement may cut an existing address's valid lifetime to
+/// (two hours, RFC 4862 §5.5.3(e)).
+pub const SLAAC_MIN_VALID_LIFETIME_SECS: u32 = 2 * 60 * 60;
+
+/// Fixed IPv6 header length.
+const IPV6_HEADER_LEN: usize = 40;
+
+/// IPv6 next-header value for ICMPv6.
+const NEXT_HEADER_ICMPV6: u8 = 58;
+
+/// Hop limit every Neighbor Discovery message must carry (RFC 4861 §6.1).
+const ND_HOP_LIMIT: u8 = 255;
+
+const ICMPV6_ROUTER_SOLICIT: u8 = 133;
+const ICMPV6_ROUTER_ADVERT: u8 = 134;
+
+/// Router Advertisement header length (type through retrans timer).
+const RA_HEADER_LEN: usize = 16;
+
+/// Router Solicitation header length (type through reserved).
+const RS_HEADER_LEN: usize = 8;
+
+const OPT_SOURCE_LLADDR: u8 = 1;
+const OPT_PREFIX_INFO: u8 = 3;
+const OPT_MTU: u8 = 5;
+const OPT_RDNSS: u8 = 25;
+
+/// Autonomous address-configuration flag of a Prefix Information option.
+const PREFIX_FLAG_AUTONOMOUS: u8 = 0x40;
+
+/// Length of an encoded Router Solicitation with a source link-layer
+/// address option: IPv6 header + RS header + 8-byte option.
+pub const ROUTER_SOLICIT_LEN: usize = IPV6_HEADER_LEN + RS_HEADER_LEN + 8;
+
+// ── Ipv6Error ─────────────────────────────────────────────────────────────────
+
+/// Errors produced by the Neighbor Discovery codec.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Ipv6Error {
+    /// Packet ends before a header, option, or declared payload length.
+    Truncated,
+    /// Packet is not an ICMPv6 Router Advertisement.
+    NotRouterAdvert,
+    /// The Router Advertisement fails RFC 4861 §6.1.2 validation: hop limit
+    /// other than 255, non-link-local source, non-zero code, or a
+    /// zero-length option.
+    Invalid,
+    /// The ICMPv6 checksum does not match.
+    BadChecksum,
+    /// The output buffer is too small.
+    BufferTooSmall,
+}
+
+impl core::fmt::Display for Ipv6Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "packet truncated"),
+            Self::NotRouterAdvert => write!(f, "not a router advertisement"),
+            Self::Invalid => write!(f, "router advertisement failed validation"),
+            Self::BadChecksum => write!(f, "bad ICMPv6 checksum"),
+            Self::BufferTooSmall => write!(f, "output buffer too small"),
+        }
+    }
+}
+
+// ── Addresses ─────────────────────────────────────────────────────────────────
+
+/// Modified EUI-64 interface identifier for `mac` (RFC 4291 appendix A):
+/// `ff:fe` inserted in the middle and the universal/local bit flipped.
+pub fn interface_id(mac: [u8; 6]) -> [u8; 8] {
+    [
+        mac[0] ^ 0x02,
+        mac[1],
+        mac[2],
+        0xff,
+        0xfe,
+        mac[3],
+        mac[4],
+        mac[5],
+    ]
+}
+
+/// `fe80::/64` link-local address for `mac`.
+pub fn link_local_address(mac: [u8; 6]) -> Ipv6Addr {
+    with_prefix(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0), mac)
+}
+
+/// Returns `true` for a unicast link-local address (`fe80::/10`).
+///
+/// Same as [`Ipv6Addr::is_unicast_link_local`], which code outside this
+/// module calls directly since `mqtt` builds without the `wifi` feature.
+pub fn is_link_local(addr: &Ipv6Addr) -> bool {
+    addr.is_unicast_link_local()
+}
+
+/// Combines the upper 64 bits of `prefix` with the interface ID of `mac`.
+fn with_prefix(prefix: Ipv6Addr, mac: [u8; 6]) -> Ipv6Addr {
+    let mut octets = prefix.octets();
+    octets[8..].copy_from_slice(&interface_id(mac));
+    Ipv6Addr::from(octets)
+}
+
+// ── Router Advertisement ──────────────────────────────────────────────────────
+
+/// A Prefix Information option of a Router Advertisement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrefixInfo {
+    /// Advertised prefix; bits past `len` are not meaningful.
+    pub prefix: Ipv6Addr,
+    /// Prefix length in bits.
+    pub len: u8,
+    /// Autonomous flag: the prefix may be used for SLAAC.
+    pub autonomous: bool,
+    /// Valid lifetime in seconds; `u32::MAX` is infinite.
+    pub valid_lifetime_secs: u32,
+    /// Preferred lifetime in seconds; `u32::MAX` is infinite.
+    pub preferred_lifetime_secs: u32,
+}
+
+/// The parts of a validated Router Advertisement that autoconfiguration
+/// uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouterAdvert {
+    /// Link-local address of the advertising router.
+    pub router: Ipv6Addr,
+    /// Seconds the router may be used as default router; `0` withdraws it.
+    pub router_lifetime_secs: u16,
+    /// First autonomous /64 prefix, or else the first prefix of any kind.
+    pub prefix: Option<PrefixInfo>,
+    /// Link MTU, if advertised.
+    pub mtu: Option<u32>,
+    /// Recursive DNS servers, in advertised order.
+    pub dns_servers: heapless::Vec<Ipv6Addr, RA_MAX_DNS_SERVERS>,
+}
+
+/// Parses and validates a Router Advertisement from a full IPv6 packet as
+/// delivered by a raw ICMPv6 socket.
+///
+/// Returns [`Ipv6Error::NotRouterAdvert`] for any other ICMPv6 message, so
+/// the caller can skip Neighbor Solicitations and echo traffic cheaply.
+pub fn parse_router_advert(packet: &[u8]) -> Result<RouterAdvert, Ipv6Error> {
+    if packet.len() < IPV6_HEADER_LEN {
+        return Err(Ipv6Error::Truncated);
+    }
+    if packet[0] >> 4 != 6 || packet[6] != NEXT_HEADER_ICMPV6 {
+        return Err(Ipv6Error::NotRouterAdvert);
+    }
+    let payload_len = usize::from(u16::from_be_bytes([packet[4], packet[5]]));
+    let icmp = packet
+        .get(IPV6_HEADER_LEN..IPV6_HEADER_LEN + payload_len)
+        .ok_or(Ipv6Error::Truncated)?;
+    if icmp.first() != Some(&ICMPV6_ROUTER_ADVERT) {
+        return Err(Ipv6Error::NotRouterAdvert);
+    }
+    if icmp.len() < RA_HEADER_LEN {
+        return Err(Ipv6Error::Truncated);
+    }
+
+    let src = addr_at(packet, 8);
+    let dst = addr_at(packet, 24);
+    if packet[7] != ND_HOP_LIMIT || !is_link_local(&src) || icmp[1] != 0 {
+        return Err(Ipv6Error::Invalid);
+    }
+    if icmpv6_checksum(&src, &dst, icmp) != 0 {
+        return Err(Ipv6Error::BadChecksum);
+    }
+
+    let mut ra = RouterAdvert {
+        router: src,
+        router_lifetime_secs: u16::from_be_bytes([icmp[6], icmp[7]]),
+        prefix: None,
+        mtu: None,
+        dns_servers: heapless::Vec::new(),
+    };
+
+    let mut options = &icmp[RA_HEADER_LEN..];
+    while !options.is_empty() {
+        if options.len() < 2 {
+            return Err(Ipv6Error::Truncated);
+        }
+        let len = usize::from(options[1]) * 8;
+        if len == 0 {
+            return Err(Ipv6Error::Invalid);
+        }
+        let option = options.get(..len).ok_or(Ipv6Error::Truncated)?;
+        match option[0] {
+            OPT_PREFIX_INFO if len == 32 => {
+                let info = PrefixInfo {
+                    prefix: addr_at(option, 16),
+                    len: option[2],
+                    autonomous: option[3] & PREFIX_FLAG_AUTONOMOUS != 0,
+                    valid_lifetime_secs: u32_at(option, 4),
+                    preferred_lifetime_secs: u32_at(option, 8),
+                };
+                let usable = |p: &PrefixInfo| p.autonomous && p.len == SLAAC_PREFIX_LEN;
+                if ra.prefix.is_none_or(|p| !usable(&p) && usable(&info)) {
+                    ra.prefix = Some(info);
+                }
+            }
+            OPT_MTU if len == 8 => ra.mtu = Some(u32_at(option, 4)),
+            // A zero lifetime withdraws the servers (RFC 8106 §5.1).
+            OPT_RDNSS if len >= 24 && u32_at(option, 4) != 0 => {
+                for addr in option[8..].chunks_exact(16) {
+                    let _ = ra.dns_servers.push(addr_at(addr, 0));
+                }
+            }
+            // Source link-layer address, malformed known options, withdrawn
+            // DNS servers, and anything unrecognised are skipped
+            // (RFC 4861 §4.6).
+            _ => {}
+        }
+        options = &options[len..];
+    }
+    Ok(ra)
+}
+
+/// Address configuration derived from a Router Advertisement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlaacConfig {
+    /// Global (or unique-local) address: advertised prefix + interface ID.
+    pub address: Ipv6Addr,
+    /// Prefix length of `address`; always [`SLAAC_PREFIX_LEN`].
+    pub prefix_len: u8,
+    /// Default router, or `None` if the RA withdrew it.
+    pub gateway: Option<Ipv6Addr>,
+    /// DNS servers from the RA's RDNSS option.
+    pub dns_servers: heapless::Vec<Ipv6Addr, RA_MAX_DNS_SERVERS>,
+    /// Seconds the address stays valid; `u32::MAX` is infinite.
+    pub valid_lifetime_secs: u32,
+}
+
+/// Forms the SLAAC address for `mac` from `ra`, or returns `None` if it has
+/// no usable prefix.
+///
+/// A prefix is usable when it has the autonomous flag, is /64, is not
+/// link-local, has a non-zero valid lifetime, and its preferred lifetime
+/// does not exceed the valid one (RFC 4862 §5.5.3).
+pub fn slaac_config(ra: &RouterAdvert, mac: [u8; 6]) -> Option<SlaacConfig> {
+    let prefix = ra.prefix?;
+    if !prefix.autonomous
+        || prefix.len != SLAAC_PREFIX_LEN
+        || is_link_local(&prefix.prefix)
+        || prefix.valid_lifetime_secs == 0
+        || prefix.preferred_lifetime_secs > prefix.valid_lifetime_secs
+    {
+        return None;
+    }
+    Some(SlaacConfig {
+        address: with_prefix(prefix.prefix, mac),
+        prefix_len: SLAAC_PREFIX_LEN,
+        gateway: (ra.router_lifetime_secs != 0).then_some(ra.router),
+        dns_servers: ra.dns_servers.clone(),
+        valid_lifetime_secs: prefix.valid_lifetime_secs,
+    })
+}
+
+/// Valid lifetime to give an address that has `remaining_secs` left when a
+/// new advertisement for its prefix offers `advertised_secs`.
+///
+/// Applies the two-hour rule of RFC 4862 §5.5.3(e): a lifetime longer than
+/// two hours or than the remaining one is taken as is, but a shorter one
+/// only cuts the remaining lifetime to two hours, so a single forged
+/// advertisement cannot expire the address.  `u32::MAX` is infinite on
+/// both sides.
+pub fn updated_valid_lifetime(advertised_secs: u32, remaining_secs: u32) -> u32 {
+    if advertised_secs > SLAAC_MIN_VALID_LIFETIME_SECS || advertised_secs > remaining_secs {
+        advertised_secs
+    } else {
+        remaining_secs.min(SLAAC_MIN_VALID_LIFETIME_SECS)
+    }
+}
+
+// ── Router Solicitation ───────────────────────────────────────────────────────
+
+/// Encodes a Router Solicitation from `src` to [`ALL_ROUTERS`] as a full
+/// IPv6 packet, with a source link-layer address option carrying `mac`.
+///
+/// `src` should be the interface's link-local address.  Returns the number
+/// of bytes written, [`ROUTER_SOLICIT_LEN`].
+pub fn encode_router_solicit(
+    buf: &mut [u8],
+    src: Ipv6Addr,
+    mac: [u8; 6],
+) -> Result<usize, Ipv6Error> {
+    let out = buf
+        .get_mut(..ROUTER_SOLICIT_LEN)
+        .ok_or(Ipv6Error::BufferTooSmall)?;
+    out.fill(0);
+
+    let (header, icmp) = out.split_at_mut(IPV6_HEADER_LEN);
+    header[0] = 0x60;
+    header[4..6].copy_from_slice(&(icmp.len() as u16).to_be_bytes());
+    header[6] = NEXT_HEADER_ICMPV6;
+    header[7] = ND_HOP_LIMIT;
+    header[8..24].copy_from_slice(&src.octets());
+    header[24..40].copy_from_slice(&ALL_ROUTERS.octets());
+
+    icmp[0] = ICMPV6_ROUTER_SOLICIT;
+    icmp[RS_HEADER_LEN] = OPT_SOURCE_LLADDR;
+    icmp[RS_HEADER_LEN + 1] = 1;
+    icmp[RS_HEADER_LEN + 2..].copy_from_slice(&mac);
+    let checksum = icmpv6_checksum(&src, &ALL_ROUTERS, icmp);
+    icmp[2..4].copy_from_slice(&checksum.to_be_bytes());
+
+    Ok(ROUTER_SOLICIT_LEN)
+}
+
+// ── Wire helpers ──────────────────────────────────────────────────────────────
+
+fn addr_at(buf: &[u8], offset: usize) -> Ipv6Addr {
+    let mut octets = [0u8; 16];
+    octets.copy_from_slice(&buf[offset..offset + 16]);
+    Ipv6Addr::from(octets)
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        buf[offset],
+        buf[offset + 1],
+        buf[offset + 2],
+        buf[offset + 3],
+    ])
+}
+
+/// ICMPv6 checksum over the pseudo-header and `icmp` (RFC 8200 §8.1).
+///
+/// Returns `0` when `icmp` already carries a correct checksum, and the value
+/// to store when its checksum field is zero.
+fn icmpv6_checksum(src: &Ipv6Addr, dst: &Ipv6Addr, icmp: &[u8]) -> u16 {
+    fn add(sum: u32, bytes: &[u8]) -> u32 {
+        bytes.chunks(2).fold(sum, |sum, pair| {
+            let word = u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]);
+            sum + u32::from(word)
+        })
+    }
+    let mut sum = add(0, &src.octets());
+    sum = add(sum, &dst.octets());
+    sum = add(sum, &(icmp.len() as u32).to_be_bytes());
+    sum += u32::from(NEXT_HEADER_ICMPV6);
+    sum = add(sum, icmp);
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+// ── Async autoconfiguration loop (bare-metal only) ────────────────────────────
+
+/// Runs IPv6 autoconfiguration on the station `embassy-net` stack.
+///
+/// Never returns.  Each time the link comes up it sets the link-local
+/// address, sends up to three Router Solicitations four seconds apart
+/// (RFC 4861 §10), and applies every valid Router Advertisement through
+/// [`slaac_config`], shortening the lifetime of an address it already has
+/// only as far as [`updated_valid_lifetime`] allows.  When the prefix
+/// expires or the link drops it falls back to the link-local address.
+///
+/// Needs the `ipv6` feature.  The raw socket counts against the stack's
+/// socket budget; with the feature enabled the default
+/// [`STA_SOCKET_COUNT`](super::STA_SOCKET_COUNT) includes [`SOCKETS`] for
+/// it.
+///
+/// # Spawn this as a dedicated embassy task
+///
+/// ```ignore
+/// use rustyfarian_esp_hal_network::wifi::ipv6;
+///
+/// #[embassy_executor::task]
+/// async fn ipv6_task(stack: embassy_net::Stack<'static>) -> ! {
+///     ipv6::run(stack).await
+/// }
+/// ```
+///
+/// # Panic-free
+///
+/// Malformed or invalid advertisements and send errors are logged and
+/// skipped; the task never aborts.
+#[cfg(all(feature = "embassy", feature = "ipv6", feature = "_wifi-chip"))]
+pub async fn run(stack: embassy_net::Stack<'static>) -> ! {
+    use embassy_net::raw::{IpProtocol, IpVersion, PacketMetadata, RawSocket};
+    use embassy_net::{ConfigV6, HardwareAddress, Ipv6Cidr, StaticConfigV6};
+    use embassy_time::{with_deadline, Duration, Instant, Timer};
+    use static_cell::StaticCell;
+
+    /// Room for a few queued advertisements of a typical 1280-byte MTU.
+    const SOCKET_BUF_LEN: usize = 2048;
+    /// Router Solicitations sent per link-up (RFC 4861 §10).
+    const MAX_RTR_SOLICITATIONS: u32 = 3;
+    /// Spacing between Router Solicitations (RFC 4861 §10).
+    const RTR_SOLICITATION_INTERVAL: Duration = Duration::from_secs(4);
+    /// How often the link state is checked while waiting for packets.
+    const LINK_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+    static RX_META: StaticCell<[PacketMetadata; 4]> = StaticCell::new();
+    static RX_BUF: StaticCell<[u8; SOCKET_BUF_LEN]> = StaticCell::new();
+    static TX_META: StaticCell<[PacketMetadata; 2]> = StaticCell::new();
+    static TX_BUF: StaticCell<[u8; ROUTER_SOLICIT_LEN * 2]> = StaticCell::new();
+
+    fn static_config(
+        address: Ipv6Addr,
+        gateway: Option<Ipv6Addr>,
+        dns_servers: &[Ipv6Addr],
+    ) -> ConfigV6 {
+        let mut config = StaticConfigV6 {
+            address: Ipv6Cidr::new(address, SLAAC_PREFIX_LEN),
+            gateway,
+            dns_servers: Default::default(),
+        };
+        for server in dns_servers {
+            let _ = config.dns_servers.push(*server);
+        }
+        ConfigV6::Static(config)
+    }
+
+    /// `Instant` `secs` seconds from now; `u32::MAX` lifetimes never expire.
+    fn deadline(now: Instant, secs: u32) -> Instant {
+        if secs == u32::MAX {
+            Instant::MAX
+        } else {
+            now + Duration::from_secs(u64::from(secs))
+        }
+    }
+
+    #[allow(unreachable_patterns)]
+    let mac = match stack.hardware_address() {
+        HardwareAddress::Ethernet(addr) => addr.0,
+        _ => loop {
+            log::warn!("IPv6: interface has no MAC address; autoconfiguration will not run");
+            Timer::after(Duration::from_secs(60)).await;
+        },
+    };
+    let link_local = link_local_address(mac);
+
+    let sock = RawSocket::new::<esp_radio::wifi::Interface<'static>>(
+        stack,
+        IpVersion::Ipv6,
+        IpProtocol::Icmpv6,
+        RX_META.init([PacketMetadata::EMPTY; 4]),
+        RX_BUF.init([0u8; SOCKET_BUF_LEN]),
+        TX_META.init([PacketMetadata::EMPTY; 2]),
+        TX_BUF.init([0u8; ROUTER_SOLICIT_LEN * 2]),
+    );
+    let mut rs = [0u8; ROUTER_SOLICIT_LEN];
+    // Cannot fail: the buffer is exactly `ROUTER_SOLICIT_LEN` long.
+    let rs_len = encode_router_solicit(&mut rs, link_local, mac).unwrap_or(0);
+    let mut packet = [0u8; SOCKET_BUF_LEN];
+
+    loop {
+        stack.wait_link_up().await;
+        stack.set_config_v6(static_config(link_local, None, &[]));
+        log::info!("IPv6 link-local address {}", link_local);
+
+        let mut current: Option<SlaacConfig> = None;
+        let mut address_expires = Instant::MAX;
+        let mut router_expires = Instant::MAX;
+        let mut solicitations = 0u32;
+        let mut next_solicit = Instant::now();
+
+        while stack.is_link_up() {
+            let now = Instant::now();
+            if current.is_none() && solicitations < MAX_RTR_SOLICITATIONS && now >= next_solicit {
+                sock.send(&rs[..rs_len]).await;
+                solicitations += 1;
+                next_solicit = now + RTR_SOLICITATION_INTERVAL;
+            }
+
+            if now >= address_expires {
+                log::info!("IPv6: prefix expired; back to link-local");
+                current = None;
+                address_expires = Instant::MAX;
+                router_expires = Instant::MAX;
+                stack.set_config_v6(static_config(link_local, None, &[]));
+            } else if now >= router_expires {
+                if let Some(config) = current.as_mut() {
+                    config.gateway = None;
+                    stack.set_config_v6(static_config(config.address, None, &config.dns_servers));
+                }
+                router_expires = Instant::MAX;
+            }
+
+            let mut wake = (now + LINK_CHECK_INTERVAL)
+                .min(address_expires)
+                .min(router_expires);
+            if current.is_none() && solicitations < MAX_RTR_SOLICITATIONS {
+                wake = wake.min(next_solicit);
+            }
+            let Ok(received) = with_deadline(wake, sock.recv(&mut packet)).await else {
+                continue;
+            };
+            let Ok(len) = received else {
+                log::warn!("IPv6: oversized ICMPv6 packet dropped");
+                continue;
+            };
+            let ra = match parse_router_advert(&packet[..len]) {
+                Ok(ra) => ra,
+                Err(Ipv6Error::NotRouterAdvert) => continue,
+                Err(e) => {
+                    log::warn!("IPv6: router advertisement ignored ({})", e);
+                    continue;
+                }
+            };
+            let Some(mut config) = slaac_config(&ra, mac) else {
+                continue;
+            };
+
+            let now = Instant::now();
+            if current
+                .as_ref()
+                .is_some_and(|c| c.address == config.address)
+            {
+                let remaining = if address_expires == Instant::MAX {
+                    u32::MAX
+                } else {
+                    // Finite lifetimes are below `u32::MAX` seconds.
+                    let left = address_expires.saturating_duration_since(now).as_secs();
+                    u32::try_from(left).unwrap_or(u32::MAX - 1)
+                };
+                config.valid_lifetime_secs =
+                    updated_valid_lifetime(config.valid_lifetime_secs, remaining);
+            }
+            address_expires = deadline(now, config.valid_lifetime_secs);
+            router_expires = if config.gateway.is_some() {
+                now + Duration::from_secs(u64::from(ra.router_lifetime_secs))
+            } else {
+                Instant::MAX
+            };
+            let changed = current.as_ref().is_none_or(|c| {
+                c.address != config.address
+                    || c.gateway != config.gateway
+                    || c.dns_servers != config.dns_servers
+            });
+            if changed {
+                log::info!(
+                    "IPv6 address {}/{} via {:?}",
+                    config.address,
+                    config.prefix_len,
+                    config.gateway
+                );
+                stack.set_config_v6(static_config(
+                    config.address,
+                    config.gateway,
+                    &config.dns_servers,
+                ));
+            }
+            current = Some(config);
+        }
+        log::info!("IPv6: link down");
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAC: [u8; 6] = [0x24, 0x0a, 0xc4, 0x12, 0x34, 0x56];
+    const ROUTER: Ipv6Addr = Ipv6Addr::new(0xfe80, 0, 0, 0, 0x0211, 0x22ff, 0xfe33, 0x4455);
+    const PREFIX: Ipv6Addr = Ipv6Addr::new(0x2001, 0x0db8, 0x0001, 0x0002, 0, 0, 0, 0);
+    const DNS64: Ipv6Addr = Ipv6Addr::new(0x2001, 0x0db8, 0, 0, 0, 0, 0, 0x64);
+    const ALL_NODES: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 1);
+
+    /// Options for [`ra_packet`].
+    struct Ra<'a> {
+        src: Ipv6Addr,
+        hop_limit: u8,
+        router_lifetime: u16,
+        options: &'a [&'a [u8]],
+    }
+
+    impl Default for Ra<'_> {
+        fn default() -> Self {
+            Self {
+                src: ROUTER,
+                hop_limit: ND_HOP_LIMIT,
+                router_lifetime: 1800,
+                options: &[],
+            }
+        }
+    }
+
+    fn prefix_option(prefix: Ipv6Addr, len: u8, flags: u8, valid: u32, preferred: u32) -> [u8; 32] {
+        let mut o = [0u8; 32];
+        o[0] = OPT_PREFIX_INFO;
+        o[1] = 4;
+        o[2] = len;
+        o[3] = flags;
+        o[4..8].copy_from_slice(&valid.to_be_bytes());
+        o[8..12].copy_from_slice(&preferred.to_be_bytes());
+        o[16..32].copy_from_slice(&prefix.octets());
+        o
+    }
+
+    fn rdnss_option(lifetime: u32, servers: &[Ipv6Addr]) -> heapless::Vec<u8, 72> {
+        let mut o = heapless::Vec::new();
+        o.extend_from_slice(&[OPT_RDNSS, (1 + 2 * servers.len()) as u8, 0, 0])
+            .unwrap();
+        o.extend_from_slice(&lifetime.to_be_bytes()).unwrap();
+        for s in servers {
+            o.extend_from_slice(&s.octets()).unwrap();
+        }
+        o
+    }
+
+    /// Builds a complete IPv6 packet carrying a Router Advertisement with a
+    /// valid checksum.
+    fn ra_packet(ra: Ra<'_>) -> heapless::Vec<u8, 512> {
+        let mut icmp: heapless::Vec<u8, 472> = heapless::Vec::new();
+        icmp.extend_from_slice(&[ICMPV6_ROUTER_ADVERT, 0, 0, 0, 64, 0])
+            .unwrap();
+        icmp.extend_from_slice(&ra.router_lifetime.to_be_bytes())
+            .unwrap();
+        icmp.extend_from_slice(&[0; 8]).unwrap();
+        for option in ra.options {
+            icmp.extend_from_slice(option).unwrap();
+        }
+        let checksum = icmpv6_checksum(&ra.src, &ALL_NODES, &icmp);
+        icmp[2..4].copy_from_slice(&checksum.to_be_bytes());
+
+        let mut packet = heapless::Vec::new();
+        packet.extend_from_slice(&[0x60, 0, 0, 0]).unwrap();
+        packet
+            .extend_from_slice(&(icmp.len() as u16).to_be_bytes())
+            .unwrap();
+        packet
+            .extend_from_slice(&[NEXT_HEADER_ICMPV6, ra.hop_limit])
+            .unwrap();
+        packet.extend_from_slice(&ra.src.octets()).unwrap();
+        packet.extend_from_slice(&ALL_NODES.octets()).unwrap();
+        packet.extend_from_slice(&icmp).unwrap();
+        packet
+    }
+
+    // ── Addresses ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn interface_id_is_modified_eui64() {
+        assert_eq!(
+            interface_id(MAC),
+            [0x26, 0x0a, 0xc4, 0xff, 0xfe, 0x12, 0x34, 0x56]
+        );
+    }
+
+    #[test]
+    fn link_local_address_uses_fe80_prefix() {
+        assert_eq!(
+            link_local_address(MAC),
+            Ipv6Addr::new(0xfe80, 0, 0, 0, 0x260a, 0xc4ff, 0xfe12, 0x3456)
+        );
+        assert!(is_link_local(&link_local_address(MAC)));
+        assert!(!is_link_local(&PREFIX));
+        assert!(is_link_local(&Ipv6Addr::new(0xfebf, 0, 0, 0, 0, 0, 0, 1)));
+    }
+
+    // ── Router Advertisement parsing ──────────────────────────────────────────
+
+    #[test]
+    fn parses_prefix_mtu_and_rdnss() {
+        let prefix = prefix_option(PREFIX, 64, 0xc0, 86_400, 14_400);
+        let mtu = [OPT_MTU, 1, 0, 0, 0, 0, 0x05, 0xdc];
+        let rdnss = rdnss_option(600, &[DNS64]);
+        let lladdr = [OPT_SOURCE_LLADDR, 1, 0, 0x11, 0x22, 0x33, 0x44, 0x55];
+        let packet = ra_packet(Ra {
+            options: &[&lladdr, &prefix, &mtu, &rdnss],
+            ..Ra::default()
+        });
+
+        let ra = parse_router_advert(&packet).unwrap();
+        assert_eq!(ra.router, ROUTER);
+        assert_eq!(ra.router_lifetime_secs, 1800);
+        assert_eq!(ra.mtu, Some(1500));
+        assert_eq!(ra.dns_servers.as_slice(), &[DNS64]);
+        let info = ra.prefix.unwrap();
+        assert_eq!(info.prefix, PREFIX);
+        assert_eq!(info.len, 64);
+        assert!(info.autonomous);
+        assert_eq!(info.valid_lifetime_secs, 86_400);
+        assert_eq!(info.preferred_lifetime_secs, 14_400);
+    }
+
+    #[test]
+    fn prefers_an_autonomous_64_prefix() {
+        let onlink_only = prefix_option(
+            Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 0),
+            64,
+            0x80,
+            600,
+            600,
+        );
+        let slaac = prefix_option(PREFIX, 64, 0xc0, 600, 600);
+        let packet = ra_packet(Ra {
+            options: &[&onlink_only, &slaac],
+            ..Ra::default()
+        });
+        assert_eq!(
+            parse_router_advert(&packet).unwrap().prefix.unwrap().prefix,
+            PREFIX
+        );
+    }
+
+    #[test]
+    fn rdnss_with_zero_lifetime_is_ignored() {
+        let rdnss = rdnss_option(0, &[DNS64]);
+        let packet = ra_packet(Ra {
+            options: &[&rdnss],
+            ..Ra::default()
+        });
+        assert!(parse_router_advert(&packet).unwrap().dns_servers.is_empty());
+    }
+
+    #[test]
+    fn rejects_hop_limit_below_255() {
+        let packet = ra_packet(Ra {
+            hop_limit: 64,
+            ..Ra::default()
+        });
+        assert_eq!(parse_router_advert(&packet), Err(Ipv6Error::Invalid));
+    }
+
+    #[test]
+    fn rejects_global_source() {
+        let packet = ra_packet(Ra {
+            src: PREFIX,
+            ..Ra::default()
+        });
+        assert_eq!(parse_router_advert(&packet), Err(Ipv6Error::Invalid));
+    }
+
+    #[test]
+    fn rejects_bad_checksum() {
+        let mut packet = ra_packet(Ra::default());
+        let last = packet.len() - 1;
+        packet[last] ^= 0xff;
+        assert_eq!(parse_router_advert(&packet), Err(Ipv6Error::BadChecksum));
+    }
+
+    #[test]
+    fn rejects_zero_length_option() {
+        let packet = ra_packet(Ra {
+            options: &[&[OPT_MTU, 0, 0, 0, 0, 0, 0, 0]],
+            ..Ra::default()
+        });
+        assert_eq!(parse_router_advert(&packet), Err(Ipv6Error::Invalid));
+    }
+
+    #[test]
+    fn rejects_truncated_packets() {
+        let packet = ra_packet(Ra {
+            options: &[&prefix_option(PREFIX, 64, 0xc0, 600, 600)],
+            ..Ra::default()
+        });
+        assert_eq!(
+            parse_router_advert(&packet[..30]),
+            Err(Ipv6Error::Truncated)
+        );
+        assert_eq!(
+            parse_router_advert(&packet[..packet.len() - 1]),
+            Err(Ipv6Error::Truncated)
+        );
+    }
+
+    #[test]
+    fn other_icmpv6_is_not_a_router_advert() {
+        let mut rs = [0u8; ROUTER_SOLICIT_LEN];
+        encode_router_solicit(&mut rs, link_local_address(MAC), MAC).unwrap();
+        assert_eq!(parse_router_advert(&rs), Err(Ipv6Error::NotRouterAdvert));
+    }
+
+    // ── SLAAC ─────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn slaac_combines_prefix_and_interface_id() {
+        let prefix = prefix_option(PREFIX, 64, 0xc0, 86_400, 14_400);
+        let rdnss = rdnss_option(600, &[DNS64]);
+        let ra = parse_router_advert(&ra_packet(Ra {
+            options: &[&prefix, &rdnss],
+            ..Ra::default()
+        }))
+        .unwrap();
+
+        let config = slaac_config(&ra, MAC).unwrap();
+        assert_eq!(
+            config.address,
+            Ipv6Addr::new(0x2001, 0x0db8, 1, 2, 0x260a, 0xc4ff, 0xfe12, 0x3456)
+        );
+        assert_eq!(config.prefix_len, 64);
+        assert_eq!(config.gateway, Some(ROUTER));
+        assert_eq!(config.dns_servers.as_slice(), &[DNS64]);
+        assert_eq!(config.valid_lifetime_secs, 86_400);
+    }
+
+    #[test]
+    fn zero_router_lifetime_means_no_gateway() {
+        let prefix = prefix_option(PREFIX, 64, 0xc0, 600, 600);
+        let ra = parse_router_advert(&ra_packet(Ra {
+            router_lifetime: 0,
+            options: &[&prefix],
+            ..Ra::default()
+        }))
+        .unwrap();
+        assert_eq!(slaac_config(&ra, MAC).unwrap().gateway, None);
+    }
+
+    #[test]
+    fn unusable_prefixes_yield_no_config() {
+        let cases = [
+            prefix_option(PREFIX, 64, 0x80, 600, 600), // not autonomous
+            prefix_option(PREFIX, 56, 0xc0, 600, 600), // not /64
+            prefix_option(
+                Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0),
+                64,
+                0xc0,
+                600,
+                600,
+            ),
+            prefix_option(PREFIX, 64, 0xc0, 0, 0), // expired
+            prefix_option(PREFIX, 64, 0xc0, 600, 700), // preferred > valid
+        ];
+        for option in &cases {
+            let ra = parse_router_advert(&ra_packet(Ra {
+                options: &[option],
+                ..Ra::default()
+            }))
+            .unwrap();
+            assert_eq!(slaac_config(&ra, MAC), None, "{option:?}");
+        }
+        let ra = parse_router_advert(&ra_packet(Ra::default())).unwrap();
+        assert_eq!(slaac_config(&ra, MAC), None, "no prefix option");
+    }
+
+    // ── Router Solicitation ───────────────────────────────────────────────────
+
+    #[test]
+    fn short_lifetime_cannot_expire_an_address_early() {
+        const TWO_HOURS: u32 = SLAAC_MIN_VALID_LIFETIME_SECS;
+        // A forged 1 s lifetime only cuts a day-long address to two hours.
+        assert_eq!(updated_valid_lifetime(1, 86_400), TWO_HOURS);
+        assert_eq!(updated_valid_lifetime(1, u32::MAX), TWO_HOURS);
+        assert_eq!(updated_valid_lifetime(TWO_HOURS, 86_400), TWO_HOURS);
+        // Under two hours left: the advertisement is ignored.
+        assert_eq!(updated_valid_lifetime(1, 600), 600);
+        assert_eq!(updated_valid_lifetime(0, 600), 600);
+    }
+
+    #[test]
+    fn long_or_extending_lifetimes_are_taken_as_is() {
+        assert_eq!(updated_valid_lifetime(86_400, 600), 86_400);
+        assert_eq!(updated_valid_lifetime(7_201, 86_400), 7_201);
+        assert_eq!(updated_valid_lifetime(u32::MAX, 600), u32::MAX);
+        assert_eq!(updated_valid_lifetime(900, 600), 900);
+    }
+
+    #[test]
+    fn router_solicit_wire_format() {
+        let src = link_local_address(MAC);
+        let mut buf = [0xaau8; 64];
+        let len = encode_router_solicit(&mut buf, src, MAC).unwrap();
+        assert_eq!(len, ROUTER_SOLICIT_LEN);
+
+        let packet = &buf[..len];
+        assert_eq!(packet[0], 0x60);
+        assert_eq!(&packet[4..6], &16u16.to_be_bytes());
+        assert_eq!(packet[6], NEXT_HEADER_ICMPV6);
+        assert_eq!(packet[7], 255);
+        assert_eq!(addr_at(packet, 8), src);
+        assert_eq!(addr_at(packet, 24), ALL_ROUTERS);
+
+        let icmp = &packet[IPV6_HEADER_LEN..];
+        assert_eq!(icmp[0], ICMPV6_ROUTER_SOLICIT);
+        assert_eq!(icmp[1], 0);
+        assert_eq!(&icmp[4..8], &[0; 4]);
+        assert_eq!(&icmp[8..10], &[OPT_SOURCE_LLADDR, 1]);
+        assert_eq!(&icmp[10..16], &MAC);
+        assert_eq!(icmpv6_checksum(&src, &ALL_ROUTERS, icmp), 0);
+    }
+
+    #[test]
+    fn router_solicit_needs_room() {
+        let mut buf = [0u8; ROUTER_SOLICIT_LEN - 1];
+        assert_eq!(
+            encode_router_solicit(&mut buf, link_local_address(MAC), MAC),
+            Err(Ipv6Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn checksum_matches_known_vector() {
+        // Router Solicitation from fe80::1 to ff02::2 with no options;
+        // checksum computed independently.
+        let src = Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1);
+        let icmp = [ICMPV6_ROUTER_SOLICIT, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(icmpv6_checksum(&src, &ALL_ROUTERS, &icmp), 0x7d36);
+    }
+}
@@ -0,0 +1,1142 @@
+//! Multicast DNS responder for the station interface (RFC 6762 / RFC 6763).
+//!
+//! ## What it covers
+//!
+//! A minimal mDNS responder that makes the device reachable as
+//! `<hostname>.local` and, optionally, advertises DNS-SD services such as
+//! `_http._tcp` so service browsers list it.  It is driven by the shared
+//! [`MdnsConfig`] from `juggler`, the same type the ESP-IDF tier hands to the
+//! `espressif/mdns` component.  The codec is hand-rolled in the style of
+//! `provisioning::dns_catchall` and host-tested end-to-end; only [`run`]
+//! touches `embassy-net`.
+//!
+//! ## Protocol coverage
+//!
+//! - Queries (QR=0, opcode 0) with any number of questions; responses and
+//!   other opcodes are ignored.  Question names may use compression
+//!   pointers — unlike a unicast resolver, mDNS queriers do compress
+//!   multi-question packets — so pointers are followed, with a hop limit as
+//!   the loop defence.
+//! - Answers `A` for `<hostname>.local`; `PTR` for each
+//!   `<service>.<proto>.local` (with `SRV`, `TXT`, and `A` as additional
+//!   records); `SRV` / `TXT` for each `<instance>.<service>.<proto>.local`;
+//!   and the `_services._dns-sd._udp.local` enumeration `PTR`.  `ANY`
+//!   queries get every matching record.
+//! - Unique records (`A`, `SRV`, `TXT`) carry the cache-flush bit; shared
+//!   `PTR` records do not.
+//! - Replies go to the multicast group unless a question sets the QU
+//!   ("unicast response") bit or the query came from a port other than
+//!   5353 (a "legacy unicast" resolver such as `nslookup`), in which case
+//!   they go back to the sender.  Legacy replies echo the query ID and
+//!   questions and cap the TTL at 10 s (RFC 6762 §6.7).
+//! - [`run`] sends two unsolicited announcements one second apart when the
+//!   link comes up (RFC 6762 §8.3).
+//!
+//! ## Not covered
+//!
+//! Probing and conflict resolution (§8.1–8.2), known-answer suppression
+//! (§7.1), `AAAA` / `NSEC` records, and goodbye packets.  Pick a hostname
+//! that is unique on the network — deriving it from the MAC address works
+//! well.
+
+// When building without the embassy + chip features the async `run` function
+// and its UdpSocket usage are compiled away.  Allow dead-code on the items
+// that remain so clippy -D warnings does not fail on stub/host builds.
+#![cfg_attr(
+    not(all(feature = "embassy", feature = "_wifi-chip")),
+    allow(dead_code)
+)]
+
+use core::net::Ipv4Addr;
+
+use juggler::wifi::{validate_mdns_config, MdnsConfig, MdnsService, MDNS_MESSAGE_MAX_LEN};
+
+// ── mDNS wire constants ───────────────────────────────────────────────────────
+
+/// mDNS UDP port.
+pub const MDNS_PORT: u16 = 5353;
+
+/// IPv4 mDNS multicast group.
+pub const MDNS_GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+
+/// `embassy-net` sockets [`run`] keeps open: one UDP socket.
+pub const SOCKETS: usize = 1;
+
+/// Size of the receive and transmit packet buffers.
+///
+/// [`MDNS_MESSAGE_MAX_LEN`]: [`validate_mdns_config`] rejects any
+/// configuration whose announcement would not fit, while staying below the
+/// 1472-byte UDP payload of a 1500-byte MTU.
+pub const MDNS_MSG_MAX: usize = MDNS_MESSAGE_MAX_LEN;
+
+/// DNS header length.
+const HEADER_LEN: usize = 12;
+
+/// Upper bound on questions examined per query; the rest are ignored.
+const MAX_QUESTIONS: usize = 16;
+
+/// Upper bound on compression pointers followed while matching one name.
+const MAX_POINTER_HOPS: usize = 8;
+
+/// Maximum total name length in wire format (RFC 1035 §3.1).
+const NAME_MAX_WIRE: usize = 255;
+
+/// Bit mask for the high two bits of a length byte — a compression pointer
+/// when both are set.
+const COMPRESSION_MASK: u8 = 0xC0;
+
+/// TTL cap for legacy unicast responses (RFC 6762 §6.7).
+const LEGACY_TTL_MAX_SECS: u32 = 10;
+
+// ── DNS header bits and record types ──────────────────────────────────────────
+
+/// QR bit — set in responses.
+const FLAG_QR: u16 = 0x8000;
+/// Opcode field mask (bits 11–14).
+const FLAG_OPCODE_MASK: u16 = 0x7800;
+/// Response flags: QR=1, AA=1.
+const RESPONSE_FLAGS: u16 = 0x8400;
+
+/// Top bit of the question class — "unicast response requested".
+const CLASS_QU: u16 = 0x8000;
+/// Top bit of the record class — "cache flush" on unique records.
+const CLASS_CACHE_FLUSH: u16 = 0x8000;
+const CLASS_IN: u16 = 1;
+const CLASS_ANY: u16 = 255;
+
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_SRV: u16 = 33;
+const TYPE_ANY: u16 = 255;
+
+/// Labels of the DNS-SD service-type enumeration name (RFC 6763 §9).
+const ENUMERATION_NAME: [&str; 4] = ["_services", "_dns-sd", "_udp", "local"];
+
+// ── MdnsError ─────────────────────────────────────────────────────────────────
+
+/// Errors produced by the mDNS codec.
+#[derive(Debug, PartialEq, Eq)]
+pub enum MdnsError {
+    /// Packet is shorter than the 12-byte DNS header.
+    TooShort,
+    /// A question name is malformed: a reserved length-byte prefix, a label
+    /// running past the end of the packet, or a name longer than 255 bytes.
+    MalformedName,
+    /// The packet ends before a question's type and class fields.
+    Truncated,
+    /// The output buffer is too small to hold the response.
+    BufferTooSmall,
+}
+
+// ── MdnsReply ─────────────────────────────────────────────────────────────────
+
+/// A response encoded by [`MdnsResponder::respond`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct MdnsReply {
+    /// Number of bytes written to the output buffer.
+    pub len: usize,
+    /// `true` if the reply must go back to the querier's address and port
+    /// instead of the multicast group.
+    pub unicast: bool,
+}
+
+// ── Record set ────────────────────────────────────────────────────────────────
+
+/// One resource record the responder can emit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Record {
+    /// `<hostname>.local A <ip>`.
+    Host,
+    /// `<service>.<proto>.local PTR <instance>.<service>.<proto>.local`.
+    Ptr(usize),
+    /// `<instance>.<service>.<proto>.local SRV 0 0 <port> <hostname>.local`.
+    Srv(usize),
+    /// `<instance>.<service>.<proto>.local TXT …`.
+    Txt(usize),
+    /// `_services._dns-sd._udp.local PTR <service>.<proto>.local`.
+    Enumeration(usize),
+}
+
+impl Record {
+    /// Bit position in a [`RecordSet`]: the host record, then four per
+    /// service.  `MDNS_MAX_SERVICES` (4) keeps this within 17 bits.
+    fn bit(self) -> u32 {
+        let (service, offset) = match self {
+            Self::Host => return 1,
+            Self::Ptr(i) => (i, 0),
+            Self::Srv(i) => (i, 1),
+            Self::Txt(i) => (i, 2),
+            Self::Enumeration(i) => (i, 3),
+        };
+        1 << (1 + 4 * service + offset)
+    }
+}
+
+/// Bit set of [`Record`]s.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct RecordSet(u32);
+
+impl RecordSet {
+    fn insert(&mut self, record: Record) {
+        self.0 |= record.bit();
+    }
+
+    fn contains(self, record: Record) -> bool {
+        self.0 & record.bit() != 0
+    }
+
+    fn without(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterates the records in emission order for `services` services.
+    fn iter(self, services: usize) -> impl Iterator<Item = Record> {
+        let per_service = (0..services).flat_map(|i| {
+            [
+                Record::Ptr(i),
+                Record::Srv(i),
+                Record::Txt(i),
+                Record::Enumeration(i),
+            ]
+        });
+        core::iter::once(Record::Host)
+            .chain(per_service)
+            .filter(move |r| self.contains(*r))
+    }
+
+    fn len(self) -> u16 {
+        self.0.count_ones() as u16
+    }
+}
+
+// ── MdnsResponder ─────────────────────────────────────────────────────────────
+
+/// Pure mDNS responder: turns query packets into response packets.
+///
+/// Holds only the validated [`MdnsConfig`]; the current IPv4 address is
+/// passed per call so a DHCP renewal that changes it needs no rebuild.
+#[derive(Debug, Clone, Copy)]
+pub struct MdnsResponder<'a> {
+    config: MdnsConfig<'a>,
+}
+
+impl<'a> MdnsResponder<'a> {
+    /// Creates a responder after checking `config` with
+    /// [`validate_mdns_config`].
+    pub fn new(config: MdnsConfig<'a>) -> Result<Self, &'static str> {
+        validate_mdns_config(&config)?;
+        Ok(Self { config })
+    }
+
+    /// Returns the configuration this responder answers for.
+    pub fn config(&self) -> &MdnsConfig<'a> {
+        &self.config
+    }
+
+    /// Decodes `query` and encodes the matching answers into `out`.
+    ///
+    /// `src_port` is the querier's UDP source port (anything other than
+    /// 5353 selects a legacy unicast reply) and `ip` the address to put in
+    /// `A` records.  Returns `Ok(None)` when the packet is a response, uses
+    /// an opcode other than QUERY, or asks about nothing this device owns.
+    pub fn respond(
+        &self,
+        query: &[u8],
+        src_port: u16,
+        ip: Ipv4Addr,
+        out: &mut [u8],
+    ) -> Result<Option<MdnsReply>, MdnsError> {
+        if query.len() < HEADER_LEN {
+            return Err(MdnsError::TooShort);
+        }
+        let id = u16::from_be_bytes([query[0], query[1]]);
+        let flags = u16::from_be_bytes([query[2], query[3]]);
+        let qdcount = usize::from(u16::from_be_bytes([query[4], query[5]]));
+        if flags & (FLAG_QR | FLAG_OPCODE_MASK) != 0 {
+            return Ok(None);
+        }
+
+        let mut answers = RecordSet::default();
+        let mut additional = RecordSet::default();
+        let mut unicast = src_port != MDNS_PORT;
+        let legacy = unicast;
+
+        let mut pos = HEADER_LEN;
+        let questions = qdcount.min(MAX_QUESTIONS);
+        for _ in 0..questions {
+            let name_start = pos;
+            pos = skip_name(query, pos)?;
+            let Some(fields) = query.get(pos..pos + 4) else {
+                return Err(MdnsError::Truncated);
+            };
+            let qtype = u16::from_be_bytes([fields[0], fields[1]]);
+            let qclass = u16::from_be_bytes([fields[2], fields[3]]);
+            pos += 4;
+
+            let class = qclass & !CLASS_QU;
+            if class != CLASS_IN && class != CLASS_ANY {
+                continue;
+            }
+            let before = answers;
+            self.match_question(query, name_start, qtype, &mut answers, &mut additional);
+            if answers != before && qclass & CLASS_QU != 0 {
+                unicast = true;
+            }
+        }
+
+        if answers.is_empty() {
+            return Ok(None);
+        }
+        additional = additional.without(answers);
+
+        let mut w = Writer::new(out);
+        if legacy {
+            // Echo the ID and, if every question was examined, the question
+            // section verbatim.  Copying it to the same offset keeps any
+            // compression pointers inside it valid.
+            let echoed = if qdcount == questions {
+                qdcount as u16
+            } else {
+                0
+            };
+            w.header(id, echoed, answers.len(), additional.len())?;
+            if echoed != 0 {
+                w.bytes(&query[HEADER_LEN..pos])?;
+            }
+        } else {
+            w.header(0, 0, answers.len(), additional.len())?;
+        }
+        let ttl = if legacy {
+            self.config.ttl_secs.min(LEGACY_TTL_MAX_SECS)
+        } else {
+            self.config.ttl_secs
+        };
+        let services = self.config.services.len();
+        for record in answers.iter(services).chain(additional.iter(services)) {
+            self.write_record(&mut w, record, ttl, !legacy, ip)?;
+        }
+        Ok(Some(MdnsReply {
+            len: w.pos,
+            unicast,
+        }))
+    }
+
+    /// Encodes an unsolicited announcement of every record into `out` and
+    /// returns its length.  Send it to [`MDNS_GROUP`]:[`MDNS_PORT`].
+    pub fn announce(&self, ip: Ipv4Addr, out: &mut [u8]) -> Result<usize, MdnsError> {
+        let mut all = RecordSet::default();
+        all.insert(Record::Host);
+        for i in 0..self.config.services.len() {
+            for record in [
+                Record::Ptr(i),
+                Record::Srv(i),
+                Record::Txt(i),
+                Record::Enumeration(i),
+            ] {
+                all.insert(record);
+            }
+        }
+        let mut w = Writer::new(out);
+        w.header(0, 0, all.len(), 0)?;
+        for record in all.iter(self.config.services.len()) {
+            self.write_record(&mut w, record, self.config.ttl_secs, true, ip)?;
+        }
+        Ok(w.pos)
+    }
+
+    /// Adds the records answering one question to `answers` / `additional`.
+    fn match_question(
+        &self,
+        packet: &[u8],
+        name: usize,
+        qtype: u16,
+        answers: &mut RecordSet,
+        additional: &mut RecordSet,
+    ) {
+        let wants = |t: u16| qtype == t || qtype == TYPE_ANY;
+        let host = self.config.hostname;
+
+        if wants(TYPE_A) && name_matches(packet, name, &[host, "local"]) {
+            answers.insert(Record::Host);
+        }
+        for (i, service) in self.config.services.iter().enumerate() {
+            let proto = service.protocol.label();
+            if wants(TYPE_PTR) && name_matches(packet, name, &[service.service, proto, "local"]) {
+                answers.insert(Record::Ptr(i));
+                additional.insert(Record::Srv(i));
+                additional.insert(Record::Txt(i));
+                additional.insert(Record::Host);
+            }
+            if name_matches(packet, name, &self.instance_name(service)) {
+                if wants(TYPE_SRV) {
+                    answers.insert(Record::Srv(i));
+                    additional.insert(Record::Host);
+                }
+                if wants(TYPE_TXT) {
+                    answers.insert(Record::Txt(i));
+                }
+            }
+            if wants(TYPE_PTR) && name_matches(packet, name, &ENUMERATION_NAME) {
+                answers.insert(Record::Enumeration(i));
+            }
+        }
+    }
+
+    /// Labels of `<instance>.<service>.<proto>.local`.
+    fn instance_name(&self, service: &MdnsService<'a>) -> [&'a str; 4] {
+        [
+            service.instance.unwrap_or(self.config.hostname),
+            service.service,
+            service.protocol.label(),
+            "local",
+        ]
+    }
+
+    fn write_record(
+        &self,
+        w: &mut Writer<'_>,
+        record: Record,
+        ttl: u32,
+        cache_flush: bool,
+        ip: Ipv4Addr,
+    ) -> Result<(), MdnsError> {
+        let host = [self.config.hostname, "local"];
+        let unique_class = if cache_flush {
+            CLASS_IN | CLASS_CACHE_FLUSH
+        } else {
+            CLASS_IN
+        };
+        match record {
+            Record::Host => {
+                w.name(&host)?;
+                w.u16(TYPE_A)?;
+                w.u16(unique_class)?;
+                w.u32(ttl)?;
+                w.u16(4)?;
+                w.bytes(&ip.octets())
+            }
+            Record::Ptr(i) => {
+                let service = &self.config.services[i];
+                w.name(&[service.service, service.protocol.label(), "local"])?;
+                w.u16(TYPE_PTR)?;
+                w.u16(CLASS_IN)?;
+                w.u32(ttl)?;
+                w.rdata(|w| w.name(&self.instance_name(service)))
+            }
+            Record::Srv(i) => {
+                let service = &self.config.services[i];
+                w.name(&self.instance_name(service))?;
+                w.u16(TYPE_SRV)?;
+                w.u16(unique_class)?;
+                w.u32(ttl)?;
+                w.rdata(|w| {
+                    w.u16(0)?; // priority
+                    w.u16(0)?; // weight
+                    w.u16(service.port)?;
+                    w.name(&host)
+                })
+            }
+            Record::Txt(i) => {
+                let service = &self.config.services[i];
+                w.name(&self.instance_name(service))?;
+                w.u16(TYPE_TXT)?;
+                w.u16(unique_class)?;
+                w.u32(ttl)?;
+                w.rdata(|w| {
+                    if service.txt.is_empty() {
+                        // An empty TXT record is a single zero-length string
+                        // (RFC 6763 §6.1).
+                        return w.bytes(&[0]);
+                    }
+                    for (key, value) in service.txt {
+                        // Validation caps `key=value` at 255 bytes.
+                        w.bytes(&[(key.len() + 1 + value.len()) as u8])?;
+                        w.bytes(key.as_bytes())?;
+                        w.bytes(b"=")?;
+                        w.bytes(value.as_bytes())?;
+                    }
+                    Ok(())
+                })
+            }
+            Record::Enumeration(i) => {
+                let service = &self.config.services[i];
+                w.name(&ENUMERATION_NAME)?;
+                w.u16(TYPE_PTR)?;
+                w.u16(CLASS_IN)?;
+                w.u32(ttl)?;
+                w.rdata(|w| w.name(&[service.service, service.protocol.label(), "local"]))
+            }
+        }
+    }
+}
+
+// ── Codec — names ─────────────────────────────────────────────────────────────
+
+/// Returns the offset just past the name starting at `pos`.
+///
+/// Validates label lengths but does not follow compression pointers — a
+/// pointer always ends the name in place.
+fn skip_name(buf: &[u8], mut pos: usize) -> Result<usize, MdnsError> {
+    let start = pos;
+    loop {
+        let len = *buf.get(pos).ok_or(MdnsError::MalformedName)?;
+        if len & COMPRESSION_MASK == COMPRESSION_MASK {
+            if pos + 2 > buf.len() {
+                return Err(MdnsError::MalformedName);
+            }
+            return Ok(pos + 2);
+        }
+        if len & COMPRESSION_MASK != 0 {
+            return Err(MdnsError::MalformedName);
+        }
+        pos += 1 + usize::from(len);
+        if pos - start > NAME_MAX_WIRE || pos > buf.len() {
+            return Err(MdnsError::MalformedName);
+        }
+        if len == 0 {
+            return Ok(pos);
+        }
+    }
+}
+
+/// Returns `true` if the name at `pos` equals `labels` (ASCII
+/// case-insensitive), following compression pointers up to
+/// `MAX_POINTER_HOPS` deep.
+fn name_matches(buf: &[u8], mut pos: usize, labels: &[&str]) -> bool {
+    let mut expected = labels.iter();
+    let mut hops = 0;
+    loop {
+        let Some(&len) = buf.get(pos) else {
+            return false;
+        };
+        if len & COMPRESSION_MASK == COMPRESSION_MASK {
+            let Some(&low) = buf.get(pos + 1) else {
+                return false;
+            };
+            hops += 1;
+            if hops > MAX_POINTER_HOPS {
+                return false;
+            }
+            pos = usize::from(u16::from_be_bytes([len & !COMPRESSION_MASK, low]));
+            continue;
+        }
+        if len & COMPRESSION_MASK != 0 {
+            return false;
+        }
+        if len == 0 {
+            return expected.next().is_none();
+        }
+        let end = pos + 1 + usize::from(len);
+        let Some(label) = buf.get(pos + 1..end) else {
+            return false;
+        };
+        match expected.next() {
+            Some(want) if want.as_bytes().eq_ignore_ascii_case(label) => pos = end,
+            _ => return false,
+        }
+    }
+}
+
+// ── Codec — writer ────────────────────────────────────────────────────────────
+
+/// Bounds-checked big-endian writer over the output buffer.
+struct Writer<'b> {
+    buf: &'b mut [u8],
+    pos: usize,
+}
+
+impl<'b> Writer<'b> {
+    fn new(buf: &'b mut [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn bytes(&mut self, data: &[u8]) -> Result<(), MdnsError> {
+        let end = self.pos + data.len();
+        self.buf
+            .get_mut(self.pos..end)
+            .ok_or(MdnsError::BufferTooSmall)?
+            .copy_from_slice(data);
+        self.pos = end;
+        Ok(())
+    }
+
+    fn u16(&mut self, value: u16) -> Result<(), MdnsError> {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    fn u32(&mut self, value: u32) -> Result<(), MdnsError> {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    fn header(&mut self, id: u16, qd: u16, an: u16, ar: u16) -> Result<(), MdnsError> {
+        self.u16(id)?;
+        self.u16(RESPONSE_FLAGS)?;
+        self.u16(qd)?;
+        self.u16(an)?;
+        self.u16(0)?; // NSCOUNT
+        self.u16(ar)
+    }
+
+    /// Writes an uncompressed name.  Labels are at most 63 bytes after
+    /// validation, so the length byte never collides with a pointer prefix.
+    fn name(&mut self, labels: &[&str]) -> Result<(), MdnsError> {
+        for label in labels {
+            self.bytes(&[label.len() as u8])?;
+            self.bytes(label.as_bytes())?;
+        }
+        self.bytes(&[0])
+    }
+
+    /// Writes RDLENGTH followed by the RDATA produced by `f`, patching the
+    /// length in once `f` is done.
+    fn rdata(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<(), MdnsError>,
+    ) -> Result<(), MdnsError> {
+        let len_at = self.pos;
+        self.u16(0)?;
+        f(self)?;
+        let len = (self.pos - len_at - 2) as u16;
+        self.buf[len_at..len_at + 2].copy_from_slice(&len.to_be_bytes());
+        Ok(())
+    }
+}
+
+// ── Async responder loop (bare-metal only) ────────────────────────────────────
+
+/// Runs the mDNS responder on the station `embassy-net` stack.
+///
+/// Never returns.  Waits for the stack to obtain an IPv4 address, joins
+/// [`MDNS_GROUP`], binds UDP port 5353, announces every record twice, then
+/// answers queries.  The `A` record always carries the stack's current
+/// address, so a DHCP renewal to a new lease is picked up automatically.
+///
+/// The socket counts against the stack's socket budget; the default
+/// [`STA_SOCKET_COUNT`](super::STA_SOCKET_COUNT) of
+/// [`WiFiManager::init_async`][super::WiFiManager::init_async] includes
+/// [`SOCKETS`] for it.
+///
+/// # Spawn this as a dedicated embassy task
+///
+/// ```ignore
+/// use rustyfarian_esp_hal_network::wifi::mdns;
+/// use rustyfarian_esp_hal_network::wifi::{MdnsConfig, MdnsService, ServiceProtocol};
+///
+/// static SERVICES: [MdnsService; 1] = [MdnsService::new("_http", ServiceProtocol::Tcp, 80)];
+///
+/// #[embassy_executor::task]
+/// async fn mdns_task(stack: embassy_net::Stack<'static>) -> ! {
+///     mdns::run(stack, MdnsConfig::new("greenhouse").with_services(&SERVICES)).await
+/// }
+/// ```
+///
+/// # Panic-free
+///
+/// An invalid config, a bind or multicast-join failure, send errors, and
+/// malformed packets are all logged at `warn` and never abort the task; an
+/// invalid config or bind failure parks it.
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
+pub async fn run(stack: embassy_net::Stack<'static>, config: MdnsConfig<'static>) -> ! {
+    use embassy_net::udp::{PacketMetadata, UdpSocket};
+    use embassy_time::{Duration, Timer};
+    use static_cell::StaticCell;
+
+    /// Socket buffer size: room for a few queued datagrams of `MDNS_MSG_MAX`.
+    const SOCKET_BUF_LEN: usize = 2 * MDNS_MSG_MAX;
+
+    static RX_META: StaticCell<[PacketMetadata; 4]> = StaticCell::new();
+    static RX_BUF: StaticCell<[u8; SOCKET_BUF_LEN]> = StaticCell::new();
+    static TX_META: StaticCell<[PacketMetadata; 4]> = StaticCell::new();
+    static TX_BUF: StaticCell<[u8; SOCKET_BUF_LEN]> = StaticCell::new();
+
+    // Park the task rather than returning — callers rely on `-> !`.
+    async fn park() -> ! {
+        loop {
+            Timer::after(Duration::from_secs(60)).await;
+        }
+    }
+
+    let responder = match MdnsResponder::new(config) {
+        Ok(r) => r,
+        Err(e) => {
+            log::warn!("mDNS: invalid config ({}); responder will not run", e);
+            park().await
+        }
+    };
+
+    stack.wait_config_up().await;
+    if let Err(e) = stack.join_multicast_group(MDNS_GROUP) {
+        log::warn!("mDNS: failed to join {} ({:?})", MDNS_GROUP, e);
+    }
+
+    let rx_meta = RX_META.init([PacketMetadata::EMPTY; 4]);
+    let rx_buf = RX_BUF.init([0u8; SOCKET_BUF_LEN]);
+    let tx_meta = TX_META.init([PacketMetadata::EMPTY; 4]);
+    let tx_buf = TX_BUF.init([0u8; SOCKET_BUF_LEN]);
+    let mut sock = UdpSocket::new(stack, rx_meta, rx_buf, tx_meta, tx_buf);
+
+    if let Err(e) = sock.bind(MDNS_PORT) {
+        log::warn!(
+            "mDNS: failed to bind port {} ({:?}); responder will not run",
+            MDNS_PORT,
+            e
+        );
+        park().await
+    }
+
+    let current_ip = || stack.config_v4().map(|c| c.address.address());
+    let mut rx_pkt = [0u8; MDNS_MSG_MAX];
+    let mut tx_pkt = [0u8; MDNS_MSG_MAX];
+
+    if let Some(ip) = current_ip() {
+        log::info!(
+            "mDNS responder up: {}.local -> {} ({} service(s))",
+            responder.config().hostname,
+            ip,
+            responder.config().services.len()
+        );
+        match responder.announce(ip, &mut tx_pkt) {
+            Ok(len) => {
+                for round in 0..2 {
+                    if round > 0 {
+                        Timer::after(Duration::from_secs(1)).await;
+                    }
+                    if let Err(e) = sock.send_to(&tx_pkt[..len], (MDNS_GROUP, MDNS_PORT)).await {
+                        log::warn!("mDNS announce failed: {:?}", e);
+                    }
+                }
+            }
+            Err(e) => log::warn!("mDNS announcement encode failed: {:?}", e),
+        }
+    }
+
+    loop {
+        let (n, meta) = match sock.recv_from(&mut rx_pkt).await {
+            Ok(r) => r,
+            Err(e) => {
+                log::warn!("mDNS recv_from error: {:?}", e);
+                continue;
+            }
+        };
+        let Some(ip) = current_ip() else {
+            continue;
+        };
+        let n = n.min(rx_pkt.len());
+        match responder.respond(&rx_pkt[..n], meta.endpoint.port, ip, &mut tx_pkt) {
+            Ok(Some(reply)) => {
+                let sent = if reply.unicast {
+                    sock.send_to(&tx_pkt[..reply.len], meta.endpoint).await
+                } else {
+                    sock.send_to(&tx_pkt[..reply.len], (MDNS_GROUP, MDNS_PORT))
+                        .await
+                };
+                if let Err(e) = sent {
+                    log::warn!("mDNS send_to failed: {:?}", e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("mDNS: malformed packet from {:?}: {:?}", meta.endpoint, e),
+        }
+    }
+}
+
+// ── Unit tests (host-testable codec) ─────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use juggler::wifi::ServiceProtocol;
+
+    const IP: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 42);
+    const HTTP: [MdnsService<'static>; 1] =
+        [MdnsService::new("_http", ServiceProtocol::Tcp, 80).with_txt(&[("path", "/")])];
+
+    fn responder() -> MdnsResponder<'static> {
+        MdnsResponder::new(MdnsConfig::new("greenhouse").with_services(&HTTP)).unwrap()
+    }
+
+    /// Appends `labels` as an uncompressed wire name.
+    fn push_name(pkt: &mut heapless::Vec<u8, 512>, labels: &[&str]) {
+        for label in labels {
+            pkt.push(label.len() as u8).unwrap();
+            pkt.extend_from_slice(label.as_bytes()).unwrap();
+        }
+        pkt.push(0).unwrap();
+    }
+
+    /// Builds a query with one question per `(labels, qtype, qclass)`.
+    fn query(id: u16, questions: &[(&[&str], u16, u16)]) -> heapless::Vec<u8, 512> {
+        let mut pkt = heapless::Vec::new();
+        pkt.extend_from_slice(&id.to_be_bytes()).unwrap();
+        pkt.extend_from_slice(&[0, 0]).unwrap();
+        pkt.extend_from_slice(&(questions.len() as u16).to_be_bytes())
+            .unwrap();
+        pkt.extend_from_slice(&[0; 6]).unwrap();
+        for (labels, qtype, qclass) in questions {
+            push_name(&mut pkt, labels);
+            pkt.extend_from_slice(&qtype.to_be_bytes()).unwrap();
+            pkt.extend_from_slice(&qclass.to_be_bytes()).unwrap();
+        }
+        pkt
+    }
+
+    fn counts(resp: &[u8]) -> [u16; 4] {
+        [4, 6, 8, 10].map(|i| u16::from_be_bytes([resp[i], resp[i + 1]]))
+    }
+
+    /// Returns `(type, class, ttl, rdata)` of each record after the header
+    /// and `qdcount` questions.
+    fn records(resp: &[u8]) -> heapless::Vec<(u16, u16, u32, &[u8]), 16> {
+        let [qd, an, ns, ar] = counts(resp);
+        let mut pos = HEADER_LEN;
+        for _ in 0..qd {
+            pos = skip_name(resp, pos).unwrap() + 4;
+        }
+        let mut out = heapless::Vec::new();
+        for _ in 0..(an + ns + ar) {
+            pos = skip_name(resp, pos).unwrap();
+            let f = &resp[pos..pos + 10];
+            let rtype = u16::from_be_bytes([f[0], f[1]]);
+            let class = u16::from_be_bytes([f[2], f[3]]);
+            let ttl = u32::from_be_bytes([f[4], f[5], f[6], f[7]]);
+            let rdlen = usize::from(u16::from_be_bytes([f[8], f[9]]));
+            pos += 10;
+            out.push((rtype, class, ttl, &resp[pos..pos + rdlen]))
+                .unwrap();
+            pos += rdlen;
+        }
+        assert_eq!(pos, resp.len(), "trailing bytes after records");
+        out
+    }
+
+    fn respond(pkt: &[u8], src_port: u16) -> Option<(MdnsReply, heapless::Vec<u8, 1024>)> {
+        let mut out = [0u8; MDNS_MSG_MAX];
+        let reply = responder().respond(pkt, src_port, IP, &mut out).unwrap()?;
+        let bytes = heapless::Vec::from_slice(&out[..reply.len]).unwrap();
+        Some((reply, bytes))
+    }
+
+    // ── Host record ───────────────────────────────────────────────────────────
+
+    #[test]
+    fn a_query_for_hostname_gets_multicast_a_record() {
+        let pkt = query(0, &[(&["greenhouse", "local"], TYPE_A, CLASS_IN)]);
+        let (reply, resp) = respond(&pkt, MDNS_PORT).unwrap();
+        assert!(!reply.unicast);
+        assert_eq!(&resp[..4], &[0, 0, 0x84, 0x00]);
+        assert_eq!(counts(&resp), [0, 1, 0, 0]);
+        let recs = records(&resp);
+        assert_eq!(
+            recs[0],
+            (TYPE_A, CLASS_IN | CLASS_CACHE_FLUSH, 120, &IP.octets()[..])
+        );
+    }
+
+    #[test]
+    fn hostname_match_is_case_insensitive() {
+        let pkt = query(0, &[(&["GreenHouse", "LOCAL"], TYPE_A, CLASS_IN)]);
+        assert!(respond(&pkt, MDNS_PORT).is_some());
+    }
+
+    #[test]
+    fn other_names_and_types_are_ignored() {
+        let other = query(0, &[(&["barn", "local"], TYPE_A, CLASS_IN)]);
+        assert!(respond(&other, MDNS_PORT).is_none());
+        let aaaa = query(0, &[(&["greenhouse", "local"], 28, CLASS_IN)]);
+        assert!(respond(&aaaa, MDNS_PORT).is_none());
+        let chaos = query(0, &[(&["greenhouse", "local"], TYPE_A, 3)]);
+        assert!(respond(&chaos, MDNS_PORT).is_none());
+    }
+
+    #[test]
+    fn responses_and_non_query_opcodes_are_ignored() {
+        let mut pkt = query(0, &[(&["greenhouse", "local"], TYPE_A, CLASS_IN)]);
+        pkt[2] = 0x84;
+        assert!(respond(&pkt, MDNS_PORT).is_none());
+        pkt[2] = 0x28; // opcode 5 (UPDATE)
+        assert!(respond(&pkt, MDNS_PORT).is_none());
+    }
+
+    #[test]
+    fn qu_bit_requests_unicast_reply() {
+        let pkt = query(
+            0,
+            &[(&["greenhouse", "local"], TYPE_A, CLASS_IN | CLASS_QU)],
+        );
+        let (reply, _) = respond(&pkt, MDNS_PORT).unwrap();
+        assert!(reply.unicast);
+    }
+
+    #[test]
+    fn legacy_unicast_echoes_id_and_question_and_caps_ttl() {
+        let pkt = query(0xBEEF, &[(&["greenhouse", "local"], TYPE_A, CLASS_IN)]);
+        let (reply, resp) = respond(&pkt, 50_000).unwrap();
+        assert!(reply.unicast);
+        assert_eq!(&resp[..2], &[0xBE, 0xEF]);
+        assert_eq!(counts(&resp), [1, 1, 0, 0]);
+        assert_eq!(&resp[HEADER_LEN..pkt.len()], &pkt[HEADER_LEN..]);
+        let recs = records(&resp);
+        assert_eq!(recs[0].1, CLASS_IN, "no cache-flush bit in legacy replies");
+        assert_eq!(recs[0].2, LEGACY_TTL_MAX_SECS);
+    }
+
+    // ── DNS-SD ────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn ptr_query_gets_ptr_with_srv_txt_a_additionals() {
+        let pkt = query(0, &[(&["_http", "_tcp", "local"], TYPE_PTR, CLASS_IN)]);
+        let (_, resp) = respond(&pkt, MDNS_PORT).unwrap();
+        assert_eq!(counts(&resp), [0, 1, 0, 3]);
+        let recs = records(&resp);
+        let types: heapless::Vec<u16, 4> = recs.iter().map(|r| r.0).collect();
+        assert_eq!(types, [TYPE_PTR, TYPE_A, TYPE_SRV, TYPE_TXT]);
+
+        // PTR rdata is the uncompressed instance name; PTR is a shared record.
+        let ptr = recs.iter().find(|r| r.0 == TYPE_PTR).unwrap();
+        assert_eq!(ptr.1, CLASS_IN);
+        assert!(name_matches(
+            ptr.3,
+            0,
+            &["greenhouse", "_http", "_tcp", "local"]
+        ));
+    }
+
+    #[test]
+    fn srv_record_carries_port_and_target() {
+        let pkt = query(
+            0,
+            &[(
+                &["greenhouse", "_http", "_tcp", "local"],
+                TYPE_SRV,
+                CLASS_IN,
+            )],
+        );
+        let (_, resp) = respond(&pkt, MDNS_PORT).unwrap();
+        let recs = records(&resp);
+        let srv = recs.iter().find(|r| r.0 == TYPE_SRV).unwrap();
+        assert_eq!(&srv.3[..6], &[0, 0, 0, 0, 0, 80]);
+        assert!(name_matches(srv.3, 6, &["greenhouse", "local"]));
+        assert!(recs.iter().any(|r| r.0 == TYPE_A), "A as additional");
+    }
+
+    #[test]
+    fn txt_record_encodes_key_value_pairs() {
+        let pkt = query(
+            0,
+            &[(
+                &["greenhouse", "_http", "_tcp", "local"],
+                TYPE_TXT,
+                CLASS_IN,
+            )],
+        );
+        let (_, resp) = respond(&pkt, MDNS_PORT).unwrap();
+        let recs = records(&resp);
+        assert_eq!(recs.len(), 1);
+        assert_eq!(recs[0].3, b"\x06path=/");
+    }
+
+    #[test]
+    fn empty_txt_is_single_zero_byte() {
+        let services = [MdnsService::new("_ssh", ServiceProtocol::Tcp, 22).with_instance("Node")];
+        let r = MdnsResponder::new(MdnsConfig::new("node").with_services(&services)).unwrap();
+        let pkt = query(
+            0,
+            &[(&["Node", "_ssh", "_tcp", "local"], TYPE_TXT, CLASS_IN)],
+        );
+        let mut out = [0u8; MDNS_MSG_MAX];
+        let reply = r.respond(&pkt, MDNS_PORT, IP, &mut out).unwrap().unwrap();
+        assert_eq!(records(&out[..reply.len])[0].3, &[0]);
+    }
+
+    #[test]
+    fn service_enumeration_lists_service_types() {
+        let pkt = query(
+            0,
+            &[(
+                &["_services", "_dns-sd", "_udp", "local"],
+                TYPE_PTR,
+                CLASS_IN,
+            )],
+        );
+        let (_, resp) = respond(&pkt, MDNS_PORT).unwrap();
+        let recs = records(&resp);
+        assert_eq!(recs.len(), 1);
+        assert!(name_matches(recs[0].3, 0, &["_http", "_tcp", "local"]));
+    }
+
+    #[test]
+    fn any_query_for_instance_returns_srv_and_txt() {
+        let pkt = query(
+            0,
+            &[(
+                &["greenhouse", "_http", "_tcp", "local"],
+                TYPE_ANY,
+                CLASS_ANY,
+            )],
+        );
+        let (_, resp) = respond(&pkt, MDNS_PORT).unwrap();
+        assert_eq!(counts(&resp), [0, 2, 0, 1]);
+    }
+
+    #[test]
+    fn answers_are_not_repeated_as_additionals() {
+        let pkt = query(
+            0,
+            &[
+                (&["_http", "_tcp", "local"], TYPE_PTR, CLASS_IN),
+                (&["greenhouse", "local"], TYPE_A, CLASS_IN),
+            ],
+        );
+        let (_, resp) = respond(&pkt, MDNS_PORT).unwrap();
+        assert_eq!(counts(&resp), [0, 2, 0, 2]);
+    }
+
+    // ── Compression and malformed input ───────────────────────────────────────
+
+    #[test]
+    fn compressed_second_question_is_matched() {
+        // Question 1: _http._tcp.local PTR; question 2: greenhouse + pointer
+        // to "local" inside question 1.
+        let mut pkt = query(0, &[(&["_http", "_tcp", "local"], TYPE_PTR, CLASS_IN)]);
+        pkt[5] = 2;
+        let local_at = HEADER_LEN + 1 + 5 + 1 + 4;
+        push_label(&mut pkt, "greenhouse");
+        pkt.extend_from_slice(&[0xC0, local_at as u8]).unwrap();
+        pkt.extend_from_slice(&TYPE_A.to_be_bytes()).unwrap();
+        pkt.extend_from_slice(&CLASS_IN.to_be_bytes()).unwrap();
+
+        let (_, resp) = respond(&pkt, MDNS_PORT).unwrap();
+        assert_eq!(counts(&resp)[1], 2);
+    }
+
+    fn push_label(pkt: &mut heapless::Vec<u8, 512>, label: &str) {
+        pkt.push(label.len() as u8).unwrap();
+        pkt.extend_from_slice(label.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn pointer_loop_does_not_match_or_hang() {
+        let mut pkt = query(0, &[]);
+        pkt[5] = 1;
+        pkt.extend_from_slice(&[0xC0, HEADER_LEN as u8]).unwrap(); // points at itself
+        pkt.extend_from_slice(&TYPE_A.to_be_bytes()).unwrap();
+        pkt.extend_from_slice(&CLASS_IN.to_be_bytes()).unwrap();
+        assert!(respond(&pkt, MDNS_PORT).is_none());
+    }
+
+    #[test]
+    fn malformed_packets_are_errors() {
+        let mut out = [0u8; MDNS_MSG_MAX];
+        let r = responder();
+        assert_eq!(
+            r.respond(&[0; 11], MDNS_PORT, IP, &mut out),
+            Err(MdnsError::TooShort)
+        );
+
+        let mut truncated = query(0, &[(&["greenhouse", "local"], TYPE_A, CLASS_IN)]);
+        truncated.truncate(truncated.len() - 2);
+        assert_eq!(
+            r.respond(&truncated, MDNS_PORT, IP, &mut out),
+            Err(MdnsError::Truncated)
+        );
+
+        let mut bad_label = query(0, &[]);
+        bad_label[5] = 1;
+        bad_label.extend_from_slice(&[0x40, 0, 0, 1, 0, 1]).unwrap();
+        assert_eq!(
+            r.respond(&bad_label, MDNS_PORT, IP, &mut out),
+            Err(MdnsError::MalformedName)
+        );
+
+        let mut overrun = query(0, &[]);
+        overrun[5] = 1;
+        overrun.extend_from_slice(&[10, b'a']).unwrap();
+        assert_eq!(
+            r.respond(&overrun, MDNS_PORT, IP, &mut out),
+            Err(MdnsError::MalformedName)
+        );
+    }
+
+    #[test]
+    fn small_output_buffer_is_an_error() {
+        let pkt = query(0, &[(&["greenhouse", "local"], TYPE_A, CLASS_IN)]);
+        let mut out = [0u8; 20];
+        assert_eq!(
+            responder().respond(&pkt, MDNS_PORT, IP, &mut out),
+            Err(MdnsError::BufferTooSmall)
+        );
+    }
+
+    // ── Announcement and config ───────────────────────────────────────────────
+
+    #[test]
+    fn announcement_contains_every_record() {
+        let mut out = [0u8; MDNS_MSG_MAX];
+        let len = responder().announce(IP, &mut out).unwrap();
+        let resp = &out[..len];
+        assert_eq!(counts(resp), [0, 5, 0, 0]);
+        let types: heapless::Vec<u16, 8> = records(resp).iter().map(|r| r.0).collect();
+        assert_eq!(types, [TYPE_A, TYPE_PTR, TYPE_SRV, TYPE_TXT, TYPE_PTR]);
+    }
+
+    #[test]
+    fn host_only_announcement_is_single_a_record() {
+        let r = MdnsResponder::new(MdnsConfig::new("node").with_ttl(60)).unwrap();
+        let mut out = [0u8; MDNS_MSG_MAX];
+        let len = r.announce(IP, &mut out).unwrap();
+        let recs = records(&out[..len]);
+        assert_eq!(recs.len(), 1);
+        assert_eq!(recs[0].2, 60);
+    }
+
+    #[test]
+    fn announcement_len_matches_shared_estimate() {
+        let r = responder();
+        let mut out = [0u8; MDNS_MSG_MAX];
+        let len = r.announce(IP, &mut out).unwrap();
+        assert_eq!(len, juggler::wifi::mdns_announcement_len(r.config()));
+    }
+
+    #[test]
+    fn largest_valid_config_fits_the_buffer() {
+        // Grow one TXT value until validation refuses it; the last accepted
+        // configuration must still encode into `MDNS_MSG_MAX`.
+        let value = "v".repeat(200);
+        let mut last_ok = None;
+        for len in 0..=value.len() {
+            let txt = [
+                ("a", &value[..len]),
+                ("b", value.as_str()),
+                ("c", value.as_str()),
+                ("d", value.as_str()),
+            ];
+            let services = [MdnsService::new("_http", ServiceProtocol::Tcp, 80).with_txt(&txt)];
+            if MdnsResponder::new(MdnsConfig::new("greenhouse").with_services(&services)).is_err() {
+                break;
+            }
+            last_ok = Some(len);
+        }
+        let len = last_ok.expect("base configuration must be valid");
+        assert!(len < value.len(), "limit never reached");
+        let txt = [
+            ("a", &value[..len]),
+            ("b", value.as_str()),
+            ("c", value.as_str()),
+            ("d", value.as_str()),
+        ];
+        let services = [MdnsService::new("_http", ServiceProtocol::Tcp, 80).with_txt(&txt)];
+        let r = MdnsResponder::new(MdnsConfig::new("greenhouse").with_services(&services)).unwrap();
+        let mut out = [0u8; MDNS_MSG_MAX];
+        assert_eq!(r.announce(IP, &mut out), Ok(MDNS_MSG_MAX));
+    }
+
+    #[test]
+    fn invalid_config_is_rejected() {
+        assert!(MdnsResponder::new(MdnsConfig::new("bad name")).is_err());
+    }
+}
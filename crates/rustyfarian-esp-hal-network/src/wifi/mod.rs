@@ -6,52 +6,150 @@
 //! were removed, and direct `smoltcp` integration was deleted in favour of
 //! `embassy-net`.  As a result this crate now exposes a single async entry
 //! point: [`WiFiManager::init_async`], which returns an [`AsyncWifiHandle`]
-//! wired into an `embassy-net` stack with automatic DHCPv4.
+//! wired into an `embassy-net` stack with automatic DHCPv4 (or a static
+//! IPv4 address, see [`WiFiConfig::with_static_ip`]).
 //!
 //! The `embassy` Cargo feature is therefore effectively required for any
 //! Wi-Fi use on bare-metal targets.
 //!
+//! # Chips
+//!
+//! The driver builds for `esp32c3`, `esp32c6`, `esp32s3`, and `esp32`; the
+//! API is the same on all four.  [`init_heap`] registers the heap layout
+//! each chip needs, so firmware does not have to carry per-chip
+//! `heap_allocator!` calls.
+//!
 //! # Quick start
 //!
 //! ```ignore
 //! use rustyfarian_esp_hal_network::wifi::{AsyncWifiHandle, WiFiManager, WiFiConfig, WiFiConfigExt};
 //!
 //! let peripherals = esp_hal::init(esp_hal::Config::default());
-//! esp_alloc::heap_allocator!(size: 72 * 1024);
+//! rustyfarian_esp_hal_network::wifi::init_heap();
 //!
 //! let config = WiFiConfig::new("MyNetwork", "password123")
 //!     .with_peripherals(peripherals.TIMG0, peripherals.SW_INTERRUPT, peripherals.WIFI);
 //! let AsyncWifiHandle { controller, stack, runner } = WiFiManager::init_async(config)?;
 //! // spawn `runner.run().await` and a task that owns `controller`
 //! ```
+//!
+//! # Multiple networks
+//!
+//! Build the config with [`WiFiConfig::from_networks`] and, in the task that
+//! owns the controller, call [`WiFiManager::connect_networks`] instead of
+//! `controller.connect_async()`; it scans, ranks the list, and falls back to
+//! the next candidate on failure.
+//!
+//! # Fast reconnect after deep sleep
+//!
+//! Keep a [`WifiFastConnectCache`] in RTC memory and associate with
+//! [`WiFiManager::connect_fast`]: it goes straight to the cached BSSID and
+//! channel, falls back to [`WiFiManager::connect_networks`] when that AP is
+//! gone, and returns the refreshed cache.  Before storing it, add the DHCP
+//! lease from [`AsyncWifiHandle::wait_for_ip`] with [`WiFiManager::ip_lease`]
+//! and the PMK with [`WiFiManager::derive_fast_connect_pmk`].
+//!
+//! # Link supervision
+//!
+//! Instead of writing a reconnect loop, make the controller task's body
+//! [`WiFiManager::supervise_link`]: it associates, waits for the link to
+//! drop, and retries with exponential backoff, publishing each
+//! [`WifiLinkState`] through a `static` [`WifiLinkWatch`] that other tasks
+//! can await.  Pass it a [`LinkQualityMonitor`] to have it sample the RSSI
+//! while the link is up.
+//!
+//! # Hostname and mDNS
+//!
+//! [`WiFiConfig::with_hostname`] sends a hostname to the DHCP server.  To
+//! also answer `<hostname>.local` and advertise DNS-SD services, spawn a
+//! task running [`mdns::run`] on the station stack with an [`MdnsConfig`].
+//!
+//! # SoftAP clients
+//!
+//! [`WiFiManager::init_softap_async`] hands back an empty [`ApStationTable`]
+//! carrying the config's `max_connections` and allowlist.  Wrap it in an
+//! [`ApStationMonitor`], loop on [`WiFiManager::next_ap_station_event`] in
+//! the controller task, and pass the monitor to the DHCP server; the monitor
+//! then lists the connected stations with their leased addresses and reports
+//! each [`ApStationEvent`].
+//!
+//! # IPv6
+//!
+//! With the `ipv6` feature, spawn a task running [`ipv6::run`] on the
+//! station stack: it assigns a link-local address and, once a router
+//! advertises a prefix, a SLAAC address.  Await
+//! [`AsyncWifiHandle::wait_for_ipv6`] for the latter.  The stack keeps its
+//! IPv4 DHCP client, so dual-stack and IPv6-only segments both work.
+
+#[cfg(any(feature = "ipv6", test))]
+pub mod ipv6;
+pub mod mdns;
+mod stations;
+
+pub use stations::ApStationMonitor;
 
 pub use juggler::wifi::{
-    validate_password, validate_ssid, wifi_disconnect_reason_name, ApConfig, ConnectMode,
-    TxPowerLevel, WiFiConfig, WifiDriver, WifiPowerSave, DEFAULT_TIMEOUT_SECS, PASSWORD_MAX_LEN,
-    POLL_INTERVAL_MS, SSID_MAX_LEN,
+    classify_disconnect, derive_pmk, insert_scan_result, netmask_prefix_len, pmk_applicable,
+    priority_order, rank_networks, scan_candidates, select_network, validate_ap_subnet,
+    validate_auth_method, validate_hostname, validate_ip_config, validate_link_quality_config,
+    validate_mdns_config, validate_network_list, validate_password, validate_phy, validate_ssid,
+    wifi_disconnect_reason_name, ApConfig, ApInfo, ApRejectReason, ApStation, ApStationEvent,
+    ApStationTable, ApStations, ApSubnet, AsyncWifiDriver, AuthMode, ConnectMode, Country,
+    DisconnectClass, DisconnectReason, IpConfig, LinkQuality, LinkQualityConfig, LinkQualityEvent,
+    LinkQualityMonitor, MdnsConfig, MdnsService, NetworkOrder, ScanResults, ServiceProtocol,
+    TxPowerLevel, WiFiConfig, WifiAuthMethod, WifiBandwidth, WifiDriver, WifiFastConnectCache,
+    WifiLinkAction, WifiLinkEvent, WifiLinkState, WifiLinkSupervisor, WifiNetwork, WifiNetworkList,
+    WifiPhy, WifiPowerSave, WifiProtocol, AP_ALLOWLIST_MAX, AP_MAX_CONNECTIONS_MAX,
+    DEFAULT_ANTENNA_GAIN_DBI, DEFAULT_TIMEOUT_SECS, FAST_CONNECT_TIMEOUT_SECS, HOSTNAME_MAX_LEN,
+    LINK_RETRY_BASE_MS, LINK_RETRY_MAX_MS, MAX_NETWORKS, MDNS_DEFAULT_TTL_SECS, MDNS_MAX_SERVICES,
+    MDNS_MAX_TXT_ENTRIES, PASSWORD_MAX_LEN, POLL_INTERVAL_MS, QUALITY_RECOVERED_DBM,
+    QUALITY_ROAM_DBM, QUALITY_WEAK_DBM, SCAN_MAX_RESULTS, SSID_MAX_LEN,
 };
 
-/// `embassy-net` `StackResources<N>` size for the SoftAP scaffold.
-///
-/// The AP `embassy-net` stack opened by [`WiFiManager::init_softap_async`]
-/// must reserve one socket slot per long-lived substrate task plus one
-/// spare.  The three substrate modules in `rustyfarian-esp-hal-network`
-/// each own one socket: `dhcp::run` (UDP), `dns_catchall::run` (UDP),
-/// `http_server::run` (TCP).  Adding a fourth long-lived socket-owning
-/// task in `rustyfarian-esp-hal-network` requires bumping this constant
-/// in lockstep — `embassy-net` returns `SocketAlreadyOpen` /
-/// `OutOfResources` on exhaustion rather than blocking.
+/// Default `embassy-net` socket budget of the station stack built by
+/// [`WiFiManager::init_async`].
 ///
-/// Cross-crate coupling: this constant is declared here (in the Wi-Fi
-/// crate) to avoid a circular dependency, but the socket count it encodes
-/// is owned by the provisioning crate's substrate modules.  If you add a
-/// fourth long-lived substrate task in `rustyfarian-esp-hal-network`,
-/// bump this constant here too.
+/// Covers the DHCP client, one TCP and one UDP socket — the baseline of
+/// `embassy-net`'s own examples — plus the [`mdns::run`] responder, with
+/// the `ipv6` feature the raw socket of [`ipv6::run`], and with the `mqtt`
+/// feature the MQTT client's TCP socket and the DNS resolver's socket.
+/// Raise it with `HalWifiConfig::with_sockets`.
+pub const STA_SOCKET_COUNT: usize =
+    3 + mdns::SOCKETS + IPV6_SOCKETS + MQTT_SOCKETS + RESOLVER_SOCKETS;
+
+/// Default `embassy-net` socket budget of the SoftAP stack built by
+/// [`WiFiManager::init_softap_async`].
 ///
-/// Promoted from a magic `4` in the second-pass PR review of #72 so the
-/// invariant is enforced at the source rather than via parallel comments
-/// in the example and the driver.
-pub const SUBSTRATE_SOCKET_COUNT: usize = 4;
+/// Sized for the provisioning substrate (DHCP, DNS catch-all, and HTTP
+/// portal; see `provisioning::SUBSTRATE_SOCKETS`) plus one spare, and with
+/// the `mqtt` feature the DNS resolver's socket.  The provisioning module
+/// checks at compile time that this default still covers the substrate.
+/// Raise it with `HalApConfig::with_sockets`.
+pub const AP_SOCKET_COUNT: usize = 4 + RESOLVER_SOCKETS;
+
+/// Sockets [`ipv6::run`] adds to [`STA_SOCKET_COUNT`].
+#[cfg(feature = "ipv6")]
+const IPV6_SOCKETS: usize = ipv6::SOCKETS;
+#[cfg(not(feature = "ipv6"))]
+const IPV6_SOCKETS: usize = 0;
+
+/// Sockets the MQTT client adds to [`STA_SOCKET_COUNT`].
+#[cfg(feature = "mqtt")]
+const MQTT_SOCKETS: usize = crate::mqtt::SOCKETS;
+#[cfg(not(feature = "mqtt"))]
+const MQTT_SOCKETS: usize = 0;
+
+/// Sockets `embassy-net` reserves on every stack for its DNS resolver,
+/// which the `mqtt` feature enables.
+#[cfg(feature = "mqtt")]
+pub(crate) const RESOLVER_SOCKETS: usize = 1;
+#[cfg(not(feature = "mqtt"))]
+pub(crate) const RESOLVER_SOCKETS: usize = 0;
+
+/// Former name of [`AP_SOCKET_COUNT`].
+#[deprecated(note = "use AP_SOCKET_COUNT; the substrate's own need is \
+                     provisioning::SUBSTRATE_SOCKETS")]
+pub const SUBSTRATE_SOCKET_COUNT: usize = AP_SOCKET_COUNT;
 
 pub use pennant::{NoLed, SimpleLed, StatusLed};
 
@@ -103,10 +201,7 @@ impl<P: embedded_hal::digital::OutputPin> StatusLed for ActiveLowLed<P> {
 // chip features only produce a working driver in combination with `embassy`.
 // Surface that as a compile-time error rather than silently falling through to
 // the host stub when a user enables a chip feature without `embassy`.
-#[cfg(all(
-    any(feature = "esp32c6", feature = "esp32c3"),
-    not(feature = "embassy")
-))]
+#[cfg(all(feature = "_wifi-chip", not(feature = "embassy")))]
 compile_error!(
     "rustyfarian-esp-hal-network on bare-metal requires the `embassy` feature \
      (esp-radio 0.18 is async-only). Enable both: --features <chip>,embassy"
@@ -114,39 +209,143 @@ compile_error!(
 
 // ─── Real implementation (behind chip + embassy feature gates) ──────────────
 
-#[cfg(all(feature = "embassy", any(feature = "esp32c6", feature = "esp32c3")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 mod driver {
+    extern crate alloc;
+
+    use core::pin::pin;
+
+    use embassy_futures::select::{select, Either};
     use embassy_net::{
         Config as NetConfig, DhcpConfig, Ipv4Address, Ipv4Cidr, Runner, Stack, StackResources,
         StaticConfigV4,
     };
+    use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+    use embassy_sync::watch::Watch;
+    use embassy_time::Timer;
     use esp_hal::interrupt::software::SoftwareInterruptControl;
     use esp_hal::timer::timg::TimerGroup;
     use esp_radio::wifi::ap::AccessPointConfig;
+    use esp_radio::wifi::scan::ScanConfig;
     use esp_radio::wifi::sta::StationConfig;
     use esp_radio::wifi::{
-        AuthenticationMethod, Config, ControllerConfig, Interface, PowerSaveMode, WifiController,
+        AccessPointStationEventInfo, AuthenticationMethod, Config, ConnectedStationInfo,
+        ControllerConfig, CountryInfo, DisconnectReason as RadioDisconnectReason, Interface,
+        PowerSaveMode, WifiController,
     };
     use juggler::wifi::{
-        validate_ap_config, validate_password, validate_ssid, ApConfig, TxPowerLevel, WiFiConfig,
-        WifiPowerSave,
+        insert_scan_result, netmask_prefix_len, pmk_applicable, priority_order, rank_networks,
+        validate_ap_config, validate_auth_method, validate_hostname, validate_ip_config,
+        validate_network_list, validate_password, validate_ssid, ApConfig, ApInfo, ApStationEvent,
+        ApStationTable, ApSubnet, AsyncWifiDriver, AuthMode, Country, DisconnectReason, IpConfig,
+        LinkQualityEvent, LinkQualityMonitor, ScanResults, TxPowerLevel, WiFiConfig,
+        WifiAuthMethod, WifiFastConnectCache, WifiLinkAction, WifiLinkEvent, WifiLinkState,
+        WifiLinkSupervisor, WifiNetworkList, WifiPowerSave, AP_CHANNEL_MAX,
     };
     use static_cell::StaticCell;
 
+    // ─── Heap ───────────────────────────────────────────────────────────────
+
+    /// Registers the heap regions the Wi-Fi driver needs with `esp-alloc`,
+    /// laid out for the selected chip.
+    ///
+    /// Call once, right after `esp_hal::init` and before
+    /// [`WiFiManager::init_async`] or [`WiFiManager::init_softap_async`];
+    /// later calls do nothing.
+    ///
+    /// | Chip       | Regions                                          |
+    /// |:-----------|:-------------------------------------------------|
+    /// | `esp32c3`  | 72 KiB DRAM                                      |
+    /// | `esp32c6`  | 64 KiB reclaimed bootloader RAM + 36 KiB DRAM    |
+    /// | `esp32s3`  | 64 KiB reclaimed bootloader RAM + 36 KiB DRAM    |
+    /// | `esp32`    | 64 KiB reclaimed bootloader RAM + 36 KiB DRAM    |
+    ///
+    /// On every chip but the ESP32-C3, 64 KiB comes from the RAM the ESP-IDF
+    /// second-stage bootloader used (`dram2_seg`), which is free once the
+    /// application runs.  Applications that need more heap can register
+    /// further regions (e.g. PSRAM via `esp_alloc::psram_allocator!`) before
+    /// or after this call.
+    pub fn init_heap() {
+        static INITIALISED: StaticCell<()> = StaticCell::new();
+        if INITIALISED.try_init(()).is_none() {
+            return;
+        }
+        #[cfg(feature = "esp32c3")]
+        esp_alloc::heap_allocator!(size: 72 * 1024);
+        #[cfg(not(feature = "esp32c3"))]
+        {
+            esp_alloc::heap_allocator!(#[esp_hal::ram(reclaimed)] size: 64 * 1024);
+            esp_alloc::heap_allocator!(size: 36 * 1024);
+        }
+    }
+
     /// Wi-Fi configuration bundled with the hardware peripherals needed for init.
     ///
     /// Built from a [`WiFiConfig`] via [`with_peripherals`][WiFiConfigExt::with_peripherals],
-    /// then passed to [`WiFiManager::init_async`].
-    pub struct HalWifiConfig<'a> {
+    /// then passed to [`WiFiManager::init_async`].  `SOCKETS` is the
+    /// `embassy-net` socket budget of the station stack; see
+    /// [`with_sockets`][Self::with_sockets].
+    pub struct HalWifiConfig<'a, const SOCKETS: usize = { super::STA_SOCKET_COUNT }> {
         ssid: &'a str,
         password: &'a str,
+        networks: WifiNetworkList<'a>,
+        ip: IpConfig,
+        auth: WifiAuthMethod<'a>,
+        hostname: Option<&'a str>,
         power_save: WifiPowerSave,
         tx_power: TxPowerLevel,
+        country: Option<Country>,
+        seed: Option<u64>,
         timg0: esp_hal::peripherals::TIMG0<'static>,
         sw_interrupt: esp_hal::peripherals::SW_INTERRUPT<'static>,
         wifi: esp_hal::peripherals::WIFI<'static>,
     }
 
+    impl<'a, const SOCKETS: usize> HalWifiConfig<'a, SOCKETS> {
+        /// Sizes the station stack for `N` concurrent sockets instead of the
+        /// default [`STA_SOCKET_COUNT`](super::STA_SOCKET_COUNT).
+        ///
+        /// Count every socket open at the same time: the DHCP client (unless
+        /// the IP is static), the [`mdns::run`](super::mdns::run) responder if
+        /// spawned, and the application's own TCP and UDP sockets.
+        /// `embassy-net` fails to open a socket beyond the budget rather than
+        /// waiting for a slot.
+        ///
+        /// ```ignore
+        /// let config = WiFiConfig::new(SSID, PASSWORD)
+        ///     .with_peripherals(peripherals.TIMG0, peripherals.SW_INTERRUPT, peripherals.WIFI)
+        ///     .with_sockets::<8>();
+        /// ```
+        pub fn with_sockets<const N: usize>(self) -> HalWifiConfig<'a, N> {
+            HalWifiConfig {
+                ssid: self.ssid,
+                password: self.password,
+                networks: self.networks,
+                ip: self.ip,
+                auth: self.auth,
+                hostname: self.hostname,
+                power_save: self.power_save,
+                tx_power: self.tx_power,
+                country: self.country,
+                seed: self.seed,
+                timg0: self.timg0,
+                sw_interrupt: self.sw_interrupt,
+                wifi: self.wifi,
+            }
+        }
+
+        /// Seeds the stack's local-port randomisation with `seed` instead of
+        /// the boot clock.
+        ///
+        /// The clock reads much the same on every boot, so firmware that
+        /// wants unpredictable ephemeral ports should pass a value from the
+        /// hardware RNG, e.g. `u64::from(rng.random()) << 32 | u64::from(rng.random())`.
+        pub fn with_seed(mut self, seed: u64) -> Self {
+            self.seed = Some(seed);
+            self
+        }
+    }
+
     /// Extension trait that adds [`with_peripherals`][WiFiConfigExt::with_peripherals]
     /// to [`WiFiConfig`], producing a [`HalWifiConfig`] ready for
     /// [`WiFiManager::init_async`].
@@ -169,10 +368,16 @@ mod driver {
             wifi: esp_hal::peripherals::WIFI<'static>,
         ) -> HalWifiConfig<'a> {
             HalWifiConfig {
+                networks: self.networks(),
+                ip: self.ip,
+                auth: self.auth,
+                hostname: self.hostname,
                 ssid: self.ssid,
                 password: self.password,
                 power_save: self.power_save,
                 tx_power: self.tx_power,
+                country: self.country,
+                seed: None,
                 timg0,
                 sw_interrupt,
                 wifi,
@@ -198,6 +403,38 @@ mod driver {
         }
     }
 
+    /// Maps a personal-mode [`WifiAuthMethod`] to the `esp-radio` station
+    /// auth method; 802.1X is not wired up on bare metal yet.
+    fn station_auth_method(auth: &WifiAuthMethod<'_>) -> Result<AuthenticationMethod, WifiError> {
+        match auth {
+            WifiAuthMethod::Wpa2Personal => Ok(AuthenticationMethod::Wpa2Personal),
+            WifiAuthMethod::Wpa3Personal => Ok(AuthenticationMethod::Wpa3Personal),
+            WifiAuthMethod::Wpa2Wpa3Personal => Ok(AuthenticationMethod::Wpa2Wpa3Personal),
+            WifiAuthMethod::EapPeap { .. } | WifiAuthMethod::EapTls { .. } => {
+                log::error!("{} is not supported on bare-metal targets", auth.name());
+                Err(WifiError::ConfigureFailed)
+            }
+        }
+    }
+
+    /// Controller config carrying `country`, or the `esp-radio` default
+    /// (`"CN"`) when none is set.
+    ///
+    /// `esp-radio` 0.18 always programs channels 1–13 with the country code
+    /// (its `schan`/`nchan` are not configurable yet), so on bare metal the
+    /// code is advertised but the channel range is enforced only by
+    /// [`validate_ap_config`] and the scan/ESP-NOW channel lists.  Channel
+    /// 14, which [`Country::Jp`] allows, is outside that range, so
+    /// [`WiFiManager::init_softap_async`] rejects it.
+    fn controller_config(country: Option<Country>) -> ControllerConfig {
+        match country {
+            Some(country) => {
+                ControllerConfig::default().with_country_info(CountryInfo::from(country.code()))
+            }
+            None => ControllerConfig::default(),
+        }
+    }
+
     fn map_power_save(ps: WifiPowerSave) -> PowerSaveMode {
         match ps {
             WifiPowerSave::None => PowerSaveMode::None,
@@ -250,32 +487,22 @@ mod driver {
         ///
         /// # Heap requirement
         ///
-        /// The caller must set up the heap via `esp_alloc::heap_allocator!`
-        /// **before** calling this method.  On ESP32-C3 a single 72 KiB region
-        /// suffices; on ESP32-C6 two regions are needed (64 KiB reclaimed IRAM
-        /// for Wi-Fi DMA + 36 KiB DRAM).  See the chip-specific async examples.
+        /// The heap must be set up **before** calling this method — call
+        /// [`init_heap`] for the per-chip layout, or register regions with
+        /// `esp_alloc::heap_allocator!` yourself.
         ///
         /// # Socket budget
         ///
-        /// The `embassy-net` stack is sized with `StackResources<3>`, which
-        /// covers DHCP plus one TCP and one UDP socket — the baseline used by
-        /// `embassy-net`'s own examples.  Applications that need more
-        /// concurrent sockets must build their own stack on top of the
-        /// `Interface` returned by `esp_radio::wifi::new(..)`:
+        /// The `embassy-net` stack has room for `SOCKETS` sockets, by default
+        /// [`STA_SOCKET_COUNT`](super::STA_SOCKET_COUNT): DHCP plus one TCP
+        /// and one UDP socket — the baseline used by `embassy-net`'s own
+        /// examples — and the [`mdns::run`][super::mdns::run] responder's UDP
+        /// socket.  Applications that need more concurrent sockets size the
+        /// stack with [`HalWifiConfig::with_sockets`].  The socket table is
+        /// allocated on the heap, once per boot (see below).
         ///
-        /// ```ignore
-        /// let (controller, interfaces) =
-        ///     esp_radio::wifi::new(peripherals.WIFI, ControllerConfig::default())?;
-        /// // configure `controller` as in `init_async` above ...
-        /// static RESOURCES: StaticCell<StackResources<8>> = StaticCell::new();
-        /// let resources = RESOURCES.init(StackResources::<8>::new());
-        /// let (stack, runner) = embassy_net::new(
-        ///     interfaces.station,
-        ///     NetConfig::dhcpv4(DhcpConfig::default()),
-        ///     resources,
-        ///     seed,
-        /// );
-        /// ```
+        /// The stack's local-port RNG is seeded from the boot clock unless
+        /// [`HalWifiConfig::with_seed`] supplies a seed.
         ///
         /// # TX-power policy
         ///
@@ -291,22 +518,66 @@ mod driver {
         ///
         /// # One-shot
         ///
-        /// Call at most once per boot — a `static` `StackResources` is
-        /// initialised via [`StaticCell`] and a second call will panic.
-        pub fn init_async(config: HalWifiConfig<'_>) -> Result<AsyncWifiHandle, WifiError> {
+        /// Call at most once per boot — a one-shot [`StaticCell`] guards the
+        /// station stack and a second call will panic.
+        ///
+        /// # Static IP
+        ///
+        /// With [`IpConfig::Static`] (see [`WiFiConfig::with_static_ip`]) the
+        /// stack is built with `embassy_net::Config::ipv4_static` instead of
+        /// DHCPv4; the address is validated with [`validate_ip_config`] first.
+        /// [`AsyncWifiHandle::wait_for_ip`] then returns the configured
+        /// address as soon as the link is up.
+        ///
+        /// # Multiple networks
+        ///
+        /// For a config built with [`WiFiConfig::from_networks`] every entry
+        /// is validated here and the highest-priority one is applied as the
+        /// initial station config.  Fallback happens at connect time: use
+        /// [`WiFiManager::connect_networks`] in the `wifi_task`.
+        ///
+        /// # Authentication
+        ///
+        /// WPA2, WPA3-SAE, and WPA2/WPA3 transition mode are supported via
+        /// [`WiFiConfig::with_auth`].  The EAP variants of
+        /// [`WifiAuthMethod`] are ESP-IDF-only for now and fail with
+        /// [`WifiError::ConfigureFailed`].
+        ///
+        /// # Hostname
+        ///
+        /// A hostname set with [`WiFiConfig::with_hostname`] is checked with
+        /// [`validate_hostname`] and sent to the DHCP server as option 12.
+        /// It is not used with a static IP; either way, answering
+        /// `<hostname>.local` is the job of [`mdns::run`][super::mdns::run].
+        pub fn init_async<const SOCKETS: usize>(
+            config: HalWifiConfig<'_, SOCKETS>,
+        ) -> Result<AsyncWifiHandle, WifiError> {
             validate_ssid(config.ssid).map_err(|_| WifiError::ConfigureFailed)?;
-            validate_password(config.password).map_err(|_| WifiError::ConfigureFailed)?;
+            validate_auth_method(&config.auth, config.password)
+                .map_err(|_| WifiError::ConfigureFailed)?;
+            validate_network_list(&config.networks).map_err(|_| WifiError::ConfigureFailed)?;
+            for network in &config.networks {
+                station_auth_method(&network.auth)?;
+            }
+            validate_ip_config(&config.ip).map_err(|_| WifiError::ConfigureFailed)?;
+            if let Some(hostname) = config.hostname {
+                validate_hostname(hostname).map_err(|_| WifiError::ConfigureFailed)?;
+            }
+            if SOCKETS == 0 && matches!(config.ip, IpConfig::Dhcp) {
+                // The DHCP client needs a socket of its own.
+                return Err(WifiError::ConfigureFailed);
+            }
 
             // 1. Start the scheduler (esp-radio requires a running scheduler).
             let timg = TimerGroup::new(config.timg0);
             let sw_ints = SoftwareInterruptControl::new(config.sw_interrupt);
             esp_rtos::start(timg.timer0, sw_ints.software_interrupt0);
 
-            // 2. Construct the Wi-Fi controller with a default ControllerConfig
-            //    (empty station config).  Credentials are applied via an explicit
+            // 2. Construct the Wi-Fi controller with the configured country and
+            //    an empty station config.  Credentials are applied via an explicit
             //    `set_config` call immediately after `wifi::new` returns (step 3).
             let (mut controller, interfaces) =
-                esp_radio::wifi::new(config.wifi, ControllerConfig::default())
+                esp_radio::wifi::new(config.wifi, controller_config(config.country))
                     .map_err(WifiError::Driver)?;
 
             // 3. Apply station credentials.  esp_radio::wifi::new already called
@@ -315,7 +586,8 @@ mod driver {
             //    so wifi_task's first connect_async uses the real credentials.
             let station = StationConfig::default()
                 .with_ssid(config.ssid)
-                .with_password(config.password.into());
+                .with_password(config.password.into())
+                .with_auth_method(station_auth_method(&config.auth)?);
             controller
                 .set_config(&Config::Station(station))
                 .map_err(WifiError::Driver)?;
@@ -337,13 +609,17 @@ mod driver {
             //
             // Default to Low (8.5 dBm) if the caller left tx_power at Medium (the
             // wifi_pure default). Medium (~13 dBm) still causes auth failures on
-            // PCB-antenna boards; Low is the safe baseline for bare-metal.
-            let quarter_dbm = if config.tx_power == TxPowerLevel::default() {
-                TxPowerLevel::Low.to_quarter_dbm()
+            // PCB-antenna boards; Low is the safe baseline for bare-metal.  A
+            // configured country can only lower the result further.
+            let level = if config.tx_power == TxPowerLevel::default() {
+                TxPowerLevel::Low
             } else {
-                config.tx_power.to_quarter_dbm()
+                config.tx_power
             };
-            set_tx_power_or_log(quarter_dbm);
+            let level = config
+                .country
+                .map_or(level, |country| country.clamp_tx_power(level));
+            set_tx_power_or_log(level.to_quarter_dbm());
 
             // 5. Power save (non-fatal if it fails).
             let ps = map_power_save(config.power_save);
@@ -369,24 +645,17 @@ mod driver {
                 config.power_save,
             );
 
-            // 5. Build the embassy-net stack on top of the STA interface.
-            //    DHCP + one TCP + one UDP baseline (matches embassy-net examples).
-            static RESOURCES: StaticCell<StackResources<3>> = StaticCell::new();
-            let resources = RESOURCES.init(StackResources::<3>::new());
-
-            // Seed the stack's local-port RNG from the monotonic clock.
-            // This is not cryptographic — it is used only by `embassy-net` for
-            // ephemeral source-port randomization.  Upgrade to the `esp-hal`
-            // RNG peripheral if `init_async` ever gains access to it.
-            let seed = esp_hal::time::Instant::now()
-                .duration_since_epoch()
-                .as_micros();
+            // 5. Build the embassy-net stack on top of the STA interface,
+            //    with room for `SOCKETS` sockets (`STA_SOCKET_COUNT` unless
+            //    the caller chose otherwise).
+            static STA_STACK: StaticCell<()> = StaticCell::new();
+            let resources = stack_resources::<SOCKETS>(&STA_STACK);
 
             let (stack, runner) = embassy_net::new(
                 interfaces.station,
-                NetConfig::dhcpv4(DhcpConfig::default()),
+                station_net_config(&config.ip, config.hostname),
                 resources,
-                seed,
+                stack_seed(config.seed),
             );
 
             Ok(AsyncWifiHandle {
@@ -397,43 +666,821 @@ mod driver {
         }
     }
 
+    impl WiFiManager {
+        /// Associates with the best reachable network in `networks`, falling
+        /// back to the next candidate on failure.
+        ///
+        /// Scans once, orders the list with [`rank_networks`] (visible
+        /// networks by priority then RSSI, then unseen networks by priority),
+        /// and for each candidate applies the station config and awaits
+        /// `connect_async`.  A failed scan is logged and the list is tried in
+        /// [`priority_order`].  Returns the index of the network that
+        /// connected.
+        ///
+        /// Call this from the task that owns the controller, in place of a
+        /// bare `controller.connect_async()`, both for the initial connect and
+        /// after `wait_for_disconnect_async` returns.  The station config left
+        /// applied on failure is the last candidate tried.
+        ///
+        /// # Errors
+        ///
+        /// [`WifiError::ConfigureFailed`] if the list is empty or invalid;
+        /// otherwise the driver error from the last candidate when every
+        /// candidate failed.
+        pub async fn connect_networks(
+            controller: &mut WifiController<'static>,
+            networks: &WifiNetworkList<'_>,
+        ) -> Result<usize, WifiError> {
+            validate_network_list(networks).map_err(|_| WifiError::ConfigureFailed)?;
+            Self::connect_ranked(controller, networks)
+                .await
+                .map(|(index, _)| index)
+        }
+
+        /// [`Self::connect_networks`] without the list validation, also
+        /// returning what the driver reported for the association.
+        async fn connect_ranked(
+            controller: &mut WifiController<'static>,
+            networks: &WifiNetworkList<'_>,
+        ) -> Result<(usize, ConnectedStationInfo), WifiError> {
+            let order = if networks.len() > 1 {
+                match controller.scan_async(&ScanConfig::default()).await {
+                    Ok(aps) => {
+                        log::info!("Wi-Fi scan found {} access point(s)", aps.len());
+                        rank_networks(
+                            networks,
+                            aps.iter().map(|ap| (ap.ssid.as_str(), ap.signal_strength)),
+                        )
+                    }
+                    Err(e) => {
+                        log::warn!("Wi-Fi scan failed, trying networks by priority: {:?}", e);
+                        priority_order(networks)
+                    }
+                }
+            } else {
+                priority_order(networks)
+            };
+
+            let mut last_err = WifiError::ConfigureFailed;
+            for (attempt, &index) in order.iter().enumerate() {
+                let Some(network) = networks.get(index) else {
+                    continue;
+                };
+                log::info!(
+                    "Wi-Fi candidate {}/{} (SSID len={}, priority={})",
+                    attempt + 1,
+                    order.len(),
+                    network.ssid.len(),
+                    network.priority
+                );
+                let auth_method = match station_auth_method(&network.auth) {
+                    Ok(method) => method,
+                    Err(e) => {
+                        last_err = e;
+                        continue;
+                    }
+                };
+                let station = StationConfig::default()
+                    .with_ssid(network.ssid)
+                    .with_password(network.password.into())
+                    .with_auth_method(auth_method);
+                // Same mode as before, so `set_config` does not restart the
+                // radio; TX-power and power-save settings are preserved.
+                if let Err(e) = controller.set_config(&Config::Station(station)) {
+                    log::warn!("Wi-Fi candidate {} config failed: {:?}", attempt + 1, e);
+                    last_err = WifiError::Driver(e);
+                    continue;
+                }
+                match controller.connect_async().await {
+                    Ok(info) => return Ok((index, info)),
+                    Err(e) => {
+                        log::warn!("Wi-Fi candidate {} failed: {:?}", attempt + 1, e);
+                        last_err = WifiError::Driver(e);
+                    }
+                }
+            }
+            Err(last_err)
+        }
+
+        /// Associates with the AP recorded in `cache` without scanning,
+        /// falling back to [`WiFiManager::connect_networks`] when that fails,
+        /// and returns the cache to keep for the next wake.
+        ///
+        /// When `cache` is valid and belongs to one of `networks` (see
+        /// [`WifiFastConnectCache::network_index`]), the station config is
+        /// pinned to the cached BSSID and channel and, for WPA2-Personal, the
+        /// cached PMK replaces the passphrase.  A vanished AP makes the
+        /// driver report `NoApFound` after probing that one channel, so the
+        /// fallback starts quickly.  An invalid or foreign cache goes
+        /// straight to the full connect.
+        ///
+        /// The returned cache carries the BSSID and channel the station
+        /// associated with and, for WPA2-Personal networks, the PMK from
+        /// `cache` when the credentials are unchanged.  A new PMK is never
+        /// derived here, since PBKDF2 would stall the executor for tens of
+        /// milliseconds mid-connect; fill it in with
+        /// [`WiFiManager::derive_fast_connect_pmk`] before storing the
+        /// cache.  It holds no IP lease either; add one with
+        /// [`WifiFastConnectCache::with_ip_lease`] and [`WiFiManager::ip_lease`]
+        /// once [`AsyncWifiHandle::wait_for_ip`] resolves.
+        ///
+        /// # Errors
+        ///
+        /// As for [`WiFiManager::connect_networks`].
+        pub async fn connect_fast(
+            controller: &mut WifiController<'static>,
+            networks: &WifiNetworkList<'_>,
+            cache: &WifiFastConnectCache,
+        ) -> Result<WifiFastConnectCache, WifiError> {
+            validate_network_list(networks).map_err(|_| WifiError::ConfigureFailed)?;
+
+            let cached = match cache.network_index(networks) {
+                Some(index) => Self::connect_cached(controller, networks, index, cache)
+                    .await
+                    .map(|info| (index, info)),
+                None => None,
+            };
+            let (index, info) = match cached {
+                Some(connected) => connected,
+                None => Self::connect_ranked(controller, networks).await?,
+            };
+            let network = networks.get(index).ok_or(WifiError::ConfigureFailed)?;
+            Ok(cache.refreshed_reusing_pmk(
+                network.ssid,
+                network.password,
+                pmk_applicable(&network.auth, network.password),
+                info.bssid,
+                info.channel,
+            ))
+        }
+
+        /// One association attempt against the cached AP; `None` on any
+        /// failure, which is logged.
+        async fn connect_cached(
+            controller: &mut WifiController<'static>,
+            networks: &WifiNetworkList<'_>,
+            index: usize,
+            cache: &WifiFastConnectCache,
+        ) -> Option<ConnectedStationInfo> {
+            let network = networks.get(index)?;
+            let auth_method = station_auth_method(&network.auth).ok()?;
+            let psk = cache
+                .psk_hex()
+                .filter(|_| pmk_applicable(&network.auth, network.password));
+            let password = psk.as_deref().unwrap_or(network.password);
+            let station = StationConfig::default()
+                .with_ssid(network.ssid)
+                .with_password(password.into())
+                .with_auth_method(auth_method)
+                .with_bssid(cache.bssid())
+                .with_channel(cache.channel());
+            log::info!(
+                "Wi-Fi fast connect: cached AP on channel {}",
+                cache.channel()
+            );
+            let result = match controller.set_config(&Config::Station(station)) {
+                Ok(()) => controller.connect_async().await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(info) => {
+                    log::info!("Wi-Fi fast connect succeeded");
+                    Some(info)
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Wi-Fi fast connect failed, falling back to a full connect: {:?}",
+                        e
+                    );
+                    None
+                }
+            }
+        }
+
+        /// Adds the PMK that [`WiFiManager::connect_fast`] left out, if the
+        /// cache's network in `networks` is WPA2-Personal and has none yet.
+        ///
+        /// This runs PBKDF2, blocking the executor for tens of milliseconds
+        /// on a cache miss, so call it once the work of the wake is done,
+        /// right before storing the cache and sleeping.  Caches that already
+        /// carry a PMK, and those for no network in `networks`, are returned
+        /// unchanged at no cost.
+        pub fn derive_fast_connect_pmk(
+            cache: &WifiFastConnectCache,
+            networks: &WifiNetworkList<'_>,
+        ) -> WifiFastConnectCache {
+            match cache
+                .network_index(networks)
+                .and_then(|index| networks.get(index))
+            {
+                Some(network) if pmk_applicable(&network.auth, network.password) => {
+                    cache.with_derived_pmk(network.ssid, network.password)
+                }
+                _ => *cache,
+            }
+        }
+
+        /// The station's current address as the lease to store with
+        /// [`WifiFastConnectCache::with_ip_lease`].
+        ///
+        /// Without a gateway there is nothing a static configuration could
+        /// route through, so that yields [`IpConfig::Dhcp`] (no lease).
+        pub fn ip_lease(config: &StaticConfigV4) -> IpConfig {
+            match config.gateway {
+                Some(gateway) => IpConfig::Static {
+                    ip: config.address.address(),
+                    netmask: config.address.netmask(),
+                    gateway,
+                    dns: config.dns_servers.first().copied(),
+                },
+                None => IpConfig::Dhcp,
+            }
+        }
+    }
+
+    impl WiFiManager {
+        /// Scans for nearby access points.
+        ///
+        /// Call from the task that owns the controller.  The station
+        /// interface must be started, which [`WiFiManager::init_async`]
+        /// guarantees; scanning is not supported on an AP-only controller.
+        /// While connected, the radio leaves the home channel briefly per
+        /// scanned channel.  Results are sorted strongest-first and capped
+        /// at [`SCAN_MAX_RESULTS`][juggler::wifi::SCAN_MAX_RESULTS].
+        pub async fn scan(
+            controller: &mut WifiController<'static>,
+        ) -> Result<ScanResults, WifiError> {
+            let aps = controller
+                .scan_async(&ScanConfig::default())
+                .await
+                .map_err(WifiError::Driver)?;
+            let mut results = ScanResults::new();
+            for ap in &aps {
+                insert_scan_result(
+                    &mut results,
+                    ApInfo::new(
+                        ap.ssid.as_str(),
+                        ap.bssid,
+                        ap.channel,
+                        ap.signal_strength,
+                        map_auth_method(ap.auth_method),
+                    ),
+                );
+            }
+            log::debug!(
+                "Wi-Fi scan: {} access point(s) seen, {} kept",
+                aps.len(),
+                results.len()
+            );
+            Ok(results)
+        }
+    }
+
+    // ─── Link quality ───────────────────────────────────────────────────
+
+    impl WiFiManager {
+        /// Returns the RSSI of the associated access point (dBm), as seen in
+        /// its last beacon.
+        ///
+        /// Fails with [`WifiError::Driver`] when the station is not running.
+        pub fn rssi(controller: &WifiController<'static>) -> Result<i8, WifiError> {
+            let rssi = controller.rssi().map_err(WifiError::Driver)?;
+            Ok(rssi.clamp(i8::MIN.into(), 0) as i8)
+        }
+
+        /// Reads the current RSSI and feeds it to `monitor`, returning the
+        /// threshold event it triggers, if any.
+        ///
+        /// Call periodically from the task that owns the controller while
+        /// the link is up, and `monitor.reset()` after a disconnect.  Events
+        /// are logged here (`warn` for weak / roam, `info` for recovered), so
+        /// callers only need to match on them to act — raise the TX power,
+        /// schedule a [`scan`](Self::scan), and so on.
+        pub fn sample_link_quality(
+            controller: &WifiController<'static>,
+            monitor: &mut LinkQualityMonitor,
+        ) -> Result<Option<LinkQualityEvent>, WifiError> {
+            let event = monitor.sample(Self::rssi(controller)?);
+            log_link_quality_event(event);
+            Ok(event)
+        }
+    }
+
+    fn log_link_quality_event(event: Option<LinkQualityEvent>) {
+        match event {
+            Some(LinkQualityEvent::Weak { rssi_dbm }) => {
+                log::warn!("Wi-Fi link weak ({} dBm)", rssi_dbm)
+            }
+            Some(LinkQualityEvent::RoamCandidate { rssi_dbm }) => {
+                log::warn!("Wi-Fi link poor ({} dBm), roam candidate", rssi_dbm)
+            }
+            Some(LinkQualityEvent::Recovered { rssi_dbm }) => {
+                log::info!("Wi-Fi link recovered ({} dBm)", rssi_dbm)
+            }
+            None => {}
+        }
+    }
+
+    // ─── AsyncWifiDriver ────────────────────────────────────────────────
+
+    /// [`AsyncWifiDriver`] over an `esp-radio` [`WifiController`], so shared
+    /// connection logic written against the trait runs on bare metal too.
+    ///
+    /// Wrap the controller from [`AsyncWifiHandle`] in the task that owns it:
+    ///
+    /// ```ignore
+    /// #[embassy_executor::task]
+    /// async fn wifi_task(controller: WifiController<'static>) {
+    ///     let mut driver = EspHalWifiDriver::new(controller);
+    ///     shared::bring_up(&mut driver).await;
+    /// }
+    /// ```
+    ///
+    /// `esp_radio::wifi::new` (called by [`WiFiManager::init_async`]) already
+    /// starts the radio, so [`start`](AsyncWifiDriver::start) has nothing left
+    /// to do and always succeeds.
+    pub struct EspHalWifiDriver {
+        controller: WifiController<'static>,
+    }
+
+    impl EspHalWifiDriver {
+        /// Wraps a controller obtained from [`WiFiManager::init_async`].
+        pub fn new(controller: WifiController<'static>) -> Self {
+            Self { controller }
+        }
+
+        /// The wrapped controller, for calls the trait does not cover.
+        pub fn controller(&mut self) -> &mut WifiController<'static> {
+            &mut self.controller
+        }
+
+        /// Returns the wrapped controller.
+        pub fn into_inner(self) -> WifiController<'static> {
+            self.controller
+        }
+    }
+
+    impl AsyncWifiDriver for EspHalWifiDriver {
+        type Error = WifiError;
+
+        /// Configures a WPA2-Personal network, or an open one when
+        /// `password` is empty; use [`WiFiConfig::with_auth`] with
+        /// [`WiFiManager::init_async`] for WPA3.
+        async fn configure(&mut self, ssid: &str, password: &str) -> Result<(), WifiError> {
+            validate_ssid(ssid).map_err(|_| WifiError::ConfigureFailed)?;
+            validate_password(password).map_err(|_| WifiError::ConfigureFailed)?;
+            let auth_method = if password.is_empty() {
+                AuthenticationMethod::None
+            } else {
+                AuthenticationMethod::Wpa2Personal
+            };
+            let station = StationConfig::default()
+                .with_ssid(ssid)
+                .with_password(password.into())
+                .with_auth_method(auth_method);
+            self.controller
+                .set_config(&Config::Station(station))
+                .map_err(WifiError::Driver)
+        }
+
+        async fn start(&mut self) -> Result<(), WifiError> {
+            Ok(())
+        }
+
+        async fn connect(&mut self) -> Result<(), WifiError> {
+            self.controller
+                .connect_async()
+                .await
+                .map(|_| ())
+                .map_err(WifiError::Driver)
+        }
+
+        async fn disconnect(&mut self) -> Result<(), WifiError> {
+            self.controller
+                .disconnect_async()
+                .await
+                .map(|_| ())
+                .map_err(WifiError::Driver)
+        }
+
+        fn is_connected(&self) -> Result<bool, WifiError> {
+            Ok(self.controller.is_connected())
+        }
+
+        async fn scan(&mut self) -> Result<ScanResults, WifiError> {
+            WiFiManager::scan(&mut self.controller).await
+        }
+    }
+
+    // ─── Link supervision ───────────────────────────────────────────────
+
+    /// Number of receivers a [`WifiLinkWatch`] supports.
+    ///
+    /// Each task that calls `watch.receiver()` holds one slot for its
+    /// lifetime; four covers a status LED, an MQTT task, and two spares.
+    pub const LINK_WATCH_RECEIVERS: usize = 4;
+
+    /// Watch channel through which [`WiFiManager::supervise_link`] publishes
+    /// every [`WifiLinkState`] change.
+    ///
+    /// Declare it as a `static` so both the supervisor task and its
+    /// observers can borrow it for `'static`:
+    ///
+    /// ```ignore
+    /// static LINK: WifiLinkWatch = WifiLinkWatch::new();
+    /// ```
+    pub type WifiLinkWatch = Watch<CriticalSectionRawMutex, WifiLinkState, LINK_WATCH_RECEIVERS>;
+
+    impl WiFiManager {
+        /// Keeps the station associated for as long as the future is polled.
+        ///
+        /// Runs a [`WifiLinkSupervisor`]: associates via
+        /// [`WiFiManager::connect_networks`], waits for the link to drop,
+        /// then retries after an exponential backoff
+        /// ([`LINK_RETRY_BASE_MS`][juggler::wifi::LINK_RETRY_BASE_MS] doubling
+        /// up to [`LINK_RETRY_MAX_MS`][juggler::wifi::LINK_RETRY_MAX_MS]) that resets on every successful
+        /// association.  Each state change is sent to `link`, so other tasks
+        /// can `receiver().changed().await` instead of polling the controller.
+        ///
+        /// With `quality` set to `(monitor, period_ms)`, the RSSI is sampled
+        /// every `period_ms` while the link is up, as by
+        /// [`sample_link_quality`](Self::sample_link_quality), and the
+        /// monitor is reset after each disconnect.
+        ///
+        /// Call it as the body of the task that owns the controller — it
+        /// replaces the hand-written reconnect loop:
+        ///
+        /// ```ignore
+        /// #[embassy_executor::task]
+        /// async fn wifi_link(mut controller: WifiController<'static>) {
+        ///     let mut monitor = LinkQualityMonitor::new(LinkQualityConfig::default()).unwrap();
+        ///     WiFiManager::supervise_link(
+        ///         &mut controller,
+        ///         &NETWORKS,
+        ///         &LINK,
+        ///         Some((&mut monitor, 10_000)),
+        ///     )
+        ///     .await
+        /// }
+        /// ```
+        pub async fn supervise_link(
+            controller: &mut WifiController<'static>,
+            networks: &WifiNetworkList<'_>,
+            link: &WifiLinkWatch,
+            mut quality: Option<(&mut LinkQualityMonitor, u64)>,
+        ) -> ! {
+            let sender = link.sender();
+            let mut supervisor = WifiLinkSupervisor::new();
+            let mut event = WifiLinkEvent::Start;
+            loop {
+                let action = supervisor.handle(event);
+                sender.send(supervisor.state());
+                event = match action {
+                    Some(WifiLinkAction::Connect) => {
+                        match Self::connect_networks(controller, networks).await {
+                            Ok(index) => {
+                                log::info!("Wi-Fi link up (network {})", index);
+                                WifiLinkEvent::Connected
+                            }
+                            Err(e) => WifiLinkEvent::Disconnected {
+                                reason: error_reason_code(&e),
+                            },
+                        }
+                    }
+                    Some(WifiLinkAction::Wait { delay_ms }) => {
+                        log::info!(
+                            "Wi-Fi link down (retry {}), reconnecting in {} ms",
+                            supervisor.retry_count(),
+                            delay_ms
+                        );
+                        Timer::after_millis(delay_ms).await;
+                        WifiLinkEvent::RetryTimerElapsed
+                    }
+                    Some(WifiLinkAction::WaitForDisconnect) => {
+                        let disconnected = controller.wait_for_disconnect_async();
+                        let result = match quality.as_mut() {
+                            None => disconnected.await,
+                            Some((monitor, period_ms)) => {
+                                // Kept alive across samples so no disconnect
+                                // event is missed between them.
+                                let mut disconnected = pin!(disconnected);
+                                loop {
+                                    let tick = Timer::after_millis(*period_ms);
+                                    match select(disconnected.as_mut(), tick).await {
+                                        Either::First(result) => break result,
+                                        Either::Second(()) => {
+                                            if let Err(e) =
+                                                Self::sample_link_quality(controller, monitor)
+                                            {
+                                                log::debug!("RSSI sample failed: {:?}", e);
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        };
+                        if let Some((monitor, _)) = quality.as_mut() {
+                            monitor.reset();
+                        }
+                        let reason = match result {
+                            Ok(info) => disconnect_reason_code(info.reason),
+                            Err(e) => error_reason_code(&WifiError::Driver(e)),
+                        };
+                        let typed = DisconnectReason::from_code(reason);
+                        log::warn!(
+                            "Wi-Fi disconnected (reason {} {}, {})",
+                            reason,
+                            typed.name().unwrap_or("unmapped"),
+                            typed.class().name()
+                        );
+                        WifiLinkEvent::Disconnected { reason }
+                    }
+                    // Nothing here feeds `Stop`, and every action above yields
+                    // an event that is valid in the resulting state.  Recover
+                    // rather than spin if that invariant is ever broken.
+                    Some(WifiLinkAction::Stop) | None => {
+                        log::error!(
+                            "Wi-Fi link supervisor stalled in {:?}; restarting",
+                            supervisor.state()
+                        );
+                        supervisor = WifiLinkSupervisor::new();
+                        WifiLinkEvent::Start
+                    }
+                };
+            }
+        }
+    }
+
+    /// Extracts the disconnect reason code from a connect error, or `0` if
+    /// the error did not come from a disconnect event.
+    fn error_reason_code(error: &WifiError) -> u16 {
+        match error {
+            WifiError::Driver(esp_radio::wifi::WifiError::Disconnected(info)) => {
+                disconnect_reason_code(info.reason)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Converts `esp-radio`'s [`DisconnectReason`][RadioDisconnectReason]
+    /// back to the raw `wifi_err_reason_t` code understood by
+    /// [`DisconnectReason::from_code`].
+    ///
+    /// `esp-radio` keeps its own `from_raw` private and marks the enum
+    /// `#[non_exhaustive]`, so the table is mirrored here; variants added in
+    /// a later release map to `0`.
+    fn disconnect_reason_code(reason: RadioDisconnectReason) -> u16 {
+        use RadioDisconnectReason as R;
+        match reason {
+            R::Unspecified => 1,
+            R::AuthenticationExpired => 2,
+            R::AuthenticationLeave => 3,
+            R::DisassociatedDueToInactivity => 4,
+            R::AssociationTooMany => 5,
+            R::Class2FrameFromNonAuthenticatedStation => 6,
+            R::Class3FrameFromNonAssociatedStation => 7,
+            R::AssociationLeave => 8,
+            R::AssociationNotAuthenticated => 9,
+            R::DisassociatedPowerCapabilityBad => 10,
+            R::DisassociatedUnsupportedChannel => 11,
+            R::BssTransitionDisassociated => 12,
+            R::IeInvalid => 13,
+            R::MicFailure => 14,
+            R::FourWayHandshakeTimeout => 15,
+            R::GroupKeyUpdateTimeout => 16,
+            R::IeIn4wayDiffers => 17,
+            R::GroupCipherInvalid => 18,
+            R::PairwiseCipherInvalid => 19,
+            R::AkmpInvalid => 20,
+            R::UnsupportedRsnIeVersion => 21,
+            R::InvalidRsnIeCapabilities => 22,
+            R::_802_1xAuthenticationFailed => 23,
+            R::CipherSuiteRejected => 24,
+            R::TdlsPeerUnreachable => 25,
+            R::TdlsUnspecified => 26,
+            R::SspRequestedDisassociation => 27,
+            R::NoSspRoamingAgreement => 28,
+            R::BadCipherOrAkm => 29,
+            R::NotAuthorizedThisLocation => 30,
+            R::ServiceChangePercludesTs => 31,
+            R::UnspecifiedQos => 32,
+            R::NotEnoughBandwidth => 33,
+            R::MissingAcks => 34,
+            R::ExceededTxOp => 35,
+            R::StationLeaving => 36,
+            R::EndBlockAck => 37,
+            R::UnknownBlockAck => 38,
+            R::Timeout => 39,
+            R::PeerInitiated => 46,
+            R::AccessPointInitiatedDisassociation => 47,
+            R::InvalidFtActionFrameCount => 48,
+            R::InvalidPmkid => 49,
+            R::InvalidMde => 50,
+            R::InvalidFte => 51,
+            R::TransmissionLinkEstablishmentFailed => 67,
+            R::AlterativeChannelOccupied => 68,
+            R::BeaconTimeout => 200,
+            R::NoAccessPointFound => 201,
+            R::AuthenticationFailed => 202,
+            R::AssociationFailed => 203,
+            R::HandshakeTimeout => 204,
+            R::ConnectionFailed => 205,
+            R::AccessPointTsfReset => 206,
+            R::Roaming => 207,
+            R::AssociationComebackTimeTooLong => 208,
+            R::SaQueryTimeout => 209,
+            R::NoAccessPointFoundWithCompatibleSecurity => 210,
+            R::NoAccessPointFoundInAuthmodeThreshold => 211,
+            R::NoAccessPointFoundInRssiThreshold => 212,
+            _ => 0,
+        }
+    }
+
+    /// Allocates a socket table for `SOCKETS` sockets and leaks it, giving
+    /// the stack the `'static` resources it needs.
+    ///
+    /// A `static` cannot depend on the const parameter, so the table lives
+    /// on the heap.  `once` is the stack's one-shot guard and is claimed
+    /// first: a second call panics instead of leaking another table.
+    fn stack_resources<const SOCKETS: usize>(
+        once: &'static StaticCell<()>,
+    ) -> &'static mut StackResources<SOCKETS> {
+        once.init(());
+        alloc::boxed::Box::leak(alloc::boxed::Box::new(StackResources::<SOCKETS>::new()))
+    }
+
+    /// Returns `seed`, or a value read from the monotonic clock.
+    ///
+    /// Not cryptographic either way — `embassy-net` uses it only for
+    /// ephemeral source-port randomisation.
+    fn stack_seed(seed: Option<u64>) -> u64 {
+        seed.unwrap_or_else(|| {
+            esp_hal::time::Instant::now()
+                .duration_since_epoch()
+                .as_micros()
+        })
+    }
+
+    /// Builds the `embassy-net` config for the STA interface from an
+    /// [`IpConfig`] that has already passed [`validate_ip_config`] and a
+    /// hostname that has passed [`validate_hostname`].
+    fn station_net_config(ip: &IpConfig, hostname: Option<&str>) -> NetConfig {
+        match *ip {
+            IpConfig::Dhcp => {
+                let mut dhcp = DhcpConfig::default();
+                // Validation caps the hostname at 32 bytes, the capacity of
+                // `DhcpConfig::hostname`, so the conversion cannot fail.
+                dhcp.hostname = hostname.and_then(|h| h.try_into().ok());
+                if let Some(h) = hostname {
+                    log::info!("Wi-Fi DHCP hostname: {}", h);
+                }
+                NetConfig::dhcpv4(dhcp)
+            }
+            IpConfig::Static {
+                ip,
+                netmask,
+                gateway,
+                dns,
+            } => {
+                // Validation guarantees a contiguous mask; `unwrap_or(24)` is
+                // unreachable but avoids a panic path in firmware.
+                let prefix = netmask_prefix_len(netmask).unwrap_or(24);
+                let mut static_cfg = StaticConfigV4 {
+                    address: Ipv4Cidr::new(ip, prefix),
+                    gateway: Some(gateway),
+                    dns_servers: Default::default(),
+                };
+                if let Some(dns) = dns {
+                    // Capacity is 3; the first push cannot fail.
+                    let _ = static_cfg.dns_servers.push(dns);
+                }
+                log::info!("Wi-Fi static IP {}/{} via {}", ip, prefix, gateway);
+                NetConfig::ipv4_static(static_cfg)
+            }
+        }
+    }
+
+    /// Maps the `esp-radio` auth method reported in a scan record to the
+    /// platform-independent [`AuthMode`].
+    fn map_auth_method(auth: Option<AuthenticationMethod>) -> AuthMode {
+        match auth {
+            Some(AuthenticationMethod::None) => AuthMode::Open,
+            Some(AuthenticationMethod::Wep) => AuthMode::Wep,
+            Some(AuthenticationMethod::Wpa) => AuthMode::WpaPersonal,
+            Some(AuthenticationMethod::Wpa2Personal) => AuthMode::Wpa2Personal,
+            Some(AuthenticationMethod::WpaWpa2Personal) => AuthMode::WpaWpa2Personal,
+            Some(AuthenticationMethod::Wpa2Enterprise) => AuthMode::Wpa2Enterprise,
+            Some(AuthenticationMethod::Wpa3Personal) => AuthMode::Wpa3Personal,
+            Some(AuthenticationMethod::Wpa2Wpa3Personal) => AuthMode::Wpa2Wpa3Personal,
+            Some(AuthenticationMethod::WapiPersonal) => AuthMode::WapiPersonal,
+            Some(AuthenticationMethod::Owe) => AuthMode::Owe,
+            Some(AuthenticationMethod::Wpa3EntSuiteB192Bit) => AuthMode::Wpa3Enterprise,
+            // `AuthenticationMethod` is `#[non_exhaustive]`; newer modes map
+            // to `Unknown` until this crate learns about them.
+            _ => AuthMode::Unknown,
+        }
+    }
+
     impl AsyncWifiHandle {
         /// Awaits until the `embassy-net` stack has an IPv4 configuration
         /// (either via DHCP or static) and returns the full configuration:
         /// CIDR address, default gateway, and DNS servers.
         ///
+        /// With the `ipv6` feature the stack can come up on IPv6 alone; this
+        /// keeps waiting until IPv4 is configured as well, so on an
+        /// IPv6-only segment it never returns — use
+        /// [`wait_for_ipv6`](Self::wait_for_ipv6) there.
+        ///
         /// For more control (custom timeout, concurrent LED animation),
         /// poll [`embassy_net::Stack::config_v4`] directly alongside other
         /// futures with `embassy_futures::select`.
         pub async fn wait_for_ip(&self) -> embassy_net::StaticConfigV4 {
-            self.stack.wait_config_up().await;
-            self.stack
-                .config_v4()
-                .expect("stack reports config up but has no IPv4 config")
+            loop {
+                self.stack.wait_config_up().await;
+                if let Some(config) = self.stack.config_v4() {
+                    return config;
+                }
+                embassy_time::Timer::after(embassy_time::Duration::from_millis(CONFIG_POLL_MS))
+                    .await;
+            }
+        }
+
+        /// Awaits until the station has a routable IPv6 address and returns
+        /// its configuration: CIDR address, default router, and DNS servers.
+        ///
+        /// The address comes from SLAAC, so a task must be running
+        /// [`ipv6::run`](super::ipv6::run) on the stack; the link-local
+        /// address it assigns first does not count.  Never returns on a
+        /// segment without IPv6 router advertisements.
+        #[cfg(feature = "ipv6")]
+        pub async fn wait_for_ipv6(&self) -> embassy_net::StaticConfigV6 {
+            loop {
+                self.stack.wait_config_up().await;
+                if let Some(config) = self.stack.config_v6() {
+                    if !config.address.address().is_unicast_link_local() {
+                        return config;
+                    }
+                }
+                embassy_time::Timer::after(embassy_time::Duration::from_millis(CONFIG_POLL_MS))
+                    .await;
+            }
         }
     }
 
+    /// Poll interval of [`AsyncWifiHandle::wait_for_ip`] and
+    /// [`AsyncWifiHandle::wait_for_ipv6`] while the stack is up on the other
+    /// IP version only.
+    const CONFIG_POLL_MS: u64 = 250;
+
     // ─── SoftAP (AP-mode) lifecycle ────────────────────────────────────────────
 
-    /// Fixed IP address of the SoftAP interface (`192.168.4.1`).
+    /// Default IP address of the SoftAP interface (`192.168.4.1`).
     ///
     /// Clients obtain addresses in the `192.168.4.0/24` subnet via DHCP (when a
-    /// DHCP server is running on top of the AP stack).  The provisioning crate
-    /// references this constant so the AP IP is never restated as a magic literal.
-    pub const AP_IP: Ipv4Address = Ipv4Address::new(192, 168, 4, 1);
+    /// DHCP server is running on top of the AP stack).  An [`ApConfig`] built
+    /// with [`with_ip`][juggler::wifi::ApConfig::with_ip] moves the AP
+    /// elsewhere; [`SoftApHandle::subnet`] always holds the address in use.
+    pub const AP_IP: Ipv4Address = ApSubnet::DEFAULT.ip;
 
     /// SoftAP configuration bundled with the hardware peripherals needed for init.
     ///
     /// Built from an [`ApConfig`] via
     /// [`with_ap_peripherals`][ApConfigExt::with_ap_peripherals], then passed to
-    /// [`WiFiManager::init_softap_async`].
-    pub struct HalApConfig<'a> {
+    /// [`WiFiManager::init_softap_async`].  `SOCKETS` is the `embassy-net`
+    /// socket budget of the AP stack; see [`with_sockets`][Self::with_sockets].
+    pub struct HalApConfig<'a, const SOCKETS: usize = { super::AP_SOCKET_COUNT }> {
         ap: ApConfig<'a>,
+        seed: Option<u64>,
         timg0: esp_hal::peripherals::TIMG0<'static>,
         sw_interrupt: esp_hal::peripherals::SW_INTERRUPT<'static>,
         wifi: esp_hal::peripherals::WIFI<'static>,
     }
 
+    impl<'a, const SOCKETS: usize> HalApConfig<'a, SOCKETS> {
+        /// Sizes the AP stack for `N` concurrent sockets instead of the
+        /// default [`AP_SOCKET_COUNT`](super::AP_SOCKET_COUNT).
+        ///
+        /// A provisioning session needs at least
+        /// `provisioning::SUBSTRATE_SOCKETS`; add one for every further
+        /// socket the firmware opens on the AP stack while it runs.
+        pub fn with_sockets<const N: usize>(self) -> HalApConfig<'a, N> {
+            HalApConfig {
+                ap: self.ap,
+                seed: self.seed,
+                timg0: self.timg0,
+                sw_interrupt: self.sw_interrupt,
+                wifi: self.wifi,
+            }
+        }
+
+        /// Seeds the stack's local-port randomisation with `seed` instead of
+        /// the boot clock; see [`HalWifiConfig::with_seed`].
+        pub fn with_seed(mut self, seed: u64) -> Self {
+            self.seed = Some(seed);
+            self
+        }
+    }
+
     /// Extension trait that adds
     /// [`with_ap_peripherals`][ApConfigExt::with_ap_peripherals] to [`ApConfig`],
     /// producing a [`HalApConfig`] ready for
@@ -458,6 +1505,7 @@ mod driver {
         ) -> HalApConfig<'a> {
             HalApConfig {
                 ap: self,
+                seed: None,
                 timg0,
                 sw_interrupt,
                 wifi,
@@ -472,8 +1520,8 @@ mod driver {
     ///
     /// - A `net_task` that owns the [`Runner`] and calls `runner.run().await`.
     /// - A `wifi_task` that owns the [`WifiController`] and waits on station
-    ///   events directly via
-    ///   [`WifiController::wait_for_access_point_connected_event_async`] —
+    ///   events via [`WiFiManager::next_ap_station_event`] (or directly via
+    ///   [`WifiController::wait_for_access_point_connected_event_async`]) —
     ///   **the AP radio is already started by the time `init_softap_async`
     ///   returns** (`set_config(Config::AccessPoint(_))` triggers
     ///   `esp_wifi_start()` internally in `esp-radio 0.18`).  There is no
@@ -489,24 +1537,34 @@ mod driver {
         /// Runner for the network task — `runner.run().await` must be polled
         /// continuously in a dedicated task.
         pub runner: Runner<'static, Interface<'static>>,
+        /// AP address, netmask, and DHCP pool the stack was configured with,
+        /// for the DHCP and DNS tasks running on top of it.
+        pub subnet: ApSubnet,
+        /// Empty station table enforcing the config's `max_connections` and
+        /// allowlist; wrap it in an [`ApStationMonitor`](super::ApStationMonitor).
+        pub stations: ApStationTable,
+        /// Socket budget the stack was built with (the `SOCKETS` parameter
+        /// of [`HalApConfig`]).
+        pub sockets: usize,
     }
 
-    // Separate StaticCell for the AP stack so calling both `init_async` and
-    // `init_softap_async` in one boot does not panic on the second `.init()`.
-    static AP_RESOURCES: StaticCell<StackResources<{ super::SUBSTRATE_SOCKET_COUNT }>> =
-        StaticCell::new();
+    // Separate one-shot guard for the AP stack so calling both `init_async`
+    // and `init_softap_async` in one boot does not panic on the second `.init()`.
+    static AP_STACK: StaticCell<()> = StaticCell::new();
 
     impl WiFiManager {
         /// Initialises the scheduler and the Wi-Fi radio in SoftAP mode, applies
         /// the AP configuration, clamps TX power, and builds the `embassy-net`
-        /// stack with a static IPv4 address (`192.168.4.1/24`).
+        /// stack with the static IPv4 address of [`ApConfig::subnet`]
+        /// (`192.168.4.1/24` by default).
         ///
         /// # AP configuration
         ///
         /// The access point is WPA2-protected when `config.ap.password` is
         /// `Some(_)`, or open when it is `None`.  Open APs emit a log warning
         /// at `warn` level because anyone in radio range can reach the portal
-        /// endpoints.
+        /// endpoints.  Channel 14 is rejected even with [`Country::Jp`]:
+        /// `esp-radio` 0.18 only programs channels 1–13.
         ///
         /// # TX-power policy
         ///
@@ -519,6 +1577,9 @@ mod driver {
         /// (e.g. a captive-portal restricted to a small room) can pass an
         /// explicit [`TxPowerLevel`] via
         /// [`ApConfig::with_tx_power`][juggler::wifi::ApConfig::with_tx_power].
+        /// With [`ApConfig::with_country`][juggler::wifi::ApConfig::with_country]
+        /// the level is further capped by the domain limit, and the country
+        /// code is passed to the radio.
         ///
         /// TX-power clamping must happen **after** `set_config()`, which is
         /// what triggers `esp_wifi_start()`.  Failure is non-fatal and logged
@@ -526,24 +1587,46 @@ mod driver {
         ///
         /// # Static IP
         ///
-        /// The AP stack is wired to `192.168.4.1/24` with the AP itself as
-        /// the default gateway.  No DNS servers are configured here; a
-        /// captive-portal DNS catch-all is the provisioning crate's concern.
-        /// The `AP_IP` const in this crate holds the same address for
-        /// callers that need to reference it without restating the literal.
+        /// The AP stack is wired to the address and netmask of
+        /// [`ApConfig::subnet`] with the AP itself as the default gateway.
+        /// No DHCP or DNS server runs here; the returned
+        /// [`SoftApHandle::subnet`] carries the pool for the provisioning
+        /// crate's DHCP task, which serves at most 11 addresses within one
+        /// /24.
         ///
         /// # One-shot per boot
         ///
-        /// Call at most once per boot — a `static` `StackResources` is
-        /// initialised via [`StaticCell`] and a second call will panic.
+        /// Call at most once per boot — a one-shot [`StaticCell`] guards the
+        /// AP stack and a second call will panic.
         /// If you need both STA and AP in the same firmware, call
         /// [`WiFiManager::init_async`] first for STA, then
         /// `init_softap_async` for AP; the scheduler is started by
         /// `init_async`, so `init_softap_async` must be called afterwards.
         /// Calling `init_softap_async` before `init_async` also works, but
         /// calling either twice is not supported.
-        pub fn init_softap_async(config: HalApConfig<'_>) -> Result<SoftApHandle, WifiError> {
+        ///
+        /// # Socket budget
+        ///
+        /// The stack has room for `SOCKETS` sockets, by default
+        /// [`AP_SOCKET_COUNT`](super::AP_SOCKET_COUNT), which covers a
+        /// provisioning session plus one spare; size it with
+        /// [`HalApConfig::with_sockets`] and seed it with
+        /// [`HalApConfig::with_seed`].  [`SoftApHandle::sockets`] reports the
+        /// budget so the provisioning session can check it.
+        pub fn init_softap_async<const SOCKETS: usize>(
+            config: HalApConfig<'_, SOCKETS>,
+        ) -> Result<SoftApHandle, WifiError> {
             validate_ap_config(&config.ap).map_err(|_| WifiError::ConfigureFailed)?;
+            // `validate_ap_config` allows channel 14 for `Country::Jp`, but
+            // esp-radio 0.18 programs channels 1–13 whatever the country.
+            if config.ap.channel > AP_CHANNEL_MAX {
+                log::error!(
+                    "SoftAP channel {} is not available on bare metal (esp-radio programs 1–{})",
+                    config.ap.channel,
+                    AP_CHANNEL_MAX
+                );
+                return Err(WifiError::ConfigureFailed);
+            }
 
             if config.ap.password.is_none() {
                 log::warn!(
@@ -557,11 +1640,11 @@ mod driver {
             let sw_ints = SoftwareInterruptControl::new(config.sw_interrupt);
             esp_rtos::start(timg.timer0, sw_ints.software_interrupt0);
 
-            // 2. Construct the Wi-Fi controller with default ControllerConfig.
+            // 2. Construct the Wi-Fi controller with the configured country.
             //    `esp_radio::wifi::new` starts the radio driver; AP credentials
             //    are applied via `set_config` immediately after (step 3).
             let (mut controller, interfaces) =
-                esp_radio::wifi::new(config.wifi, ControllerConfig::default())
+                esp_radio::wifi::new(config.wifi, controller_config(config.ap.country))
                     .map_err(WifiError::Driver)?;
 
             // 3. Build the esp-radio AP config and apply it.
@@ -595,7 +1678,7 @@ mod driver {
             // provided by esp-wifi-sys, always linked by esp-radio, and only valid
             // after `esp_wifi_start()`.  `to_quarter_dbm()` returns values in
             // [8, 78], within the SDK-documented valid range [8, 84].
-            set_tx_power_or_log(config.ap.tx_power.to_quarter_dbm());
+            set_tx_power_or_log(config.ap.effective_tx_power().to_quarter_dbm());
 
             log::info!(
                 "SoftAP configured (ssid len={}, auth={}, channel={}, max_conn={})",
@@ -609,21 +1692,18 @@ mod driver {
                 config.ap.max_connections,
             );
 
-            // 5. Build the embassy-net stack with a static AP IP.
-            //    The capacity is `SUBSTRATE_SOCKET_COUNT` (DHCP UDP + DNS UDP +
-            //    HTTP TCP + one spare); see the crate-root constant for the full
-            //    coupling rationale.  A future fourth substrate task that opens
-            //    a socket must bump the constant in lockstep.
-            let resources =
-                AP_RESOURCES.init(StackResources::<{ super::SUBSTRATE_SOCKET_COUNT }>::new());
-
-            let seed = esp_hal::time::Instant::now()
-                .duration_since_epoch()
-                .as_micros();
+            // 5. Build the embassy-net stack with a static AP IP and room for
+            //    `SOCKETS` sockets (`AP_SOCKET_COUNT` unless the caller chose
+            //    otherwise); the provisioning session checks the budget
+            //    against what its substrate tasks open.
+            let resources = stack_resources::<SOCKETS>(&AP_STACK);
 
+            // `validate_ap_config` has checked the netmask is contiguous.
+            let subnet = config.ap.subnet;
+            let prefix = subnet.prefix_len().ok_or(WifiError::ConfigureFailed)?;
             let static_cfg = StaticConfigV4 {
-                address: Ipv4Cidr::new(AP_IP, 24),
-                gateway: Some(AP_IP),
+                address: Ipv4Cidr::new(subnet.ip, prefix),
+                gateway: Some(subnet.ip),
                 dns_servers: Default::default(),
             };
 
@@ -631,15 +1711,89 @@ mod driver {
                 interfaces.access_point,
                 NetConfig::ipv4_static(static_cfg),
                 resources,
-                seed,
+                stack_seed(config.seed),
             );
 
+            log::info!("SoftAP address {}/{}", subnet.ip, prefix);
+
             Ok(SoftApHandle {
                 controller,
                 stack,
                 runner,
+                subnet,
+                stations: ApStationTable::from_config(&config.ap),
+                sockets: SOCKETS,
             })
         }
+
+        /// Waits for the next association or disassociation on the SoftAP,
+        /// records it in `stations`, and returns the resulting event.
+        ///
+        /// A station the monitor rejects — not on the allowlist, or over
+        /// `max_connections` — is deauthenticated before
+        /// [`ApStationEvent::Rejected`] is returned; its disassociation is
+        /// then swallowed.  Call it in a loop from the task that owns the
+        /// controller:
+        ///
+        /// ```ignore
+        /// #[embassy_executor::task]
+        /// async fn wifi_task(controller: WifiController<'static>, stations: &'static ApStationMonitor) {
+        ///     loop {
+        ///         if let Err(e) = WiFiManager::next_ap_station_event(&controller, stations).await {
+        ///             log::warn!("AP event error: {}", e);
+        ///         }
+        ///     }
+        /// }
+        /// ```
+        pub async fn next_ap_station_event(
+            controller: &WifiController<'_>,
+            stations: &super::ApStationMonitor,
+        ) -> Result<ApStationEvent, WifiError> {
+            loop {
+                match controller
+                    .wait_for_access_point_connected_event_async()
+                    .await
+                    .map_err(WifiError::Driver)?
+                {
+                    AccessPointStationEventInfo::Connected(info) => {
+                        let event = stations.associate(info.mac);
+                        if let ApStationEvent::Rejected { reason, .. } = event {
+                            log::warn!(
+                                "SoftAP: rejecting station {:02x?} ({:?})",
+                                info.mac,
+                                reason
+                            );
+                            deauth_station_or_log(info.aid);
+                        }
+                        return Ok(event);
+                    }
+                    AccessPointStationEventInfo::Disconnected(info) => {
+                        if let Some(event) = stations.disassociate(info.mac) {
+                            return Ok(event);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Deauthenticates the SoftAP station with association id `aid`.
+    ///
+    /// `esp-radio 0.18` has no deauth API, so this calls the driver directly.
+    /// A failure is logged at `warn`; the station then stays associated but
+    /// untracked.
+    fn deauth_station_or_log(aid: u16) {
+        extern "C" {
+            fn esp_wifi_deauth_sta(aid: u16) -> i32;
+        }
+        // SAFETY: `esp_wifi_deauth_sta` is provided by esp-wifi-sys, always
+        // linked by every esp-radio build, and valid while the AP is started.
+        // `aid` comes from a connect event, so it is never 0 (which would
+        // deauthenticate every station).
+        let rc = unsafe { esp_wifi_deauth_sta(aid) };
+        if rc != 0 {
+            log::warn!("esp_wifi_deauth_sta({}) failed with code {:#010x}", aid, rc);
+        }
     }
 
     /// Clamps the TX power to `quarter_dbm` (units of 0.25 dBm).
@@ -672,10 +1826,11 @@ mod driver {
     }
 }
 
-#[cfg(all(feature = "embassy", any(feature = "esp32c6", feature = "esp32c3")))]
+#[cfg(all(feature = "embassy", feature = "_wifi-chip"))]
 pub use driver::{
-    ApConfigExt, AsyncWifiHandle, HalApConfig, HalWifiConfig, SoftApHandle, WiFiConfigExt,
-    WiFiManager, WifiError, AP_IP,
+    init_heap, ApConfigExt, AsyncWifiHandle, EspHalWifiDriver, HalApConfig, HalWifiConfig,
+    SoftApHandle, WiFiConfigExt, WiFiManager, WifiError, WifiLinkWatch, AP_IP,
+    LINK_WATCH_RECEIVERS,
 };
 
 // ─── Stub fallback (no chip feature — host / doc / test builds) ─────────────
@@ -683,13 +1838,13 @@ pub use driver::{
 // The chip-without-embassy combination is rejected by the `compile_error!`
 // above, so this branch only fires when no chip feature is selected.
 
-#[cfg(not(any(feature = "esp32c6", feature = "esp32c3")))]
+#[cfg(not(feature = "_wifi-chip"))]
 mod stub {
     /// Wi-Fi error placeholder for host builds.
     #[derive(Debug)]
     pub enum WifiError {
-        /// Wi-Fi requires a chip feature (`esp32c3` or `esp32c6`) on
-        /// bare-metal targets.
+        /// Wi-Fi requires a chip feature (`esp32c3`, `esp32c6`, `esp32s3`,
+        /// or `esp32`) on bare-metal targets.
         NotSupported,
     }
 
@@ -716,5 +1871,5 @@ mod stub {
     }
 }
 
-#[cfg(not(any(feature = "esp32c6", feature = "esp32c3")))]
+#[cfg(not(feature = "_wifi-chip"))]
 pub use stub::{WiFiManager, WifiError};
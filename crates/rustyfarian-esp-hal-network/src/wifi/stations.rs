@@ -0,0 +1,110 @@
+//! Shared SoftAP station table for the bare-metal tier.
+//!
+//! Two tasks learn about the AP's clients: the task that owns the
+//! `WifiController` sees association and disassociation, and the DHCP task
+//! sees which address each station leased.  An [`ApStationMonitor`] is the
+//! [`ApStationTable`] they both feed, behind a critical-section mutex, plus
+//! an optional `fn` callback that receives every [`ApStationEvent`].
+//!
+//! ```ignore
+//! use static_cell::StaticCell;
+//!
+//! static STATIONS: StaticCell<ApStationMonitor> = StaticCell::new();
+//!
+//! let ap = WiFiManager::init_softap_async(config)?;
+//! let stations: &'static ApStationMonitor =
+//!     STATIONS.init(ApStationMonitor::new(ap.stations).with_on_event(|e| log::info!("{:?}", e)));
+//!
+//! // controller task:
+//! loop {
+//!     WiFiManager::next_ap_station_event(&ap.controller, stations).await?;
+//! }
+//!
+//! // DHCP task:
+//! dhcp::run(ap.stack, DhcpServerConfig::for_subnet(&ap.subnet).with_station_monitor(stations)).await
+//! ```
+
+use core::cell::RefCell;
+use core::net::Ipv4Addr;
+
+use embassy_sync::blocking_mutex::CriticalSectionMutex;
+use juggler::wifi::{ApStationEvent, ApStationTable, ApStations};
+
+/// [`ApStationTable`] shared between the controller task and the DHCP task.
+pub struct ApStationMonitor {
+    table: CriticalSectionMutex<RefCell<ApStationTable>>,
+    /// Optional event callback.  `fn` pointer (not `Arc<dyn Fn>`) because
+    /// `no_std` forbids heap-allocated closures.
+    on_event: Option<fn(ApStationEvent)>,
+}
+
+impl ApStationMonitor {
+    /// Wraps `table`, usually the empty one in
+    /// [`SoftApHandle::stations`](super::SoftApHandle) that carries the
+    /// config's `max_connections` and allowlist.
+    pub const fn new(table: ApStationTable) -> Self {
+        Self {
+            table: CriticalSectionMutex::new(RefCell::new(table)),
+            on_event: None,
+        }
+    }
+
+    /// Calls `callback` with every event the monitor produces.
+    ///
+    /// The callback runs on whichever task fed the event and must not block.
+    pub fn with_on_event(mut self, callback: fn(ApStationEvent)) -> Self {
+        self.on_event = Some(callback);
+        self
+    }
+
+    /// Snapshot of the admitted stations, in association order.
+    pub fn stations(&self) -> ApStations {
+        self.table
+            .lock(|table| table.borrow().stations().iter().copied().collect())
+    }
+
+    /// Number of admitted stations.
+    pub fn station_count(&self) -> usize {
+        self.table.lock(|table| table.borrow().stations().len())
+    }
+
+    /// Records an association; see [`ApStationTable::associate`].
+    ///
+    /// The caller must deauthenticate the station when this returns
+    /// [`ApStationEvent::Rejected`].
+    pub fn associate(&self, mac: [u8; 6]) -> ApStationEvent {
+        let event = self.table.lock(|table| table.borrow_mut().associate(mac));
+        self.emit(event);
+        event
+    }
+
+    /// Records a disassociation; see [`ApStationTable::disassociate`].
+    pub fn disassociate(&self, mac: [u8; 6]) -> Option<ApStationEvent> {
+        let event = self
+            .table
+            .lock(|table| table.borrow_mut().disassociate(mac))?;
+        self.emit(event);
+        Some(event)
+    }
+
+    /// Records a DHCP lease; see [`ApStationTable::assign_ip`].
+    pub fn lease_assigned(&self, mac: [u8; 6], ip: Ipv4Addr) -> Option<ApStationEvent> {
+        let event = self
+            .table
+            .lock(|table| table.borrow_mut().assign_ip(mac, ip))?;
+        self.emit(event);
+        Some(event)
+    }
+
+    /// Forgets every station, keeping the admission policy.
+    pub fn clear(&self) {
+        self.table.lock(|table| table.borrow_mut().clear());
+    }
+
+    /// Runs the callback outside the lock, so it may query the monitor.
+    fn emit(&self, event: ApStationEvent) {
+        if let Some(callback) = self.on_event {
+            callback(event);
+        }
+    }
+}
@@ -11,6 +11,9 @@ fn main() {
     // `rerun-if-changed` paths are relative to the crate dir, so reach up two
     // levels (crates/rustyfarian-esp-idf-network/ -> workspace root).
     println!("cargo:rerun-if-changed=../../sdkconfig.defaults");
+    // Component cfgs `esp-idf-sys` propagates; `wifi::mdns` is gated on them.
+    println!("cargo:rustc-check-cfg=cfg(esp_idf_comp_mdns_enabled)");
+    println!("cargo:rustc-check-cfg=cfg(esp_idf_comp_espressif__mdns_enabled)");
     // Required for ESP-IDF ldproxy linker argument injection.
     embuild::espidf::sysenv::output();
 }
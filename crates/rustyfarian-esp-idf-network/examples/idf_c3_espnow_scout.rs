@@ -1,7 +1,7 @@
 //! ESP-NOW scout — **SoftAP channel-lock variant** (ESP32-C3 Super Mini).
 //!
 //! Starts the Wi-Fi radio in SoftAP mode (`init_with_radio`), scans channels
-//! 1-13 to find the coordinator by MAC-layer ACK, then sends a message every
+//! 1-11 to find the coordinator by MAC-layer ACK, then sends a message every
 //! second.  SoftAP beacon scheduling prevents the Wi-Fi driver from
 //! autonomously hopping channels, so every send lands on the coordinator's
 //! channel without any per-send re-pinning.  This is the race-free, recommended
@@ -1,7 +1,7 @@
 //! ESP-NOW scout — **STA + promiscuous-bracket variant** (ESP32-C3 Super Mini).
 //!
 //! Starts the Wi-Fi radio in unassociated STA mode (`init_with_radio_sta`),
-//! scans channels 1-13 to find the coordinator by MAC-layer ACK, then sends a
+//! scans channels 1-11 to find the coordinator by MAC-layer ACK, then sends a
 //! message every second.  Before each send, `send_and_wait` re-pins the radio
 //! to the discovered channel using a promiscuous-mode bracket, which suppresses
 //! the ESP-IDF background channel scanner for the duration of the set + send.
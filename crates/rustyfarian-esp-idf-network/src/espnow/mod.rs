@@ -40,6 +40,24 @@
 //! No AMPDU or coex settings needed since there is no Wi-Fi connection
 //! competing for the radio.
 //!
+//! ## Long range
+//!
+//! Links that span a field rather than a room can switch the owned radio to
+//! Espressif's LR protocol with the `_phy` constructors:
+//!
+//! ```rust,no_run
+//! # use rustyfarian_esp_idf_network::espnow::{EspIdfEspNow, WifiPhy};
+//! # let peripherals = esp_idf_svc::hal::peripherals::Peripherals::take().unwrap();
+//! # let sys_loop = esp_idf_svc::eventloop::EspSystemEventLoop::take().unwrap();
+//! let driver =
+//!     EspIdfEspNow::init_with_radio_phy(peripherals.modem, sys_loop, None, WifiPhy::LONG_RANGE)
+//!         .unwrap();
+//! ```
+//!
+//! Every node on the link needs the same setting — an LR-only radio neither
+//! hears nor is heard by an 802.11b/g/n one.  [`WifiProtocol::BgnLr`] keeps
+//! both, at the cost of the LR-only mode's robustness.
+//!
 //! ## ESP-NOW + Wi-Fi on separate chips (two-MCU architecture)
 //!
 //! No special `sdkconfig` needed on either chip — radio contention is
@@ -51,6 +69,8 @@ use std::sync::{Arc, Condvar, Mutex};
 use std::time::{Duration, Instant};
 
 use anyhow::Context as _;
+
+use crate::phy::apply_phy;
 use esp_idf_svc::espnow::{EspNow, PeerInfo, SendStatus};
 use esp_idf_svc::hal::modem::Modem;
 use esp_idf_svc::wifi::{AccessPointConfiguration, AuthMethod, Configuration, EspWifi};
@@ -59,6 +79,7 @@ pub use juggler::espnow::{
     BROADCAST_MAC, DEFAULT_CONFIRMATION_GAP, DEFAULT_PROBE_CONFIRMATIONS, DEFAULT_PROBE_TIMEOUT,
     DEFAULT_RX_CHANNEL_CAPACITY, DEFAULT_SCAN_CHANNELS, MAX_DATA_LEN,
 };
+pub use juggler::wifi::{validate_phy, WifiBandwidth, WifiPhy, WifiProtocol};
 
 /// Radio-management mode the driver is operating in.
 ///
@@ -214,6 +235,21 @@ impl EspIdfEspNow {
         sys_loop: esp_idf_svc::eventloop::EspSystemEventLoop,
         nvs: Option<esp_idf_svc::nvs::EspDefaultNvsPartition>,
     ) -> anyhow::Result<Self> {
+        Self::init_with_radio_phy(modem, sys_loop, nvs, WifiPhy::DEFAULT)
+    }
+
+    /// Like [`init_with_radio`](Self::init_with_radio), with the SoftAP
+    /// interface switched to `phy` — typically [`WifiPhy::LONG_RANGE`].
+    ///
+    /// `phy` is checked with [`validate_phy`] before the radio starts and
+    /// applied right after; every peer must use a compatible protocol.
+    pub fn init_with_radio_phy(
+        modem: Modem<'static>,
+        sys_loop: esp_idf_svc::eventloop::EspSystemEventLoop,
+        nvs: Option<esp_idf_svc::nvs::EspDefaultNvsPartition>,
+        phy: WifiPhy,
+    ) -> anyhow::Result<Self> {
+        validate_phy(&phy).map_err(|e| anyhow::anyhow!("ESP-NOW PHY config invalid: {}", e))?;
         let mut wifi = EspWifi::new(modem, sys_loop, nvs)
             .context("failed to create EspWifi for ESP-NOW radio")?;
         wifi.set_configuration(&Configuration::AccessPoint(AccessPointConfiguration {
@@ -225,6 +261,7 @@ impl EspIdfEspNow {
         .context("failed to configure SoftAP for ESP-NOW radio")?;
         wifi.start()
             .context("failed to start Wi-Fi SoftAP for ESP-NOW")?;
+        apply_phy(esp_idf_svc::sys::wifi_interface_t_WIFI_IF_AP, phy)?;
         log::info!("Wi-Fi SoftAP started for ESP-NOW (channel-stable, no AP connection)");
 
         Self::init_inner(
@@ -254,10 +291,23 @@ impl EspIdfEspNow {
         sys_loop: esp_idf_svc::eventloop::EspSystemEventLoop,
         nvs: Option<esp_idf_svc::nvs::EspDefaultNvsPartition>,
     ) -> anyhow::Result<Self> {
+        Self::init_with_radio_sta_phy(modem, sys_loop, nvs, WifiPhy::DEFAULT)
+    }
+
+    /// Like [`init_with_radio_sta`](Self::init_with_radio_sta), with the STA
+    /// interface switched to `phy`.
+    pub fn init_with_radio_sta_phy(
+        modem: Modem<'static>,
+        sys_loop: esp_idf_svc::eventloop::EspSystemEventLoop,
+        nvs: Option<esp_idf_svc::nvs::EspDefaultNvsPartition>,
+        phy: WifiPhy,
+    ) -> anyhow::Result<Self> {
+        validate_phy(&phy).map_err(|e| anyhow::anyhow!("ESP-NOW PHY config invalid: {}", e))?;
         let mut wifi = EspWifi::new(modem, sys_loop, nvs)
             .context("failed to create EspWifi for ESP-NOW radio")?;
         wifi.start()
             .context("failed to start Wi-Fi radio for ESP-NOW")?;
+        apply_phy(esp_idf_svc::sys::wifi_interface_t_WIFI_IF_STA, phy)?;
         log::info!("Wi-Fi STA started for ESP-NOW (promiscuous-bracket channel re-pin)");
 
         Self::init_inner(
@@ -445,7 +495,30 @@ impl EspIdfEspNow {
         peer_mac: &MacAddress,
         scan_config: &ScanConfig<'_>,
     ) -> anyhow::Result<(Self, ScanResult)> {
-        let driver = Self::init_with_radio(modem, sys_loop, nvs)?;
+        Self::init_with_radio_scanning_phy(
+            modem,
+            sys_loop,
+            nvs,
+            WifiPhy::DEFAULT,
+            peer_mac,
+            scan_config,
+        )
+    }
+
+    /// Like [`init_with_radio_scanning`](Self::init_with_radio_scanning),
+    /// starting the radio with [`init_with_radio_phy`](Self::init_with_radio_phy).
+    ///
+    /// The probes go out in the chosen protocol, so an LR peer is found
+    /// only by an LR scanner.
+    pub fn init_with_radio_scanning_phy(
+        modem: Modem<'static>,
+        sys_loop: esp_idf_svc::eventloop::EspSystemEventLoop,
+        nvs: Option<esp_idf_svc::nvs::EspDefaultNvsPartition>,
+        phy: WifiPhy,
+        peer_mac: &MacAddress,
+        scan_config: &ScanConfig<'_>,
+    ) -> anyhow::Result<(Self, ScanResult)> {
+        let driver = Self::init_with_radio_phy(modem, sys_loop, nvs, phy)?;
         let result = driver.scan_for_peer(peer_mac, scan_config)?;
         Ok((driver, result))
     }
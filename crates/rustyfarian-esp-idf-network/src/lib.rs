@@ -20,7 +20,8 @@
 //! ### Domain features (opt-in)
 //!
 //! - **`wifi`** — Wi-Fi connection manager and SoftAP lifecycle handler via `esp-idf-svc`.
-//!   Provides [`wifi::WiFiManager`] for STA mode and [`wifi::SoftApManager`] for AP mode.
+//!   Provides [`wifi::WiFiManager`] for STA mode, [`wifi::SoftApManager`] for AP mode, and
+//!   [`wifi::ApStaManager`] for both at once.
 //!
 //! - **`mqtt`** — Auto-reconnecting MQTT client with builder API and background event loop.
 //!   Provides [`mqtt::MqttBuilder`] and [`mqtt::MqttHandle`]. **This feature compiles
@@ -44,6 +45,13 @@
 //!   LoRaWAN credentials. Requires `wifi` feature (SoftAP setup). Provides
 //!   [`provisioning::ProvisioningBuilder`] and [`provisioning::ProvisioningSession`].
 //!
+//! - **`mdns`** — `wifi::MdnsResponder`, answering `<hostname>.local` and
+//!   advertising DNS-SD services. Implies `wifi`. The firmware crate must also
+//!   add the `espressif/mdns` ESP-IDF component via
+//!   `[[package.metadata.esp-idf-sys.extra_components]]`; without it the
+//!   feature compiles to nothing, which keeps `--all-features` checks
+//!   building.
+//!
 //! ## Re-export patterns
 //!
 //! Each domain module in this crate re-exports the corresponding types from
@@ -72,6 +80,9 @@ pub mod lora;
 #[cfg(feature = "espnow")]
 pub mod espnow;
 
+#[cfg(any(feature = "wifi", feature = "espnow"))]
+mod phy;
+
 /// OTA firmware update driver.
 #[cfg(feature = "ota")]
 pub mod ota;
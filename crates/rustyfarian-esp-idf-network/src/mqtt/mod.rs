@@ -38,6 +38,64 @@
 //! }
 //! ```
 //!
+//! ## TLS and mutual TLS
+//!
+//! Brokers on `mqtts://` (port 8883) take an [`MqttTls`] via
+//! [`MqttConfig::with_tls`]: pin a CA certificate (PEM or DER) or trust
+//! ESP-IDF's certificate bundle, and add a client certificate and key for
+//! mutual TLS.  PEM data may be passed as is; the NUL terminator mbedTLS
+//! needs is appended when it is missing:
+//!
+//! ```ignore
+//! use rustyfarian_esp_idf_network::mqtt::{MqttConfig, MqttTls, TlsCert};
+//!
+//! const CA: &[u8] = include_bytes!("../certs/ca.pem");
+//! const CERT: &[u8] = include_bytes!("../certs/device.pem");
+//! const KEY: &[u8] = include_bytes!("../certs/device.key");
+//!
+//! let config = MqttConfig::new("broker.example.com", 8883, "my-device").with_tls(
+//!     MqttTls::with_ca(TlsCert::Pem(CA))
+//!         .with_client_cert(TlsCert::Pem(CERT), TlsCert::Pem(KEY)),
+//! );
+//! ```
+//!
+//! ## MQTT over WebSocket
+//!
+//! Where only outbound HTTPS is allowed, tunnel MQTT through a secure
+//! WebSocket on port 443.  The TLS settings turn `ws://` into `wss://`:
+//!
+//! ```ignore
+//! use rustyfarian_esp_idf_network::mqtt::{MqttConfig, MqttTls, MqttTransport};
+//!
+//! let config = MqttConfig::new("broker.example.com", 443, "my-device")
+//!     .with_transport(MqttTransport::WebSocket { path: "/mqtt" })
+//!     .with_tls(MqttTls::with_ca_bundle());
+//! ```
+//!
+//! ## Offline queue
+//!
+//! By default a QoS 1 or 2 publish made while the broker is unreachable is
+//! handed to ESP-IDF's outbox, which forgets it on reset or after its
+//! expiry.  [`MqttBuilder::with_offline_queue`] captures such publishes in a
+//! bounded queue instead — RAM, NVS, or a raw data partition — and replays
+//! them in order after the next connect.  QoS 0 publishes are never queued:
+//!
+//! ```ignore
+//! use rustyfarian_esp_idf_network::mqtt::{MqttBuilder, OfflineQueue, OverflowPolicy};
+//!
+//! let handle = MqttBuilder::new(config)
+//!     .with_offline_queue(
+//!         OfflineQueue::new(4096)
+//!             .with_policy(OverflowPolicy::DropOldest)
+//!             .with_nvs(nvs_partition.clone()),
+//!     )
+//!     .build()?;
+//! ```
+//!
+//! A persistent queue is written to flash by the event loop, never on the
+//! publish path; see [`OfflineQueue`] for how often, and
+//! [`MqttHandle::flush_offline_queue`] to force a write before a reset.
+//!
 //! ## Battery-optimized configuration
 //!
 //! On thermally constrained boards (e.g. ESP32-C3 Super Mini) where MQTT is
@@ -56,7 +114,7 @@
 use anyhow::Context as _;
 use pennant::PulseEffect;
 use rgb::RGB8;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
@@ -64,11 +122,23 @@ use std::time::Duration;
 pub use pennant::{SimpleLed, StatusLed};
 
 use juggler::mqtt::{
-    connection_wait_iterations, format_broker_url, next_state, spawn_subscriber_thread,
-    validate_broker_host, validate_broker_port, validate_client_id, validate_publish_topic,
-    validate_subscribe_filter, MqttConnectionState, MqttEvent, QoS as PureQoS, SubscribeClient,
+    connection_wait_iterations, format_broker_url, next_state, publish_or_queue, retry_replay,
+    spawn_subscriber_thread, validate_broker_host, validate_broker_port, validate_client_id,
+    validate_publish_topic, validate_subscribe_filter, validate_tls, validate_transport,
+    MqttConnectionState, MqttEvent, QoS as PureQoS, SubscribeClient,
+};
+use juggler::pem::pem_needs_nul;
+
+pub use juggler::mqtt::{
+    MqttTls, MqttTransport, OverflowPolicy, TlsCa, TlsCert, MQTT_PORT, MQTT_TLS_PORT,
+    MQTT_WSS_PORT, MQTT_WS_PORT, WS_DEFAULT_PATH,
 };
 
+mod offline;
+
+pub use offline::{OfflineQueue, QueueStore};
+use offline::{OfflineState, OfflineStore};
+
 /// Poll interval used while waiting for the MQTT broker connection to be confirmed.
 ///
 /// Must stay consistent with the `poll_interval_ms` argument passed to
@@ -129,9 +199,11 @@ pub const LED_POLL_INTERVAL_MS: u64 = 50;
 /// 20 frames at 50 ms = 1 second of error indication before returning.
 pub const ERROR_PULSE_FRAMES: u32 = 20;
 
+use esp_idf_svc::handle::RawHandle as _;
 use esp_idf_svc::mqtt::client::{
     EspMqttClient, EventPayload, LwtConfiguration, MqttClientConfiguration, QoS,
 };
+use esp_idf_svc::tls::X509;
 
 /// Error returned by the `try_publish*` family when the publish cannot
 /// complete without blocking.
@@ -188,12 +260,13 @@ impl<'a> LwtConfig<'a> {
 /// MQTT broker connection configuration.
 ///
 /// Credentials (`username`, `password`) are redacted in the `Debug` output
-/// (`Some("<redacted>")`) to prevent them from appearing in log files.
+/// (`Some("<redacted>")`) to prevent them from appearing in log files; so is
+/// the TLS client key password.
 #[derive(Clone)]
 pub struct MqttConfig<'a> {
     /// MQTT broker hostname or IP address
     pub host: &'a str,
-    /// MQTT broker port (typically 1883 for unencrypted)
+    /// MQTT broker port (typically 1883 for unencrypted, 8883 for TLS)
     pub port: u16,
     /// Unique client identifier
     pub client_id: &'a str,
@@ -210,6 +283,17 @@ pub struct MqttConfig<'a> {
     ///
     /// Set via [`with_task_stack_size`](Self::with_task_stack_size).
     pub task_stack_size: usize,
+    /// Transport selector (default: [`MqttTransport::Tcp`]).
+    ///
+    /// Set via [`with_transport`](Self::with_transport).  With TLS settings
+    /// present the connection uses its secure counterpart; see
+    /// [`effective_transport`](Self::effective_transport).
+    pub transport: MqttTransport<'a>,
+    /// TLS settings; `Some` selects `mqtts://` or `wss://`.
+    ///
+    /// Set via [`with_tls`](Self::with_tls).  Certificates are `'static`
+    /// because ESP-IDF keeps pointing at them for every reconnect.
+    pub tls: Option<MqttTls<'static>>,
     lwt: Option<LwtConfig<'a>>,
     username: Option<&'a str>,
     password: Option<&'a str>,
@@ -235,6 +319,8 @@ impl<'a> std::fmt::Debug for MqttConfig<'a> {
             .field("connection_timeout_ms", &self.connection_timeout_ms)
             .field("reconnect_timeout_ms", &self.reconnect_timeout_ms)
             .field("task_stack_size", &self.task_stack_size)
+            .field("transport", &self.transport)
+            .field("tls", &self.tls)
             .field("lwt", &self.lwt)
             .field("username", &redacted_username)
             .field("password", &redacted_password)
@@ -253,6 +339,8 @@ impl<'a> MqttConfig<'a> {
             connection_timeout_ms: None,
             reconnect_timeout_ms: None,
             task_stack_size: DEFAULT_MQTT_TASK_STACK_SIZE,
+            transport: MqttTransport::Tcp,
+            tls: None,
             lwt: None,
             username: None,
             password: None,
@@ -324,6 +412,65 @@ impl<'a> MqttConfig<'a> {
         self
     }
 
+    /// Connects over TLS (`mqtts://`, or `wss://` for a WebSocket
+    /// transport) with the given settings.
+    ///
+    /// The broker certificate is verified against the pinned CA or the
+    /// ESP-IDF certificate bundle, and its name against `host`, which is
+    /// also sent as the TLS server name (SNI).  When `host` is an IP address
+    /// the certificate does not name, set the name it does carry with
+    /// [`MqttTls::with_server_name`].  The port is not changed; pass
+    /// [`MQTT_TLS_PORT`] to [`new`](Self::new) for the usual 8883.
+    ///
+    /// [`MqttBuilder::build`] rejects the settings if [`validate_tls`]
+    /// does.
+    pub fn with_tls(mut self, tls: MqttTls<'static>) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Selects the transport: TCP, TLS, WebSocket, or secure WebSocket.
+    ///
+    /// The WebSocket variants carry the HTTP path of the broker's endpoint
+    /// (most brokers serve [`WS_DEFAULT_PATH`]); [`MqttBuilder::build`]
+    /// checks it with [`validate_ws_path`](juggler::mqtt::validate_ws_path).
+    /// A secure transport needs [`with_tls`](Self::with_tls) as well.  The
+    /// port is not changed; [`MqttTransport::default_port`] gives the
+    /// conventional one.
+    pub fn with_transport(mut self, transport: MqttTransport<'a>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// The transport the connection actually uses: the selected one, or
+    /// its [secured](MqttTransport::secured) counterpart when TLS settings
+    /// are present.
+    pub fn effective_transport(&self) -> MqttTransport<'a> {
+        if self.tls.is_some() {
+            self.transport.secured()
+        } else {
+            self.transport
+        }
+    }
+
+    /// Returns `Ok(())` if the transport, and TLS settings if any, are
+    /// usable.
+    fn check_transport(&self) -> anyhow::Result<()> {
+        let transport = self.effective_transport();
+        validate_transport(&transport)
+            .map_err(|e| anyhow::anyhow!("invalid MQTT transport: {}", e))?;
+        match &self.tls {
+            Some(tls) => {
+                validate_tls(tls).map_err(|e| anyhow::anyhow!("invalid MQTT TLS settings: {}", e))
+            }
+            None if transport.is_secure() => Err(anyhow::anyhow!(
+                "invalid MQTT transport: {}:// needs TLS settings (MqttConfig::with_tls)",
+                transport.scheme()
+            )),
+            None => Ok(()),
+        }
+    }
+
     /// Overrides the ESP-IDF MQTT client task stack size.
     ///
     /// Defaults to 8192 bytes (8 KiB), which provides sufficient headroom
@@ -404,7 +551,9 @@ where
             retain: lwt.retain,
         });
 
-        let mqtt_cfg = MqttClientConfiguration {
+        config.check_transport()?;
+
+        let mut mqtt_cfg = MqttClientConfiguration {
             client_id: Some(config.client_id),
             keep_alive_interval: Some(Duration::from_secs(config.keep_alive_secs.unwrap_or(30))),
             reconnect_timeout: config.reconnect_timeout_ms.map(Duration::from_millis),
@@ -414,8 +563,11 @@ where
             password: config.password,
             ..Default::default()
         };
+        if let Some(tls) = &config.tls {
+            apply_tls(&mut mqtt_cfg, tls);
+        }
 
-        let mqtt_url = format!("mqtt://{}:{}", config.host, config.port);
+        let mqtt_url = format_broker_url(config.host, config.port, config.effective_transport());
         let (client, mut connection) = EspMqttClient::new(&mqtt_url, &mqtt_cfg)?;
 
         let connected = Arc::new(AtomicBool::new(false));
@@ -469,6 +621,12 @@ where
             })
             .context("failed to spawn MQTT event loop thread")?;
 
+        if let Some(tls) = &config.tls {
+            apply_server_name(client.handle(), &mqtt_cfg, tls)?;
+            // An error from the attempt before the restart is stale.
+            connection_error.store(false, Ordering::Release);
+        }
+
         let shutdown_for_err = Arc::clone(&shutdown);
         let mut manager = Self {
             client,
@@ -714,6 +872,7 @@ pub struct MqttBuilder<'a> {
     on_message: Option<OnMessageCallback>,
     subscribe_topics: Vec<(String, PureQoS)>,
     with_startup_message: bool,
+    offline_queue: Option<OfflineQueue>,
 }
 
 impl<'a> MqttBuilder<'a> {
@@ -726,6 +885,7 @@ impl<'a> MqttBuilder<'a> {
             on_message: None,
             subscribe_topics: Vec::new(),
             with_startup_message: false,
+            offline_queue: None,
         }
     }
 
@@ -848,6 +1008,29 @@ impl<'a> MqttBuilder<'a> {
         self
     }
 
+    /// Queues QoS 1 and QoS 2 publishes made while disconnected and replays
+    /// them, oldest first, after the next connect.
+    ///
+    /// While the handle is disconnected, [`MqttHandle::publish_with`] and
+    /// [`MqttHandle::try_publish_with`] store the publish instead of handing
+    /// it to the client; a full queue applies the configured
+    /// [`OverflowPolicy`], and only [`OverflowPolicy::Reject`] surfaces an
+    /// error.  QoS 0 publishes bypass the queue.  The replay runs on the
+    /// event loop after `on_connect` and before the
+    /// [`subscribe`](Self::subscribe) topics are sent, and
+    /// [`MqttHandle::is_connected`] turns `true` only once it has finished,
+    /// so live publishes never overtake queued ones.
+    ///
+    /// A persistent [`QueueStore`] is loaded by [`build`](Self::build): the
+    /// publishes queued before a reset are replayed on the first connect.
+    /// Queuing a publish touches only RAM; the event loop writes the queue
+    /// out after each MQTT event, and
+    /// [`MqttHandle::flush_offline_queue`] does so on demand.
+    pub fn with_offline_queue(mut self, queue: OfflineQueue) -> Self {
+        self.offline_queue = Some(queue);
+        self
+    }
+
     /// Starts the background event loop and returns an [`MqttHandle`].
     ///
     /// Returns immediately — the initial broker connection happens in the
@@ -868,16 +1051,33 @@ impl<'a> MqttBuilder<'a> {
             .map_err(|e| anyhow::anyhow!("invalid MQTT port: {}", e))?;
         validate_client_id(config.client_id)
             .map_err(|e| anyhow::anyhow!("invalid MQTT client_id: {}", e))?;
+        config.check_transport()?;
         for (topic, _) in &self.subscribe_topics {
             validate_subscribe_filter(topic.as_str())
                 .map_err(|e| anyhow::anyhow!("invalid subscribe filter '{}': {}", topic, e))?;
         }
+        let (offline, offline_store) = self
+            .offline_queue
+            .map(OfflineState::open)
+            .transpose()
+            .context("failed to open the offline queue")?
+            .map_or((None, None), |(state, store)| (Some(state), store));
+        let queued = offline
+            .as_ref()
+            .map_or_else(|| Arc::new(AtomicUsize::new(0)), OfflineState::queued);
+        // Only a persistent queue has anything to flush.
+        let offline_dirty = offline
+            .as_ref()
+            .filter(|_| offline_store.is_some())
+            .map(OfflineState::dirty);
+        let offline = offline.map(|state| Arc::new(Mutex::new(state)));
+        let offline_store = offline_store.map(|store| Arc::new(Mutex::new(store)));
 
         // Build owned copies of all string fields.
         // esp_mqtt_client_init() calls strdup() on each of these immediately,
         // so they only need to live through the EspMqttClient::new() call below.
         // No Box::leak required.
-        let url = format_broker_url(config.host, config.port);
+        let url = format_broker_url(config.host, config.port, config.effective_transport());
         let client_id = config.client_id.to_string();
         // codeql[rust/cleartext-logging] - credentials are passed to the MQTT
         // broker via EspMqttClient::new(); this is required for authentication
@@ -899,7 +1099,7 @@ impl<'a> MqttBuilder<'a> {
                 retain: lwt.retain,
             });
 
-        let mqtt_cfg = MqttClientConfiguration {
+        let mut mqtt_cfg = MqttClientConfiguration {
             client_id: Some(client_id.as_str()),
             keep_alive_interval: Some(Duration::from_secs(config.keep_alive_secs.unwrap_or(30))),
             reconnect_timeout: config.reconnect_timeout_ms.map(Duration::from_millis),
@@ -909,11 +1109,19 @@ impl<'a> MqttBuilder<'a> {
             password: password.as_deref(),
             ..Default::default()
         };
+        // Unlike the strings above, the certificates are not copied by
+        // esp_mqtt_client_init(); they are `'static` for that reason.
+        if let Some(tls) = &config.tls {
+            apply_tls(&mut mqtt_cfg, tls);
+        }
 
         let (client, mut connection) =
             EspMqttClient::new(&url, &mqtt_cfg).context("failed to create EspMqttClient")?;
-        // url, client_id, username, password, lwt_topic, lwt_payload and mqtt_cfg
-        // are all dropped here — the C library has already strdup'd what it needs.
+        // The C library has already strdup'd url, client_id, username, password
+        // and lwt_topic; mqtt_cfg is only kept for apply_server_name() below.
+        // Raw handle for apply_server_name(), which must not hold the client
+        // mutex the event loop takes.
+        let raw_client = client.handle();
 
         let shared_client = Arc::new(Mutex::new(SubscribableClient(client)));
         let client_for_thread = Arc::clone(&shared_client);
@@ -928,6 +1136,9 @@ impl<'a> MqttBuilder<'a> {
         let alive = Arc::new(());
         let alive_weak = Arc::downgrade(&alive);
 
+        let offline_for_thread = offline.clone();
+        let offline_store_for_thread = offline_store.clone();
+        let queued_for_thread = Arc::clone(&queued);
         let on_connect = self.on_connect;
         let on_disconnect = self.on_disconnect;
         let on_message = self.on_message;
@@ -941,6 +1152,23 @@ impl<'a> MqttBuilder<'a> {
             .spawn(move || {
                 log::info!("[mqtt] builder event loop started");
                 let mut state = MqttConnectionState::Connecting;
+                // Writes the offline queue snapshot if it changed.  Called once
+                // per event, so publishes queued while offline cost about one
+                // write per reconnect attempt instead of one each.
+                let flush_offline = || {
+                    let (Some(dirty), Some(offline), Some(store)) = (
+                        &offline_dirty,
+                        &offline_for_thread,
+                        &offline_store_for_thread,
+                    ) else {
+                        return;
+                    };
+                    if dirty.load(Ordering::Acquire) {
+                        if let Err(e) = offline::flush(offline, store) {
+                            log::warn!("[mqtt] {:#}", e);
+                        }
+                    }
+                };
 
                 loop {
                     // Exit when all MqttHandle clones have been dropped.
@@ -1002,6 +1230,15 @@ impl<'a> MqttBuilder<'a> {
                                         }
                                     }
                                 }
+                                // Replay before the subscriber thread takes the client
+                                // mutex, and keep the queue locked until `connected`
+                                // flips: a publisher that saw the flag clear is then
+                                // queued behind the replay rather than stranded.
+                                let offline = offline_for_thread.as_ref().map(|offline| {
+                                    let mut offline = offline.lock().unwrap();
+                                    offline.replay(&mut client_for_thread.lock().unwrap());
+                                    offline
+                                });
                                 if !subscribe_topics.is_empty() {
                                     spawn_subscriber_thread(
                                         Arc::clone(&client_for_thread),
@@ -1014,6 +1251,22 @@ impl<'a> MqttBuilder<'a> {
                                 // for the mutex while on_connect still holds it, which could
                                 // cause both threads to deadlock inside esp_mqtt_client_enqueue.
                                 connected_for_thread.store(true, Ordering::Release);
+                                drop(offline);
+                            }
+                        }
+                        // The outbox shrank: retry a replay that stopped short.
+                        // Never wait on the client mutex here — the subscriber
+                        // thread holds it until this loop delivers SUBACK.
+                        EventPayload::Published(_) | EventPayload::Deleted(_)
+                            if state == MqttConnectionState::Connected =>
+                        {
+                            if let Some(ref offline) = offline_for_thread {
+                                retry_replay(
+                                    &queued_for_thread,
+                                    offline,
+                                    &client_for_thread,
+                                    |offline, client| offline.replay(client),
+                                );
                             }
                         }
                         EventPayload::Disconnected => {
@@ -1037,21 +1290,40 @@ impl<'a> MqttBuilder<'a> {
                         }
                         EventPayload::Subscribed(id) => {
                             log::info!("[mqtt] subscription confirmed (id: {})", id);
+                            // A retry skipped while the subscriber thread held
+                            // the client mutex may now go through.
+                            if let Some(ref offline) = offline_for_thread {
+                                retry_replay(
+                                    &queued_for_thread,
+                                    offline,
+                                    &client_for_thread,
+                                    |offline, client| offline.replay(client),
+                                );
+                            }
                         }
                         EventPayload::Error(e) => {
                             log::error!("[mqtt] error: {:?}", e);
                         }
                         _ => {}
                     }
+                    flush_offline();
                 }
 
+                flush_offline();
                 log::info!("[mqtt] builder event loop exited");
             })
             .context("failed to spawn MQTT builder event loop thread")?;
 
+        if let Some(tls) = &config.tls {
+            apply_server_name(raw_client, &mqtt_cfg, tls)?;
+        }
+
         Ok(MqttHandle {
             client: shared_client,
             connected: connected_for_handle,
+            offline,
+            offline_store,
+            queued,
             _alive: alive,
         })
     }
@@ -1125,6 +1397,161 @@ impl<'a> MqttBuilder<'a> {
     }
 }
 
+/// Wraps `cert` for the ESP-IDF client, appending a NUL to PEM without one.
+///
+/// ESP-MQTT keeps the certificate pointer for every reconnect, so the
+/// NUL-terminated copy is leaked: one allocation per certificate per
+/// client, as clients are built once per boot.
+fn x509(cert: TlsCert<'static>) -> X509<'static> {
+    match cert {
+        TlsCert::Pem(bytes) if pem_needs_nul(bytes) => {
+            X509::pem_until_nul(Vec::leak([bytes, &[0]].concat()))
+        }
+        TlsCert::Pem(bytes) => X509::pem_until_nul(bytes),
+        TlsCert::Der(bytes) => X509::der(bytes),
+    }
+}
+
+/// Copies validated TLS settings into the ESP-IDF client configuration.
+///
+/// [`MqttTls::server_name`] has no field here; [`apply_server_name`] sets
+/// it once the client runs.
+fn apply_tls(cfg: &mut MqttClientConfiguration<'_>, tls: &MqttTls<'static>) {
+    match tls.ca {
+        TlsCa::Cert(ca) => cfg.server_certificate = Some(x509(ca)),
+        TlsCa::Bundle => {
+            cfg.crt_bundle_attach = Some(esp_idf_svc::sys::esp_crt_bundle_attach);
+        }
+    }
+    cfg.client_certificate = tls.client_cert.map(x509);
+    cfg.private_key = tls.client_key.map(x509);
+    cfg.private_key_password = tls.client_key_password;
+    cfg.skip_cert_common_name_check = tls.skip_hostname_check;
+}
+
+/// Points SNI and the certificate name check at [`MqttTls::server_name`],
+/// if set.
+///
+/// esp-idf-svc 0.52 does not expose ESP-MQTT's `common_name`, and
+/// `EspMqttClient::new` starts the client at once, so the name is set on
+/// the running client: stop it, hand `esp_mqtt_set_config` all of `cfg`
+/// again plus `common_name`, and start it, which rebuilds the TLS
+/// transport.  `esp_mqtt_set_config` resets any numeric field left at zero
+/// — keepalive, timeouts, last-will QoS — to ESP-MQTT's default, so every
+/// field of `cfg` is copied, not only the TLS ones.  The MQTT task holds the
+/// client lock until each event is consumed, so this must run after the
+/// event loop thread is up.
+///
+/// The attempt already under way when this runs still uses the broker host
+/// as SNI.  Against a broker that needs the override it fails its handshake
+/// and reports an `Error` (and possibly a `Disconnected`) event to the event
+/// loop, racing with the stop; the client then reconnects with the right
+/// name, so the cost is one failed attempt and its log lines.
+fn apply_server_name(
+    client: esp_idf_svc::sys::esp_mqtt_client_handle_t,
+    cfg: &MqttClientConfiguration<'_>,
+    tls: &MqttTls<'static>,
+) -> anyhow::Result<()> {
+    use esp_idf_svc::sys::{
+        esp, esp_mqtt_client_config_t, esp_mqtt_client_start, esp_mqtt_client_stop,
+        esp_mqtt_protocol_ver_t_MQTT_PROTOCOL_UNDEFINED, esp_mqtt_set_config,
+    };
+    use std::ffi::CString;
+
+    let Some(server_name) = tls.server_name else {
+        return Ok(());
+    };
+    // Every string is copied by esp_mqtt_set_config(); these only need to
+    // outlive that call.
+    let cstr = |value: &str, what: &str| {
+        CString::new(value).with_context(|| format!("{} contains a NUL byte", what))
+    };
+    let common_name = cstr(server_name, "TLS server name")?;
+    let client_id = cfg.client_id.map(|id| cstr(id, "client id")).transpose()?;
+    let username = cfg.username.map(|u| cstr(u, "username")).transpose()?;
+    let password = cfg.password.map(|p| cstr(p, "password")).transpose()?;
+    let lwt_topic = cfg
+        .lwt
+        .as_ref()
+        .map(|lwt| cstr(lwt.topic, "LWT topic"))
+        .transpose()?;
+    let ptr = |s: &Option<CString>| s.as_ref().map_or(core::ptr::null(), |s| s.as_ptr());
+
+    // Mirrors esp-idf-svc's own conversion of `MqttClientConfiguration`.
+    let mut raw = esp_mqtt_client_config_t::default();
+
+    let verification = &mut raw.broker.verification;
+    verification.use_global_ca_store = cfg.use_global_ca_store;
+    verification.skip_cert_common_name_check = cfg.skip_cert_common_name_check;
+    verification.crt_bundle_attach = cfg.crt_bundle_attach;
+    if let Some(ca) = cfg.server_certificate {
+        verification.certificate = ca.data().as_ptr().cast();
+        verification.certificate_len = ca.data().len();
+    }
+    verification.common_name = common_name.as_ptr();
+
+    let credentials = &mut raw.credentials;
+    credentials.client_id = ptr(&client_id);
+    credentials.set_null_client_id = cfg.client_id.is_none();
+    credentials.username = ptr(&username);
+    let auth = &mut credentials.authentication;
+    auth.password = ptr(&password);
+    if let (Some(cert), Some(key)) = (cfg.client_certificate, cfg.private_key) {
+        auth.certificate = cert.data().as_ptr().cast();
+        auth.certificate_len = cert.data().len();
+        auth.key = key.data().as_ptr().cast();
+        auth.key_len = key.data().len();
+        if let Some(password) = cfg.private_key_password {
+            auth.key_password = password.as_ptr().cast();
+            auth.key_password_len = password.len() as _;
+        }
+    }
+
+    let session = &mut raw.session;
+    session.protocol_ver = cfg
+        .protocol_version
+        .map_or(esp_mqtt_protocol_ver_t_MQTT_PROTOCOL_UNDEFINED, Into::into);
+    session.disable_clean_session = cfg.disable_clean_session;
+    match cfg.keep_alive_interval {
+        Some(interval) => session.keepalive = interval.as_secs() as _,
+        None => session.disable_keepalive = true,
+    }
+    if let Some(lwt) = cfg.lwt.as_ref() {
+        session.last_will.topic = ptr(&lwt_topic);
+        session.last_will.msg = lwt.payload.as_ptr().cast();
+        session.last_will.msg_len = lwt.payload.len() as _;
+        session.last_will.qos = lwt.qos as _;
+        session.last_will.retain = lwt.retain as _;
+    }
+
+    let network = &mut raw.network;
+    network.refresh_connection_after_ms = cfg.connection_refresh_interval.as_millis() as _;
+    network.timeout_ms = cfg.network_timeout.as_millis() as _;
+    match cfg.reconnect_timeout {
+        Some(timeout) => network.reconnect_timeout_ms = timeout.as_millis() as _,
+        None => network.disable_auto_reconnect = true,
+    }
+
+    raw.task.priority = cfg.task_prio as _;
+    raw.task.stack_size = cfg.task_stack as _;
+    raw.buffer.size = cfg.buffer_size as _;
+    raw.buffer.out_size = cfg.out_buffer_size as _;
+    if let Some(limit) = cfg.outbox_limit {
+        raw.outbox.limit = limit as _;
+    }
+
+    log::info!("[mqtt] restarting client to apply TLS server name");
+    // SAFETY: `client` belongs to an `EspMqttClient` the caller keeps alive;
+    // `raw` points at `cfg`, whose certificates are `'static`, and at the
+    // C strings above, all of which outlive these calls.
+    unsafe {
+        esp!(esp_mqtt_client_stop(client)).context("failed to stop the MQTT client")?;
+        esp!(esp_mqtt_set_config(client, &raw)).context("failed to set the TLS server name")?;
+        esp!(esp_mqtt_client_start(client)).context("failed to restart the MQTT client")?;
+    }
+    Ok(())
+}
+
 /// Cheaply cloneable MQTT handle returned by [`MqttBuilder::build`].
 ///
 /// Publish from any thread using `&self`.
@@ -1143,6 +1570,13 @@ impl<'a> MqttBuilder<'a> {
 pub struct MqttHandle {
     client: Arc<Mutex<SubscribableClient>>,
     connected: Arc<AtomicBool>,
+    // Never held while waiting on `client` outside the event loop; see
+    // `publish_or_queue`.
+    offline: Option<Arc<Mutex<OfflineState>>>,
+    // Persistence for `offline`; `None` without a queue or with a RAM one.
+    offline_store: Option<Arc<Mutex<OfflineStore>>>,
+    // Mirrors the offline queue length; `0` without a queue.
+    queued: Arc<AtomicUsize>,
     // Keeps the event loop alive.  When the last clone is dropped the
     // Arc refcount reaches zero, and the thread's Weak::upgrade() returns
     // None, causing the event loop to exit.
@@ -1172,6 +1606,13 @@ impl MqttHandle {
     /// * `payload` - The message payload
     /// * `qos`     - Quality of Service level
     /// * `retain`  - Whether the broker should retain this message
+    ///
+    /// With an [offline queue](MqttBuilder::with_offline_queue), a QoS 1 or 2
+    /// publish made while disconnected, or while earlier ones still wait to
+    /// be replayed, is queued instead so it cannot overtake them.  A publish
+    /// that passes the queue check and then loses the link before it reaches
+    /// the client lands in ESP-MQTT's outbox, and may go out ahead of the
+    /// publishes queued during that outage.
     pub fn publish_with(
         &self,
         topic: &str,
@@ -1181,13 +1622,30 @@ impl MqttHandle {
     ) -> anyhow::Result<()> {
         validate_publish_topic(topic)
             .map_err(|e| anyhow::anyhow!("invalid publish topic: {}", e))?;
-        log::debug!("[mqtt] publishing to '{}': {} bytes", topic, payload.len());
-        let mut guard = self
-            .client
-            .lock()
-            .map_err(|_| anyhow::anyhow!("MQTT client mutex poisoned"))?;
-        guard.enqueue(topic, qos, retain, payload)?;
-        Ok(())
+        let enqueue = |client: &mut SubscribableClient| -> anyhow::Result<()> {
+            log::debug!("[mqtt] publishing to '{}': {} bytes", topic, payload.len());
+            client.enqueue(topic, qos, retain, payload)?;
+            Ok(())
+        };
+        let Some(offline) = &self.offline else {
+            let mut guard = self
+                .client
+                .lock()
+                .map_err(|_| anyhow::anyhow!("MQTT client mutex poisoned"))?;
+            return enqueue(&mut guard);
+        };
+        // Whether to queue is decided under the queue lock, which is released
+        // before the client mutex is taken.
+        publish_or_queue(
+            offline,
+            &self.client,
+            |offline| {
+                (qos != QoS::AtMostOnce && (!self.is_connected() || offline.len() > 0))
+                    .then(|| offline.push(topic, payload, qos, retain))
+            },
+            enqueue,
+        )
+        .map_err(|e| anyhow::anyhow!(e))?
     }
 
     /// Non-blocking publish with QoS 1 and no retain flag.
@@ -1231,6 +1689,12 @@ impl MqttHandle {
     /// * `payload` - The message payload
     /// * `qos`     - Quality of Service level
     /// * `retain`  - Whether the broker should retain this message
+    ///
+    /// With an [offline queue](MqttBuilder::with_offline_queue), a QoS 1 or 2
+    /// publish made while disconnected, or while earlier ones still wait to
+    /// be replayed, is queued instead, and `WouldBlock` also covers the
+    /// queue being locked by a replay.  Queuing never writes to flash, so a
+    /// persistent [`QueueStore`] does not make this call block.
     pub fn try_publish_with(
         &self,
         topic: &str,
@@ -1240,6 +1704,20 @@ impl MqttHandle {
     ) -> Result<(), TryPublishError> {
         validate_publish_topic(topic)
             .map_err(|e| TryPublishError::Other(anyhow::anyhow!("invalid publish topic: {}", e)))?;
+        if let Some(offline) = &self.offline {
+            // Released before the client mutex is touched; see `publish_or_queue`.
+            let mut offline = offline.try_lock().map_err(|e| match e {
+                std::sync::TryLockError::WouldBlock => TryPublishError::WouldBlock,
+                std::sync::TryLockError::Poisoned(_) => {
+                    TryPublishError::Other(anyhow::anyhow!("MQTT offline queue mutex poisoned"))
+                }
+            })?;
+            if qos != QoS::AtMostOnce && (!self.is_connected() || offline.len() > 0) {
+                return offline
+                    .push(topic, payload, qos, retain)
+                    .map_err(TryPublishError::Other);
+            }
+        }
         log::debug!("[mqtt] try_publish to '{}': {} bytes", topic, payload.len());
         let mut guard = self.client.try_lock().map_err(|e| match e {
             std::sync::TryLockError::WouldBlock => TryPublishError::WouldBlock,
@@ -1303,4 +1781,30 @@ impl MqttHandle {
     pub fn is_connected(&self) -> bool {
         self.connected.load(Ordering::Acquire)
     }
+
+    /// Number of publishes waiting in the
+    /// [offline queue](MqttBuilder::with_offline_queue); `0` without one.
+    pub fn queued_publishes(&self) -> usize {
+        self.queued.load(Ordering::Acquire)
+    }
+
+    /// Writes the [offline queue](MqttBuilder::with_offline_queue) to its
+    /// [`QueueStore`] now, if it changed since the last write.
+    ///
+    /// The event loop flushes after every MQTT event, so publishes queued
+    /// while offline reach flash within about one reconnect attempt.  Call
+    /// this before a planned reset or deep sleep so none are lost.  Blocks
+    /// on the flash write; a no-op without a queue or with
+    /// [`QueueStore::Ram`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the write fails.  The queue stays marked as
+    /// changed, so the next flush retries.
+    pub fn flush_offline_queue(&self) -> anyhow::Result<()> {
+        match (&self.offline, &self.offline_store) {
+            (Some(offline), Some(store)) => offline::flush(offline, store),
+            _ => Ok(()),
+        }
+    }
 }
@@ -0,0 +1,423 @@
+//! Offline publish queue for [`MqttBuilder`](super::MqttBuilder).
+//!
+//! Wraps a [`PublishQueue`] in RAM and, optionally, mirrors it to an NVS
+//! namespace or a raw data partition so queued publishes survive a reset.
+//! The snapshot format is the queue's own (header plus buffer, CRC-checked),
+//! so a torn write is discarded on the next boot rather than replayed.
+//!
+//! Pushing and replaying only touch RAM and mark the queue dirty; the
+//! snapshot is written by [`flush`], which the event loop calls after each
+//! MQTT event and [`MqttHandle::flush_offline_queue`](super::MqttHandle::flush_offline_queue)
+//! on demand.  The flash write runs under the store's own lock, never the
+//! queue's, so publishers are not held up by it.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context as _;
+
+use esp_idf_svc::mqtt::client::{EspMqttClient, QoS};
+use esp_idf_svc::nvs::{EspDefaultNvsPartition, EspNvs, NvsDefault};
+use esp_idf_svc::partition::EspPartition;
+
+use juggler::mqtt::{
+    newest_slot, slot_header, slot_seq, Enqueued, OverflowPolicy, PublishQueue, QueueError,
+    SLOT_HEADER_LEN, SNAPSHOT_HEADER_LEN,
+};
+
+/// NVS namespace holding the queue snapshot.
+const NAMESPACE: &str = "rf_mqttq";
+
+/// NVS key of the snapshot blob (header followed by the buffer).
+const KEY_SNAPSHOT: &str = "snapshot";
+
+/// Where an [`OfflineQueue`] keeps its publishes.
+pub enum QueueStore {
+    /// RAM only.  Queued publishes are lost on reset.
+    Ram,
+    /// The `rf_mqttq` namespace of an NVS partition.  NVS spreads writes
+    /// over its pages, but a snapshot is one blob of the full capacity, so
+    /// keep the capacity well below the partition's free space.
+    Nvs(EspDefaultNvsPartition),
+    /// A raw data partition, looked up by label in the partition table.
+    /// Snapshots alternate between two slots at its start, each tagged with
+    /// a sequence number and CRC, so a power cut mid-write leaves the
+    /// previous snapshot intact.  It must hold two whole-sector slots and
+    /// must not be opened anywhere else.
+    Partition(&'static str),
+}
+
+/// Store-and-forward settings for publishes made while the broker is
+/// unreachable; see [`MqttBuilder::with_offline_queue`](super::MqttBuilder::with_offline_queue).
+///
+/// # Persistence and flash wear
+///
+/// With a persistent [`QueueStore`] the snapshot is not written on the
+/// publish path.  The event loop writes it after any MQTT event that found
+/// the queue changed — while offline that is about once per reconnect
+/// attempt ([`MqttConfig::with_reconnect_timeout`](super::MqttConfig::with_reconnect_timeout)) —
+/// and [`MqttHandle::flush_offline_queue`](super::MqttHandle::flush_offline_queue)
+/// writes it at once.  Publishes queued since the last write are lost on a
+/// reset, so flush before a planned reset or deep sleep.
+///
+/// Each write erases and rewrites the snapshot: with the default 10 s
+/// reconnect timeout, a ten-minute outage costs about 60 writes.  On a raw
+/// partition those alternate between two slots, halving the wear on each
+/// (rated for roughly 100 000 erase cycles).  Prefer NVS, which
+/// wear-levels, or RAM for high-rate telemetry, and reserve
+/// [`QueueStore::Partition`] for low-rate data that must not be lost.
+pub struct OfflineQueue {
+    capacity: usize,
+    policy: OverflowPolicy,
+    store: QueueStore,
+}
+
+impl OfflineQueue {
+    /// A RAM-only queue of `capacity` bytes that drops the oldest publishes
+    /// when full.
+    ///
+    /// Each publish takes 6 bytes of framing plus its topic and payload.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            policy: OverflowPolicy::DropOldest,
+            store: QueueStore::Ram,
+        }
+    }
+
+    /// Sets what happens to a publish that does not fit.
+    pub fn with_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Persists the queue in NVS so it survives a reset.
+    pub fn with_nvs(mut self, partition: EspDefaultNvsPartition) -> Self {
+        self.store = QueueStore::Nvs(partition);
+        self
+    }
+
+    /// Persists the queue in the raw data partition labelled `label`.
+    pub fn with_partition(mut self, label: &'static str) -> Self {
+        self.store = QueueStore::Partition(label);
+        self
+    }
+}
+
+/// Opened persistence backend.
+enum Backend {
+    Nvs(EspNvs<NvsDefault>),
+    Partition {
+        partition: EspPartition,
+        /// Bytes per slot: slot header, snapshot header, and buffer, rounded
+        /// up to whole erase sectors.
+        slot_len: usize,
+        /// Sequence number of the newest valid snapshot; `0` when none.
+        seq: u32,
+        /// Slot the next snapshot goes to — the one not holding the newest.
+        next_slot: usize,
+    },
+}
+
+/// The live queue shared by the handle and the event loop.
+pub(super) struct OfflineState {
+    queue: PublishQueue<Vec<u8>>,
+    // Mirrors `queue.len()` so the event loop can check it without the lock.
+    queued: Arc<AtomicUsize>,
+    // Set when the queue changed since the last snapshot was taken.
+    dirty: Arc<AtomicBool>,
+}
+
+/// Persistence half of the offline queue, locked separately from
+/// [`OfflineState`] so flash writes never hold the queue lock.
+pub(super) struct OfflineStore {
+    backend: Backend,
+}
+
+impl OfflineState {
+    /// Opens the backend and restores the newest snapshot it holds.
+    ///
+    /// Returns the store alongside, or `None` for a RAM-only queue.
+    pub(super) fn open(config: OfflineQueue) -> anyhow::Result<(Self, Option<OfflineStore>)> {
+        let OfflineQueue {
+            capacity,
+            policy,
+            store,
+        } = config;
+        if capacity == 0 {
+            anyhow::bail!("offline queue capacity must be non-zero");
+        }
+        let snapshot_len = SNAPSHOT_HEADER_LEN + capacity;
+        let (backend, restored) = match store {
+            QueueStore::Ram => (None, None),
+            QueueStore::Nvs(partition) => {
+                let nvs = EspNvs::new(partition, NAMESPACE, true)
+                    .context("failed to open NVS namespace 'rf_mqttq'")?;
+                let restored = match nvs.blob_len(KEY_SNAPSHOT)? {
+                    Some(len) if len == snapshot_len => {
+                        let mut blob = vec![0u8; snapshot_len];
+                        nvs.get_blob(KEY_SNAPSHOT, &mut blob)?;
+                        let buf = blob.split_off(SNAPSHOT_HEADER_LEN);
+                        match PublishQueue::restore(buf, &blob, policy) {
+                            Ok(queue) => Some(queue),
+                            Err(e) => {
+                                log::warn!("[mqtt] discarding offline queue snapshot: {:?}", e);
+                                None
+                            }
+                        }
+                    }
+                    Some(len) => {
+                        log::warn!(
+                            "[mqtt] discarding offline queue snapshot of {} bytes (capacity changed)",
+                            len
+                        );
+                        None
+                    }
+                    None => None,
+                };
+                (Some(Backend::Nvs(nvs)), restored)
+            }
+            QueueStore::Partition(label) => {
+                // SAFETY: the label is documented as reserved for this queue,
+                // so no other EspPartition refers to it.
+                let mut partition = unsafe { EspPartition::new(label) }?
+                    .with_context(|| format!("partition '{}' not found", label))?;
+                // Whole sectors are erased on every write.
+                let slot_len =
+                    (SLOT_HEADER_LEN + snapshot_len).next_multiple_of(partition.erase_size());
+                if partition.size() < 2 * slot_len {
+                    anyhow::bail!(
+                        "partition '{}' holds {} bytes; the offline queue needs {}",
+                        label,
+                        partition.size(),
+                        2 * slot_len
+                    );
+                }
+                let mut slots = [None, None];
+                for (index, slot) in slots.iter_mut().enumerate() {
+                    *slot = read_slot(&mut partition, index * slot_len, snapshot_len, policy)?;
+                }
+                let newest = newest_slot([
+                    slots[0].as_ref().map(|(seq, _)| *seq),
+                    slots[1].as_ref().map(|(seq, _)| *seq),
+                ]);
+                let (seq, restored) = match newest.and_then(|index| slots[index].take()) {
+                    Some((seq, queue)) => (seq, Some(queue)),
+                    None => (0, None),
+                };
+                let backend = Backend::Partition {
+                    partition,
+                    slot_len,
+                    seq,
+                    next_slot: newest.map_or(0, |index| 1 - index),
+                };
+                (Some(backend), restored)
+            }
+        };
+
+        let queue = match restored {
+            Some(queue) => {
+                log::info!("[mqtt] restored {} queued publishes", queue.len());
+                queue
+            }
+            None => PublishQueue::new(vec![0u8; capacity], policy),
+        };
+        let state = Self {
+            queued: Arc::new(AtomicUsize::new(queue.len())),
+            dirty: Arc::new(AtomicBool::new(false)),
+            queue,
+        };
+        Ok((state, backend.map(|backend| OfflineStore { backend })))
+    }
+
+    /// Number of queued publishes.
+    pub(super) fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Shared counter that tracks [`len`](Self::len) and can be read
+    /// without locking the queue.
+    pub(super) fn queued(&self) -> Arc<AtomicUsize> {
+        Arc::clone(&self.queued)
+    }
+
+    /// Shared flag, set when the queue has changed since the last
+    /// [`flush`], that can be read without locking the queue.
+    pub(super) fn dirty(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.dirty)
+    }
+
+    /// Queues a publish made while disconnected.  RAM only; the snapshot
+    /// is written by the next [`flush`].
+    pub(super) fn push(
+        &mut self,
+        topic: &str,
+        payload: &[u8],
+        qos: QoS,
+        retain: bool,
+    ) -> anyhow::Result<()> {
+        match self.queue.push(topic, payload, qos_level(qos), retain) {
+            Ok(Enqueued::Stored) => {
+                log::debug!("[mqtt] offline: queued publish to '{}'", topic);
+            }
+            Ok(Enqueued::Evicted(n)) => {
+                log::warn!(
+                    "[mqtt] offline queue full: evicted {} oldest publish(es)",
+                    n
+                );
+            }
+            Ok(Enqueued::Dropped) => {
+                log::warn!("[mqtt] offline queue full: dropped publish to '{}'", topic);
+                return Ok(());
+            }
+            Err(QueueError::Full) => anyhow::bail!("offline queue full"),
+            Err(e) => anyhow::bail!("cannot queue publish to '{}': {:?}", topic, e),
+        }
+        self.changed();
+        Ok(())
+    }
+
+    /// Enqueues every queued publish on `client`, oldest first.
+    ///
+    /// Stops at the first enqueue failure so nothing is reordered; the rest
+    /// is retried once the outbox drains (the event loop calls this again on
+    /// `Published` and `Deleted`), and new publishes queue behind it.
+    pub(super) fn replay(&mut self, client: &mut EspMqttClient<'_>) {
+        let total = self.queue.len();
+        if total == 0 {
+            return;
+        }
+        let mut sent = 0;
+        while let Some(publish) = self.queue.front() {
+            let qos = if publish.qos == 2 {
+                QoS::ExactlyOnce
+            } else {
+                QoS::AtLeastOnce
+            };
+            if let Err(e) = client.enqueue(publish.topic, qos, publish.retain, publish.payload) {
+                log::warn!(
+                    "[mqtt] replay of '{}' failed, keeping it queued: {:?}",
+                    publish.topic,
+                    e
+                );
+                break;
+            }
+            self.queue.pop_front();
+            sent += 1;
+        }
+        log::info!("[mqtt] replayed {}/{} queued publishes", sent, total);
+        if sent > 0 {
+            self.changed();
+        }
+    }
+
+    fn changed(&mut self) {
+        self.queued.store(self.queue.len(), Ordering::Release);
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// Copies the snapshot header and buffer and clears the dirty flag, or
+    /// returns `None` if nothing changed since the last copy.
+    fn take_snapshot(&mut self) -> Option<([u8; SNAPSHOT_HEADER_LEN], Vec<u8>)> {
+        self.dirty
+            .swap(false, Ordering::AcqRel)
+            .then(|| (self.queue.snapshot_header(), self.queue.as_bytes().to_vec()))
+    }
+}
+
+impl OfflineStore {
+    /// Writes one snapshot to the backend.
+    fn write(&mut self, header: &[u8; SNAPSHOT_HEADER_LEN], buf: &[u8]) -> anyhow::Result<()> {
+        match &mut self.backend {
+            Backend::Nvs(nvs) => {
+                let mut blob = Vec::with_capacity(SNAPSHOT_HEADER_LEN + buf.len());
+                blob.extend_from_slice(header);
+                blob.extend_from_slice(buf);
+                nvs.set_blob(KEY_SNAPSHOT, &blob)?;
+            }
+            Backend::Partition {
+                partition,
+                slot_len,
+                seq,
+                next_slot,
+            } => {
+                // The other slot keeps the previous snapshot until this one
+                // is complete; a torn write fails its CRC on the next boot.
+                let next_seq = seq.saturating_add(1);
+                let offset = *next_slot * *slot_len;
+                partition.erase(offset, *slot_len)?;
+                partition.write(offset, &slot_header(next_seq, header))?;
+                partition.write(offset + SLOT_HEADER_LEN, header)?;
+                partition.write(offset + SLOT_HEADER_LEN + SNAPSHOT_HEADER_LEN, buf)?;
+                *seq = next_seq;
+                *next_slot = 1 - *next_slot;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes the queue in `state` to `store` if it changed since the last
+/// write.
+///
+/// The queue lock is held only to copy the snapshot; the write itself runs
+/// under the store lock, which also keeps concurrent flushes in order.  On
+/// failure the queue stays dirty, so the next flush retries, and the RAM
+/// copy stays authoritative meanwhile.
+pub(super) fn flush(
+    state: &Mutex<OfflineState>,
+    store: &Mutex<OfflineStore>,
+) -> anyhow::Result<()> {
+    let mut store = store
+        .lock()
+        .map_err(|_| anyhow::anyhow!("MQTT offline store mutex poisoned"))?;
+    let mut state_guard = state
+        .lock()
+        .map_err(|_| anyhow::anyhow!("MQTT offline queue mutex poisoned"))?;
+    let Some((header, buf)) = state_guard.take_snapshot() else {
+        return Ok(());
+    };
+    let dirty = state_guard.dirty();
+    drop(state_guard);
+    store
+        .write(&header, &buf)
+        .inspect_err(|_| dirty.store(true, Ordering::Release))
+        .context("failed to persist the offline queue")
+}
+
+/// Reads the slot at `offset` and restores the queue it holds, with its
+/// sequence number, or `None` if the slot is erased, torn, or corrupt.
+fn read_slot(
+    partition: &mut EspPartition,
+    offset: usize,
+    snapshot_len: usize,
+    policy: OverflowPolicy,
+) -> anyhow::Result<Option<(u32, PublishQueue<Vec<u8>>)>> {
+    let mut blob = vec![0u8; SLOT_HEADER_LEN + snapshot_len];
+    partition.read(offset, &mut blob)?;
+    let buf = blob.split_off(SLOT_HEADER_LEN + SNAPSHOT_HEADER_LEN);
+    let (slot, header) = blob.split_at(SLOT_HEADER_LEN);
+    let Some(seq) = slot_seq(slot, header) else {
+        return Ok(None);
+    };
+    match PublishQueue::restore(buf, header, policy) {
+        Ok(queue) => Ok(Some((seq, queue))),
+        Err(e) => {
+            log::warn!(
+                "[mqtt] discarding offline queue slot at {:#x}: {:?}",
+                offset,
+                e
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Wire QoS level of `qos`.
+fn qos_level(qos: QoS) -> u8 {
+    match qos {
+        QoS::AtMostOnce => 0,
+        QoS::AtLeastOnce => 1,
+        QoS::ExactlyOnce => 2,
+    }
+}
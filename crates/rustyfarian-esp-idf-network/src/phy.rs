@@ -0,0 +1,41 @@
+//! PHY settings shared by the Wi-Fi managers and the ESP-NOW driver.
+
+use anyhow::Context as _;
+use juggler::wifi::{WifiBandwidth, WifiPhy};
+
+/// Applies `phy` to `interface`, leaving the driver default alone.
+///
+/// Both calls need the interface enabled, so callers make them right after
+/// `wifi.start()` and before any association, as the ESP-IDF ESP-NOW example
+/// does.  The protocol goes first: HT40 is refused unless 802.11n is already
+/// on.
+pub(crate) fn apply_phy(
+    interface: esp_idf_svc::sys::wifi_interface_t,
+    phy: WifiPhy,
+) -> anyhow::Result<()> {
+    if phy.is_default() {
+        return Ok(());
+    }
+    // SAFETY: plain FFI setters on an interface of a started radio; both take
+    // their arguments by value.
+    esp_idf_svc::sys::esp!(unsafe {
+        esp_idf_svc::sys::esp_wifi_set_protocol(interface, phy.protocol.bitmap())
+    })
+    .with_context(|| format!("failed to set Wi-Fi protocol {:?}", phy.protocol))?;
+    let bandwidth = match phy.bandwidth {
+        WifiBandwidth::Ht20 => esp_idf_svc::sys::wifi_bandwidth_t_WIFI_BW_HT20,
+        WifiBandwidth::Ht40 => esp_idf_svc::sys::wifi_bandwidth_t_WIFI_BW_HT40,
+    };
+    // SAFETY: as above.
+    esp_idf_svc::sys::esp!(unsafe {
+        esp_idf_svc::sys::esp_wifi_set_bandwidth(interface, bandwidth)
+    })
+    .with_context(|| format!("failed to set Wi-Fi bandwidth {:?}", phy.bandwidth))?;
+    log::info!(
+        "WiFi PHY on interface {}: {:?}, {:?}",
+        interface,
+        phy.protocol,
+        phy.bandwidth
+    );
+    Ok(())
+}
@@ -78,7 +78,7 @@ use esp_idf_svc::hal::modem::Modem;
 use esp_idf_svc::nvs::EspDefaultNvsPartition;
 use esp_idf_svc::wifi::WifiEvent;
 
-use crate::wifi::{softap_mac, ApConfig, SoftApManager};
+use crate::wifi::{softap_mac, ApConfig, ApSubnet, SoftApManager};
 use juggler::provisioning::{resolve_wait, ProvisioningInput, WaitResolution};
 
 use dns::DnsResponder;
@@ -361,6 +361,7 @@ const DEFAULT_MAX_CONNECTIONS: u8 = juggler::wifi::AP_MAX_CONNECTIONS_DEFAULT;
 /// Builder for a [`ProvisioningSession`].
 pub struct ProvisioningBuilder<'a> {
     config: PortalConfig<'a>,
+    subnet: ApSubnet,
     status_entries: Vec<(String, String)>,
     on_event: Option<Arc<dyn Fn(ProvisioningEvent) + Send + Sync + 'static>>,
 }
@@ -372,11 +373,22 @@ impl<'a> ProvisioningBuilder<'a> {
     pub fn new(config: PortalConfig<'a>) -> Self {
         Self {
             config,
+            subnet: ApSubnet::DEFAULT,
             status_entries: Vec::new(),
             on_event: None,
         }
     }
 
+    /// Experimental: API may change before 1.0.
+    ///
+    /// Moves the portal AP off the default `192.168.4.1/24` subnet, e.g.
+    /// when a nearby router already uses it.  The DNS responder answers with
+    /// the new AP address.
+    pub fn with_ap_subnet(mut self, subnet: ApSubnet) -> Self {
+        self.subnet = subnet;
+        self
+    }
+
     /// Experimental: API may change before 1.0.
     ///
     /// Adds a `key`/`value` pair rendered under the `/status` `extra` object.
@@ -441,7 +453,9 @@ impl<'a> ProvisioningBuilder<'a> {
             None => ApConfig::open(ssid.as_str()),
         }
         .with_channel(self.config.channel)
-        .with_max_connections(DEFAULT_MAX_CONNECTIONS);
+        .with_max_connections(DEFAULT_MAX_CONNECTIONS)
+        .with_ip(self.subnet.ip, self.subnet.netmask)
+        .with_dhcp_range(self.subnet.dhcp_start, self.subnet.dhcp_end);
 
         let softap = SoftApManager::start(modem, sys_loop.clone(), Some(nvs.clone()), ap_config)
             .context("failed to start SoftAP")?;
@@ -0,0 +1,74 @@
+//! mDNS responder backed by the ESP-IDF `espressif/mdns` component.
+//!
+//! Requires the `mdns` feature **and** the component itself, which ESP-IDF
+//! 5.x no longer bundles.  Add it to the firmware crate's `Cargo.toml`:
+//!
+//! ```toml
+//! [[package.metadata.esp-idf-sys.extra_components]]
+//! remote_component = { name = "espressif/mdns", version = "1.2" }
+//! ```
+//!
+//! Without the component `esp_idf_svc::mdns` does not exist, so this module
+//! is compiled out (gated on the `esp_idf_comp_*mdns_enabled` cfg that
+//! `esp-idf-sys` sets) and `MdnsResponder` is missing even with the feature
+//! on.
+
+use anyhow::Context as _;
+use esp_idf_svc::mdns::EspMdns;
+use juggler::wifi::{validate_mdns_config, MdnsConfig};
+
+/// Running mDNS responder.
+///
+/// Answers `<hostname>.local` and advertises the configured DNS-SD services
+/// on every ESP-IDF interface that is up, for as long as the value is kept
+/// alive; dropping it stops the responder (`mdns_free`).  Only one can exist
+/// at a time.
+///
+/// ```ignore
+/// static SERVICES: [MdnsService; 1] = [MdnsService::new("_http", ServiceProtocol::Tcp, 80)];
+///
+/// let wifi = WiFiManager::init(config.with_peripherals(modem, sys_loop, Some(nvs)))?;
+/// let _mdns = MdnsResponder::start(&MdnsConfig::new("greenhouse").with_services(&SERVICES))?;
+/// ```
+pub struct MdnsResponder {
+    _mdns: EspMdns,
+}
+
+impl MdnsResponder {
+    /// Validates `config` with [`validate_mdns_config`] and starts the
+    /// responder.
+    ///
+    /// The component manages record TTLs itself, so
+    /// [`MdnsConfig::ttl_secs`] is ignored on this tier.
+    pub fn start(config: &MdnsConfig<'_>) -> anyhow::Result<Self> {
+        validate_mdns_config(config).map_err(|e| anyhow::anyhow!("mDNS config invalid: {}", e))?;
+
+        let mut mdns = EspMdns::take().context("failed to initialise mDNS")?;
+        mdns.set_hostname(config.hostname)
+            .context("failed to set mDNS hostname")?;
+        mdns.set_instance_name(config.hostname)
+            .context("failed to set mDNS instance name")?;
+        for service in config.services {
+            mdns.add_service(
+                Some(service.instance.unwrap_or(config.hostname)),
+                service.service,
+                service.protocol.label(),
+                service.port,
+                service.txt,
+            )
+            .with_context(|| {
+                format!(
+                    "failed to add mDNS service {}.{}",
+                    service.service,
+                    service.protocol.label()
+                )
+            })?;
+        }
+        log::info!(
+            "mDNS responder up: {}.local ({} service(s))",
+            config.hostname,
+            config.services.len()
+        );
+        Ok(Self { _mdns: mdns })
+    }
+}
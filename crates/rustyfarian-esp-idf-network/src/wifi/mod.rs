@@ -50,15 +50,54 @@
 //! let config = WiFiConfig::new("MyNetwork", "password123");
 //! let wifi = WiFiManager::new(modem, sys_loop, Some(nvs), config, Some(&mut led))?;
 //! ```
+//!
+//! # Multiple Networks
+//!
+//! Devices that move between sites can carry a prioritised list.  The manager
+//! scans once, tries visible networks by priority then signal strength, and
+//! falls back to the next candidate when one fails:
+//!
+//! ```ignore
+//! use rustyfarian_esp_idf_network::wifi::{WiFiConfig, WifiNetwork, WifiNetworkList};
+//!
+//! let mut networks = WifiNetworkList::new();
+//! networks.push(WifiNetwork::new("Workshop", "psk-1").with_priority(10))?;
+//! networks.push(WifiNetwork::new("Lab", "psk-2").with_priority(5))?;
+//! let config = WiFiConfig::from_networks(networks)
+//!     .with_peripherals(peripherals.modem, sys_loop, Some(nvs));
+//! let wifi = WiFiManager::init(config)?;
+//! ```
+//!
+//! # Fast Reconnect After Deep Sleep
+//!
+//! Keep a [`WifiFastConnectCache`] in RTC memory and hand it back on every
+//! wake; the manager skips the scan when the cached AP is still there:
+//!
+//! ```ignore
+//! #[link_section = ".rtc.data"]
+//! static mut CACHE: WifiFastConnectCache = WifiFastConnectCache::empty();
+//!
+//! let config = WiFiConfig::new("MyNetwork", "password123")
+//!     .with_fast_connect(unsafe { CACHE })
+//!     .with_peripherals(peripherals.modem, sys_loop, Some(nvs));
+//! let wifi = WiFiManager::init(config)?;
+//! // ... publish ...
+//! unsafe { CACHE = wifi.fast_connect_cache()? };
+//! ```
 
 use std::net::Ipv4Addr;
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
 use anyhow::Context as _;
 
+use crate::phy::apply_phy;
+
 use esp_idf_svc::eventloop::{EspSystemEventLoop, EspSystemSubscription};
 use esp_idf_svc::hal::modem::Modem;
+use esp_idf_svc::ipv4;
+use esp_idf_svc::netif::{EspNetif, NetifConfiguration};
 use esp_idf_svc::nvs::EspDefaultNvsPartition;
 use esp_idf_svc::wifi::{
     AccessPointConfiguration, AuthMethod, BlockingWifi, ClientConfiguration, Configuration,
@@ -69,14 +108,46 @@ use rgb::RGB8;
 
 // Re-export all pure types from juggler::wifi
 pub use juggler::wifi::{
-    validate_ap_config, validate_password, validate_ssid, wifi_disconnect_reason_name, ApConfig,
-    ConnectMode, TxPowerLevel, WiFiConfig, WifiDriver, WifiPowerSave, AP_MAX_CONNECTIONS_DEFAULT,
-    AP_PASSWORD_MIN_LEN, DEFAULT_TIMEOUT_SECS, PASSWORD_MAX_LEN, POLL_INTERVAL_MS, SSID_MAX_LEN,
+    classify_disconnect, insert_scan_result, netmask_prefix_len, priority_order, rank_networks,
+    scan_candidates, select_network, validate_ap_config, validate_ap_subnet, validate_auth_method,
+    validate_hostname, validate_ip_config, validate_link_quality_config, validate_mdns_config,
+    validate_network_list, validate_password, validate_phy, validate_ssid,
+    wifi_disconnect_reason_name, ApConfig, ApInfo, ApRejectReason, ApStation, ApStationEvent,
+    ApStationTable, ApStations, ApSubnet, AsyncWifiDriver, AuthMode, ConnectMode, Country,
+    DisconnectClass, DisconnectReason, IpConfig, LinkQuality, LinkQualityConfig, LinkQualityEvent,
+    LinkQualityMonitor, MdnsConfig, MdnsService, NetworkOrder, ScanResults, ServiceProtocol,
+    TxPowerLevel, WiFiConfig, WifiAuthMethod, WifiBandwidth, WifiDriver, WifiNetwork,
+    WifiNetworkList, WifiPhy, WifiPowerSave, WifiProtocol, AP_ALLOWLIST_MAX,
+    AP_MAX_CONNECTIONS_DEFAULT, AP_MAX_CONNECTIONS_MAX, AP_PASSWORD_MIN_LEN,
+    DEFAULT_ANTENNA_GAIN_DBI, DEFAULT_TIMEOUT_SECS, HOSTNAME_MAX_LEN, MAX_NETWORKS,
+    MDNS_MAX_SERVICES, MDNS_MAX_TXT_ENTRIES, PASSWORD_MAX_LEN, POLL_INTERVAL_MS,
+    QUALITY_RECOVERED_DBM, QUALITY_ROAM_DBM, QUALITY_WEAK_DBM, SCAN_MAX_RESULTS, SSID_MAX_LEN,
+};
+pub use juggler::wifi::{
+    derive_pmk, pmk_applicable, WifiFastConnectCache, FAST_CONNECT_TIMEOUT_SECS, PMK_LEN,
 };
 
+// `esp_idf_svc::mdns` only exists when the firmware pulls in the mDNS
+// component; without it the `mdns` feature compiles to nothing, so
+// `--all-features` checks still build.
+#[cfg(all(
+    feature = "mdns",
+    any(esp_idf_comp_mdns_enabled, esp_idf_comp_espressif__mdns_enabled)
+))]
+mod mdns;
+mod stations;
+#[cfg(all(
+    feature = "mdns",
+    any(esp_idf_comp_mdns_enabled, esp_idf_comp_espressif__mdns_enabled)
+))]
+pub use mdns::MdnsResponder;
+
 // Re-export StatusLed and SimpleLed from pennant for convenience
 pub use pennant::{SimpleLed, StatusLed};
 
+use stations::ApStationTracker;
+
+use juggler::pem::pem_needs_nul;
 use juggler::status_colors;
 
 /// ESP-IDF Wi-Fi configuration bundled with the hardware peripherals needed for init.
@@ -89,6 +160,13 @@ pub struct IdfWifiConfig<'a> {
     connect_mode: ConnectMode,
     power_save: WifiPowerSave,
     tx_power: TxPowerLevel,
+    network_list: WifiNetworkList<'a>,
+    ip: IpConfig,
+    auth: WifiAuthMethod<'a>,
+    hostname: Option<&'a str>,
+    country: Option<Country>,
+    fast_connect: Option<WifiFastConnectCache>,
+    phy: WifiPhy,
     modem: Modem<'static>,
     sys_loop: EspSystemEventLoop,
     nvs: Option<EspDefaultNvsPartition>,
@@ -119,6 +197,13 @@ impl<'a> WiFiConfigExt<'a> for WiFiConfig<'a> {
             connect_mode: self.connect_mode,
             power_save: self.power_save,
             tx_power: self.tx_power,
+            network_list: self.network_list,
+            ip: self.ip,
+            auth: self.auth,
+            hostname: self.hostname,
+            country: self.country,
+            fast_connect: self.fast_connect,
+            phy: self.phy,
             modem,
             sys_loop,
             nvs,
@@ -142,6 +227,54 @@ pub struct WiFiManager {
     wifi: BlockingWifi<EspWifi<'static>>,
     /// Kept alive to receive disconnect-reason log events in non-blocking mode.
     _disconnect_subscription: Option<EspSystemSubscription<'static>>,
+    /// Per-network station configs; kept alive because ESP-IDF holds
+    /// pointers into the EAP-TLS certificate buffers while connected.
+    candidates: Vec<StaCandidate>,
+    /// Whether the 802.1X supplicant is currently enabled.
+    enterprise_active: bool,
+    /// Cache passed in through [`WiFiConfig::with_fast_connect`], consulted
+    /// by [`WiFiManager::fast_connect_cache`] to avoid re-deriving the PMK.
+    fast_connect: Option<WifiFastConnectCache>,
+}
+
+/// Station configuration for one network, plus an owned copy of its EAP
+/// material for 802.1X networks.
+struct StaCandidate {
+    client: ClientConfiguration,
+    enterprise: Option<EnterpriseCredentials>,
+    /// Whether a PMK may replace the passphrase ([`pmk_applicable`]).
+    pmk_applicable: bool,
+}
+
+/// EAP material copied out of a [`WifiAuthMethod`].
+///
+/// `esp_eap_client_set_identity` / `_username` / `_password` copy their
+/// input, but `esp_eap_client_set_ca_cert` and
+/// `esp_eap_client_set_certificate_and_key` only store the pointer, so the
+/// buffers must outlive the connection.  PEM buffers also need a trailing NUL
+/// counted in their length for mbedTLS to parse them; [`owned_cert`] adds it.
+enum EnterpriseCredentials {
+    Peap {
+        identity: String,
+        username: String,
+        password: String,
+    },
+    Tls {
+        identity: String,
+        client_cert: Vec<u8>,
+        private_key: Vec<u8>,
+        ca_cert: Option<Vec<u8>>,
+    },
+}
+
+/// Copies a PEM or DER blob, appending the NUL terminator mbedTLS requires
+/// for PEM input.
+fn owned_cert(bytes: &[u8]) -> Vec<u8> {
+    let mut owned = bytes.to_vec();
+    if pem_needs_nul(bytes) {
+        owned.push(0);
+    }
+    owned
 }
 
 impl WiFiManager {
@@ -156,13 +289,19 @@ impl WiFiManager {
     /// let wifi = WiFiManager::init(config)?;
     /// ```
     pub fn init(config: IdfWifiConfig<'_>) -> anyhow::Result<Self> {
-        let wifi_config = WiFiConfig {
-            ssid: config.ssid,
-            password: config.password,
-            connect_mode: config.connect_mode,
-            power_save: config.power_save,
-            tx_power: config.tx_power,
-        };
+        // `WiFiConfig` is `#[non_exhaustive]`: start from `new` and copy
+        // every field over.
+        let mut wifi_config = WiFiConfig::new(config.ssid, config.password);
+        wifi_config.connect_mode = config.connect_mode;
+        wifi_config.power_save = config.power_save;
+        wifi_config.tx_power = config.tx_power;
+        wifi_config.network_list = config.network_list;
+        wifi_config.ip = config.ip;
+        wifi_config.auth = config.auth;
+        wifi_config.hostname = config.hostname;
+        wifi_config.country = config.country;
+        wifi_config.fast_connect = config.fast_connect;
+        wifi_config.phy = config.phy;
         Self::new_without_led(config.modem, config.sys_loop, config.nvs, wifi_config)
     }
 
@@ -182,6 +321,73 @@ impl WiFiManager {
     /// - Blue pulse: Connecting
     /// - Red pulse: Connection timeout (loops forever)
     /// - Green: Connected successfully
+    ///
+    /// # Multiple networks
+    ///
+    /// When `config` carries more than one network (see
+    /// [`WiFiConfig::from_networks`]), the manager scans once after
+    /// `wifi.start()` and tries the candidates in [`rank_networks`] order:
+    /// visible networks by priority then RSSI, then unseen (possibly hidden)
+    /// networks by priority.  Each candidate gets the full blocking timeout;
+    /// on failure the manager disconnects, applies the next candidate, and
+    /// retries.  A failed scan is logged and falls back to
+    /// [`priority_order`].
+    ///
+    /// In non-blocking mode there is no failure signal to fall back on, so
+    /// only the best-ranked candidate is attempted.
+    ///
+    /// # Static IP
+    ///
+    /// With [`IpConfig::Static`] (see [`WiFiConfig::with_static_ip`]) the
+    /// default STA netif is swapped for one with a fixed address before the
+    /// driver starts, so the DHCP client never runs.  [`WiFiManager::get_ip`]
+    /// then reports the configured address as soon as the link is up.
+    ///
+    /// # WPA3 and enterprise networks
+    ///
+    /// Each network's [`WifiAuthMethod`] selects the station auth mode:
+    /// WPA3-SAE and WPA2/WPA3 transition set the matching threshold, while the
+    /// EAP variants enable the ESP-IDF 802.1X supplicant
+    /// (`esp_wifi_sta_enterprise_enable`) with the given identity material.
+    /// The supplicant is switched off again when falling back to a
+    /// personal-mode network.  Enterprise support requires
+    /// `CONFIG_ESP_WIFI_ENTERPRISE_SUPPORT=y` (the ESP-IDF default).
+    ///
+    /// # Hostname
+    ///
+    /// A hostname set with [`WiFiConfig::with_hostname`] is checked with
+    /// [`validate_hostname`] and applied to the STA netif before the driver
+    /// starts, replacing the ESP-IDF default `espressif` in DHCP requests.
+    /// Answering `<hostname>.local` additionally needs an
+    /// `MdnsResponder` (`mdns` feature).
+    ///
+    /// # Country
+    ///
+    /// A [`Country`] set with [`WiFiConfig::with_country`] is applied before
+    /// the driver starts, limiting scans and association to its channels,
+    /// and the TX power is lowered to the domain limit
+    /// ([`WiFiConfig::effective_tx_power`]).  Without one the ESP-IDF
+    /// default (`"01"`, channels 1–11 active) stays in effect.
+    ///
+    /// # Fast reconnect
+    ///
+    /// A [`WifiFastConnectCache`] set with [`WiFiConfig::with_fast_connect`]
+    /// that matches one of the networks replaces the scan: in blocking mode
+    /// the station is pinned to the cached BSSID and channel and gets
+    /// [`FAST_CONNECT_TIMEOUT_SECS`] to associate before the manager falls
+    /// back to the normal candidate walk above.  Non-blocking mode has no
+    /// failure signal, so only the channel is used, as a hint where the
+    /// driver's scan starts.  A cached PMK stands in for the passphrase of
+    /// WPA2-Personal networks.  Read the cache to store for the next wake
+    /// with [`WiFiManager::fast_connect_cache`].
+    ///
+    /// # PHY mode
+    ///
+    /// A protocol set or bandwidth chosen with [`WiFiConfig::with_protocol`]
+    /// / [`WiFiConfig::with_bandwidth`] is checked with [`validate_phy`] up
+    /// front and applied to the STA interface right after `wifi.start()`,
+    /// before association.  The default ([`WifiPhy::DEFAULT`]) leaves the
+    /// driver untouched.
     pub fn new<L>(
         modem: Modem<'static>,
         sys_loop: EspSystemEventLoop,
@@ -193,38 +399,90 @@ impl WiFiManager {
         L: StatusLed,
         L::Error: std::fmt::Debug,
     {
-        log::info!("Connecting to WiFi SSID (len={})", config.ssid.len());
+        let networks = config.networks();
+        log::info!(
+            "Connecting to WiFi SSID (len={}, {} network(s) configured)",
+            config.ssid.len(),
+            networks.len()
+        );
+
+        validate_ip_config(&config.ip)
+            .map_err(|e| anyhow::anyhow!("WiFi static IP config invalid: {}", e))?;
+        if let Some(hostname) = config.hostname {
+            validate_hostname(hostname)
+                .map_err(|e| anyhow::anyhow!("WiFi hostname invalid: {}", e))?;
+        }
+        validate_phy(&config.phy).map_err(|e| anyhow::anyhow!("WiFi PHY config invalid: {}", e))?;
+        if config.phy.protocol == WifiProtocol::Lr {
+            log::warn!("WiFi station is LR-only; it can only join an LR-only Espressif AP");
+        }
 
         // Clone before sys_loop is consumed by BlockingWifi::wrap; used for the
         // optional disconnect-event subscription in non-blocking mode.
         let sys_loop_sub = sys_loop.clone();
-        let mut wifi = BlockingWifi::wrap(EspWifi::new(modem, sys_loop.clone(), nvs)?, sys_loop)?;
-
-        validate_ssid(config.ssid)
-            .map_err(|e| anyhow::anyhow!("WiFi SSID invalid (len={}): {}", config.ssid.len(), e))?;
-        validate_password(config.password).map_err(anyhow::Error::msg)?;
+        let mut esp_wifi = EspWifi::new(modem, sys_loop.clone(), nvs)?;
+        if let IpConfig::Static {
+            ip,
+            netmask,
+            gateway,
+            dns,
+        } = config.ip
+        {
+            // The default STA netif runs the DHCP client; replace it before
+            // the driver starts so no DHCP DISCOVER ever goes out.
+            esp_wifi
+                .swap_netif_sta(Self::static_sta_netif(ip, netmask, gateway, dns)?)
+                .context("failed to attach static-IP STA netif")?;
+            log::info!("WiFi static IP {}/{} via {}", ip, netmask, gateway);
+        }
+        if let Some(hostname) = config.hostname {
+            // Must land before `wifi.start()` so the first DHCP DISCOVER
+            // already carries it.
+            Self::set_sta_hostname(&esp_wifi, hostname)?;
+        }
+        let mut wifi = BlockingWifi::wrap(esp_wifi, sys_loop)?;
 
-        let ssid = config.ssid.try_into().map_err(|_| {
-            anyhow::anyhow!(
-                "internal: SSID conversion failed after validation (len={}, limit={})",
-                config.ssid.len(),
-                SSID_MAX_LEN
-            )
-        })?;
-        let password = config.password.try_into().map_err(|_| {
-            anyhow::anyhow!(
-                "internal: password conversion failed after validation (len={}, limit={})",
-                config.password.len(),
-                PASSWORD_MAX_LEN
-            )
-        })?;
+        // Validate every candidate up front so a typo in a fallback entry
+        // surfaces at boot rather than only when the primary network is down.
+        let mut candidates = Vec::with_capacity(networks.len());
+        for network in &networks {
+            candidates.push(Self::sta_candidate(network)?);
+        }
 
-        wifi.set_configuration(&Configuration::Client(ClientConfiguration {
-            ssid,
-            password,
-            ..Default::default()
-        }))?;
+        // Start with the cached AP, or else the preferred entry; `wifi.start()`
+        // needs a configuration and a single-network config never
+        // reconfigures after this unless the fast connect fails.
+        // `networks()` never returns an empty list, so index 0 exists.
+        let blocking = led.is_some() || matches!(config.connect_mode, ConnectMode::Blocking { .. });
+        let fast = config
+            .fast_connect
+            .and_then(|cache| Self::fast_client(&cache, &networks, &candidates, blocking));
+        let first = fast
+            .as_ref()
+            .map_or_else(|| priority_order(&networks)[0], |(index, _)| *index);
+        let mut enterprise_active = false;
+        match &fast {
+            Some((index, client)) => {
+                log::info!(
+                    "WiFi fast connect: cached AP on channel {:?}",
+                    client.channel
+                );
+                Self::apply_station(
+                    &mut wifi,
+                    client,
+                    candidates[*index].enterprise.as_ref(),
+                    None,
+                    &mut enterprise_active,
+                )?;
+            }
+            None => {
+                Self::apply_candidate(&mut wifi, &candidates[first], None, &mut enterprise_active)?
+            }
+        }
 
+        if let Some(country) = config.country {
+            apply_country(country)?;
+        }
         wifi.start()?;
 
         let ps_mode = match config.power_save {
@@ -235,19 +493,21 @@ impl WiFiManager {
         esp_idf_svc::sys::esp!(unsafe { esp_idf_svc::sys::esp_wifi_set_ps(ps_mode) })
             .context("failed to set WiFi power save mode")?;
         log::info!("WiFi started, power save: {:?}", config.power_save);
+        apply_phy(esp_idf_svc::sys::wifi_interface_t_WIFI_IF_STA, config.phy)?;
 
-        let tx_power = config.tx_power.to_quarter_dbm();
+        let tx_level = config.effective_tx_power();
+        let tx_power = tx_level.to_quarter_dbm();
         match esp_idf_svc::sys::esp!(unsafe {
             esp_idf_svc::sys::esp_wifi_set_max_tx_power(tx_power)
         }) {
             Ok(()) => log::info!(
                 "WiFi TX power set to {:?} ({} quarter-dBm)",
-                config.tx_power,
+                tx_level,
                 tx_power
             ),
             Err(e) => log::warn!(
                 "Failed to set WiFi TX power to {:?} ({} quarter-dBm), continuing at radio default: {:?}",
-                config.tx_power,
+                tx_level,
                 tx_power,
                 e
             ),
@@ -261,17 +521,20 @@ impl WiFiManager {
                     .subscribe::<WifiEvent, _>(|event: WifiEvent<'_>| {
                         if let WifiEvent::StaDisconnected(info) = event {
                             let reason = info.reason();
+                            let class = classify_disconnect(reason).name();
                             match wifi_disconnect_reason_name(reason) {
                                 Some(name) => log::warn!(
-                                    "WiFi disconnected — reason {} ({}) — \
+                                    "WiFi disconnected — reason {} ({}, {}) — \
                                      check SSID/password and network availability",
                                     reason,
                                     name,
+                                    class,
                                 ),
                                 None => log::warn!(
-                                    "WiFi disconnected — reason {} (unmapped) — \
+                                    "WiFi disconnected — reason {} (unmapped, {}) — \
                                      check SSID/password and network availability",
                                     reason,
+                                    class,
                                 ),
                             }
                         }
@@ -282,7 +545,7 @@ impl WiFiManager {
             None
         };
 
-        if let Some(led_driver) = led {
+        if blocking {
             let timeout_secs = match config.connect_mode {
                 ConnectMode::Blocking { timeout_secs } => timeout_secs,
                 ConnectMode::NonBlocking => {
@@ -294,24 +557,78 @@ impl WiFiManager {
                     DEFAULT_TIMEOUT_SECS
                 }
             };
-            Self::connect_with_led(&mut wifi, led_driver, timeout_secs)?;
-        } else {
-            match config.connect_mode {
-                ConnectMode::Blocking { timeout_secs } => {
-                    Self::wait_for_connection(&mut wifi, timeout_secs)?;
-                }
-                ConnectMode::NonBlocking => {
-                    wifi.wifi_mut()
-                        .connect()
-                        .context("WiFi connect initiation failed")?;
-                    log::info!("WiFi connect initiated (non-blocking)");
+            let mut led = led;
+            let mut attempt =
+                |wifi: &mut BlockingWifi<EspWifi<'static>>, secs: u64| match led.as_deref_mut() {
+                    Some(led_driver) => Self::connect_with_led(wifi, led_driver, secs),
+                    None => Self::wait_for_connection(wifi, secs),
+                };
+
+            let fast_connected = match fast {
+                Some(_) => match attempt(&mut wifi, FAST_CONNECT_TIMEOUT_SECS.min(timeout_secs)) {
+                    Ok(()) => {
+                        log::info!("WiFi fast connect succeeded");
+                        true
+                    }
+                    Err(e) => {
+                        log::warn!(
+                            "WiFi fast connect failed, falling back to a full connect: {:#}",
+                            e
+                        );
+                        if let Err(e) = wifi.wifi_mut().disconnect() {
+                            log::debug!("disconnect before fallback failed (ignored): {:?}", e);
+                        }
+                        Self::apply_candidate(
+                            &mut wifi,
+                            &candidates[first],
+                            None,
+                            &mut enterprise_active,
+                        )?;
+                        false
+                    }
+                },
+                None => false,
+            };
+
+            if !fast_connected {
+                let order = if networks.len() > 1 {
+                    Self::rank_by_scan(&mut wifi, &networks)
+                } else {
+                    priority_order(&networks)
+                };
+                if order[0] != first {
+                    Self::apply_candidate(
+                        &mut wifi,
+                        &candidates[order[0]],
+                        None,
+                        &mut enterprise_active,
+                    )?;
                 }
+                Self::connect_candidates(
+                    &mut wifi,
+                    &networks,
+                    &order,
+                    &candidates,
+                    None,
+                    &mut enterprise_active,
+                    |wifi| attempt(wifi, timeout_secs),
+                )?;
             }
+        } else {
+            // Non-blocking mode tries only the best-ranked candidate, which
+            // `first` already is.
+            wifi.wifi_mut()
+                .connect()
+                .context("WiFi connect initiation failed")?;
+            log::info!("WiFi connect initiated (non-blocking)");
         }
 
         Ok(Self {
             wifi,
             _disconnect_subscription: disconnect_subscription,
+            candidates,
+            enterprise_active,
+            fast_connect: config.fast_connect,
         })
     }
 
@@ -328,6 +645,346 @@ impl WiFiManager {
         Self::new::<NoLed>(modem, sys_loop, nvs, config, None)
     }
 
+    /// Builds the station configuration for one network, validating the
+    /// SSID and authentication settings first.
+    fn sta_candidate(network: &WifiNetwork<'_>) -> anyhow::Result<StaCandidate> {
+        let ssid = network.ssid;
+        validate_ssid(ssid)
+            .map_err(|e| anyhow::anyhow!("WiFi SSID invalid (len={}): {}", ssid.len(), e))?;
+        validate_auth_method(&network.auth, network.password)
+            .map_err(|e| anyhow::anyhow!("WiFi {} settings invalid: {}", network.auth.name(), e))?;
+
+        let ssid_field = ssid.try_into().map_err(|_| {
+            anyhow::anyhow!(
+                "internal: SSID conversion failed after validation (len={}, limit={})",
+                ssid.len(),
+                SSID_MAX_LEN
+            )
+        })?;
+        // Enterprise networks authenticate through the supplicant; the PSK
+        // field stays empty.
+        let password = if network.auth.is_enterprise() {
+            ""
+        } else {
+            network.password
+        };
+        let password_field = password.try_into().map_err(|_| {
+            anyhow::anyhow!(
+                "internal: password conversion failed after validation (len={}, limit={})",
+                password.len(),
+                PASSWORD_MAX_LEN
+            )
+        })?;
+
+        let (auth_method, enterprise) = match network.auth {
+            WifiAuthMethod::Wpa2Personal => (AuthMethod::WPA2Personal, None),
+            WifiAuthMethod::Wpa3Personal => (AuthMethod::WPA3Personal, None),
+            WifiAuthMethod::Wpa2Wpa3Personal => (AuthMethod::WPA2WPA3Personal, None),
+            WifiAuthMethod::EapPeap {
+                identity,
+                username,
+                password,
+            } => (
+                AuthMethod::WPA2Enterprise,
+                Some(EnterpriseCredentials::Peap {
+                    identity: identity.to_owned(),
+                    username: username.to_owned(),
+                    password: password.to_owned(),
+                }),
+            ),
+            WifiAuthMethod::EapTls {
+                identity,
+                client_cert,
+                private_key,
+                ca_cert,
+            } => (
+                AuthMethod::WPA2Enterprise,
+                Some(EnterpriseCredentials::Tls {
+                    identity: identity.to_owned(),
+                    client_cert: owned_cert(client_cert),
+                    private_key: owned_cert(private_key),
+                    ca_cert: ca_cert.map(owned_cert),
+                }),
+            ),
+        };
+
+        Ok(StaCandidate {
+            client: ClientConfiguration {
+                ssid: ssid_field,
+                password: password_field,
+                auth_method,
+                ..Default::default()
+            },
+            enterprise,
+            pmk_applicable: pmk_applicable(&network.auth, network.password),
+        })
+    }
+
+    /// Station config for the network `cache` was recorded for, pinned to
+    /// the cached channel (and, when `pin_bssid`, the cached BSSID), with
+    /// the cached PMK as the password where it applies.
+    ///
+    /// Returns the network's index alongside, or `None` if `cache` is
+    /// invalid or matches no configured network.
+    fn fast_client(
+        cache: &WifiFastConnectCache,
+        networks: &WifiNetworkList<'_>,
+        candidates: &[StaCandidate],
+        pin_bssid: bool,
+    ) -> Option<(usize, ClientConfiguration)> {
+        let index = cache.network_index(networks)?;
+        let candidate = candidates.get(index)?;
+        let mut client = candidate.client.clone();
+        client.channel = Some(cache.channel());
+        if pin_bssid {
+            client.bssid = Some(cache.bssid());
+        }
+        if candidate.pmk_applicable {
+            // `heapless` differs between juggler and embedded-svc; go via &str.
+            if let Some(psk) = cache.psk_hex().and_then(|psk| psk.as_str().try_into().ok()) {
+                client.password = psk;
+            }
+        }
+        Some((index, client))
+    }
+
+    /// Applies a candidate's station configuration and switches the 802.1X
+    /// supplicant on or off to match.
+    ///
+    /// With `ap` set the driver is configured in mixed mode, keeping that
+    /// access point up alongside the station.
+    fn apply_candidate(
+        wifi: &mut BlockingWifi<EspWifi<'static>>,
+        candidate: &StaCandidate,
+        ap: Option<&AccessPointConfiguration>,
+        enterprise_active: &mut bool,
+    ) -> anyhow::Result<()> {
+        Self::apply_station(
+            wifi,
+            &candidate.client,
+            candidate.enterprise.as_ref(),
+            ap,
+            enterprise_active,
+        )
+    }
+
+    /// [`Self::apply_candidate`] with the station config given separately,
+    /// for the fast-connect variant of a candidate.
+    fn apply_station(
+        wifi: &mut BlockingWifi<EspWifi<'static>>,
+        client: &ClientConfiguration,
+        enterprise: Option<&EnterpriseCredentials>,
+        ap: Option<&AccessPointConfiguration>,
+        enterprise_active: &mut bool,
+    ) -> anyhow::Result<()> {
+        let configuration = match ap {
+            Some(ap) => Configuration::Mixed(client.clone(), ap.clone()),
+            None => Configuration::Client(client.clone()),
+        };
+        wifi.set_configuration(&configuration)?;
+        match enterprise {
+            Some(credentials) => {
+                Self::enable_enterprise(credentials)?;
+                *enterprise_active = true;
+            }
+            None if *enterprise_active => {
+                esp_idf_svc::sys::esp!(unsafe {
+                    esp_idf_svc::sys::esp_wifi_sta_enterprise_disable()
+                })
+                .context("failed to disable WiFi enterprise mode")?;
+                *enterprise_active = false;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Hands EAP material to the ESP-IDF supplicant and enables it.
+    ///
+    /// Lengths are bounded by [`validate_auth_method`] (text fields) or are
+    /// certificate sizes far below `i32::MAX`.
+    fn enable_enterprise(credentials: &EnterpriseCredentials) -> anyhow::Result<()> {
+        use esp_idf_svc::sys::{
+            esp, esp_eap_client_set_ca_cert, esp_eap_client_set_certificate_and_key,
+            esp_eap_client_set_identity, esp_eap_client_set_password, esp_eap_client_set_username,
+            esp_wifi_sta_enterprise_enable,
+        };
+
+        // SAFETY (all calls below): every pointer/length pair comes from a
+        // `String`/`Vec` owned by the manager's candidate list, which lives
+        // as long as the manager and therefore the connection.
+        match credentials {
+            EnterpriseCredentials::Peap {
+                identity,
+                username,
+                password,
+            } => {
+                esp!(unsafe {
+                    esp_eap_client_set_identity(identity.as_ptr(), identity.len() as i32)
+                })
+                .context("failed to set EAP identity")?;
+                esp!(unsafe {
+                    esp_eap_client_set_username(username.as_ptr(), username.len() as i32)
+                })
+                .context("failed to set EAP username")?;
+                esp!(unsafe {
+                    esp_eap_client_set_password(password.as_ptr(), password.len() as i32)
+                })
+                .context("failed to set EAP password")?;
+            }
+            EnterpriseCredentials::Tls {
+                identity,
+                client_cert,
+                private_key,
+                ca_cert,
+            } => {
+                esp!(unsafe {
+                    esp_eap_client_set_identity(identity.as_ptr(), identity.len() as i32)
+                })
+                .context("failed to set EAP identity")?;
+                if let Some(ca_cert) = ca_cert {
+                    esp!(unsafe {
+                        esp_eap_client_set_ca_cert(ca_cert.as_ptr(), ca_cert.len() as i32)
+                    })
+                    .context("failed to set EAP CA certificate")?;
+                }
+                esp!(unsafe {
+                    esp_eap_client_set_certificate_and_key(
+                        client_cert.as_ptr(),
+                        client_cert.len() as i32,
+                        private_key.as_ptr(),
+                        private_key.len() as i32,
+                        std::ptr::null(),
+                        0,
+                    )
+                })
+                .context("failed to set EAP-TLS certificate and key")?;
+            }
+        }
+        esp!(unsafe { esp_wifi_sta_enterprise_enable() })
+            .context("failed to enable WiFi enterprise mode")?;
+        Ok(())
+    }
+
+    /// Sets the STA netif hostname (DHCP option 12).  `hostname` must
+    /// already have passed [`validate_hostname`], so it holds no NUL.
+    fn set_sta_hostname(wifi: &EspWifi<'static>, hostname: &str) -> anyhow::Result<()> {
+        use esp_idf_svc::handle::RawHandle;
+        use esp_idf_svc::sys::{esp, esp_netif_set_hostname};
+
+        let c_hostname = std::ffi::CString::new(hostname)
+            .map_err(|_| anyhow::anyhow!("internal: hostname contains NUL after validation"))?;
+        // SAFETY: the netif handle is valid for the lifetime of `wifi`, and
+        // ESP-IDF copies the string, so the CString may drop afterwards.
+        esp!(unsafe { esp_netif_set_hostname(wifi.sta_netif().handle(), c_hostname.as_ptr()) })
+            .context("failed to set WiFi hostname")?;
+        log::info!("WiFi hostname: {}", hostname);
+        Ok(())
+    }
+
+    /// Builds a STA netif with a fixed IPv4 address instead of the DHCP
+    /// client.  The inputs must already have passed [`validate_ip_config`].
+    fn static_sta_netif(
+        ip: Ipv4Addr,
+        netmask: Ipv4Addr,
+        gateway: Ipv4Addr,
+        dns: Option<Ipv4Addr>,
+    ) -> anyhow::Result<EspNetif> {
+        let prefix = netmask_prefix_len(netmask)
+            .ok_or_else(|| anyhow::anyhow!("internal: netmask not contiguous after validation"))?;
+        let conf = NetifConfiguration {
+            ip_configuration: Some(ipv4::Configuration::Client(
+                ipv4::ClientConfiguration::Fixed(ipv4::ClientSettings {
+                    ip,
+                    subnet: ipv4::Subnet {
+                        gateway,
+                        mask: ipv4::Mask(prefix),
+                    },
+                    dns,
+                    secondary_dns: None,
+                }),
+            )),
+            ..NetifConfiguration::wifi_default_client()
+        };
+        EspNetif::new_with_conf(&conf).context("failed to create static-IP STA netif")
+    }
+
+    /// Scans once and ranks `networks` against the result.
+    ///
+    /// A scan failure is non-fatal: the candidates are then tried in plain
+    /// priority order.
+    fn rank_by_scan(
+        wifi: &mut BlockingWifi<EspWifi<'static>>,
+        networks: &WifiNetworkList<'_>,
+    ) -> NetworkOrder {
+        match wifi.scan() {
+            Ok(aps) => {
+                log::info!("WiFi scan found {} access point(s)", aps.len());
+                rank_networks(
+                    networks,
+                    aps.iter().map(|ap| (ap.ssid.as_str(), ap.signal_strength)),
+                )
+            }
+            Err(e) => {
+                log::warn!("WiFi scan failed, trying networks by priority: {:?}", e);
+                priority_order(networks)
+            }
+        }
+    }
+
+    /// Tries each candidate in `order` with `connect`, falling back to the
+    /// next one on failure.  The first candidate's configuration must
+    /// already be applied; `ap` is passed through to [`Self::apply_candidate`].
+    fn connect_candidates<F>(
+        wifi: &mut BlockingWifi<EspWifi<'static>>,
+        networks: &WifiNetworkList<'_>,
+        order: &NetworkOrder,
+        candidates: &[StaCandidate],
+        ap: Option<&AccessPointConfiguration>,
+        enterprise_active: &mut bool,
+        mut connect: F,
+    ) -> anyhow::Result<()>
+    where
+        F: FnMut(&mut BlockingWifi<EspWifi<'static>>) -> anyhow::Result<()>,
+    {
+        let mut last_err = None;
+        for (attempt, &index) in order.iter().enumerate() {
+            if attempt > 0 {
+                // Drop any half-finished association before switching SSIDs.
+                if let Err(e) = wifi.wifi_mut().disconnect() {
+                    log::debug!("disconnect before fallback failed (ignored): {:?}", e);
+                }
+                Self::apply_candidate(wifi, &candidates[index], ap, enterprise_active)?;
+            }
+            if let Some(network) = networks.get(index) {
+                log::info!(
+                    "WiFi candidate {}/{} (SSID len={}, priority={}, {})",
+                    attempt + 1,
+                    order.len(),
+                    network.ssid.len(),
+                    network.priority,
+                    network.auth.name()
+                );
+            }
+            match connect(wifi) {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    log::warn!("WiFi candidate {} failed: {:#}", attempt + 1, e);
+                    last_err = Some(e);
+                }
+            }
+        }
+        let err = last_err.unwrap_or_else(|| anyhow::anyhow!("no Wi-Fi networks configured"));
+        if order.len() == 1 {
+            // Keep the single-network error message unchanged.
+            return Err(err);
+        }
+        Err(err.context(format!(
+            "all {} configured Wi-Fi networks failed",
+            order.len()
+        )))
+    }
+
     fn connect_with_led<L>(
         wifi: &mut BlockingWifi<EspWifi<'static>>,
         led: &mut L,
@@ -452,6 +1109,57 @@ impl WiFiManager {
         Ok(())
     }
 
+    /// Returns the [`WifiFastConnectCache`] to keep for the next wake: the
+    /// BSSID and channel of the AP the station is associated with, the PMK
+    /// for WPA2-Personal networks, and the current IPv4 address, if any.
+    ///
+    /// The PMK is taken from the cache passed to
+    /// [`WiFiConfig::with_fast_connect`] when that was for the same
+    /// credentials; otherwise it is derived here (PBKDF2, tens of
+    /// milliseconds), so call this once, right before sleeping.
+    ///
+    /// Fails if the station is not associated, or is associated with a
+    /// network that was not part of the [`WiFiConfig`].
+    pub fn fast_connect_cache(&self) -> anyhow::Result<WifiFastConnectCache> {
+        Self::read_fast_connect_cache(&self.wifi, &self.candidates, self.fast_connect)
+    }
+
+    /// [`Self::fast_connect_cache`] for any manager holding a station:
+    /// refreshes `previous` from the AP `wifi` is associated with.
+    fn read_fast_connect_cache(
+        wifi: &BlockingWifi<EspWifi<'static>>,
+        candidates: &[StaCandidate],
+        previous: Option<WifiFastConnectCache>,
+    ) -> anyhow::Result<WifiFastConnectCache> {
+        let ap = wifi
+            .wifi()
+            .driver()
+            .get_ap_info()
+            .context("WiFi station is not associated")?;
+        let candidate = candidates
+            .iter()
+            .find(|candidate| candidate.client.ssid == ap.ssid)
+            .ok_or_else(|| anyhow::anyhow!("associated network is not a configured candidate"))?;
+        let previous = previous.unwrap_or_else(WifiFastConnectCache::empty);
+        let cache = previous.refreshed(
+            &candidate.client.ssid,
+            &candidate.client.password,
+            candidate.pmk_applicable,
+            ap.bssid,
+            ap.channel,
+        );
+        let lease = match wifi.wifi().sta_netif().get_ip_info() {
+            Ok(info) if !info.ip.is_unspecified() => IpConfig::Static {
+                ip: info.ip,
+                netmask: Ipv4Addr::from(info.subnet.mask),
+                gateway: info.subnet.gateway,
+                dns: info.dns,
+            },
+            _ => IpConfig::Dhcp,
+        };
+        Ok(cache.with_ip_lease(lease))
+    }
+
     /// Blocks until the Wi-Fi station is associated and an IP address is
     /// assigned via DHCP.
     ///
@@ -508,6 +1216,138 @@ impl WiFiManager {
     pub fn is_connected(&self) -> anyhow::Result<bool> {
         Ok(self.wifi.is_connected()?)
     }
+
+    /// Returns the RSSI of the associated access point (dBm), as seen in its
+    /// last beacon.
+    ///
+    /// Wraps `esp_wifi_sta_get_ap_info`, which fails while the station is
+    /// not associated.
+    pub fn rssi(&self) -> anyhow::Result<i8> {
+        // SAFETY: `wifi_ap_record_t` is a plain C struct; an all-zero value is
+        // valid and `esp_wifi_sta_get_ap_info` fills it in place.
+        let mut record: esp_idf_svc::sys::wifi_ap_record_t = unsafe { core::mem::zeroed() };
+        esp_idf_svc::sys::esp!(unsafe { esp_idf_svc::sys::esp_wifi_sta_get_ap_info(&mut record) })
+            .context("failed to read WiFi RSSI")?;
+        Ok(record.rssi)
+    }
+
+    /// Reads the current RSSI and feeds it to `monitor`, returning the
+    /// threshold event it triggers, if any.
+    ///
+    /// Call periodically while connected and `monitor.reset()` after a
+    /// disconnect.  Events are logged here (`warn` for weak / roam, `info`
+    /// for recovered), so callers only need to match on them to act — raise
+    /// the TX power, run a background [`scan`](Self::scan), and so on.
+    pub fn sample_link_quality(
+        &self,
+        monitor: &mut LinkQualityMonitor,
+    ) -> anyhow::Result<Option<LinkQualityEvent>> {
+        let event = monitor.sample(self.rssi()?);
+        match event {
+            Some(LinkQualityEvent::Weak { rssi_dbm }) => {
+                log::warn!("WiFi link weak ({} dBm)", rssi_dbm)
+            }
+            Some(LinkQualityEvent::RoamCandidate { rssi_dbm }) => {
+                log::warn!("WiFi link poor ({} dBm), roam candidate", rssi_dbm)
+            }
+            Some(LinkQualityEvent::Recovered { rssi_dbm }) => {
+                log::info!("WiFi link recovered ({} dBm)", rssi_dbm)
+            }
+            None => {}
+        }
+        Ok(event)
+    }
+
+    /// Scans for nearby access points.
+    ///
+    /// Blocks for the duration of an all-channel scan (typically 1–3 s).
+    /// While connected, the radio leaves the home channel briefly per scanned
+    /// channel, so expect a short latency bump on in-flight traffic.
+    /// Results are sorted strongest-first and capped at [`SCAN_MAX_RESULTS`].
+    pub fn scan(&mut self) -> anyhow::Result<ScanResults> {
+        let aps = self.wifi.scan().context("WiFi scan failed")?;
+        let mut results = ScanResults::new();
+        for ap in &aps {
+            insert_scan_result(
+                &mut results,
+                ApInfo::new(
+                    ap.ssid.as_str(),
+                    ap.bssid,
+                    ap.channel,
+                    ap.signal_strength,
+                    map_auth_method(ap.auth_method),
+                ),
+            );
+        }
+        log::debug!(
+            "WiFi scan: {} access point(s) seen, {} kept",
+            aps.len(),
+            results.len()
+        );
+        Ok(results)
+    }
+}
+
+/// [`WifiDriver`] implementation so shared logic written against the trait
+/// can drive the ESP-IDF manager.
+///
+/// `connect` only initiates association (like the non-blocking mode of
+/// [`WiFiManager::new`]); poll [`WifiDriver::is_connected`] or use
+/// [`WiFiManager::wait_connected`] to wait for it.
+impl WifiDriver for WiFiManager {
+    type Error = anyhow::Error;
+
+    /// Configures a WPA2-Personal (or open) network; use [`WiFiManager::new`]
+    /// with [`WiFiConfig::with_auth`] for WPA3 or enterprise networks.
+    fn configure(&mut self, ssid: &str, password: &str) -> anyhow::Result<()> {
+        let candidate = Self::sta_candidate(&WifiNetwork::new(ssid, password))?;
+        Self::apply_candidate(
+            &mut self.wifi,
+            &candidate,
+            None,
+            &mut self.enterprise_active,
+        )
+    }
+
+    fn start(&mut self) -> anyhow::Result<()> {
+        Ok(self.wifi.start()?)
+    }
+
+    fn connect(&mut self) -> anyhow::Result<()> {
+        self.wifi
+            .wifi_mut()
+            .connect()
+            .context("WiFi connect initiation failed")
+    }
+
+    fn disconnect(&mut self) -> anyhow::Result<()> {
+        Ok(self.wifi.disconnect()?)
+    }
+
+    fn is_connected(&self) -> anyhow::Result<bool> {
+        WiFiManager::is_connected(self)
+    }
+
+    fn scan(&mut self) -> anyhow::Result<ScanResults> {
+        WiFiManager::scan(self)
+    }
+}
+
+/// Maps the `esp-idf-svc` auth method reported in a scan record to the
+/// platform-independent [`AuthMode`].
+fn map_auth_method(auth: Option<AuthMethod>) -> AuthMode {
+    match auth {
+        None => AuthMode::Unknown,
+        Some(AuthMethod::None) => AuthMode::Open,
+        Some(AuthMethod::WEP) => AuthMode::Wep,
+        Some(AuthMethod::WPA) => AuthMode::WpaPersonal,
+        Some(AuthMethod::WPA2Personal) => AuthMode::Wpa2Personal,
+        Some(AuthMethod::WPAWPA2Personal) => AuthMode::WpaWpa2Personal,
+        Some(AuthMethod::WPA2Enterprise) => AuthMode::Wpa2Enterprise,
+        Some(AuthMethod::WPA3Personal) => AuthMode::Wpa3Personal,
+        Some(AuthMethod::WPA2WPA3Personal) => AuthMode::Wpa2Wpa3Personal,
+        Some(AuthMethod::WAPIPersonal) => AuthMode::WapiPersonal,
+    }
 }
 
 /// Reads the SoftAP factory MAC address from efuse.
@@ -532,15 +1372,24 @@ pub fn softap_mac() -> anyhow::Result<[u8; 6]> {
 /// SoftAP (access-point) lifecycle manager.
 ///
 /// Brings up a Wi-Fi access point from an [`ApConfig`] and exposes the AP
-/// netif IP, the AP MAC, and the associated-station count — the surface the
+/// netif IP, the AP MAC, and the associated stations — the surface the
 /// captive-portal provisioning crate consumes.
 ///
+/// # Stations
+///
+/// [`stations`](Self::stations) lists the admitted clients with the address
+/// the DHCP server leased them, and
+/// [`on_station_event`](Self::on_station_event) reports each
+/// [`ApStationEvent`] as it happens.  Stations that fail the config's
+/// allowlist or arrive once `max_connections` are admitted are
+/// deauthenticated and reported as [`ApStationEvent::Rejected`].
+///
 /// # Mode
 ///
 /// This is AP-only.
-/// v1 deliberately does not run mixed AP+STA mode; the provisioning flow
-/// commits credentials and the host reboots into normal STA boot
-/// (reboot-based handoff), sidestepping ESP-IDF mode-switch issues.
+/// The provisioning flow commits credentials and the host reboots into normal
+/// STA boot (reboot-based handoff), sidestepping ESP-IDF mode-switch issues.
+/// Use [`ApStaManager`] to keep an AP up while the station associates.
 ///
 /// # TX power on ESP32-C3 Super Mini boards
 ///
@@ -553,6 +1402,7 @@ pub fn softap_mac() -> anyhow::Result<[u8; 6]> {
 /// not a verified fix for the AP path.
 pub struct SoftApManager {
     wifi: EspWifi<'static>,
+    stations: ApStationTracker,
 }
 
 impl SoftApManager {
@@ -560,7 +1410,9 @@ impl SoftApManager {
     ///
     /// Validates the configuration via [`validate_ap_config`], configures the
     /// access point (WPA2-Personal when a password is present, open otherwise),
-    /// starts the radio, then applies the requested transmit power.
+    /// applies the [`Country`] if one is set, starts the radio, then applies
+    /// the [`WifiPhy`] mode (unless it is the default) and the requested
+    /// transmit power, clamped to the country limit.
     /// As with the STA path, a TX-power failure is logged at `warn` and the AP
     /// continues at the radio default rather than hard-erroring.
     /// The SSID is logged by length only, never by value.
@@ -570,74 +1422,30 @@ impl SoftApManager {
         nvs: Option<EspDefaultNvsPartition>,
         config: ApConfig<'_>,
     ) -> anyhow::Result<Self> {
-        validate_ap_config(&config).map_err(|e| {
-            anyhow::anyhow!("AP config invalid (ssid len={}): {}", config.ssid.len(), e)
-        })?;
+        let ap_config = access_point_configuration(&config)?;
 
         log::info!("Starting SoftAP (ssid len={})", config.ssid.len());
 
+        let stations = ApStationTracker::subscribe(&sys_loop, &config)?;
         let mut wifi = EspWifi::new(modem, sys_loop, nvs)?;
-
-        let ssid = config.ssid.try_into().map_err(|_| {
-            anyhow::anyhow!(
-                "internal: SSID conversion failed after validation (len={}, limit={})",
-                config.ssid.len(),
-                SSID_MAX_LEN
-            )
-        })?;
-
-        let mut ap_config = AccessPointConfiguration {
-            ssid,
-            channel: config.channel,
-            max_connections: config.max_connections as u16,
-            ..Default::default()
-        };
-        match config.password {
-            Some(pw) => {
-                ap_config.password = pw.try_into().map_err(|_| {
-                    anyhow::anyhow!(
-                        "internal: password conversion failed after validation (len={}, limit={})",
-                        pw.len(),
-                        PASSWORD_MAX_LEN
-                    )
-                })?;
-                ap_config.auth_method = AuthMethod::WPA2Personal;
-            }
-            None => {
-                ap_config.auth_method = AuthMethod::None;
-            }
-        }
-
         wifi.set_configuration(&Configuration::AccessPoint(ap_config))?;
 
-        pin_ap_netif_ip(&wifi)?;
-
-        wifi.start()?;
+        pin_ap_netif_ip(&wifi, &config.subnet)?;
 
-        let tx_power = config.tx_power.to_quarter_dbm();
-        match esp_idf_svc::sys::esp!(unsafe {
-            esp_idf_svc::sys::esp_wifi_set_max_tx_power(tx_power)
-        }) {
-            Ok(()) => log::info!(
-                "SoftAP TX power set to {:?} ({} quarter-dBm)",
-                config.tx_power,
-                tx_power
-            ),
-            Err(e) => log::warn!(
-                "Failed to set SoftAP TX power to {:?} ({} quarter-dBm), continuing at radio default: {:?}",
-                config.tx_power,
-                tx_power,
-                e
-            ),
+        if let Some(country) = config.country {
+            apply_country(country)?;
         }
+        wifi.start()?;
+        apply_phy(esp_idf_svc::sys::wifi_interface_t_WIFI_IF_AP, config.phy)?;
+        set_ap_tx_power(config.effective_tx_power());
 
-        Ok(Self { wifi })
+        Ok(Self { wifi, stations })
     }
 
     /// Returns the IPv4 address of the AP netif.
     ///
-    /// `SoftApManager::start` pins the AP netif to the documented ESP-IDF
-    /// default `192.168.4.1/24` via `pin_ap_netif_ip`, so the value returned
+    /// `SoftApManager::start` pins the AP netif to [`ApConfig::subnet`]
+    /// (`192.168.4.1/24` by default) via `pin_ap_netif_ip`, so the value returned
     /// here is stable across reboots and is not affected by stale NVS netif
     /// state. The address is still read live from the netif rather than
     /// assumed.
@@ -650,30 +1458,31 @@ impl SoftApManager {
     /// Wraps the unsafe `esp_wifi_get_mac(WIFI_IF_AP, ...)` IDF call; the AP
     /// interface is up after [`start`](Self::start) succeeds.
     pub fn ap_mac(&self) -> anyhow::Result<[u8; 6]> {
-        let mut mac = [0u8; 6];
-        esp_idf_svc::sys::esp!(unsafe {
-            esp_idf_svc::sys::esp_wifi_get_mac(
-                esp_idf_svc::sys::wifi_interface_t_WIFI_IF_AP,
-                mac.as_mut_ptr(),
-            )
-        })
-        .context("failed to read AP MAC")?;
-        Ok(mac)
+        read_ap_mac()
     }
 
     /// Returns the number of stations currently associated with the AP.
     ///
     /// Wraps the unsafe `esp_wifi_ap_get_sta_list` IDF call.
     pub fn station_count(&self) -> anyhow::Result<u16> {
-        // SAFETY: `wifi_sta_list_t` is a plain C struct of integers and a fixed
-        // array; an all-zero value is a valid empty list. `esp_wifi_ap_get_sta_list`
-        // fills it in place and the AP interface is up after `start`.
-        let mut sta_list: esp_idf_svc::sys::wifi_sta_list_t = unsafe { core::mem::zeroed() };
-        esp_idf_svc::sys::esp!(unsafe {
-            esp_idf_svc::sys::esp_wifi_ap_get_sta_list(&mut sta_list)
-        })
-        .context("failed to read AP station list")?;
-        Ok(sta_list.num as u16)
+        read_ap_station_count()
+    }
+
+    /// Returns the admitted stations in association order, each with the
+    /// address the DHCP server leased it, if any yet.
+    pub fn stations(&self) -> Vec<ApStation> {
+        self.stations.stations()
+    }
+
+    /// Registers `f` to receive every [`ApStationEvent`], replacing any
+    /// earlier callback.
+    ///
+    /// `f` runs on the system event loop task and must not block.
+    pub fn on_station_event<F>(&self, f: F)
+    where
+        F: Fn(ApStationEvent) + Send + Sync + 'static,
+    {
+        self.stations.set_on_event(Arc::new(f));
     }
 
     /// Stops the SoftAP and releases the radio.
@@ -683,9 +1492,514 @@ impl SoftApManager {
     }
 }
 
-/// Forces the AP netif onto the documented ESP-IDF default `192.168.4.1/24`
-/// and configures the IDF DHCP server to advertise the AP as the DNS server
-/// (DHCP Option 6).
+/// Concurrent SoftAP + station manager (ESP-IDF mixed mode).
+///
+/// Keeps an access point up while the station side associates, so a device
+/// can offer a local maintenance AP in the field, or a provisioning portal
+/// can check freshly entered credentials with [`connect_sta`](Self::connect_sta)
+/// without tearing itself down.  The AP is configured exactly like
+/// [`SoftApManager`] (same validation, netif pinned to [`ApConfig::subnet`], DHCP
+/// DNS offer); the station side accepts a full [`WiFiConfig`] and reuses the
+/// [`WiFiManager`] validation and multi-network fallback.  AP clients are
+/// tracked and admitted as for [`SoftApManager`].
+///
+/// # Channel
+///
+/// The radio has a single channel.  Once the station associates, the AP
+/// moves to the upstream network's channel and [`ApConfig::channel`] no
+/// longer applies; clients of the AP may drop and rejoin while that happens.
+///
+/// # Station failures
+///
+/// A station that fails to connect never takes the AP down: [`start`](Self::start)
+/// logs the failure and returns the manager anyway, and
+/// [`connect_sta`](Self::connect_sta) returns the error with the AP still
+/// serving.
+///
+/// ```ignore
+/// let ap = ApConfig::wpa2("sensor-setup", "maintenance");
+/// let mut wifi = ApStaManager::start(modem, sys_loop, Some(nvs), ap, None)?;
+/// log::info!("portal at {}", wifi.ap_ip()?);
+///
+/// // later, with credentials from the portal form:
+/// wifi.connect_sta(&WiFiConfig::new(&ssid, &password))?;
+/// log::info!("upstream IP {:?}", wifi.sta_ip(10_000)?);
+/// ```
+pub struct ApStaManager {
+    wifi: BlockingWifi<EspWifi<'static>>,
+    /// AP half of every mixed-mode configuration applied to the driver.
+    ap_configuration: AccessPointConfiguration,
+    /// Station configs of the last `WiFiConfig`; kept alive for the same
+    /// reason as in [`WiFiManager`].
+    candidates: Vec<StaCandidate>,
+    /// Cache from the last `WiFiConfig`, as in [`WiFiManager`].
+    fast_connect: Option<WifiFastConnectCache>,
+    /// Whether the 802.1X supplicant is currently enabled.
+    enterprise_active: bool,
+    stations: ApStationTracker,
+}
+
+impl ApStaManager {
+    /// Starts the AP from `ap` and, when `sta` is given, connects the
+    /// station alongside it.
+    ///
+    /// `ap` is checked with [`validate_ap_config`]; `sta` gets the same
+    /// checks as [`WiFiManager::new`] (SSIDs, auth settings, static IP,
+    /// hostname), all before the radio starts.  The station's
+    /// [`IpConfig`] and hostname are fixed here for the lifetime of the
+    /// manager.  `sta.connect_mode` decides whether this call waits for the
+    /// station; a blocking connect that fails is logged at `warn` and the
+    /// manager is still returned with the AP running.
+    ///
+    /// The AP transmit power ([`ApConfig::tx_power`]) applies to the whole
+    /// radio; the STA config's TX power and power-save settings are ignored,
+    /// as modem sleep would starve the AP's beacons.  Likewise the AP
+    /// config's [`Country`] wins; the STA config's is used only when the AP
+    /// has none, and the AP channel and TX power are checked and clamped
+    /// against whichever applies.  Each side's [`WifiPhy`] applies to its
+    /// own interface and, like addressing, the station's is fixed here.
+    /// A fast-connect cache in `sta` is used as by
+    /// [`connect_sta`](Self::connect_sta).
+    pub fn start(
+        modem: Modem<'static>,
+        sys_loop: EspSystemEventLoop,
+        nvs: Option<EspDefaultNvsPartition>,
+        ap: ApConfig<'_>,
+        sta: Option<WiFiConfig<'_>>,
+    ) -> anyhow::Result<Self> {
+        // Validate (and later clamp) the AP against the domain the radio
+        // will actually run in.
+        let mut ap = ap;
+        if let Some(country) = ap.country.or(sta.as_ref().and_then(|sta| sta.country)) {
+            ap = ap.with_country(country);
+        }
+        let ap_configuration = access_point_configuration(&ap)?;
+
+        let mut candidates = Vec::new();
+        if let Some(sta) = &sta {
+            validate_ip_config(&sta.ip)
+                .map_err(|e| anyhow::anyhow!("WiFi static IP config invalid: {}", e))?;
+            if let Some(hostname) = sta.hostname {
+                validate_hostname(hostname)
+                    .map_err(|e| anyhow::anyhow!("WiFi hostname invalid: {}", e))?;
+            }
+            validate_phy(&sta.phy)
+                .map_err(|e| anyhow::anyhow!("WiFi PHY config invalid: {}", e))?;
+            candidates = Self::sta_candidates(sta)?;
+        }
+
+        log::info!(
+            "Starting SoftAP + STA (ssid len={}, {} STA network(s) configured)",
+            ap.ssid.len(),
+            candidates.len()
+        );
+
+        let stations = ApStationTracker::subscribe(&sys_loop, &ap)?;
+        let mut esp_wifi = EspWifi::new(modem, sys_loop.clone(), nvs)?;
+        if let Some(sta) = &sta {
+            if let IpConfig::Static {
+                ip,
+                netmask,
+                gateway,
+                dns,
+            } = sta.ip
+            {
+                esp_wifi
+                    .swap_netif_sta(WiFiManager::static_sta_netif(ip, netmask, gateway, dns)?)
+                    .context("failed to attach static-IP STA netif")?;
+                log::info!("WiFi static IP {}/{} via {}", ip, netmask, gateway);
+            }
+            if let Some(hostname) = sta.hostname {
+                WiFiManager::set_sta_hostname(&esp_wifi, hostname)?;
+            }
+        }
+        pin_ap_netif_ip(&esp_wifi, &ap.subnet)?;
+        let mut wifi = BlockingWifi::wrap(esp_wifi, sys_loop)?;
+
+        let mut enterprise_active = false;
+        match &sta {
+            Some(sta) => {
+                let first = priority_order(&sta.networks())[0];
+                WiFiManager::apply_candidate(
+                    &mut wifi,
+                    &candidates[first],
+                    Some(&ap_configuration),
+                    &mut enterprise_active,
+                )?;
+            }
+            None => wifi.set_configuration(&Configuration::Mixed(
+                ClientConfiguration::default(),
+                ap_configuration.clone(),
+            ))?,
+        }
+
+        if let Some(country) = ap.country {
+            apply_country(country)?;
+        }
+        wifi.start()?;
+        apply_phy(esp_idf_svc::sys::wifi_interface_t_WIFI_IF_AP, ap.phy)?;
+        if let Some(sta) = &sta {
+            apply_phy(esp_idf_svc::sys::wifi_interface_t_WIFI_IF_STA, sta.phy)?;
+        }
+        set_ap_tx_power(ap.effective_tx_power());
+
+        let mut manager = Self {
+            wifi,
+            ap_configuration,
+            candidates: Vec::new(),
+            fast_connect: None,
+            enterprise_active,
+            stations,
+        };
+        if let Some(sta) = &sta {
+            if let Err(e) = manager.connect_candidates(sta, candidates) {
+                log::warn!("WiFi station not connected, SoftAP stays up: {:#}", e);
+            }
+        }
+        Ok(manager)
+    }
+
+    /// Connects the station to the network(s) in `config`, keeping the AP up.
+    ///
+    /// Any current station association is dropped first.  Networks are
+    /// validated and tried exactly as in [`WiFiManager::new`]; with
+    /// [`ConnectMode::Blocking`] this waits for the link and returns the
+    /// error if every candidate fails, which makes it suitable for checking
+    /// credentials entered into a portal served over the AP.
+    ///
+    /// A [`WiFiConfig::with_fast_connect`] cache is honoured as in
+    /// [`WiFiManager::new`]: the station tries the cached AP first and falls
+    /// back to the candidate walk.  The AP follows the station to the cached
+    /// channel as soon as it associates.
+    ///
+    /// Addressing, hostname, and PHY mode were fixed by
+    /// [`start`](Self::start), so `config.ip`, `config.hostname`, and
+    /// `config.phy` are ignored here.
+    pub fn connect_sta(&mut self, config: &WiFiConfig<'_>) -> anyhow::Result<()> {
+        let candidates = Self::sta_candidates(config)?;
+        if !matches!(config.ip, IpConfig::Dhcp) || config.hostname.is_some() {
+            log::warn!("connect_sta ignores IP and hostname settings; they are fixed at start");
+        }
+        if let Err(e) = self.wifi.wifi_mut().disconnect() {
+            log::debug!("disconnect before reconnect failed (ignored): {:?}", e);
+        }
+        let first = priority_order(&config.networks())[0];
+        WiFiManager::apply_candidate(
+            &mut self.wifi,
+            &candidates[first],
+            Some(&self.ap_configuration),
+            &mut self.enterprise_active,
+        )?;
+        self.connect_candidates(config, candidates)
+    }
+
+    /// Validates every network in `config` and builds its station config.
+    fn sta_candidates(config: &WiFiConfig<'_>) -> anyhow::Result<Vec<StaCandidate>> {
+        let networks = config.networks();
+        let mut candidates = Vec::with_capacity(networks.len());
+        for network in &networks {
+            candidates.push(WiFiManager::sta_candidate(network)?);
+        }
+        Ok(candidates)
+    }
+
+    /// Ranks and connects `candidates` per `config.connect_mode`.  The
+    /// priority-first candidate must already be applied.
+    ///
+    /// The candidates and fast-connect cache are stored on the manager
+    /// whatever the outcome, since the supplicant may hold pointers into
+    /// the former.
+    fn connect_candidates(
+        &mut self,
+        config: &WiFiConfig<'_>,
+        candidates: Vec<StaCandidate>,
+    ) -> anyhow::Result<()> {
+        let result = self.connect_with(config, &candidates);
+        self.candidates = candidates;
+        self.fast_connect = config.fast_connect;
+        result
+    }
+
+    /// Body of [`Self::connect_candidates`]; tries the fast-connect cache
+    /// first when `config` carries a matching one.
+    fn connect_with(
+        &mut self,
+        config: &WiFiConfig<'_>,
+        candidates: &[StaCandidate],
+    ) -> anyhow::Result<()> {
+        let networks = config.networks();
+        let ap = Some(&self.ap_configuration);
+        let blocking = matches!(config.connect_mode, ConnectMode::Blocking { .. });
+        let fast = config
+            .fast_connect
+            .and_then(|cache| WiFiManager::fast_client(&cache, &networks, candidates, blocking));
+        if let Some((index, client)) = &fast {
+            log::info!(
+                "WiFi fast connect: cached AP on channel {:?}",
+                client.channel
+            );
+            WiFiManager::apply_station(
+                &mut self.wifi,
+                client,
+                candidates[*index].enterprise.as_ref(),
+                ap,
+                &mut self.enterprise_active,
+            )?;
+        }
+        match config.connect_mode {
+            ConnectMode::Blocking { timeout_secs } => {
+                let first = priority_order(&networks)[0];
+                if fast.is_some() {
+                    match WiFiManager::wait_for_connection(
+                        &mut self.wifi,
+                        FAST_CONNECT_TIMEOUT_SECS.min(timeout_secs),
+                    ) {
+                        Ok(()) => {
+                            log::info!("WiFi fast connect succeeded");
+                            return Ok(());
+                        }
+                        Err(e) => {
+                            log::warn!(
+                                "WiFi fast connect failed, falling back to a full connect: {:#}",
+                                e
+                            );
+                            if let Err(e) = self.wifi.wifi_mut().disconnect() {
+                                log::debug!("disconnect before fallback failed (ignored): {:?}", e);
+                            }
+                            WiFiManager::apply_candidate(
+                                &mut self.wifi,
+                                &candidates[first],
+                                ap,
+                                &mut self.enterprise_active,
+                            )?;
+                        }
+                    }
+                }
+                let order = if networks.len() > 1 {
+                    WiFiManager::rank_by_scan(&mut self.wifi, &networks)
+                } else {
+                    priority_order(&networks)
+                };
+                if order[0] != first {
+                    WiFiManager::apply_candidate(
+                        &mut self.wifi,
+                        &candidates[order[0]],
+                        ap,
+                        &mut self.enterprise_active,
+                    )?;
+                }
+                WiFiManager::connect_candidates(
+                    &mut self.wifi,
+                    &networks,
+                    &order,
+                    candidates,
+                    ap,
+                    &mut self.enterprise_active,
+                    |wifi| WiFiManager::wait_for_connection(wifi, timeout_secs),
+                )
+            }
+            ConnectMode::NonBlocking => {
+                // As in `WiFiManager`, the cache only hints the channel here.
+                self.wifi
+                    .wifi_mut()
+                    .connect()
+                    .context("WiFi connect initiation failed")?;
+                log::info!("WiFi connect initiated (non-blocking)");
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the [`WifiFastConnectCache`] to keep for the next wake, as
+    /// for [`WiFiManager::fast_connect_cache`].
+    pub fn fast_connect_cache(&self) -> anyhow::Result<WifiFastConnectCache> {
+        WiFiManager::read_fast_connect_cache(&self.wifi, &self.candidates, self.fast_connect)
+    }
+
+    /// Disconnects the station; the AP keeps running.
+    pub fn disconnect_sta(&mut self) -> anyhow::Result<()> {
+        Ok(self.wifi.disconnect()?)
+    }
+
+    /// Returns whether the station is currently associated.
+    pub fn is_sta_connected(&self) -> anyhow::Result<bool> {
+        Ok(self.wifi.is_connected()?)
+    }
+
+    /// Waits up to `timeout_ms` for the station to be associated with an
+    /// IPv4 address; see [`WiFiManager::get_ip`].  Returns `None` on timeout.
+    pub fn sta_ip(&self, timeout_ms: u64) -> anyhow::Result<Option<Ipv4Addr>> {
+        let start = std::time::Instant::now();
+        let timeout = Duration::from_millis(timeout_ms);
+
+        loop {
+            match self.wifi.is_connected() {
+                Ok(true) => match self.wifi.wifi().sta_netif().get_ip_info() {
+                    Ok(ip_info) if !ip_info.ip.is_unspecified() => return Ok(Some(ip_info.ip)),
+                    Ok(_) => {}
+                    Err(e) => log::debug!("get_ip_info transient error: {}", e),
+                },
+                Ok(false) => {}
+                Err(e) => log::debug!("is_connected transient error: {}", e),
+            }
+
+            if start.elapsed() >= timeout {
+                return Ok(None);
+            }
+
+            thread::sleep(Duration::from_millis(POLL_INTERVAL_MS));
+        }
+    }
+
+    /// Returns the IPv4 address of the AP netif (pinned to
+    /// [`ApConfig::subnet`], as for [`SoftApManager::ap_ip`]).
+    pub fn ap_ip(&self) -> anyhow::Result<Ipv4Addr> {
+        Ok(self.wifi.wifi().ap_netif().get_ip_info()?.ip)
+    }
+
+    /// Returns the AP interface MAC address.
+    pub fn ap_mac(&self) -> anyhow::Result<[u8; 6]> {
+        read_ap_mac()
+    }
+
+    /// Returns the number of stations currently associated with the AP.
+    pub fn station_count(&self) -> anyhow::Result<u16> {
+        read_ap_station_count()
+    }
+
+    /// Returns the admitted AP stations, as for [`SoftApManager::stations`].
+    pub fn stations(&self) -> Vec<ApStation> {
+        self.stations.stations()
+    }
+
+    /// Registers `f` to receive every AP [`ApStationEvent`], as for
+    /// [`SoftApManager::on_station_event`].
+    pub fn on_station_event<F>(&self, f: F)
+    where
+        F: Fn(ApStationEvent) + Send + Sync + 'static,
+    {
+        self.stations.set_on_event(Arc::new(f));
+    }
+
+    /// Stops both interfaces and releases the radio.
+    pub fn stop(mut self) -> anyhow::Result<()> {
+        self.wifi.stop()?;
+        Ok(())
+    }
+}
+
+/// Validates `config` and builds the matching ESP-IDF access-point
+/// configuration (WPA2-Personal when a password is present, open otherwise).
+fn access_point_configuration(config: &ApConfig<'_>) -> anyhow::Result<AccessPointConfiguration> {
+    validate_ap_config(config).map_err(|e| {
+        anyhow::anyhow!("AP config invalid (ssid len={}): {}", config.ssid.len(), e)
+    })?;
+
+    let ssid = config.ssid.try_into().map_err(|_| {
+        anyhow::anyhow!(
+            "internal: SSID conversion failed after validation (len={}, limit={})",
+            config.ssid.len(),
+            SSID_MAX_LEN
+        )
+    })?;
+
+    let mut ap_config = AccessPointConfiguration {
+        ssid,
+        channel: config.channel,
+        max_connections: config.max_connections as u16,
+        ..Default::default()
+    };
+    match config.password {
+        Some(pw) => {
+            ap_config.password = pw.try_into().map_err(|_| {
+                anyhow::anyhow!(
+                    "internal: password conversion failed after validation (len={}, limit={})",
+                    pw.len(),
+                    PASSWORD_MAX_LEN
+                )
+            })?;
+            ap_config.auth_method = AuthMethod::WPA2Personal;
+        }
+        None => {
+            ap_config.auth_method = AuthMethod::None;
+        }
+    }
+    Ok(ap_config)
+}
+
+/// Restricts the radio to `country`'s channels with a manual country policy,
+/// so the driver neither scans nor associates outside them and does not
+/// adopt a different domain from AP beacons.  Must run after
+/// `EspWifi::new` (which calls `esp_wifi_init`); the managers call it just
+/// before `wifi.start()`.
+fn apply_country(country: Country) -> anyhow::Result<()> {
+    let [first, second] = country.code();
+    // SAFETY: `wifi_country_t` is a plain C struct; all-zero is a valid
+    // value, and every field the driver reads is set below.
+    let mut info: esp_idf_svc::sys::wifi_country_t = unsafe { core::mem::zeroed() };
+    info.cc = [first as _, second as _, b' ' as _];
+    info.schan = country.first_channel();
+    info.nchan = country.channel_count();
+    info.max_tx_power = country.max_eirp_dbm();
+    info.policy = esp_idf_svc::sys::wifi_country_policy_t_WIFI_COUNTRY_POLICY_MANUAL;
+    esp_idf_svc::sys::esp!(unsafe { esp_idf_svc::sys::esp_wifi_set_country(&info) })
+        .with_context(|| format!("failed to set Wi-Fi country {:?}", country))?;
+    log::info!(
+        "WiFi country {:?}: channels {}..={}",
+        country,
+        country.first_channel(),
+        country.last_channel()
+    );
+    Ok(())
+}
+
+/// Applies the AP transmit power.  As with the STA path, a failure is logged
+/// at `warn` and the radio continues at its default.
+fn set_ap_tx_power(level: TxPowerLevel) {
+    let tx_power = level.to_quarter_dbm();
+    match esp_idf_svc::sys::esp!(unsafe { esp_idf_svc::sys::esp_wifi_set_max_tx_power(tx_power) })
+    {
+        Ok(()) => log::info!(
+            "SoftAP TX power set to {:?} ({} quarter-dBm)",
+            level,
+            tx_power
+        ),
+        Err(e) => log::warn!(
+            "Failed to set SoftAP TX power to {:?} ({} quarter-dBm), continuing at radio default: {:?}",
+            level,
+            tx_power,
+            e
+        ),
+    }
+}
+
+/// Reads the AP interface MAC via `esp_wifi_get_mac(WIFI_IF_AP, ...)`.
+fn read_ap_mac() -> anyhow::Result<[u8; 6]> {
+    let mut mac = [0u8; 6];
+    esp_idf_svc::sys::esp!(unsafe {
+        esp_idf_svc::sys::esp_wifi_get_mac(
+            esp_idf_svc::sys::wifi_interface_t_WIFI_IF_AP,
+            mac.as_mut_ptr(),
+        )
+    })
+    .context("failed to read AP MAC")?;
+    Ok(mac)
+}
+
+/// Counts the stations associated with the AP via `esp_wifi_ap_get_sta_list`.
+fn read_ap_station_count() -> anyhow::Result<u16> {
+    // SAFETY: `wifi_sta_list_t` is a plain C struct of integers and a fixed
+    // array; an all-zero value is a valid empty list. `esp_wifi_ap_get_sta_list`
+    // fills it in place and the AP interface is up once the driver started.
+    let mut sta_list: esp_idf_svc::sys::wifi_sta_list_t = unsafe { core::mem::zeroed() };
+    esp_idf_svc::sys::esp!(unsafe { esp_idf_svc::sys::esp_wifi_ap_get_sta_list(&mut sta_list) })
+        .context("failed to read AP station list")?;
+    Ok(sta_list.num as u16)
+}
+
+/// Forces the AP netif onto `subnet` (`192.168.4.1/24` by default), sets the
+/// IDF DHCP server's lease range to the subnet's pool, and configures it to
+/// advertise the AP as the DNS server (DHCP Option 6).
 ///
 /// `esp-idf-svc` does not expose `set_ip_info`, and `EspWifi::new` can read a
 /// stale netif configuration retained in NVS that lands the SoftAP on an
@@ -700,27 +2014,50 @@ impl SoftApManager {
 /// AP netif's main DNS to the AP IP and enabling the DHCP-server DNS offer
 /// mirrors what the esp-hal DHCP path does in Option 6 of its hand-rolled
 /// OFFER/ACK messages.
-fn pin_ap_netif_ip(wifi: &EspWifi<'_>) -> anyhow::Result<()> {
+///
+/// lwIP's DHCP server quietly falls back to its own pool when the range is
+/// not in the AP's /24, so such a range is rejected here rather than left to
+/// hand out addresses the caller did not ask for.
+fn pin_ap_netif_ip(wifi: &EspWifi<'_>, subnet: &ApSubnet) -> anyhow::Result<()> {
     use core::ffi::c_void;
 
     use esp_idf_svc::handle::RawHandle;
     use esp_idf_svc::sys::{
         esp, esp_ip4_addr_t, esp_ip_addr_t,
         esp_netif_dhcp_option_id_t_ESP_NETIF_DOMAIN_NAME_SERVER,
+        esp_netif_dhcp_option_id_t_ESP_NETIF_REQUESTED_IP_ADDRESS,
         esp_netif_dhcp_option_mode_t_ESP_NETIF_OP_SET, esp_netif_dhcps_option,
         esp_netif_dhcps_start, esp_netif_dhcps_stop, esp_netif_dns_info_t,
         esp_netif_dns_type_t_ESP_NETIF_DNS_MAIN, esp_netif_ip_info_t, esp_netif_set_dns_info,
         esp_netif_set_ip_info, ESP_IPADDR_TYPE_V4,
     };
 
+    // `esp_ip4_addr_t::addr` holds the octets in network order.
+    let ip4 = |addr: Ipv4Addr| esp_ip4_addr_t {
+        addr: u32::from_le_bytes(addr.octets()),
+    };
+
     let handle = wifi.ap_netif().handle();
-    let ap_addr = u32::from_le_bytes([192, 168, 4, 1]);
+    let ap_addr = ip4(subnet.ip).addr;
     let info = esp_netif_ip_info_t {
-        ip: esp_ip4_addr_t { addr: ap_addr },
-        netmask: esp_ip4_addr_t {
-            addr: u32::from_le_bytes([255, 255, 255, 0]),
-        },
-        gw: esp_ip4_addr_t { addr: ap_addr },
+        ip: ip4(subnet.ip),
+        netmask: ip4(subnet.netmask),
+        gw: ip4(subnet.ip),
+    };
+
+    let same_24 = |addr: Ipv4Addr| addr.octets()[..3] == subnet.ip.octets()[..3];
+    if !same_24(subnet.dhcp_start) || !same_24(subnet.dhcp_end) {
+        anyhow::bail!(
+            "AP DHCP range {}-{} is outside {}/24; the IDF DHCP server cannot serve it",
+            subnet.dhcp_start,
+            subnet.dhcp_end,
+            subnet.ip
+        );
+    }
+    let mut lease = esp_idf_svc::sys::dhcps_lease_t {
+        enable: true,
+        start_ip: ip4(subnet.dhcp_start),
+        end_ip: ip4(subnet.dhcp_end),
     };
 
     // Source of truth: lwIP `dhcpserver.h` defines `OFFER_DNS = 0x02`; the DHCP
@@ -738,6 +2075,17 @@ fn pin_ap_netif_ip(wifi: &EspWifi<'_>) -> anyhow::Result<()> {
 
         esp!(esp_netif_set_ip_info(handle, &info)).context("failed to set AP netif IP")?;
 
+        // SAFETY: the option pointer refers to the local `lease`, which
+        // outlives the call; the size matches the struct.
+        esp!(esp_netif_dhcps_option(
+            handle,
+            esp_netif_dhcp_option_mode_t_ESP_NETIF_OP_SET,
+            esp_netif_dhcp_option_id_t_ESP_NETIF_REQUESTED_IP_ADDRESS,
+            &mut lease as *mut _ as *mut c_void,
+            core::mem::size_of_val(&lease) as u32,
+        ))
+        .context("failed to set AP DHCP lease range")?;
+
         // Tell the AP netif's DNS slot (MAIN) to point at the AP itself so
         // that the IDF DHCP server can advertise it via Option 6.
         // SAFETY: esp_netif_set_dns_info takes a *mut but only reads the
@@ -0,0 +1,134 @@
+//! SoftAP client tracking for [`SoftApManager`](super::SoftApManager) and
+//! [`ApStaManager`](super::ApStaManager).
+//!
+//! Feeds the driver's `ApStaConnected` / `ApStaDisconnected` Wi-Fi events and
+//! the DHCP server's `ApStaIpAssigned` IP event into an [`ApStationTable`],
+//! deauthenticates the stations it rejects, and forwards every
+//! [`ApStationEvent`] to the callback registered by the application.
+
+use std::sync::{Arc, Mutex};
+
+use esp_idf_svc::eventloop::{EspSystemEventLoop, EspSystemSubscription};
+use esp_idf_svc::netif::IpEvent;
+use esp_idf_svc::wifi::WifiEvent;
+use juggler::wifi::{ApConfig, ApStation, ApStationEvent, ApStationTable};
+
+/// Callback slot shared with the event-loop subscriptions.
+type StationCallback = Arc<dyn Fn(ApStationEvent) + Send + Sync + 'static>;
+
+/// Station table plus the event subscriptions that keep it current.
+pub(super) struct ApStationTracker {
+    table: Arc<Mutex<ApStationTable>>,
+    on_event: Arc<Mutex<Option<StationCallback>>>,
+    /// Stored so the events keep firing (dropped subscriptions fire zero
+    /// times — known lore).
+    _wifi_subscription: EspSystemSubscription<'static>,
+    _ip_subscription: EspSystemSubscription<'static>,
+}
+
+impl ApStationTracker {
+    /// Subscribes to the AP's station and DHCP events, enforcing the
+    /// `max_connections` and allowlist of `config`.
+    ///
+    /// Call before the radio starts so no association is missed.
+    pub(super) fn subscribe(
+        sys_loop: &EspSystemEventLoop,
+        config: &ApConfig<'_>,
+    ) -> anyhow::Result<Self> {
+        let table = Arc::new(Mutex::new(ApStationTable::from_config(config)));
+        let on_event: Arc<Mutex<Option<StationCallback>>> = Arc::new(Mutex::new(None));
+
+        let wifi_subscription = {
+            let table = table.clone();
+            let on_event = on_event.clone();
+            sys_loop
+                .subscribe::<WifiEvent, _>(move |event: WifiEvent<'_>| {
+                    let event = match event {
+                        WifiEvent::ApStaConnected(info) => {
+                            let event = lock(&table).associate(info.mac());
+                            if let ApStationEvent::Rejected { reason, .. } = event {
+                                log::warn!(
+                                    "SoftAP: rejecting station {:02x?} ({:?})",
+                                    info.mac(),
+                                    reason
+                                );
+                                deauth_station(info.aid());
+                            }
+                            Some(event)
+                        }
+                        WifiEvent::ApStaDisconnected(info) => lock(&table).disassociate(info.mac()),
+                        WifiEvent::ApStopped => {
+                            lock(&table).clear();
+                            None
+                        }
+                        _ => None,
+                    };
+                    if let Some(event) = event {
+                        dispatch(&on_event, event);
+                    }
+                })
+                .map_err(|e| anyhow::anyhow!("AP station event subscription failed: {e:?}"))?
+        };
+
+        let ip_subscription = {
+            let table = table.clone();
+            let on_event = on_event.clone();
+            sys_loop
+                .subscribe::<IpEvent, _>(move |event: IpEvent<'_>| {
+                    if let IpEvent::ApStaIpAssigned(assignment) = event {
+                        let event = lock(&table).assign_ip(assignment.mac(), assignment.ip());
+                        if let Some(event) = event {
+                            dispatch(&on_event, event);
+                        }
+                    }
+                })
+                .map_err(|e| anyhow::anyhow!("AP DHCP event subscription failed: {e:?}"))?
+        };
+
+        Ok(Self {
+            table,
+            on_event,
+            _wifi_subscription: wifi_subscription,
+            _ip_subscription: ip_subscription,
+        })
+    }
+
+    /// Admitted stations, in association order.
+    pub(super) fn stations(&self) -> Vec<ApStation> {
+        lock(&self.table).stations().to_vec()
+    }
+
+    /// Replaces the station-event callback.
+    pub(super) fn set_on_event(&self, callback: StationCallback) {
+        *lock(&self.on_event) = Some(callback);
+    }
+}
+
+/// Locks `mutex`, recovering the data if a callback panicked while holding it.
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Runs the registered callback, if any, outside the slot's lock.
+fn dispatch(on_event: &Mutex<Option<StationCallback>>, event: ApStationEvent) {
+    let callback = lock(on_event).clone();
+    if let Some(callback) = callback {
+        (callback)(event);
+    }
+}
+
+/// Deauthenticates the station with association id `aid`.  A failure is
+/// logged at `warn`; the station then stays associated but untracked.
+fn deauth_station(aid: u8) {
+    if let Err(e) =
+        esp_idf_svc::sys::esp!(unsafe { esp_idf_svc::sys::esp_wifi_deauth_sta(u16::from(aid)) })
+    {
+        log::warn!(
+            "SoftAP: failed to deauthenticate station aid={}: {:?}",
+            aid,
+            e
+        );
+    }
+}